@@ -0,0 +1,85 @@
+//! Times `status_bitmap` over 2,000 transactions recorded by the monitor, with a warm store.
+//!
+//! Run with `cargo bench --bench status_bitmap`.
+
+use bitcoin::{absolute::LockTime, BlockHash, Transaction, Txid};
+use bitcoin_indexer::{indexer::MockIndexerApi, types::FullBlock};
+use bitvmx_transaction_monitor::{
+    config::{MonitorSettings, MonitorSettingsConfig},
+    monitor::Monitor,
+    store::{MonitorStore, MonitorStoreApi},
+    types::{TransactionBlockchainStatus, TransactionStatus},
+};
+use std::{
+    hint::black_box,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+use storage_backend::{storage::Storage, storage_config::StorageConfig};
+use uuid::Uuid;
+
+const TXS: u32 = 2_000;
+const RUNS: u32 = 100;
+
+fn main() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/bench_{}", Uuid::new_v4());
+    let config = StorageConfig::new(path.clone(), None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let mut tx_ids: Vec<Txid> = Vec::new();
+    for i in 0..TXS {
+        let tx = Transaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: LockTime::from_consensus(i),
+            input: vec![],
+            output: vec![],
+        };
+        let height = 100 + i % 200;
+        tx_ids.push(tx.compute_txid());
+        store.save_tx_snapshot(TransactionStatus {
+            tx_id: tx.compute_txid(),
+            tx,
+            block_info: Some(FullBlock {
+                height,
+                hash: BlockHash::from_str(&format!("{:064x}", height))?,
+                prev_hash: BlockHash::from_str(&format!("{:064x}", height - 1))?,
+                txs: vec![],
+                orphan: false,
+                estimated_fee_rate: 0,
+            }),
+            confirmations: 1,
+            status: TransactionBlockchainStatus::Confirmed,
+        })?;
+    }
+    store.update_monitor_height(300)?;
+
+    let monitor = Monitor::new(
+        MockIndexerApi::new(),
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+
+    // Warm up
+    monitor.status_bitmap(&tx_ids)?;
+
+    let mut total = Duration::ZERO;
+    for _ in 0..RUNS {
+        let started = Instant::now();
+        black_box(monitor.status_bitmap(black_box(&tx_ids))?);
+        total += started.elapsed();
+    }
+
+    println!(
+        "status_bitmap: {} txids in {:?} per call ({} runs, {} fallbacks)",
+        TXS,
+        total / RUNS,
+        RUNS,
+        monitor.counters.status_fallbacks()
+    );
+
+    let _ = std::fs::remove_dir_all(path);
+
+    Ok(())
+}
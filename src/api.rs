@@ -0,0 +1,7 @@
+/// Payloads of the JSON API, frozen as version 1.
+///
+/// They are plain structs, apart from the internal types, and the conversions of the module are
+/// the only place both meet: changing an internal type doesn't change what clients get. The JSON
+/// of every payload is checked against the golden files in `tests/fixtures/api_v1`, a breaking
+/// change goes to a new `api::v2` module instead.
+pub mod v1;
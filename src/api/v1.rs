@@ -0,0 +1,1131 @@
+use crate::errors::MonitorError;
+use crate::health::HealthScore;
+use crate::store::TypesToMonitorStore;
+use crate::types::{
+    AckMonitorNews, DetectionDescriptor, HealthState, MonitorInfo, MonitorNews, NewsCursor,
+    NewsEnvelope, NewsPage, OutputFilter, RegistrationOptions, ReorgSummary, Resolution,
+    RskPeginData, SnoozeTarget, SpendDeadline, SpendValueGate, TransactionBlockchainStatus,
+    TransactionStatus, TypesToMonitor,
+};
+use bitcoin::consensus::encode::serialize_hex;
+use bitcoin::{Amount, BlockHash, ScriptBuf, Txid, Work};
+use bitvmx_bitcoin_rpc::types::BlockHeight;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Version string sent in every response of this module.
+pub const VERSION: &str = "v1";
+
+fn version() -> String {
+    VERSION.to_string()
+}
+
+/// Response of the pending news, `next` is the cursor of the following page when paged.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct NewsResponse {
+    pub version: String,
+    pub news: Vec<NewsItem>,
+    pub next: Option<Cursor>,
+}
+
+impl From<&[NewsEnvelope]> for NewsResponse {
+    fn from(envelopes: &[NewsEnvelope]) -> Self {
+        Self {
+            version: version(),
+            news: envelopes.iter().map(NewsItem::from).collect(),
+            next: None,
+        }
+    }
+}
+
+impl From<&NewsPage> for NewsResponse {
+    fn from(page: &NewsPage) -> Self {
+        Self {
+            next: page.next.as_ref().map(Cursor::from),
+            ..Self::from(page.news.as_slice())
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Cursor {
+    pub sequence: u64,
+    pub id: String,
+}
+
+impl From<&NewsCursor> for Cursor {
+    fn from(cursor: &NewsCursor) -> Self {
+        Self {
+            sequence: cursor.sequence,
+            id: cursor.id.clone(),
+        }
+    }
+}
+
+impl From<Cursor> for NewsCursor {
+    fn from(cursor: Cursor) -> Self {
+        Self {
+            sequence: cursor.sequence,
+            id: cursor.id,
+        }
+    }
+}
+
+/// A pending news with its position in the news sequence, see [`NewsEnvelope`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct NewsItem {
+    pub epoch: u64,
+    pub sequence: u64,
+    pub resolution: NewsResolution,
+    pub metadata: BTreeMap<String, String>,
+    /// Only sent when set, see [`NewsEnvelope::orphan_disputed`]
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub orphan_disputed: bool,
+    pub news: News,
+}
+
+impl From<&NewsEnvelope> for NewsItem {
+    fn from(envelope: &NewsEnvelope) -> Self {
+        Self {
+            epoch: envelope.epoch,
+            sequence: envelope.sequence,
+            resolution: envelope.resolution.into(),
+            metadata: envelope.metadata.clone(),
+            orphan_disputed: envelope.orphan_disputed,
+            news: News::from(&envelope.news),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NewsResolution {
+    Fresh,
+    Snapshot,
+    SnapshotWithHeightAdjustment,
+}
+
+impl From<Resolution> for NewsResolution {
+    fn from(resolution: Resolution) -> Self {
+        match resolution {
+            Resolution::Fresh => Self::Fresh,
+            Resolution::Snapshot => Self::Snapshot,
+            Resolution::SnapshotWithHeightAdjustment => Self::SnapshotWithHeightAdjustment,
+        }
+    }
+}
+
+/// A news, told apart by its `kind`. See [`MonitorNews`] for what each one means.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum News {
+    Transaction {
+        txid: Txid,
+        context: String,
+        status: TxStatus,
+    },
+    SpendingUtxo {
+        txid: Txid,
+        vout: u32,
+        context: String,
+        spender: TxStatus,
+    },
+    SpendingUtxoUnconfirmed {
+        txid: Txid,
+        vout: u32,
+        context: String,
+        spender_txid: Txid,
+    },
+    MinorSpend {
+        txid: Txid,
+        vout: u32,
+        context: String,
+        spender: TxStatus,
+    },
+    RskPegin {
+        txid: Txid,
+        status: TxStatus,
+        pegin: Option<Pegin>,
+    },
+    #[serde(rename = "script_pubkey")]
+    ScriptPubKey {
+        txid: Txid,
+        vout: u32,
+        context: String,
+        status: TxStatus,
+    },
+    OpReturn {
+        txid: Txid,
+        /// Hex of the data pushed in the OP_RETURN output
+        data: String,
+        context: String,
+    },
+    NewBlock {
+        height: BlockHeight,
+        hash: BlockHash,
+    },
+    OrphanedTransaction {
+        txid: Txid,
+        context: String,
+        status: TxStatus,
+    },
+    MonitorReplaced {
+        old_txid: Txid,
+        new_txid: Txid,
+        context: String,
+    },
+    ReplacedTransactionConfirmed {
+        old_txid: Txid,
+        new_txid: Txid,
+        context: String,
+    },
+    SequenceEpochChanged {
+        old_epoch: u64,
+        new_epoch: u64,
+    },
+    Reorg {
+        old_tip: BlockHash,
+        new_tip: BlockHash,
+        summary: Reorg,
+    },
+    LowWorkReorg {
+        height: BlockHeight,
+        /// Big endian hex of the work of the replaced blocks
+        old_work: String,
+        /// Big endian hex of the work of the blocks of the new branch
+        new_work: String,
+    },
+    AlternativeResolved {
+        winner: Txid,
+        losers: Vec<Txid>,
+        context: String,
+    },
+    MonitorExpired {
+        txid: Txid,
+        /// None for a transaction monitor
+        vout: Option<u32>,
+        context: String,
+        height: BlockHeight,
+    },
+    HealthStateChanged {
+        state: Health,
+        score: u32,
+    },
+    ManuallyResolved {
+        monitor: Target,
+        note: String,
+    },
+    Composite {
+        id: String,
+    },
+}
+
+fn work_hex(work: &Work) -> String {
+    hex::encode(work.to_be_bytes())
+}
+
+impl From<&MonitorNews> for News {
+    fn from(news: &MonitorNews) -> Self {
+        match news {
+            MonitorNews::Transaction(txid, status, context) => Self::Transaction {
+                txid: *txid,
+                context: context.to_string(),
+                status: status.into(),
+            },
+            MonitorNews::SpendingUTXOTransaction(txid, vout, status, context) => {
+                Self::SpendingUtxo {
+                    txid: *txid,
+                    vout: *vout,
+                    context: context.to_string(),
+                    spender: status.into(),
+                }
+            }
+            MonitorNews::SpendingUTXOUnconfirmed(txid, vout, spender_txid, context) => {
+                Self::SpendingUtxoUnconfirmed {
+                    txid: *txid,
+                    vout: *vout,
+                    context: context.to_string(),
+                    spender_txid: *spender_txid,
+                }
+            }
+            MonitorNews::MinorSpend(txid, vout, status, context) => Self::MinorSpend {
+                txid: *txid,
+                vout: *vout,
+                context: context.to_string(),
+                spender: status.into(),
+            },
+            MonitorNews::RskPeginTransaction(txid, status, pegin) => Self::RskPegin {
+                txid: *txid,
+                status: status.into(),
+                pegin: pegin.as_ref().map(Pegin::from),
+            },
+            MonitorNews::ScriptPubKeyTransaction(txid, vout, status, context) => {
+                Self::ScriptPubKey {
+                    txid: *txid,
+                    vout: *vout,
+                    context: context.to_string(),
+                    status: status.into(),
+                }
+            }
+            MonitorNews::OpReturnTransaction(txid, data, context) => Self::OpReturn {
+                txid: *txid,
+                data: hex::encode(data),
+                context: context.to_string(),
+            },
+            MonitorNews::NewBlock(height, hash) => Self::NewBlock {
+                height: *height,
+                hash: *hash,
+            },
+            MonitorNews::OrphanedTransaction(txid, status, context) => Self::OrphanedTransaction {
+                txid: *txid,
+                context: context.to_string(),
+                status: status.into(),
+            },
+            MonitorNews::MonitorReplaced { old, new, context } => Self::MonitorReplaced {
+                old_txid: *old,
+                new_txid: *new,
+                context: context.to_string(),
+            },
+            MonitorNews::ReplacedTransactionConfirmed { old, new, context } => {
+                Self::ReplacedTransactionConfirmed {
+                    old_txid: *old,
+                    new_txid: *new,
+                    context: context.to_string(),
+                }
+            }
+            MonitorNews::SequenceEpochChanged {
+                old_epoch,
+                new_epoch,
+            } => Self::SequenceEpochChanged {
+                old_epoch: *old_epoch,
+                new_epoch: *new_epoch,
+            },
+            MonitorNews::Reorg {
+                old_tip,
+                new_tip,
+                summary,
+            } => Self::Reorg {
+                old_tip: *old_tip,
+                new_tip: *new_tip,
+                summary: summary.into(),
+            },
+            MonitorNews::LowWorkReorg {
+                height,
+                old_work,
+                new_work,
+            } => Self::LowWorkReorg {
+                height: *height,
+                old_work: work_hex(old_work),
+                new_work: work_hex(new_work),
+            },
+            MonitorNews::AlternativeResolved {
+                winner,
+                losers,
+                context,
+            } => Self::AlternativeResolved {
+                winner: *winner,
+                losers: losers.clone(),
+                context: context.to_string(),
+            },
+            MonitorNews::MonitorExpired {
+                tx_id,
+                vout,
+                context,
+                height,
+            } => Self::MonitorExpired {
+                txid: *tx_id,
+                vout: *vout,
+                context: context.to_string(),
+                height: *height,
+            },
+            MonitorNews::HealthStateChanged { state, score } => Self::HealthStateChanged {
+                state: (*state).into(),
+                score: *score,
+            },
+            MonitorNews::ManuallyResolved(descriptor, note) => Self::ManuallyResolved {
+                monitor: (&descriptor.monitor).into(),
+                note: note.clone(),
+            },
+            MonitorNews::Composite(id) => Self::Composite { id: id.clone() },
+        }
+    }
+}
+
+/// Status of a transaction, `tx` is its consensus encoding in hex.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TxStatus {
+    pub txid: Txid,
+    pub status: TxState,
+    pub confirmations: u32,
+    pub block: Option<Block>,
+    pub tx: String,
+}
+
+impl From<&TransactionStatus> for TxStatus {
+    fn from(status: &TransactionStatus) -> Self {
+        Self {
+            txid: status.tx_id,
+            status: (&status.status).into(),
+            confirmations: status.confirmations,
+            block: status.block_info.as_ref().map(|block| Block {
+                height: block.height,
+                hash: block.hash,
+                orphan: block.orphan,
+            }),
+            tx: serialize_hex(&status.tx),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TxState {
+    Mempool,
+    Confirmed,
+    Finalized,
+    Orphan,
+}
+
+impl From<&TransactionBlockchainStatus> for TxState {
+    fn from(status: &TransactionBlockchainStatus) -> Self {
+        match status {
+            TransactionBlockchainStatus::Mempool => Self::Mempool,
+            TransactionBlockchainStatus::Confirmed => Self::Confirmed,
+            TransactionBlockchainStatus::Finalized => Self::Finalized,
+            TransactionBlockchainStatus::Orphan => Self::Orphan,
+        }
+    }
+}
+
+/// Block a transaction was found in.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Block {
+    pub height: BlockHeight,
+    pub hash: BlockHash,
+    pub orphan: bool,
+}
+
+/// Payload of a pegin, see [`RskPeginData`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Pegin {
+    pub packet_number: u64,
+    /// Hex of the 20 bytes of the RSK address
+    pub rsk_address: String,
+    /// Hex of the x-only public key
+    pub reimbursement_key: String,
+    pub amount_sat: u64,
+}
+
+impl From<&RskPeginData> for Pegin {
+    fn from(data: &RskPeginData) -> Self {
+        Self {
+            packet_number: data.packet_number,
+            rsk_address: hex::encode(data.rsk_address),
+            reimbursement_key: data.reimbursement_key.to_string(),
+            amount_sat: data.amount.to_sat(),
+        }
+    }
+}
+
+/// Number of items of each kind changed by a reorg, see [`ReorgSummary`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Reorg {
+    pub moved_transactions: u64,
+    pub disappeared_spends: u64,
+    pub changed_spends: u64,
+    pub invalidated_pegins: u64,
+    pub reopened_news: u64,
+}
+
+impl From<&ReorgSummary> for Reorg {
+    fn from(summary: &ReorgSummary) -> Self {
+        Self {
+            moved_transactions: summary.moved_transactions as u64,
+            disappeared_spends: summary.disappeared_spends as u64,
+            changed_spends: summary.changed_spends as u64,
+            invalidated_pegins: summary.invalidated_pegins as u64,
+            reopened_news: summary.reopened_news as u64,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Health {
+    Healthy,
+    Degraded,
+}
+
+impl From<HealthState> for Health {
+    fn from(state: HealthState) -> Self {
+        match state {
+            HealthState::Healthy => Self::Healthy,
+            HealthState::Degraded => Self::Degraded,
+        }
+    }
+}
+
+impl From<Health> for HealthState {
+    fn from(state: Health) -> Self {
+        match state {
+            Health::Healthy => Self::Healthy,
+            Health::Degraded => Self::Degraded,
+        }
+    }
+}
+
+/// Request acknowledging a news.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct AckRequest {
+    pub news: Ack,
+}
+
+/// The news acknowledged, told apart by the `kind` of the news. See [`AckMonitorNews`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Ack {
+    Transaction {
+        txid: Txid,
+        context: String,
+    },
+    SpendingUtxo {
+        txid: Txid,
+        vout: u32,
+        context: String,
+    },
+    SpendingUtxoUnconfirmed {
+        txid: Txid,
+        vout: u32,
+        context: String,
+    },
+    MinorSpend {
+        txid: Txid,
+        vout: u32,
+        context: String,
+    },
+    RskPegin {
+        txid: Txid,
+    },
+    #[serde(rename = "script_pubkey")]
+    ScriptPubKey {
+        txid: Txid,
+        vout: u32,
+        context: String,
+    },
+    OpReturn {
+        txid: Txid,
+        context: String,
+    },
+    NewBlock {
+        /// The oldest pending block when None
+        hash: Option<BlockHash>,
+    },
+    OrphanedTransaction {
+        txid: Txid,
+        context: String,
+    },
+    MonitorReplaced {
+        old_txid: Txid,
+        context: String,
+    },
+    ReplacedTransactionConfirmed {
+        old_txid: Txid,
+        context: String,
+    },
+    SequenceEpochChanged {
+        new_epoch: u64,
+    },
+    Reorg {
+        new_tip: BlockHash,
+    },
+    LowWorkReorg {
+        height: BlockHeight,
+    },
+    AlternativeResolved {
+        winner: Txid,
+        context: String,
+    },
+    MonitorExpired {
+        txid: Txid,
+        /// None for a transaction monitor
+        #[serde(default)]
+        vout: Option<u32>,
+        context: String,
+    },
+    HealthStateChanged {
+        state: Health,
+    },
+    ManuallyResolved {
+        monitor: Target,
+    },
+    Composite {
+        id: String,
+    },
+}
+
+impl From<Ack> for AckMonitorNews {
+    fn from(ack: Ack) -> Self {
+        match ack {
+            Ack::Transaction { txid, context } => Self::Transaction(txid, context.into()),
+            Ack::SpendingUtxo {
+                txid,
+                vout,
+                context,
+            } => Self::SpendingUTXOTransaction(txid, vout, context.into()),
+            Ack::SpendingUtxoUnconfirmed {
+                txid,
+                vout,
+                context,
+            } => Self::SpendingUTXOUnconfirmed(txid, vout, context.into()),
+            Ack::MinorSpend {
+                txid,
+                vout,
+                context,
+            } => Self::MinorSpend(txid, vout, context.into()),
+            Ack::RskPegin { txid } => Self::RskPeginTransaction(txid),
+            Ack::ScriptPubKey {
+                txid,
+                vout,
+                context,
+            } => Self::ScriptPubKeyTransaction(txid, vout, context.into()),
+            Ack::OpReturn { txid, context } => Self::OpReturnTransaction(txid, context.into()),
+            Ack::NewBlock { hash } => Self::NewBlock(hash),
+            Ack::OrphanedTransaction { txid, context } => {
+                Self::OrphanedTransaction(txid, context.into())
+            }
+            Ack::MonitorReplaced { old_txid, context } => Self::MonitorReplaced {
+                old: old_txid,
+                context: context.into(),
+            },
+            Ack::ReplacedTransactionConfirmed { old_txid, context } => {
+                Self::ReplacedTransactionConfirmed {
+                    old: old_txid,
+                    context: context.into(),
+                }
+            }
+            Ack::SequenceEpochChanged { new_epoch } => Self::SequenceEpochChanged { new_epoch },
+            Ack::Reorg { new_tip } => Self::Reorg { new_tip },
+            Ack::LowWorkReorg { height } => Self::LowWorkReorg { height },
+            Ack::AlternativeResolved { winner, context } => Self::AlternativeResolved {
+                winner,
+                context: context.into(),
+            },
+            Ack::MonitorExpired {
+                txid,
+                vout,
+                context,
+            } => Self::MonitorExpired {
+                tx_id: txid,
+                vout,
+                context: context.into(),
+            },
+            Ack::HealthStateChanged { state } => Self::HealthStateChanged {
+                state: state.into(),
+            },
+            Ack::ManuallyResolved { monitor } => Self::ManuallyResolved(DetectionDescriptor {
+                monitor: monitor.into(),
+                tx_id: None,
+            }),
+            Ack::Composite { id } => Self::Composite(id),
+        }
+    }
+}
+
+/// A single monitored item, told apart by its `kind`. See [`SnoozeTarget`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Target {
+    Transaction {
+        txid: Txid,
+        context: String,
+    },
+    SpendingUtxo {
+        txid: Txid,
+        vout: u32,
+        context: String,
+    },
+    RskPegin {},
+    NewBlock {},
+    #[serde(rename = "script_pubkey")]
+    ScriptPubKey {
+        /// Hex of the script_pubkey
+        script_pubkey: ScriptBuf,
+        context: String,
+    },
+    OpReturnPrefix {
+        /// Hex of the prefix
+        #[serde(with = "hex::serde")]
+        prefix: Vec<u8>,
+        context: String,
+    },
+}
+
+impl From<&SnoozeTarget> for Target {
+    fn from(target: &SnoozeTarget) -> Self {
+        match target {
+            SnoozeTarget::Transaction(txid, context) => Self::Transaction {
+                txid: *txid,
+                context: context.to_string(),
+            },
+            SnoozeTarget::SpendingUTXOTransaction(txid, vout, context) => Self::SpendingUtxo {
+                txid: *txid,
+                vout: *vout,
+                context: context.to_string(),
+            },
+            SnoozeTarget::RskPegin => Self::RskPegin {},
+            SnoozeTarget::NewBlock => Self::NewBlock {},
+            SnoozeTarget::ScriptPubKey(script_pubkey, context) => Self::ScriptPubKey {
+                script_pubkey: script_pubkey.clone(),
+                context: context.to_string(),
+            },
+            SnoozeTarget::OpReturnPrefix(prefix, context) => Self::OpReturnPrefix {
+                prefix: prefix.clone(),
+                context: context.to_string(),
+            },
+        }
+    }
+}
+
+impl From<Target> for SnoozeTarget {
+    fn from(target: Target) -> Self {
+        match target {
+            Target::Transaction { txid, context } => Self::Transaction(txid, context.into()),
+            Target::SpendingUtxo {
+                txid,
+                vout,
+                context,
+            } => Self::SpendingUTXOTransaction(txid, vout, context.into()),
+            Target::RskPegin {} => Self::RskPegin,
+            Target::NewBlock {} => Self::NewBlock,
+            Target::ScriptPubKey {
+                script_pubkey,
+                context,
+            } => Self::ScriptPubKey(script_pubkey, context.into()),
+            Target::OpReturnPrefix { prefix, context } => {
+                Self::OpReturnPrefix(prefix, context.into())
+            }
+        }
+    }
+}
+
+/// Response of a request that returns nothing but its version, e.g. an ack or a registration.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct OkResponse {
+    pub version: String,
+}
+
+impl Default for OkResponse {
+    fn default() -> Self {
+        Self { version: version() }
+    }
+}
+
+/// Request registering a monitor, see [`crate::monitor::Monitor::save_monitor_with`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RegisterRequest {
+    pub monitor: MonitorSpec,
+    /// Replaces the metadata of an earlier registration, None keeps it
+    #[serde(default)]
+    pub metadata: Option<BTreeMap<String, String>>,
+}
+
+impl RegisterRequest {
+    /// The monitor and the options to register it with. Fails with
+    /// `MonitorError::InvalidMonitor` when a script, a prefix or a committee is not valid hex.
+    pub fn into_registration(self) -> Result<(TypesToMonitor, RegistrationOptions), MonitorError> {
+        let options = RegistrationOptions {
+            metadata: self.metadata,
+            ..Default::default()
+        };
+        Ok((self.monitor.try_into()?, options))
+    }
+}
+
+/// A monitor, told apart by its `kind`. See [`TypesToMonitor`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "kind", rename_all = "snake_case", deny_unknown_fields)]
+pub enum MonitorSpec {
+    Transactions {
+        txids: Vec<Txid>,
+        context: String,
+        #[serde(default)]
+        trigger: Option<u32>,
+        /// Left out when None, the monitors without expiry are sent as before it existed
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expires_at_height: Option<BlockHeight>,
+    },
+    SpendingUtxo {
+        txid: Txid,
+        vout: u32,
+        context: String,
+        #[serde(default)]
+        trigger: Option<u32>,
+        #[serde(default)]
+        deadline: Option<Deadline>,
+        #[serde(default)]
+        value_gate: Option<ValueGate>,
+        /// Left out when None, the monitors without expiry are sent as before it existed
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        expires_at_height: Option<BlockHeight>,
+    },
+    RskPegin {
+        #[serde(default)]
+        trigger: Option<u32>,
+        /// Hex of the script_pubkeys of the committee addresses, any address when empty
+        #[serde(default)]
+        committee: Vec<String>,
+        #[serde(default)]
+        packet_range: Option<PacketRange>,
+        #[serde(default)]
+        min_amount_sat: Option<u64>,
+    },
+    NewBlock {},
+    #[serde(rename = "script_pubkey")]
+    ScriptPubKey {
+        /// Hex of the script_pubkey
+        script_pubkey: String,
+        context: String,
+        #[serde(default)]
+        trigger: Option<u32>,
+        #[serde(default)]
+        filter: Option<Filter>,
+    },
+    OpReturnPrefix {
+        /// Hex of the prefix
+        prefix: String,
+        context: String,
+    },
+    Alternatives {
+        txids: Vec<Txid>,
+        context: String,
+    },
+}
+
+fn script_from_hex(field: &str, script: &str) -> Result<ScriptBuf, MonitorError> {
+    ScriptBuf::from_hex(script)
+        .map_err(|e| MonitorError::InvalidMonitor(format!("{field} is not valid hex: {e}")))
+}
+
+impl TryFrom<MonitorSpec> for TypesToMonitor {
+    type Error = MonitorError;
+
+    fn try_from(spec: MonitorSpec) -> Result<Self, Self::Error> {
+        Ok(match spec {
+            MonitorSpec::Transactions {
+                txids,
+                context,
+                trigger,
+                expires_at_height,
+            } => Self::Transactions(txids, context.into(), trigger, expires_at_height),
+            MonitorSpec::SpendingUtxo {
+                txid,
+                vout,
+                context,
+                trigger,
+                deadline,
+                value_gate,
+                expires_at_height,
+            } => Self::SpendingUTXOTransaction(
+                txid,
+                vout,
+                context.into(),
+                trigger,
+                deadline.map(|d| SpendDeadline::new(d.height, d.mempool_watch_window)),
+                value_gate.map(|gate| SpendValueGate {
+                    min_spend_value: Amount::from_sat(gate.min_spend_value_sat),
+                    measure_monitored_output: gate.measure_monitored_output,
+                    report_minor: gate.report_minor,
+                }),
+                expires_at_height,
+            ),
+            MonitorSpec::RskPegin {
+                trigger,
+                committee,
+                packet_range,
+                min_amount_sat,
+            } => Self::RskPegin(
+                trigger,
+                committee
+                    .iter()
+                    .map(|script| script_from_hex("committee", script))
+                    .collect::<Result<_, _>>()?,
+                packet_range.map(|range| (range.first, range.last)),
+                min_amount_sat.map(Amount::from_sat),
+            ),
+            MonitorSpec::NewBlock {} => Self::NewBlock,
+            MonitorSpec::ScriptPubKey {
+                script_pubkey,
+                context,
+                trigger,
+                filter,
+            } => Self::ScriptPubKey(
+                script_from_hex("script_pubkey", &script_pubkey)?,
+                context.into(),
+                trigger,
+                filter.map(|filter| OutputFilter {
+                    min_value: filter.min_value_sat.map(Amount::from_sat),
+                    ignore_dust_below_relay: filter.ignore_dust_below_relay,
+                }),
+            ),
+            MonitorSpec::OpReturnPrefix { prefix, context } => Self::OpReturnPrefix(
+                hex::decode(&prefix).map_err(|e| {
+                    MonitorError::InvalidMonitor(format!("prefix is not valid hex: {e}"))
+                })?,
+                context.into(),
+            ),
+            MonitorSpec::Alternatives { txids, context } => {
+                Self::Alternatives(txids, context.into())
+            }
+        })
+    }
+}
+
+impl From<&TypesToMonitorStore> for MonitorSpec {
+    fn from(monitor: &TypesToMonitorStore) -> Self {
+        match monitor {
+            TypesToMonitorStore::Transaction(txid, context, trigger, expires_at_height) => {
+                Self::Transactions {
+                    txids: vec![*txid],
+                    context: context.to_string(),
+                    trigger: *trigger,
+                    expires_at_height: *expires_at_height,
+                }
+            }
+            TypesToMonitorStore::SpendingUTXOTransaction(
+                txid,
+                vout,
+                context,
+                trigger,
+                deadline,
+                value_gate,
+                expires_at_height,
+            ) => Self::SpendingUtxo {
+                txid: *txid,
+                vout: *vout,
+                context: context.to_string(),
+                trigger: *trigger,
+                deadline: deadline.map(|d| Deadline {
+                    height: d.height,
+                    mempool_watch_window: d.mempool_watch_window,
+                }),
+                value_gate: value_gate.map(|gate| ValueGate {
+                    min_spend_value_sat: gate.min_spend_value.to_sat(),
+                    measure_monitored_output: gate.measure_monitored_output,
+                    report_minor: gate.report_minor,
+                }),
+                expires_at_height: *expires_at_height,
+            },
+            TypesToMonitorStore::NewBlock => Self::NewBlock {},
+            TypesToMonitorStore::RskPegin(trigger, committee, packet_range, min_amount) => {
+                Self::RskPegin {
+                    trigger: *trigger,
+                    committee: committee.iter().map(|s| s.to_hex_string()).collect(),
+                    packet_range: packet_range.map(|(first, last)| PacketRange { first, last }),
+                    min_amount_sat: min_amount.map(|amount| amount.to_sat()),
+                }
+            }
+            TypesToMonitorStore::ScriptPubKey(script_pubkey, context, trigger, filter) => {
+                Self::ScriptPubKey {
+                    script_pubkey: script_pubkey.to_hex_string(),
+                    context: context.to_string(),
+                    trigger: *trigger,
+                    filter: filter.as_ref().map(|filter| Filter {
+                        min_value_sat: filter.min_value.map(|value| value.to_sat()),
+                        ignore_dust_below_relay: filter.ignore_dust_below_relay,
+                    }),
+                }
+            }
+            TypesToMonitorStore::OpReturnPrefix(prefix, context) => Self::OpReturnPrefix {
+                prefix: hex::encode(prefix),
+                context: context.to_string(),
+            },
+        }
+    }
+}
+
+/// See [`SpendDeadline`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Deadline {
+    pub height: BlockHeight,
+    pub mempool_watch_window: u32,
+}
+
+/// See [`SpendValueGate`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ValueGate {
+    pub min_spend_value_sat: u64,
+    pub measure_monitored_output: bool,
+    pub report_minor: bool,
+}
+
+/// First and last packet numbers of the pegins reported, both included.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct PacketRange {
+    pub first: u64,
+    pub last: u64,
+}
+
+/// See [`OutputFilter`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct Filter {
+    pub min_value_sat: Option<u64>,
+    pub ignore_dust_below_relay: bool,
+}
+
+/// Response listing the monitors, see [`crate::monitor::Monitor::get_monitors`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct MonitorsResponse {
+    pub version: String,
+    pub monitors: Vec<MonitorItem>,
+}
+
+impl From<&[MonitorInfo]> for MonitorsResponse {
+    fn from(monitors: &[MonitorInfo]) -> Self {
+        Self {
+            version: version(),
+            monitors: monitors.iter().map(MonitorItem::from).collect(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct MonitorItem {
+    pub monitor: MonitorSpec,
+    pub active: bool,
+    pub spender_txid: Option<Txid>,
+    pub metadata: BTreeMap<String, String>,
+}
+
+impl From<&MonitorInfo> for MonitorItem {
+    fn from(info: &MonitorInfo) -> Self {
+        Self {
+            monitor: MonitorSpec::from(&info.monitor),
+            active: info.active,
+            spender_txid: info.spender_tx_id,
+            metadata: info.metadata.clone(),
+        }
+    }
+}
+
+/// Response of the status of a transaction.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct TxStatusResponse {
+    pub version: String,
+    pub status: TxStatus,
+}
+
+impl From<&TransactionStatus> for TxStatusResponse {
+    fn from(status: &TransactionStatus) -> Self {
+        Self {
+            version: version(),
+            status: status.into(),
+        }
+    }
+}
+
+/// Response of the health of the monitor, see [`HealthScore`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct HealthResponse {
+    pub version: String,
+    pub score: u32,
+    pub state: Health,
+    pub indexer_failures: u32,
+    pub store_corruptions: u32,
+    pub news_resolution_failures: u32,
+    pub tick_duration_p95_ms: u64,
+}
+
+impl From<&HealthScore> for HealthResponse {
+    fn from(health: &HealthScore) -> Self {
+        Self {
+            version: version(),
+            score: health.score,
+            state: health.state.into(),
+            indexer_failures: health.indexer_failures,
+            store_corruptions: health.store_corruptions,
+            news_resolution_failures: health.news_resolution_failures,
+            tick_duration_p95_ms: health.tick_duration_p95.as_millis() as u64,
+        }
+    }
+}
+
+/// Response of the status of the monitor, see [`crate::monitor::Monitor::is_ready`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct StatusResponse {
+    pub version: String,
+    pub monitor_height: BlockHeight,
+    pub is_ready: bool,
+}
+
+impl StatusResponse {
+    pub fn new(monitor_height: BlockHeight, is_ready: bool) -> Self {
+        Self {
+            version: version(),
+            monitor_height,
+            is_ready,
+        }
+    }
+}
+
+/// Response of a failed request. `code` names the error and `transient` tells whether the same
+/// request may succeed later, see [`MonitorError::is_transient`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct ErrorResponse {
+    pub version: String,
+    pub code: String,
+    pub message: String,
+    pub transient: bool,
+}
+
+impl From<&MonitorError> for ErrorResponse {
+    fn from(error: &MonitorError) -> Self {
+        let code = match error {
+            MonitorError::IndexerError(_) => "indexer_error",
+            MonitorError::MonitorStoreError(_) => "store_error",
+            MonitorError::BitcoinClientError(_) => "bitcoin_client_error",
+            MonitorError::WalletRpcError(_) => "wallet_rpc_error",
+            MonitorError::NodeRpcError(_) => "node_rpc_error",
+            MonitorError::UnexpectedError(_) => "unexpected_error",
+            MonitorError::TransactionNotFound(_) => "transaction_not_found",
+            MonitorError::InvalidConfirmationTrigger(..) => "invalid_confirmation_trigger",
+            MonitorError::InvalidMonitor(_) => "invalid_monitor",
+            MonitorError::InvalidSettings(_) => "invalid_settings",
+            MonitorError::HeldByLowWorkReorg(_) => "held_by_low_work_reorg",
+            MonitorError::IndexerInconsistency { .. } => "indexer_inconsistency",
+            MonitorError::CommandTimeout(_) => "command_timeout",
+            MonitorError::MonitorStopped => "monitor_stopped",
+            MonitorError::ExportFailed(_) => "export_failed",
+            MonitorError::BlockSourceMismatch(_) => "block_source_mismatch",
+            MonitorError::ExternalChainMismatch(_) => "external_chain_mismatch",
+            MonitorError::NewsNotAcked { .. } => "news_not_acked",
+            MonitorError::UnexpectedDetection(_) => "unexpected_detection",
+        };
+
+        Self {
+            version: version(),
+            code: code.to_string(),
+            message: error.to_string(),
+            transient: error.is_transient(),
+        }
+    }
+}
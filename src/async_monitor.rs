@@ -0,0 +1,123 @@
+use crate::errors::MonitorError;
+use crate::monitor::Monitor;
+use crate::store::MonitorStoreApi;
+use crate::types::{AckMonitorNews, MonitorInfo, MonitorNews, TypesToMonitor};
+use bitcoin_indexer::indexer::IndexerApi;
+use bitvmx_bitcoin_rpc::types::BlockHeight;
+use tokio::sync::{mpsc, oneshot};
+use tokio::task;
+
+type Call<I, B> = Box<dyn FnOnce(&mut Monitor<I, B>) + Send>;
+
+/// Async front of a [`Monitor`], for services running on tokio.
+///
+/// The indexer, the RPC client and the storage are blocking, and the monitor of
+/// `Monitor::new_with_paths` can't move between threads, so the monitor is built and owned by a
+/// blocking tokio task. Every method queues a call to that task and awaits its result, so calls
+/// run one at a time in the order they were made, and never block the runtime. The task stops once the `AsyncMonitor` is dropped, which
+/// must happen before the runtime shuts down, as the runtime waits for its blocking tasks.
+pub struct AsyncMonitor<I, B>
+where
+    I: IndexerApi + 'static,
+    B: MonitorStoreApi + 'static,
+{
+    calls: mpsc::UnboundedSender<Call<I, B>>,
+}
+
+impl<I, B> AsyncMonitor<I, B>
+where
+    I: IndexerApi + 'static,
+    B: MonitorStoreApi + 'static,
+{
+    /// Builds the monitor with `build` on a blocking task, e.g. with `Monitor::new_with_paths`,
+    /// and returns once it is ready to take calls.
+    pub async fn spawn<F>(build: F) -> Result<Self, MonitorError>
+    where
+        F: FnOnce() -> Result<Monitor<I, B>, MonitorError> + Send + 'static,
+    {
+        let (calls, mut receiver) = mpsc::unbounded_channel::<Call<I, B>>();
+        let (ready, built) = oneshot::channel();
+
+        task::spawn_blocking(move || {
+            let mut monitor = match build() {
+                Ok(monitor) => monitor,
+                Err(e) => {
+                    let _ = ready.send(Err(e));
+                    return;
+                }
+            };
+            let _ = ready.send(Ok(()));
+
+            while let Some(call) = receiver.blocking_recv() {
+                call(&mut monitor);
+            }
+        });
+
+        built.await.map_err(|_| MonitorError::MonitorStopped)??;
+
+        Ok(Self { calls })
+    }
+
+    /// Runs `f` on the task owning the monitor and returns its result, for the methods of
+    /// [`Monitor`] without an async counterpart.
+    pub async fn call<T, F>(&self, f: F) -> Result<T, MonitorError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Monitor<I, B>) -> Result<T, MonitorError> + Send + 'static,
+    {
+        let (reply, result) = oneshot::channel();
+        self.calls
+            .send(Box::new(move |monitor| {
+                // The caller may have stopped waiting
+                let _ = reply.send(f(monitor));
+            }))
+            .map_err(|_| MonitorError::MonitorStopped)?;
+
+        result.await.map_err(|_| MonitorError::MonitorStopped)?
+    }
+
+    pub async fn is_ready(&self) -> Result<bool, MonitorError> {
+        self.call(|monitor| monitor.is_ready()).await
+    }
+
+    pub async fn tick(&self) -> Result<(), MonitorError> {
+        self.call(|monitor| monitor.tick()).await
+    }
+
+    pub async fn get_monitor_height(&self) -> Result<BlockHeight, MonitorError> {
+        self.call(|monitor| monitor.get_monitor_height()).await
+    }
+
+    pub async fn monitor(&self, data: TypesToMonitor) -> Result<(), MonitorError> {
+        self.call(move |monitor| monitor.save_monitor(data)).await
+    }
+
+    pub async fn cancel(&self, data: TypesToMonitor) -> Result<(), MonitorError> {
+        self.call(move |monitor| monitor.cancel(data)).await
+    }
+
+    pub async fn get_monitors(
+        &self,
+        include_inactive: bool,
+    ) -> Result<Vec<MonitorInfo>, MonitorError> {
+        self.call(move |monitor| monitor.get_monitors(include_inactive))
+            .await
+    }
+
+    pub async fn get_news(&self) -> Result<Vec<MonitorNews>, MonitorError> {
+        self.call(|monitor| monitor.get_news()).await
+    }
+
+    pub async fn has_news(&self) -> Result<bool, MonitorError> {
+        self.call(|monitor| monitor.has_news()).await
+    }
+
+    pub async fn ack_news(&self, data: AckMonitorNews) -> Result<(), MonitorError> {
+        self.call(move |monitor| monitor.ack_news(data)).await
+    }
+
+    pub async fn ack_news_batch(&self, items: Vec<AckMonitorNews>) -> Result<(), MonitorError> {
+        self.call(move |monitor| monitor.ack_news_batch(items))
+            .await
+    }
+}
@@ -0,0 +1,540 @@
+use crate::errors::MonitorError;
+use crate::monitor::{
+    INTERNAL_MINOR_SPEND, INTERNAL_RSK_PEGIN, INTERNAL_SCRIPT_PUBKEY, INTERNAL_SPENDING_UTXO,
+    INTERNAL_UNREPORTED_SPEND,
+};
+use crate::settings::{MAX_METADATA_ENTRIES, MAX_METADATA_LEN};
+use crate::types::{MonitorContext, OutputFilter, SpendDeadline, SpendValueGate, TypesToMonitor};
+use bitcoin::{Amount, ScriptBuf, Txid};
+use bitvmx_bitcoin_rpc::types::BlockHeight;
+use std::collections::BTreeMap;
+
+/// Rejects contexts that collide with the ones the monitor creates for its internal monitors.
+pub(crate) fn validate_context(context: &str) -> Result<(), MonitorError> {
+    if context.starts_with(INTERNAL_RSK_PEGIN)
+        || context.starts_with(INTERNAL_SPENDING_UTXO)
+        || context.starts_with(INTERNAL_SCRIPT_PUBKEY)
+        || context.starts_with(INTERNAL_MINOR_SPEND)
+        || context.starts_with(INTERNAL_UNREPORTED_SPEND)
+    {
+        return Err(MonitorError::InvalidMonitor(format!(
+            "context {context} uses a reserved internal prefix"
+        )));
+    }
+
+    Ok(())
+}
+
+/// Rejects metadata with too many entries or with keys or values that are too long.
+pub(crate) fn validate_metadata(metadata: &BTreeMap<String, String>) -> Result<(), MonitorError> {
+    if metadata.len() > MAX_METADATA_ENTRIES {
+        return Err(MonitorError::InvalidMonitor(format!(
+            "metadata has {} entries, the maximum is {MAX_METADATA_ENTRIES}",
+            metadata.len()
+        )));
+    }
+
+    if metadata
+        .iter()
+        .any(|(key, value)| key.len() > MAX_METADATA_LEN || value.len() > MAX_METADATA_LEN)
+    {
+        return Err(MonitorError::InvalidMonitor(format!(
+            "metadata keys and values must not be longer than {MAX_METADATA_LEN} bytes"
+        )));
+    }
+
+    Ok(())
+}
+
+/// A transaction with 0 confirmations is not in a block yet, so a trigger of 0 would never fire.
+pub(crate) fn validate_confirmation_trigger(
+    confirmation_trigger: Option<u32>,
+) -> Result<(), MonitorError> {
+    if confirmation_trigger == Some(0) {
+        return Err(MonitorError::InvalidMonitor(
+            "confirmation trigger must be at least 1".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Builds a [`TypesToMonitor::Transactions`] monitor.
+///
+/// There is no start height: the transactions are looked up in the indexer by txid whatever the
+/// block that mined them, before the registration or after it. Only the end of the watch is set,
+/// with [`WatchTx::expires_at`].
+///
+/// ```
+/// use bitcoin::Txid;
+/// use bitvmx_transaction_monitor::{types::TypesToMonitor, WatchTx};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let tx_id: Txid = "0000000000000000000000000000000000000000000000000000000000000001".parse()?;
+///
+/// let monitor = WatchTx::new(tx_id).context("my_context").trigger_at(6).build()?;
+///
+/// assert_eq!(
+///     monitor,
+///     TypesToMonitor::Transactions(vec![tx_id], "my_context".into(), Some(6), None)
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchTx {
+    tx_ids: Vec<Txid>,
+    context: MonitorContext,
+    confirmation_trigger: Option<u32>,
+    expires_at_height: Option<BlockHeight>,
+}
+
+impl WatchTx {
+    /// Starts a monitor for a single transaction.
+    pub fn new(tx_id: Txid) -> Self {
+        Self::many(vec![tx_id])
+    }
+
+    /// Starts a monitor for several transactions sharing the same context and trigger.
+    pub fn many(tx_ids: Vec<Txid>) -> Self {
+        Self {
+            tx_ids,
+            context: MonitorContext::default(),
+            confirmation_trigger: None,
+            expires_at_height: None,
+        }
+    }
+
+    /// Sets the context returned with every news of this monitor.
+    pub fn context(mut self, context: impl Into<MonitorContext>) -> Self {
+        self.context = context.into();
+        self
+    }
+
+    /// Sends a single news once the transaction reaches the given number of confirmations.
+    /// Without a trigger, news are sent on every new confirmation.
+    pub fn trigger_at(mut self, confirmations: u32) -> Self {
+        self.confirmation_trigger = Some(confirmations);
+        self
+    }
+
+    /// Deactivates the monitor and sends a `MonitorNews::MonitorExpired` news if the transaction
+    /// wasn't mined by the block at `height`.
+    pub fn expires_at(mut self, height: BlockHeight) -> Self {
+        self.expires_at_height = Some(height);
+        self
+    }
+
+    pub fn build(self) -> Result<TypesToMonitor, MonitorError> {
+        if self.tx_ids.is_empty() {
+            return Err(MonitorError::InvalidMonitor(
+                "at least one transaction id is required".to_string(),
+            ));
+        }
+
+        validate_context(&self.context)?;
+        validate_confirmation_trigger(self.confirmation_trigger)?;
+
+        Ok(TypesToMonitor::Transactions(
+            self.tx_ids,
+            self.context,
+            self.confirmation_trigger,
+            self.expires_at_height,
+        ))
+    }
+}
+
+/// Builds a [`TypesToMonitor::SpendingUTXOTransaction`] monitor.
+///
+/// ```
+/// use bitcoin::Txid;
+/// use bitvmx_transaction_monitor::{types::TypesToMonitor, WatchOutpoint};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let tx_id: Txid = "0000000000000000000000000000000000000000000000000000000000000001".parse()?;
+///
+/// let monitor = WatchOutpoint::new(tx_id, 1).context("my_context").build()?;
+///
+/// assert_eq!(
+///     monitor,
+///     TypesToMonitor::SpendingUTXOTransaction(tx_id, 1, "my_context".into(), None, None, None, None)
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchOutpoint {
+    tx_id: Txid,
+    vout: u32,
+    context: MonitorContext,
+    confirmation_trigger: Option<u32>,
+    deadline: Option<SpendDeadline>,
+    min_spend_value: Option<Amount>,
+    measure_monitored_output: bool,
+    report_minor_spends: bool,
+    expires_at_height: Option<BlockHeight>,
+}
+
+impl WatchOutpoint {
+    /// Starts a monitor for the transaction spending the output `vout` of `tx_id`.
+    pub fn new(tx_id: Txid, vout: u32) -> Self {
+        Self {
+            tx_id,
+            vout,
+            context: MonitorContext::default(),
+            confirmation_trigger: None,
+            deadline: None,
+            min_spend_value: None,
+            measure_monitored_output: false,
+            report_minor_spends: true,
+            expires_at_height: None,
+        }
+    }
+
+    /// Sets the context returned with every news of this monitor.
+    pub fn context(mut self, context: impl Into<MonitorContext>) -> Self {
+        self.context = context.into();
+        self
+    }
+
+    /// Sends a single news once the spending transaction reaches the given number of confirmations.
+    pub fn trigger_at(mut self, confirmations: u32) -> Self {
+        self.confirmation_trigger = Some(confirmations);
+        self
+    }
+
+    /// Also reports unconfirmed spends found in the mempool during the last
+    /// `mempool_watch_window` blocks before the deadline `height`.
+    pub fn deadline(mut self, height: BlockHeight, mempool_watch_window: u32) -> Self {
+        self.deadline = Some(SpendDeadline::new(height, mempool_watch_window));
+        self
+    }
+
+    /// Reports the spends moving less than `amount` as `MonitorNews::MinorSpend`. The value of
+    /// a spend is the total output value of the spending transaction, see
+    /// [`Self::measure_monitored_output`].
+    pub fn min_spend_value(mut self, amount: Amount) -> Self {
+        self.min_spend_value = Some(amount);
+        self
+    }
+
+    /// Compares the value of the monitored output, instead of the total output value of the
+    /// spending transaction, with the minimum spend value.
+    pub fn measure_monitored_output(mut self) -> Self {
+        self.measure_monitored_output = true;
+        self
+    }
+
+    /// Sends no news for the spends below the minimum spend value, they are still recorded in
+    /// the monitor.
+    pub fn ignore_minor_spends(mut self) -> Self {
+        self.report_minor_spends = false;
+        self
+    }
+
+    /// Deactivates the monitor and sends a `MonitorNews::MonitorExpired` news if the output
+    /// wasn't spent by the block at `height`.
+    pub fn expires_at(mut self, height: BlockHeight) -> Self {
+        self.expires_at_height = Some(height);
+        self
+    }
+
+    pub fn build(self) -> Result<TypesToMonitor, MonitorError> {
+        validate_context(&self.context)?;
+        validate_confirmation_trigger(self.confirmation_trigger)?;
+
+        let value_gate = match self.min_spend_value {
+            Some(min_spend_value) => Some(SpendValueGate {
+                min_spend_value,
+                measure_monitored_output: self.measure_monitored_output,
+                report_minor: self.report_minor_spends,
+            }),
+            None if self.measure_monitored_output || !self.report_minor_spends => {
+                return Err(MonitorError::InvalidMonitor(
+                    "minor spends need a minimum spend value".to_string(),
+                ))
+            }
+            None => None,
+        };
+
+        Ok(TypesToMonitor::SpendingUTXOTransaction(
+            self.tx_id,
+            self.vout,
+            self.context,
+            self.confirmation_trigger,
+            self.deadline,
+            value_gate,
+            self.expires_at_height,
+        ))
+    }
+}
+
+/// Builds a [`TypesToMonitor::RskPegin`] monitor.
+///
+/// ```
+/// use bitvmx_transaction_monitor::{types::TypesToMonitor, WatchPegin};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let monitor = WatchPegin::new().trigger_at(6).build()?;
+///
+/// assert_eq!(monitor, TypesToMonitor::RskPegin(Some(6), vec![], None, None));
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WatchPegin {
+    confirmation_trigger: Option<u32>,
+    committee: Vec<ScriptBuf>,
+    packet_range: Option<(u64, u64)>,
+    min_amount: Option<Amount>,
+}
+
+impl WatchPegin {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sends a single news once each detected pegin reaches the given number of confirmations.
+    pub fn trigger_at(mut self, confirmations: u32) -> Self {
+        self.confirmation_trigger = Some(confirmations);
+        self
+    }
+
+    /// Only reports the pegins whose first output pays to this committee, given as the
+    /// script_pubkey of its taproot address (`address.script_pubkey()`). Can be called once per
+    /// committee; without it, any pegin is reported.
+    pub fn committee(mut self, script_pubkey: ScriptBuf) -> Self {
+        if !self.committee.contains(&script_pubkey) {
+            self.committee.push(script_pubkey);
+        }
+        self
+    }
+
+    /// Only reports the pegins with a packet number from `first` to `last`, both included.
+    pub fn packet_range(mut self, first: u64, last: u64) -> Self {
+        self.packet_range = Some((first, last));
+        self
+    }
+
+    /// Only reports the pegins paying at least `amount` to the committee, an amount equal to the
+    /// minimum is reported.
+    pub fn min_amount(mut self, amount: Amount) -> Self {
+        self.min_amount = Some(amount);
+        self
+    }
+
+    pub fn build(self) -> Result<TypesToMonitor, MonitorError> {
+        validate_confirmation_trigger(self.confirmation_trigger)?;
+
+        if let Some(script_pubkey) = self.committee.iter().find(|script| !script.is_p2tr()) {
+            return Err(MonitorError::InvalidMonitor(format!(
+                "committee {} is not a taproot output",
+                script_pubkey.to_hex_string()
+            )));
+        }
+
+        if let Some((first, last)) = self.packet_range.filter(|(first, last)| first > last) {
+            return Err(MonitorError::InvalidMonitor(format!(
+                "packet range {first}..={last} is empty"
+            )));
+        }
+
+        Ok(TypesToMonitor::RskPegin(
+            self.confirmation_trigger,
+            self.committee,
+            self.packet_range,
+            self.min_amount,
+        ))
+    }
+}
+
+/// Builds a [`TypesToMonitor::ScriptPubKey`] monitor.
+///
+/// ```
+/// use bitcoin::ScriptBuf;
+/// use bitvmx_transaction_monitor::{types::TypesToMonitor, WatchScript};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let script_pubkey = ScriptBuf::from_hex("51")?;
+///
+/// let monitor = WatchScript::new(script_pubkey.clone())
+///     .context("my_context")
+///     .build()?;
+///
+/// assert_eq!(
+///     monitor,
+///     TypesToMonitor::ScriptPubKey(script_pubkey, "my_context".into(), None, None)
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchScript {
+    script_pubkey: ScriptBuf,
+    context: MonitorContext,
+    confirmation_trigger: Option<u32>,
+    filter: OutputFilter,
+}
+
+impl WatchScript {
+    /// Starts a monitor for the outputs paying to `script_pubkey`.
+    pub fn new(script_pubkey: ScriptBuf) -> Self {
+        Self {
+            script_pubkey,
+            context: MonitorContext::default(),
+            confirmation_trigger: None,
+            filter: OutputFilter::default(),
+        }
+    }
+
+    /// Sets the context returned with every news of this monitor.
+    pub fn context(mut self, context: impl Into<MonitorContext>) -> Self {
+        self.context = context.into();
+        self
+    }
+
+    /// Sends a single news once each transaction found reaches the given number of confirmations.
+    pub fn trigger_at(mut self, confirmations: u32) -> Self {
+        self.confirmation_trigger = Some(confirmations);
+        self
+    }
+
+    /// Outputs worth less than `value` are only counted, see `Monitor::address_filter_stats`.
+    pub fn min_value(mut self, value: Amount) -> Self {
+        self.filter.min_value = Some(value);
+        self
+    }
+
+    /// Outputs below the dust limit of the default relay policy are only counted, see
+    /// `Monitor::address_filter_stats`.
+    pub fn ignore_dust_below_relay(mut self) -> Self {
+        self.filter.ignore_dust_below_relay = true;
+        self
+    }
+
+    pub fn build(self) -> Result<TypesToMonitor, MonitorError> {
+        if self.script_pubkey.is_empty() {
+            return Err(MonitorError::InvalidMonitor(
+                "script_pubkey must not be empty".to_string(),
+            ));
+        }
+        validate_context(&self.context)?;
+        validate_confirmation_trigger(self.confirmation_trigger)?;
+
+        // A filter that filters nothing is not stored
+        let filter = (self.filter != OutputFilter::default()).then_some(self.filter);
+
+        Ok(TypesToMonitor::ScriptPubKey(
+            self.script_pubkey,
+            self.context,
+            self.confirmation_trigger,
+            filter,
+        ))
+    }
+}
+
+/// Builds a [`TypesToMonitor::OpReturnPrefix`] monitor.
+///
+/// ```
+/// use bitvmx_transaction_monitor::{types::TypesToMonitor, WatchOpReturn};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let monitor = WatchOpReturn::new(b"MY_PROTOCOL").context("my_context").build()?;
+///
+/// assert_eq!(
+///     monitor,
+///     TypesToMonitor::OpReturnPrefix(b"MY_PROTOCOL".to_vec(), "my_context".into())
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchOpReturn {
+    prefix: Vec<u8>,
+    context: MonitorContext,
+}
+
+impl WatchOpReturn {
+    /// Starts a monitor for the transactions with an OP_RETURN output starting with `prefix`.
+    pub fn new(prefix: impl Into<Vec<u8>>) -> Self {
+        Self {
+            prefix: prefix.into(),
+            context: MonitorContext::default(),
+        }
+    }
+
+    /// Sets the context returned with every news of this monitor.
+    pub fn context(mut self, context: impl Into<MonitorContext>) -> Self {
+        self.context = context.into();
+        self
+    }
+
+    pub fn build(self) -> Result<TypesToMonitor, MonitorError> {
+        if self.prefix.is_empty() {
+            return Err(MonitorError::InvalidMonitor(
+                "OP_RETURN prefix must not be empty".to_string(),
+            ));
+        }
+        validate_context(&self.context)?;
+
+        Ok(TypesToMonitor::OpReturnPrefix(self.prefix, self.context))
+    }
+}
+
+/// Builds a [`TypesToMonitor::Alternatives`] monitor.
+///
+/// ```
+/// use bitcoin::Txid;
+/// use bitvmx_transaction_monitor::{types::TypesToMonitor, WatchAlternatives};
+/// use std::str::FromStr;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let happy_path = Txid::from_str(&"01".repeat(32))?;
+/// let dispute = Txid::from_str(&"02".repeat(32))?;
+/// let monitor = WatchAlternatives::new([happy_path, dispute])
+///     .context("my_context")
+///     .build()?;
+///
+/// assert_eq!(
+///     monitor,
+///     TypesToMonitor::Alternatives(vec![happy_path, dispute], "my_context".into())
+/// );
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct WatchAlternatives {
+    tx_ids: Vec<Txid>,
+    context: MonitorContext,
+}
+
+impl WatchAlternatives {
+    /// Starts a monitor for mutually exclusive transactions, only one of them is expected to
+    /// confirm.
+    pub fn new(tx_ids: impl IntoIterator<Item = Txid>) -> Self {
+        Self {
+            tx_ids: tx_ids.into_iter().collect(),
+            context: MonitorContext::default(),
+        }
+    }
+
+    /// Sets the context returned with every news of this monitor.
+    pub fn context(mut self, context: impl Into<MonitorContext>) -> Self {
+        self.context = context.into();
+        self
+    }
+
+    pub fn build(self) -> Result<TypesToMonitor, MonitorError> {
+        let mut distinct = self.tx_ids.clone();
+        distinct.sort();
+        distinct.dedup();
+        if distinct.len() < 2 || distinct.len() != self.tx_ids.len() {
+            return Err(MonitorError::InvalidMonitor(
+                "at least two distinct transaction ids are required".to_string(),
+            ));
+        }
+        validate_context(&self.context)?;
+
+        Ok(TypesToMonitor::Alternatives(self.tx_ids, self.context))
+    }
+}
@@ -0,0 +1,81 @@
+use crate::errors::MonitorError;
+use crate::store::MonitorStoreApi;
+use crate::types::{ChainLogEntry, ChainLogIssue, ChainLogVerification};
+use bitcoin::BlockHash;
+use bitvmx_bitcoin_rpc::types::BlockHeight;
+use std::collections::HashMap;
+
+/// Entries of the chain log with a height from `from_height` to `to_height` (both included), in
+/// processing order. After a reorg both branches are listed, each block as it was processed.
+pub fn chain_log<S: MonitorStoreApi>(
+    store: &S,
+    from_height: BlockHeight,
+    to_height: BlockHeight,
+) -> Result<Vec<ChainLogEntry>, MonitorError> {
+    Ok(store
+        .get_chain_log()?
+        .into_iter()
+        .filter(|entry| (from_height..=to_height).contains(&entry.height))
+        .collect())
+}
+
+/// Hash of the block at `height` in the chain ending at the last of `entries`, following the hash
+/// linkage back from it and skipping the blocks of the branches it replaced. None when the linkage
+/// breaks before reaching `height`.
+pub fn canonical_hash_at(entries: &[ChainLogEntry], height: BlockHeight) -> Option<BlockHash> {
+    let mut expected = entries.last()?.hash;
+
+    for entry in entries.iter().rev() {
+        if entry.hash != expected {
+            continue;
+        }
+        if entry.height <= height {
+            return (entry.height == height).then_some(entry.hash);
+        }
+        expected = entry.prev_hash;
+    }
+
+    None
+}
+
+/// Checks the hash linkage of the chain log: each block must link to the block processed before
+/// it, or to an earlier logged block when a reorg replaced the blocks in between.
+pub fn verify_chain_log(entries: &[ChainLogEntry]) -> ChainLogVerification {
+    let mut verification = ChainLogVerification {
+        entries: entries.len(),
+        issues: vec![],
+    };
+    // Height of each logged block, the log may hold the same block twice if a reorg came back
+    let mut logged: HashMap<BlockHash, BlockHeight> = HashMap::new();
+
+    for (index, entry) in entries.iter().enumerate() {
+        if let Some(previous) = index.checked_sub(1).map(|i| &entries[i]) {
+            let issue = match logged.get(&entry.prev_hash) {
+                None => Some(ChainLogIssue::Gap {
+                    height: entry.height,
+                    hash: entry.hash,
+                    prev_hash: entry.prev_hash,
+                }),
+                Some(&parent_height) if parent_height + 1 != entry.height => {
+                    Some(ChainLogIssue::HeightMismatch {
+                        height: entry.height,
+                        hash: entry.hash,
+                        parent_height,
+                    })
+                }
+                Some(_) if entry.prev_hash == previous.hash => None,
+                Some(&parent_height) => Some(ChainLogIssue::Fork {
+                    fork_height: parent_height,
+                    fork_hash: entry.prev_hash,
+                    replaced_tip: previous.hash,
+                    hash: entry.hash,
+                }),
+            };
+            verification.issues.extend(issue);
+        }
+
+        logged.insert(entry.hash, entry.height);
+    }
+
+    verification
+}
@@ -0,0 +1,231 @@
+use crate::config::{MonitorSettings, SettingsUpdate};
+use crate::errors::MonitorError;
+use crate::types::{
+    AckMonitorNews, MonitorInfo, NewsEnvelope, RegistrationOptions, TransactionStatus,
+    TypesToMonitor,
+};
+use bitcoin::Txid;
+use bitvmx_bitcoin_rpc::types::BlockHeight;
+use std::cell::RefCell;
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, RecvTimeoutError};
+use std::time::Duration;
+
+/// Default bound of the wait of a [`MonitorHandle`] for its command to be applied.
+pub const DEFAULT_COMMAND_TIMEOUT: Duration = Duration::from_secs(10);
+
+type Reply<T> = mpsc::Sender<Result<T, MonitorError>>;
+
+/// Mutation or read queued by a [`MonitorHandle`], with the channel its result is sent back on.
+pub(crate) enum Command {
+    Monitor(TypesToMonitor, RegistrationOptions, Reply<()>),
+    Cancel(TypesToMonitor, Reply<()>),
+    AckNews(AckMonitorNews, Reply<()>),
+    UpdateSettings(MonitorSettings, Reply<SettingsUpdate>),
+    GetMonitors(bool, Reply<Vec<MonitorInfo>>),
+    GetNews(Reply<Vec<NewsEnvelope>>),
+    GetTxStatus(Txid, Reply<TransactionStatus>),
+    GetMonitorHeight(Reply<BlockHeight>),
+    IsReady(Reply<bool>),
+    /// Sent by a [`BlockTrigger`], ends the wait of the run loop so it ticks right away
+    Wake,
+}
+
+/// Result of a queued command, available once the monitor applied it.
+pub struct CommandReceipt<T> {
+    receiver: mpsc::Receiver<Result<T, MonitorError>>,
+}
+
+impl<T> CommandReceipt<T> {
+    /// Waits up to `timeout` for the command to be applied. A command that times out stays queued
+    /// and may still be applied later.
+    pub fn wait(self, timeout: Duration) -> Result<T, MonitorError> {
+        match self.receiver.recv_timeout(timeout) {
+            Ok(result) => result,
+            Err(RecvTimeoutError::Timeout) => Err(MonitorError::CommandTimeout(timeout)),
+            Err(RecvTimeoutError::Disconnected) => Err(MonitorError::MonitorStopped),
+        }
+    }
+}
+
+/// Queues mutations and reads for a monitor owned by another thread, e.g. the run loop.
+///
+/// Commands are applied by the monitor at a safe point: at the start of a tick, or between
+/// ticks with [`crate::monitor::Monitor::apply_commands`]. So they never interleave with the
+/// read-modify-write of the monitor lists done by a tick. The blocking methods wait for the
+/// result up to the timeout of the handle, and return what the same method of the monitor does.
+///
+/// The servers reach the monitor through a handle because it can't be shared between threads:
+/// no monitor is `Sync`, and the one on the node built by
+/// [`crate::monitor::Monitor::new_with_paths`] is not `Send` either while its indexer holds the
+/// storage as `Rc`. A monitor with a `Send` indexer on the default
+/// [`crate::store::MonitorStore`] can be moved to the thread ticking it, and is still reached
+/// from the others through a handle.
+#[derive(Clone)]
+pub struct MonitorHandle {
+    sender: mpsc::Sender<Command>,
+    timeout: Duration,
+}
+
+impl MonitorHandle {
+    /// Sets how long the blocking methods wait for their command to be applied.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    pub fn monitor(&self, data: TypesToMonitor) -> Result<(), MonitorError> {
+        self.submit_monitor(data)?.wait(self.timeout)
+    }
+
+    pub fn monitor_with(
+        &self,
+        data: TypesToMonitor,
+        options: RegistrationOptions,
+    ) -> Result<(), MonitorError> {
+        self.submit(|reply| Command::Monitor(data, options, reply))?
+            .wait(self.timeout)
+    }
+
+    pub fn cancel(&self, data: TypesToMonitor) -> Result<(), MonitorError> {
+        self.submit_cancel(data)?.wait(self.timeout)
+    }
+
+    pub fn ack_news(&self, data: AckMonitorNews) -> Result<(), MonitorError> {
+        self.submit_ack_news(data)?.wait(self.timeout)
+    }
+
+    pub fn update_settings(
+        &self,
+        settings: MonitorSettings,
+    ) -> Result<SettingsUpdate, MonitorError> {
+        self.submit_update_settings(settings)?.wait(self.timeout)
+    }
+
+    pub fn get_monitors(&self, include_inactive: bool) -> Result<Vec<MonitorInfo>, MonitorError> {
+        self.submit(|reply| Command::GetMonitors(include_inactive, reply))?
+            .wait(self.timeout)
+    }
+
+    pub fn get_news_envelopes(&self) -> Result<Vec<NewsEnvelope>, MonitorError> {
+        self.submit(Command::GetNews)?.wait(self.timeout)
+    }
+
+    pub fn get_tx_status(&self, tx_id: Txid) -> Result<TransactionStatus, MonitorError> {
+        self.submit(|reply| Command::GetTxStatus(tx_id, reply))?
+            .wait(self.timeout)
+    }
+
+    pub fn get_monitor_height(&self) -> Result<BlockHeight, MonitorError> {
+        self.submit(Command::GetMonitorHeight)?.wait(self.timeout)
+    }
+
+    pub fn is_ready(&self) -> Result<bool, MonitorError> {
+        self.submit(Command::IsReady)?.wait(self.timeout)
+    }
+
+    pub fn submit_monitor(&self, data: TypesToMonitor) -> Result<CommandReceipt<()>, MonitorError> {
+        self.submit(|reply| Command::Monitor(data, RegistrationOptions::default(), reply))
+    }
+
+    pub fn submit_cancel(&self, data: TypesToMonitor) -> Result<CommandReceipt<()>, MonitorError> {
+        self.submit(|reply| Command::Cancel(data, reply))
+    }
+
+    pub fn submit_ack_news(
+        &self,
+        data: AckMonitorNews,
+    ) -> Result<CommandReceipt<()>, MonitorError> {
+        self.submit(|reply| Command::AckNews(data, reply))
+    }
+
+    pub fn submit_update_settings(
+        &self,
+        settings: MonitorSettings,
+    ) -> Result<CommandReceipt<SettingsUpdate>, MonitorError> {
+        self.submit(|reply| Command::UpdateSettings(settings, reply))
+    }
+
+    fn submit<T>(
+        &self,
+        command: impl FnOnce(Reply<T>) -> Command,
+    ) -> Result<CommandReceipt<T>, MonitorError> {
+        let (reply, receiver) = mpsc::channel();
+        self.sender
+            .send(command(reply))
+            .map_err(|_| MonitorError::MonitorStopped)?;
+
+        Ok(CommandReceipt { receiver })
+    }
+}
+
+/// Wakes the run loop of a monitor waiting between ticks in
+/// [`crate::monitor::Monitor::wait_for_commands`], so a new block is processed without waiting
+/// for the tick interval. Used by [`crate::zmq::ZmqBlockListener`] on each block published by the
+/// node, and by tests to inject wakeups. Wakeups sent during a tick end the next wait, a block
+/// announced while the tick runs is never left for the interval.
+#[derive(Clone)]
+pub struct BlockTrigger {
+    sender: mpsc::Sender<Command>,
+}
+
+impl BlockTrigger {
+    /// Fails with [`MonitorError::MonitorStopped`] once the monitor is dropped.
+    pub fn notify(&self) -> Result<(), MonitorError> {
+        self.sender
+            .send(Command::Wake)
+            .map_err(|_| MonitorError::MonitorStopped)
+    }
+}
+
+/// Commands queued for a monitor. Settings updates found by a tick are kept in `deferred`, as
+/// they can only be applied with the monitor borrowed mutably.
+pub(crate) struct CommandQueue {
+    sender: mpsc::Sender<Command>,
+    receiver: mpsc::Receiver<Command>,
+    deferred: RefCell<VecDeque<Command>>,
+}
+
+impl CommandQueue {
+    pub(crate) fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+
+        Self {
+            sender,
+            receiver,
+            deferred: RefCell::new(VecDeque::new()),
+        }
+    }
+
+    pub(crate) fn handle(&self) -> MonitorHandle {
+        MonitorHandle {
+            sender: self.sender.clone(),
+            timeout: DEFAULT_COMMAND_TIMEOUT,
+        }
+    }
+
+    pub(crate) fn block_trigger(&self) -> BlockTrigger {
+        BlockTrigger {
+            sender: self.sender.clone(),
+        }
+    }
+
+    /// Next queued command, if any. The deferred commands are not included.
+    pub(crate) fn try_recv(&self) -> Option<Command> {
+        self.receiver.try_recv().ok()
+    }
+
+    /// Waits up to `timeout` for the next queued command. The queue keeps a sender, so it only
+    /// returns None on timeout.
+    pub(crate) fn recv_timeout(&self, timeout: Duration) -> Option<Command> {
+        self.receiver.recv_timeout(timeout).ok()
+    }
+
+    pub(crate) fn defer(&self, command: Command) {
+        self.deferred.borrow_mut().push_back(command);
+    }
+
+    pub(crate) fn take_deferred(&self) -> VecDeque<Command> {
+        self.deferred.take()
+    }
+}
@@ -0,0 +1,123 @@
+use crate::types::StorageStats;
+use serde::de::{DeserializeOwned, Error as _};
+use serde::ser::Error as _;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::cell::Cell;
+
+/// Values whose JSON is shorter than this are written as they are, compressing them saves little.
+pub const COMPRESSION_THRESHOLD: usize = 1024;
+
+/// First character of a compressed value, followed by the base64 of the zstd frame of its JSON.
+/// A value without it is read as plain JSON, so the values written before compression are read
+/// the same way.
+const COMPRESSED_HEADER: char = '\u{1}';
+
+#[cfg(feature = "compression")]
+const ZSTD_LEVEL: i32 = 3;
+
+/// Values of the compressible keys written by the store, see [`StorageStats`].
+#[derive(Debug, Default)]
+pub(crate) struct CompressionCounters {
+    compressed_values: Cell<u64>,
+    uncompressed_values: Cell<u64>,
+    raw_bytes: Cell<u64>,
+    stored_bytes: Cell<u64>,
+}
+
+impl CompressionCounters {
+    fn add_compressed(&self, raw_bytes: usize, stored_bytes: usize) {
+        self.compressed_values.set(self.compressed_values.get() + 1);
+        self.raw_bytes.set(self.raw_bytes.get() + raw_bytes as u64);
+        self.stored_bytes
+            .set(self.stored_bytes.get() + stored_bytes as u64);
+    }
+
+    fn add_uncompressed(&self) {
+        self.uncompressed_values
+            .set(self.uncompressed_values.get() + 1);
+    }
+
+    pub(crate) fn stats(&self) -> StorageStats {
+        StorageStats {
+            compressed_values: self.compressed_values.get(),
+            uncompressed_values: self.uncompressed_values.get(),
+            raw_bytes: self.raw_bytes.get(),
+            stored_bytes: self.stored_bytes.get(),
+        }
+    }
+}
+
+/// Writes `value` compressed when its JSON reaches [`COMPRESSION_THRESHOLD`] and the
+/// `compression` feature is enabled, and as it is otherwise.
+pub(crate) struct Compressible<'a, V> {
+    pub value: &'a V,
+    pub counters: &'a CompressionCounters,
+}
+
+impl<V: Serialize> Serialize for Compressible<'_, V> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if cfg!(feature = "compression") {
+            let json = serde_json::to_vec(self.value).map_err(S::Error::custom)?;
+            if json.len() >= COMPRESSION_THRESHOLD {
+                if let Some(compressed) = compress(&json) {
+                    self.counters.add_compressed(json.len(), compressed.len());
+                    return serializer.serialize_str(&compressed);
+                }
+            }
+        }
+
+        self.counters.add_uncompressed();
+        self.value.serialize(serializer)
+    }
+}
+
+/// Reads a value written by [`Compressible`], compressed or not.
+pub(crate) struct Decompressed<V>(pub V);
+
+impl<'de, V: DeserializeOwned> Deserialize<'de> for Decompressed<V> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = serde_json::Value::deserialize(deserializer)?;
+
+        let value = match value {
+            serde_json::Value::String(stored) if stored.starts_with(COMPRESSED_HEADER) => {
+                let json = decompress(&stored[COMPRESSED_HEADER.len_utf8()..])
+                    .map_err(D::Error::custom)?;
+                serde_json::from_slice(&json)
+            }
+            value => serde_json::from_value(value),
+        };
+
+        value.map(Decompressed).map_err(D::Error::custom)
+    }
+}
+
+/// The header followed by the base64 of the zstd frame of `json`.
+#[cfg(feature = "compression")]
+fn compress(json: &[u8]) -> Option<String> {
+    use base64::Engine;
+
+    let frame = zstd::encode_all(json, ZSTD_LEVEL).ok()?;
+    let mut compressed = String::from(COMPRESSED_HEADER);
+    base64::engine::general_purpose::STANDARD.encode_string(frame, &mut compressed);
+    Some(compressed)
+}
+
+#[cfg(not(feature = "compression"))]
+fn compress(_json: &[u8]) -> Option<String> {
+    None
+}
+
+#[cfg(feature = "compression")]
+fn decompress(encoded: &str) -> Result<Vec<u8>, String> {
+    use base64::Engine;
+
+    let frame = base64::engine::general_purpose::STANDARD
+        .decode(encoded)
+        .map_err(|e| format!("compressed value is not valid base64: {e}"))?;
+    zstd::decode_all(frame.as_slice()).map_err(|e| format!("invalid zstd frame: {e}"))
+}
+
+#[cfg(not(feature = "compression"))]
+fn decompress(_encoded: &str) -> Result<Vec<u8>, String> {
+    Err("compressed value, enable the compression feature to read it".to_string())
+}
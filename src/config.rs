@@ -1,7 +1,17 @@
-use crate::settings::{DEFAULT_CONFIRMATION_THRESHOLD, DEFAULT_MAX_MONITORING_CONFIRMATIONS};
+use crate::settings::{
+    DEFAULT_CHAIN_LOG_BLOCKS, DEFAULT_CONFIRMATION_THRESHOLD,
+    DEFAULT_DEACTIVATION_ACK_GRACE_BLOCKS, DEFAULT_DEGRADED_BELOW,
+    DEFAULT_DEGRADED_MAX_BLOCKS_PER_TICK, DEFAULT_HEALTH_WINDOW_TICKS, DEFAULT_MAX_BLOCKS_PER_TICK,
+    DEFAULT_MAX_MONITORING_CONFIRMATIONS, DEFAULT_NEWS_HISTORY_BLOCKS, DEFAULT_RECOVERED_AT,
+    DEFAULT_RETENTION_BLOCKS, DEFAULT_SLOW_TICK_MS, DEFAULT_SWEEP_INTERVAL_BLOCKS,
+    DEFAULT_WEBHOOK_INITIAL_RETRY_MS, DEFAULT_WEBHOOK_MAX_RETRY_MS, DEFAULT_WEBHOOK_TIMEOUT_MS,
+};
+use crate::types::{BlockSource, Resolution};
+use bitcoin::Txid;
 use bitcoin_indexer::config::IndexerSettings;
 use bitvmx_bitcoin_rpc::rpc_config::RpcConfig;
 use serde::Deserialize;
+use std::path::PathBuf;
 use storage_backend::storage_config::StorageConfig;
 
 #[derive(Deserialize, Debug)]
@@ -9,13 +19,81 @@ pub struct MonitorConfig {
     pub storage: StorageConfig,
     pub bitcoin: RpcConfig,
     pub settings: Option<MonitorSettingsConfig>,
+    /// Maximum level of the logs of the binary, e.g. `info` or `debug`.
+    pub log_level: Option<String>,
+    /// Address the binary serves Prometheus metrics on when built with the `metrics` feature,
+    /// e.g. `0.0.0.0:9187`. `--metrics-listen` takes precedence.
+    pub metrics_listen: Option<String>,
+    /// Address the binary serves the JSON API on when built with the `http-api` feature, e.g.
+    /// `127.0.0.1:8180`, see `http_api::HttpApiServer`.
+    pub http_listen: Option<String>,
+    /// Endpoint the binary posts the news to, see [`crate::news_dispatcher::NewsDispatcher`].
+    pub webhook: Option<WebhookConfig>,
+    /// `zmqpubhashblock` endpoint of the node, e.g. `tcp://127.0.0.1:28332`. The binary ticks as
+    /// soon as a block is published there, polling every tick interval is kept as a fallback.
+    pub zmq_block_endpoint: Option<String>,
+    /// YAML or JSON file listing monitors, see [`crate::watch_file::WatchFile`]. The binary
+    /// registers the monitors added to it and cancels the ones removed while it runs.
+    pub watch_file: Option<PathBuf>,
+}
+
+impl MonitorConfig {
+    /// Names of the settings that differ in `reloaded` and can't be changed without a restart.
+    pub fn restart_required_changes(&self, reloaded: &MonitorConfig) -> Vec<&'static str> {
+        let mut changes = vec![];
+
+        if self.storage.path != reloaded.storage.path {
+            changes.push("storage.path");
+        }
+        if self.bitcoin.url != reloaded.bitcoin.url {
+            changes.push("bitcoin.url");
+        }
+        if self.metrics_listen != reloaded.metrics_listen {
+            changes.push("metrics_listen");
+        }
+        if self.http_listen != reloaded.http_listen {
+            changes.push("http_listen");
+        }
+        if self.webhook != reloaded.webhook {
+            changes.push("webhook");
+        }
+        if self.zmq_block_endpoint != reloaded.zmq_block_endpoint {
+            changes.push("zmq_block_endpoint");
+        }
+        if self.watch_file != reloaded.watch_file {
+            changes.push("watch_file");
+        }
+
+        changes
+    }
+}
+
+/// Outcome of reloading the settings of a running monitor.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SettingsUpdate {
+    /// Settings that changed and are used from the next tick.
+    pub applied: Vec<&'static str>,
+    /// Settings that changed but are ignored until the process is restarted.
+    pub requires_restart: Vec<&'static str>,
 }
 
 #[derive(Deserialize, Debug, Clone)]
 pub struct MonitorSettingsConfig {
     pub confirmation_threshold: Option<u32>,
     pub max_monitoring_confirmations: Option<u32>,
+    pub deactivation_requires_ack_grace_blocks: Option<u32>,
     pub indexer_settings: Option<IndexerSettings>,
+    pub sweep_interval_blocks: Option<u32>,
+    pub auto_prune_interval: Option<u32>,
+    pub retention: Option<RetentionSettings>,
+    pub news_resolution: Option<Resolution>,
+    pub track_mempool: Option<bool>,
+    pub reveal_contexts: Option<bool>,
+    pub max_blocks_per_tick: Option<u32>,
+    pub strict_detections: Option<bool>,
+    pub strict_allowed_txids: Option<Vec<Txid>>,
+    pub health: Option<HealthSettings>,
+    pub block_source: Option<BlockSource>,
 }
 
 impl Default for MonitorSettingsConfig {
@@ -23,7 +101,19 @@ impl Default for MonitorSettingsConfig {
         Self {
             confirmation_threshold: Some(DEFAULT_CONFIRMATION_THRESHOLD),
             max_monitoring_confirmations: Some(DEFAULT_MAX_MONITORING_CONFIRMATIONS),
+            deactivation_requires_ack_grace_blocks: Some(DEFAULT_DEACTIVATION_ACK_GRACE_BLOCKS),
             indexer_settings: Some(IndexerSettings::default()),
+            sweep_interval_blocks: Some(DEFAULT_SWEEP_INTERVAL_BLOCKS),
+            auto_prune_interval: None,
+            retention: Some(RetentionSettings::default()),
+            news_resolution: Some(Resolution::default()),
+            track_mempool: Some(false),
+            reveal_contexts: Some(false),
+            max_blocks_per_tick: Some(DEFAULT_MAX_BLOCKS_PER_TICK),
+            strict_detections: Some(false),
+            strict_allowed_txids: Some(vec![]),
+            health: Some(HealthSettings::default()),
+            block_source: Some(BlockSource::default()),
         }
     }
 }
@@ -37,7 +127,25 @@ impl From<MonitorSettingsConfig> for MonitorSettings {
             max_monitoring_confirmations: monitor_settings
                 .max_monitoring_confirmations
                 .unwrap_or(DEFAULT_MAX_MONITORING_CONFIRMATIONS),
+            deactivation_requires_ack_grace_blocks: monitor_settings
+                .deactivation_requires_ack_grace_blocks
+                .unwrap_or(DEFAULT_DEACTIVATION_ACK_GRACE_BLOCKS),
             indexer_settings: monitor_settings.indexer_settings,
+            sweep_interval_blocks: monitor_settings
+                .sweep_interval_blocks
+                .unwrap_or(DEFAULT_SWEEP_INTERVAL_BLOCKS),
+            auto_prune_interval: monitor_settings.auto_prune_interval,
+            retention: monitor_settings.retention.unwrap_or_default(),
+            news_resolution: monitor_settings.news_resolution.unwrap_or_default(),
+            track_mempool: monitor_settings.track_mempool.unwrap_or_default(),
+            reveal_contexts: monitor_settings.reveal_contexts.unwrap_or_default(),
+            max_blocks_per_tick: monitor_settings
+                .max_blocks_per_tick
+                .unwrap_or(DEFAULT_MAX_BLOCKS_PER_TICK),
+            strict_detections: monitor_settings.strict_detections.unwrap_or_default(),
+            strict_allowed_txids: monitor_settings.strict_allowed_txids.unwrap_or_default(),
+            health: monitor_settings.health.unwrap_or_default(),
+            block_source: monitor_settings.block_source.unwrap_or_default(),
         }
     }
 }
@@ -46,5 +154,129 @@ impl From<MonitorSettingsConfig> for MonitorSettings {
 pub struct MonitorSettings {
     pub confirmation_threshold: u32,
     pub max_monitoring_confirmations: u32,
+    /// Blocks past `max_monitoring_confirmations` a transaction monitor with unacknowledged news
+    /// stays active for. It is deactivated as soon as its news are acknowledged, or once the
+    /// grace has passed, see [`crate::types::DeactivationReason`].
+    pub deactivation_requires_ack_grace_blocks: u32,
     pub indexer_settings: Option<IndexerSettings>,
+    pub sweep_interval_blocks: u32,
+    /// Blocks between two automatic prunes of the store, each one removing the inactive
+    /// monitors and acknowledged news older than these blocks, see
+    /// [`crate::monitor::Monitor::prune`]. None, the default, leaves them in the store.
+    pub auto_prune_interval: Option<u32>,
+    pub retention: RetentionSettings,
+    pub news_resolution: Resolution,
+    /// Sends a news as soon as a monitored transaction is found in the mempool.
+    pub track_mempool: bool,
+    /// Shows the full contexts in logs and exports, see [`crate::redaction::reveal_contexts`].
+    pub reveal_contexts: bool,
+    /// Blocks processed by a tick when the monitor is behind the indexer, one by one from the
+    /// block after the monitor height.
+    pub max_blocks_per_tick: u32,
+    /// Testing facility for hermetic environments: a tick fails with
+    /// [`crate::errors::MonitorError::UnexpectedDetection`] when it writes a news of a monitor not
+    /// registered as expected (see [`crate::types::RegistrationOptions`]), or a pegin or
+    /// OP_RETURN detection of a transaction out of `strict_allowed_txids`. Not meant for
+    /// production, where any detection may be legitimate.
+    pub strict_detections: bool,
+    /// Transactions the pegin and OP_RETURN rules may detect in strict mode.
+    pub strict_allowed_txids: Vec<Txid>,
+    /// Weights of the health score and the thresholds of the degraded mode, see
+    /// [`crate::health::HealthScore`].
+    pub health: HealthSettings,
+    /// Whether `tick` drives the indexer or the caller applies the blocks of its own pipeline
+    /// with [`crate::monitor::Monitor::apply_block`]. Only one of them runs, the other fails with
+    /// [`crate::errors::MonitorError::BlockSourceMismatch`].
+    pub block_source: BlockSource,
+}
+
+/// Number of blocks the entries the sweep of expired keys purges are retained for.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct RetentionSettings {
+    /// Blocks the canceled monitors are kept in the trash for.
+    pub trash_blocks: u32,
+    /// Blocks kept in the chain log, the oldest processed are dropped. Not an expiring key
+    /// family, the log is capped by number of blocks.
+    pub chain_log_blocks: u32,
+    /// Blocks the news history is kept for, the news written before are pruned by the sweep of
+    /// expired keys.
+    pub news_history_blocks: u32,
+}
+
+impl Default for RetentionSettings {
+    fn default() -> Self {
+        Self {
+            trash_blocks: DEFAULT_RETENTION_BLOCKS,
+            chain_log_blocks: DEFAULT_CHAIN_LOG_BLOCKS,
+            news_history_blocks: DEFAULT_NEWS_HISTORY_BLOCKS,
+        }
+    }
+}
+
+/// Weights taken off the health score of 100 by each failure in the last `window_ticks` ticks,
+/// and by a p95 tick duration above `slow_tick_ms`. The monitor is degraded when the score goes
+/// below `degraded_below` and recovers once it is back at `recovered_at`.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct HealthSettings {
+    pub window_ticks: u32,
+    pub indexer_failure_weight: u32,
+    pub store_corruption_weight: u32,
+    pub news_resolution_failure_weight: u32,
+    pub orphan_disagreement_weight: u32,
+    pub slow_tick_weight: u32,
+    pub slow_tick_ms: u64,
+    pub degraded_below: u32,
+    pub recovered_at: u32,
+    /// Blocks processed by a tick in degraded mode, when it is lower than `max_blocks_per_tick`.
+    pub degraded_max_blocks_per_tick: u32,
+}
+
+impl Default for HealthSettings {
+    fn default() -> Self {
+        Self {
+            window_ticks: DEFAULT_HEALTH_WINDOW_TICKS,
+            indexer_failure_weight: 10,
+            store_corruption_weight: 50,
+            news_resolution_failure_weight: 5,
+            orphan_disagreement_weight: 5,
+            slow_tick_weight: 30,
+            slow_tick_ms: DEFAULT_SLOW_TICK_MS,
+            degraded_below: DEFAULT_DEGRADED_BELOW,
+            recovered_at: DEFAULT_RECOVERED_AT,
+            degraded_max_blocks_per_tick: DEFAULT_DEGRADED_MAX_BLOCKS_PER_TICK,
+        }
+    }
+}
+
+/// HTTP endpoint the news are posted to, one JSON `api::v1::NewsItem` per request. Only plain
+/// `http://` urls are supported, an https endpoint goes behind a TLS terminating proxy.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct WebhookConfig {
+    pub url: String,
+    /// Value of the `Authorization` header of the requests, e.g. `Bearer <token>`. Never logged.
+    pub auth_header: Option<String>,
+    #[serde(default)]
+    pub retry: WebhookRetrySettings,
+}
+
+/// Waits of the deliveries to a failing webhook, doubling from `initial_delay_ms` with each
+/// failure up to `max_delay_ms`, and the time the webhook has to answer a request.
+#[derive(Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct WebhookRetrySettings {
+    pub initial_delay_ms: u64,
+    pub max_delay_ms: u64,
+    pub timeout_ms: u64,
+}
+
+impl Default for WebhookRetrySettings {
+    fn default() -> Self {
+        Self {
+            initial_delay_ms: DEFAULT_WEBHOOK_INITIAL_RETRY_MS,
+            max_delay_ms: DEFAULT_WEBHOOK_MAX_RETRY_MS,
+            timeout_ms: DEFAULT_WEBHOOK_TIMEOUT_MS,
+        }
+    }
 }
@@ -1,8 +1,18 @@
+use crate::types::{AckMonitorNews, BlockSource, DetectionDescriptor};
+use bitcoin::BlockHash;
 use bitcoin_indexer::errors::IndexerError;
 use bitvmx_bitcoin_rpc::errors::BitcoinClientError;
+use bitvmx_bitcoin_rpc::types::BlockHeight;
+use std::io;
+use std::time::Duration;
 use storage_backend::error::StorageError;
 use thiserror::Error;
 
+/// Wait suggested before retrying a call that failed reaching the node or the indexer.
+const NODE_RETRY_AFTER: Duration = Duration::from_secs(5);
+/// Wait suggested before retrying a call that failed on the storage or on a lagging indexer.
+const LOCAL_RETRY_AFTER: Duration = Duration::from_secs(1);
+
 #[derive(Error, Debug)]
 pub enum MonitorError {
     #[error("Error with Indexer: {0}")]
@@ -22,6 +32,174 @@ pub enum MonitorError {
 
     #[error("Invalid confirmation trigger: requested {0}, max allowed {1}")]
     InvalidConfirmationTrigger(u32, u32),
+
+    #[error("Invalid monitor: {0}")]
+    InvalidMonitor(String),
+
+    #[error("Invalid settings: {0}")]
+    InvalidSettings(String),
+
+    #[error("News re-opened by the low work reorg at height {0} can't be acknowledged until the reorg news is")]
+    HeldByLowWorkReorg(BlockHeight),
+
+    /// The indexer returned a block of another height than the requested one, or a transaction
+    /// in a block above the best height (`requested`).
+    #[error("Indexer inconsistency: requested height {requested}, got height {got}")]
+    IndexerInconsistency {
+        requested: BlockHeight,
+        got: BlockHeight,
+    },
+
+    /// A command queued through a `MonitorHandle` was not applied within the wait, it stays
+    /// queued and may still be applied.
+    #[error("Command not applied within {0:?}")]
+    CommandTimeout(Duration),
+
+    #[error("The monitor stopped before applying the command")]
+    MonitorStopped,
+
+    /// A call to the wallet failed, see `Monitor::import_from_wallet`.
+    #[error("Wallet RPC error: {0}")]
+    WalletRpcError(String),
+
+    /// A call to the node failed, see [`crate::preflight::NodeApi`].
+    #[error("Node RPC error: {0}")]
+    NodeRpcError(String),
+
+    #[error("Failed to write the export: {0}")]
+    ExportFailed(#[from] std::io::Error),
+
+    /// `tick` called on a monitor whose blocks are applied by the caller, or `apply_block` and
+    /// `apply_reorg` on one that drives its indexer. The block source is in the settings.
+    #[error("Not available with the {0:?} block source")]
+    BlockSourceMismatch(BlockSource),
+
+    /// A block given to `apply_block` or `apply_reorg` doesn't extend the blocks applied before,
+    /// or an orphaned block is not one of the last blocks applied.
+    #[error("Block {0} doesn't match the chain applied to the monitor")]
+    ExternalChainMismatch(BlockHash),
+
+    /// Items of `ack_news_batch` left unacknowledged: the ones matching no stored news and the
+    /// ones held by a low work reorg. The other items of the batch are acknowledged.
+    #[error("{} news of the batch not acknowledged: {} unknown, {} held by a low work reorg", .unknown.len() + .held.len(), .unknown.len(), .held.len())]
+    NewsNotAcked {
+        unknown: Vec<AckMonitorNews>,
+        held: Vec<AckMonitorNews>,
+    },
+
+    /// Only returned by `tick` in strict mode, once the news of the detection is persisted.
+    #[error("Unexpected detection: {0}")]
+    UnexpectedDetection(DetectionDescriptor),
+}
+
+/// Classification of the errors, for callers deciding whether to retry.
+///
+/// Every variant is either transient (the same call may succeed later), fatal (the monitor can't
+/// go on, e.g. corrupted storage or invalid settings) or neither (the call itself is wrong, e.g.
+/// an invalid monitor, and fails again until it changes). The methods match every variant
+/// without a catch-all, so a new variant doesn't build until it is classified.
+impl MonitorError {
+    pub fn is_transient(&self) -> bool {
+        match self {
+            MonitorError::IndexerError(_)
+            | MonitorError::BitcoinClientError(_)
+            | MonitorError::WalletRpcError(_)
+            | MonitorError::NodeRpcError(_)
+            | MonitorError::HeldByLowWorkReorg(_)
+            | MonitorError::IndexerInconsistency { .. }
+            | MonitorError::CommandTimeout(_) => true,
+            MonitorError::MonitorStoreError(e) => e.is_transient(),
+            MonitorError::ExportFailed(e) => is_transient_io(e),
+            MonitorError::UnexpectedError(_)
+            | MonitorError::TransactionNotFound(_)
+            | MonitorError::InvalidConfirmationTrigger(..)
+            | MonitorError::InvalidMonitor(_)
+            | MonitorError::InvalidSettings(_)
+            | MonitorError::MonitorStopped
+            | MonitorError::BlockSourceMismatch(_)
+            | MonitorError::ExternalChainMismatch(_)
+            | MonitorError::NewsNotAcked { .. }
+            | MonitorError::UnexpectedDetection(_) => false,
+        }
+    }
+
+    pub fn is_fatal(&self) -> bool {
+        match self {
+            MonitorError::InvalidSettings(_)
+            | MonitorError::MonitorStopped
+            | MonitorError::BlockSourceMismatch(_)
+            | MonitorError::UnexpectedDetection(_) => true,
+            MonitorError::MonitorStoreError(e) => e.is_fatal(),
+            MonitorError::IndexerError(_)
+            | MonitorError::BitcoinClientError(_)
+            | MonitorError::WalletRpcError(_)
+            | MonitorError::NodeRpcError(_)
+            | MonitorError::HeldByLowWorkReorg(_)
+            | MonitorError::IndexerInconsistency { .. }
+            | MonitorError::CommandTimeout(_)
+            | MonitorError::ExportFailed(_)
+            | MonitorError::UnexpectedError(_)
+            | MonitorError::TransactionNotFound(_)
+            | MonitorError::InvalidConfirmationTrigger(..)
+            | MonitorError::InvalidMonitor(_)
+            | MonitorError::ExternalChainMismatch(_)
+            | MonitorError::NewsNotAcked { .. } => false,
+        }
+    }
+
+    /// Wait suggested before retrying a transient error, None when the error is not transient or
+    /// doesn't clear with time (a news held by a low work reorg waits for the ack of the reorg).
+    pub fn retry_after_hint(&self) -> Option<Duration> {
+        match self {
+            MonitorError::IndexerError(_)
+            | MonitorError::BitcoinClientError(_)
+            | MonitorError::WalletRpcError(_)
+            | MonitorError::NodeRpcError(_) => Some(NODE_RETRY_AFTER),
+            MonitorError::IndexerInconsistency { .. } => Some(LOCAL_RETRY_AFTER),
+            MonitorError::CommandTimeout(wait) => Some(*wait),
+            MonitorError::MonitorStoreError(e) => e.is_transient().then_some(LOCAL_RETRY_AFTER),
+            MonitorError::ExportFailed(e) => is_transient_io(e).then_some(LOCAL_RETRY_AFTER),
+            MonitorError::HeldByLowWorkReorg(_)
+            | MonitorError::UnexpectedError(_)
+            | MonitorError::TransactionNotFound(_)
+            | MonitorError::InvalidConfirmationTrigger(..)
+            | MonitorError::InvalidMonitor(_)
+            | MonitorError::InvalidSettings(_)
+            | MonitorError::MonitorStopped
+            | MonitorError::BlockSourceMismatch(_)
+            | MonitorError::ExternalChainMismatch(_)
+            | MonitorError::NewsNotAcked { .. }
+            | MonitorError::UnexpectedDetection(_) => None,
+        }
+    }
+
+    /// HTTP status of a response failed with this error: 503 when transient, 500 when fatal or
+    /// unexpected, 404 for a transaction not found and 400 for the other errors of the call.
+    pub fn http_status(&self) -> u16 {
+        match self {
+            e if e.is_transient() => 503,
+            e if e.is_fatal() => 500,
+            MonitorError::TransactionNotFound(_) => 404,
+            MonitorError::UnexpectedError(_) | MonitorError::MonitorStoreError(_) => 500,
+            _ => 400,
+        }
+    }
+
+    /// Finds the MonitorError of an anyhow error, also when the caller wrapped it with context,
+    /// so anyhow-based callers keep the classification.
+    pub fn from_anyhow(error: &anyhow::Error) -> Option<&MonitorError> {
+        error
+            .chain()
+            .find_map(|cause| cause.downcast_ref::<MonitorError>())
+    }
+}
+
+/// Interrupted or timed out writes may succeed if retried, other io errors won't.
+fn is_transient_io(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        io::ErrorKind::Interrupted | io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock
+    )
 }
 
 #[derive(Error, Debug)]
@@ -29,9 +207,50 @@ pub enum MonitorStoreError {
     #[error("Error with Internal Storage: {0}")]
     InternalStorageError(#[from] StorageError),
 
+    #[error("Corrupted entry {key}, expected {expected_type}: {source}")]
+    CorruptedEntry {
+        key: String,
+        expected_type: &'static str,
+        source: StorageError,
+    },
+
+    #[error("Failed to write {key}: {source}")]
+    WriteFailed { key: String, source: StorageError },
+
     #[error("Unexpected error: {0}")]
     UnexpectedError(String),
 
     #[error("Transaction not found: {0}")]
     TransactionNotFound(String),
+
+    #[error("Store schema version {0} is newer than the version {1} this monitor reads")]
+    IncompatibleSchema(u32, u32),
+}
+
+impl MonitorStoreError {
+    /// Errors of the storage backend may clear once the disk or the database recovers.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            MonitorStoreError::InternalStorageError(_) | MonitorStoreError::WriteFailed { .. } => {
+                true
+            }
+            MonitorStoreError::CorruptedEntry { .. }
+            | MonitorStoreError::UnexpectedError(_)
+            | MonitorStoreError::TransactionNotFound(_)
+            | MonitorStoreError::IncompatibleSchema(..) => false,
+        }
+    }
+
+    /// A corrupted entry is read the same way on every retry, and a store of a newer schema
+    /// stays unreadable until the monitor is upgraded.
+    pub fn is_fatal(&self) -> bool {
+        match self {
+            MonitorStoreError::CorruptedEntry { .. }
+            | MonitorStoreError::IncompatibleSchema(..) => true,
+            MonitorStoreError::InternalStorageError(_)
+            | MonitorStoreError::WriteFailed { .. }
+            | MonitorStoreError::UnexpectedError(_)
+            | MonitorStoreError::TransactionNotFound(_) => false,
+        }
+    }
 }
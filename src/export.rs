@@ -0,0 +1,142 @@
+use crate::errors::MonitorError;
+use crate::redaction::redact_context;
+use crate::store::{MonitorStoreApi, MonitoredTypes};
+use crate::types::{DetectionRecord, MonitorContext};
+use bitcoin::{Amount, BlockHash, OutPoint};
+use bitvmx_bitcoin_rpc::types::BlockHeight;
+use std::borrow::Cow;
+use std::io;
+
+/// Columns of the detections CSV, in the order they are written.
+pub const DETECTIONS_CSV_COLUMNS: [&str; 7] = [
+    "height",
+    "kind",
+    "txid",
+    "outpoint",
+    "amount_sat",
+    "context",
+    "block_hash",
+];
+
+/// Builds the archive record of a detection reported by `news`, found in `block_hash` at
+/// `height`. News that are not detections of a transaction (new blocks, reorgs, replacements)
+/// have no record.
+pub fn detection_record(
+    news: &MonitoredTypes,
+    height: BlockHeight,
+    block_hash: BlockHash,
+    amount: Option<Amount>,
+) -> Option<DetectionRecord> {
+    let (kind, tx_id, outpoint, context) = match news {
+        MonitoredTypes::Transaction(tx_id, context) => {
+            ("transaction", *tx_id, None, context.clone())
+        }
+        MonitoredTypes::RskPeginTransaction(tx_id, _) => {
+            ("rsk_pegin", *tx_id, None, MonitorContext::default())
+        }
+        MonitoredTypes::SpendingUTXOTransaction(target, vout, context, spender) => (
+            "spending_utxo",
+            *spender,
+            Some(OutPoint::new(*target, *vout)),
+            context.clone(),
+        ),
+        MonitoredTypes::MinorSpend(target, vout, context, spender) => (
+            "minor_spend",
+            *spender,
+            Some(OutPoint::new(*target, *vout)),
+            context.clone(),
+        ),
+        MonitoredTypes::ScriptPubKeyTransaction(_, context, tx_id, vout) => (
+            "script_pubkey",
+            *tx_id,
+            Some(OutPoint::new(*tx_id, *vout)),
+            context.clone(),
+        ),
+        MonitoredTypes::OpReturnTransaction(_, context, tx_id, _) => {
+            ("op_return", *tx_id, None, context.clone())
+        }
+        _ => return None,
+    };
+
+    Some(DetectionRecord {
+        kind: kind.to_string(),
+        tx_id,
+        outpoint,
+        amount,
+        context,
+        height,
+        block_hash,
+    })
+}
+
+/// Writes the archived detections found from `from_height` to `to_height` (both included) with
+/// at least `confirmation_threshold` confirmations at the monitor height, as CSV (RFC 4180) with
+/// a header row. Returns the number of detections written.
+///
+/// Contexts are redacted unless they are revealed, see [`crate::redaction`].
+/// Blocks have no timestamp in the indexer, so detections are dated by their height.
+pub fn export_detections_csv<S: MonitorStoreApi>(
+    store: &S,
+    confirmation_threshold: u32,
+    from_height: BlockHeight,
+    to_height: BlockHeight,
+    writer: &mut dyn io::Write,
+) -> Result<u64, MonitorError> {
+    let monitor_height = store.get_monitor_height()?;
+    let finalized = store
+        .get_archived_detections(from_height, to_height)?
+        .into_iter()
+        .filter(|record| {
+            monitor_height >= record.height
+                && monitor_height - record.height + 1 >= confirmation_threshold
+        });
+
+    write_csv_row(writer, DETECTIONS_CSV_COLUMNS.iter().map(|c| c.to_string()))?;
+
+    let mut rows = 0;
+    for record in finalized {
+        write_csv_row(
+            writer,
+            [
+                record.height.to_string(),
+                record.kind,
+                record.tx_id.to_string(),
+                record
+                    .outpoint
+                    .map(|outpoint| outpoint.to_string())
+                    .unwrap_or_default(),
+                record
+                    .amount
+                    .map(|amount| amount.to_sat().to_string())
+                    .unwrap_or_default(),
+                redact_context(&record.context),
+                record.block_hash.to_string(),
+            ],
+        )?;
+        rows += 1;
+    }
+    writer.flush()?;
+
+    Ok(rows)
+}
+
+fn write_csv_row(
+    writer: &mut dyn io::Write,
+    fields: impl IntoIterator<Item = String>,
+) -> io::Result<()> {
+    let row: Vec<String> = fields
+        .into_iter()
+        .map(|field| csv_field(&field).into_owned())
+        .collect();
+
+    write!(writer, "{}\r\n", row.join(","))
+}
+
+/// Quotes a field holding a comma, a quote or a line break, doubling its quotes.
+fn csv_field(field: &str) -> Cow<'_, str> {
+    if field.contains([',', '"', '\r', '\n']) {
+        Cow::Owned(format!("\"{}\"", field.replace('"', "\"\"")))
+    } else {
+        Cow::Borrowed(field)
+    }
+}
@@ -0,0 +1,41 @@
+use bitcoin::{BlockHash, Txid};
+use bitcoin_indexer::errors::IndexerError;
+use bitcoin_indexer::indexer::IndexerApi;
+use bitcoin_indexer::types::{FullBlock, TransactionInfo};
+use bitvmx_bitcoin_rpc::types::BlockHeight;
+
+/// Indexer of a monitor whose blocks come from the pipeline of the caller, see
+/// [`crate::types::BlockSource::External`]. The monitor never asks it anything: the blocks and
+/// transactions it looks up come from the blocks applied and its own snapshots.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NoIndexer;
+
+impl IndexerApi for NoIndexer {
+    fn tick(&self) -> Result<(), IndexerError> {
+        Ok(())
+    }
+
+    fn get_best_block(&self) -> Result<Option<FullBlock>, IndexerError> {
+        Ok(None)
+    }
+
+    fn get_block_by_height(&self, _height: BlockHeight) -> Result<Option<FullBlock>, IndexerError> {
+        Ok(None)
+    }
+
+    fn get_block_by_hash(&self, _hash: &BlockHash) -> Result<Option<FullBlock>, IndexerError> {
+        Ok(None)
+    }
+
+    fn get_tx(&self, _tx_id: &Txid) -> Result<Option<TransactionInfo>, IndexerError> {
+        Ok(None)
+    }
+
+    fn is_ready(&self) -> Result<bool, IndexerError> {
+        Ok(true)
+    }
+
+    fn get_estimated_fee_rate(&self) -> Result<u64, IndexerError> {
+        Ok(0)
+    }
+}
@@ -0,0 +1,18 @@
+use crate::errors::MonitorError;
+use bitcoin::{BlockHash, CompactTarget};
+use bitvmx_bitcoin_rpc::bitcoin_client::{BitcoinClient, BitcoinClientApi};
+use mockall::automock;
+
+/// Source of block header fields the indexer doesn't keep, used to compare the work of reorg branches.
+#[automock]
+pub trait BlockHeaderApi {
+    /// Returns the compact target (`bits`) of the block, or None when the header is not available.
+    fn get_block_bits(&self, hash: &BlockHash) -> Result<Option<CompactTarget>, MonitorError>;
+}
+
+impl BlockHeaderApi for BitcoinClient {
+    fn get_block_bits(&self, hash: &BlockHash) -> Result<Option<CompactTarget>, MonitorError> {
+        let block = self.get_block_by_hash(hash)?;
+        Ok(Some(block.header.bits))
+    }
+}
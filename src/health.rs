@@ -0,0 +1,166 @@
+use crate::config::HealthSettings;
+use crate::errors::{MonitorError, MonitorStoreError};
+use crate::types::HealthState;
+use std::cell::{Cell, RefCell};
+use std::collections::VecDeque;
+use std::time::Duration;
+
+/// Health of the monitor over its last ticks, recomputed at the end of every tick.
+///
+/// Each failure in the window takes its weight off a score of 100, and so does a p95 tick duration
+/// above the slow tick duration, see [`HealthSettings`]. The state only changes when the score
+/// crosses `degraded_below` or `recovered_at`, so a score between both keeps the current state.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthScore {
+    pub score: u32,
+    pub state: HealthState,
+    /// Ticks failed by the indexer or the node in the window
+    pub indexer_failures: u32,
+    /// Ticks failed by a corrupted entry of the store in the window
+    pub store_corruptions: u32,
+    /// Reads of news that could not be built in the window
+    pub news_resolution_failures: u32,
+    /// Orphan flags of the indexer the chain log disagreed with in the window
+    pub orphan_disagreements: u32,
+    pub tick_duration_p95: Duration,
+}
+
+impl Default for HealthScore {
+    fn default() -> Self {
+        Self {
+            score: 100,
+            state: HealthState::Healthy,
+            indexer_failures: 0,
+            store_corruptions: 0,
+            news_resolution_failures: 0,
+            orphan_disagreements: 0,
+            tick_duration_p95: Duration::ZERO,
+        }
+    }
+}
+
+/// Failures and duration of one tick.
+#[derive(Debug, Clone, Copy, Default)]
+struct TickSample {
+    indexer_failures: u32,
+    store_corruptions: u32,
+    news_resolution_failures: u32,
+    orphan_disagreements: u32,
+    duration: Duration,
+}
+
+/// Rolling window of the last ticks the [`HealthScore`] is computed from.
+#[derive(Debug, Default)]
+pub(crate) struct HealthTracker {
+    window: RefCell<VecDeque<TickSample>>,
+    /// Failures since the last tick ended, news are also read between ticks
+    pending: Cell<TickSample>,
+    score: RefCell<HealthScore>,
+}
+
+impl HealthTracker {
+    /// Counts the error a tick failed with, when it is an indexer failure or a corrupted entry.
+    pub(crate) fn record_tick_error(&self, error: &MonitorError) {
+        let mut pending = self.pending.get();
+
+        match error {
+            MonitorError::IndexerError(_)
+            | MonitorError::BitcoinClientError(_)
+            | MonitorError::IndexerInconsistency { .. } => pending.indexer_failures += 1,
+            MonitorError::MonitorStoreError(MonitorStoreError::CorruptedEntry { .. }) => {
+                pending.store_corruptions += 1
+            }
+            _ => return,
+        }
+
+        self.pending.set(pending);
+    }
+
+    pub(crate) fn add_news_resolution_failure(&self) {
+        let mut pending = self.pending.get();
+        pending.news_resolution_failures += 1;
+        self.pending.set(pending);
+    }
+
+    pub(crate) fn add_orphan_disagreement(&self) {
+        let mut pending = self.pending.get();
+        pending.orphan_disagreements += 1;
+        self.pending.set(pending);
+    }
+
+    /// Closes the sample of the tick that took `duration` and recomputes the score. Returns the new
+    /// score when the state changed.
+    pub(crate) fn end_tick(
+        &self,
+        duration: Duration,
+        settings: &HealthSettings,
+    ) -> Option<HealthScore> {
+        let sample = TickSample {
+            duration,
+            ..self.pending.take()
+        };
+
+        let mut window = self.window.borrow_mut();
+        window.push_back(sample);
+        while window.len() > settings.window_ticks.max(1) as usize {
+            window.pop_front();
+        }
+
+        let indexer_failures = window.iter().map(|s| s.indexer_failures).sum::<u32>();
+        let store_corruptions = window.iter().map(|s| s.store_corruptions).sum::<u32>();
+        let news_resolution_failures = window
+            .iter()
+            .map(|s| s.news_resolution_failures)
+            .sum::<u32>();
+        let orphan_disagreements = window.iter().map(|s| s.orphan_disagreements).sum::<u32>();
+        let tick_duration_p95 = p95(window.iter().map(|s| s.duration).collect());
+
+        let mut penalty = indexer_failures.saturating_mul(settings.indexer_failure_weight)
+            + store_corruptions.saturating_mul(settings.store_corruption_weight)
+            + news_resolution_failures.saturating_mul(settings.news_resolution_failure_weight)
+            + orphan_disagreements.saturating_mul(settings.orphan_disagreement_weight);
+        if tick_duration_p95 > Duration::from_millis(settings.slow_tick_ms) {
+            penalty += settings.slow_tick_weight;
+        }
+        let score = 100u32.saturating_sub(penalty);
+
+        let previous = self.score.borrow().state;
+        let state = match previous {
+            HealthState::Healthy if score < settings.degraded_below => HealthState::Degraded,
+            HealthState::Degraded if score >= settings.recovered_at => HealthState::Healthy,
+            state => state,
+        };
+
+        let health = HealthScore {
+            score,
+            state,
+            indexer_failures,
+            store_corruptions,
+            news_resolution_failures,
+            orphan_disagreements,
+            tick_duration_p95,
+        };
+        self.score.replace(health.clone());
+
+        (state != previous).then_some(health)
+    }
+
+    pub(crate) fn score(&self) -> HealthScore {
+        self.score.borrow().clone()
+    }
+
+    pub(crate) fn is_degraded(&self) -> bool {
+        self.score.borrow().state == HealthState::Degraded
+    }
+}
+
+/// Nearest rank 95th percentile, zero for no durations.
+fn p95(mut durations: Vec<Duration>) -> Duration {
+    if durations.is_empty() {
+        return Duration::ZERO;
+    }
+
+    durations.sort_unstable();
+    let rank = (durations.len() * 95).div_ceil(100);
+    durations[rank.saturating_sub(1)]
+}
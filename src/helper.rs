@@ -4,6 +4,8 @@ use bitcoin::secp256k1::ffi::{
 };
 use bitcoin::{Address, Network, OutPoint, Script, Transaction, Txid};
 
+use crate::types::RskPeginData;
+
 /// Validates the OP_RETURN data to ensure it contains 4 fields and starts with "RSK_PEGIN".
 pub fn is_valid_op_return_rsk_data(data: Vec<Vec<u8>>) -> bool {
     if data.len() != 1 {
@@ -104,6 +106,26 @@ pub fn is_a_pegin_tx(tx: &Transaction) -> bool {
     false
 }
 
+/// Parses the OP_RETURN data of a pegin, see [`is_a_pegin_tx`]. Returns None if `tx` is not a
+/// valid pegin.
+pub fn parse_pegin_data(tx: &Transaction) -> Option<RskPeginData> {
+    if !is_a_pegin_tx(tx) {
+        return None;
+    }
+
+    // Validated above: "RSK_PEGIN" (9 bytes), packet number (8), RSK address (20), key (32)
+    let data = extract_output_data(&tx.output[1].script_pubkey).concat();
+    let (packet_number, rest) = data[9..].split_at(8);
+    let (rsk_address, reimbursement_key) = rest.split_at(20);
+
+    Some(RskPeginData {
+        packet_number: u64::from_be_bytes(packet_number.try_into().ok()?),
+        rsk_address: rsk_address.try_into().ok()?,
+        reimbursement_key: bitcoin::XOnlyPublicKey::from_slice(reimbursement_key).ok()?,
+        amount: tx.output[0].value,
+    })
+}
+
 pub fn extract_output_data(script: &Script) -> Vec<Vec<u8>> {
     // Iterate over script instructions to find pushed data
     let instructions = script.instructions_minimal();
@@ -118,6 +140,16 @@ pub fn extract_output_data(script: &Script) -> Vec<Vec<u8>> {
     result
 }
 
+/// Returns the data pushed in the first OP_RETURN output of `tx` that starts with `prefix`.
+/// The pushes of the output are concatenated.
+pub fn find_op_return_with_prefix(tx: &Transaction, prefix: &[u8]) -> Option<Vec<u8>> {
+    tx.output
+        .iter()
+        .filter(|output| output.script_pubkey.is_op_return())
+        .map(|output| extract_output_data(&output.script_pubkey).concat())
+        .find(|payload| payload.starts_with(prefix))
+}
+
 pub fn is_spending_output(tx: &Transaction, target_txid: Txid, target_vout: u32) -> bool {
     tx.input.iter().any(|input| {
         input.previous_output
@@ -0,0 +1,187 @@
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tracing::{debug, warn};
+
+/// Largest request body read, a registration of a few thousand transactions fits.
+pub(crate) const MAX_BODY_SIZE: usize = 1 << 20;
+
+/// Largest request line plus headers read, a client sending more is dropped.
+const MAX_HEAD_SIZE: u64 = 16 << 10;
+
+/// How long a client has to send its request, and to read the response.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Listener thread of the JSON servers of the crate, serving one connection at a time with
+/// `serve`. The thread stops when the server is dropped.
+pub(crate) struct Server {
+    local_addr: SocketAddr,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl Server {
+    pub(crate) fn start<F>(addr: impl ToSocketAddrs, name: &str, mut serve: F) -> io::Result<Self>
+    where
+        F: FnMut(TcpStream) -> io::Result<()> + Send + 'static,
+    {
+        let listener = TcpListener::bind(addr)?;
+        let local_addr = listener.local_addr()?;
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+        let thread_name = name.to_string();
+
+        let handle = std::thread::Builder::new()
+            .name(name.to_string())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    if thread_stop.load(Ordering::SeqCst) {
+                        break;
+                    }
+
+                    match stream {
+                        Ok(stream) => {
+                            if let Err(e) = serve(stream) {
+                                debug!("{} request failed: {}", thread_name, e);
+                            }
+                        }
+                        Err(e) => warn!("{} listener error: {}", thread_name, e),
+                    }
+                }
+            })?;
+
+        Ok(Self {
+            local_addr,
+            stop,
+            handle: Some(handle),
+        })
+    }
+
+    pub(crate) fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+
+        // Wake the listener up so it sees the stop flag
+        let mut wake_addr = self.local_addr;
+        if wake_addr.ip().is_unspecified() {
+            wake_addr.set_ip([127, 0, 0, 1].into());
+        }
+        let _ = TcpStream::connect(wake_addr);
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+pub(crate) struct Request {
+    pub(crate) method: String,
+    /// Path and query of the request
+    pub(crate) target: String,
+    /// None when it is over [`MAX_BODY_SIZE`], it is not read
+    pub(crate) body: Option<Vec<u8>>,
+}
+
+/// Reads the request line, the headers and the body of the `Content-Length` header. A client
+/// slower than [`REQUEST_TIMEOUT`] or with a head over [`MAX_HEAD_SIZE`] fails the request, so
+/// it can't hold the listener.
+pub(crate) fn read_request(stream: &TcpStream) -> io::Result<Request> {
+    stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+    stream.set_write_timeout(Some(REQUEST_TIMEOUT))?;
+    let mut reader = BufReader::new(stream.try_clone()?);
+    let mut head = (&mut reader).take(MAX_HEAD_SIZE);
+
+    let mut request_line = String::new();
+    head.read_line(&mut request_line)?;
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let target = parts.next().unwrap_or_default().to_string();
+
+    let mut content_length = 0;
+    let mut header = String::new();
+    loop {
+        if head.read_line(&mut header)? == 0 {
+            if head.limit() == 0 {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "request head too large",
+                ));
+            }
+            break;
+        }
+        if header.trim() == "" {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                content_length = value.trim().parse().unwrap_or(0);
+            }
+        }
+        header.clear();
+    }
+
+    let body = if content_length > MAX_BODY_SIZE {
+        None
+    } else {
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body)?;
+        Some(body)
+    };
+
+    Ok(Request {
+        method,
+        target,
+        body,
+    })
+}
+
+/// Writes a JSON response and closes the connection.
+pub(crate) fn write_json(stream: TcpStream, status: u16, body: &str) -> io::Result<()> {
+    write_response(
+        stream,
+        status,
+        &[("Content-Type", "application/json")],
+        body,
+    )
+}
+
+/// Writes a response with `headers` and closes the connection.
+pub(crate) fn write_response(
+    mut stream: TcpStream,
+    status: u16,
+    headers: &[(&str, &str)],
+    body: &str,
+) -> io::Result<()> {
+    write!(stream, "HTTP/1.1 {} {}\r\n", status, reason_phrase(status))?;
+    for (name, value) in headers {
+        write!(stream, "{}: {}\r\n", name, value)?;
+    }
+    write!(
+        stream,
+        "Content-Length: {}\r\nConnection: close\r\n\r\n{}",
+        body.len(),
+        body
+    )?;
+    stream.flush()
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        204 => "No Content",
+        400 => "Bad Request",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        413 => "Payload Too Large",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    }
+}
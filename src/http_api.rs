@@ -0,0 +1,154 @@
+use crate::api::v1::{
+    AckRequest, ErrorResponse, MonitorsResponse, NewsResponse, OkResponse, RegisterRequest,
+    StatusResponse, VERSION,
+};
+use crate::commands::MonitorHandle;
+use crate::errors::MonitorError;
+use crate::http::{read_request, write_json, Server, MAX_BODY_SIZE};
+use crate::schema::describe_schema;
+use serde::{de::DeserializeOwned, Serialize};
+use std::io;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+
+/// Serves the JSON API of [`crate::api::v1`] for a monitor owned by the run loop:
+///
+/// - `POST /monitors` registers the monitor of a [`RegisterRequest`]
+/// - `GET /monitors` lists the active monitors, all of them with `?include_inactive=true`
+/// - `GET /news` returns the pending news
+/// - `POST /news/ack` acknowledges the news of an [`AckRequest`]
+/// - `GET /status` returns the monitor height and whether it is ready
+/// - `GET /schema` returns the [`describe_schema`] description of the payloads
+///
+/// The requests go through a [`MonitorHandle`], see there why, and are answered once the run
+/// loop applies them, between ticks or at the start of the next one. Errors are
+/// answered with an [`ErrorResponse`] and the status of [`MonitorError::http_status`]. Requests
+/// are served one at a time. The listener stops when the server is dropped.
+pub struct HttpApiServer {
+    server: Server,
+}
+
+impl HttpApiServer {
+    pub fn start(addr: impl ToSocketAddrs, monitor: MonitorHandle) -> io::Result<Self> {
+        let server = Server::start(addr, "http-api", move |stream| serve(stream, &monitor))?;
+
+        Ok(Self { server })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.server.local_addr()
+    }
+}
+
+/// Status and JSON body of a response.
+struct Response {
+    status: u16,
+    body: String,
+}
+
+impl Response {
+    fn ok(body: &impl Serialize) -> io::Result<Self> {
+        Ok(Self {
+            status: 200,
+            body: serde_json::to_string(body).map_err(io::Error::other)?,
+        })
+    }
+
+    fn result<T: Serialize>(result: Result<T, MonitorError>) -> io::Result<Self> {
+        match result {
+            Ok(body) => Self::ok(&body),
+            Err(e) => Self::error(e.http_status(), &ErrorResponse::from(&e)),
+        }
+    }
+
+    fn error(status: u16, error: &ErrorResponse) -> io::Result<Self> {
+        Ok(Self {
+            status,
+            body: serde_json::to_string(error).map_err(io::Error::other)?,
+        })
+    }
+
+    /// An error of the request itself, which never reaches the monitor.
+    fn rejected(status: u16, code: &str, message: impl Into<String>) -> io::Result<Self> {
+        Self::error(
+            status,
+            &ErrorResponse {
+                version: VERSION.to_string(),
+                code: code.to_string(),
+                message: message.into(),
+                transient: false,
+            },
+        )
+    }
+}
+
+fn serve(stream: TcpStream, monitor: &MonitorHandle) -> io::Result<()> {
+    let request = read_request(&stream)?;
+
+    let response = match &request.body {
+        Some(body) => route(&request.method, &request.target, body, monitor)?,
+        None => Response::rejected(
+            413,
+            "body_too_large",
+            format!("the body is over {} bytes", MAX_BODY_SIZE),
+        )?,
+    };
+
+    write_json(stream, response.status, &response.body)
+}
+
+fn route(method: &str, target: &str, body: &[u8], monitor: &MonitorHandle) -> io::Result<Response> {
+    let (path, query) = target.split_once('?').unwrap_or((target, ""));
+
+    match (method, path) {
+        ("POST", "/monitors") => match parse::<RegisterRequest>(body) {
+            Ok(request) => Response::result(
+                request
+                    .into_registration()
+                    .and_then(|(data, options)| monitor.monitor_with(data, options))
+                    .map(|()| OkResponse::default()),
+            ),
+            Err(response) => response,
+        },
+        ("GET", "/monitors") => {
+            let include_inactive = query
+                .split('&')
+                .any(|param| param == "include_inactive=true");
+            Response::result(
+                monitor
+                    .get_monitors(include_inactive)
+                    .map(|monitors| MonitorsResponse::from(monitors.as_slice())),
+            )
+        }
+        ("GET", "/news") => Response::result(
+            monitor
+                .get_news_envelopes()
+                .map(|news| NewsResponse::from(news.as_slice())),
+        ),
+        ("POST", "/news/ack") => match parse::<AckRequest>(body) {
+            Ok(request) => Response::result(
+                monitor
+                    .ack_news(request.news.into())
+                    .map(|()| OkResponse::default()),
+            ),
+            Err(response) => response,
+        },
+        ("GET", "/status") => Response::result(
+            monitor
+                .get_monitor_height()
+                .and_then(|height| Ok(StatusResponse::new(height, monitor.is_ready()?))),
+        ),
+        ("GET", "/schema") => Response::ok(&describe_schema()),
+        (_, "/monitors" | "/news" | "/news/ack" | "/status" | "/schema") => Response::rejected(
+            405,
+            "method_not_allowed",
+            format!("{} is not allowed on {}", method, path),
+        ),
+        _ => Response::rejected(404, "not_found", format!("no route for {}", path)),
+    }
+}
+
+/// The request of `body`, or the response rejecting it.
+fn parse<T: DeserializeOwned>(body: &[u8]) -> Result<T, io::Result<Response>> {
+    serde_json::from_slice(body)
+        .map_err(|e| Response::rejected(400, "invalid_request", e.to_string()))
+}
@@ -1,7 +1,46 @@
+pub mod api;
+#[cfg(feature = "async")]
+pub mod async_monitor;
+pub mod builder;
+pub mod chain_log;
+pub mod commands;
+pub mod compression;
 pub mod config;
 pub mod errors;
+pub mod export;
+pub mod external;
+pub mod headers;
+pub mod health;
 pub mod helper;
+mod http;
+#[cfg(feature = "http-api")]
+pub mod http_api;
+pub mod mempool;
+pub mod metrics;
+pub mod migrations;
 pub mod monitor;
+pub mod news_dispatcher;
+pub mod news_event;
+pub mod preflight;
+pub mod progress;
+#[cfg(feature = "psbt")]
+pub mod psbt;
+pub mod redaction;
+pub mod rpc;
+pub mod scan_set;
+pub mod schema;
+pub mod self_test;
 pub mod settings;
+pub mod shutdown;
 pub mod store;
+pub mod table;
+#[cfg(feature = "testing")]
+pub mod testing;
 pub mod types;
+pub mod wallet_import;
+pub mod watch_file;
+pub mod zmq;
+
+pub use builder::{
+    WatchAlternatives, WatchOpReturn, WatchOutpoint, WatchPegin, WatchScript, WatchTx,
+};
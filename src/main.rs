@@ -0,0 +1,848 @@
+use anyhow::Result;
+use bitcoin::{BlockHash, OutPoint, Txid};
+use bitvmx_bitcoin_rpc::bitcoin_client::BitcoinClient;
+use bitvmx_settings::settings;
+use bitvmx_transaction_monitor::{
+    chain_log::{chain_log, verify_chain_log},
+    config::{MonitorConfig, MonitorSettings, MonitorSettingsConfig},
+    errors::{MonitorError, MonitorStoreError},
+    export::export_detections_csv,
+    monitor::Monitor,
+    news_dispatcher::NewsDispatcher,
+    preflight::preflight_with_paths,
+    redaction::{self, redact_context},
+    rpc::RpcServer,
+    schema::describe_schema,
+    self_test::run_self_test,
+    shutdown::ShutdownToken,
+    store::{MonitorStore, MonitorStoreApi},
+    table::{monitors_table, news_table},
+    types::{AckMonitorNews, MonitorContext, MonitorInfo, MonitorType, StaleTx, TypesToMonitor},
+    watch_file::WatchFile,
+    zmq::ZmqBlockListener,
+    WatchOutpoint, WatchTx,
+};
+use clap::{Parser, Subcommand, ValueEnum};
+use std::{fs::File, io::BufWriter, rc::Rc, str::FromStr, sync::Arc, time::Duration};
+use storage_backend::storage::Storage;
+use tracing::{error, info, warn};
+use tracing_subscriber::{filter::LevelFilter, fmt, prelude::*, reload, Registry};
+
+/// Longest wait between two ticks while they keep failing with transient errors.
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60);
+
+/// Exit code of the store subcommands when what they look for is not in the store, like the
+/// news of `ack`. Argument errors already exit with 2.
+const EXIT_NOT_FOUND: i32 = 3;
+
+/// Exit code of the store subcommands when the store can't be opened, read or written.
+const EXIT_STORAGE_ERROR: i32 = 4;
+
+#[derive(Parser, Debug)]
+#[command(
+    version,
+    about = "Monitors bitcoin transactions and reports news about them"
+)]
+struct Args {
+    /// Path to the monitor configuration file
+    #[arg(long, default_value = "config/monitor_config.yaml")]
+    config: String,
+
+    /// Milliseconds to wait between ticks
+    #[arg(long, default_value_t = 1000)]
+    tick_interval_ms: u64,
+
+    /// Shows the full monitor contexts in the logs and the output of the commands, overriding
+    /// the reveal_contexts setting
+    #[arg(long)]
+    reveal_contexts: bool,
+
+    /// Address to serve Prometheus metrics on, e.g. 0.0.0.0:9187, overriding metrics_listen in
+    /// the configuration file
+    #[cfg(feature = "metrics")]
+    #[arg(long)]
+    metrics_listen: Option<String>,
+
+    /// Address to serve the JSON-RPC 2.0 methods of the monitor on over HTTP, e.g.
+    /// 127.0.0.1:8545
+    #[arg(long)]
+    rpc_listen: Option<String>,
+
+    /// Checks the node, the storage and the settings without running the monitor, printing
+    /// every problem found. Exits with an error if any check fails
+    #[arg(long)]
+    check: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Asks the running monitor to reload its configuration file, like SIGHUP does on unix
+    Reload,
+    /// Prints the monitor height and the monitored transactions waiting in the mempool
+    Status {
+        /// Prints the status as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Lists the transactions first seen in the mempool more than this number of blocks ago
+        #[arg(long, default_value_t = 6)]
+        stale_after_blocks: u32,
+    },
+    /// Writes the finalized detections between two heights to a CSV file
+    Export {
+        /// First height exported
+        #[arg(long)]
+        from: u32,
+
+        /// Last height exported, included
+        #[arg(long)]
+        to: u32,
+
+        /// Path of the CSV file, replaced if it exists
+        #[arg(long)]
+        out: String,
+    },
+    /// Prints the monitors of a target, active or not, with their history
+    Explain {
+        /// Txid, outpoint as txid:vout, script pubkey or OP_RETURN prefix in hex
+        target: String,
+
+        /// Prints the monitors as JSON
+        #[arg(long)]
+        json: bool,
+    },
+    /// Prints the blocks processed by the monitor as JSON Lines, one block per line in
+    /// processing order
+    ChainLog {
+        /// First height printed
+        #[arg(long, default_value_t = 0)]
+        from: u32,
+
+        /// Last height printed, included
+        #[arg(long, default_value_t = u32::MAX)]
+        to: u32,
+
+        /// Checks the hash linkage of the whole log instead, printing a line per gap or fork.
+        /// Exits with an error if there is a gap
+        #[arg(long)]
+        verify: bool,
+    },
+    /// Registers monitors for what a Bitcoin Core wallet tracks: its unconfirmed transactions,
+    /// its unspent outputs and its receiving addresses. Targets already monitored are skipped
+    ImportWallet {
+        /// Name of the wallet in the configured node
+        #[arg(long)]
+        wallet: String,
+
+        /// Context of the monitors registered
+        #[arg(long, default_value = "wallet-import")]
+        context: String,
+    },
+    /// Prints the monitors and news of the JSON API with the type of their fields, and the
+    /// versions of the API and the store
+    Schema {
+        /// Prints the schema as JSON, for the code generators of client types
+        #[arg(long)]
+        json: bool,
+    },
+    /// Runs the monitor on a temporary storage against the configured regtest node and prints a
+    /// report of each check. Exits with an error if any check fails
+    SelfTest {
+        /// Runs the checks that mine blocks, send transactions and invalidate a block
+        #[arg(long)]
+        allow_chain_mutation: bool,
+    },
+    #[command(flatten)]
+    Store(StoreCommand),
+}
+
+/// Subcommands working on the configured storage without running the monitor, which must not
+/// be running on it. They exit with `EXIT_NOT_FOUND` when what they look for is not in the
+/// store, and with `EXIT_STORAGE_ERROR` when the store can't be opened, read or written.
+#[derive(Subcommand, Debug)]
+enum StoreCommand {
+    /// Registers a monitor for a transaction, picked up by the next run of the monitor
+    AddTx {
+        txid: Txid,
+
+        /// Context returned with the news of the transaction
+        #[arg(long, default_value = "")]
+        context: String,
+    },
+    /// Registers a monitor for the spend of an output, given as txid:vout
+    AddSpend {
+        outpoint: OutPoint,
+
+        /// Context returned with the news of the spend
+        #[arg(long, default_value = "")]
+        context: String,
+    },
+    /// Prints a table of the active monitors
+    ListMonitors {
+        /// Lists the inactive monitors too
+        #[arg(long)]
+        inactive: bool,
+    },
+    /// Prints a table of the pending news, in sequence order
+    News,
+    /// Acknowledges a pending news
+    Ack {
+        kind: AckKind,
+
+        /// Txid of the news, outpoint as txid:vout for the spend and script pubkey news, block
+        /// hash for the new block news
+        id: String,
+
+        /// Context of the news
+        #[arg(long, default_value = "")]
+        context: String,
+    },
+}
+
+/// Kinds of the news acknowledged by `ack`, named like the news kinds of the JSON API.
+#[derive(ValueEnum, Debug, Clone, Copy)]
+#[value(rename_all = "snake_case")]
+enum AckKind {
+    Transaction,
+    OrphanedTransaction,
+    OpReturn,
+    RskPegin,
+    SpendingUtxo,
+    SpendingUtxoUnconfirmed,
+    MinorSpend,
+    ScriptPubkey,
+    NewBlock,
+}
+
+type LogLevelHandle = reload::Handle<LevelFilter, Registry>;
+
+fn main() -> Result<()> {
+    let (level_layer, log_level) = reload::Layer::new(LevelFilter::INFO);
+    tracing_subscriber::registry()
+        .with(level_layer)
+        .with(fmt::layer().with_target(false))
+        .init();
+
+    let args = Args::parse();
+
+    // The schema is the one of this build, it needs no configuration
+    if let Some(Command::Schema { json }) = args.command {
+        return print_schema(json);
+    }
+
+    let mut config = settings::load_config_file::<MonitorConfig>(Some(args.config.clone()))?;
+    apply_cli_overrides(&args, &mut config);
+    apply_log_level(&config, &log_level);
+
+    // The self-test uses its own storage, the one of a running monitor is left untouched
+    if let Some(Command::SelfTest {
+        allow_chain_mutation,
+    }) = args.command
+    {
+        let report = run_self_test(&config.bitcoin, allow_chain_mutation);
+        println!("{}", report);
+        if !report.passed() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    if let Some(Command::Store(command)) = &args.command {
+        redaction::reveal_contexts(settings_of(&config).reveal_contexts);
+        if let Err(e) = run_store_command(&config, command) {
+            if e.downcast_ref::<MonitorStoreError>().is_some() {
+                error!("{}", e);
+                std::process::exit(EXIT_STORAGE_ERROR);
+            }
+            return Err(e);
+        }
+        return Ok(());
+    }
+
+    // The preflight opens the store without migrating it, a store it can't open is reported
+    if args.check {
+        let report =
+            preflight_with_paths(&config.bitcoin, &config.storage, config.settings.clone());
+        println!("{}", report);
+        if !report.passed() {
+            std::process::exit(1);
+        }
+        return Ok(());
+    }
+
+    let storage = Rc::new(Storage::new(&config.storage)?);
+
+    match args.command {
+        Some(Command::Reload) => {
+            MonitorStore::new(storage)?.request_reload()?;
+            info!("Reload requested, it is applied on the next tick of the running monitor");
+            return Ok(());
+        }
+        Some(Command::Status {
+            json,
+            stale_after_blocks,
+        }) => {
+            redaction::reveal_contexts(settings_of(&config).reveal_contexts);
+            return print_status(&MonitorStore::new(storage)?, json, stale_after_blocks);
+        }
+        Some(Command::Export { from, to, out }) => {
+            let settings = settings_of(&config);
+            redaction::reveal_contexts(settings.reveal_contexts);
+            let mut writer = BufWriter::new(File::create(&out)?);
+            let rows = export_detections_csv(
+                &MonitorStore::new(storage)?,
+                settings.confirmation_threshold,
+                from,
+                to,
+                &mut writer,
+            )?;
+            info!("Exported {} detections to {}", rows, out);
+            return Ok(());
+        }
+        Some(Command::Explain { target, json }) => {
+            redaction::reveal_contexts(settings_of(&config).reveal_contexts);
+            return print_explain(&MonitorStore::new(storage)?, &target, json);
+        }
+        Some(Command::ChainLog { from, to, verify }) => {
+            return print_chain_log(&MonitorStore::new(storage)?, from, to, verify);
+        }
+        Some(Command::ImportWallet { wallet, context }) => {
+            let monitor =
+                Monitor::new_with_paths(&config.bitcoin, storage, config.settings.clone())?;
+            let mut rpc_config = config.bitcoin.clone();
+            rpc_config.wallet = wallet.clone();
+            let client = BitcoinClient::new_from_config(&rpc_config)?;
+
+            let report = monitor.import_from_wallet(&client, &wallet, context)?;
+            for (kind, count) in [
+                ("Transactions", report.transactions),
+                ("Unspent outputs", report.utxos),
+                ("Addresses", report.scripts),
+            ] {
+                println!(
+                    "{}: {} registered, {} already monitored",
+                    kind, count.registered, count.skipped
+                );
+            }
+            return Ok(());
+        }
+        Some(Command::SelfTest { .. })
+        | Some(Command::Schema { .. })
+        | Some(Command::Store(_))
+        | None => {}
+    }
+
+    let mut monitor = Monitor::new_with_paths(&config.bitcoin, storage, config.settings.clone())?;
+
+    #[cfg(feature = "metrics")]
+    let exporter = match &config.metrics_listen {
+        Some(addr) => {
+            let exporter =
+                bitvmx_transaction_monitor::metrics::MetricsExporter::start(addr.as_str())?;
+            info!(
+                "Serving metrics on http://{}/metrics",
+                exporter.local_addr()
+            );
+            Some(exporter)
+        }
+        None => None,
+    };
+
+    #[cfg(feature = "http-api")]
+    let _api_server = match &config.http_listen {
+        Some(addr) => {
+            let server = bitvmx_transaction_monitor::http_api::HttpApiServer::start(
+                addr.as_str(),
+                monitor.handle(),
+            )?;
+            info!("Serving the API on http://{}", server.local_addr());
+            Some(server)
+        }
+        None => None,
+    };
+
+    let _rpc_server = match &args.rpc_listen {
+        Some(addr) => {
+            let server = RpcServer::start(addr.as_str(), monitor.handle())?;
+            info!("Serving JSON-RPC on http://{}", server.local_addr());
+            Some(server)
+        }
+        None => None,
+    };
+
+    let mut dispatcher = match &config.webhook {
+        Some(webhook) => {
+            let dispatcher = NewsDispatcher::new(webhook.clone())?;
+            info!("Posting the news to {}", webhook.url);
+            Some(dispatcher)
+        }
+        None => None,
+    };
+
+    // Blocks published by the node end the wait between ticks, which still poll in case one is
+    // missed. The listener stops when dropped at the end of the run loop
+    let _block_listener = match &config.zmq_block_endpoint {
+        Some(endpoint) => {
+            let listener = ZmqBlockListener::start(endpoint, monitor.block_trigger())?;
+            info!("Ticking on the blocks published on {}", endpoint);
+            Some(listener)
+        }
+        None => None,
+    };
+
+    let mut watch_file = config.watch_file.as_ref().map(WatchFile::new);
+
+    // Ctrl-C lets the tick in flight finish its block, and ends the wait for the next one
+    let shutdown = ShutdownToken::new();
+    let handler_shutdown = shutdown.clone();
+    let wake = monitor.block_trigger();
+    ctrlc::set_handler(move || {
+        handler_shutdown.request();
+        let _ = wake.notify();
+    })?;
+
+    #[cfg(unix)]
+    let sighup = {
+        let sighup = Arc::new(std::sync::atomic::AtomicBool::new(false));
+        signal_hook::flag::register(signal_hook::consts::SIGHUP, sighup.clone())?;
+        sighup
+    };
+
+    // News are logged by another thread as each tick ends, they are left pending for consumers
+    let news_receiver = monitor.news_receiver();
+    std::thread::spawn(move || {
+        for news in news_receiver {
+            info!(?news, "News");
+        }
+    });
+
+    info!("Monitor started");
+
+    let tick_interval = Duration::from_millis(args.tick_interval_ms);
+    let mut failed_ticks = 0;
+
+    while !shutdown.is_requested() {
+        let mut reload_requested = monitor.take_reload_request().unwrap_or_else(|e| {
+            error!("Failed to read the reload request: {}", e);
+            false
+        });
+        #[cfg(unix)]
+        {
+            reload_requested |= sighup.swap(false, std::sync::atomic::Ordering::SeqCst);
+        }
+        if reload_requested {
+            reload_config(&args, &mut config, &mut monitor, &log_level);
+        }
+
+        if let Some(watch_file) = &mut watch_file {
+            match watch_file.sync(&monitor) {
+                Ok(Some(changes)) => info!(
+                    added = changes.added.len(),
+                    removed = changes.removed.len(),
+                    "Watch file {} read",
+                    watch_file.path().display()
+                ),
+                Ok(None) => {}
+                Err(e) => error!(
+                    "Failed to read the watch file, keeping its previous monitors: {}",
+                    e
+                ),
+            }
+        }
+
+        let wait = match monitor.tick_with_shutdown(&shutdown) {
+            Ok(progress) => {
+                if progress.stopped_early {
+                    info!(
+                        blocks_processed = progress.blocks_processed,
+                        monitor_height = progress.monitor_height,
+                        "Tick stopped early by the shutdown, the blocks left are processed on the next start"
+                    );
+                }
+                failed_ticks = 0;
+                tick_interval
+            }
+            Err(e) if e.is_fatal() => {
+                error!("Tick failed, stopping the monitor: {}", e);
+                return Err(e.into());
+            }
+            Err(e) => {
+                failed_ticks += 1;
+                let wait = retry_delay(&e, failed_ticks, tick_interval);
+                error!(
+                    transient = e.is_transient(),
+                    failed_ticks, "Tick failed, next one in {:?}: {}", wait, e
+                );
+                wait
+            }
+        };
+
+        #[cfg(feature = "metrics")]
+        if let Some(exporter) = &exporter {
+            match monitor.metrics_snapshot() {
+                Ok(snapshot) => exporter.update(&snapshot),
+                Err(e) => {
+                    error!("Failed to collect metrics: {}", e);
+                    exporter.fail(&e);
+                }
+            }
+        }
+
+        // The news left by a failed tick are delivered too, the dispatcher keeps its own backoff
+        if let Some(dispatcher) = &mut dispatcher {
+            if let Err(e) = dispatcher.dispatch(&monitor) {
+                error!("Failed to dispatch the news: {}", e);
+            }
+        }
+
+        // Commands queued through the monitor handles are applied as they arrive until the next
+        // tick, which starts early if a block is published
+        monitor.wait_for_commands(wait);
+    }
+
+    // The metrics exporter and the API servers, if any, stop when dropped at the end of the run
+    // loop
+    match monitor.get_monitor_height() {
+        Ok(height) => info!("Monitor stopped at height {}", height),
+        Err(e) => info!("Monitor stopped, its height can't be read: {}", e),
+    }
+
+    Ok(())
+}
+
+/// Wait before the tick following `failed_ticks` consecutive failures. Transient errors back off
+/// from their retry hint, doubling with each failure up to `MAX_RETRY_DELAY`; the other errors
+/// won't clear by waiting, so the ticks keep their interval.
+fn retry_delay(error: &MonitorError, failed_ticks: u32, tick_interval: Duration) -> Duration {
+    if !error.is_transient() {
+        return tick_interval;
+    }
+
+    let base = error.retry_after_hint().unwrap_or(tick_interval);
+    base.saturating_mul(1 << failed_ticks.saturating_sub(1).min(6))
+        .clamp(tick_interval, MAX_RETRY_DELAY.max(tick_interval))
+}
+
+/// Reads the configuration file again and applies the settings that can change while running.
+/// `config` keeps the effective configuration, so settings that require a restart keep being
+/// reported until the process is restarted.
+fn reload_config(
+    args: &Args,
+    config: &mut MonitorConfig,
+    monitor: &mut MonitorType,
+    log_level: &LogLevelHandle,
+) {
+    let path = &args.config;
+    let mut reloaded = match settings::load_config_file::<MonitorConfig>(Some(path.to_string())) {
+        Ok(reloaded) => reloaded,
+        Err(e) => {
+            error!(
+                "Failed to reload {}, keeping the current settings: {}",
+                path, e
+            );
+            return;
+        }
+    };
+    apply_cli_overrides(args, &mut reloaded);
+
+    let mut update = match monitor.reload_config(config, &reloaded) {
+        Ok(update) => update,
+        Err(e) => {
+            error!(
+                "Failed to reload {}, keeping the current settings: {}",
+                path, e
+            );
+            return;
+        }
+    };
+
+    if config.log_level != reloaded.log_level {
+        apply_log_level(&reloaded, log_level);
+        update.applied.push("log_level");
+    }
+
+    config.settings = reloaded.settings;
+    config.log_level = reloaded.log_level;
+
+    info!(
+        applied = ?update.applied,
+        requires_restart = ?update.requires_restart,
+        "Configuration reloaded from {}",
+        path
+    );
+}
+
+/// Prints the state kept in the store, the running monitor is not queried. Contexts are redacted
+/// unless they are revealed.
+fn print_status<S: MonitorStoreApi>(store: &S, json: bool, stale_after_blocks: u32) -> Result<()> {
+    let monitor_height = store.get_monitor_height()?;
+    let stale_unconfirmed: Vec<StaleTx> = store
+        .get_stale_unconfirmed(stale_after_blocks)?
+        .into_iter()
+        .map(|stale| StaleTx {
+            context: redact_context(&stale.context).into(),
+            ..stale
+        })
+        .collect();
+
+    if json {
+        let status = serde_json::json!({
+            "monitor_height": monitor_height,
+            "stale_unconfirmed": stale_unconfirmed,
+        });
+        println!("{}", serde_json::to_string_pretty(&status)?);
+        return Ok(());
+    }
+
+    println!("Monitor height: {}", monitor_height);
+    println!(
+        "Unconfirmed for more than {} blocks: {}",
+        stale_after_blocks,
+        stale_unconfirmed.len()
+    );
+    for stale in stale_unconfirmed {
+        println!(
+            "  {} | Context({}) | FirstSeen({}) | Waiting({} blocks) | FeeRate({})",
+            stale.txid,
+            stale.context,
+            stale.first_seen_height,
+            stale.blocks_waiting,
+            stale.last_known_fee_rate
+        );
+    }
+
+    Ok(())
+}
+
+fn print_schema(json: bool) -> Result<()> {
+    let schema = describe_schema();
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&schema)?);
+        return Ok(());
+    }
+
+    println!(
+        "API {}, store schema version {}, monitor export version {}",
+        schema.api_version, schema.store_schema_version, schema.monitor_export_version
+    );
+    for (title, variants) in [
+        ("Monitors", schema.monitors),
+        ("News", schema.news),
+        ("Targets", schema.targets),
+    ] {
+        println!("{}:", title);
+        for variant in variants {
+            println!("  {}: {}", variant.kind, variant.description);
+            for field in variant.fields {
+                println!("    {}", field);
+            }
+        }
+    }
+    println!("Objects:");
+    for object in schema.objects {
+        println!("  {}: {}", object.name, object.description);
+        for field in object.fields {
+            println!("    {}", field);
+        }
+    }
+    println!("Enums:");
+    for enumeration in schema.enums {
+        println!("  {}: {}", enumeration.name, enumeration.values.join(" | "));
+    }
+
+    Ok(())
+}
+
+/// Prints the monitors of `target` kept in the store, active or not, with their spender and
+/// their history. Contexts are redacted unless they are revealed.
+fn print_explain<S: MonitorStoreApi>(store: &S, target: &str, json: bool) -> Result<()> {
+    let target = target.to_lowercase();
+    let monitors: Vec<MonitorInfo> = store
+        .get_monitor_infos(true)?
+        .into_iter()
+        .filter(|info| info.monitor.target() == target)
+        .collect();
+
+    if json {
+        let monitors: Vec<serde_json::Value> = monitors
+            .iter()
+            .map(|info| {
+                serde_json::json!({
+                    "kind": info.monitor.kind(),
+                    "target": info.monitor.target(),
+                    "context": redact_context(info.monitor.context()),
+                    "active": info.active,
+                    "spender_tx_id": info.spender_tx_id,
+                    "history": info.history,
+                })
+            })
+            .collect();
+        println!("{}", serde_json::to_string_pretty(&monitors)?);
+        return Ok(());
+    }
+
+    if monitors.is_empty() {
+        println!("No monitor for {}", target);
+        return Ok(());
+    }
+
+    for info in monitors {
+        println!(
+            "{:?} {} | Context({}) | Active({}) | Spender({})",
+            info.monitor.kind(),
+            info.monitor.target(),
+            redact_context(info.monitor.context()),
+            info.active,
+            info.spender_tx_id
+                .map(|tx_id| tx_id.to_string())
+                .unwrap_or_else(|| "-".to_string())
+        );
+        for event in info.history {
+            println!("  {:?}", event);
+        }
+    }
+
+    Ok(())
+}
+
+/// Prints the chain log kept in the store as JSON Lines, or the issues found verifying it.
+fn print_chain_log<S: MonitorStoreApi>(store: &S, from: u32, to: u32, verify: bool) -> Result<()> {
+    if !verify {
+        for entry in chain_log(store, from, to)? {
+            println!("{}", serde_json::to_string(&entry)?);
+        }
+        return Ok(());
+    }
+
+    let verification = verify_chain_log(&store.get_chain_log()?);
+    for issue in &verification.issues {
+        println!("{}", serde_json::to_string(issue)?);
+    }
+    // Logs share stdout with the JSON Lines
+    eprintln!(
+        "Chain log verified | Entries({}) | Issues({})",
+        verification.entries,
+        verification.issues.len()
+    );
+    if !verification.is_continuous() {
+        std::process::exit(1);
+    }
+
+    Ok(())
+}
+
+/// Runs a store subcommand on the configured storage, opened for the command only. Contexts
+/// are redacted unless they are revealed.
+fn run_store_command(config: &MonitorConfig, command: &StoreCommand) -> Result<()> {
+    let store = MonitorStore::open(&config.storage)?;
+
+    match command {
+        StoreCommand::AddTx { txid, context } => {
+            let monitor = WatchTx::new(*txid).context(context).build()?;
+            add_monitor(&store, monitor)?;
+            println!("Monitoring transaction {}", txid);
+        }
+        StoreCommand::AddSpend { outpoint, context } => {
+            let monitor = WatchOutpoint::new(outpoint.txid, outpoint.vout)
+                .context(context)
+                .build()?;
+            add_monitor(&store, monitor)?;
+            println!("Monitoring the spend of {}", outpoint);
+        }
+        StoreCommand::ListMonitors { inactive } => {
+            print!("{}", monitors_table(&store.get_monitor_infos(*inactive)?));
+        }
+        StoreCommand::News => {
+            print!("{}", news_table(&store.get_sequenced_news()?));
+        }
+        StoreCommand::Ack { kind, id, context } => {
+            let ack = ack_of(*kind, id, context)?;
+            if !store.ack_news_batch(vec![ack])?.is_empty() {
+                eprintln!("No pending {:?} news for {}", kind, id);
+                std::process::exit(EXIT_NOT_FOUND);
+            }
+            println!("Acknowledged the {:?} news for {}", kind, id);
+        }
+    }
+
+    Ok(())
+}
+
+/// Adds `monitor` to the store like `Monitor::save_monitor`, flagging the pending work so the
+/// next run of the monitor looks at it.
+fn add_monitor(store: &MonitorStore, monitor: TypesToMonitor) -> Result<()> {
+    store.set_pending_work(true)?;
+    store.add_monitor(monitor)?;
+    Ok(())
+}
+
+/// The acknowledgment of the news of `kind` for `id`, see [`StoreCommand::Ack`].
+fn ack_of(kind: AckKind, id: &str, context: &str) -> Result<AckMonitorNews> {
+    let context = MonitorContext::from(context);
+
+    let ack = match kind {
+        AckKind::Transaction => AckMonitorNews::Transaction(Txid::from_str(id)?, context),
+        AckKind::OrphanedTransaction => {
+            AckMonitorNews::OrphanedTransaction(Txid::from_str(id)?, context)
+        }
+        AckKind::OpReturn => AckMonitorNews::OpReturnTransaction(Txid::from_str(id)?, context),
+        AckKind::RskPegin => AckMonitorNews::RskPeginTransaction(Txid::from_str(id)?),
+        AckKind::SpendingUtxo => {
+            let outpoint = OutPoint::from_str(id)?;
+            AckMonitorNews::SpendingUTXOTransaction(outpoint.txid, outpoint.vout, context)
+        }
+        AckKind::SpendingUtxoUnconfirmed => {
+            let outpoint = OutPoint::from_str(id)?;
+            AckMonitorNews::SpendingUTXOUnconfirmed(outpoint.txid, outpoint.vout, context)
+        }
+        AckKind::MinorSpend => {
+            let outpoint = OutPoint::from_str(id)?;
+            AckMonitorNews::MinorSpend(outpoint.txid, outpoint.vout, context)
+        }
+        AckKind::ScriptPubkey => {
+            let outpoint = OutPoint::from_str(id)?;
+            AckMonitorNews::ScriptPubKeyTransaction(outpoint.txid, outpoint.vout, context)
+        }
+        AckKind::NewBlock => AckMonitorNews::NewBlock(Some(BlockHash::from_str(id)?)),
+    };
+
+    Ok(ack)
+}
+
+/// Applies the command line arguments that override settings of the configuration file.
+fn apply_cli_overrides(args: &Args, config: &mut MonitorConfig) {
+    if args.reveal_contexts {
+        config
+            .settings
+            .get_or_insert_with(MonitorSettingsConfig::default)
+            .reveal_contexts = Some(true);
+    }
+
+    #[cfg(feature = "metrics")]
+    if let Some(addr) = &args.metrics_listen {
+        config.metrics_listen = Some(addr.clone());
+    }
+}
+
+fn settings_of(config: &MonitorConfig) -> MonitorSettings {
+    MonitorSettings::from(config.settings.clone().unwrap_or_default())
+}
+
+fn apply_log_level(config: &MonitorConfig, log_level: &LogLevelHandle) {
+    let level = match config.log_level.as_deref().map(LevelFilter::from_str) {
+        None => LevelFilter::INFO,
+        Some(Ok(level)) => level,
+        Some(Err(e)) => {
+            warn!("Invalid log_level, keeping the current one: {}", e);
+            return;
+        }
+    };
+
+    if let Err(e) = log_level.reload(level) {
+        error!("Failed to change the log level: {}", e);
+    }
+}
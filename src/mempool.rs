@@ -0,0 +1,83 @@
+use crate::errors::MonitorError;
+use bitcoin::{OutPoint, Transaction, Txid};
+use bitcoincore_rpc::RpcApi;
+use bitvmx_bitcoin_rpc::bitcoin_client::{BitcoinClient, BitcoinClientApi};
+use mockall::automock;
+use serde::Deserialize;
+use std::collections::HashSet;
+
+/// Source of unconfirmed transactions, only queried for monitors that look for spends in the mempool.
+///
+/// Only the transactions of the monitors are asked for, so a tick costs the same whatever the
+/// size of the mempool.
+#[automock]
+pub trait MempoolApi {
+    /// Returns the transactions of `tx_ids` currently in the mempool.
+    fn get_mempool_txs(&self, tx_ids: &HashSet<Txid>) -> Result<Vec<Transaction>, MonitorError>;
+    /// Returns the transactions of the mempool spending any of `outpoints`.
+    fn get_mempool_spenders(
+        &self,
+        outpoints: &[OutPoint],
+    ) -> Result<Vec<Transaction>, MonitorError>;
+}
+
+/// Entry of `gettxspendingprevout`, without a spender when the output is not spent in the mempool.
+#[derive(Deserialize)]
+struct SpendingPrevout {
+    #[serde(default)]
+    spendingtxid: Option<Txid>,
+}
+
+impl MempoolApi for BitcoinClient {
+    fn get_mempool_txs(&self, tx_ids: &HashSet<Txid>) -> Result<Vec<Transaction>, MonitorError> {
+        let mut txs = Vec::new();
+
+        for tx_id in self.get_raw_mempool()? {
+            if !tx_ids.contains(&tx_id) {
+                continue;
+            }
+
+            // The transaction can leave the mempool between both calls
+            if let Some(tx) = self.get_transaction(&tx_id)? {
+                txs.push(tx);
+            }
+        }
+
+        Ok(txs)
+    }
+
+    /// Asks the node for the spenders of `outpoints` with `gettxspendingprevout`, available
+    /// since Bitcoin Core 24.
+    fn get_mempool_spenders(
+        &self,
+        outpoints: &[OutPoint],
+    ) -> Result<Vec<Transaction>, MonitorError> {
+        if outpoints.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let prevouts: Vec<serde_json::Value> = outpoints
+            .iter()
+            .map(|outpoint| serde_json::json!({ "txid": outpoint.txid, "vout": outpoint.vout }))
+            .collect();
+        let spent: Vec<SpendingPrevout> = self
+            .client
+            .call("gettxspendingprevout", &[prevouts.into()])
+            .map_err(|e| MonitorError::NodeRpcError(e.to_string()))?;
+
+        let spenders: HashSet<Txid> = spent
+            .into_iter()
+            .filter_map(|prevout| prevout.spendingtxid)
+            .collect();
+
+        let mut txs = Vec::new();
+        for tx_id in spenders {
+            // The spender can leave the mempool between both calls
+            if let Some(tx) = self.get_transaction(&tx_id)? {
+                txs.push(tx);
+            }
+        }
+
+        Ok(txs)
+    }
+}
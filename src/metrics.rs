@@ -0,0 +1,458 @@
+use crate::health::HealthScore;
+use crate::news_event::NewsEvent;
+use crate::types::{HealthState, StorageStats};
+use bitvmx_bitcoin_rpc::types::BlockHeight;
+use std::cell::{Cell, RefCell};
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::time::Duration;
+
+/// Counters updated by the monitor while it ticks.
+#[derive(Debug, Default)]
+pub struct MonitorCounters {
+    news_emitted: Cell<u64>,
+    news_emitted_by_kind: RefCell<BTreeMap<&'static str, u64>>,
+    news_acked: Cell<u64>,
+    indexer_calls: Cell<u64>,
+    indexer_errors: Cell<u64>,
+    status_fallbacks: Cell<u64>,
+    scan_set_builds: Cell<u64>,
+    orphan_disagreements: Cell<u64>,
+    last_tick_duration: Cell<Duration>,
+}
+
+impl MonitorCounters {
+    pub(crate) fn add_news_emitted(&self, event: &NewsEvent) {
+        self.news_emitted.set(self.news_emitted.get() + 1);
+        *self
+            .news_emitted_by_kind
+            .borrow_mut()
+            .entry(event.kind)
+            .or_default() += 1;
+    }
+
+    pub(crate) fn add_news_acked(&self, count: u64) {
+        self.news_acked.set(self.news_acked.get() + count);
+    }
+
+    pub(crate) fn add_indexer_call(&self) {
+        self.indexer_calls.set(self.indexer_calls.get() + 1);
+    }
+
+    pub(crate) fn add_indexer_error(&self) {
+        self.indexer_errors.set(self.indexer_errors.get() + 1);
+    }
+
+    pub(crate) fn add_status_fallbacks(&self, count: u64) {
+        self.status_fallbacks
+            .set(self.status_fallbacks.get() + count);
+    }
+
+    pub(crate) fn add_scan_set_build(&self) {
+        self.scan_set_builds.set(self.scan_set_builds.get() + 1);
+    }
+
+    pub(crate) fn add_orphan_disagreement(&self) {
+        self.orphan_disagreements
+            .set(self.orphan_disagreements.get() + 1);
+    }
+
+    pub(crate) fn set_last_tick_duration(&self, duration: Duration) {
+        self.last_tick_duration.set(duration);
+    }
+
+    /// News written to the store by `tick`.
+    pub fn news_emitted(&self) -> u64 {
+        self.news_emitted.get()
+    }
+
+    /// News written to the store by `tick`, by the kind of [`NewsEvent`].
+    pub fn news_emitted_by_kind(&self) -> BTreeMap<&'static str, u64> {
+        self.news_emitted_by_kind.borrow().clone()
+    }
+
+    /// News acknowledged through the monitor, one by one, in batches or all at once.
+    pub fn news_acked(&self) -> u64 {
+        self.news_acked.get()
+    }
+
+    /// Calls made from the monitor to the indexer.
+    pub fn indexer_calls(&self) -> u64 {
+        self.indexer_calls.get()
+    }
+
+    /// Ticks failed on an indexer or node error.
+    pub fn indexer_errors(&self) -> u64 {
+        self.indexer_errors.get()
+    }
+
+    /// Transactions of `status_bitmap` the monitor had no record of, resolved by the indexer.
+    pub fn status_fallbacks(&self) -> u64 {
+        self.status_fallbacks.get()
+    }
+
+    /// Times the scan set of the ScriptPubKey monitors was built, see [`crate::scan_set::ScanSet`].
+    pub fn scan_set_builds(&self) -> u64 {
+        self.scan_set_builds.get()
+    }
+
+    /// Transactions the indexer flagged orphan, or not, against the chain logged by the monitor.
+    pub fn orphan_disagreements(&self) -> u64 {
+        self.orphan_disagreements.get()
+    }
+
+    pub fn last_tick_duration(&self) -> Duration {
+        self.last_tick_duration.get()
+    }
+}
+
+/// Number of items per monitor kind.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CountsByKind {
+    pub transaction: usize,
+    pub spending_utxo: usize,
+    pub rsk_pegin: usize,
+    pub new_block: usize,
+    pub script_pubkey: usize,
+    pub op_return: usize,
+}
+
+impl CountsByKind {
+    fn samples(&self) -> [(&'static str, usize); 6] {
+        [
+            ("transaction", self.transaction),
+            ("spending_utxo", self.spending_utxo),
+            ("rsk_pegin", self.rsk_pegin),
+            ("new_block", self.new_block),
+            ("script_pubkey", self.script_pubkey),
+            ("op_return", self.op_return),
+        ]
+    }
+}
+
+/// Point in time view of the monitor, see [`crate::monitor::Monitor::metrics_snapshot`].
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetricsSnapshot {
+    pub monitor_height: BlockHeight,
+    /// None when the indexer has no blocks yet.
+    pub indexer_height: Option<BlockHeight>,
+    pub active_monitors: CountsByKind,
+    pub unacked_news: CountsByKind,
+    pub last_tick_duration: Duration,
+    pub news_emitted: u64,
+    pub news_emitted_by_kind: BTreeMap<&'static str, u64>,
+    pub news_acked: u64,
+    pub indexer_calls: u64,
+    pub indexer_errors: u64,
+    pub status_fallbacks: u64,
+    /// Scan generation of the store, see [`crate::store::MonitorStoreApi::get_scan_generation`].
+    pub scan_generation: u64,
+    /// Scripts in the scan set of the monitor, 0 until the first block is processed.
+    pub scan_set_size: usize,
+    pub scan_set_builds: u64,
+    pub orphan_disagreements: u64,
+    /// Health score of the last ticks, see [`crate::health::HealthScore`].
+    pub health: HealthScore,
+    /// Compression of the values written by the store, see [`crate::types::StorageStats`].
+    pub storage: StorageStats,
+}
+
+impl MetricsSnapshot {
+    /// Renders the snapshot in the Prometheus text exposition format.
+    pub fn to_prometheus(&self) -> String {
+        let mut out = String::new();
+
+        write_metric(
+            &mut out,
+            "bitvmx_monitor_height",
+            "gauge",
+            "Last block height processed by the monitor",
+            &[("", self.monitor_height as f64)],
+        );
+
+        if let Some(indexer_height) = self.indexer_height {
+            write_metric(
+                &mut out,
+                "bitvmx_monitor_indexer_height",
+                "gauge",
+                "Best block height known by the indexer",
+                &[("", indexer_height as f64)],
+            );
+        }
+
+        write_metric(
+            &mut out,
+            "bitvmx_monitor_active_monitors",
+            "gauge",
+            "Active monitors per kind",
+            &self
+                .active_monitors
+                .samples()
+                .map(|(kind, count)| (kind, count as f64)),
+        );
+
+        write_metric(
+            &mut out,
+            "bitvmx_monitor_unacked_news",
+            "gauge",
+            "News not acknowledged yet per kind",
+            &self
+                .unacked_news
+                .samples()
+                .map(|(kind, count)| (kind, count as f64)),
+        );
+
+        write_metric(
+            &mut out,
+            "bitvmx_monitor_last_tick_duration_seconds",
+            "gauge",
+            "Duration of the last tick",
+            &[("", self.last_tick_duration.as_secs_f64())],
+        );
+
+        write_metric(
+            &mut out,
+            "bitvmx_monitor_news_emitted_total",
+            "counter",
+            "News written by tick",
+            &[("", self.news_emitted as f64)],
+        );
+
+        write_metric(
+            &mut out,
+            "bitvmx_monitor_news_emitted_by_kind_total",
+            "counter",
+            "News written by tick per kind",
+            &self
+                .news_emitted_by_kind
+                .iter()
+                .map(|(kind, count)| (*kind, *count as f64))
+                .collect::<Vec<_>>(),
+        );
+
+        write_metric(
+            &mut out,
+            "bitvmx_monitor_news_acked_total",
+            "counter",
+            "News acknowledged",
+            &[("", self.news_acked as f64)],
+        );
+
+        write_metric(
+            &mut out,
+            "bitvmx_monitor_indexer_calls_total",
+            "counter",
+            "Calls made to the indexer",
+            &[("", self.indexer_calls as f64)],
+        );
+
+        write_metric(
+            &mut out,
+            "bitvmx_monitor_indexer_errors_total",
+            "counter",
+            "Ticks failed on an indexer or node error",
+            &[("", self.indexer_errors as f64)],
+        );
+
+        write_metric(
+            &mut out,
+            "bitvmx_monitor_status_fallbacks_total",
+            "counter",
+            "Transactions of status_bitmap resolved by the indexer",
+            &[("", self.status_fallbacks as f64)],
+        );
+
+        write_metric(
+            &mut out,
+            "bitvmx_monitor_scan_generation",
+            "gauge",
+            "Generation of the ScriptPubKey monitors in the store",
+            &[("", self.scan_generation as f64)],
+        );
+
+        write_metric(
+            &mut out,
+            "bitvmx_monitor_scan_set_size",
+            "gauge",
+            "Scripts scanned in each block",
+            &[("", self.scan_set_size as f64)],
+        );
+
+        write_metric(
+            &mut out,
+            "bitvmx_monitor_scan_set_builds_total",
+            "counter",
+            "Times the scan set was built",
+            &[("", self.scan_set_builds as f64)],
+        );
+
+        write_metric(
+            &mut out,
+            "bitvmx_monitor_orphan_disagreements_total",
+            "counter",
+            "Orphan flags of the indexer the chain log disagreed with",
+            &[("", self.orphan_disagreements as f64)],
+        );
+
+        write_metric(
+            &mut out,
+            "bitvmx_monitor_health_score",
+            "gauge",
+            "Health score of the last ticks, from 0 to 100",
+            &[("", self.health.score as f64)],
+        );
+
+        write_metric(
+            &mut out,
+            "bitvmx_monitor_degraded",
+            "gauge",
+            "1 while the monitor runs in degraded mode",
+            &[(
+                "",
+                (self.health.state == HealthState::Degraded) as u8 as f64,
+            )],
+        );
+
+        write_metric(
+            &mut out,
+            "bitvmx_monitor_store_compressible_values_total",
+            "counter",
+            "Values written to the compressible keys of the store, by whether they were compressed",
+            &[
+                ("compressed", self.storage.compressed_values as f64),
+                ("uncompressed", self.storage.uncompressed_values as f64),
+            ],
+        );
+
+        write_metric(
+            &mut out,
+            "bitvmx_monitor_store_compression_ratio",
+            "gauge",
+            "Size of the compressed values over the size they were written with",
+            &[("", self.storage.compression_ratio())],
+        );
+
+        out
+    }
+}
+
+/// Writes one metric family, samples with an empty kind are written without labels.
+fn write_metric(out: &mut String, name: &str, kind: &str, help: &str, samples: &[(&str, f64)]) {
+    let _ = writeln!(out, "# HELP {name} {help}");
+    let _ = writeln!(out, "# TYPE {name} {kind}");
+
+    for (label, value) in samples {
+        if label.is_empty() {
+            let _ = writeln!(out, "{name} {value}");
+        } else {
+            let _ = writeln!(out, "{name}{{kind=\"{label}\"}} {value}");
+        }
+    }
+}
+
+#[cfg(feature = "metrics")]
+pub use exporter::MetricsExporter;
+
+#[cfg(feature = "metrics")]
+mod exporter {
+    use super::MetricsSnapshot;
+    use crate::errors::MonitorError;
+    use crate::http::{read_request, write_response, Server};
+    use crate::schema::describe_schema;
+    use std::io;
+    use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+    use std::sync::{Arc, Mutex};
+
+    /// Serves the last published [`MetricsSnapshot`] on `GET /metrics`, and the
+    /// [`crate::schema::SchemaDescription`] of this version as JSON on `GET /schema`.
+    ///
+    /// The listener thread can't reach the monitor (see [`crate::commands::MonitorHandle`]), so
+    /// the run loop publishes a snapshot after every tick with [`MetricsExporter::update`] and the
+    /// listener only serves the rendered text.
+    /// The listener stops when the exporter is dropped.
+    pub struct MetricsExporter {
+        body: Arc<Mutex<Published>>,
+        server: Server,
+    }
+
+    impl MetricsExporter {
+        pub fn start(addr: impl ToSocketAddrs) -> io::Result<Self> {
+            let body = Arc::new(Mutex::new(Published::Snapshot(String::new())));
+
+            let server_body = body.clone();
+            let server = Server::start(addr, "metrics-exporter", move |stream| {
+                serve(stream, &server_body)
+            })?;
+
+            Ok(Self { body, server })
+        }
+
+        pub fn local_addr(&self) -> SocketAddr {
+            self.server.local_addr()
+        }
+
+        /// Publishes the snapshot served on the next scrapes.
+        pub fn update(&self, snapshot: &MetricsSnapshot) {
+            if let Ok(mut body) = self.body.lock() {
+                *body = Published::Snapshot(snapshot.to_prometheus());
+            }
+        }
+
+        /// Answers the next scrapes with the HTTP status of `error`, and its retry hint as
+        /// `Retry-After`, until a snapshot is published again. A stale snapshot is not served.
+        pub fn fail(&self, error: &MonitorError) {
+            if let Ok(mut body) = self.body.lock() {
+                *body = Published::Failed {
+                    status: error.http_status(),
+                    retry_after: error.retry_after_hint().map(|wait| wait.as_secs().max(1)),
+                };
+            }
+        }
+    }
+
+    enum Published {
+        Snapshot(String),
+        Failed {
+            status: u16,
+            retry_after: Option<u64>,
+        },
+    }
+
+    fn serve(stream: TcpStream, body: &Mutex<Published>) -> io::Result<()> {
+        let request = read_request(&stream)?;
+
+        match (request.method.as_str(), request.target.as_str()) {
+            ("GET", "/metrics") => {
+                // Rendered out of the lock, the run loop publishes while the client reads
+                let published = match &*body
+                    .lock()
+                    .map_err(|_| io::Error::other("metrics lock poisoned"))?
+                {
+                    Published::Snapshot(body) => Ok(body.clone()),
+                    Published::Failed {
+                        status,
+                        retry_after,
+                    } => Err((*status, retry_after.map(|secs| secs.to_string()))),
+                };
+
+                match published {
+                    Ok(body) => write_response(
+                        stream,
+                        200,
+                        &[("Content-Type", "text/plain; version=0.0.4")],
+                        &body,
+                    ),
+                    Err((status, Some(retry_after))) => {
+                        write_response(stream, status, &[("Retry-After", &retry_after)], "")
+                    }
+                    Err((status, None)) => write_response(stream, status, &[], ""),
+                }
+            }
+            ("GET", "/schema") => {
+                let body = serde_json::to_string(&describe_schema()).map_err(io::Error::other)?;
+                write_response(stream, 200, &[("Content-Type", "application/json")], &body)
+            }
+            _ => write_response(stream, 404, &[], ""),
+        }
+    }
+}
@@ -0,0 +1,880 @@
+use crate::errors::MonitorStoreError;
+use crate::store::{BlockchainKey, ListedMonitor, MonitorKey, MonitorStore};
+use crate::types::{
+    AddressFilterStats, AlternativeNewsEntry, AlternativesMonitor, BlockWorkEntry, ChainLogEntry,
+    CompositeNewsEntry, CompositeRule, DetectionRecord, EpochNewsEntry, ExpiredMonitorNewsEntry,
+    ExpiryEntry, ExternalBlockEntry, FingerprintEntry, HealthNewsEntry, LowWorkReorgNewsEntry,
+    ManualResolutionNewsEntry, MempoolSighting, MonitorMetadataEntry, NewsAck, OpReturnNewsEntry,
+    OpReturnPrefixMonitor, ReorgImpact, ReorgNewsEntry, ReplacedTxEntry, ReplacementNewsEntry,
+    RskPeginMonitorState, RskPeginNewsEntry, ScriptPubKeyMonitor, ScriptPubKeyNewsEntry,
+    SnoozeEntry, SnoozeTarget, SpendingUTXOMonitor, SpendingUTXONewsEntry, TransactionMonitor,
+    TransactionNewsEntry, TransactionStatus, TrashEntry,
+};
+use bitcoin::{BlockHash, Txid};
+use bitvmx_bitcoin_rpc::types::BlockHeight;
+use serde_json::json;
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Deref;
+use storage_backend::storage::Storage;
+use tracing::info;
+use uuid::Uuid;
+
+/// Normalized view of everything the monitor store holds: monitors, news, heights and
+/// bookkeeping entries.
+///
+/// Every item is flattened to a path (e.g. `monitor/tx/active/<txid>/<context>`) and a JSON
+/// value, so two states compare equal regardless of how the store lays them out or in which
+/// order the items were written. Migrations prove they keep the logical state with
+/// `logical_snapshot(before) == logical_snapshot(after)`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LogicalState {
+    entries: BTreeMap<String, String>,
+}
+
+/// A path that differs between two [`LogicalState`]s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Difference {
+    /// The path only exists in the state `diff` was called on.
+    Removed { path: String, value: String },
+    /// The path only exists in the state passed to `diff`.
+    Added { path: String, value: String },
+    Changed {
+        path: String,
+        before: String,
+        after: String,
+    },
+}
+
+impl LogicalState {
+    pub fn entries(&self) -> &BTreeMap<String, String> {
+        &self.entries
+    }
+
+    pub fn get(&self, path: &str) -> Option<&str> {
+        self.entries.get(path).map(String::as_str)
+    }
+
+    /// Differences from `self` to `other`, sorted by path.
+    pub fn diff(&self, other: &LogicalState) -> Vec<Difference> {
+        let mut differences = Vec::new();
+
+        for (path, before) in &self.entries {
+            match other.entries.get(path) {
+                None => differences.push(Difference::Removed {
+                    path: path.clone(),
+                    value: before.clone(),
+                }),
+                Some(after) if after != before => differences.push(Difference::Changed {
+                    path: path.clone(),
+                    before: before.clone(),
+                    after: after.clone(),
+                }),
+                Some(_) => {}
+            }
+        }
+
+        for (path, after) in &other.entries {
+            if !self.entries.contains_key(path) {
+                differences.push(Difference::Added {
+                    path: path.clone(),
+                    value: after.clone(),
+                });
+            }
+        }
+
+        differences.sort_by(|a, b| a.path().cmp(b.path()));
+        differences
+    }
+
+    fn insert(&mut self, path: String, value: serde_json::Value) {
+        self.entries.insert(path, value.to_string());
+    }
+}
+
+impl Difference {
+    pub fn path(&self) -> &str {
+        match self {
+            Difference::Removed { path, .. }
+            | Difference::Added { path, .. }
+            | Difference::Changed { path, .. } => path,
+        }
+    }
+}
+
+/// Version of the store layout written by this version, kept under `monitor/schema_version`.
+/// Stores written before the key existed are version 0.
+pub const STORE_SCHEMA_VERSION: u32 = 4;
+
+/// Upgrade of the store from version `from` to `from + 1`, applied in one store transaction with
+/// the write of the new version.
+struct Migration<S> {
+    from: u32,
+    description: &'static str,
+    apply: fn(&MonitorStore<S>, Uuid) -> Result<(), MonitorStoreError>,
+}
+
+/// Migrations in the order they are applied, one per version.
+fn migrations<S: Deref<Target = Storage>>() -> [Migration<S>; 4] {
+    [
+        Migration {
+            from: 0,
+            description:
+                "move the lists of monitors and the new block news of 0.7 to their own keys",
+            apply: move_legacy_layouts,
+        },
+        Migration {
+            from: 1,
+            description: "store the outpoints spent by each spender as outpoints",
+            apply: spends_as_outpoints,
+        },
+        Migration {
+            from: 2,
+            description: "store each fingerprint of news and detections under its own key",
+            apply: fingerprints_by_id,
+        },
+        Migration {
+            from: 3,
+            description: "store the snapshot of each transaction under its own key",
+            apply: snapshots_by_txid,
+        },
+    ]
+}
+
+/// Brings the store to [`STORE_SCHEMA_VERSION`], applying the migrations from the version it
+/// was written with. A new store is written the current version. A store of a newer version is
+/// rejected with [`MonitorStoreError::IncompatibleSchema`] before anything is written.
+pub fn migrate<S: Deref<Target = Storage>>(
+    store: &MonitorStore<S>,
+) -> Result<(), MonitorStoreError> {
+    let key = store.get_key(MonitorKey::SchemaVersion);
+    let version: u32 = store.read_typed(&key)?.unwrap_or_default();
+
+    if version > STORE_SCHEMA_VERSION {
+        return Err(MonitorStoreError::IncompatibleSchema(
+            version,
+            STORE_SCHEMA_VERSION,
+        ));
+    }
+
+    for migration in migrations().iter().filter(|m| m.from >= version) {
+        let transaction_id = store.begin_transaction();
+
+        let result = (migration.apply)(store, transaction_id)
+            .and_then(|_| store.write_typed(&key, migration.from + 1, Some(transaction_id)));
+
+        match result {
+            Ok(()) => store.commit_transaction(transaction_id)?,
+            Err(e) => {
+                store.rollback_transaction(transaction_id)?;
+                return Err(e);
+            }
+        }
+
+        info!(
+            "Migrated the store to schema version {} | {}",
+            migration.from + 1,
+            migration.description
+        );
+    }
+
+    Ok(())
+}
+
+/// Version 0 to 1: the lists of monitors stored as a single `Vec` are moved to per-item keys and
+/// the single new block news to the queue, as they were on their first read.
+fn move_legacy_layouts<S: Deref<Target = Storage>>(
+    store: &MonitorStore<S>,
+    transaction_id: Uuid,
+) -> Result<(), MonitorStoreError> {
+    fn move_lists<M: ListedMonitor, S: Deref<Target = Storage>>(
+        store: &MonitorStore<S>,
+        list_key: fn(bool) -> MonitorKey,
+        transaction_id: Uuid,
+    ) -> Result<(), MonitorStoreError> {
+        for is_active in [true, false] {
+            let list = list_key(is_active);
+            if store
+                .read_typed::<Vec<String>>(&store.get_key(list))?
+                .is_none()
+            {
+                store.move_legacy_list::<M>(list, transaction_id)?;
+            }
+        }
+        Ok(())
+    }
+
+    move_lists::<TransactionMonitor, S>(store, MonitorKey::Transactions, transaction_id)?;
+    move_lists::<SpendingUTXOMonitor, S>(
+        store,
+        MonitorKey::SpendingUTXOTransactions,
+        transaction_id,
+    )?;
+    move_lists::<ScriptPubKeyMonitor, S>(store, MonitorKey::ScriptPubKeys, transaction_id)?;
+
+    let news_key = store.get_key(MonitorKey::NewBlockNews);
+    if store.read_typed::<serde_json::Value>(&news_key)?.is_none() {
+        store.move_legacy_block_news(transaction_id)?;
+    }
+
+    Ok(())
+}
+
+/// Version 1 to 2: the outpoints spent by each spender, stored as `(txid, vout)` tuples, are
+/// rewritten as outpoints. The spenders are the ones recorded by the SpendingUTXO monitors.
+fn spends_as_outpoints<S: Deref<Target = Storage>>(
+    store: &MonitorStore<S>,
+    transaction_id: Uuid,
+) -> Result<(), MonitorStoreError> {
+    let mut spenders = BTreeSet::new();
+    for is_active in [true, false] {
+        let monitors: Vec<SpendingUTXOMonitor> =
+            store.read_list(MonitorKey::SpendingUTXOTransactions(is_active))?;
+        for monitor in monitors {
+            spenders.extend(monitor.entries.iter().filter_map(|e| e.spender_tx_id));
+        }
+    }
+
+    for spender in spenders {
+        let key = store.get_key(MonitorKey::Spender(spender));
+        if let Some(spent) = store.read_typed::<Vec<(Txid, u32)>>(&key)? {
+            store.write_spends(spender, &spent, Some(transaction_id))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Version 2 to 3: the fingerprints of news and detections, stored as a single `Vec`, are moved
+/// to a key per id and listed under the monitor height, which they are pruned by.
+fn fingerprints_by_id<S: Deref<Target = Storage>>(
+    store: &MonitorStore<S>,
+    transaction_id: Uuid,
+) -> Result<(), MonitorStoreError> {
+    let key = store.get_key(MonitorKey::Fingerprints);
+    let Some(fingerprints) = store.read_typed::<Vec<FingerprintEntry>>(&key)? else {
+        return Ok(());
+    };
+
+    if !fingerprints.is_empty() {
+        let height: BlockHeight = store
+            .read_typed(&store.get_blockchain_key(BlockchainKey::CurrentBlockHeight))?
+            .unwrap_or_default();
+        let ids: Vec<String> = fingerprints.iter().map(|f| f.id.clone()).collect();
+
+        for fingerprint in fingerprints {
+            store.write_typed(
+                &store.fingerprint_key(&fingerprint.id),
+                FingerprintEntry {
+                    height,
+                    ..fingerprint
+                },
+                Some(transaction_id),
+            )?;
+        }
+        store.write_typed(
+            &store.get_key(MonitorKey::FingerprintsAt(height)),
+            &ids,
+            Some(transaction_id),
+        )?;
+        store.write_typed(
+            &store.get_key(MonitorKey::FingerprintHeights),
+            (height, height),
+            Some(transaction_id),
+        )?;
+    }
+
+    store.delete_key(&key, Some(transaction_id))
+}
+
+/// Version 3 to 4: the transaction snapshots, stored as a single `Vec`, are moved to a key per
+/// txid, listed so `prune` can drop them with their monitors.
+fn snapshots_by_txid<S: Deref<Target = Storage>>(
+    store: &MonitorStore<S>,
+    transaction_id: Uuid,
+) -> Result<(), MonitorStoreError> {
+    let key = store.get_key(MonitorKey::TransactionSnapshots);
+    let Some(snapshots) = store.read_typed::<Vec<TransactionStatus>>(&key)? else {
+        return Ok(());
+    };
+
+    if !snapshots.is_empty() {
+        let ids: Vec<Txid> = snapshots.iter().map(|s| s.tx_id).collect();
+
+        for snapshot in snapshots {
+            store.write_typed(
+                &store.tx_snapshot_key(&snapshot.tx_id),
+                &snapshot,
+                Some(transaction_id),
+            )?;
+        }
+        store.write_typed(
+            &store.get_key(MonitorKey::TransactionSnapshotIds),
+            &ids,
+            Some(transaction_id),
+        )?;
+    }
+
+    store.delete_key(&key, Some(transaction_id))
+}
+
+/// Reads the whole store into a [`LogicalState`].
+///
+/// Monitor settings are not persisted, the only setting kept in the store is the pending work flag.
+/// The scan generation is left out, it only tells a running monitor to rebuild its scan set, and
+/// so is the index of the outpoints spent by each spender, derived from the spending monitors.
+/// The news history is left out too, it records when each news was written and acknowledged,
+/// and so is the schema version, the same for every store once opened.
+pub fn logical_snapshot<S: Deref<Target = Storage>>(
+    store: &MonitorStore<S>,
+) -> Result<LogicalState, MonitorStoreError> {
+    let mut state = LogicalState::default();
+
+    let height: BlockHeight = store
+        .read_typed(&store.get_blockchain_key(BlockchainKey::CurrentBlockHeight))?
+        .unwrap_or_default();
+    state.insert("height".to_string(), json!(height));
+
+    let tip: Option<BlockHash> =
+        store.read_typed(&store.get_blockchain_key(BlockchainKey::CurrentBlockHash))?;
+    if let Some(tip) = tip {
+        state.insert("tip".to_string(), json!(tip));
+    }
+
+    let pending_work: bool = store
+        .read_typed(&store.get_key(MonitorKey::PendingWork))?
+        .unwrap_or_default();
+    state.insert("pending_work".to_string(), json!(pending_work));
+
+    let reload_request: bool = store
+        .read_typed(&store.get_key(MonitorKey::ReloadRequest))?
+        .unwrap_or_default();
+    if reload_request {
+        state.insert("reload_request".to_string(), json!(true));
+    }
+
+    let news_sequence: u64 = store
+        .read_typed(&store.get_key(MonitorKey::NewsSequence))?
+        .unwrap_or_default();
+    state.insert("news_sequence".to_string(), json!(news_sequence));
+
+    let news_epoch: u64 = store
+        .read_typed(&store.get_key(MonitorKey::NewsEpoch))?
+        .unwrap_or_default();
+    state.insert("news_epoch".to_string(), json!(news_epoch));
+
+    for (is_active, status) in [(true, "active"), (false, "inactive")] {
+        let txs: Vec<TransactionMonitor> = store.read_list(MonitorKey::Transactions(is_active))?;
+
+        for monitor in txs {
+            for entry in monitor.entries {
+                state.insert(
+                    format!("monitor/tx/{status}/{}/{}", monitor.tx_id, entry.extra_data),
+                    json!({
+                        "confirmation_trigger": entry.confirmation_trigger,
+                        "trigger_sent": entry.trigger_sent,
+                        "last_reported_confirmations": entry.last_reported_confirmations,
+                        "last_reported_block_hash": entry.last_reported_block_hash,
+                        "history": entry.history,
+                    }),
+                );
+            }
+        }
+
+        let utxos: Vec<SpendingUTXOMonitor> =
+            store.read_list(MonitorKey::SpendingUTXOTransactions(is_active))?;
+
+        for monitor in utxos {
+            for entry in monitor.entries {
+                state.insert(
+                    format!(
+                        "monitor/spending/{status}/{}:{}/{}",
+                        monitor.tx_id, monitor.vout, entry.extra_data
+                    ),
+                    json!({
+                        "spender_tx_id": entry.spender_tx_id,
+                        "confirmation_trigger": entry.confirmation_trigger,
+                        "deadline": entry.deadline,
+                        "history": entry.history,
+                        "backfill_done": entry.backfill_done,
+                    }),
+                );
+            }
+        }
+
+        let scripts: Vec<ScriptPubKeyMonitor> =
+            store.read_list(MonitorKey::ScriptPubKeys(is_active))?;
+
+        for monitor in scripts {
+            for entry in monitor.entries {
+                state.insert(
+                    format!(
+                        "monitor/script/{status}/{}/{}",
+                        monitor.script_pubkey.to_hex_string(),
+                        entry.extra_data
+                    ),
+                    json!({
+                        "confirmation_trigger": entry.confirmation_trigger,
+                        "filter": entry.filter,
+                        "history": entry.history,
+                    }),
+                );
+            }
+        }
+    }
+
+    let rsk_pegin: Option<RskPeginMonitorState> =
+        store.read_typed(&store.get_key(MonitorKey::RskPegin))?;
+    if let Some(rsk_pegin) = rsk_pegin {
+        state.insert(
+            "monitor/rsk_pegin".to_string(),
+            json!({
+                "status": rsk_pegin.status,
+                "confirmation_trigger": rsk_pegin.confirmation_trigger,
+                "committee": rsk_pegin.committee,
+                "packet_range": rsk_pegin.packet_range,
+                "min_amount": rsk_pegin.min_amount,
+            }),
+        );
+    }
+
+    let op_return_prefixes: Vec<OpReturnPrefixMonitor> = store
+        .read_typed(&store.get_key(MonitorKey::OpReturnPrefixes))?
+        .unwrap_or_default();
+    for monitor in op_return_prefixes {
+        state.insert(
+            format!(
+                "monitor/op_return/{}/{}",
+                hex::encode(&monitor.prefix),
+                monitor.extra_data
+            ),
+            json!({ "history": monitor.history }),
+        );
+    }
+
+    let alternatives: Vec<AlternativesMonitor> = store
+        .read_typed(&store.get_key(MonitorKey::Alternatives))?
+        .unwrap_or_default();
+    for race in alternatives {
+        let tx_ids: Vec<String> = race.tx_ids.iter().map(Txid::to_string).collect();
+        state.insert(
+            format!(
+                "monitor/alternatives/{}/{}",
+                tx_ids.join(","),
+                race.extra_data
+            ),
+            json!({ "winner": race.winner }),
+        );
+    }
+
+    let composite_rules: Vec<CompositeRule> = store
+        .read_typed(&store.get_key(MonitorKey::CompositeRules))?
+        .unwrap_or_default();
+    for rule in composite_rules {
+        state.insert(
+            format!("composite/{}", rule.id),
+            json!({
+                "all_of": rule.all_of,
+                "satisfied": rule.satisfied,
+                "emitted_at": rule.emitted_at,
+            }),
+        );
+    }
+
+    let new_block: Option<bool> = store.read_typed(&store.get_key(MonitorKey::NewBlock))?;
+    if let Some(new_block) = new_block {
+        state.insert("monitor/new_block".to_string(), json!(new_block));
+    }
+
+    let txs_news: Vec<TransactionNewsEntry> = store
+        .read_typed(&store.get_key(MonitorKey::TransactionsNews))?
+        .unwrap_or_default();
+    for entry in txs_news {
+        state.insert(
+            format!("news/tx/{}/{}", entry.tx_id, entry.extra_data),
+            ack_value(&entry.ack),
+        );
+    }
+
+    let orphaned_news: Vec<TransactionNewsEntry> = store
+        .read_typed(&store.get_key(MonitorKey::OrphanedTransactionsNews))?
+        .unwrap_or_default();
+    for entry in orphaned_news {
+        state.insert(
+            format!("news/tx/orphaned/{}/{}", entry.tx_id, entry.extra_data),
+            ack_value(&entry.ack),
+        );
+    }
+
+    let rsk_news: Vec<RskPeginNewsEntry> = store
+        .read_typed(&store.get_key(MonitorKey::RskPeginTransactionsNews))?
+        .unwrap_or_default();
+    for entry in rsk_news {
+        let mut value = ack_value(&entry.ack);
+        if let Some(data) = entry.data {
+            value["pegin"] = json!(data);
+        }
+        state.insert(format!("news/rsk_pegin/{}", entry.tx_id), value);
+    }
+
+    for (key, kind) in [
+        (MonitorKey::SpendingUTXOTransactionsNews, "spending"),
+        (
+            MonitorKey::SpendingUTXOUnconfirmedNews,
+            "spending_unconfirmed",
+        ),
+        (MonitorKey::MinorSpendNews, "minor_spend"),
+    ] {
+        let spending_news: Vec<SpendingUTXONewsEntry> =
+            store.read_typed(&store.get_key(key))?.unwrap_or_default();
+        for entry in spending_news {
+            let mut value = ack_value(&entry.ack);
+            value["spender_tx_id"] = json!(entry.spender_tx_id);
+            state.insert(
+                format!(
+                    "news/{kind}/{}:{}/{}",
+                    entry.tx_id, entry.utxo_index, entry.extra_data
+                ),
+                value,
+            );
+        }
+    }
+
+    let script_news: Vec<ScriptPubKeyNewsEntry> = store
+        .read_typed(&store.get_key(MonitorKey::ScriptPubKeyNews))?
+        .unwrap_or_default();
+    for entry in script_news {
+        let mut value = ack_value(&entry.ack);
+        value["script_pubkey"] = json!(entry.script_pubkey.to_hex_string());
+        state.insert(
+            format!(
+                "news/script/{}:{}/{}",
+                entry.tx_id, entry.vout, entry.extra_data
+            ),
+            value,
+        );
+    }
+
+    let op_return_news: Vec<OpReturnNewsEntry> = store
+        .read_typed(&store.get_key(MonitorKey::OpReturnNews))?
+        .unwrap_or_default();
+    for entry in op_return_news {
+        let mut value = ack_value(&entry.ack);
+        value["payload"] = json!(hex::encode(&entry.payload));
+        state.insert(
+            format!(
+                "news/op_return/{}/{}/{}",
+                entry.tx_id,
+                hex::encode(&entry.prefix),
+                entry.extra_data
+            ),
+            value,
+        );
+    }
+
+    let replacement_news: Vec<ReplacementNewsEntry> = store
+        .read_typed(&store.get_key(MonitorKey::ReplacementNews))?
+        .unwrap_or_default();
+    for entry in replacement_news {
+        let kind = if entry.old_confirmed {
+            "replaced_confirmed"
+        } else {
+            "replaced"
+        };
+        let mut value = ack_value(&entry.ack);
+        value["new_tx_id"] = json!(entry.new_tx_id);
+        state.insert(
+            format!("news/{kind}/{}/{}", entry.old_tx_id, entry.extra_data),
+            value,
+        );
+    }
+
+    let alternative_news: Vec<AlternativeNewsEntry> = store
+        .read_typed(&store.get_key(MonitorKey::AlternativeNews))?
+        .unwrap_or_default();
+    for entry in alternative_news {
+        let mut value = ack_value(&entry.ack);
+        value["losers"] = json!(entry.losers);
+        state.insert(
+            format!("news/alternatives/{}/{}", entry.winner, entry.extra_data),
+            value,
+        );
+    }
+
+    let expired_news: Vec<ExpiredMonitorNewsEntry> = store
+        .read_typed(&store.get_key(MonitorKey::ExpiredMonitorsNews))?
+        .unwrap_or_default();
+    for entry in expired_news {
+        let mut value = ack_value(&entry.ack);
+        value["expires_at_height"] = json!(entry.expires_at_height);
+        let target = match entry.vout {
+            Some(vout) => format!("{}:{vout}", entry.tx_id),
+            None => entry.tx_id.to_string(),
+        };
+        state.insert(format!("news/expired/{target}/{}", entry.extra_data), value);
+    }
+
+    let resolution_news: Vec<ManualResolutionNewsEntry> = store
+        .read_typed(&store.get_key(MonitorKey::ManualResolutionNews))?
+        .unwrap_or_default();
+    for entry in resolution_news {
+        let mut value = ack_value(&entry.ack);
+        value["note"] = json!(entry.note);
+        state.insert(format!("news/resolved/{}", entry.target.path()), value);
+    }
+
+    let composite_news: Vec<CompositeNewsEntry> = store
+        .read_typed(&store.get_key(MonitorKey::CompositeNews))?
+        .unwrap_or_default();
+    for entry in composite_news {
+        state.insert(
+            format!("news/composite/{}", entry.id),
+            ack_value(&entry.ack),
+        );
+    }
+
+    for entry in store.read_block_news()? {
+        let mut value = ack_value(&entry.ack);
+        value["height"] = json!(entry.height);
+        state.insert(format!("news/new_block/{}", entry.ack.block_hash), value);
+    }
+
+    let epoch_news: Option<EpochNewsEntry> =
+        store.read_typed(&store.get_key(MonitorKey::EpochNews))?;
+    if let Some(entry) = epoch_news {
+        let mut value = ack_value(&entry.ack);
+        value["old_epoch"] = json!(entry.old_epoch);
+        state.insert(format!("news/epoch/{}", entry.new_epoch), value);
+    }
+
+    let reorg_news: Option<ReorgNewsEntry> =
+        store.read_typed(&store.get_key(MonitorKey::ReorgNews))?;
+    if let Some(entry) = reorg_news {
+        let mut value = ack_value(&entry.ack);
+        value["old_tip"] = json!(entry.old_tip);
+        value["summary"] = json!(entry.summary);
+        state.insert(format!("news/reorg/{}", entry.new_tip), value);
+    }
+
+    let reorg_impact: Option<ReorgImpact> =
+        store.read_typed(&store.get_key(MonitorKey::LastReorgImpact))?;
+    if let Some(impact) = reorg_impact {
+        state.insert("reorg/last_impact".to_string(), json!(impact));
+    }
+
+    let low_work_news: Option<LowWorkReorgNewsEntry> =
+        store.read_typed(&store.get_key(MonitorKey::LowWorkReorgNews))?;
+    if let Some(entry) = low_work_news {
+        let mut value = ack_value(&entry.ack);
+        value["old_work"] = json!(entry.old_work);
+        value["new_work"] = json!(entry.new_work);
+        state.insert(format!("news/low_work_reorg/{}", entry.height), value);
+    }
+
+    let health_news: Option<HealthNewsEntry> =
+        store.read_typed(&store.get_key(MonitorKey::HealthNews))?;
+    if let Some(entry) = health_news {
+        let mut value = ack_value(&entry.ack);
+        value["score"] = json!(entry.score);
+        state.insert(format!("news/health/{:?}", entry.state), value);
+    }
+
+    let block_work: Vec<BlockWorkEntry> = store
+        .read_typed(&store.get_key(MonitorKey::BlockWork))?
+        .unwrap_or_default();
+    for entry in block_work {
+        state.insert(
+            format!("block_work/{}", entry.height),
+            json!({ "hash": entry.hash, "bits": entry.bits.map(|bits| bits.to_consensus()) }),
+        );
+    }
+
+    let external_chain: Vec<ExternalBlockEntry> = store
+        .read_typed(&store.get_key(MonitorKey::ExternalChain))?
+        .unwrap_or_default();
+    for entry in external_chain {
+        state.insert(format!("external_chain/{}", entry.height), json!(entry));
+    }
+
+    let (first, next): (u64, u64) = store
+        .read_typed(&store.get_key(MonitorKey::ChainLogBounds))?
+        .unwrap_or_default();
+    for seq in first..next {
+        let entry: Option<ChainLogEntry> =
+            store.read_typed(&store.get_key(MonitorKey::ChainLog(seq)))?;
+        if let Some(entry) = entry {
+            state.insert(format!("chain_log/{seq}"), json!(entry));
+        }
+    }
+
+    let fingerprint_heights: Option<(BlockHeight, BlockHeight)> =
+        store.read_typed(&store.get_key(MonitorKey::FingerprintHeights))?;
+    if let Some((lowest, highest)) = fingerprint_heights {
+        for height in lowest..=highest {
+            let ids: Vec<String> = store
+                .read_typed(&store.get_key(MonitorKey::FingerprintsAt(height)))?
+                .unwrap_or_default();
+            for id in ids {
+                let entry: Option<FingerprintEntry> =
+                    store.read_typed(&store.fingerprint_key(&id))?;
+                // Listed again at the height it was recorded last
+                if let Some(entry) = entry.filter(|entry| entry.height == height) {
+                    state.insert(format!("fingerprint/{}", entry.id), json!(entry.block_hash));
+                }
+            }
+        }
+    }
+
+    let expiries: Vec<ExpiryEntry> = store
+        .read_typed(&store.get_key(MonitorKey::Expiries))?
+        .unwrap_or_default();
+    for entry in expiries {
+        state.insert(format!("expiry/{}", entry.key), json!(entry.expires_at));
+    }
+
+    let replaced: Vec<ReplacedTxEntry> = store
+        .read_typed(&store.get_key(MonitorKey::ReplacedTransactions))?
+        .unwrap_or_default();
+    for mut entry in replaced {
+        entry.contexts.sort();
+        state.insert(
+            format!("replaced/{}", entry.old_tx_id),
+            json!({
+                "new_tx_id": entry.new_tx_id,
+                "contexts": entry.contexts,
+                "expires_at": entry.expires_at,
+            }),
+        );
+    }
+
+    let snoozes: Vec<SnoozeEntry> = store
+        .read_typed(&store.get_key(MonitorKey::Snoozes))?
+        .unwrap_or_default();
+    for entry in snoozes {
+        let path = format!("snooze/{}", entry.target.path());
+        let mut pending: Vec<String> = entry
+            .pending
+            .iter()
+            .map(|news| json!(news).to_string())
+            .collect();
+        pending.sort();
+        state.insert(
+            path,
+            json!({
+                "until": entry.until,
+                "pending": pending,
+            }),
+        );
+    }
+
+    let snapshot_ids: Vec<Txid> = store
+        .read_typed(&store.get_key(MonitorKey::TransactionSnapshotIds))?
+        .unwrap_or_default();
+    for tx_id in snapshot_ids {
+        let entry: Option<TransactionStatus> = store.read_typed(&store.tx_snapshot_key(&tx_id))?;
+        if let Some(entry) = entry {
+            state.insert(format!("snapshot/tx/{}", entry.tx_id), json!(entry));
+        }
+    }
+
+    let sightings: Vec<MempoolSighting> = store
+        .read_typed(&store.get_key(MonitorKey::MempoolSightings))?
+        .unwrap_or_default();
+    for entry in sightings {
+        state.insert(
+            format!("mempool_sighting/{}", entry.tx_id),
+            json!({
+                "first_seen_height": entry.first_seen_height,
+                "last_seen_height": entry.last_seen_height,
+                "last_known_fee_rate": entry.last_known_fee_rate,
+            }),
+        );
+    }
+
+    let filter_stats: Vec<AddressFilterStats> = store
+        .read_typed(&store.get_key(MonitorKey::AddressFilterStats))?
+        .unwrap_or_default();
+    for entry in filter_stats {
+        state.insert(
+            format!(
+                "address_filter_stats/{}/{}",
+                entry.script_pubkey.to_hex_string(),
+                entry.context
+            ),
+            json!({
+                "filtered_outputs": entry.filtered_outputs,
+                "filtered_value": entry.filtered_value.to_sat(),
+            }),
+        );
+    }
+
+    let archive: Vec<DetectionRecord> = store
+        .read_typed(&store.get_key(MonitorKey::DetectionArchive))?
+        .unwrap_or_default();
+    for entry in archive {
+        let outpoint = entry
+            .outpoint
+            .map(|outpoint| outpoint.to_string())
+            .unwrap_or_default();
+        state.insert(
+            format!(
+                "detection/{}/{}/{}/{}",
+                entry.kind, entry.tx_id, outpoint, entry.context
+            ),
+            json!({
+                "amount": entry.amount.map(|amount| amount.to_sat()),
+                "height": entry.height,
+                "block_hash": entry.block_hash,
+            }),
+        );
+    }
+
+    let trash: Vec<TrashEntry> = store
+        .read_typed(&store.get_key(MonitorKey::Trash))?
+        .unwrap_or_default();
+    for entry in trash {
+        state.insert(
+            format!("trash/{}", entry.id),
+            json!({
+                "deleted_at": entry.deleted_at,
+                "monitors": entry.monitors,
+                "reason": entry.reason,
+            }),
+        );
+    }
+
+    let trash_next_id: Option<u64> = store.read_typed(&store.get_key(MonitorKey::TrashNextId))?;
+    if let Some(trash_next_id) = trash_next_id {
+        state.insert("trash_next_id".to_string(), json!(trash_next_id));
+    }
+
+    let expected: Vec<SnoozeTarget> = store
+        .read_typed(&store.get_key(MonitorKey::ExpectedMonitors))?
+        .unwrap_or_default();
+    for target in expected {
+        state.insert(format!("expected/{}", json!(target)), json!(true));
+    }
+
+    let metadata: Vec<MonitorMetadataEntry> = store
+        .read_typed(&store.get_key(MonitorKey::MonitorMetadata))?
+        .unwrap_or_default();
+    for entry in metadata {
+        state.insert(
+            format!("metadata/{}", json!(entry.target)),
+            json!(entry.metadata),
+        );
+    }
+
+    Ok(state)
+}
+
+fn ack_value(ack: &NewsAck) -> serde_json::Value {
+    let mut value = json!({
+        "block_hash": ack.block_hash,
+        "acknowledged": ack.acknowledged,
+        "sequence": ack.sequence,
+    });
+    if let Some(metadata) = &ack.metadata {
+        value["metadata"] = json!(metadata);
+    }
+    value
+}
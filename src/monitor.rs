@@ -1,26 +1,145 @@
-use crate::config::{MonitorSettings, MonitorSettingsConfig};
+use crate::builder::{validate_confirmation_trigger, validate_context, validate_metadata};
+use crate::chain_log::{canonical_hash_at, chain_log, verify_chain_log};
+use crate::commands::{BlockTrigger, Command, CommandQueue, MonitorHandle};
+use crate::config::{MonitorConfig, MonitorSettings, MonitorSettingsConfig, SettingsUpdate};
 use crate::errors::MonitorError;
-use crate::helper::{is_a_pegin_tx, is_spending_output};
+use crate::export::{detection_record, export_detections_csv};
+use crate::headers::BlockHeaderApi;
+use crate::health::{HealthScore, HealthTracker};
+use crate::helper::{find_op_return_with_prefix, is_spending_output, parse_pegin_data};
+use crate::mempool::MempoolApi;
+use crate::metrics::{CountsByKind, MetricsSnapshot, MonitorCounters};
+use crate::news_event::NewsEvent;
+use crate::preflight::NodeApi;
+use crate::progress::context_progress;
+use crate::redaction;
+use crate::scan_set::{ScanSet, ScannedOutput};
+use crate::shutdown::ShutdownToken;
 use crate::store::{MonitorStore, MonitorStoreApi, MonitoredTypes, TypesToMonitorStore};
 use crate::types::{
-    AckMonitorNews, MonitorNews, TransactionBlockchainStatus, TransactionStatus, TypesToMonitor,
+    AckActor, AckMetadata, AckMonitorNews, AddressFilterStats, CancelNewsPolicy,
+    DetectionDescriptor, HealthState, MonitorEvent, MonitorInfo, MonitorNews, MovedTransaction,
+    NewsAck, NewsCursor, NewsEnvelope, NewsPage, OutputFilter, PruneReport, RegistrationOptions,
+    ReopenedNews, ReorgImpact, Resolution, RskPeginData, SnoozeEntry, SnoozeTarget, SpendChange,
+    SpendValueGate, StaleTx, TransactionBlockchainStatus, TransactionRef, TransactionStatus,
+    TrashEntry, TrashSelector, TxState, TypesToMonitor,
+};
+use crate::types::{
+    BlockSource, BlockWorkEntry, ChainLogEntry, ChainLogVerification, CompositeRule,
+    ContextProgress, DeactivationReason, ExternalBlockEntry, MonitorContext, MonitorKind,
+    NewsHistoryEntry, NewsPredicate, TickProgress, TickReport,
+};
+use bitcoin::{
+    hashes::Hash, Amount, BlockHash, CompactTarget, OutPoint, Script, ScriptBuf, Transaction, Txid,
 };
-use bitcoin::Txid;
 use bitcoin_indexer::indexer::Indexer;
 use bitcoin_indexer::indexer::IndexerApi;
 use bitcoin_indexer::store::IndexerStore;
-use bitcoin_indexer::types::FullBlock;
+use bitcoin_indexer::types::{FullBlock, TransactionInfo};
 use bitcoin_indexer::IndexerType;
 use bitvmx_bitcoin_rpc::bitcoin_client::BitcoinClient;
 use bitvmx_bitcoin_rpc::rpc_config::RpcConfig;
 use bitvmx_bitcoin_rpc::types::BlockHeight;
 use mockall::automock;
+use std::cell::{Cell, RefCell};
+use std::collections::{HashMap, HashSet};
+use std::io;
 use std::rc::Rc;
+use std::sync::{mpsc, Arc};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use storage_backend::storage::Storage;
-use tracing::{debug, info};
+use tracing::{debug, debug_span, info, info_span, warn};
+
+pub(crate) const INTERNAL_RSK_PEGIN: &str = "INTERNAL_RSK_PEGIN";
+pub(crate) const INTERNAL_SPENDING_UTXO: &str = "INTERNAL_SPENDING_UTXO";
+pub(crate) const INTERNAL_SCRIPT_PUBKEY: &str = "INTERNAL_SCRIPT_PUBKEY";
+pub(crate) const INTERNAL_MINOR_SPEND: &str = "INTERNAL_MINOR_SPEND";
+pub(crate) const INTERNAL_UNREPORTED_SPEND: &str = "INTERNAL_UNREPORTED_SPEND";
+
+/// Stored items read at a time by [`Monitor::for_each_monitor`], so a tick never holds more than
+/// this many targets of a kind.
+const MONITOR_PAGE_ITEMS: usize = 1_000;
+
+/// Contexts of the transaction monitors that could be reported from the mempool, by txid.
+type MempoolCandidates = HashMap<Txid, Vec<MonitorContext>>;
+
+/// Builds the context of the transaction monitor of the spender found by a spending UTXO monitor
+pub(crate) fn build_spending_utxo_context(
+    target_tx_id: Txid,
+    target_utxo_index: u32,
+    extra_data: &str,
+) -> MonitorContext {
+    format!(
+        "{}:{}:{}:{}",
+        INTERNAL_SPENDING_UTXO, target_tx_id, target_utxo_index, extra_data
+    )
+    .into()
+}
 
-const INTERNAL_RSK_PEGIN: &str = "INTERNAL_RSK_PEGIN";
-const INTERNAL_SPENDING_UTXO: &str = "INTERNAL_SPENDING_UTXO";
+/// Builds the context of the transaction monitor of a spend moving less than the
+/// [`crate::types::SpendValueGate`] of its SpendingUTXO monitor, reported as a minor spend or not
+/// at all.
+pub(crate) fn build_minor_spend_context(
+    target_tx_id: Txid,
+    target_utxo_index: u32,
+    extra_data: &str,
+    report_minor: bool,
+) -> MonitorContext {
+    let prefix = if report_minor {
+        INTERNAL_MINOR_SPEND
+    } else {
+        INTERNAL_UNREPORTED_SPEND
+    };
+
+    format!(
+        "{}:{}:{}:{}",
+        prefix, target_tx_id, target_utxo_index, extra_data
+    )
+    .into()
+}
+
+/// Whether `extra_data` is the context of a transaction monitor created by the monitor itself,
+/// for a pegin, a spend or an output paying to a monitored script.
+pub(crate) fn is_internal_context(extra_data: &str) -> bool {
+    [
+        INTERNAL_RSK_PEGIN,
+        INTERNAL_SPENDING_UTXO,
+        INTERNAL_SCRIPT_PUBKEY,
+        INTERNAL_MINOR_SPEND,
+        INTERNAL_UNREPORTED_SPEND,
+    ]
+    .iter()
+    .any(|prefix| extra_data.starts_with(prefix))
+}
+
+/// Transactions whose status a news was decided on.
+fn news_tx_ids(news: &MonitoredTypes) -> impl Iterator<Item = Txid> {
+    let tx_ids = match news {
+        MonitoredTypes::Transaction(tx_id, _)
+        | MonitoredTypes::RskPeginTransaction(tx_id, _)
+        | MonitoredTypes::OrphanedTransaction(tx_id, _)
+        | MonitoredTypes::MonitorExpired(tx_id, ..) => vec![*tx_id],
+        MonitoredTypes::SpendingUTXOTransaction(tx_id, _, _, spender)
+        | MonitoredTypes::SpendingUTXOUnconfirmed(tx_id, _, _, spender)
+        | MonitoredTypes::MinorSpend(tx_id, _, _, spender) => vec![*tx_id, *spender],
+        MonitoredTypes::MonitorReplaced(old, new, _)
+        | MonitoredTypes::ReplacedTransactionConfirmed(old, new, _) => vec![*old, *new],
+        MonitoredTypes::ScriptPubKeyTransaction(_, _, tx_id, _)
+        | MonitoredTypes::OpReturnTransaction(_, _, tx_id, _) => vec![*tx_id],
+        MonitoredTypes::AlternativeResolved(winner, losers, _) => std::iter::once(*winner)
+            .chain(losers.iter().copied())
+            .collect(),
+        MonitoredTypes::NewBlock(..)
+        | MonitoredTypes::SequenceEpochChanged(..)
+        | MonitoredTypes::Reorg(..)
+        | MonitoredTypes::LowWorkReorg(..)
+        | MonitoredTypes::HealthStateChanged(..)
+        | MonitoredTypes::ManuallyResolved(..)
+        | MonitoredTypes::Composite(_) => vec![],
+    };
+
+    tx_ids.into_iter()
+}
 
 pub struct Monitor<I, B>
 where
@@ -30,9 +149,73 @@ where
     pub indexer: I,
     pub store: B,
     pub settings: MonitorSettings,
+    pub counters: MonitorCounters,
+    /// Only needed by SpendingUTXO monitors with a deadline, see [`crate::types::SpendDeadline`].
+    pub mempool: Option<Box<dyn MempoolApi + Send>>,
+    /// Source of the block bits used to compare the work of reorg branches, see [`MonitorNews::LowWorkReorg`].
+    pub headers: Option<Box<dyn BlockHeaderApi + Send>>,
+    /// Node whose network is recorded in the store and checked by [`Monitor::preflight`].
+    pub node: Option<Box<dyn NodeApi + Send>>,
+    /// Monitor height of the last sweep of expired keys, None until the first tick.
+    last_sweep_height: Cell<Option<BlockHeight>>,
+    /// Monitor height of the last automatic prune, None until the first one.
+    last_prune_height: Cell<Option<BlockHeight>>,
+    /// Whether the news sequence was checked for a store restore since startup.
+    news_sequence_checked: Cell<bool>,
+    /// Whether the network of the node was recorded in the store since startup.
+    network_recorded: Cell<bool>,
+    /// Mutations queued by the [`MonitorHandle`]s of the monitor.
+    commands: CommandQueue,
+    /// Senders of the receivers returned by [`Monitor::news_receiver`].
+    news_senders: RefCell<Vec<mpsc::Sender<MonitorNews>>>,
+    /// News written since the last tick, only kept while there are news receivers.
+    unpublished_news: RefCell<Vec<MonitoredTypes>>,
+    /// Indexer lookups since the last tick, see [`Monitor::get_indexed_tx`]. Boxed, as most
+    /// lookups of a large monitor set find nothing and then only take a pointer.
+    tx_cache: RefCell<HashMap<Txid, Option<Box<TransactionInfo>>>>,
+    /// Best height of the indexer in the current tick, the blocks below it are being caught up.
+    indexer_best_height: Cell<BlockHeight>,
+    /// First unexpected detection of the current tick, only looked for in strict mode.
+    unexpected_detection: RefCell<Option<DetectionDescriptor>>,
+    /// Scan set of the ScriptPubKey monitors, None until the first block is processed.
+    scan_set: RefCell<Option<ScanSet>>,
+    /// Failures and durations of the last ticks, see [`Monitor::health`].
+    health: HealthTracker,
+    /// Block being applied with [`Monitor::apply_block`], None otherwise.
+    applied_block: RefCell<Option<AppliedBlock>>,
+    /// Last block processed, or being processed. Its news are recorded in the news history at
+    /// its height, before the monitor moves to it.
+    processed_block: Cell<Option<ProcessedBlock>>,
+    /// Hash at each height of the chain logged by the monitor, looked up since the last tick or
+    /// block, see [`Monitor::logged_block_at`].
+    logged_chain: RefCell<HashMap<BlockHeight, Option<BlockHash>>>,
+    /// Transactions of the current tick whose orphan flag the chain log disagreed with.
+    orphan_disputes: RefCell<HashSet<Txid>>,
+    /// Monitors deactivated by the current tick, reported by its summary event.
+    tick_deactivations: Cell<u32>,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct ProcessedBlock {
+    hash: BlockHash,
+    height: BlockHeight,
+    prev_hash: BlockHash,
+}
+
+/// Block being applied in the `External` block source, with the chain it extends.
+struct AppliedBlock {
+    /// The blocks kept, the applied one last
+    chain: Vec<ExternalBlockEntry>,
+    txs: HashMap<Txid, Transaction>,
 }
 
-impl Monitor<IndexerType, MonitorStore> {
+impl Monitor<IndexerType, MonitorStore<Rc<Storage>>> {
+    /// Builds a monitor on the node of `rpc_config`.
+    ///
+    /// This monitor is not `Send`: the indexer of the pinned `bitcoin-indexer` holds the storage
+    /// and its own store as `Rc`, so it has to stay on the thread that built it until a release
+    /// of the indexer holds them as `Arc`. Only a monitor on the default `MonitorStore` with a
+    /// `Send` indexer can be moved to another thread.
     pub fn new_with_paths(
         rpc_config: &RpcConfig,
         storage: Rc<Storage>,
@@ -40,6 +223,9 @@ impl Monitor<IndexerType, MonitorStore> {
     ) -> Result<Self, MonitorError> {
         let settings = MonitorSettings::from(settings.unwrap_or_default());
         let bitcoin_client = BitcoinClient::new_from_config(rpc_config)?;
+        let mempool_client = BitcoinClient::new_from_config(rpc_config)?;
+        let headers_client = BitcoinClient::new_from_config(rpc_config)?;
+        let node_client = BitcoinClient::new_from_config(rpc_config)?;
         let indexer_store = IndexerStore::new(storage.clone())
             .map_err(|e| MonitorError::UnexpectedError(e.to_string()))?;
         let indexer = Indexer::new(
@@ -48,7 +234,10 @@ impl Monitor<IndexerType, MonitorStore> {
             settings.indexer_settings.clone(),
         )?;
         let bitvmx_store = MonitorStore::new(storage)?;
-        let monitor = Monitor::new(indexer, bitvmx_store, settings)?;
+        let monitor = Monitor::new(indexer, bitvmx_store, settings)?
+            .with_mempool(Box::new(mempool_client))
+            .with_headers(Box::new(headers_client))
+            .with_node(Box::new(node_client));
 
         Ok(monitor)
     }
@@ -72,11 +261,48 @@ pub trait MonitorApi {
     /// - Detects new transactions that need to be monitored
     /// - Triggers the indexer to continue syncing if needed
     ///
+    /// Monitors are evaluated in a canonical order on each block, by [`MonitorKind`](crate::types::MonitorKind), then by
+    /// target (txid, outpoint, script or prefix) and context, whatever the order they were
+    /// registered in. The news of a block get their sequences in that order, so the same
+    /// monitors on the same chain always produce the same news sequence.
+    ///
     /// # Returns
     /// - `Ok(())`: If the tick completed successfully
+    /// - `Err(MonitorError::BlockSourceMismatch)`: If the blocks are applied by the caller
     /// - `Err`: If there was an error during processing
     fn tick(&self) -> Result<(), MonitorError>;
 
+    /// Runs the monitors on a block of the pipeline of the caller, instead of the indexer
+    /// driven by `tick`. Requires the `External` block source.
+    ///
+    /// # Arguments
+    /// * `block` - The block after the last one applied, any block for the first one
+    ///
+    /// # Returns
+    /// - `Ok(TickReport)`: The block applied and the news it wrote. Applying the last block again
+    ///   does nothing
+    /// - `Err(MonitorError::ExternalChainMismatch)`: If the block doesn't extend the last one applied
+    /// - `Err`: If there was an error during processing, the block can be applied again
+    fn apply_block(&self, block: &FullBlock) -> Result<TickReport, MonitorError>;
+
+    /// Replaces the last blocks applied with the first block of another branch. The rest of
+    /// the branch is applied with `apply_block`.
+    ///
+    /// # Arguments
+    /// * `orphaned` - Hashes of the last blocks applied that left the chain
+    /// * `new_tip` - The block of the new branch built on the block before them
+    ///
+    /// # Returns
+    /// - `Ok(TickReport)`: The block applied, the news it wrote and the impact of the reorg
+    /// - `Err(MonitorError::ExternalChainMismatch)`: If an orphaned block is not among the last
+    ///   blocks applied, or the new tip doesn't extend the block before them
+    /// - `Err`: If there was an error during processing
+    fn apply_reorg(
+        &self,
+        orphaned: &[BlockHash],
+        new_tip: &FullBlock,
+    ) -> Result<TickReport, MonitorError>;
+
     /// Gets the current block height that the monitor has processed.
     ///
     /// # Returns
@@ -109,13 +335,34 @@ pub trait MonitorApi {
     ///   - RskPeginTransaction: Monitor RSK pegin transactions
     ///   - SpendingUTXOTransaction: Monitor transactions spending a specific UTXO
     ///   - NewBlock: Monitor new blocks
+    ///   - ScriptPubKey: Monitor outputs paying to a script
+    ///   - OpReturnPrefix: Monitor transactions tagged with an OP_RETURN prefix
+    ///   - Alternatives: Monitor mutually exclusive transactions until one of them confirms
     ///
     /// # Returns
     /// - `Ok(())`: If monitoring was set up successfully
     /// - `Err`: If there was an error setting up monitoring
     fn monitor(&self, data: TypesToMonitor) -> Result<(), MonitorError>;
 
-    /// Cancels monitoring for a specific type of monitoring.
+    /// Starts monitoring several items at once.
+    ///
+    /// Each item is validated independently, so an invalid item does not prevent
+    /// the valid ones from being registered.
+    ///
+    /// # Arguments
+    /// * `items` - The monitors to register, see [`MonitorApi::monitor`]
+    ///
+    /// # Returns
+    /// - `Ok(Vec<Result<(), MonitorError>>)`: The outcome of every item, in input order
+    /// - `Err`: If there was an error writing to the store
+    fn monitor_batch(
+        &self,
+        items: Vec<TypesToMonitor>,
+    ) -> Result<Vec<Result<(), MonitorError>>, MonitorError>;
+
+    /// Cancels monitoring for a specific type of monitoring. Its unacked news are kept until
+    /// acknowledged, see `cancel_with_policy` to drop or acknowledge them. The monitor is kept
+    /// in the trash with its state, see `restore_from_trash`.
     ///
     /// # Arguments
     /// * `data` - The type of monitoring to cancel, which can be:
@@ -123,12 +370,221 @@ pub trait MonitorApi {
     ///   - RskPeginTransaction: Monitor RSK pegin transactions
     ///   - SpendingUTXOTransaction: Monitor transactions spending a specific UTXO
     ///   - NewBlock: Monitor new blocks
+    ///   - ScriptPubKey: Monitor outputs paying to a script
+    ///   - OpReturnPrefix: Monitor transactions tagged with an OP_RETURN prefix
+    ///   - Alternatives: Monitor mutually exclusive transactions until one of them confirms
     ///
     /// # Returns
     /// - `Ok(())`: If monitoring was canceled successfully
     /// - `Err`: If there was an error canceling monitoring
     fn cancel(&self, data: TypesToMonitor) -> Result<(), MonitorError>;
 
+    /// Cancels monitoring like `cancel`, settling the unacked news of the monitor with `policy`
+    /// in the same store transaction as the removal of the monitor.
+    ///
+    /// # Arguments
+    /// * `data` - The type of monitoring to cancel, as for `cancel`
+    /// * `policy` - Whether the news of the monitor are kept, dropped or acknowledged
+    ///
+    /// # Returns
+    /// - `Ok(())`: If monitoring was canceled and its news settled
+    /// - `Err`: If there was an error writing to the store, nothing is changed then
+    fn cancel_with_policy(
+        &self,
+        data: TypesToMonitor,
+        policy: CancelNewsPolicy,
+    ) -> Result<(), MonitorError>;
+
+    /// Cancels monitoring like `cancel` without keeping the monitor in the trash, so it can't
+    /// be restored.
+    ///
+    /// # Arguments
+    /// * `data` - The type of monitoring to cancel, as for `cancel`
+    ///
+    /// # Returns
+    /// - `Ok(())`: If monitoring was canceled successfully
+    /// - `Err`: If there was an error canceling monitoring
+    fn cancel_permanent(&self, data: TypesToMonitor) -> Result<(), MonitorError>;
+
+    /// Closes a monitor whose event an operator confirmed out of band, e.g. on a block explorer
+    /// during an indexer outage, so the automation waiting on it proceeds.
+    ///
+    /// The monitor is moved to the trash with `CancelReason::ManuallyResolved` and its pending
+    /// news are acknowledged, their `NewsAck::metadata` keeping `note`. A
+    /// `MonitorNews::ManuallyResolved` is sent per monitored item as a record of the resolution.
+    /// Later ticks send no news for it, even if the transaction is found.
+    ///
+    /// # Arguments
+    /// * `target` - The monitor to resolve, as registered
+    /// * `note` - Why the monitor was resolved, e.g. where the event was seen
+    ///
+    /// # Returns
+    /// - `Ok(())`: If the monitor was resolved
+    /// - `Err(MonitorError::InvalidMonitor)`: If there is no such monitor, active or not
+    /// - `Err`: If there was an error writing to the store, nothing is changed then
+    fn resolve_manually(&self, target: TypesToMonitor, note: String) -> Result<(), MonitorError>;
+
+    /// Lists the monitors canceled within the last `retention.trash_blocks` blocks.
+    ///
+    /// # Returns
+    /// - `Ok(Vec<TrashEntry>)`: The canceled monitors with their state, oldest first
+    /// - `Err`: If there was an error reading the store
+    fn list_trash(&self) -> Result<Vec<TrashEntry>, MonitorError>;
+
+    /// Brings back canceled monitors with the state they had when canceled (context, triggers,
+    /// spender found). A monitor registered again since replaces its entry.
+    ///
+    /// # Arguments
+    /// * `selector` - The trash entries to restore, by id, by context or all of them
+    ///
+    /// # Returns
+    /// - `Ok(Vec<TrashEntry>)`: The restored trash entries, empty if none matched
+    /// - `Err`: If there was an error writing to the store, nothing is changed then
+    fn restore_from_trash(&self, selector: TrashSelector) -> Result<Vec<TrashEntry>, MonitorError>;
+
+    /// Removes from the store the inactive Transaction and SpendingUTXO monitors with no event
+    /// since `older_than_height`, and the acknowledged news written before it. Runs every
+    /// `auto_prune_interval` blocks when set.
+    ///
+    /// # Arguments
+    /// * `older_than_height` - Monitor height below which inactive monitors and acked news go
+    ///
+    /// # Returns
+    /// - `Ok(PruneReport)`: How many monitor entries and news were removed
+    /// - `Err`: If there was an error writing to the store, nothing is changed then
+    fn prune(&self, older_than_height: BlockHeight) -> Result<PruneReport, MonitorError>;
+
+    /// Lists the monitors being tracked.
+    ///
+    /// # Arguments
+    /// * `include_inactive` - Also lists the monitors deactivated after `max_monitoring_confirmations`
+    ///
+    /// # Returns
+    /// - `Ok(Vec<MonitorInfo>)`: The active monitors followed by the inactive ones, one per context,
+    ///   with the spender found by each SpendingUTXOTransaction monitor
+    /// - `Err`: If there was an error reading the store
+    fn get_monitors(&self, include_inactive: bool) -> Result<Vec<MonitorInfo>, MonitorError>;
+
+    /// Returns the last events of a monitor: registered, found in a block, confirmation
+    /// milestones, orphaned and deactivated.
+    ///
+    /// # Arguments
+    /// * `monitor` - The monitor, as listed by `get_monitors`, matched by kind, target and context
+    ///
+    /// # Returns
+    /// - `Ok(Vec<MonitorEvent>)`: Up to `MONITOR_HISTORY_LEN` events, oldest first, empty for the
+    ///   new block and pegin monitors or an unknown monitor
+    /// - `Err`: If there was an error reading the store
+    fn monitor_history(
+        &self,
+        monitor: &TypesToMonitorStore,
+    ) -> Result<Vec<MonitorEvent>, MonitorError>;
+
+    /// Returns the monitored outpoints a transaction spent, e.g. to start an investigation from a
+    /// suspicious spender.
+    ///
+    /// # Arguments
+    /// * `spender` - The txid of the spending transaction
+    ///
+    /// # Returns
+    /// - `Ok(Vec<(Txid, u32)>)`: The outpoints of the SpendingUTXOTransaction monitors, active or
+    ///   inactive, whose spender is `spender`, in the order the spends were found. A spend replaced
+    ///   by a reorg or a canceled monitor is no longer listed
+    /// - `Err`: If there was an error reading the store
+    fn outpoints_spent_by(&self, spender: &Txid) -> Result<Vec<(Txid, u32)>, MonitorError>;
+
+    /// Returns the active monitors referencing a transaction, without listing every monitor.
+    ///
+    /// # Arguments
+    /// * `tx_id` - The txid looked up
+    ///
+    /// # Returns
+    /// - `Ok(Vec<TypesToMonitorStore>)`: The Transaction monitors of `tx_id`, then the
+    ///   SpendingUTXOTransaction monitors of its outputs, then the SpendingUTXOTransaction
+    ///   monitors whose recorded spender is `tx_id`, one per context
+    /// - `Err`: If there was an error reading the store
+    fn find_monitors_for_tx(&self, tx_id: &Txid) -> Result<Vec<TypesToMonitorStore>, MonitorError>;
+
+    /// Reactivates a monitor deactivated once its transaction reached `max_monitoring_confirmations`.
+    ///
+    /// The monitor is moved back to the active list with its context, confirmation trigger and
+    /// trigger state, and is processed again from the next tick. OP_RETURN prefix monitors are
+    /// removed when deactivated and can't be reactivated.
+    ///
+    /// # Arguments
+    /// * `data` - The deactivated monitor, matched by its target and context
+    ///
+    /// # Returns
+    /// - `Ok(())`: If the monitor was reactivated or was not deactivated
+    /// - `Err`: If there was an error writing to the store
+    fn reactivate(&self, data: TypesToMonitor) -> Result<(), MonitorError>;
+
+    /// Moves a transaction monitor to the transaction replacing it (e.g. an RBF fee bump).
+    ///
+    /// Context, trigger and trigger state of every entry are moved to `new`, the `old` monitor is
+    /// canceled and its unacked news purged, and a `MonitorNews::MonitorReplaced` is sent per context.
+    /// If `old` confirms anyway within `max_monitoring_confirmations` blocks, a
+    /// `MonitorNews::ReplacedTransactionConfirmed` is sent.
+    ///
+    /// # Arguments
+    /// * `old` - The transaction being replaced, it must have an active monitor
+    /// * `new` - The replacement transaction
+    ///
+    /// # Returns
+    /// - `Ok(())`: If the monitor was moved
+    /// - `Err`: If `old` is not monitored or there was an error writing to the store
+    fn replace_monitored_tx(&self, old: Txid, new: Txid) -> Result<(), MonitorError>;
+
+    /// Mutes the news of a monitor for the next `blocks` blocks.
+    ///
+    /// The monitor keeps tracking while snoozed. Once the monitor height passes the snooze,
+    /// the latest news held back for each item is sent.
+    ///
+    /// # Arguments
+    /// * `target` - The monitor to snooze, it must be active
+    /// * `blocks` - Number of blocks to snooze the monitor for
+    ///
+    /// # Returns
+    /// - `Ok(())`: If the monitor was snoozed
+    /// - `Err`: If the monitor is not active or there was an error writing to the store
+    fn snooze(&self, target: TypesToMonitor, blocks: u32) -> Result<(), MonitorError>;
+
+    /// Gets the snoozed monitors, with the height they are snoozed until.
+    fn get_snoozes(&self) -> Result<Vec<SnoozeEntry>, MonitorError>;
+
+    /// Gets the report of the last reorg that changed monitored items, see `MonitorNews::Reorg`.
+    fn last_reorg_impact(&self) -> Result<Option<ReorgImpact>, MonitorError>;
+
+    /// Registers a composite rule: a `MonitorNews::Composite(id)` is sent once every predicate of
+    /// `all_of` is satisfied at the same block. Until then a reorg undoing a predicate resets it,
+    /// once sent the rule is no longer evaluated.
+    ///
+    /// # Arguments
+    /// * `id` - The id of the rule, sent in its news
+    /// * `all_of` - The predicates to satisfy, on the transactions found by the monitor
+    ///
+    /// # Returns
+    /// - `Ok(())`: If the rule was registered, it is evaluated from the next block
+    /// - `Err(MonitorError::InvalidMonitor)`: If `id` is already registered or `all_of` is empty
+    /// - `Err`: If there was an error writing to the store
+    fn register_composite(
+        &self,
+        id: String,
+        all_of: Vec<NewsPredicate>,
+    ) -> Result<(), MonitorError>;
+
+    /// Cancels a composite rule, its news is not sent if it was not yet. A news already sent stays
+    /// until acknowledged.
+    ///
+    /// # Returns
+    /// - `Ok(())`: If the rule was removed
+    /// - `Err(MonitorError::InvalidMonitor)`: If there is no rule with `id`
+    /// - `Err`: If there was an error writing to the store
+    fn cancel_composite(&self, id: &str) -> Result<(), MonitorError>;
+
+    /// Gets the composite rules with the state of their predicates, in registration order.
+    fn get_composites(&self) -> Result<Vec<CompositeRule>, MonitorError>;
+
     /// Gets status updates for monitored transactions.
     ///
     /// Returns updates for transactions that have had status changes, such as:
@@ -141,6 +597,44 @@ pub trait MonitorApi {
     /// - `Err`: If there was an error retrieving updates
     fn get_news(&self) -> Result<Vec<MonitorNews>, MonitorError>;
 
+    /// Checks whether there are pending news, without building them.
+    ///
+    /// Only the acknowledgment flags of the stored news are read, so it is cheap enough to be
+    /// polled on every tick, calling `get_news` when it returns true.
+    ///
+    /// # Returns
+    /// - `Ok(bool)`: True if there is at least one news not acknowledged yet
+    /// - `Err`: If there was an error reading the store
+    fn has_news(&self) -> Result<bool, MonitorError>;
+
+    /// Counts the pending news, without building them.
+    ///
+    /// # Returns
+    /// - `Ok(usize)`: The number of news `get_news` would return
+    /// - `Err`: If there was an error reading the store
+    fn news_count(&self) -> Result<usize, MonitorError>;
+
+    /// Same as `get_news`, each news comes with the `Resolution` used to build its status.
+    fn get_news_envelopes(&self) -> Result<Vec<NewsEnvelope>, MonitorError>;
+
+    /// Gets up to `limit` pending news after the `after` cursor, to drain a large backlog in chunks.
+    ///
+    /// News are ordered by sequence, so the pages are stable while no news is emitted, and
+    /// acknowledging news doesn't move the ones not read yet to a page already read.
+    ///
+    /// # Arguments
+    /// * `after` - The `next` cursor of the previous page, None for the first page
+    /// * `limit` - Maximum number of news in the page
+    ///
+    /// # Returns
+    /// - `Ok(NewsPage)`: The news of the page and the cursor of the next one, None after the last page
+    /// - `Err`: If there was an error retrieving the news
+    fn get_news_page(
+        &self,
+        after: Option<NewsCursor>,
+        limit: usize,
+    ) -> Result<NewsPage, MonitorError>;
+
     /// Acknowledges that a transaction status update has been processed.
     ///
     /// After processing a status update from get_news(), this method should be called
@@ -158,6 +652,49 @@ pub trait MonitorApi {
     /// - `Err`: If there was an error processing the acknowledgment
     fn ack_news(&self, data: AckMonitorNews) -> Result<(), MonitorError>;
 
+    /// Acknowledges several news at once, reading and writing each news key a single time.
+    ///
+    /// One news that can't be acknowledged doesn't stop the others: every other item of the
+    /// batch is still acknowledged.
+    ///
+    /// # Arguments
+    /// * `items` - The news to acknowledge, as they would be passed to `ack_news`
+    ///
+    /// # Returns
+    /// - `Ok(())`: If every item was acknowledged
+    /// - `Err(MonitorError::NewsNotAcked)`: With the items matching no news and the ones held
+    ///   by a low work reorg, after acknowledging the rest
+    /// - `Err`: If there was an error writing the acknowledgments, none of them is kept
+    fn ack_news_batch(&self, items: Vec<AckMonitorNews>) -> Result<(), MonitorError>;
+
+    /// Acknowledges every pending news at once, e.g. to discard the backlog after reprocessing
+    /// the state from scratch.
+    ///
+    /// Only the acknowledgment flags are flipped: the stored news are kept, so the same news is
+    /// not written again by the next ticks.
+    ///
+    /// # Returns
+    /// - `Ok(usize)`: The number of news acknowledged
+    /// - `Err`: If there was an error writing the acknowledgments, none of them is kept
+    fn ack_all_news(&self) -> Result<usize, MonitorError>;
+
+    /// Gets the news history: every news written, with the block it was written for and when it
+    /// was written and acknowledged. Acknowledged news stay in the history until they are older
+    /// than `retention.news_history_blocks`.
+    ///
+    /// # Arguments
+    /// * `from_seq` - First position in the news sequence listed
+    /// * `limit` - Maximum number of entries listed
+    ///
+    /// # Returns
+    /// - `Ok(Vec<NewsHistoryEntry>)`: The news in sequence order
+    /// - `Err`: If there was an error reading the store
+    fn get_news_history(
+        &self,
+        from_seq: u64,
+        limit: usize,
+    ) -> Result<Vec<NewsHistoryEntry>, MonitorError>;
+
     /// Gets the current status of a specific transaction.
     ///
     /// # Arguments
@@ -169,14 +706,139 @@ pub trait MonitorApi {
     /// - `Err`: If there was an error retrieving the status
     fn get_tx_status(&self, tx_id: &Txid) -> Result<TransactionStatus, MonitorError>;
 
+    /// Gets the current status of several transactions.
+    ///
+    /// # Arguments
+    /// * `tx_ids` - Hashes of the transactions to check
+    ///
+    /// # Returns
+    /// - `Ok(Vec<(Txid, Option<TransactionStatus>)>)`: The status of each transaction in the order
+    ///   of `tx_ids`, `None` for the transactions that are not found
+    /// - `Err`: If there was an error retrieving the statuses
+    fn get_tx_statuses(
+        &self,
+        tx_ids: &[Txid],
+    ) -> Result<Vec<(Txid, Option<TransactionStatus>)>, MonitorError>;
+
+    /// Gets the state of many transactions in one byte each, e.g. to render the progress of a
+    /// protocol polling thousands of transactions.
+    ///
+    /// States are resolved from the statuses the monitor stored with its news and the monitor
+    /// height. Only the transactions the monitor has no record of are looked up in the indexer,
+    /// counted in `MonitorCounters::status_fallbacks`.
+    ///
+    /// # Arguments
+    /// * `tx_ids` - Hashes of the transactions to check
+    ///
+    /// # Returns
+    /// - `Ok(Vec<TxState>)`: The state of each transaction in the order of `tx_ids`
+    /// - `Err`: If there was an error reading the store or querying the indexer
+    fn status_bitmap(&self, tx_ids: &[Txid]) -> Result<Vec<TxState>, MonitorError>;
+
+    /// Lists the monitored transactions waiting in the mempool, to drive fee bumping.
+    ///
+    /// Transactions are tracked from the first tick they are seen in the mempool, with
+    /// `track_mempool` enabled, until they are found in a block. The list is built from the
+    /// store, without querying the indexer.
+    ///
+    /// # Arguments
+    /// * `older_than_blocks` - Only transactions first seen more than this number of blocks
+    ///   before the monitor height are listed
+    ///
+    /// # Returns
+    /// - `Ok(Vec<StaleTx>)`: One entry per transaction and context, the longest waiting first
+    /// - `Err`: If there was an error reading the store
+    fn stale_unconfirmed(&self, older_than_blocks: u32) -> Result<Vec<StaleTx>, MonitorError>;
+
+    /// Gets the outputs paying to a monitored script that were counted instead of reported,
+    /// because of the `OutputFilter` of their monitor.
+    ///
+    /// # Arguments
+    /// * `script_pubkey` - The monitored script, `address.script_pubkey()` for an address
+    ///
+    /// # Returns
+    /// - `Ok(Vec<AddressFilterStats>)`: The number and value of the filtered outputs, one entry per context
+    /// - `Err`: If there was an error reading the store
+    fn address_filter_stats(
+        &self,
+        script_pubkey: &Script,
+    ) -> Result<Vec<AddressFilterStats>, MonitorError>;
+
+    /// Writes the finalized detections (transactions, pegins, spends, script and OP_RETURN
+    /// outputs found) from the detection archive as CSV, one row per detection with its height,
+    /// kind, txid, outpoint, amount, context and block hash. Live news are not read, so acked
+    /// detections are exported too.
+    ///
+    /// # Arguments
+    /// * `from_height` - First height of the detections exported
+    /// * `to_height` - Last height of the detections exported, included
+    /// * `writer` - Where the CSV is written
+    ///
+    /// # Returns
+    /// - `Ok(u64)`: The number of detections written, with `confirmation_threshold` confirmations
+    /// - `Err`: If there was an error reading the store or writing the CSV
+    fn export_detections_csv(
+        &self,
+        from_height: BlockHeight,
+        to_height: BlockHeight,
+        writer: &mut dyn io::Write,
+    ) -> Result<u64, MonitorError>;
+
+    /// Gets the blocks processed by the monitor, as recorded in the chain log kept for external
+    /// audit. After a reorg the blocks of both branches are listed.
+    ///
+    /// # Arguments
+    /// * `from` - First height of the blocks listed
+    /// * `to` - Last height of the blocks listed, included
+    ///
+    /// # Returns
+    /// - `Ok(Vec<ChainLogEntry>)`: The blocks with their previous hash and the time they were
+    ///   processed at, in processing order
+    /// - `Err`: If there was an error reading the store
+    fn chain_log(
+        &self,
+        from: BlockHeight,
+        to: BlockHeight,
+    ) -> Result<Vec<ChainLogEntry>, MonitorError>;
+
+    /// Checks the hash linkage of the whole chain log, flagging the gaps and the forks recorded.
+    fn verify_chain_log(&self) -> Result<ChainLogVerification, MonitorError>;
+
+    /// Gets the progress of the monitors registered with a context, active or deactivated: how
+    /// many were detected, finalized, orphaned or expired. Read from the store, the indexer is
+    /// not queried.
+    ///
+    /// # Arguments
+    /// * `context` - The context the monitors were registered with
+    ///
+    /// # Returns
+    /// - `Ok(ContextProgress)`: The counts and the finalized ratio, all zero for an unknown context
+    /// - `Err`: If there was an error reading the store
+    fn context_progress(&self, context: &str) -> Result<ContextProgress, MonitorError>;
+
+    /// Gets the progress of every context with monitors, sorted by context.
+    fn all_context_progress(&self) -> Result<Vec<ContextProgress>, MonitorError>;
+
     fn get_estimated_fee_rate(&self) -> Result<u64, MonitorError>;
 }
 
-impl MonitorApi for Monitor<IndexerType, MonitorStore> {
+impl MonitorApi for Monitor<IndexerType, MonitorStore<Rc<Storage>>> {
     fn tick(&self) -> Result<(), MonitorError> {
         self.tick()
     }
 
+    fn apply_block(&self, block: &FullBlock) -> Result<TickReport, MonitorError> {
+        self.apply_block(block)
+    }
+
+    fn apply_reorg(
+        &self,
+        orphaned: &[BlockHash],
+        new_tip: &FullBlock,
+    ) -> Result<TickReport, MonitorError> {
+        self.apply_reorg(orphaned, new_tip)
+    }
+
     fn get_monitor_height(&self) -> Result<BlockHeight, MonitorError> {
         self.get_monitor_height()
     }
@@ -191,289 +853,3198 @@ impl MonitorApi for Monitor<IndexerType, MonitorStore> {
         Ok(())
     }
 
+    fn monitor_batch(
+        &self,
+        items: Vec<TypesToMonitor>,
+    ) -> Result<Vec<Result<(), MonitorError>>, MonitorError> {
+        self.monitor_batch(items)
+    }
+
     fn cancel(&self, data: TypesToMonitor) -> Result<(), MonitorError> {
-        self.store.cancel_monitor(data)?;
+        self.cancel(data)
+    }
 
-        Ok(())
+    fn cancel_with_policy(
+        &self,
+        data: TypesToMonitor,
+        policy: CancelNewsPolicy,
+    ) -> Result<(), MonitorError> {
+        self.cancel_with_policy(data, policy)
     }
 
-    fn get_news(&self) -> Result<Vec<MonitorNews>, MonitorError> {
-        self.get_news()
+    fn cancel_permanent(&self, data: TypesToMonitor) -> Result<(), MonitorError> {
+        self.cancel_permanent(data)
     }
 
-    fn ack_news(&self, data: AckMonitorNews) -> Result<(), MonitorError> {
-        self.ack_news(data)
+    fn resolve_manually(&self, target: TypesToMonitor, note: String) -> Result<(), MonitorError> {
+        self.resolve_manually(target, note)
     }
 
-    fn get_tx_status(&self, tx_id: &Txid) -> Result<TransactionStatus, MonitorError> {
-        self.get_tx_status(tx_id)
+    fn list_trash(&self) -> Result<Vec<TrashEntry>, MonitorError> {
+        self.list_trash()
     }
 
-    fn is_ready(&self) -> Result<bool, MonitorError> {
-        let is_ready = self.indexer.is_ready()?;
-        Ok(is_ready)
+    fn restore_from_trash(&self, selector: TrashSelector) -> Result<Vec<TrashEntry>, MonitorError> {
+        self.restore_from_trash(selector)
     }
 
-    fn get_confirmation_threshold(&self) -> u32 {
-        self.settings.confirmation_threshold
+    fn prune(&self, older_than_height: BlockHeight) -> Result<PruneReport, MonitorError> {
+        self.prune(older_than_height)
     }
 
-    fn get_current_block(&self) -> Result<Option<FullBlock>, MonitorError> {
-        self.get_current_block()
+    fn get_monitors(&self, include_inactive: bool) -> Result<Vec<MonitorInfo>, MonitorError> {
+        self.get_monitors(include_inactive)
     }
 
-    fn get_estimated_fee_rate(&self) -> Result<u64, MonitorError> {
-        self.get_estimated_fee_rate()
+    fn monitor_history(
+        &self,
+        monitor: &TypesToMonitorStore,
+    ) -> Result<Vec<MonitorEvent>, MonitorError> {
+        self.monitor_history(monitor)
     }
-}
 
-impl<I, B> Monitor<I, B>
-where
-    I: IndexerApi,
-    B: MonitorStoreApi,
-{
-    pub fn new(
-        indexer: I,
-        bitvmx_store: B,
-        settings: MonitorSettings,
-    ) -> Result<Self, MonitorError> {
-        Ok(Self {
-            indexer,
-            store: bitvmx_store,
-            settings,
-        })
+    fn outpoints_spent_by(&self, spender: &Txid) -> Result<Vec<(Txid, u32)>, MonitorError> {
+        self.outpoints_spent_by(spender)
     }
 
-    pub fn save_monitor(&self, data: TypesToMonitor) -> Result<(), MonitorError> {
-        if data != TypesToMonitor::NewBlock {
-            self.store.set_pending_work(true)?;
-        }
+    fn find_monitors_for_tx(&self, tx_id: &Txid) -> Result<Vec<TypesToMonitorStore>, MonitorError> {
+        self.find_monitors_for_tx(tx_id)
+    }
 
-        // Check if the TypesToMonitor instance has a confirmation trigger (if it's a transaction), and if so,
-        // ensure it does not exceed the configured max_monitoring_confirmations.
-        // Max monitoring confirmations is the number of confirmations that the monitor will wait for before deactivating the monitor.
-        // If it does, return an error.
-        match &data {
-            TypesToMonitor::Transactions(_, _, confirmation_trigger)
-            | TypesToMonitor::RskPegin(confirmation_trigger)
-            | TypesToMonitor::SpendingUTXOTransaction(_, _, _, confirmation_trigger) => {
-                if let Some(confirmation_trigger) = confirmation_trigger {
-                    if *confirmation_trigger >= self.settings.max_monitoring_confirmations {
-                        return Err(MonitorError::InvalidConfirmationTrigger(
-                            *confirmation_trigger,
-                            self.settings.max_monitoring_confirmations,
-                        ));
-                    }
-                }
-            }
-            _ => {}
-        }
+    fn reactivate(&self, data: TypesToMonitor) -> Result<(), MonitorError> {
+        self.reactivate(data)
+    }
+
+    fn replace_monitored_tx(&self, old: Txid, new: Txid) -> Result<(), MonitorError> {
+        self.replace_monitored_tx(old, new)
+    }
+
+    fn snooze(&self, target: TypesToMonitor, blocks: u32) -> Result<(), MonitorError> {
+        self.snooze(target, blocks)
+    }
+
+    fn get_snoozes(&self) -> Result<Vec<SnoozeEntry>, MonitorError> {
+        self.get_snoozes()
+    }
+
+    fn last_reorg_impact(&self) -> Result<Option<ReorgImpact>, MonitorError> {
+        self.last_reorg_impact()
+    }
+
+    fn register_composite(
+        &self,
+        id: String,
+        all_of: Vec<NewsPredicate>,
+    ) -> Result<(), MonitorError> {
+        self.register_composite(id, all_of)
+    }
+
+    fn cancel_composite(&self, id: &str) -> Result<(), MonitorError> {
+        self.cancel_composite(id)
+    }
+
+    fn get_composites(&self) -> Result<Vec<CompositeRule>, MonitorError> {
+        self.get_composites()
+    }
+
+    fn get_news(&self) -> Result<Vec<MonitorNews>, MonitorError> {
+        self.get_news()
+    }
+
+    fn has_news(&self) -> Result<bool, MonitorError> {
+        self.has_news()
+    }
+
+    fn news_count(&self) -> Result<usize, MonitorError> {
+        self.news_count()
+    }
+
+    fn get_news_envelopes(&self) -> Result<Vec<NewsEnvelope>, MonitorError> {
+        self.get_news_envelopes()
+    }
+
+    fn get_news_page(
+        &self,
+        after: Option<NewsCursor>,
+        limit: usize,
+    ) -> Result<NewsPage, MonitorError> {
+        self.get_news_page(after, limit)
+    }
+
+    fn ack_news(&self, data: AckMonitorNews) -> Result<(), MonitorError> {
+        self.ack_news(data)
+    }
+
+    fn ack_news_batch(&self, items: Vec<AckMonitorNews>) -> Result<(), MonitorError> {
+        self.ack_news_batch(items)
+    }
+
+    fn ack_all_news(&self) -> Result<usize, MonitorError> {
+        self.ack_all_news()
+    }
+
+    fn get_news_history(
+        &self,
+        from_seq: u64,
+        limit: usize,
+    ) -> Result<Vec<NewsHistoryEntry>, MonitorError> {
+        self.get_news_history(from_seq, limit)
+    }
+
+    fn get_tx_status(&self, tx_id: &Txid) -> Result<TransactionStatus, MonitorError> {
+        self.get_tx_status(tx_id)
+    }
+
+    fn get_tx_statuses(
+        &self,
+        tx_ids: &[Txid],
+    ) -> Result<Vec<(Txid, Option<TransactionStatus>)>, MonitorError> {
+        self.get_tx_statuses(tx_ids)
+    }
+
+    fn status_bitmap(&self, tx_ids: &[Txid]) -> Result<Vec<TxState>, MonitorError> {
+        self.status_bitmap(tx_ids)
+    }
+
+    fn stale_unconfirmed(&self, older_than_blocks: u32) -> Result<Vec<StaleTx>, MonitorError> {
+        self.stale_unconfirmed(older_than_blocks)
+    }
+
+    fn address_filter_stats(
+        &self,
+        script_pubkey: &Script,
+    ) -> Result<Vec<AddressFilterStats>, MonitorError> {
+        self.address_filter_stats(script_pubkey)
+    }
+
+    fn export_detections_csv(
+        &self,
+        from_height: BlockHeight,
+        to_height: BlockHeight,
+        writer: &mut dyn io::Write,
+    ) -> Result<u64, MonitorError> {
+        self.export_detections_csv(from_height, to_height, writer)
+    }
+
+    fn chain_log(
+        &self,
+        from: BlockHeight,
+        to: BlockHeight,
+    ) -> Result<Vec<ChainLogEntry>, MonitorError> {
+        self.chain_log(from, to)
+    }
+
+    fn verify_chain_log(&self) -> Result<ChainLogVerification, MonitorError> {
+        self.verify_chain_log()
+    }
+
+    fn context_progress(&self, context: &str) -> Result<ContextProgress, MonitorError> {
+        self.context_progress(context)
+    }
+
+    fn all_context_progress(&self) -> Result<Vec<ContextProgress>, MonitorError> {
+        self.all_context_progress()
+    }
+
+    fn is_ready(&self) -> Result<bool, MonitorError> {
+        self.is_ready()
+    }
+
+    fn get_confirmation_threshold(&self) -> u32 {
+        self.settings.confirmation_threshold
+    }
+
+    fn get_current_block(&self) -> Result<Option<FullBlock>, MonitorError> {
+        self.get_current_block()
+    }
+
+    fn get_estimated_fee_rate(&self) -> Result<u64, MonitorError> {
+        self.get_estimated_fee_rate()
+    }
+}
+
+impl<I, B> Monitor<I, B>
+where
+    I: IndexerApi,
+    B: MonitorStoreApi,
+{
+    pub fn new(
+        indexer: I,
+        bitvmx_store: B,
+        settings: MonitorSettings,
+    ) -> Result<Self, MonitorError> {
+        redaction::reveal_contexts(settings.reveal_contexts);
+
+        Ok(Self {
+            indexer,
+            store: bitvmx_store,
+            settings,
+            counters: MonitorCounters::default(),
+            mempool: None,
+            headers: None,
+            node: None,
+            last_sweep_height: Cell::new(None),
+            last_prune_height: Cell::new(None),
+            news_sequence_checked: Cell::new(false),
+            network_recorded: Cell::new(false),
+            commands: CommandQueue::new(),
+            news_senders: RefCell::new(vec![]),
+            unpublished_news: RefCell::new(vec![]),
+            tx_cache: RefCell::new(HashMap::new()),
+            indexer_best_height: Cell::new(0),
+            unexpected_detection: RefCell::new(None),
+            scan_set: RefCell::new(None),
+            health: HealthTracker::default(),
+            applied_block: RefCell::new(None),
+            processed_block: Cell::new(None),
+            logged_chain: RefCell::new(HashMap::new()),
+            orphan_disputes: RefCell::new(HashSet::new()),
+            tick_deactivations: Cell::new(0),
+        })
+    }
+
+    /// Returns a receiver that gets a copy of every news written by the following ticks, once
+    /// each tick ends, so a consumer can block on it instead of polling `get_news`.
+    ///
+    /// News sent over the channel are not acknowledged, they stay in `get_news` until `ack_news`.
+    /// News written outside a tick (e.g. by `replace_monitored_tx`) are sent at the end of the
+    /// next one. Dropping the receiver just stops the copies, the tick does not fail.
+    pub fn news_receiver(&self) -> mpsc::Receiver<MonitorNews> {
+        let (sender, receiver) = mpsc::channel();
+        self.news_senders.borrow_mut().push(sender);
+        receiver
+    }
+
+    /// Returns a handle to queue mutations from other threads, see [`MonitorHandle`].
+    /// Calling the methods of the monitor from the thread that owns it applies them directly.
+    pub fn handle(&self) -> MonitorHandle {
+        self.commands.handle()
+    }
+
+    /// Returns a trigger ending the wait of [`Monitor::wait_for_commands`], see [`BlockTrigger`].
+    pub fn block_trigger(&self) -> BlockTrigger {
+        self.commands.block_trigger()
+    }
+
+    /// Applies the commands queued by the handles, including the settings updates a tick left
+    /// for later. Returns the number of commands applied.
+    pub fn apply_commands(&mut self) -> usize {
+        self.apply_queued_commands().0
+    }
+
+    /// Applies the commands queued by the handles as they arrive, for up to `timeout` or until a
+    /// [`BlockTrigger`] wakes the monitor. Used by the run loop between ticks, so commands don't
+    /// wait for the next tick and new blocks don't wait for the tick interval.
+    pub fn wait_for_commands(&mut self, timeout: Duration) -> usize {
+        let deadline = Instant::now() + timeout;
+        let (mut applied, mut woken) = self.apply_queued_commands();
+
+        while !woken {
+            let Some(command) = self
+                .commands
+                .recv_timeout(deadline.saturating_duration_since(Instant::now()))
+            else {
+                break;
+            };
+
+            if self.apply_command(command) {
+                applied += 1;
+            } else {
+                woken = true;
+            }
+        }
+
+        // The commands and wakeups queued with the wakeup are taken too, the next tick sees them
+        if woken {
+            applied += self.apply_commands();
+        }
+
+        applied
+    }
+
+    /// Applies the queued commands, returning how many were applied and whether a wakeup was
+    /// among them.
+    fn apply_queued_commands(&mut self) -> (usize, bool) {
+        let mut applied = 0;
+        let mut woken = false;
+
+        for command in self.commands.take_deferred() {
+            if self.apply_command(command) {
+                applied += 1;
+            } else {
+                woken = true;
+            }
+        }
+
+        while let Some(command) = self.commands.try_recv() {
+            if self.apply_command(command) {
+                applied += 1;
+            } else {
+                woken = true;
+            }
+        }
+
+        (applied, woken)
+    }
+
+    /// Returns false for a wakeup, which applies nothing.
+    fn apply_command(&mut self, command: Command) -> bool {
+        match self.apply_store_command(command) {
+            Some(Command::UpdateSettings(settings, reply)) => {
+                // The caller may have stopped waiting
+                let _ = reply.send(self.update_settings(settings));
+            }
+            Some(Command::Wake) => return false,
+            _ => {}
+        }
+
+        true
+    }
+
+    /// Applies a command that reads or changes the store and gives back the settings updates,
+    /// which need the monitor borrowed mutably, and the wakeups, which end the wait of the run
+    /// loop.
+    fn apply_store_command(&self, command: Command) -> Option<Command> {
+        // The caller may have stopped waiting, so the replies are not checked
+        match command {
+            Command::Monitor(data, options, reply) => {
+                let _ = reply.send(self.save_monitor_with(data, options));
+            }
+            Command::Cancel(data, reply) => {
+                let _ = reply.send(self.cancel(data));
+            }
+            Command::AckNews(data, reply) => {
+                let _ = reply.send(self.ack_news(data));
+            }
+            Command::GetMonitors(include_inactive, reply) => {
+                let _ = reply.send(self.get_monitors(include_inactive));
+            }
+            Command::GetNews(reply) => {
+                let _ = reply.send(self.get_news_envelopes());
+            }
+            Command::GetTxStatus(tx_id, reply) => {
+                let _ = reply.send(self.get_tx_status(&tx_id));
+            }
+            Command::GetMonitorHeight(reply) => {
+                let _ = reply.send(self.get_monitor_height());
+            }
+            Command::IsReady(reply) => {
+                let _ = reply.send(self.is_ready());
+            }
+            command @ (Command::UpdateSettings(..) | Command::Wake) => return Some(command),
+        }
+
+        None
+    }
+
+    /// Applies the commands queued when the tick starts, so they don't interleave with the
+    /// updates of the monitor lists. Settings updates and wakeups are left for
+    /// [`Monitor::apply_commands`].
+    fn drain_commands(&self) {
+        while let Some(command) = self.commands.try_recv() {
+            if let Some(command) = self.apply_store_command(command) {
+                self.commands.defer(command);
+            }
+        }
+    }
+
+    /// Replaces the settings of the running monitor, keeping its caches and counters.
+    ///
+    /// The indexer settings are only read when the indexer is created and the block source can't
+    /// change under a running monitor, a change of them is reported in
+    /// [`SettingsUpdate::requires_restart`] and the current ones are kept.
+    pub fn update_settings(
+        &mut self,
+        settings: MonitorSettings,
+    ) -> Result<SettingsUpdate, MonitorError> {
+        if settings.confirmation_threshold == 0
+            || settings.max_monitoring_confirmations == 0
+            || settings.max_blocks_per_tick == 0
+        {
+            return Err(MonitorError::InvalidSettings(
+                "confirmation_threshold, max_monitoring_confirmations and max_blocks_per_tick must be greater than 0"
+                    .to_string(),
+            ));
+        }
+
+        let mut update = SettingsUpdate::default();
+        let current = &self.settings;
+
+        if current.confirmation_threshold != settings.confirmation_threshold {
+            update.applied.push("confirmation_threshold");
+        }
+        if current.max_monitoring_confirmations != settings.max_monitoring_confirmations {
+            update.applied.push("max_monitoring_confirmations");
+        }
+        if current.deactivation_requires_ack_grace_blocks
+            != settings.deactivation_requires_ack_grace_blocks
+        {
+            update
+                .applied
+                .push("deactivation_requires_ack_grace_blocks");
+        }
+        if current.sweep_interval_blocks != settings.sweep_interval_blocks {
+            update.applied.push("sweep_interval_blocks");
+        }
+        if current.auto_prune_interval != settings.auto_prune_interval {
+            update.applied.push("auto_prune_interval");
+        }
+        if current.retention != settings.retention {
+            update.applied.push("retention");
+        }
+        if current.news_resolution != settings.news_resolution {
+            update.applied.push("news_resolution");
+        }
+        if current.track_mempool != settings.track_mempool {
+            update.applied.push("track_mempool");
+        }
+        if current.max_blocks_per_tick != settings.max_blocks_per_tick {
+            update.applied.push("max_blocks_per_tick");
+        }
+        if current.strict_detections != settings.strict_detections {
+            update.applied.push("strict_detections");
+        }
+        if current.strict_allowed_txids != settings.strict_allowed_txids {
+            update.applied.push("strict_allowed_txids");
+        }
+        if current.health != settings.health {
+            update.applied.push("health");
+        }
+        if current.reveal_contexts != settings.reveal_contexts {
+            redaction::reveal_contexts(settings.reveal_contexts);
+            update.applied.push("reveal_contexts");
+        }
+        if format!("{:?}", current.indexer_settings) != format!("{:?}", settings.indexer_settings) {
+            update.requires_restart.push("indexer_settings");
+        }
+        if current.block_source != settings.block_source {
+            update.requires_restart.push("block_source");
+        }
+
+        self.settings = MonitorSettings {
+            indexer_settings: self.settings.indexer_settings.clone(),
+            block_source: self.settings.block_source,
+            ..settings
+        };
+
+        Ok(update)
+    }
+
+    /// Applies the settings of a configuration file read again while running, see
+    /// [`Monitor::update_settings`]. `current` is the configuration the process started with.
+    pub fn reload_config(
+        &mut self,
+        current: &MonitorConfig,
+        reloaded: &MonitorConfig,
+    ) -> Result<SettingsUpdate, MonitorError> {
+        let settings = MonitorSettings::from(reloaded.settings.clone().unwrap_or_default());
+        let mut update = self.update_settings(settings)?;
+        update
+            .requires_restart
+            .extend(current.restart_required_changes(reloaded));
+
+        Ok(update)
+    }
+
+    /// Asks the process running this monitor to reload its configuration file.
+    pub fn request_reload(&self) -> Result<(), MonitorError> {
+        self.store.request_reload()?;
+        Ok(())
+    }
+
+    /// Returns whether a reload was requested with [`Monitor::request_reload`] since the last
+    /// call.
+    pub fn take_reload_request(&self) -> Result<bool, MonitorError> {
+        Ok(self.store.take_reload_request()?)
+    }
+
+    /// Sets the mempool source used by SpendingUTXO monitors with a deadline.
+    pub fn with_mempool(mut self, mempool: Box<dyn MempoolApi + Send>) -> Self {
+        self.mempool = Some(mempool);
+        self
+    }
+
+    /// Sets the source of the block bits used to raise an alarm on reorgs to a branch with less work.
+    pub fn with_headers(mut self, headers: Box<dyn BlockHeaderApi + Send>) -> Self {
+        self.headers = Some(headers);
+        self
+    }
+
+    /// Sets the node whose network is recorded in the store on the first tick, see
+    /// [`Monitor::preflight`].
+    pub fn with_node(mut self, node: Box<dyn NodeApi + Send>) -> Self {
+        self.node = Some(node);
+        self
+    }
+
+    pub fn save_monitor(&self, data: TypesToMonitor) -> Result<(), MonitorError> {
+        self.save_monitor_with(data, RegistrationOptions::default())
+    }
+
+    /// Registers a monitor like `save_monitor`, with the given options.
+    pub fn save_monitor_with(
+        &self,
+        data: TypesToMonitor,
+        options: RegistrationOptions,
+    ) -> Result<(), MonitorError> {
+        self.validate_monitor(&data)?;
+        if let Some(metadata) = &options.metadata {
+            validate_metadata(metadata)?;
+        }
+
+        if data != TypesToMonitor::NewBlock {
+            self.store.set_pending_work(true)?;
+        }
+
+        if options.expected {
+            self.store
+                .mark_expected(SnoozeTarget::of_monitor(data.clone()))?;
+        }
+
+        if let Some(metadata) = options.metadata {
+            self.store
+                .set_monitor_metadata(SnoozeTarget::of_monitor(data.clone()), metadata)?;
+        }
 
         self.store.add_monitor(data)?;
 
         Ok(())
     }
 
-    pub fn get_monitor_height(&self) -> Result<BlockHeight, MonitorError> {
-        self.store
-            .get_monitor_height()
-            .map_err(|e| MonitorError::UnexpectedError(e.to_string()))
+    /// Registers several monitors at once. Every item is validated on its own and the valid ones
+    /// are written with a single store update per monitor kind.
+    ///
+    /// The outer error is only returned when the store fails; validation errors are reported
+    /// per item, in the same order as `items`.
+    ///
+    /// Imports from a file go through it: the monitors added to the watch file of the binary are
+    /// registered with one call, see [`crate::watch_file::WatchFile::sync`].
+    pub fn monitor_batch(
+        &self,
+        items: Vec<TypesToMonitor>,
+    ) -> Result<Vec<Result<(), MonitorError>>, MonitorError> {
+        let mut outcomes = Vec::with_capacity(items.len());
+        let mut valid = Vec::with_capacity(items.len());
+
+        for item in items {
+            match self.validate_monitor(&item) {
+                Ok(()) => {
+                    valid.push(item);
+                    outcomes.push(Ok(()));
+                }
+                Err(e) => outcomes.push(Err(e)),
+            }
+        }
+
+        if valid.is_empty() {
+            return Ok(outcomes);
+        }
+
+        if valid.iter().any(|item| *item != TypesToMonitor::NewBlock) {
+            self.store.set_pending_work(true)?;
+        }
+
+        self.store.add_monitors(valid)?;
+
+        Ok(outcomes)
+    }
+
+    fn validate_monitor(&self, data: &TypesToMonitor) -> Result<(), MonitorError> {
+        // Check if the TypesToMonitor instance has a confirmation trigger (if it's a transaction), and if so,
+        // ensure it does not exceed the configured max_monitoring_confirmations.
+        // Max monitoring confirmations is the number of confirmations that the monitor will wait for before deactivating the monitor.
+        // If it does, return an error.
+        match data {
+            TypesToMonitor::Transactions(tx_ids, extra_data, confirmation_trigger, _) => {
+                if tx_ids.is_empty() {
+                    return Err(MonitorError::InvalidMonitor(
+                        "at least one transaction id is required".to_string(),
+                    ));
+                }
+                validate_context(extra_data)?;
+                self.validate_max_confirmation_trigger(*confirmation_trigger)?;
+            }
+            TypesToMonitor::SpendingUTXOTransaction(_, _, extra_data, confirmation_trigger, ..) => {
+                validate_context(extra_data)?;
+                self.validate_max_confirmation_trigger(*confirmation_trigger)?;
+            }
+            TypesToMonitor::RskPegin(confirmation_trigger, ..) => {
+                self.validate_max_confirmation_trigger(*confirmation_trigger)?;
+            }
+            TypesToMonitor::NewBlock => {}
+            TypesToMonitor::ScriptPubKey(script_pubkey, extra_data, confirmation_trigger, _) => {
+                if script_pubkey.is_empty() {
+                    return Err(MonitorError::InvalidMonitor(
+                        "script_pubkey must not be empty".to_string(),
+                    ));
+                }
+                validate_context(extra_data)?;
+                self.validate_max_confirmation_trigger(*confirmation_trigger)?;
+            }
+            TypesToMonitor::OpReturnPrefix(prefix, extra_data) => {
+                if prefix.is_empty() {
+                    return Err(MonitorError::InvalidMonitor(
+                        "OP_RETURN prefix must not be empty".to_string(),
+                    ));
+                }
+                validate_context(extra_data)?;
+            }
+            TypesToMonitor::Alternatives(tx_ids, extra_data) => {
+                let distinct: HashSet<&Txid> = tx_ids.iter().collect();
+                if distinct.len() < 2 || distinct.len() != tx_ids.len() {
+                    return Err(MonitorError::InvalidMonitor(
+                        "at least two distinct transaction ids are required".to_string(),
+                    ));
+                }
+                validate_context(extra_data)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn validate_max_confirmation_trigger(
+        &self,
+        confirmation_trigger: Option<u32>,
+    ) -> Result<(), MonitorError> {
+        validate_confirmation_trigger(confirmation_trigger)?;
+
+        if let Some(confirmation_trigger) = confirmation_trigger {
+            if confirmation_trigger >= self.settings.max_monitoring_confirmations {
+                return Err(MonitorError::InvalidConfirmationTrigger(
+                    confirmation_trigger,
+                    self.settings.max_monitoring_confirmations,
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn is_ready(&self) -> Result<bool, MonitorError> {
+        // The caller applies the blocks as it gets them
+        if self.is_external() {
+            return Ok(true);
+        }
+
+        self.counters.add_indexer_call();
+        let is_ready = self.indexer.is_ready()?;
+        Ok(is_ready)
+    }
+
+    pub fn get_monitor_height(&self) -> Result<BlockHeight, MonitorError> {
+        self.store
+            .get_monitor_height()
+            .map_err(|e| MonitorError::UnexpectedError(e.to_string()))
+    }
+
+    // This method checks if the monitor has pending work to be done.
+    // It checks if the block in the monitor is the same as the best block in the indexer.
+    // If the block is not the same, it means that the monitor is not synced with the indexer, so it has pending work to be done to sync it.
+    // If the block is the same, it means that the monitor is synced with the indexer, so it has no pending work to be done.
+    pub fn is_pending_work(&self) -> Result<bool, MonitorError> {
+        let is_pending_work = self.store.has_pending_work()?;
+
+        if is_pending_work {
+            return Ok(true);
+        }
+
+        // The hash of the last block processed is kept in the store, so a tick at the same tip
+        // only asks the indexer for its best block and skips the monitors
+        let monitor_block_hash = match self.store.get_monitor_block_hash()? {
+            Some(hash) => hash,
+            // Stores written before the hash was kept
+            None => match self.get_current_block()? {
+                Some(block) => block.hash,
+                None => {
+                    debug!("No block found in Monitor, pending work to be done");
+                    return Ok(true);
+                }
+            },
+        };
+
+        self.counters.add_indexer_call();
+        let block = self.indexer.get_best_block()?;
+
+        if block.is_none() {
+            return Ok(false);
+        }
+
+        let block = block.unwrap();
+
+        if block.hash != monitor_block_hash {
+            debug!("Best block hash mismatch, pending work to be done");
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    /// Parses the spending UTXO context, or the context of a minor spend, and extracts target_tx_id, target_utxo_index, and original_extra_data
+    /// Returns None if the context is not valid or cannot be parsed
+    fn parse_spending_utxo_context(extra_data: &str) -> Option<(Txid, u32, MonitorContext)> {
+        if !Self::is_spender_context(extra_data) {
+            return None;
+        }
+
+        // Parse the context: INTERNAL_SPENDING_UTXO:{target_tx_id_hex}:{target_utxo_index}:{original_extra_data}
+        let parts: Vec<&str> = extra_data.split(':').collect();
+        if parts.len() >= 4 {
+            if let (Ok(target_tx_id), Ok(target_utxo_index)) =
+                (parts[1].parse::<Txid>(), parts[2].parse::<u32>())
+            {
+                let original_extra_data = parts[3..].join(":").into();
+                return Some((target_tx_id, target_utxo_index, original_extra_data));
+            }
+        }
+
+        None
+    }
+
+    fn is_spender_context(extra_data: &str) -> bool {
+        [
+            INTERNAL_SPENDING_UTXO,
+            INTERNAL_MINOR_SPEND,
+            INTERNAL_UNREPORTED_SPEND,
+        ]
+        .iter()
+        .any(|prefix| extra_data.starts_with(prefix))
+    }
+
+    /// Builds the context string for transactions paying to a monitored script_pubkey
+    fn build_script_pubkey_context(
+        script_pubkey: &ScriptBuf,
+        vout: u32,
+        extra_data: &str,
+    ) -> MonitorContext {
+        format!(
+            "{}:{}:{}:{}",
+            INTERNAL_SCRIPT_PUBKEY,
+            script_pubkey.to_hex_string(),
+            vout,
+            extra_data
+        )
+        .into()
+    }
+
+    /// Parses the script pubkey context and extracts script_pubkey, vout, and original_extra_data
+    /// Returns None if the context is not valid or cannot be parsed
+    fn parse_script_pubkey_context(extra_data: &str) -> Option<(ScriptBuf, u32, MonitorContext)> {
+        if !extra_data.starts_with(INTERNAL_SCRIPT_PUBKEY) {
+            return None;
+        }
+
+        // Parse the context: INTERNAL_SCRIPT_PUBKEY:{script_pubkey_hex}:{vout}:{original_extra_data}
+        let parts: Vec<&str> = extra_data.split(':').collect();
+        if parts.len() >= 4 {
+            if let (Ok(script_pubkey), Ok(vout)) =
+                (ScriptBuf::from_hex(parts[1]), parts[2].parse::<u32>())
+            {
+                let original_extra_data = parts[3..].join(":").into();
+                return Some((script_pubkey, vout, original_extra_data));
+            }
+        }
+
+        None
+    }
+
+    /// Monitor registered by the user the transaction monitor with `extra_data` belongs to, which
+    /// keeps the events of the transaction in its history. None for the pegin transactions.
+    fn history_owner(tx_id: Txid, extra_data: &str) -> Option<TypesToMonitorStore> {
+        match extra_data {
+            ed if ed == INTERNAL_RSK_PEGIN => None,
+            ed if ed.starts_with(INTERNAL_SCRIPT_PUBKEY) => Self::parse_script_pubkey_context(ed)
+                .map(|(script_pubkey, _, context)| {
+                    TypesToMonitorStore::ScriptPubKey(script_pubkey, context, None, None)
+                }),
+            ed if Self::is_spender_context(ed) => Self::parse_spending_utxo_context(ed).map(
+                |(target_tx_id, target_utxo_index, context)| {
+                    TypesToMonitorStore::SpendingUTXOTransaction(
+                        target_tx_id,
+                        target_utxo_index,
+                        context,
+                        None,
+                        None,
+                        None,
+                        None,
+                    )
+                },
+            ),
+            _ => Some(TypesToMonitorStore::Transaction(
+                tx_id,
+                extra_data.into(),
+                None,
+                None,
+            )),
+        }
+    }
+
+    /// Records the block of the transaction and the confirmation milestones it reached in the
+    /// history of `owner`. Events already recorded are ignored, see [`MonitorEvent::record`].
+    fn record_tx_events(
+        &self,
+        owner: &TypesToMonitorStore,
+        tx: &TransactionInfo,
+        tx_id: Txid,
+        number_confirmation_trigger: Option<u32>,
+    ) -> Result<(), MonitorError> {
+        let block = &tx.block_info;
+        if block.orphan {
+            self.store.record_monitor_event(
+                owner,
+                MonitorEvent::Orphaned {
+                    tx_id,
+                    height: block.height,
+                    block_hash: block.hash,
+                },
+            )?;
+            return Ok(());
+        }
+
+        self.store.record_monitor_event(
+            owner,
+            MonitorEvent::Detected {
+                tx_id,
+                height: block.height,
+                block_hash: block.hash,
+            },
+        )?;
+
+        let mut milestones: Vec<u32> = number_confirmation_trigger
+            .into_iter()
+            .chain([self.settings.confirmation_threshold])
+            .filter(|milestone| tx.confirmations >= *milestone)
+            .collect();
+        milestones.sort_unstable();
+        milestones.dedup();
+
+        for confirmations in milestones {
+            self.store.record_monitor_event(
+                owner,
+                MonitorEvent::Confirmed {
+                    tx_id,
+                    confirmations,
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Determines if news should be sent based on the confirmation trigger.
+    fn should_send_news(
+        &self,
+        tx_id: Txid,
+        extra_data: &str,
+        number_confirmation_trigger: Option<u32>,
+        current_confirmations: u32,
+    ) -> Result<bool, MonitorError> {
+        let trigger_sent = self.store.get_transaction_trigger_sent(tx_id, extra_data)?;
+
+        if let Some(trigger) = number_confirmation_trigger {
+            // Send news when confirmations are greater than or equal to the trigger value
+            // but only once (when trigger_sent is false)
+            Ok(current_confirmations >= trigger && !trigger_sent)
+        } else {
+            // If None, always send news when current confirmations are less than the max monitoring confirmations
+            Ok(current_confirmations < self.settings.max_monitoring_confirmations)
+        }
+    }
+
+    pub fn tick(&self) -> Result<(), MonitorError> {
+        if self.is_external() {
+            return Err(MonitorError::BlockSourceMismatch(BlockSource::External));
+        }
+
+        self.run_tick(|| self.process_tick(None)).map(|_| ())
+    }
+
+    /// Runs a tick like [`Monitor::tick`] that checks `shutdown` before each block. Once the
+    /// shutdown is requested the block being processed is finished, so its news and the monitor
+    /// height stay consistent, and the tick returns without going through the next blocks, the
+    /// mempool and the maintenance of the store.
+    pub fn tick_with_shutdown(
+        &self,
+        shutdown: &ShutdownToken,
+    ) -> Result<TickProgress, MonitorError> {
+        if self.is_external() {
+            return Err(MonitorError::BlockSourceMismatch(BlockSource::External));
+        }
+
+        self.run_tick(|| self.process_tick(Some(shutdown)))
+    }
+
+    /// Runs `process` as a tick: its failure and duration count in the health score, the news it
+    /// wrote are sent to the receivers and, in strict mode, an unexpected detection fails it.
+    ///
+    /// The tick runs in a `tick` span, with the spans of its phases below, and ends with a
+    /// `Tick summary` event counting the news written and the monitors deactivated.
+    fn run_tick<T>(
+        &self,
+        process: impl FnOnce() -> Result<T, MonitorError>,
+    ) -> Result<T, MonitorError> {
+        let _tick = info_span!("tick").entered();
+        let started = Instant::now();
+        let news_before = self.counters.news_emitted();
+        self.tick_deactivations.set(0);
+
+        let result = process();
+        if let Err(e) = &result {
+            self.health.record_tick_error(e);
+            if matches!(
+                e,
+                MonitorError::IndexerError(_)
+                    | MonitorError::BitcoinClientError(_)
+                    | MonitorError::IndexerInconsistency { .. }
+            ) {
+                self.counters.add_indexer_error();
+            }
+        }
+        if let Err(e) = self.update_health(started.elapsed()) {
+            warn!("Health state change not written: {}", e);
+        }
+        self.publish_news();
+        self.counters.set_last_tick_duration(started.elapsed());
+
+        debug!(
+            height = self.store.get_monitor_height().ok(),
+            news = self.counters.news_emitted() - news_before,
+            deactivated = self.tick_deactivations.get(),
+            duration_ms = started.elapsed().as_millis() as u64,
+            failed = result.is_err(),
+            "Tick summary"
+        );
+
+        let unexpected = self.unexpected_detection.take();
+        let value = result?;
+
+        match unexpected {
+            Some(detection) => Err(MonitorError::UnexpectedDetection(detection)),
+            None => Ok(value),
+        }
+    }
+
+    /// Runs the monitors on `block`, applied by the caller instead of found by the indexer.
+    /// Requires [`BlockSource::External`] in the settings.
+    ///
+    /// The block must extend the last one applied, the first one can be at any height. The
+    /// monitor keeps the hashes of the last `max_monitoring_confirmations` blocks and the
+    /// transactions its monitors found, so the next blocks don't need to carry them:
+    /// - a transaction is looked up in the block being applied, then in the snapshots of the
+    ///   monitor, and is orphaned when its block is no longer in the chain
+    /// - the outputs of a SpendingUTXO monitor are only looked for from the block applied after
+    ///   it was registered, the blocks applied before are not kept
+    ///
+    /// Applying the last block again does nothing, a block that failed can be applied again.
+    pub fn apply_block(&self, block: &FullBlock) -> Result<TickReport, MonitorError> {
+        self.apply_external_block(&[], block)
+    }
+
+    /// Replaces the blocks `orphaned`, the last ones applied, with `new_tip`, built on the block
+    /// before them. The rest of the new branch is applied with [`Monitor::apply_block`].
+    ///
+    /// The news of the transactions of the orphaned blocks and the reorg report are the ones a
+    /// reorg found by the indexer gets, see [`TickReport::reorg`].
+    pub fn apply_reorg(
+        &self,
+        orphaned: &[BlockHash],
+        new_tip: &FullBlock,
+    ) -> Result<TickReport, MonitorError> {
+        self.apply_external_block(orphaned, new_tip)
+    }
+
+    fn is_external(&self) -> bool {
+        self.settings.block_source == BlockSource::External
+    }
+
+    fn apply_external_block(
+        &self,
+        orphaned: &[BlockHash],
+        block: &FullBlock,
+    ) -> Result<TickReport, MonitorError> {
+        if !self.is_external() {
+            return Err(MonitorError::BlockSourceMismatch(BlockSource::Indexer));
+        }
+
+        self.run_tick(|| self.process_external_block(orphaned, block))
+    }
+
+    fn process_external_block(
+        &self,
+        orphaned: &[BlockHash],
+        block: &FullBlock,
+    ) -> Result<TickReport, MonitorError> {
+        self.drain_commands();
+        self.check_news_sequence()?;
+        self.tx_cache.borrow_mut().clear();
+        self.logged_chain.borrow_mut().clear();
+        self.orphan_disputes.borrow_mut().clear();
+
+        let mut report = TickReport {
+            height: block.height,
+            block_hash: block.hash,
+            news_emitted: 0,
+            reorg: None,
+        };
+
+        let mut chain = self.store.get_external_chain()?;
+
+        if orphaned.is_empty() && chain.last().is_some_and(|tip| tip.hash == block.hash) {
+            debug!("Block({}) already applied", block.hash);
+            return Ok(report);
+        }
+
+        // The orphaned blocks are the last ones applied, in any order
+        let kept = chain.len().saturating_sub(orphaned.len());
+        let replaced = &chain[kept..];
+        if let Some(hash) = orphaned
+            .iter()
+            .find(|hash| !replaced.iter().any(|b| b.hash == **hash))
+        {
+            return Err(MonitorError::ExternalChainMismatch(*hash));
+        }
+        if let Some(b) = replaced.iter().find(|b| !orphaned.contains(&b.hash)) {
+            return Err(MonitorError::ExternalChainMismatch(b.hash));
+        }
+        chain.truncate(kept);
+
+        if let Some(tip) = chain.last() {
+            if block.prev_hash != tip.hash || block.height != tip.height + 1 {
+                warn!(
+                    "Block({}) at Height({}) doesn't extend Block({}) at Height({})",
+                    block.hash, block.height, tip.hash, tip.height
+                );
+                return Err(MonitorError::ExternalChainMismatch(block.hash));
+            }
+        }
+
+        chain.push(ExternalBlockEntry {
+            height: block.height,
+            hash: block.hash,
+            prev_hash: block.prev_hash,
+            estimated_fee_rate: block.estimated_fee_rate,
+        });
+        let window_start = block
+            .height
+            .saturating_sub(self.settings.max_monitoring_confirmations);
+        chain.retain(|b| b.height > window_start);
+
+        // The blocks applied before are not kept, so there is nothing to backfill
+        for monitor in self.store.get_spending_utxo_backfills()? {
+            if let TypesToMonitorStore::SpendingUTXOTransaction(tx_id, vout, extra_data, ..) =
+                monitor
+            {
+                self.store
+                    .set_spending_utxo_backfill_done(tx_id, vout, &extra_data)?;
+            }
+        }
+
+        let reorg_before = self.store.get_last_reorg_impact()?;
+        let news_before = self.counters.news_emitted();
+        self.indexer_best_height.set(block.height);
+
+        self.applied_block.replace(Some(AppliedBlock {
+            chain: chain.clone(),
+            txs: block
+                .txs
+                .iter()
+                .map(|tx| (tx.compute_txid(), tx.clone()))
+                .collect(),
+        }));
+        let result = self
+            .process_block(block.clone())
+            .and_then(|_| Ok(self.store.set_external_chain(chain)?));
+        self.applied_block.replace(None);
+        result?;
+
+        self.process_mempool()?;
+        self.sweep_expired_keys()?;
+        self.auto_prune()?;
+
+        report.news_emitted = self.counters.news_emitted() - news_before;
+        report.reorg = self
+            .store
+            .get_last_reorg_impact()?
+            .filter(|impact| Some(impact) != reorg_before.as_ref());
+
+        Ok(report)
+    }
+
+    /// Calls `f` with the blocks applied in the `External` block source, the one being applied
+    /// last.
+    fn with_external_chain<T>(
+        &self,
+        f: impl FnOnce(&[ExternalBlockEntry]) -> T,
+    ) -> Result<T, MonitorError> {
+        if let Some(applied) = self.applied_block.borrow().as_ref() {
+            return Ok(f(&applied.chain));
+        }
+
+        Ok(f(&self.store.get_external_chain()?))
+    }
+
+    /// Looks up a transaction in the `External` block source: in the block being applied, where
+    /// a snapshot of it is saved for the next blocks, then in the snapshots. A transaction whose
+    /// block was replaced is orphaned, one only seen in the mempool is not found.
+    fn get_external_tx(&self, tx_id: &Txid) -> Result<Option<TransactionInfo>, MonitorError> {
+        let applied = self.applied_block.borrow().as_ref().and_then(|applied| {
+            let tip = applied.chain.last()?;
+            applied.txs.get(tx_id).map(|tx| TransactionInfo {
+                tx: tx.clone(),
+                block_info: tip.to_block(),
+                confirmations: 1,
+            })
+        });
+
+        if let Some(tx_info) = applied {
+            self.store.save_tx_snapshot(self.to_tx_snapshot(&tx_info))?;
+            return Ok(Some(tx_info));
+        }
+
+        let snapshot = self.store.get_tx_snapshot(tx_id)?;
+        let Some((tx, block_info)) =
+            snapshot.and_then(|snapshot| Some((snapshot.tx, snapshot.block_info?)))
+        else {
+            return Ok(None);
+        };
+
+        self.with_external_chain(|chain| {
+            let tip = chain.last()?;
+            // Blocks below the ones kept are too deep to be replaced
+            let in_chain = block_info.height <= tip.height
+                && chain
+                    .iter()
+                    .find(|b| b.height == block_info.height)
+                    .is_none_or(|b| b.hash == block_info.hash);
+
+            Some(TransactionInfo {
+                tx,
+                confirmations: if in_chain {
+                    tip.height - block_info.height + 1
+                } else {
+                    0
+                },
+                block_info: FullBlock {
+                    orphan: !in_chain,
+                    ..block_info
+                },
+            })
+        })
+    }
+
+    /// Health score of the last ticks, see [`HealthScore`].
+    pub fn health(&self) -> HealthScore {
+        self.health.score()
+    }
+
+    /// Recomputes the health score at the end of a tick that took `duration`. When the monitor
+    /// enters or leaves its degraded mode, a `MonitorNews::HealthStateChanged` news is sent.
+    fn update_health(&self, duration: Duration) -> Result<(), MonitorError> {
+        let Some(health) = self.health.end_tick(duration, &self.settings.health) else {
+            return Ok(());
+        };
+
+        match health.state {
+            HealthState::Degraded => warn!(
+                "Monitor degraded, snapshot news and {} blocks per tick | {:?}",
+                self.max_blocks_per_tick(),
+                health
+            ),
+            HealthState::Healthy => info!("Monitor recovered | {:?}", health),
+        }
+
+        // News need a block hash, there is none before the first tick
+        let current_block_hash = self
+            .get_current_block()?
+            .map(|block| block.hash)
+            .unwrap_or_else(BlockHash::all_zeros);
+
+        self.write_news(
+            MonitoredTypes::HealthStateChanged(health.state, health.score),
+            current_block_hash,
+            None,
+        )
+    }
+
+    /// Blocks processed by a tick, fewer in degraded mode.
+    fn max_blocks_per_tick(&self) -> u32 {
+        let max_blocks_per_tick = self.settings.max_blocks_per_tick.max(1);
+
+        if self.health.is_degraded() {
+            max_blocks_per_tick.min(self.settings.health.degraded_max_blocks_per_tick.max(1))
+        } else {
+            max_blocks_per_tick
+        }
+    }
+
+    /// Resolution of the news, from the snapshots in degraded mode so reading news doesn't call
+    /// the indexer.
+    fn news_resolution(&self) -> Resolution {
+        match self.settings.news_resolution {
+            Resolution::Fresh if self.health.is_degraded() => Resolution::Snapshot,
+            resolution => resolution,
+        }
+    }
+
+    /// Sends the news written since the last tick to the news receivers, dropping the senders
+    /// whose receiver is gone. The news stay in the store, so a news that can not be built is
+    /// skipped with a warning instead of failing the tick.
+    fn publish_news(&self) {
+        let unpublished = self.unpublished_news.take();
+        if unpublished.is_empty() {
+            return;
+        }
+
+        let mut news = Vec::new();
+        for data in unpublished {
+            match self.to_envelopes(vec![(0, data.clone())]) {
+                Ok(envelopes) => news.extend(envelopes.into_iter().map(|e| e.news)),
+                Err(e) => warn!("News not sent to the receivers: {:?} | {}", data, e),
+            }
+        }
+
+        self.news_senders
+            .borrow_mut()
+            .retain(|sender| news.iter().all(|n| sender.send(n.clone()).is_ok()));
+    }
+
+    fn process_tick(&self, shutdown: Option<&ShutdownToken>) -> Result<TickProgress, MonitorError> {
+        self.drain_commands();
+        self.check_news_sequence()?;
+        self.record_network()?;
+
+        self.tx_cache.borrow_mut().clear();
+        self.logged_chain.borrow_mut().clear();
+        self.orphan_disputes.borrow_mut().clear();
+        self.counters.add_indexer_call();
+        info_span!("indexer_tick").in_scope(|| self.indexer.tick())?;
+
+        let mut progress = TickProgress::default();
+        if self.is_pending_work()? {
+            progress.blocks_processed = self.process_best_block(shutdown)?;
+        } else {
+            debug!("No pending work, skipping block processing");
+        }
+
+        // What is left is checked again by the next tick
+        progress.stopped_early = shutdown.is_some_and(ShutdownToken::is_requested);
+        if !progress.stopped_early {
+            // The mempool changes between blocks, so it is checked on every tick
+            info_span!("mempool").in_scope(|| self.process_mempool())?;
+
+            self.sweep_expired_keys()?;
+            self.auto_prune()?;
+        }
+
+        progress.monitor_height = self.get_monitor_height()?;
+
+        Ok(progress)
+    }
+
+    /// Runs `recover_news_sequence` on the first tick after startup.
+    fn check_news_sequence(&self) -> Result<(), MonitorError> {
+        if !self.news_sequence_checked.get() {
+            self.recover_news_sequence()?;
+            self.news_sequence_checked.set(true);
+        }
+
+        Ok(())
+    }
+
+    /// Records the network of the node in the store on the first tick after startup, when none
+    /// is recorded yet. Nothing is recorded without a node.
+    fn record_network(&self) -> Result<(), MonitorError> {
+        if self.network_recorded.get() {
+            return Ok(());
+        }
+
+        if let Some(node) = &self.node {
+            if self.store.get_network()?.is_none() {
+                self.store.set_network(node.get_network()?)?;
+            }
+        }
+        self.network_recorded.set(true);
+
+        Ok(())
+    }
+
+    /// Detects a store restored from a backup, where the news sequence counter went back behind
+    /// the stored news. The counter is moved past them, a new epoch starts and a
+    /// `MonitorNews::SequenceEpochChanged` news is sent so consumers can re-sync.
+    ///
+    /// A store restored whole from a backup is not detected, its counter is consistent with its
+    /// news (see [`MonitorStoreApi::recover_news_sequence`]). Consumers have to be re-synced by
+    /// hand after such a restore.
+    ///
+    /// Runs on the first tick after startup.
+    pub fn recover_news_sequence(&self) -> Result<(), MonitorError> {
+        let Some((old_epoch, new_epoch)) = self.store.recover_news_sequence()? else {
+            return Ok(());
+        };
+
+        warn!(
+            "News sequence went back, store restored from a backup | Epoch({} -> {})",
+            old_epoch, new_epoch
+        );
+
+        // News need a block hash, there is none before the first tick
+        let current_block_hash = self
+            .get_current_block()?
+            .map(|block| block.hash)
+            .unwrap_or_else(BlockHash::all_zeros);
+
+        self.emit_news(
+            MonitoredTypes::SequenceEpochChanged(old_epoch, new_epoch),
+            current_block_hash,
+            None,
+        )?;
+
+        Ok(())
+    }
+
+    pub fn cancel(&self, data: TypesToMonitor) -> Result<(), MonitorError> {
+        self.cancel_with_policy(data, CancelNewsPolicy::default())
+    }
+
+    pub fn cancel_with_policy(
+        &self,
+        data: TypesToMonitor,
+        policy: CancelNewsPolicy,
+    ) -> Result<(), MonitorError> {
+        self.store.cancel_monitor_with_policy(data, policy)?;
+
+        Ok(())
+    }
+
+    pub fn cancel_permanent(&self, data: TypesToMonitor) -> Result<(), MonitorError> {
+        self.store
+            .cancel_monitor_permanent(data, CancelNewsPolicy::default())?;
+
+        Ok(())
+    }
+
+    pub fn resolve_manually(
+        &self,
+        target: TypesToMonitor,
+        note: String,
+    ) -> Result<(), MonitorError> {
+        // News need a block hash, there is none before the first tick
+        let current_block_hash = self
+            .get_current_block()?
+            .map(|block| block.hash)
+            .unwrap_or_else(BlockHash::all_zeros);
+        let metadata = AckMetadata {
+            actor: AckActor::ManualResolution,
+            note: note.clone(),
+        };
+
+        if !self
+            .store
+            .resolve_monitor_manually(target.clone(), metadata, current_block_hash)?
+        {
+            return Err(MonitorError::InvalidMonitor(format!(
+                "{:?} is not monitored",
+                target
+            )));
+        }
+
+        for monitor in SnoozeTarget::of_monitor(target.clone()) {
+            let event = NewsEvent::new(&MonitoredTypes::ManuallyResolved(monitor, note.clone()))
+                .at_block(current_block_hash);
+            event.emitted();
+            self.counters.add_news_emitted(&event);
+        }
+
+        info!("Resolved {:?} manually: {}", target, note);
+
+        Ok(())
+    }
+
+    pub fn list_trash(&self) -> Result<Vec<TrashEntry>, MonitorError> {
+        Ok(self.store.get_trash()?)
+    }
+
+    pub fn restore_from_trash(
+        &self,
+        selector: TrashSelector,
+    ) -> Result<Vec<TrashEntry>, MonitorError> {
+        let restored = self.store.restore_from_trash(&selector)?;
+
+        if !restored.is_empty() {
+            info!(
+                "Restored {} canceled monitors from the trash | {:?}",
+                restored.len(),
+                selector
+            );
+        }
+
+        Ok(restored)
+    }
+
+    pub fn prune(&self, older_than_height: BlockHeight) -> Result<PruneReport, MonitorError> {
+        let report = self.store.prune(older_than_height)?;
+
+        if report != PruneReport::default() {
+            info!(
+                "Pruned the store below height {} | {:?}",
+                older_than_height, report
+            );
+        }
+
+        Ok(report)
+    }
+
+    pub fn get_monitors(&self, include_inactive: bool) -> Result<Vec<MonitorInfo>, MonitorError> {
+        Ok(self.store.get_monitor_infos(include_inactive)?)
+    }
+
+    /// Runs `f` on the active monitors of `kind` in the canonical order. They are read from the
+    /// store a page at a time, so a large set is never loaded at once as with `get_monitors`.
+    /// Stops at the first error of `f`.
+    pub fn for_each_monitor(
+        &self,
+        kind: MonitorKind,
+        mut f: impl FnMut(TypesToMonitorStore) -> Result<(), MonitorError>,
+    ) -> Result<(), MonitorError> {
+        let mut cursor = self.store.monitor_cursor(kind)?;
+
+        while !cursor.is_done() {
+            for monitor in self.store.next_monitors(&mut cursor, MONITOR_PAGE_ITEMS)? {
+                f(monitor)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    pub fn monitor_history(
+        &self,
+        monitor: &TypesToMonitorStore,
+    ) -> Result<Vec<MonitorEvent>, MonitorError> {
+        Ok(self.store.get_monitor_history(monitor)?)
+    }
+
+    pub fn outpoints_spent_by(&self, spender: &Txid) -> Result<Vec<(Txid, u32)>, MonitorError> {
+        Ok(self.store.get_outpoints_spent_by(*spender)?)
+    }
+
+    pub fn find_monitors_for_tx(
+        &self,
+        tx_id: &Txid,
+    ) -> Result<Vec<TypesToMonitorStore>, MonitorError> {
+        Ok(self.store.find_monitors_for_tx(*tx_id)?)
+    }
+
+    pub fn reactivate(&self, data: TypesToMonitor) -> Result<(), MonitorError> {
+        self.store.reactivate_monitor(data)?;
+
+        Ok(())
+    }
+
+    pub fn replace_monitored_tx(&self, old: Txid, new: Txid) -> Result<(), MonitorError> {
+        if old == new {
+            return Err(MonitorError::InvalidMonitor(
+                "the replacement must be a different transaction".to_string(),
+            ));
+        }
+
+        // News need a block hash, there is none before the first tick
+        let current_block_hash = self
+            .get_current_block()?
+            .map(|block| block.hash)
+            .unwrap_or_else(BlockHash::all_zeros);
+        let tombstone_expires_at =
+            self.get_monitor_height()? + self.settings.max_monitoring_confirmations;
+
+        self.store.replace_transaction_monitor(
+            old,
+            new,
+            current_block_hash,
+            tombstone_expires_at,
+        )?;
+
+        // The metadata follows the entries moved to the new transaction
+        let metadata = self.store.get_monitor_metadata()?;
+        for entry in &metadata {
+            if let SnoozeTarget::Transaction(tx_id, context) = &entry.target {
+                let replacement = SnoozeTarget::Transaction(new, context.clone());
+                if *tx_id == old && !metadata.iter().any(|e| e.target == replacement) {
+                    self.store
+                        .set_monitor_metadata(vec![replacement], entry.metadata.clone())?;
+                }
+            }
+        }
+
+        info!("Replaced Transaction({}) with Transaction({})", old, new);
+
+        Ok(())
+    }
+
+    pub fn snooze(&self, target: TypesToMonitor, blocks: u32) -> Result<(), MonitorError> {
+        if blocks == 0 {
+            return Err(MonitorError::InvalidMonitor(
+                "a snooze must last at least one block".to_string(),
+            ));
+        }
+
+        let monitors = self.store.get_monitors()?;
+
+        let targets = SnoozeTarget::of_monitor(target);
+
+        for target in &targets {
+            let is_active = monitors.iter().any(|monitor| match (target, monitor) {
+                (
+                    SnoozeTarget::Transaction(tx_id, extra_data),
+                    TypesToMonitorStore::Transaction(t, e, _, _),
+                ) => t == tx_id && e == extra_data,
+                (
+                    SnoozeTarget::SpendingUTXOTransaction(tx_id, vout, extra_data),
+                    TypesToMonitorStore::SpendingUTXOTransaction(t, v, e, ..),
+                ) => t == tx_id && v == vout && e == extra_data,
+                (SnoozeTarget::RskPegin, TypesToMonitorStore::RskPegin(..)) => true,
+                (SnoozeTarget::NewBlock, TypesToMonitorStore::NewBlock) => true,
+                (
+                    SnoozeTarget::ScriptPubKey(script_pubkey, extra_data),
+                    TypesToMonitorStore::ScriptPubKey(s, e, ..),
+                ) => s == script_pubkey && e == extra_data,
+                (
+                    SnoozeTarget::OpReturnPrefix(prefix, extra_data),
+                    TypesToMonitorStore::OpReturnPrefix(p, e),
+                ) => p == prefix && e == extra_data,
+                _ => false,
+            });
+
+            if !is_active {
+                return Err(MonitorError::InvalidMonitor(format!(
+                    "{:?} is not monitored",
+                    target
+                )));
+            }
+        }
+
+        let until = self.get_monitor_height()? + blocks;
+
+        for target in targets {
+            info!("Snoozed {:?} until Height({})", target, until);
+            self.store.snooze_monitor(target, until)?;
+        }
+
+        Ok(())
+    }
+
+    pub fn get_snoozes(&self) -> Result<Vec<SnoozeEntry>, MonitorError> {
+        Ok(self.store.get_snoozes()?)
+    }
+
+    pub fn last_reorg_impact(&self) -> Result<Option<ReorgImpact>, MonitorError> {
+        Ok(self.store.get_last_reorg_impact()?)
+    }
+
+    pub fn register_composite(
+        &self,
+        id: String,
+        all_of: Vec<NewsPredicate>,
+    ) -> Result<(), MonitorError> {
+        if all_of.is_empty() {
+            return Err(MonitorError::InvalidMonitor(format!(
+                "composite rule {id} has no predicates"
+            )));
+        }
+
+        let mut rules = self.store.get_composite_rules()?;
+        if rules.iter().any(|rule| rule.id == id) {
+            return Err(MonitorError::InvalidMonitor(format!(
+                "composite rule {id} is already registered"
+            )));
+        }
+
+        info!(
+            "Registered composite rule {} | Predicates({})",
+            id,
+            all_of.len()
+        );
+        rules.push(CompositeRule {
+            id,
+            satisfied: vec![false; all_of.len()],
+            all_of,
+            emitted_at: None,
+        });
+        self.store.set_composite_rules(rules)?;
+
+        Ok(())
+    }
+
+    pub fn cancel_composite(&self, id: &str) -> Result<(), MonitorError> {
+        let mut rules = self.store.get_composite_rules()?;
+        let len = rules.len();
+        rules.retain(|rule| rule.id != id);
+
+        if rules.len() == len {
+            return Err(MonitorError::InvalidMonitor(format!(
+                "composite rule {id} is not registered"
+            )));
+        }
+
+        info!("Canceled composite rule {}", id);
+        self.store.set_composite_rules(rules)?;
+
+        Ok(())
+    }
+
+    pub fn get_composites(&self) -> Result<Vec<CompositeRule>, MonitorError> {
+        Ok(self.store.get_composite_rules()?)
+    }
+
+    /// Writes a news, or holds it back if its monitor is snoozed.
+    fn emit_news(
+        &self,
+        data: MonitoredTypes,
+        current_block_hash: BlockHash,
+        confirmations: Option<u32>,
+    ) -> Result<(), MonitorError> {
+        if self.store.defer_news(data.clone())? {
+            debug!("News held back by snooze: {:?}", data);
+            return Ok(());
+        }
+
+        self.write_news(data, current_block_hash, confirmations)
+    }
+
+    /// Writes the news to the store, logging and counting it with the same [`NewsEvent`]. A news
+    /// the store already had for the block is neither logged, counted nor sent again.
+    fn write_news(
+        &self,
+        data: MonitoredTypes,
+        current_block_hash: BlockHash,
+        confirmations: Option<u32>,
+    ) -> Result<(), MonitorError> {
+        let event = NewsEvent::new(&data)
+            .at_block(current_block_hash)
+            .with_confirmations(confirmations);
+        let _write = debug_span!("write_news", kind = event.kind).entered();
+
+        let written = match self.processed_block.get() {
+            Some(block) if block.hash == current_block_hash => {
+                self.store
+                    .update_news_at(data.clone(), current_block_hash, block.height)?
+            }
+            _ => self.store.update_news(data.clone(), current_block_hash)?,
+        };
+        // The same news already stored for the block is not counted or sent again
+        if !written {
+            debug!("News already stored for the block: {:?}", data);
+            return Ok(());
+        }
+        event.emitted();
+        self.counters.add_news_emitted(&event);
+
+        let disputed = {
+            let disputes = self.orphan_disputes.borrow();
+            !disputes.is_empty() && news_tx_ids(&data).any(|tx_id| disputes.contains(&tx_id))
+        };
+        if disputed && self.store.mark_news_orphan_disputed(&data)? {
+            warn!("News decided on a disputed orphan flag: {:?}", data);
+        }
+
+        if self.settings.strict_detections && self.unexpected_detection.borrow().is_none() {
+            if let Some(detection) = self.find_unexpected_detection(&data)? {
+                warn!("Unexpected detection in strict mode: {}", detection);
+                self.unexpected_detection.replace(Some(detection));
+            }
+        }
+
+        if !self.news_senders.borrow().is_empty() {
+            let mut unpublished = self.unpublished_news.borrow_mut();
+            if !unpublished.contains(&data) {
+                unpublished.push(data);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Detection of `data` if it is unexpected in strict mode: a news of a monitor not registered
+    /// as expected, or a pegin or OP_RETURN detection out of `strict_allowed_txids`. The news
+    /// that don't come from a monitor, like reorgs, are never unexpected.
+    fn find_unexpected_detection(
+        &self,
+        data: &MonitoredTypes,
+    ) -> Result<Option<DetectionDescriptor>, MonitorError> {
+        let Some(monitor) = MonitorStore::snooze_target(data) else {
+            return Ok(None);
+        };
+
+        let tx_id = match data {
+            MonitoredTypes::Transaction(tx_id, _)
+            | MonitoredTypes::OrphanedTransaction(tx_id, _)
+            | MonitoredTypes::RskPeginTransaction(tx_id, _)
+            | MonitoredTypes::SpendingUTXOTransaction(.., tx_id)
+            | MonitoredTypes::SpendingUTXOUnconfirmed(.., tx_id)
+            | MonitoredTypes::MinorSpend(.., tx_id)
+            | MonitoredTypes::ScriptPubKeyTransaction(_, _, tx_id, _)
+            | MonitoredTypes::OpReturnTransaction(_, _, tx_id, _) => Some(*tx_id),
+            _ => None,
+        };
+
+        // The rules match transactions nobody registered, only the allow-list makes them expected
+        let expected = match (&monitor, tx_id) {
+            (SnoozeTarget::RskPegin | SnoozeTarget::OpReturnPrefix(..), Some(tx_id)) => {
+                self.settings.strict_allowed_txids.contains(&tx_id)
+            }
+            _ => self.store.is_expected(&monitor)?,
+        };
+
+        Ok((!expected).then_some(DetectionDescriptor { monitor, tx_id }))
+    }
+
+    /// Deactivates a Transaction or SpendingUTXO monitor whose expiry height was reached without
+    /// a detection and sends a `MonitorNews::MonitorExpired` news.
+    fn expire_monitor(
+        &self,
+        monitor: TypesToMonitor,
+        expires_at_height: BlockHeight,
+        current_block_hash: BlockHash,
+    ) -> Result<(), MonitorError> {
+        let (tx_id, vout, extra_data) = match &monitor {
+            TypesToMonitor::Transactions(tx_ids, extra_data, _, _) => {
+                (tx_ids[0], None, extra_data.clone())
+            }
+            TypesToMonitor::SpendingUTXOTransaction(tx_id, vout, extra_data, ..) => {
+                (*tx_id, Some(*vout), extra_data.clone())
+            }
+            _ => return Ok(()),
+        };
+
+        self.store.deactivate_monitor(monitor)?;
+        self.count_deactivation();
+
+        info!(
+            "Monitor expired | Transaction({}) | Vout({:?}) | Height({})",
+            tx_id, vout, expires_at_height
+        );
+
+        self.emit_news(
+            MonitoredTypes::MonitorExpired(tx_id, vout, extra_data, expires_at_height),
+            current_block_hash,
+            None,
+        )?;
+
+        Ok(())
+    }
+
+    /// Whether the block `hash` at `height` is in the chain of `tip`. The blocks between them are
+    /// asked to the indexer, a block it doesn't know at `height` leaves the reorg to be found by
+    /// the monitored transactions that moved.
+    fn is_ancestor(
+        &self,
+        height: BlockHeight,
+        hash: BlockHash,
+        tip: &FullBlock,
+    ) -> Result<bool, MonitorError> {
+        if tip.height <= height {
+            return Ok(tip.hash == hash);
+        }
+        if tip.height == height + 1 {
+            return Ok(tip.prev_hash == hash);
+        }
+
+        Ok(self
+            .get_block_by_height(height)?
+            .is_none_or(|block| block.hash == hash))
+    }
+
+    fn count_deactivation(&self) {
+        self.tick_deactivations
+            .set(self.tick_deactivations.get() + 1);
+    }
+
+    /// Sends the news held back by the snoozes that ended before `indexer_best_block_height`.
+    /// A snooze is only removed once its news are written.
+    fn process_expired_snoozes(
+        &self,
+        indexer_best_block_height: BlockHeight,
+        current_block_hash: BlockHash,
+    ) -> Result<(), MonitorError> {
+        for snooze in self.store.get_expired_snoozes(indexer_best_block_height)? {
+            info!(
+                "Snooze of {:?} ended | Held back news({})",
+                snooze.target,
+                snooze.pending.len()
+            );
+
+            for news in snooze.pending {
+                self.write_news(news, current_block_hash, None)?;
+            }
+            self.store.end_snooze(&snooze.target)?;
+        }
+
+        Ok(())
+    }
+
+    /// Builds the report of a reorg processed in this tick, comparing the news and transaction
+    /// snapshots from before the monitors were processed with the new branch.
+    ///
+    /// A reorg is detected when the last processed block is no longer an ancestor of the new tip,
+    /// the new branch being shorter, as long or longer, or when a monitored transaction was found
+    /// in another block or orphaned. Only transactions
+    /// found within `max_monitoring_confirmations` of the previous tip are checked.
+    fn process_reorg(
+        &self,
+        (old_tip_height, old_tip): (BlockHeight, BlockHash),
+        news_before: Vec<(MonitoredTypes, NewsAck)>,
+        snapshots_before: Vec<TransactionStatus>,
+        new_tip: &FullBlock,
+    ) -> Result<(), MonitorError> {
+        fn block_of(status: &TransactionStatus) -> Option<BlockHash> {
+            status
+                .block_info
+                .as_ref()
+                .filter(|block| !block.orphan)
+                .map(|block| block.hash)
+        }
+
+        let snapshots_after = self.store.get_tx_snapshots()?;
+
+        let tip_replaced = !self.is_ancestor(old_tip_height, old_tip, new_tip)?;
+        // A transaction confirmed after being seen in the mempool didn't move, neither did one
+        // confirmed again after its orphaned block was reported
+        let tx_moved = snapshots_before
+            .iter()
+            .filter(|before| {
+                before.status != TransactionBlockchainStatus::Mempool
+                    && before.status != TransactionBlockchainStatus::Orphan
+            })
+            .any(|before| {
+                snapshots_after
+                    .iter()
+                    .any(|after| after.tx_id == before.tx_id && block_of(after) != block_of(before))
+            });
+
+        if !tip_replaced && !tx_moved {
+            return Ok(());
+        }
+
+        let window_start =
+            old_tip_height.saturating_sub(self.settings.max_monitoring_confirmations);
+        let blocks_before: HashMap<Txid, BlockHash> = snapshots_before
+            .iter()
+            .filter(|status| {
+                status
+                    .block_info
+                    .as_ref()
+                    .is_some_and(|block| block.height >= window_start)
+            })
+            .filter_map(|status| block_of(status).map(|hash| (status.tx_id, hash)))
+            .collect();
+
+        // Snapshots updated in this tick already have the new branch, the rest is asked to the indexer
+        let block_after = |tx_id: &Txid| -> Result<Option<BlockHash>, MonitorError> {
+            if let Some(after) = snapshots_after.iter().find(|s| s.tx_id == *tx_id) {
+                if block_of(after) != blocks_before.get(tx_id).copied() {
+                    return Ok(block_of(after));
+                }
+            }
+
+            let tx_info = self.get_tx_up_to(tx_id, new_tip.height)?;
+            Ok(tx_info
+                .filter(|info| !info.block_info.orphan)
+                .map(|info| info.block_info.hash))
+        };
+
+        let news_after = self.store.get_news_acks()?;
+
+        let mut impact = ReorgImpact {
+            old_tip_height,
+            old_tip,
+            new_tip_height: new_tip.height,
+            new_tip: new_tip.hash,
+            moved_transactions: vec![],
+            disappeared_spends: vec![],
+            changed_spends: vec![],
+            invalidated_pegins: vec![],
+            reopened_news: vec![],
+        };
+        let mut impacted = HashSet::new();
+
+        for (news, _) in &news_before {
+            match news {
+                MonitoredTypes::Transaction(tx_id, _)
+                | MonitoredTypes::RskPeginTransaction(tx_id, _)
+                | MonitoredTypes::ScriptPubKeyTransaction(_, _, tx_id, _) => {
+                    let Some(before) = blocks_before.get(tx_id).copied() else {
+                        continue;
+                    };
+                    if impacted.contains(tx_id) {
+                        continue;
+                    }
+
+                    let after = block_after(tx_id)?;
+                    if after == Some(before) {
+                        continue;
+                    }
+
+                    impacted.insert(*tx_id);
+                    let moved = MovedTransaction {
+                        tx_id: *tx_id,
+                        before,
+                        after,
+                    };
+
+                    match news {
+                        MonitoredTypes::RskPeginTransaction(..) if after.is_none() => {
+                            impact.invalidated_pegins.push(moved)
+                        }
+                        _ => impact.moved_transactions.push(moved),
+                    }
+                }
+                MonitoredTypes::SpendingUTXOTransaction(
+                    tx_id,
+                    utxo_index,
+                    extra_data,
+                    spender_before,
+                )
+                | MonitoredTypes::MinorSpend(tx_id, utxo_index, extra_data, spender_before) => {
+                    let Some(before) = blocks_before.get(spender_before).copied() else {
+                        continue;
+                    };
+
+                    // A spend of the new branch may be minor when the replaced one wasn't
+                    let spender_after = news_after.iter().find_map(|(news, _)| match news {
+                        MonitoredTypes::SpendingUTXOTransaction(t, v, e, spender)
+                        | MonitoredTypes::MinorSpend(t, v, e, spender)
+                            if t == tx_id && v == utxo_index && e == extra_data =>
+                        {
+                            Some(*spender)
+                        }
+                        _ => None,
+                    });
+
+                    let mut spend = SpendChange {
+                        tx_id: *tx_id,
+                        utxo_index: *utxo_index,
+                        extra_data: extra_data.clone(),
+                        spender_before: *spender_before,
+                        before,
+                        spender_after: None,
+                        after: None,
+                    };
+
+                    match spender_after {
+                        Some(spender_after) if spender_after != *spender_before => {
+                            spend.spender_after = Some(spender_after);
+                            spend.after = block_after(&spender_after)?;
+                            impacted.insert(spender_after);
+                            impacted.insert(*spender_before);
+                            impact.changed_spends.push(spend);
+                        }
+                        _ => match block_after(spender_before)? {
+                            None => {
+                                impacted.insert(*spender_before);
+                                impact.disappeared_spends.push(spend);
+                            }
+                            Some(after) if after != before => {
+                                impacted.insert(*spender_before);
+                                impact.moved_transactions.push(MovedTransaction {
+                                    tx_id: *spender_before,
+                                    before,
+                                    after: Some(after),
+                                });
+                            }
+                            Some(_) => {}
+                        },
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        // Acknowledged news of the impacted transactions sent again on the new branch
+        for (news, ack) in &news_after {
+            let tx_id = match news {
+                MonitoredTypes::Transaction(tx_id, _)
+                | MonitoredTypes::RskPeginTransaction(tx_id, _)
+                | MonitoredTypes::SpendingUTXOTransaction(_, _, _, tx_id)
+                | MonitoredTypes::MinorSpend(_, _, _, tx_id)
+                | MonitoredTypes::ScriptPubKeyTransaction(_, _, tx_id, _) => tx_id,
+                _ => continue,
+            };
+            if ack.acknowledged || !impacted.contains(tx_id) {
+                continue;
+            }
+
+            let id = MonitorStore::news_fingerprint_id(news);
+            let reopened = news_before.iter().find(|(before, before_ack)| {
+                before_ack.acknowledged && MonitorStore::news_fingerprint_id(before) == id
+            });
+
+            if let Some((_, before_ack)) = reopened {
+                impact.reopened_news.push(ReopenedNews {
+                    news: news.clone(),
+                    before: before_ack.block_hash,
+                    after: ack.block_hash,
+                });
+            }
+        }
+
+        let summary = impact.summary();
+
+        warn!(
+            "Reorg | Tip({}:{} -> {}:{}) | {:?}",
+            old_tip_height, old_tip, new_tip.height, new_tip.hash, summary
+        );
+
+        self.store.save_reorg_impact(impact)?;
+        self.emit_news(
+            MonitoredTypes::Reorg(old_tip, new_tip.hash, summary),
+            new_tip.hash,
+            None,
+        )?;
+
+        Ok(())
+    }
+
+    /// Warns when a transaction replaced with `replace_monitored_tx` confirms anyway,
+    /// until its tombstone expires.
+    fn process_replaced_transactions(
+        &self,
+        indexer_best_block_height: BlockHeight,
+        current_block_hash: BlockHash,
+    ) -> Result<(), MonitorError> {
+        for replaced in self.store.get_replaced_transactions()? {
+            if indexer_best_block_height > replaced.expires_at {
+                self.store.remove_replaced_transaction(replaced.old_tx_id)?;
+                continue;
+            }
+
+            let Some(tx) = self.get_tx_up_to(&replaced.old_tx_id, indexer_best_block_height)?
+            else {
+                continue;
+            };
+
+            if tx.block_info.orphan || tx.confirmations == 0 {
+                continue;
+            }
+
+            warn!(
+                "Replaced Transaction({}) confirmed instead of Transaction({}) | Height({})",
+                replaced.old_tx_id, replaced.new_tx_id, tx.block_info.height
+            );
+
+            for context in replaced.contexts {
+                self.write_news(
+                    MonitoredTypes::ReplacedTransactionConfirmed(
+                        replaced.old_tx_id,
+                        replaced.new_tx_id,
+                        context,
+                    ),
+                    current_block_hash,
+                    Some(tx.confirmations),
+                )?;
+            }
+
+            self.store.remove_replaced_transaction(replaced.old_tx_id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Settles the races of the `Alternatives` monitors: the first alternative to reach the
+    /// confirmation threshold wins, the monitors of the others are canceled and an
+    /// `AlternativeResolved` news is sent. Until the winner reaches `max_monitoring_confirmations`
+    /// a reorg taking it below the threshold opens the race again.
+    fn process_alternatives(
+        &self,
+        indexer_best_block_height: BlockHeight,
+        current_block_hash: BlockHash,
+    ) -> Result<(), MonitorError> {
+        let threshold = self.settings.confirmation_threshold;
+        let confirmations_of = |tx_id: &Txid| -> Result<u32, MonitorError> {
+            Ok(self
+                .get_tx_up_to(tx_id, indexer_best_block_height)?
+                .filter(|tx| !tx.block_info.orphan)
+                .map_or(0, |tx| tx.confirmations))
+        };
+
+        for mut race in self.store.get_alternatives()? {
+            if let Some(winner) = race.winner {
+                let confirmations = confirmations_of(&winner)?;
+                if confirmations >= threshold {
+                    if confirmations >= self.settings.max_monitoring_confirmations {
+                        self.store.remove_alternatives(&race)?;
+                    }
+                    continue;
+                }
+
+                warn!(
+                    "Alternative Transaction({}) went below the confirmation threshold, race reopened | Confirmations({})",
+                    winner, confirmations
+                );
+                self.store.resolve_alternatives(&race, None)?;
+                race.winner = None;
+            }
+
+            // Ties go to the alternative with the most confirmations, then to the first registered
+            let mut winner: Option<(Txid, u32)> = None;
+            for tx_id in &race.tx_ids {
+                let confirmations = confirmations_of(tx_id)?;
+                if confirmations >= threshold && winner.is_none_or(|(_, c)| confirmations > c) {
+                    winner = Some((*tx_id, confirmations));
+                }
+            }
+            let Some((winner, confirmations)) = winner else {
+                continue;
+            };
+
+            info!(
+                "Alternative Transaction({}) won the race | Confirmations({})",
+                winner, confirmations
+            );
+            self.store.resolve_alternatives(&race, Some(winner))?;
+            self.write_news(
+                MonitoredTypes::AlternativeResolved(
+                    winner,
+                    race.losers(winner),
+                    race.extra_data.clone(),
+                ),
+                current_block_hash,
+                Some(confirmations),
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Evaluates the composite rules not sent yet at the processed block, sending the news of the
+    /// ones with every predicate satisfied. A predicate satisfied at an earlier block and not
+    /// anymore was undone by a reorg, and the rule waits for it again.
+    fn process_composites(
+        &self,
+        indexer_best_block_height: BlockHeight,
+        current_block_hash: BlockHash,
+    ) -> Result<(), MonitorError> {
+        let mut rules = self.store.get_composite_rules()?;
+        if rules.iter().all(|rule| rule.emitted_at.is_some()) {
+            return Ok(());
+        }
+
+        let mut changed = false;
+        let mut completed = vec![];
+
+        for rule in rules.iter_mut().filter(|rule| rule.emitted_at.is_none()) {
+            let satisfied = rule
+                .all_of
+                .iter()
+                .map(|predicate| self.is_satisfied(predicate, indexer_best_block_height))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            if rule
+                .satisfied
+                .iter()
+                .zip(&satisfied)
+                .any(|(before, now)| *before && !now)
+            {
+                info!(
+                    "Composite rule {} reset by a reorg at Height({})",
+                    rule.id, indexer_best_block_height
+                );
+            }
+
+            if satisfied.iter().all(|s| *s) {
+                completed.push(rule.id.clone());
+            }
+            if rule.satisfied != satisfied {
+                rule.satisfied = satisfied;
+                changed = true;
+            }
+        }
+
+        if changed {
+            self.store.set_composite_rules(rules)?;
+        }
+
+        for id in completed {
+            info!(
+                "Composite rule {} satisfied at Height({})",
+                id, indexer_best_block_height
+            );
+            self.write_news(MonitoredTypes::Composite(id), current_block_hash, None)?;
+        }
+
+        Ok(())
+    }
+
+    /// Whether `predicate` holds at the processed block `height`.
+    fn is_satisfied(
+        &self,
+        predicate: &NewsPredicate,
+        height: BlockHeight,
+    ) -> Result<bool, MonitorError> {
+        let (tx_id, min_confirmations, pegin) = match predicate {
+            NewsPredicate::Transaction {
+                tx_id,
+                min_confirmations,
+            } => (*tx_id, *min_confirmations, false),
+            NewsPredicate::RskPegin {
+                tx_id,
+                min_confirmations,
+            } => (*tx_id, *min_confirmations, true),
+            NewsPredicate::SpendingUTXO {
+                tx_id,
+                vout,
+                min_confirmations,
+            } => match self.store.get_spending_utxo_spender(*tx_id, *vout)? {
+                Some(spender) => (spender, *min_confirmations, false),
+                None => return Ok(false),
+            },
+        };
+
+        Ok(self
+            .get_tx_up_to(&tx_id, height)?
+            .filter(|tx_info| !tx_info.block_info.orphan)
+            .is_some_and(|tx_info| {
+                tx_info.confirmations >= min_confirmations.max(1)
+                    && (!pegin || parse_pegin_data(&tx_info.tx).is_some())
+            }))
+    }
+
+    /// Removes expired keys from the store, at most once every `sweep_interval_blocks`.
+    fn sweep_expired_keys(&self) -> Result<(), MonitorError> {
+        let monitor_height = self.get_monitor_height()?;
+
+        if let Some(last_sweep_height) = self.last_sweep_height.get() {
+            if monitor_height < last_sweep_height + self.settings.sweep_interval_blocks {
+                return Ok(());
+            }
+        }
+
+        let removed = self.store.sweep_expired(monitor_height)?;
+
+        let trash_purged_before =
+            (monitor_height + 1).saturating_sub(self.settings.retention.trash_blocks);
+        let purged = self.store.purge_trash(trash_purged_before)?;
+        if purged > 0 {
+            debug!(
+                "Purged {} canceled monitors from the trash at height {}",
+                purged, monitor_height
+            );
+        }
+
+        let history_pruned_before =
+            (monitor_height + 1).saturating_sub(self.settings.retention.news_history_blocks);
+        let pruned = self.store.prune_news_history(history_pruned_before)?;
+        if pruned > 0 {
+            debug!(
+                "Pruned {} news from the news history at height {}",
+                pruned, monitor_height
+            );
+        }
+
+        self.last_sweep_height.set(Some(monitor_height));
+
+        if removed > 0 {
+            debug!(
+                "Swept {} expired keys at height {}",
+                removed, monitor_height
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Prunes the store every `auto_prune_interval` blocks, of the inactive monitors and acked
+    /// news older than the interval, see [`Monitor::prune`].
+    fn auto_prune(&self) -> Result<(), MonitorError> {
+        let Some(interval) = self
+            .settings
+            .auto_prune_interval
+            .filter(|blocks| *blocks > 0)
+        else {
+            return Ok(());
+        };
+        let monitor_height = self.get_monitor_height()?;
+
+        match self.last_prune_height.get() {
+            Some(last_prune_height) if monitor_height < last_prune_height + interval => {}
+            // The first prune waits for a full interval of blocks
+            None => self.last_prune_height.set(Some(monitor_height)),
+            Some(_) => {
+                self.prune((monitor_height + 1).saturating_sub(interval))?;
+                self.last_prune_height.set(Some(monitor_height));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Processes the blocks the monitor is behind the indexer, returning how many. Stops before a
+    /// block once `shutdown` is requested.
+    fn process_best_block(&self, shutdown: Option<&ShutdownToken>) -> Result<u32, MonitorError> {
+        let stop_requested = || shutdown.is_some_and(ShutdownToken::is_requested);
+
+        self.counters.add_indexer_call();
+        let indexer_best_block = self.indexer.get_best_block()?;
+        let indexer_best_block = indexer_best_block.unwrap();
+        self.indexer_best_height.set(indexer_best_block.height);
+
+        // Behind the indexer, the blocks in between are processed in order so the monitors
+        // scanning blocks don't miss any, up to max_blocks_per_tick per tick. The first tick
+        // starts from the best block.
+        let monitor_height = self.get_monitor_height()?;
+        let is_first_tick = self.store.get_monitor_block_hash()?.is_none();
+
+        // Outputs spent before their monitor was registered, in the blocks already gone through
+        let scanned_height = if is_first_tick {
+            indexer_best_block.height.saturating_sub(1)
+        } else {
+            monitor_height
+        };
+        self.backfill_spending_utxo_monitors(scanned_height)?;
+
+        if stop_requested() {
+            return Ok(0);
+        }
+
+        if is_first_tick || indexer_best_block.height <= monitor_height + 1 {
+            self.process_block(indexer_best_block)?;
+            return Ok(1);
+        }
+
+        let last_height = indexer_best_block
+            .height
+            .min(monitor_height + self.max_blocks_per_tick());
+        let mut processed = 0;
+
+        for height in monitor_height + 1..=last_height {
+            if stop_requested() {
+                break;
+            }
+
+            let block = if height == indexer_best_block.height {
+                indexer_best_block.clone()
+            } else {
+                let Some(block) = self.get_block_by_height(height)? else {
+                    warn!(
+                        "Block at Height({}) not found in the indexer, processing its best block",
+                        height
+                    );
+                    self.process_block(indexer_best_block)?;
+                    return Ok(processed + 1);
+                };
+                block
+            };
+
+            self.process_block(block)?;
+            processed += 1;
+        }
+
+        Ok(processed)
+    }
+
+    /// Looks for the spends of the SpendingUTXO monitors registered since the last tick in the
+    /// blocks from the one of their transaction up to `last_height`, which the monitor went
+    /// through before they were registered. A spend found is recorded and reported as if it was
+    /// found when its block was processed; from then on the monitors only look at new blocks.
+    /// Monitors of transactions the indexer doesn't know (e.g. still unconfirmed) are not
+    /// backfilled.
+    fn backfill_spending_utxo_monitors(
+        &self,
+        last_height: BlockHeight,
+    ) -> Result<(), MonitorError> {
+        let backfills = self.store.get_spending_utxo_backfills()?;
+        // Blocks shared by the monitors of the same transactions
+        let mut blocks: HashMap<BlockHeight, Option<FullBlock>> = HashMap::new();
+
+        for monitor in backfills {
+            let TypesToMonitorStore::SpendingUTXOTransaction(
+                target_tx_id,
+                target_utxo_index,
+                extra_data,
+                number_confirmation_trigger,
+                _,
+                value_gate,
+                _,
+            ) = monitor
+            else {
+                continue;
+            };
+
+            // The output can't be spent before the block of its transaction
+            let from_height = match self.get_indexed_tx(&target_tx_id)? {
+                Some(tx_info) => tx_info.block_info.height,
+                None => last_height + 1,
+            };
+
+            for height in from_height..=last_height {
+                let block = match blocks.get(&height) {
+                    Some(block) => block.clone(),
+                    None => {
+                        let block = self.get_block_by_height(height)?;
+                        blocks.insert(height, block.clone());
+                        block
+                    }
+                };
+                let Some(block) = block else {
+                    warn!(
+                        "Block at Height({}) not found in the indexer, not backfilled",
+                        height
+                    );
+                    continue;
+                };
+
+                let spender = block
+                    .txs
+                    .iter()
+                    .find(|tx| is_spending_output(tx, target_tx_id, target_utxo_index));
+
+                if let Some(spender) = spender {
+                    info!(
+                        "Backfill found the spend of SpendingUTXOTransaction({}:{}) | Spender({}) | Height({})",
+                        target_tx_id,
+                        target_utxo_index,
+                        spender.compute_txid(),
+                        height
+                    );
+                    self.process_spending_utxo_transaction(
+                        target_tx_id,
+                        target_utxo_index,
+                        extra_data.clone(),
+                        number_confirmation_trigger,
+                        value_gate,
+                        Some(spender.compute_txid()),
+                        height,
+                        block.hash,
+                    )?;
+                    break;
+                }
+            }
+
+            self.store.set_spending_utxo_backfill_done(
+                target_tx_id,
+                target_utxo_index,
+                &extra_data,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Outputs of `block_txs` paying to the scripts of the active ScriptPubKey monitors, read from
+    /// the index of their list. The scan set is kept from the previous blocks unless the scan generation of the store
+    /// moved since it was built.
+    fn scan_block<'a>(
+        &self,
+        block_txs: &'a [(Txid, TransactionRef)],
+    ) -> Result<HashMap<&'a ScriptBuf, Vec<ScannedOutput<'a>>>, MonitorError> {
+        let generation = self.store.get_scan_generation()?;
+        let mut scan_set = self.scan_set.borrow_mut();
+
+        let scan_set = match scan_set.take() {
+            Some(cached) if cached.generation == generation => scan_set.insert(cached),
+            _ => {
+                self.counters.add_scan_set_build();
+                let scripts = self.store.get_monitored_scripts()?;
+                scan_set.insert(ScanSet::new(generation, scripts))
+            }
+        };
+
+        Ok(scan_set.scan(block_txs))
+    }
+
+    /// Runs the monitors on `block` and moves the monitor height to it.
+    fn process_block(&self, mut indexer_best_block: FullBlock) -> Result<(), MonitorError> {
+        let indexer_best_block_height = indexer_best_block.height;
+        let current_block_hash = indexer_best_block.hash;
+        let _block = info_span!(
+            "block",
+            height = indexer_best_block_height,
+            hash = %current_block_hash
+        )
+        .entered();
+        self.processed_block.set(Some(ProcessedBlock {
+            hash: current_block_hash,
+            height: indexer_best_block_height,
+            prev_hash: indexer_best_block.prev_hash,
+        }));
+        self.logged_chain.borrow_mut().clear();
+
+        // The transactions are hashed and moved behind an Arc once, every detection of the tick shares them
+        let block_txs: Vec<(Txid, TransactionRef)> = std::mem::take(&mut indexer_best_block.txs)
+            .into_iter()
+            .map(|tx| (tx.compute_txid(), Arc::new(tx)))
+            .collect();
+
+        // Spender of each outpoint spent in the block, so a SpendingUTXO monitor is a lookup
+        // instead of a scan of the block, which matters with thousands of them during a catch-up
+        let block_spends: HashMap<OutPoint, Txid> = block_txs
+            .iter()
+            .flat_map(|(tx_id, tx)| {
+                tx.input
+                    .iter()
+                    .map(move |input| (input.previous_output, *tx_id))
+            })
+            .collect();
+
+        self.process_expired_snoozes(indexer_best_block_height, current_block_hash)?;
+
+        // Kept to build the reorg report once the monitors are processed
+        let previous_tip = match self.store.get_monitor_block_hash()? {
+            Some(hash) => Some((self.get_monitor_height()?, hash)),
+            None => None,
+        };
+        let news_before = self.store.get_news_acks()?;
+        let snapshots_before = self.store.get_tx_snapshots()?;
+
+        // Evaluated in the canonical order, so the news of the block get the same sequence
+        // whatever the order the monitors were registered in. The cheap kinds (new block and
+        // transactions) come first and every news is written to the store as soon as it is
+        // found, so a catch-up makes them visible block by block. The monitors are read a page at
+        // a time, a large set is never loaded at once.
+        let script_outputs = self.scan_block(&block_txs)?;
+
+        for kind in MonitorKind::ALL {
+            let _monitors = debug_span!("monitors", monitor_kind = ?kind).entered();
+
+            self.for_each_monitor(kind, |tx_type| {
+                let tx_id = match &tx_type {
+                    TypesToMonitorStore::Transaction(tx_id, ..)
+                    | TypesToMonitorStore::SpendingUTXOTransaction(tx_id, ..) => Some(*tx_id),
+                    _ => None,
+                };
+                let _monitor =
+                    debug_span!("monitor", txid = tx_id.map(tracing::field::display)).entered();
+
+                match tx_type {
+                    TypesToMonitorStore::Transaction(
+                        tx_id,
+                        extra_data,
+                        number_confirmation_trigger,
+                        expires_at_height,
+                    ) => {
+                        self.process_transaction_monitor(
+                            tx_id,
+                            extra_data.clone(),
+                            number_confirmation_trigger,
+                            indexer_best_block_height,
+                            current_block_hash,
+                        )?;
+
+                        if let Some(expires_at_height) =
+                            expires_at_height.filter(|height| *height <= indexer_best_block_height)
+                        {
+                            // Mined in a block of the best chain up to the expiry, orphans don't count
+                            let detected = self
+                                .get_tx_up_to(&tx_id, indexer_best_block_height)?
+                                .is_some_and(|tx| !tx.block_info.orphan);
+
+                            if !detected {
+                                self.expire_monitor(
+                                    TypesToMonitor::Transactions(
+                                        vec![tx_id],
+                                        extra_data,
+                                        number_confirmation_trigger,
+                                        None,
+                                    ),
+                                    expires_at_height,
+                                    current_block_hash,
+                                )?;
+                            }
+                        }
+                    }
+                    TypesToMonitorStore::RskPegin(
+                        number_confirmation_trigger,
+                        committee,
+                        packet_range,
+                        min_amount,
+                    ) => {
+                        self.process_rsk_pegin_transaction(
+                            number_confirmation_trigger,
+                            &committee,
+                            packet_range,
+                            min_amount,
+                            &block_txs,
+                            indexer_best_block_height,
+                            current_block_hash,
+                        )?;
+                    }
+                    TypesToMonitorStore::SpendingUTXOTransaction(
+                        target_tx_id,
+                        target_utxo_index,
+                        extra_data,
+                        number_confirmation_trigger,
+                        _,
+                        value_gate,
+                        expires_at_height,
+                    ) => {
+                        let spender_tx_id = block_spends
+                            .get(&OutPoint::new(target_tx_id, target_utxo_index))
+                            .copied();
+                        self.process_spending_utxo_transaction(
+                            target_tx_id,
+                            target_utxo_index,
+                            extra_data.clone(),
+                            number_confirmation_trigger,
+                            value_gate,
+                            spender_tx_id,
+                            indexer_best_block_height,
+                            current_block_hash,
+                        )?;
+
+                        // A spend found in this block or an earlier one is recorded by now
+                        let expired =
+                            expires_at_height.filter(|height| *height <= indexer_best_block_height);
+
+                        if let Some(expires_at_height) = expired {
+                            let spent = self
+                                .store
+                                .get_spending_utxo_spender(target_tx_id, target_utxo_index)?
+                                .is_some();
+
+                            if !spent {
+                                self.expire_monitor(
+                                    TypesToMonitor::SpendingUTXOTransaction(
+                                        target_tx_id,
+                                        target_utxo_index,
+                                        extra_data,
+                                        number_confirmation_trigger,
+                                        None,
+                                        None,
+                                        None,
+                                    ),
+                                    expires_at_height,
+                                    current_block_hash,
+                                )?;
+                            }
+                        }
+                    }
+                    TypesToMonitorStore::NewBlock => {
+                        self.emit_news(
+                            MonitoredTypes::NewBlock(
+                                current_block_hash,
+                                Some(indexer_best_block_height),
+                            ),
+                            current_block_hash,
+                            None,
+                        )?;
+                    }
+                    TypesToMonitorStore::ScriptPubKey(
+                        script_pubkey,
+                        extra_data,
+                        number_confirmation_trigger,
+                        filter,
+                    ) => {
+                        let outputs = script_outputs
+                            .get(&script_pubkey)
+                            .map(Vec::as_slice)
+                            .unwrap_or_default();
+                        self.process_script_pubkey_monitor(
+                            script_pubkey,
+                            extra_data,
+                            number_confirmation_trigger,
+                            filter,
+                            outputs,
+                            indexer_best_block_height,
+                            current_block_hash,
+                        )?;
+                    }
+                    TypesToMonitorStore::OpReturnPrefix(prefix, extra_data) => {
+                        self.process_op_return_prefix_monitor(
+                            &prefix,
+                            extra_data,
+                            &block_txs,
+                            indexer_best_block_height,
+                            current_block_hash,
+                        )?;
+                    }
+                }
+
+                Ok(())
+            })?;
+        }
+
+        self.process_replaced_transactions(indexer_best_block_height, current_block_hash)?;
+        self.process_alternatives(indexer_best_block_height, current_block_hash)?;
+        self.process_composites(indexer_best_block_height, current_block_hash)?;
+
+        if let Some(previous_tip) = previous_tip {
+            self.process_reorg(
+                previous_tip,
+                news_before,
+                snapshots_before,
+                &indexer_best_block,
+            )?;
+        }
+
+        self.process_block_work(&indexer_best_block)?;
+
+        let _update_height = info_span!("update_height").entered();
+
+        let processed_at_unix = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |elapsed| elapsed.as_secs());
+        self.store.append_chain_log(
+            ChainLogEntry {
+                height: indexer_best_block_height,
+                hash: current_block_hash,
+                prev_hash: indexer_best_block.prev_hash,
+                processed_at_unix,
+            },
+            self.settings.retention.chain_log_blocks,
+        )?;
+
+        self.store
+            .update_monitor_height(indexer_best_block_height)?;
+        self.store.update_monitor_block_hash(current_block_hash)?;
+
+        self.store.set_pending_work(false)?;
+
+        Ok(())
+    }
+
+    /// Keeps the bits of the processed blocks and, when the new tip comes from a branch that replaced
+    /// some of them, compares the work of both branches. A `MonitorNews::LowWorkReorg` is sent when
+    /// the new branch has less work than the blocks it replaced.
+    ///
+    /// The comparison is skipped when the bits of any block of either branch are not available, or
+    /// when some replaced block was never processed by the monitor.
+    fn process_block_work(&self, new_tip: &FullBlock) -> Result<(), MonitorError> {
+        let mut blocks = self.store.get_block_work()?;
+
+        if blocks.last().is_some_and(|last| last.hash == new_tip.hash) {
+            return Ok(());
+        }
+
+        let extends_last = blocks
+            .last()
+            .is_none_or(|last| last.hash == new_tip.prev_hash);
+
+        let mut fork_height = blocks.last().map_or(new_tip.height, |last| last.height);
+        let mut replaced = Vec::new();
+
+        if !extends_last {
+            // The processed blocks are walked down until one is still in the chain of the indexer
+            for block in blocks.iter().rev() {
+                if block.height < new_tip.height {
+                    let canonical = self.get_block_by_height(block.height)?;
+
+                    if canonical.is_some_and(|canonical| canonical.hash == block.hash) {
+                        break;
+                    }
+                }
+
+                fork_height = block.height.saturating_sub(1);
+                replaced.push(block.clone());
+            }
+        }
+
+        // The blocks of the new branch after the fork, the ones in between are asked to the indexer
+        let mut branch = Vec::new();
+        for height in fork_height + 1..new_tip.height {
+            let Some(block) = self.get_block_by_height(height)? else {
+                continue;
+            };
+
+            branch.push(BlockWorkEntry {
+                height,
+                hash: block.hash,
+                bits: self.get_block_bits(&block.hash),
+            });
+        }
+        branch.push(BlockWorkEntry {
+            height: new_tip.height,
+            hash: new_tip.hash,
+            bits: self.get_block_bits(&new_tip.hash),
+        });
+
+        if !replaced.is_empty() {
+            let contiguous = replaced
+                .iter()
+                .rev()
+                .zip(fork_height + 1..)
+                .all(|(block, height)| block.height == height);
+
+            match (
+                contiguous,
+                BlockWorkEntry::total_work(&replaced),
+                BlockWorkEntry::total_work(&branch),
+            ) {
+                (true, Some(old_work), Some(new_work)) if new_work < old_work => {
+                    warn!(
+                        "Reorg to a branch with less work | Height({}) | Old work({}) | New work({})",
+                        fork_height + 1,
+                        old_work,
+                        new_work
+                    );
+                    self.emit_news(
+                        MonitoredTypes::LowWorkReorg(fork_height + 1, old_work, new_work),
+                        new_tip.hash,
+                        None,
+                    )?;
+                }
+                (true, Some(_), Some(_)) => {}
+                _ => debug!(
+                    "Work of the reorg at height {} not compared, blocks missing",
+                    fork_height + 1
+                ),
+            }
+        }
+
+        let window_start = new_tip
+            .height
+            .saturating_sub(self.settings.max_monitoring_confirmations);
+        blocks.retain(|block| block.height > window_start && block.height <= fork_height);
+        blocks.extend(
+            branch
+                .into_iter()
+                .filter(|block| block.height > window_start),
+        );
+        self.store.set_block_work(blocks)?;
+
+        Ok(())
+    }
+
+    /// Bits of the block from the header source, None when there is no source or it fails.
+    fn get_block_bits(&self, hash: &BlockHash) -> Option<CompactTarget> {
+        let headers = self.headers.as_ref()?;
+
+        match headers.get_block_bits(hash) {
+            Ok(bits) => bits,
+            Err(e) => {
+                debug!("Bits of block {} not available: {}", hash, e);
+                None
+            }
+        }
+    }
+
+    /// Refuses to acknowledge a news re-opened by a reorg to a branch with less work while the
+    /// `MonitorNews::LowWorkReorg` of that reorg is not acknowledged.
+    fn check_low_work_reorg_hold(&self, data: &AckMonitorNews) -> Result<(), MonitorError> {
+        if let Some((height, reopened)) = self.low_work_reorg_hold()? {
+            if reopened
+                .iter()
+                .any(|reopened| data.acknowledges(&reopened.news))
+            {
+                return Err(MonitorError::HeldByLowWorkReorg(height));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Height of the pending `MonitorNews::LowWorkReorg` and the news its reorg re-opened, None
+    /// when no news is held.
+    fn low_work_reorg_hold(
+        &self,
+    ) -> Result<Option<(BlockHeight, Vec<ReopenedNews>)>, MonitorError> {
+        let pending = self
+            .store
+            .get_news_acks()?
+            .into_iter()
+            .find_map(|(news, ack)| match news {
+                MonitoredTypes::LowWorkReorg(height, ..) if !ack.acknowledged => {
+                    Some((height, ack.block_hash))
+                }
+                _ => None,
+            });
+
+        let Some((height, reorg_tip)) = pending else {
+            return Ok(None);
+        };
+
+        Ok(self
+            .store
+            .get_last_reorg_impact()?
+            .filter(|impact| impact.new_tip == reorg_tip)
+            .map(|impact| (height, impact.reopened_news)))
     }
 
-    // This method checks if the monitor has pending work to be done.
-    // It checks if the block in the monitor is the same as the best block in the indexer.
-    // If the block is not the same, it means that the monitor is not synced with the indexer, so it has pending work to be done to sync it.
-    // If the block is the same, it means that the monitor is synced with the indexer, so it has no pending work to be done.
-    pub fn is_pending_work(&self) -> Result<bool, MonitorError> {
-        let is_pending_work = self.store.has_pending_work()?;
+    /// Looks in the mempool for spends of the UTXOs whose deadline window includes the monitor height,
+    /// and, with `settings.track_mempool`, for the monitored transactions not reported yet.
+    /// No mempool call is made when there is nothing to look for.
+    fn process_mempool(&self) -> Result<(), MonitorError> {
+        let Some(mempool) = &self.mempool else {
+            return Ok(());
+        };
 
-        if is_pending_work {
-            return Ok(true);
-        }
+        let monitor_height = self.get_monitor_height()?;
 
-        let block = self.get_current_block()?;
+        let mut in_window: Vec<(Txid, u32, MonitorContext)> = vec![];
+        self.for_each_monitor(MonitorKind::SpendingUTXOTransaction, |monitor| {
+            if let TypesToMonitorStore::SpendingUTXOTransaction(
+                tx_id,
+                vout,
+                extra_data,
+                _,
+                Some(deadline),
+                _,
+                _,
+            ) = monitor
+            {
+                if deadline.is_in_mempool_window(monitor_height) {
+                    in_window.push((tx_id, vout, extra_data));
+                }
+            }
+            Ok(())
+        })?;
 
-        if block.is_none() {
-            debug!("No block found in Monitor, pending work to be done");
-            return Ok(true);
+        let (unconfirmed_txs, watched_txs) = if self.settings.track_mempool {
+            self.mempool_candidates()?
+        } else {
+            (HashMap::new(), HashSet::new())
+        };
+
+        // Candidates are watched transactions, so they don't need to be checked on their own
+        if in_window.is_empty() && watched_txs.is_empty() {
+            return Ok(());
         }
 
-        let monitor_block = block.unwrap();
-        let block = self.indexer.get_best_block()?;
+        let Some(current_block) = self.get_current_block()? else {
+            return Ok(());
+        };
 
-        if block.is_none() {
-            return Ok(false);
+        // Only the outputs in their window and the watched transactions are asked for
+        if !in_window.is_empty() {
+            let outpoints: Vec<OutPoint> = in_window
+                .iter()
+                .map(|(tx_id, vout, _)| OutPoint::new(*tx_id, *vout))
+                .collect();
+            let spenders = mempool.get_mempool_spenders(&outpoints)?;
+            self.process_mempool_spends(&spenders, in_window, monitor_height, current_block.hash)?;
         }
 
-        let block = block.unwrap();
-
-        if block.hash != monitor_block.hash {
-            debug!("Best block hash mismatch, pending work to be done");
-            return Ok(true);
+        if !watched_txs.is_empty() {
+            let mempool_txs = mempool.get_mempool_txs(&watched_txs)?;
+            self.process_mempool_txs(&mempool_txs, unconfirmed_txs)?;
+            self.record_mempool_sightings(
+                &mempool_txs,
+                &watched_txs,
+                monitor_height,
+                current_block.estimated_fee_rate,
+            )?;
         }
 
-        Ok(false)
+        Ok(())
     }
 
-    /// Builds the context string for spending UTXO transactions
-    fn build_spending_utxo_context(
-        target_tx_id: Txid,
-        target_utxo_index: u32,
-        extra_data: &str,
-    ) -> String {
-        format!(
-            "{}:{}:{}:{}",
-            INTERNAL_SPENDING_UTXO,
-            target_tx_id.to_string(),
-            target_utxo_index,
-            extra_data
-        )
+    fn process_mempool_spends(
+        &self,
+        mempool_txs: &[Transaction],
+        in_window: Vec<(Txid, u32, MonitorContext)>,
+        monitor_height: BlockHeight,
+        current_block_hash: BlockHash,
+    ) -> Result<(), MonitorError> {
+        for (target_tx_id, target_utxo_index, extra_data) in in_window {
+            let spender = mempool_txs
+                .iter()
+                .find(|tx| is_spending_output(tx, target_tx_id, target_utxo_index));
+
+            if let Some(spender) = spender {
+                let spender_tx_id = spender.compute_txid();
+
+                self.emit_news(
+                    MonitoredTypes::SpendingUTXOUnconfirmed(
+                        target_tx_id,
+                        target_utxo_index,
+                        extra_data,
+                        spender_tx_id,
+                    ),
+                    current_block_hash,
+                    Some(0),
+                )?;
+
+                info!(
+                    "Unconfirmed spend of SpendingUTXOTransaction({}:{}) | Spender({}) | Height({})",
+                    target_tx_id, target_utxo_index, spender_tx_id, monitor_height,
+                );
+            }
+        }
+
+        Ok(())
     }
 
-    /// Parses the spending UTXO context and extracts target_tx_id, target_utxo_index, and original_extra_data
-    /// Returns None if the context is not valid or cannot be parsed
-    fn parse_spending_utxo_context(extra_data: &str) -> Option<(Txid, u32, String)> {
-        if !extra_data.starts_with(INTERNAL_SPENDING_UTXO) {
-            return None;
+    /// Contexts of the transaction monitors that could be reported from the mempool, by txid.
+    /// Monitors with a confirmation trigger wait for their confirmations, and transactions
+    /// already reported from the mempool or found in a block are skipped.
+    /// Also returns the transactions watched by the user, with or without a confirmation trigger.
+    fn mempool_candidates(&self) -> Result<(MempoolCandidates, HashSet<Txid>), MonitorError> {
+        let mut candidates = MempoolCandidates::new();
+        let mut watched = HashSet::new();
+
+        self.for_each_monitor(MonitorKind::Transaction, |monitor| {
+            if let TypesToMonitorStore::Transaction(tx_id, extra_data, trigger, _) = monitor {
+                if !is_internal_context(&extra_data) {
+                    watched.insert(tx_id);
+                    if trigger.is_none() {
+                        candidates.entry(tx_id).or_default().push(extra_data);
+                    }
+                }
+            }
+            Ok(())
+        })?;
+
+        if candidates.is_empty() {
+            return Ok((candidates, watched));
         }
 
-        // Parse the context: INTERNAL_SPENDING_UTXO:{target_tx_id_hex}:{target_utxo_index}:{original_extra_data}
-        let parts: Vec<&str> = extra_data.split(':').collect();
-        if parts.len() >= 4 {
-            if let (Ok(target_tx_id), Ok(target_utxo_index)) =
-                (parts[1].parse::<Txid>(), parts[2].parse::<u32>())
+        for snapshot in self.store.get_tx_snapshots()? {
+            if snapshot.status == TransactionBlockchainStatus::Mempool || snapshot.confirmations > 0
             {
-                let original_extra_data = parts[3..].join(":");
-                return Some((target_tx_id, target_utxo_index, original_extra_data));
+                candidates.remove(&snapshot.tx_id);
             }
         }
 
-        None
+        Ok((candidates, watched))
     }
 
-    /// Determines if news should be sent based on the confirmation trigger.
-    fn should_send_news(
+    /// Keeps the heights the watched transactions are seen in the mempool at, read back by
+    /// `stale_unconfirmed` to find fee bump candidates.
+    fn record_mempool_sightings(
         &self,
-        tx_id: Txid,
-        extra_data: &str,
-        number_confirmation_trigger: Option<u32>,
-        current_confirmations: u32,
-    ) -> Result<bool, MonitorError> {
-        let trigger_sent = self.store.get_transaction_trigger_sent(tx_id, extra_data)?;
-
-        if let Some(trigger) = number_confirmation_trigger {
-            // Send news when confirmations are greater than or equal to the trigger value
-            // but only once (when trigger_sent is false)
-            Ok(current_confirmations >= trigger && !trigger_sent)
-        } else {
-            // If None, always send news when current confirmations are less than the max monitoring confirmations
-            Ok(current_confirmations < self.settings.max_monitoring_confirmations)
+        mempool_txs: &[Transaction],
+        watched_txs: &HashSet<Txid>,
+        monitor_height: BlockHeight,
+        fee_rate: u64,
+    ) -> Result<(), MonitorError> {
+        for tx in mempool_txs {
+            let tx_id = tx.compute_txid();
+            if watched_txs.contains(&tx_id) {
+                self.store
+                    .record_mempool_sighting(tx_id, monitor_height, fee_rate)?;
+            }
         }
-    }
 
-    pub fn tick(&self) -> Result<(), MonitorError> {
-        self.indexer.tick()?;
+        Ok(())
+    }
 
-        if !self.is_pending_work()? {
-            debug!("No pending work, skipping tick");
+    /// Sends a news with the Mempool status for each candidate found in the mempool. The status
+    /// is kept as the snapshot of the transaction, so it is only reported once, until the news of
+    /// its block replaces it.
+    fn process_mempool_txs(
+        &self,
+        mempool_txs: &[Transaction],
+        mut candidates: HashMap<Txid, Vec<MonitorContext>>,
+    ) -> Result<(), MonitorError> {
+        if candidates.is_empty() {
             return Ok(());
         }
 
-        let indexer_best_block = self.indexer.get_best_block()?;
-        let indexer_best_block = indexer_best_block.unwrap();
-        let indexer_best_block_height = indexer_best_block.height;
-        let current_block_hash = indexer_best_block.hash;
+        for tx in mempool_txs {
+            let tx_id = tx.compute_txid();
+            let Some(contexts) = candidates.remove(&tx_id) else {
+                continue;
+            };
 
-        let txs_monitors = self.store.get_monitors()?;
+            self.store.save_tx_snapshot(TransactionStatus {
+                tx_id,
+                tx: tx.clone(),
+                block_info: None,
+                confirmations: 0,
+                status: TransactionBlockchainStatus::Mempool,
+            })?;
 
-        for tx_type in txs_monitors {
-            match tx_type {
-                TypesToMonitorStore::Transaction(
-                    tx_id,
-                    extra_data,
-                    number_confirmation_trigger,
-                ) => {
-                    self.process_transaction_monitor(
-                        tx_id,
-                        extra_data,
-                        number_confirmation_trigger,
-                        indexer_best_block_height,
-                        current_block_hash,
-                    )?;
-                }
-                TypesToMonitorStore::RskPegin(number_confirmation_trigger) => {
-                    self.process_rsk_pegin_transaction(
-                        number_confirmation_trigger,
-                        &indexer_best_block,
-                        indexer_best_block_height,
-                        current_block_hash,
-                    )?;
-                }
-                TypesToMonitorStore::SpendingUTXOTransaction(
-                    target_tx_id,
-                    target_utxo_index,
-                    extra_data,
-                    number_confirmation_trigger,
-                ) => {
-                    self.process_spending_utxo_transaction(
-                        target_tx_id,
-                        target_utxo_index,
-                        extra_data,
-                        number_confirmation_trigger,
-                        &indexer_best_block,
-                        indexer_best_block_height,
-                        current_block_hash,
-                    )?;
-                }
-                TypesToMonitorStore::NewBlock => {
-                    self.store.update_news(
-                        MonitoredTypes::NewBlock(current_block_hash),
-                        current_block_hash,
-                    )?;
-                }
+            for extra_data in contexts {
+                // Not in a block yet, the news of its block has another hash and replaces this one
+                self.emit_news(
+                    MonitoredTypes::Transaction(tx_id, extra_data),
+                    BlockHash::all_zeros(),
+                    Some(0),
+                )?;
             }
-        }
 
-        self.store
-            .update_monitor_height(indexer_best_block_height)?;
-
-        self.store.set_pending_work(false)?;
+            info!("News for Transaction({}) | Mempool", tx_id);
+        }
 
         Ok(())
     }
 
-    fn detect_rsk_pegin_txs(&self, full_block: FullBlock) -> Result<Vec<Txid>, MonitorError> {
-        let mut txs_ids = Vec::new();
+    /// Pegins of the block whose first output pays to one of the `committee` scripts, or any
+    /// pegin when `committee` is empty.
+    fn detect_rsk_pegin_txs(
+        &self,
+        committee: &[ScriptBuf],
+        packet_range: Option<(u64, u64)>,
+        min_amount: Option<Amount>,
+        block_txs: &[(Txid, TransactionRef)],
+    ) -> Result<Vec<(Txid, RskPeginData)>, MonitorError> {
+        let mut pegins = Vec::new();
+
+        for (tx_id, tx) in block_txs {
+            let pays_committee = committee.is_empty()
+                || tx
+                    .output
+                    .first()
+                    .is_some_and(|output| committee.contains(&output.script_pubkey));
+
+            if !pays_committee {
+                continue;
+            }
+
+            // A malformed OP_RETURN is not a pegin, so it is never parsed
+            let Some(data) = parse_pegin_data(tx) else {
+                continue;
+            };
+
+            let in_range = packet_range
+                .is_none_or(|(first, last)| (first..=last).contains(&data.packet_number));
+            if !in_range {
+                debug!(
+                    "Pegin({}) skipped | Packet({}) out of {:?}",
+                    tx_id, data.packet_number, packet_range
+                );
+                continue;
+            }
 
-        for tx in full_block.txs.iter() {
-            if is_a_pegin_tx(tx) {
-                txs_ids.push(tx.compute_txid());
+            if let Some(min_amount) = min_amount.filter(|min_amount| data.amount < *min_amount) {
+                debug!(
+                    "Pegin({}) skipped | Amount({}) below {}",
+                    tx_id, data.amount, min_amount
+                );
+                continue;
             }
+
+            pegins.push((*tx_id, data));
         }
 
-        Ok(txs_ids)
+        Ok(pegins)
     }
 
+    #[allow(clippy::too_many_arguments)]
     fn process_rsk_pegin_transaction(
         &self,
         number_confirmation_trigger: Option<u32>,
-        indexer_best_block: &FullBlock,
+        committee: &[ScriptBuf],
+        packet_range: Option<(u64, u64)>,
+        min_amount: Option<Amount>,
+        block_txs: &[(Txid, TransactionRef)],
         indexer_best_block_height: u32,
         current_block_hash: bitcoin::BlockHash,
     ) -> Result<(), MonitorError> {
-        let new_txs_ids = self.detect_rsk_pegin_txs(indexer_best_block.clone())?;
+        let new_pegins =
+            self.detect_rsk_pegin_txs(committee, packet_range, min_amount, block_txs)?;
 
         // Add new transactions to monitoring using add_monitor with INTERNAL_RSK_PEGIN context
-        for tx_id in &new_txs_ids {
-            self.store.add_monitor(TypesToMonitor::Transactions(
-                vec![*tx_id],
-                INTERNAL_RSK_PEGIN.to_string(),
-                number_confirmation_trigger,
-            ))?;
+        for (tx_id, data) in &new_pegins {
+            // Registering the pegin again would reset its trigger, so a reprocessed block only updates its status
+            let detection = MonitoredTypes::RskPeginTransaction(*tx_id, Some(*data));
+            if !self
+                .store
+                .is_detection_recorded(&detection, current_block_hash)?
+            {
+                self.store.add_monitor(TypesToMonitor::Transactions(
+                    vec![*tx_id],
+                    INTERNAL_RSK_PEGIN.into(),
+                    number_confirmation_trigger,
+                    None,
+                ))?;
+                self.store.record_detection(
+                    detection,
+                    current_block_hash,
+                    indexer_best_block_height,
+                )?;
+            }
 
             self.process_transaction_monitor(
                 *tx_id,
-                INTERNAL_RSK_PEGIN.to_string(),
+                INTERNAL_RSK_PEGIN.into(),
                 number_confirmation_trigger,
                 indexer_best_block_height,
                 current_block_hash,
@@ -486,19 +4057,36 @@ where
     fn process_transaction_monitor(
         &self,
         tx_id: Txid,
-        extra_data: String,
+        extra_data: MonitorContext,
         number_confirmation_trigger: Option<u32>,
         indexer_best_block_height: BlockHeight,
         current_block_hash: bitcoin::BlockHash,
     ) -> Result<(), MonitorError> {
-        let tx_info = self.indexer.get_tx(&tx_id)?;
+        let tx_info = self.get_tx_up_to(&tx_id, indexer_best_block_height)?;
 
         if let Some(tx) = tx_info {
+            let history_owner = Self::history_owner(tx_id, &extra_data);
+            if let Some(owner) = &history_owner {
+                self.record_tx_events(owner, &tx, tx_id, number_confirmation_trigger)?;
+            }
+
             if tx.block_info.orphan {
                 info!(
                     "Orphan Transaction({}) | Height({})",
                     tx_id, tx.block_info.height
                 );
+
+                if !is_internal_context(&extra_data) {
+                    return self.report_orphaned_transaction(
+                        &tx,
+                        &extra_data,
+                        number_confirmation_trigger,
+                        current_block_hash,
+                    );
+                }
+            } else {
+                // Mined, no longer waiting in the mempool
+                self.store.remove_mempool_sighting(tx_id)?;
             }
 
             // Check if we should send news based on number_confirmation_trigger
@@ -509,36 +4097,72 @@ where
                 tx.confirmations,
             )?;
 
-            if should_send_news {
+            // Only report again when the confirmations or the block of the transaction changed,
+            // e.g. not when a reorg replaced the tip at the same height
+            let block_hash = (!tx.block_info.orphan).then_some(tx.block_info.hash);
+            let unchanged = self.store.get_last_reported(tx_id, &extra_data)?
+                == Some((tx.confirmations, block_hash));
+
+            if should_send_news && !unchanged {
+                self.store.update_last_reported(
+                    tx_id,
+                    &extra_data,
+                    tx.confirmations,
+                    block_hash,
+                )?;
+
+                // Keep the status the news is emitted with, read back by get_news in snapshot mode
+                self.store.save_tx_snapshot(self.to_tx_snapshot(&tx))?;
+
                 //  news update dispatch based on extra_data pattern
-                match extra_data.as_str() {
-                    ed if ed == INTERNAL_RSK_PEGIN => {
-                        self.store.update_news(
-                            MonitoredTypes::RskPeginTransaction(tx_id),
-                            current_block_hash,
-                        )?;
+                let news = match extra_data.as_str() {
+                    ed if ed == INTERNAL_RSK_PEGIN => Some(MonitoredTypes::RskPeginTransaction(
+                        tx_id,
+                        parse_pegin_data(&tx.tx),
+                    )),
+                    ed if ed.starts_with(INTERNAL_SCRIPT_PUBKEY) => {
+                        Self::parse_script_pubkey_context(ed).map(
+                            |(script_pubkey, vout, original_extra_data)| {
+                                MonitoredTypes::ScriptPubKeyTransaction(
+                                    script_pubkey,
+                                    original_extra_data,
+                                    tx_id,
+                                    vout,
+                                )
+                            },
+                        )
+                    }
+                    ed if ed.starts_with(INTERNAL_UNREPORTED_SPEND) => None,
+                    ed if ed.starts_with(INTERNAL_MINOR_SPEND) => {
+                        Self::parse_spending_utxo_context(ed).map(
+                            |(target_tx_id, target_utxo_index, original_extra_data)| {
+                                MonitoredTypes::MinorSpend(
+                                    target_tx_id,
+                                    target_utxo_index,
+                                    original_extra_data,
+                                    tx_id,
+                                )
+                            },
+                        )
                     }
                     ed if ed.starts_with(INTERNAL_SPENDING_UTXO) => {
-                        if let Some((target_tx_id, target_utxo_index, original_extra_data)) =
-                            Self::parse_spending_utxo_context(ed)
-                        {
-                            self.store.update_news(
+                        Self::parse_spending_utxo_context(ed).map(
+                            |(target_tx_id, target_utxo_index, original_extra_data)| {
                                 MonitoredTypes::SpendingUTXOTransaction(
                                     target_tx_id,
                                     target_utxo_index,
                                     original_extra_data,
                                     tx_id,
-                                ),
-                                current_block_hash,
-                            )?;
-                        }
-                    }
-                    _ => {
-                        self.store.update_news(
-                            MonitoredTypes::Transaction(tx_id, extra_data.clone()),
-                            current_block_hash,
-                        )?;
+                                )
+                            },
+                        )
                     }
+                    _ => Some(MonitoredTypes::Transaction(tx_id, extra_data.clone())),
+                };
+
+                if let Some(news) = news {
+                    self.archive_detection(&news, &tx)?;
+                    self.emit_news(news, current_block_hash, Some(tx.confirmations))?;
                 }
 
                 info!(
@@ -554,13 +4178,37 @@ where
                 }
             }
 
-            // Check if we should deactivate monitor based on max_monitoring_confirmations
+            // Check if we should deactivate monitor based on max_monitoring_confirmations, once
+            // its news are acknowledged or the ack grace has passed
             if tx.confirmations >= self.settings.max_monitoring_confirmations {
+                let reason = if !self.has_unacked_news(tx_id, &extra_data)? {
+                    DeactivationReason::NewsAcked
+                } else if tx.confirmations
+                    >= self
+                        .settings
+                        .max_monitoring_confirmations
+                        .saturating_add(self.settings.deactivation_requires_ack_grace_blocks)
+                {
+                    warn!(
+                        "Deactivating Transaction({}) with unacknowledged news | Height({}) | Confirmations({})",
+                        tx_id, indexer_best_block_height, tx.confirmations,
+                    );
+                    DeactivationReason::AckGraceExpired
+                } else {
+                    debug!(
+                        "Transaction({}) waits for its news to be acknowledged before deactivation | Confirmations({})",
+                        tx_id, tx.confirmations,
+                    );
+                    return Ok(());
+                };
+
                 self.store.deactivate_monitor(TypesToMonitor::Transactions(
                     vec![tx_id],
                     extra_data.clone(),
                     number_confirmation_trigger,
+                    None,
                 ))?;
+                self.count_deactivation();
 
                 info!(
                     "Stop monitoring Transaction({}) | Height({}) | Confirmations({})",
@@ -577,7 +4225,11 @@ where
                             target_utxo_index,
                             original_extra_data,
                             number_confirmation_trigger,
+                            None,
+                            None,
+                            None,
                         ))?;
+                    self.count_deactivation();
 
                     info!(
                         "Stop monitoring SpendingUTXOTransaction({}:{}) | Height({}) | Confirmations({})",
@@ -587,67 +4239,450 @@ where
                         self.settings.max_monitoring_confirmations,
                     );
                 }
+
+                // The outputs found by a ScriptPubKey monitor stop, the monitor itself keeps going
+                if let Some(
+                    owner @ (TypesToMonitorStore::Transaction(..)
+                    | TypesToMonitorStore::SpendingUTXOTransaction(..)),
+                ) = &history_owner
+                {
+                    self.store.record_monitor_event(
+                        owner,
+                        MonitorEvent::Deactivated {
+                            height: indexer_best_block_height,
+                            reason,
+                        },
+                    )?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether a news written by the transaction monitor of `tx_id` with `extra_data` is waiting
+    /// to be acknowledged: its transaction news, or the spend, script output or pegin news it
+    /// reported for the monitor it was registered for.
+    fn has_unacked_news(&self, tx_id: Txid, extra_data: &str) -> Result<bool, MonitorError> {
+        let spending = Self::parse_spending_utxo_context(extra_data);
+        let script = Self::parse_script_pubkey_context(extra_data);
+
+        let unacked = self
+            .store
+            .get_news_acks()?
+            .into_iter()
+            .filter(|(_, ack)| !ack.acknowledged)
+            .any(|(news, _)| match news {
+                MonitoredTypes::Transaction(id, context)
+                | MonitoredTypes::OrphanedTransaction(id, context) => {
+                    id == tx_id && context == extra_data
+                }
+                MonitoredTypes::RskPeginTransaction(id, _) => {
+                    id == tx_id && extra_data == INTERNAL_RSK_PEGIN
+                }
+                MonitoredTypes::SpendingUTXOTransaction(target, vout, context, spender)
+                | MonitoredTypes::MinorSpend(target, vout, context, spender)
+                | MonitoredTypes::SpendingUTXOUnconfirmed(target, vout, context, spender) => {
+                    spender == tx_id && spending.as_ref() == Some(&(target, vout, context))
+                }
+                MonitoredTypes::ScriptPubKeyTransaction(script_pubkey, context, id, vout) => {
+                    id == tx_id && script.as_ref() == Some(&(script_pubkey, vout, context))
+                }
+                _ => false,
+            });
+
+        Ok(unacked)
+    }
+
+    /// Sends a `MonitorNews::OrphanedTransaction` the first time the block of a monitored
+    /// transaction is found orphaned, whatever its trigger. The trigger is armed again, so the
+    /// transaction is reported as usual once it confirms in the new chain.
+    fn report_orphaned_transaction(
+        &self,
+        tx: &TransactionInfo,
+        extra_data: &str,
+        number_confirmation_trigger: Option<u32>,
+        current_block_hash: BlockHash,
+    ) -> Result<(), MonitorError> {
+        let tx_id = tx.tx.compute_txid();
+
+        // Reported with no block once orphaned
+        let last_reported = self.store.get_last_reported(tx_id, extra_data)?;
+        if last_reported.is_some_and(|(_, block_hash)| block_hash.is_none()) {
+            return Ok(());
+        }
+
+        self.store
+            .update_last_reported(tx_id, extra_data, tx.confirmations, None)?;
+        self.store.save_tx_snapshot(self.to_tx_snapshot(tx))?;
+
+        if number_confirmation_trigger.is_some() {
+            self.store
+                .update_transaction_trigger_sent(tx_id, extra_data, false)?;
+        }
+
+        self.emit_news(
+            MonitoredTypes::OrphanedTransaction(tx_id, extra_data.into()),
+            current_block_hash,
+            Some(tx.confirmations),
+        )
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn process_spending_utxo_transaction(
+        &self,
+        target_tx_id: Txid,
+        target_utxo_index: u32,
+        extra_data: MonitorContext,
+        number_confirmation_trigger: Option<u32>,
+        value_gate: Option<SpendValueGate>,
+        spender_tx_id: Option<Txid>,
+        indexer_best_block_height: BlockHeight,
+        current_block_hash: bitcoin::BlockHash,
+    ) -> Result<(), MonitorError> {
+        // Spend of the target UTXO in the block being processed, the blocks below the tip are
+        // walked one by one when the monitor is behind
+        let Some(spending_tx_id) = spender_tx_id else {
+            return Ok(());
+        };
+
+        // Create a monitor for the spending transaction with the special context, a minor spend
+        // gets its own context so its news are minor spends, or nothing
+        let minor_gate = match value_gate {
+            Some(gate) => self
+                .spend_value(&gate, target_tx_id, target_utxo_index, spending_tx_id)?
+                .filter(|value| gate.is_minor(*value))
+                .map(|_| gate),
+            None => None,
+        };
+        let spending_context = match minor_gate {
+            Some(gate) => {
+                debug!(
+                    "Minor spend of SpendingUTXOTransaction({}:{}) | Spender({}) | Reported({})",
+                    target_tx_id, target_utxo_index, spending_tx_id, gate.report_minor
+                );
+                build_minor_spend_context(
+                    target_tx_id,
+                    target_utxo_index,
+                    &extra_data,
+                    gate.report_minor,
+                )
             }
+            None => build_spending_utxo_context(target_tx_id, target_utxo_index, &extra_data),
+        };
+
+        let detection = MonitoredTypes::SpendingUTXOTransaction(
+            target_tx_id,
+            target_utxo_index,
+            extra_data.clone(),
+            spending_tx_id,
+        );
+
+        // A spend already recorded for this block keeps its monitor (and its trigger) as it is
+        if !self
+            .store
+            .is_detection_recorded(&detection, current_block_hash)?
+        {
+            self.store.add_monitor(TypesToMonitor::Transactions(
+                vec![spending_tx_id],
+                spending_context.clone(),
+                number_confirmation_trigger,
+                None,
+            ))?;
+
+            self.store.update_spending_utxo_monitor((
+                target_tx_id,
+                target_utxo_index,
+                Some(spending_tx_id),
+            ))?;
+            self.store.record_detection(
+                detection,
+                current_block_hash,
+                indexer_best_block_height,
+            )?;
         }
 
+        // Process the spending transaction monitor
+        self.process_transaction_monitor(
+            spending_tx_id,
+            spending_context,
+            number_confirmation_trigger,
+            indexer_best_block_height,
+            current_block_hash,
+        )?;
+
         Ok(())
     }
 
-    fn process_spending_utxo_transaction(
+    /// Value a spend moves, measured as `gate` asks: the total output value of the spender, or the
+    /// value of the monitored output. None when the transaction is not found in the indexer,
+    /// in which case the spend is not minor.
+    fn spend_value(
         &self,
+        gate: &SpendValueGate,
         target_tx_id: Txid,
         target_utxo_index: u32,
-        extra_data: String,
+        spending_tx_id: Txid,
+    ) -> Result<Option<Amount>, MonitorError> {
+        if gate.measure_monitored_output {
+            let target = self.get_indexed_tx(&target_tx_id)?;
+            return Ok(target.and_then(|target| {
+                target
+                    .tx
+                    .output
+                    .get(target_utxo_index as usize)
+                    .map(|output| output.value)
+            }));
+        }
+
+        let spender = self.get_indexed_tx(&spending_tx_id)?;
+        Ok(spender.map(|spender| spender.tx.output.iter().map(|output| output.value).sum()))
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn process_script_pubkey_monitor(
+        &self,
+        script_pubkey: ScriptBuf,
+        extra_data: MonitorContext,
         number_confirmation_trigger: Option<u32>,
-        indexer_best_block: &FullBlock,
+        filter: Option<OutputFilter>,
+        outputs: &[ScannedOutput],
         indexer_best_block_height: BlockHeight,
-        current_block_hash: bitcoin::BlockHash,
+        current_block_hash: BlockHash,
     ) -> Result<(), MonitorError> {
-        // Check each transaction in the new block for a spending transaction of the target UTXO
-        for tx in indexer_best_block.txs.iter() {
-            let is_spending_output = is_spending_output(tx, target_tx_id, target_utxo_index);
+        // The outputs paying to the script were found by the scan of the block
+        for &(tx_id, vout, output) in outputs {
+            // Each output found gets its own transaction monitor, deactivated after
+            // max_monitoring_confirmations like any other transaction monitor
+            let context = Self::build_script_pubkey_context(&script_pubkey, vout, &extra_data);
 
-            if is_spending_output {
-                let spending_tx_id = tx.compute_txid();
+            // An output already recorded for this block keeps its monitor (and its trigger) as it is
+            let detection = MonitoredTypes::ScriptPubKeyTransaction(
+                script_pubkey.clone(),
+                extra_data.clone(),
+                tx_id,
+                vout,
+            );
+            let is_new_detection = !self
+                .store
+                .is_detection_recorded(&detection, current_block_hash)?;
 
-                // Create a monitor for the spending transaction with the special context
-                let spending_context =
-                    Self::build_spending_utxo_context(target_tx_id, target_utxo_index, &extra_data);
+            // Filtered outputs (e.g. dust spam) are only counted, they get no monitor nor news
+            if filter.is_some_and(|filter| filter.filters(output)) {
+                if is_new_detection {
+                    self.store
+                        .record_filtered_output(&script_pubkey, &extra_data, output.value)?;
+                    self.store.record_detection(
+                        detection,
+                        current_block_hash,
+                        indexer_best_block_height,
+                    )?;
+                }
+                continue;
+            }
 
+            if is_new_detection {
                 self.store.add_monitor(TypesToMonitor::Transactions(
-                    vec![spending_tx_id],
-                    spending_context.clone(),
+                    vec![tx_id],
+                    context.clone(),
                     number_confirmation_trigger,
+                    None,
                 ))?;
-
-                // Process the spending transaction monitor
-                self.process_transaction_monitor(
-                    spending_tx_id,
-                    spending_context,
-                    number_confirmation_trigger,
-                    indexer_best_block_height,
+                self.store.record_detection(
+                    detection,
                     current_block_hash,
+                    indexer_best_block_height,
                 )?;
             }
+
+            self.process_transaction_monitor(
+                tx_id,
+                context,
+                number_confirmation_trigger,
+                indexer_best_block_height,
+                current_block_hash,
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Sends a news for each transaction of the block tagged with `prefix`. The news is sent again
+    /// only if the transaction is found in another block.
+    fn process_op_return_prefix_monitor(
+        &self,
+        prefix: &[u8],
+        extra_data: MonitorContext,
+        block_txs: &[(Txid, TransactionRef)],
+        indexer_best_block_height: BlockHeight,
+        current_block_hash: BlockHash,
+    ) -> Result<(), MonitorError> {
+        for (tx_id, tx) in block_txs {
+            let Some(payload) = find_op_return_with_prefix(tx, prefix) else {
+                continue;
+            };
+
+            let news = MonitoredTypes::OpReturnTransaction(
+                prefix.to_vec(),
+                extra_data.clone(),
+                *tx_id,
+                payload,
+            );
+            if let Some(record) =
+                detection_record(&news, indexer_best_block_height, current_block_hash, None)
+            {
+                self.store.archive_detection(record)?;
+            }
+            self.emit_news(news, current_block_hash, Some(1))?;
+            self.store.record_monitor_event(
+                &TypesToMonitorStore::OpReturnPrefix(prefix.to_vec(), extra_data.clone()),
+                MonitorEvent::Detected {
+                    tx_id: *tx_id,
+                    height: indexer_best_block_height,
+                    block_hash: current_block_hash,
+                },
+            )?;
+
+            info!(
+                "News for OpReturnPrefix({}) | Transaction({})",
+                hex::encode(prefix),
+                tx_id
+            );
         }
 
         Ok(())
     }
 
     pub fn get_news(&self) -> Result<Vec<MonitorNews>, MonitorError> {
-        let list_news = self.store.get_news()?;
+        let envelopes = self.get_news_envelopes()?;
+        Ok(envelopes
+            .into_iter()
+            .map(|envelope| envelope.news)
+            .collect())
+    }
+
+    /// Returns whether there are pending news, reading only their acknowledgment flags.
+    pub fn has_news(&self) -> Result<bool, MonitorError> {
+        Ok(self.store.has_news()?)
+    }
+
+    /// Returns the number of pending news, reading only their acknowledgment flags.
+    pub fn news_count(&self) -> Result<usize, MonitorError> {
+        Ok(self.store.get_news_count()?)
+    }
+
+    /// Returns the pending news along with the resolution used to build each of them and their
+    /// `(epoch, sequence)` position.
+    ///
+    /// Transaction statuses are resolved following `settings.news_resolution`. A news without a
+    /// stored snapshot (emitted before snapshots were kept) falls back to `Resolution::Fresh`.
+    /// Replacement and unconfirmed news are built only from stored data and report
+    /// `Resolution::Snapshot`, new block news are looked up in the indexer and report
+    /// `Resolution::Fresh`.
+    pub fn get_news_envelopes(&self) -> Result<Vec<NewsEnvelope>, MonitorError> {
+        let list_news = self.store.get_sequenced_news()?;
+        self.to_envelopes(list_news)
+    }
+
+    /// Returns up to `limit` pending news after the `after` cursor, built as in `get_news_envelopes`.
+    pub fn get_news_page(
+        &self,
+        after: Option<NewsCursor>,
+        limit: usize,
+    ) -> Result<NewsPage, MonitorError> {
+        // One more news tells whether there is a next page
+        let mut page = self
+            .store
+            .get_news_page(after.clone(), limit.saturating_add(1))?;
+        let has_more = page.len() > limit;
+        page.truncate(limit);
+
+        let next = match page.last() {
+            Some((cursor, _)) => Some(cursor.clone()),
+            None => after,
+        }
+        .filter(|_| has_more);
+
+        let list_news = page
+            .into_iter()
+            .map(|(cursor, news)| (cursor.sequence, news))
+            .collect();
+
+        Ok(NewsPage {
+            news: self.to_envelopes(list_news)?,
+            next,
+        })
+    }
+
+    /// Builds the envelopes of the news, a failure is counted by the health score.
+    fn to_envelopes(
+        &self,
+        list_news: Vec<(u64, MonitoredTypes)>,
+    ) -> Result<Vec<NewsEnvelope>, MonitorError> {
+        let envelopes = self.build_envelopes(list_news);
+        if envelopes.is_err() {
+            self.health.add_news_resolution_failure();
+        }
+
+        envelopes
+    }
+
+    fn build_envelopes(
+        &self,
+        list_news: Vec<(u64, MonitoredTypes)>,
+    ) -> Result<Vec<NewsEnvelope>, MonitorError> {
+        let epoch = self.store.get_news_epoch()?;
+        let news_resolution = self.news_resolution();
+
+        let snapshots = match news_resolution {
+            Resolution::Fresh => vec![],
+            _ => self.store.get_tx_snapshots()?,
+        };
+
+        let monitor_height = match news_resolution {
+            Resolution::SnapshotWithHeightAdjustment => self.get_monitor_height()?,
+            _ => 0,
+        };
+
+        let metadata = self.store.get_monitor_metadata()?;
+        let disputed: HashSet<u64> = self.store.get_orphan_disputed_news()?.into_iter().collect();
 
         let mut return_news = Vec::new();
 
-        for news in list_news {
-            match news {
+        for (sequence, news) in list_news {
+            NewsEvent::new(&news).read();
+
+            let news_metadata = MonitorStore::metadata_target(&news)
+                .and_then(|target| metadata.iter().find(|entry| entry.target == target))
+                .map(|entry| entry.metadata.clone())
+                .unwrap_or_default();
+
+            let (news, resolution) = match news {
                 MonitoredTypes::Transaction(tx_id, extra_data) => {
-                    let status = self.get_tx_status(&tx_id)?;
-                    return_news.push(MonitorNews::Transaction(tx_id, status, extra_data));
+                    let (status, resolution) =
+                        self.resolve_tx_status(&tx_id, &snapshots, monitor_height)?;
+                    (
+                        MonitorNews::Transaction(tx_id, status, extra_data),
+                        resolution,
+                    )
+                }
+                MonitoredTypes::OrphanedTransaction(tx_id, extra_data) => {
+                    let (status, resolution) =
+                        self.resolve_tx_status(&tx_id, &snapshots, monitor_height)?;
+                    (
+                        MonitorNews::OrphanedTransaction(tx_id, status, extra_data),
+                        resolution,
+                    )
                 }
-                MonitoredTypes::RskPeginTransaction(tx_id) => {
-                    let status = self.get_tx_status(&tx_id)?;
-                    return_news.push(MonitorNews::RskPeginTransaction(tx_id, status));
+                MonitoredTypes::RskPeginTransaction(tx_id, data) => {
+                    let (status, resolution) =
+                        self.resolve_tx_status(&tx_id, &snapshots, monitor_height)?;
+                    // News stored by older versions have no payload, it's parsed from the status
+                    let data = data.or_else(|| parse_pegin_data(&status.tx));
+                    (
+                        MonitorNews::RskPeginTransaction(tx_id, status, data),
+                        resolution,
+                    )
                 }
                 MonitoredTypes::SpendingUTXOTransaction(
                     tx_id,
@@ -655,62 +4690,702 @@ where
                     extra_data,
                     spender_tx_id,
                 ) => {
-                    let status = self.get_tx_status(&spender_tx_id)?;
-                    return_news.push(MonitorNews::SpendingUTXOTransaction(
-                        tx_id, utxo_index, status, extra_data,
-                    ));
+                    let (status, resolution) =
+                        self.resolve_tx_status(&spender_tx_id, &snapshots, monitor_height)?;
+                    (
+                        MonitorNews::SpendingUTXOTransaction(tx_id, utxo_index, status, extra_data),
+                        resolution,
+                    )
                 }
-                MonitoredTypes::NewBlock(hash) => {
-                    let block_info = self.indexer.get_block_by_hash(&hash)?;
-                    if let Some(block_info) = block_info {
-                        return_news.push(MonitorNews::NewBlock(block_info.height, block_info.hash));
-                    }
+                MonitoredTypes::MinorSpend(tx_id, utxo_index, extra_data, spender_tx_id) => {
+                    let (status, resolution) =
+                        self.resolve_tx_status(&spender_tx_id, &snapshots, monitor_height)?;
+                    (
+                        MonitorNews::MinorSpend(tx_id, utxo_index, status, extra_data),
+                        resolution,
+                    )
                 }
-            }
+                MonitoredTypes::ScriptPubKeyTransaction(_, extra_data, tx_id, vout) => {
+                    let (status, resolution) =
+                        self.resolve_tx_status(&tx_id, &snapshots, monitor_height)?;
+                    (
+                        MonitorNews::ScriptPubKeyTransaction(tx_id, vout, status, extra_data),
+                        resolution,
+                    )
+                }
+                MonitoredTypes::OpReturnTransaction(_, extra_data, tx_id, payload) => (
+                    MonitorNews::OpReturnTransaction(tx_id, payload, extra_data),
+                    Resolution::Snapshot,
+                ),
+                MonitoredTypes::MonitorReplaced(old, new, context) => (
+                    MonitorNews::MonitorReplaced { old, new, context },
+                    Resolution::Snapshot,
+                ),
+                MonitoredTypes::ReplacedTransactionConfirmed(old, new, context) => (
+                    MonitorNews::ReplacedTransactionConfirmed { old, new, context },
+                    Resolution::Snapshot,
+                ),
+                MonitoredTypes::AlternativeResolved(winner, losers, context) => (
+                    MonitorNews::AlternativeResolved {
+                        winner,
+                        losers,
+                        context,
+                    },
+                    Resolution::Snapshot,
+                ),
+                MonitoredTypes::MonitorExpired(tx_id, vout, context, height) => (
+                    MonitorNews::MonitorExpired {
+                        tx_id,
+                        vout,
+                        context,
+                        height,
+                    },
+                    Resolution::Snapshot,
+                ),
+                MonitoredTypes::SpendingUTXOUnconfirmed(
+                    tx_id,
+                    utxo_index,
+                    extra_data,
+                    spender_tx_id,
+                ) => (
+                    MonitorNews::SpendingUTXOUnconfirmed(
+                        tx_id,
+                        utxo_index,
+                        spender_tx_id,
+                        extra_data,
+                    ),
+                    Resolution::Snapshot,
+                ),
+                MonitoredTypes::Reorg(old_tip, new_tip, summary) => (
+                    MonitorNews::Reorg {
+                        old_tip,
+                        new_tip,
+                        summary,
+                    },
+                    Resolution::Snapshot,
+                ),
+                MonitoredTypes::LowWorkReorg(height, old_work, new_work) => (
+                    MonitorNews::LowWorkReorg {
+                        height,
+                        old_work,
+                        new_work,
+                    },
+                    Resolution::Snapshot,
+                ),
+                MonitoredTypes::HealthStateChanged(state, score) => (
+                    MonitorNews::HealthStateChanged { state, score },
+                    Resolution::Snapshot,
+                ),
+                MonitoredTypes::ManuallyResolved(monitor, note) => (
+                    MonitorNews::ManuallyResolved(
+                        DetectionDescriptor {
+                            monitor,
+                            tx_id: None,
+                        },
+                        note,
+                    ),
+                    Resolution::Snapshot,
+                ),
+                MonitoredTypes::Composite(id) => (MonitorNews::Composite(id), Resolution::Snapshot),
+                MonitoredTypes::SequenceEpochChanged(old_epoch, new_epoch) => (
+                    MonitorNews::SequenceEpochChanged {
+                        old_epoch,
+                        new_epoch,
+                    },
+                    Resolution::Snapshot,
+                ),
+                MonitoredTypes::NewBlock(hash, Some(height)) => {
+                    (MonitorNews::NewBlock(height, hash), Resolution::Snapshot)
+                }
+                // News stored by older versions have no height, it is read from the indexer
+                MonitoredTypes::NewBlock(hash, None) => {
+                    self.counters.add_indexer_call();
+                    let Some(block_info) = self.indexer.get_block_by_hash(&hash)? else {
+                        continue;
+                    };
+                    (
+                        MonitorNews::NewBlock(block_info.height, block_info.hash),
+                        Resolution::Fresh,
+                    )
+                }
+            };
+
+            return_news.push(NewsEnvelope {
+                news,
+                resolution,
+                epoch,
+                sequence,
+                metadata: news_metadata,
+                orphan_disputed: disputed.contains(&sequence),
+            });
         }
 
         Ok(return_news)
     }
 
+    /// Resolves the status of a news transaction from `snapshots` or the indexer, following
+    /// `settings.news_resolution`.
+    fn resolve_tx_status(
+        &self,
+        tx_id: &Txid,
+        snapshots: &[TransactionStatus],
+        monitor_height: BlockHeight,
+    ) -> Result<(TransactionStatus, Resolution), MonitorError> {
+        let resolution = self.news_resolution();
+
+        let snapshot = match resolution {
+            Resolution::Fresh => None,
+            _ => snapshots.iter().find(|s| s.tx_id == *tx_id),
+        };
+
+        let Some(snapshot) = snapshot else {
+            let status = match self.get_tx_status(tx_id) {
+                // Only seen in the mempool, the indexer doesn't know it yet
+                Err(MonitorError::TransactionNotFound(e)) => self
+                    .store
+                    .get_tx_snapshot(tx_id)?
+                    .filter(|s| s.status == TransactionBlockchainStatus::Mempool)
+                    .ok_or(MonitorError::TransactionNotFound(e))?,
+                status => status?,
+            };
+            return Ok((status, Resolution::Fresh));
+        };
+
+        let mut status = snapshot.clone();
+
+        if resolution == Resolution::SnapshotWithHeightAdjustment {
+            if let Some(block_info) = status.block_info.as_ref().filter(|b| !b.orphan) {
+                // Confirmations never go back, a reorg is only noticed by the next tick
+                let confirmations = monitor_height.saturating_sub(block_info.height) + 1;
+                status.confirmations = status.confirmations.max(confirmations);
+                status.status = self.blockchain_status(false, status.confirmations);
+            }
+        }
+
+        Ok((status, resolution))
+    }
+
     pub fn ack_news(&self, data: AckMonitorNews) -> Result<(), MonitorError> {
+        self.check_low_work_reorg_hold(&data)?;
+        let event = NewsEvent::from_ack(&data);
         self.store.ack_news(data)?;
+        event.acked();
+        self.counters.add_news_acked(1);
         Ok(())
     }
 
+    pub fn ack_news_batch(&self, items: Vec<AckMonitorNews>) -> Result<(), MonitorError> {
+        let hold = self.low_work_reorg_hold()?;
+        let (held, to_ack): (Vec<_>, Vec<_>) = items.into_iter().partition(|item| {
+            hold.as_ref().is_some_and(|(_, reopened)| {
+                reopened
+                    .iter()
+                    .any(|reopened| item.acknowledges(&reopened.news))
+            })
+        });
+
+        let events: Vec<_> = to_ack
+            .iter()
+            .map(|item| (item.clone(), NewsEvent::from_ack(item)))
+            .collect();
+        let unknown = self.store.ack_news_batch(to_ack)?;
+
+        for (item, event) in &events {
+            if !unknown.contains(item) {
+                event.acked();
+                self.counters.add_news_acked(1);
+            }
+        }
+
+        if unknown.is_empty() && held.is_empty() {
+            return Ok(());
+        }
+
+        Err(MonitorError::NewsNotAcked { unknown, held })
+    }
+
+    pub fn ack_all_news(&self) -> Result<usize, MonitorError> {
+        let acked = self.store.ack_all_news()?;
+        self.counters.add_news_acked(acked as u64);
+        if acked > 0 {
+            info!("Acknowledged all the {} pending news", acked);
+        }
+        Ok(acked)
+    }
+
+    pub fn get_news_history(
+        &self,
+        from_seq: u64,
+        limit: usize,
+    ) -> Result<Vec<NewsHistoryEntry>, MonitorError> {
+        Ok(self.store.get_news_history(from_seq, limit)?)
+    }
+
     pub fn get_tx_status(&self, tx_id: &Txid) -> Result<TransactionStatus, MonitorError> {
-        let tx_status = self
-            .indexer
-            .get_tx(tx_id)?
+        let tx_info = self
+            .get_indexed_tx(tx_id)?
             .ok_or_else(|| MonitorError::TransactionNotFound(tx_id.to_string()))?;
 
-        let status = if tx_status.block_info.orphan {
+        Ok(self.to_tx_status(tx_info))
+    }
+
+    pub fn get_tx_statuses(
+        &self,
+        tx_ids: &[Txid],
+    ) -> Result<Vec<(Txid, Option<TransactionStatus>)>, MonitorError> {
+        tx_ids
+            .iter()
+            .map(|tx_id| {
+                let status = self
+                    .get_indexed_tx(tx_id)?
+                    .map(|tx_info| self.to_tx_status(tx_info));
+                Ok((*tx_id, status))
+            })
+            .collect()
+    }
+
+    pub fn status_bitmap(&self, tx_ids: &[Txid]) -> Result<Vec<TxState>, MonitorError> {
+        let snapshots = self.store.get_tx_snapshots()?;
+        let snapshots: HashMap<Txid, &TransactionStatus> =
+            snapshots.iter().map(|s| (s.tx_id, s)).collect();
+        let monitor_height = self.get_monitor_height()?;
+        let mut fallbacks = 0;
+
+        let states = tx_ids
+            .iter()
+            .map(|tx_id| {
+                let Some(snapshot) = snapshots.get(tx_id) else {
+                    fallbacks += 1;
+                    return Ok(match self.get_indexed_tx(tx_id)? {
+                        Some(tx_info) => (&self
+                            .blockchain_status(tx_info.block_info.orphan, tx_info.confirmations))
+                            .into(),
+                        None => TxState::Unknown,
+                    });
+                };
+
+                // Same confirmations as the news in SnapshotWithHeightAdjustment mode
+                Ok(match snapshot.block_info.as_ref().filter(|b| !b.orphan) {
+                    Some(block_info) => {
+                        let confirmations = snapshot
+                            .confirmations
+                            .max(monitor_height.saturating_sub(block_info.height) + 1);
+                        (&self.blockchain_status(false, confirmations)).into()
+                    }
+                    None => (&snapshot.status).into(),
+                })
+            })
+            .collect::<Result<Vec<_>, MonitorError>>()?;
+
+        if fallbacks > 0 {
+            debug!(
+                "status_bitmap resolved {} of {} transactions from the indexer",
+                fallbacks,
+                tx_ids.len()
+            );
+            self.counters.add_status_fallbacks(fallbacks);
+        }
+
+        Ok(states)
+    }
+
+    pub fn stale_unconfirmed(&self, older_than_blocks: u32) -> Result<Vec<StaleTx>, MonitorError> {
+        Ok(self.store.get_stale_unconfirmed(older_than_blocks)?)
+    }
+
+    pub fn address_filter_stats(
+        &self,
+        script_pubkey: &Script,
+    ) -> Result<Vec<AddressFilterStats>, MonitorError> {
+        Ok(self.store.get_address_filter_stats(script_pubkey)?)
+    }
+
+    pub fn export_detections_csv(
+        &self,
+        from_height: BlockHeight,
+        to_height: BlockHeight,
+        writer: &mut dyn io::Write,
+    ) -> Result<u64, MonitorError> {
+        export_detections_csv(
+            &self.store,
+            self.settings.confirmation_threshold,
+            from_height,
+            to_height,
+            writer,
+        )
+    }
+
+    pub fn chain_log(
+        &self,
+        from: BlockHeight,
+        to: BlockHeight,
+    ) -> Result<Vec<ChainLogEntry>, MonitorError> {
+        chain_log(&self.store, from, to)
+    }
+
+    pub fn verify_chain_log(&self) -> Result<ChainLogVerification, MonitorError> {
+        Ok(verify_chain_log(&self.store.get_chain_log()?))
+    }
+
+    pub fn context_progress(&self, context: &str) -> Result<ContextProgress, MonitorError> {
+        let monitors: Vec<MonitorInfo> = self
+            .store
+            .get_monitor_infos(true)?
+            .into_iter()
+            .filter(|info| info.monitor.context() == context)
+            .collect();
+
+        Ok(context_progress(
+            &monitors,
+            self.settings.confirmation_threshold,
+            self.store.get_monitor_height()?,
+        )
+        .remove(context)
+        .unwrap_or_else(|| ContextProgress {
+            context: context.into(),
+            ..Default::default()
+        }))
+    }
+
+    pub fn all_context_progress(&self) -> Result<Vec<ContextProgress>, MonitorError> {
+        // The monitors of the spenders and outputs found carry internal contexts
+        let monitors: Vec<MonitorInfo> = self
+            .store
+            .get_monitor_infos(true)?
+            .into_iter()
+            .filter(|info| !is_internal_context(info.monitor.context()))
+            .collect();
+
+        Ok(context_progress(
+            &monitors,
+            self.settings.confirmation_threshold,
+            self.store.get_monitor_height()?,
+        )
+        .into_values()
+        .collect())
+    }
+
+    /// Keeps the detection reported by `news` in the archive read by `export_detections_csv`,
+    /// or drops it once the transaction is orphaned.
+    fn archive_detection(
+        &self,
+        news: &MonitoredTypes,
+        tx: &TransactionInfo,
+    ) -> Result<(), MonitorError> {
+        let amount = match news {
+            MonitoredTypes::ScriptPubKeyTransaction(_, _, _, vout) => {
+                tx.tx.output.get(*vout as usize).map(|output| output.value)
+            }
+            _ => None,
+        };
+
+        let Some(record) = detection_record(news, tx.block_info.height, tx.block_info.hash, amount)
+        else {
+            return Ok(());
+        };
+
+        if tx.block_info.orphan {
+            self.store.remove_archived_detection(&record)?;
+        } else {
+            self.store.archive_detection(record)?;
+        }
+
+        Ok(())
+    }
+
+    fn to_tx_status(&self, tx_info: TransactionInfo) -> TransactionStatus {
+        let status = self.blockchain_status(tx_info.block_info.orphan, tx_info.confirmations);
+
+        TransactionStatus::new(
+            tx_info.tx,
+            tx_info.block_info,
+            status,
+            tx_info.confirmations,
+        )
+    }
+
+    /// Status kept as the snapshot of a news. Only the transaction is cloned, the transactions of
+    /// its block are not part of the snapshot.
+    fn to_tx_snapshot(&self, tx_info: &TransactionInfo) -> TransactionStatus {
+        let block_info = &tx_info.block_info;
+        let status = self.blockchain_status(block_info.orphan, tx_info.confirmations);
+
+        TransactionStatus::new(
+            tx_info.tx.clone(),
+            FullBlock {
+                height: block_info.height,
+                hash: block_info.hash,
+                prev_hash: block_info.prev_hash,
+                txs: vec![],
+                orphan: block_info.orphan,
+                estimated_fee_rate: block_info.estimated_fee_rate,
+            },
+            status,
+            tx_info.confirmations,
+        )
+    }
+
+    fn blockchain_status(&self, orphan: bool, confirmations: u32) -> TransactionBlockchainStatus {
+        if orphan {
             TransactionBlockchainStatus::Orphan
-        } else if tx_status.confirmations >= self.settings.confirmation_threshold {
+        } else if confirmations >= self.settings.confirmation_threshold {
             TransactionBlockchainStatus::Finalized
         } else {
             TransactionBlockchainStatus::Confirmed
+        }
+    }
+
+    pub fn get_current_block(&self) -> Result<Option<FullBlock>, MonitorError> {
+        let block_height = self.get_monitor_height()?;
+        self.get_block_by_height(block_height)
+    }
+
+    /// Gets a block from the indexer, failing with `MonitorError::IndexerInconsistency` if the
+    /// block returned is not at the requested height.
+    fn get_block_by_height(&self, height: BlockHeight) -> Result<Option<FullBlock>, MonitorError> {
+        if self.is_external() {
+            return self.with_external_chain(|chain| {
+                chain
+                    .iter()
+                    .find(|block| block.height == height)
+                    .map(ExternalBlockEntry::to_block)
+            });
+        }
+
+        self.counters.add_indexer_call();
+        let block = self.indexer.get_block_by_height(height)?;
+
+        if let Some(block) = block.as_ref().filter(|block| block.height != height) {
+            warn!(
+                "Indexer returned Block({}) at Height({}) for Height({})",
+                block.hash, block.height, height
+            );
+            return Err(MonitorError::IndexerInconsistency {
+                requested: height,
+                got: block.height,
+            });
+        }
+
+        Ok(block)
+    }
+
+    /// Gets a transaction from the indexer. The indexer only moves when the monitor ticks it, so
+    /// the lookups are cached until the next tick, where the same transaction is often looked up
+    /// by several monitors and again by `get_news`. With external blocks it comes from the block
+    /// being applied or the transaction snapshots instead.
+    fn get_indexed_tx(&self, tx_id: &Txid) -> Result<Option<TransactionInfo>, MonitorError> {
+        if let Some(tx_info) = self.tx_cache.borrow().get(tx_id) {
+            return Ok(tx_info.as_deref().cloned());
+        }
+
+        let tx_info = if self.is_external() {
+            self.get_external_tx(tx_id)?
+        } else {
+            self.counters.add_indexer_call();
+            let tx_info = self.indexer.get_tx(tx_id)?;
+            if let Some(tx_info) = &tx_info {
+                self.check_orphan_flag(tx_id, tx_info)?;
+            }
+            tx_info
         };
+        self.tx_cache
+            .borrow_mut()
+            .insert(*tx_id, tx_info.clone().map(Box::new));
 
-        let return_tx_status = TransactionStatus::new(
-            tx_status.tx,
-            tx_status.block_info,
-            status,
-            tx_status.confirmations,
+        Ok(tx_info)
+    }
+
+    /// Checks the orphan flag the indexer gave to `tx_info` against the chain logged by the
+    /// monitor. A disagreement is counted in the counters and the health score, and the news of
+    /// the transaction written in this tick are marked as disputed.
+    fn check_orphan_flag(
+        &self,
+        tx_id: &Txid,
+        tx_info: &TransactionInfo,
+    ) -> Result<(), MonitorError> {
+        let block = &tx_info.block_info;
+        let Some(logged_hash) = self.logged_block_at(block.height)? else {
+            return Ok(());
+        };
+
+        if block.orphan != (logged_hash == block.hash) {
+            return Ok(());
+        }
+
+        warn!(
+            "Indexer flags Transaction({}) in Block({}) at Height({}) as orphan: {}, the chain log has Block({})",
+            tx_id, block.hash, block.height, block.orphan, logged_hash
         );
+        self.counters.add_orphan_disagreement();
+        self.health.add_orphan_disagreement();
+        self.orphan_disputes.borrow_mut().insert(*tx_id);
 
-        Ok(return_tx_status)
+        Ok(())
     }
 
-    pub fn get_current_block(&self) -> Result<Option<FullBlock>, MonitorError> {
-        let block_height = self.get_monitor_height()?;
-        let block = self.indexer.get_block_by_height(block_height)?;
+    /// Hash of the block at `height` in the chain logged by the monitor, the block being processed
+    /// included. None when the log doesn't link back to `height`, or it is deeper than
+    /// `max_monitoring_confirmations` and no longer checked.
+    fn logged_block_at(&self, height: BlockHeight) -> Result<Option<BlockHash>, MonitorError> {
+        if let Some(hash) = self.logged_chain.borrow().get(&height) {
+            return Ok(*hash);
+        }
 
-        Ok(block)
+        let processed_block = self.processed_block.get();
+        let tip_height = match processed_block {
+            Some(block) => block.height,
+            None => self.get_monitor_height()?,
+        };
+        if height.saturating_add(self.settings.max_monitoring_confirmations) < tip_height {
+            return Ok(None);
+        }
+
+        let mut entries = self.store.get_chain_log_tail(height)?;
+        if let Some(block) = processed_block {
+            entries.push(ChainLogEntry {
+                height: block.height,
+                hash: block.hash,
+                prev_hash: block.prev_hash,
+                processed_at_unix: 0,
+            });
+        }
+
+        let hash = canonical_hash_at(&entries, height);
+        self.logged_chain.borrow_mut().insert(height, hash);
+        Ok(hash)
+    }
+
+    /// Gets a transaction from the indexer as it was at the processed block `height`, failing with
+    /// `MonitorError::IndexerInconsistency` if it is found in a block of the chain above the best
+    /// block of the indexer.
+    ///
+    /// While catching up on blocks below the best one, a transaction mined after `height` is not
+    /// found yet and the confirmations are counted up to `height`.
+    fn get_tx_up_to(
+        &self,
+        tx_id: &Txid,
+        height: BlockHeight,
+    ) -> Result<Option<TransactionInfo>, MonitorError> {
+        let best_height = self.indexer_best_height.get().max(height);
+        let tx_info = self.get_indexed_tx(tx_id)?;
+
+        if let Some(tx_info) = tx_info
+            .as_ref()
+            .filter(|tx_info| !tx_info.block_info.orphan && tx_info.block_info.height > best_height)
+        {
+            warn!(
+                "Indexer returned Transaction({}) at Height({}) above the best Height({})",
+                tx_id, tx_info.block_info.height, best_height
+            );
+            return Err(MonitorError::IndexerInconsistency {
+                requested: best_height,
+                got: tx_info.block_info.height,
+            });
+        }
+
+        Ok(tx_info.and_then(|mut tx_info| {
+            if tx_info.block_info.orphan || height == best_height {
+                return Some(tx_info);
+            }
+            if tx_info.block_info.height > height {
+                return None;
+            }
+            tx_info.confirmations = tx_info.confirmations.saturating_sub(best_height - height);
+            Some(tx_info)
+        }))
     }
 
     pub fn get_estimated_fee_rate(&self) -> Result<u64, MonitorError> {
+        // The fee rate of the last block applied
+        if self.is_external() {
+            return self.with_external_chain(|chain| {
+                chain.last().map_or(0, |block| block.estimated_fee_rate)
+            });
+        }
+
+        self.counters.add_indexer_call();
         self.indexer
             .get_estimated_fee_rate()
             .map_err(MonitorError::IndexerError)
     }
+
+    /// Collects the heights, monitor and news counts and the counters of the monitor.
+    pub fn metrics_snapshot(&self) -> Result<MetricsSnapshot, MonitorError> {
+        let monitor_height = self.get_monitor_height()?;
+        let indexer_height = if self.is_external() {
+            self.with_external_chain(|chain| chain.last().map(|block| block.height))?
+        } else {
+            self.indexer.get_best_block()?.map(|block| block.height)
+        };
+
+        let mut active_monitors = CountsByKind::default();
+        for kind in MonitorKind::ALL {
+            self.for_each_monitor(kind, |monitor| {
+                match monitor {
+                    TypesToMonitorStore::Transaction(..) => active_monitors.transaction += 1,
+                    TypesToMonitorStore::SpendingUTXOTransaction(..) => {
+                        active_monitors.spending_utxo += 1
+                    }
+                    TypesToMonitorStore::RskPegin(..) => active_monitors.rsk_pegin += 1,
+                    TypesToMonitorStore::NewBlock => active_monitors.new_block += 1,
+                    TypesToMonitorStore::ScriptPubKey(..) => active_monitors.script_pubkey += 1,
+                    TypesToMonitorStore::OpReturnPrefix(..) => active_monitors.op_return += 1,
+                }
+                Ok(())
+            })?;
+        }
+
+        let mut unacked_news = CountsByKind::default();
+        for news in self.store.get_news()? {
+            match news {
+                MonitoredTypes::Transaction(..)
+                | MonitoredTypes::OrphanedTransaction(..)
+                | MonitoredTypes::MonitorReplaced(..)
+                | MonitoredTypes::ReplacedTransactionConfirmed(..)
+                | MonitoredTypes::AlternativeResolved(..)
+                | MonitoredTypes::MonitorExpired(_, None, ..) => unacked_news.transaction += 1,
+                MonitoredTypes::SpendingUTXOTransaction(..)
+                | MonitoredTypes::SpendingUTXOUnconfirmed(..)
+                | MonitoredTypes::MinorSpend(..)
+                | MonitoredTypes::MonitorExpired(_, Some(_), ..) => unacked_news.spending_utxo += 1,
+                MonitoredTypes::RskPeginTransaction(..) => unacked_news.rsk_pegin += 1,
+                MonitoredTypes::NewBlock(..) => unacked_news.new_block += 1,
+                MonitoredTypes::ScriptPubKeyTransaction(..) => unacked_news.script_pubkey += 1,
+                MonitoredTypes::OpReturnTransaction(..) => unacked_news.op_return += 1,
+                MonitoredTypes::SequenceEpochChanged(..)
+                | MonitoredTypes::Reorg(..)
+                | MonitoredTypes::LowWorkReorg(..)
+                | MonitoredTypes::HealthStateChanged(..)
+                | MonitoredTypes::Composite(_) => {}
+                MonitoredTypes::ManuallyResolved(target, _) => match target {
+                    SnoozeTarget::Transaction(..) => unacked_news.transaction += 1,
+                    SnoozeTarget::SpendingUTXOTransaction(..) => unacked_news.spending_utxo += 1,
+                    SnoozeTarget::RskPegin => unacked_news.rsk_pegin += 1,
+                    SnoozeTarget::NewBlock => unacked_news.new_block += 1,
+                    SnoozeTarget::ScriptPubKey(..) => unacked_news.script_pubkey += 1,
+                    SnoozeTarget::OpReturnPrefix(..) => unacked_news.op_return += 1,
+                },
+            }
+        }
+
+        Ok(MetricsSnapshot {
+            monitor_height,
+            indexer_height,
+            active_monitors,
+            unacked_news,
+            last_tick_duration: self.counters.last_tick_duration(),
+            news_emitted: self.counters.news_emitted(),
+            news_emitted_by_kind: self.counters.news_emitted_by_kind(),
+            news_acked: self.counters.news_acked(),
+            indexer_calls: self.counters.indexer_calls(),
+            indexer_errors: self.counters.indexer_errors(),
+            status_fallbacks: self.counters.status_fallbacks(),
+            scan_generation: self.store.get_scan_generation()?,
+            scan_set_size: self.scan_set.borrow().as_ref().map_or(0, ScanSet::len),
+            scan_set_builds: self.counters.scan_set_builds(),
+            orphan_disagreements: self.counters.orphan_disagreements(),
+            health: self.health(),
+            storage: self.store.get_storage_stats(),
+        })
+    }
 }
@@ -0,0 +1,213 @@
+use crate::api::v1::NewsItem;
+use crate::config::WebhookConfig;
+use crate::errors::MonitorError;
+use crate::monitor::Monitor;
+use crate::store::MonitorStoreApi;
+use crate::types::AckMonitorNews;
+use bitcoin_indexer::indexer::IndexerApi;
+use std::io::{self, BufRead, BufReader, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+use tracing::{debug, info, warn};
+
+/// Outcome of a [`NewsDispatcher::dispatch`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DispatchReport {
+    /// News the webhook took, acknowledged in the monitor
+    pub delivered: usize,
+    /// News left pending, delivered on a later dispatch
+    pub pending: usize,
+    /// Why the last delivery failed, None when it didn't fail
+    pub failure: Option<String>,
+    /// Wait before the next delivery is attempted, None when the webhook is not failing
+    pub retry_in: Option<Duration>,
+}
+
+/// Posts the pending news of a monitor to a webhook, see [`WebhookConfig`].
+///
+/// The news are posted one by one in sequence order, and each one is acknowledged once the
+/// webhook answers it with a 2xx status. A failed delivery stops the dispatch, the news stays
+/// pending and is posted again by the first dispatch after the backoff, so the news are never
+/// dropped nor reordered. A news delivered but not acknowledged (e.g. held by a low work reorg) is
+/// posted again, the webhook tells the copies apart by their `epoch` and `sequence`.
+pub struct NewsDispatcher {
+    config: WebhookConfig,
+    endpoint: Endpoint,
+    failures: u32,
+    next_attempt: Option<Instant>,
+}
+
+struct Endpoint {
+    host: String,
+    port: u16,
+    path: String,
+}
+
+impl NewsDispatcher {
+    /// Fails with [`MonitorError::InvalidSettings`] if the url is not a plain `http://` url.
+    pub fn new(config: WebhookConfig) -> Result<Self, MonitorError> {
+        let endpoint = Endpoint::parse(&config.url)?;
+
+        Ok(Self {
+            config,
+            endpoint,
+            failures: 0,
+            next_attempt: None,
+        })
+    }
+
+    /// Posts the pending news of `monitor`, unless the webhook is failing and its backoff has not
+    /// passed. Delivery failures are reported, the errors are the ones of the monitor.
+    pub fn dispatch<I, B>(
+        &mut self,
+        monitor: &Monitor<I, B>,
+    ) -> Result<DispatchReport, MonitorError>
+    where
+        I: IndexerApi,
+        B: MonitorStoreApi,
+    {
+        let mut report = DispatchReport::default();
+
+        if let Some(next_attempt) = self.next_attempt {
+            let now = Instant::now();
+            if now < next_attempt {
+                report.pending = monitor.news_count()?;
+                report.retry_in = Some(next_attempt - now);
+                return Ok(report);
+            }
+        }
+
+        let envelopes = monitor.get_news_envelopes()?;
+        report.pending = envelopes.len();
+
+        for envelope in &envelopes {
+            if let Err(e) = self.post(&NewsItem::from(envelope)) {
+                self.failures += 1;
+                let delay = self.retry_delay();
+                self.next_attempt = Some(Instant::now() + delay);
+
+                warn!(
+                    failures = self.failures,
+                    pending = report.pending,
+                    "Webhook delivery failed, next one in {:?}: {}",
+                    delay,
+                    e
+                );
+                report.failure = Some(e.to_string());
+                report.retry_in = Some(delay);
+                return Ok(report);
+            }
+
+            match monitor.ack_news(AckMonitorNews::from(&envelope.news)) {
+                Ok(()) => {}
+                Err(MonitorError::HeldByLowWorkReorg(height)) => {
+                    debug!(
+                        "News delivered but held by the low work reorg at Height({}), it is delivered again",
+                        height
+                    );
+                    continue;
+                }
+                Err(e) => return Err(e),
+            }
+
+            report.delivered += 1;
+            report.pending -= 1;
+        }
+
+        if self.failures > 0 {
+            info!(
+                "Webhook delivery recovered after {} failures",
+                self.failures
+            );
+        }
+        self.failures = 0;
+        self.next_attempt = None;
+
+        Ok(report)
+    }
+
+    fn retry_delay(&self) -> Duration {
+        let retry = &self.config.retry;
+        let delay = retry
+            .initial_delay_ms
+            .saturating_mul(1 << self.failures.saturating_sub(1).min(16));
+
+        Duration::from_millis(delay.min(retry.max_delay_ms.max(retry.initial_delay_ms)))
+    }
+
+    /// Posts `item`, an error unless the webhook answers a 2xx status.
+    fn post(&self, item: &NewsItem) -> io::Result<()> {
+        let body = serde_json::to_string(item).map_err(io::Error::other)?;
+        let timeout = Duration::from_millis(self.config.retry.timeout_ms.max(1));
+
+        let addr = (self.endpoint.host.as_str(), self.endpoint.port)
+            .to_socket_addrs()?
+            .next()
+            .ok_or_else(|| io::Error::other(format!("{} not resolved", self.endpoint.host)))?;
+        let mut stream = TcpStream::connect_timeout(&addr, timeout)?;
+        stream.set_read_timeout(Some(timeout))?;
+        stream.set_write_timeout(Some(timeout))?;
+
+        let authorization = self
+            .config
+            .auth_header
+            .as_ref()
+            .map(|value| format!("Authorization: {}\r\n", value))
+            .unwrap_or_default();
+        write!(
+            stream,
+            "POST {} HTTP/1.1\r\nHost: {}:{}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n{}Connection: close\r\n\r\n{}",
+            self.endpoint.path,
+            self.endpoint.host,
+            self.endpoint.port,
+            body.len(),
+            authorization,
+            body
+        )?;
+        stream.flush()?;
+
+        let mut status_line = String::new();
+        BufReader::new(stream).read_line(&mut status_line)?;
+
+        match status_line.split_whitespace().nth(1).map(str::parse::<u16>) {
+            Some(Ok(status)) if (200..300).contains(&status) => Ok(()),
+            Some(Ok(status)) => Err(io::Error::other(format!("webhook answered {}", status))),
+            _ => Err(io::Error::other(format!(
+                "invalid webhook response: {:?}",
+                status_line.trim_end()
+            ))),
+        }
+    }
+}
+
+impl Endpoint {
+    fn parse(url: &str) -> Result<Self, MonitorError> {
+        let invalid = |reason: &str| {
+            MonitorError::InvalidSettings(format!("webhook.url {}: {}", url, reason))
+        };
+
+        let rest = url.strip_prefix("http://").ok_or_else(|| {
+            invalid("only http:// is supported, put a TLS terminating proxy in front of https")
+        })?;
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.rsplit_once(':') {
+            Some((host, port)) => (
+                host,
+                port.parse::<u16>().map_err(|_| invalid("invalid port"))?,
+            ),
+            None => (authority, 80),
+        };
+        if host.is_empty() {
+            return Err(invalid("missing host"));
+        }
+
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+}
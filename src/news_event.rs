@@ -0,0 +1,274 @@
+use crate::redaction::redact_context;
+use crate::store::MonitoredTypes;
+use crate::types::{AckMonitorNews, MonitorContext, SnoozeTarget};
+use bitcoin::{BlockHash, OutPoint, Txid};
+use tracing::{debug, info};
+
+/// Longest context written to the logs, longer ones are cut.
+const MAX_LOGGED_CONTEXT: usize = 64;
+
+/// Correlation fields of a news, shared by the log events and the metrics of the monitor so
+/// both describe a news the same way.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NewsEvent {
+    pub kind: &'static str,
+    /// Context of the monitor, redacted unless contexts are revealed (see
+    /// [`crate::redaction`]), sanitized and truncated to `MAX_LOGGED_CONTEXT` characters.
+    pub context: Option<String>,
+    pub tx_id: Option<Txid>,
+    pub outpoint: Option<OutPoint>,
+    pub block_hash: Option<BlockHash>,
+    pub confirmations: Option<u32>,
+}
+
+impl NewsEvent {
+    pub fn new(news: &MonitoredTypes) -> Self {
+        let (kind, context, tx_id, outpoint) = match news {
+            MonitoredTypes::Transaction(tx_id, context) => {
+                ("transaction", Some(context), Some(*tx_id), None)
+            }
+            MonitoredTypes::OrphanedTransaction(tx_id, context) => {
+                ("orphaned_transaction", Some(context), Some(*tx_id), None)
+            }
+            MonitoredTypes::RskPeginTransaction(tx_id, _) => {
+                ("rsk_pegin", None, Some(*tx_id), None)
+            }
+            MonitoredTypes::SpendingUTXOTransaction(target, vout, context, spender) => (
+                "spending_utxo",
+                Some(context),
+                Some(*spender),
+                Some(OutPoint::new(*target, *vout)),
+            ),
+            MonitoredTypes::SpendingUTXOUnconfirmed(target, vout, context, spender) => (
+                "spending_utxo_unconfirmed",
+                Some(context),
+                Some(*spender),
+                Some(OutPoint::new(*target, *vout)),
+            ),
+            MonitoredTypes::MinorSpend(target, vout, context, spender) => (
+                "minor_spend",
+                Some(context),
+                Some(*spender),
+                Some(OutPoint::new(*target, *vout)),
+            ),
+            MonitoredTypes::NewBlock(..) => ("new_block", None, None, None),
+            MonitoredTypes::MonitorReplaced(old, _, context) => {
+                ("monitor_replaced", Some(context), Some(*old), None)
+            }
+            MonitoredTypes::ReplacedTransactionConfirmed(old, _, context) => (
+                "replaced_transaction_confirmed",
+                Some(context),
+                Some(*old),
+                None,
+            ),
+            MonitoredTypes::SequenceEpochChanged(..) => {
+                ("sequence_epoch_changed", None, None, None)
+            }
+            MonitoredTypes::Reorg(..) => ("reorg", None, None, None),
+            MonitoredTypes::LowWorkReorg(..) => ("low_work_reorg", None, None, None),
+            MonitoredTypes::HealthStateChanged(..) => ("health_state_changed", None, None, None),
+            MonitoredTypes::ScriptPubKeyTransaction(_, context, tx_id, vout) => (
+                "script_pubkey",
+                Some(context),
+                Some(*tx_id),
+                Some(OutPoint::new(*tx_id, *vout)),
+            ),
+            MonitoredTypes::OpReturnTransaction(_, context, tx_id, _) => {
+                ("op_return", Some(context), Some(*tx_id), None)
+            }
+            MonitoredTypes::AlternativeResolved(winner, _, context) => {
+                ("alternative_resolved", Some(context), Some(*winner), None)
+            }
+            MonitoredTypes::MonitorExpired(tx_id, vout, context, _) => (
+                "monitor_expired",
+                Some(context),
+                Some(*tx_id),
+                vout.map(|vout| OutPoint::new(*tx_id, vout)),
+            ),
+            MonitoredTypes::ManuallyResolved(target, _) => {
+                let (context, tx_id, outpoint) = target_fields(target);
+                ("manually_resolved", context, tx_id, outpoint)
+            }
+            MonitoredTypes::Composite(_) => ("composite", None, None, None),
+        };
+
+        Self {
+            kind,
+            context: context.map(|context| sanitize_context(context)),
+            tx_id,
+            outpoint,
+            block_hash: None,
+            confirmations: None,
+        }
+    }
+
+    pub fn from_ack(ack: &AckMonitorNews) -> Self {
+        let (kind, context, tx_id, outpoint) = match ack {
+            AckMonitorNews::Transaction(tx_id, context) => {
+                ("transaction", Some(context), Some(*tx_id), None)
+            }
+            AckMonitorNews::OrphanedTransaction(tx_id, context) => {
+                ("orphaned_transaction", Some(context), Some(*tx_id), None)
+            }
+            AckMonitorNews::RskPeginTransaction(tx_id) => ("rsk_pegin", None, Some(*tx_id), None),
+            AckMonitorNews::SpendingUTXOTransaction(target, vout, context) => (
+                "spending_utxo",
+                Some(context),
+                None,
+                Some(OutPoint::new(*target, *vout)),
+            ),
+            AckMonitorNews::SpendingUTXOUnconfirmed(target, vout, context) => (
+                "spending_utxo_unconfirmed",
+                Some(context),
+                None,
+                Some(OutPoint::new(*target, *vout)),
+            ),
+            AckMonitorNews::MinorSpend(target, vout, context) => (
+                "minor_spend",
+                Some(context),
+                None,
+                Some(OutPoint::new(*target, *vout)),
+            ),
+            AckMonitorNews::ScriptPubKeyTransaction(tx_id, vout, context) => (
+                "script_pubkey",
+                Some(context),
+                Some(*tx_id),
+                Some(OutPoint::new(*tx_id, *vout)),
+            ),
+            AckMonitorNews::OpReturnTransaction(tx_id, context) => {
+                ("op_return", Some(context), Some(*tx_id), None)
+            }
+            AckMonitorNews::NewBlock(_) => ("new_block", None, None, None),
+            AckMonitorNews::MonitorReplaced { old, context } => {
+                ("monitor_replaced", Some(context), Some(*old), None)
+            }
+            AckMonitorNews::ReplacedTransactionConfirmed { old, context } => (
+                "replaced_transaction_confirmed",
+                Some(context),
+                Some(*old),
+                None,
+            ),
+            AckMonitorNews::SequenceEpochChanged { .. } => {
+                ("sequence_epoch_changed", None, None, None)
+            }
+            AckMonitorNews::Reorg { .. } => ("reorg", None, None, None),
+            AckMonitorNews::LowWorkReorg { .. } => ("low_work_reorg", None, None, None),
+            AckMonitorNews::HealthStateChanged { .. } => ("health_state_changed", None, None, None),
+            AckMonitorNews::AlternativeResolved { winner, context } => {
+                ("alternative_resolved", Some(context), Some(*winner), None)
+            }
+            AckMonitorNews::MonitorExpired {
+                tx_id,
+                vout,
+                context,
+            } => (
+                "monitor_expired",
+                Some(context),
+                Some(*tx_id),
+                vout.map(|vout| OutPoint::new(*tx_id, vout)),
+            ),
+            AckMonitorNews::ManuallyResolved(descriptor) => {
+                let (context, tx_id, outpoint) = target_fields(&descriptor.monitor);
+                ("manually_resolved", context, tx_id, outpoint)
+            }
+            AckMonitorNews::Composite(_) => ("composite", None, None, None),
+        };
+
+        // The reorg news is acknowledged by the tip it was sent at
+        let block_hash = match ack {
+            AckMonitorNews::Reorg { new_tip } => Some(*new_tip),
+            _ => None,
+        };
+
+        Self {
+            kind,
+            context: context.map(|context| sanitize_context(context)),
+            tx_id,
+            outpoint,
+            block_hash,
+            confirmations: None,
+        }
+    }
+
+    pub fn at_block(mut self, block_hash: BlockHash) -> Self {
+        self.block_hash = Some(block_hash);
+        self
+    }
+
+    pub fn with_confirmations(mut self, confirmations: Option<u32>) -> Self {
+        self.confirmations = confirmations;
+        self
+    }
+
+    /// Logs a news written to the store.
+    pub fn emitted(&self) {
+        info!(
+            kind = self.kind,
+            context = self.context.as_deref(),
+            tx_id = self.tx_id.map(tracing::field::display),
+            outpoint = self.outpoint.map(tracing::field::display),
+            block_hash = self.block_hash.map(tracing::field::display),
+            confirmations = self.confirmations,
+            "News emitted"
+        );
+    }
+
+    /// Logs a news returned by `get_news`, at debug level as consumers poll for them.
+    pub fn read(&self) {
+        debug!(
+            kind = self.kind,
+            context = self.context.as_deref(),
+            tx_id = self.tx_id.map(tracing::field::display),
+            outpoint = self.outpoint.map(tracing::field::display),
+            block_hash = self.block_hash.map(tracing::field::display),
+            confirmations = self.confirmations,
+            "News read"
+        );
+    }
+
+    /// Logs a news acknowledged by the consumer.
+    pub fn acked(&self) {
+        info!(
+            kind = self.kind,
+            context = self.context.as_deref(),
+            tx_id = self.tx_id.map(tracing::field::display),
+            outpoint = self.outpoint.map(tracing::field::display),
+            block_hash = self.block_hash.map(tracing::field::display),
+            confirmations = self.confirmations,
+            "News acknowledged"
+        );
+    }
+}
+
+/// Context, transaction and outpoint of the monitor `target`.
+fn target_fields(
+    target: &SnoozeTarget,
+) -> (Option<&MonitorContext>, Option<Txid>, Option<OutPoint>) {
+    match target {
+        SnoozeTarget::Transaction(tx_id, context) => (Some(context), Some(*tx_id), None),
+        SnoozeTarget::SpendingUTXOTransaction(tx_id, vout, context) => {
+            (Some(context), None, Some(OutPoint::new(*tx_id, *vout)))
+        }
+        SnoozeTarget::ScriptPubKey(_, context) | SnoozeTarget::OpReturnPrefix(_, context) => {
+            (Some(context), None, None)
+        }
+        SnoozeTarget::RskPegin | SnoozeTarget::NewBlock => (None, None, None),
+    }
+}
+
+/// Redacts the context unless contexts are revealed, replaces its control characters, so it
+/// can't break the log lines, and cuts it to `MAX_LOGGED_CONTEXT` characters.
+fn sanitize_context(context: &str) -> String {
+    let context = redact_context(context);
+    let mut sanitized: String = context
+        .chars()
+        .take(MAX_LOGGED_CONTEXT)
+        .map(|c| if c.is_control() { '?' } else { c })
+        .collect();
+
+    if context.chars().count() > MAX_LOGGED_CONTEXT {
+        sanitized.push_str("...");
+    }
+
+    sanitized
+}
@@ -0,0 +1,249 @@
+use crate::config::{MonitorSettings, MonitorSettingsConfig};
+use crate::errors::{MonitorError, MonitorStoreError};
+use crate::external::NoIndexer;
+use crate::migrations::STORE_SCHEMA_VERSION;
+use crate::monitor::Monitor;
+use crate::self_test::CheckOutcome;
+use crate::store::{MonitorStore, MonitorStoreApi};
+use bitcoin::Network;
+use bitcoin_indexer::indexer::IndexerApi;
+use bitcoincore_rpc::RpcApi;
+use bitvmx_bitcoin_rpc::{bitcoin_client::BitcoinClient, rpc_config::RpcConfig};
+use mockall::automock;
+use serde::Serialize;
+use std::fmt;
+use std::sync::Arc;
+use storage_backend::{storage::Storage, storage_config::StorageConfig};
+
+/// Calls to the node outside of the indexer: the network it follows, recorded in the store on
+/// the first tick and checked by [`Monitor::preflight`].
+#[automock]
+pub trait NodeApi {
+    /// Network of the chain the node follows.
+    fn get_network(&self) -> Result<Network, MonitorError>;
+}
+
+impl NodeApi for BitcoinClient {
+    fn get_network(&self) -> Result<Network, MonitorError> {
+        let info = self
+            .client
+            .get_blockchain_info()
+            .map_err(|e| MonitorError::NodeRpcError(e.to_string()))?;
+        Ok(info.chain)
+    }
+}
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct PreflightCheck {
+    pub name: &'static str,
+    pub outcome: CheckOutcome,
+}
+
+/// Checks run by [`Monitor::preflight`], in the order they ran. Every check runs, so the report
+/// has all the problems found at once.
+#[derive(Serialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct PreflightReport {
+    pub checks: Vec<PreflightCheck>,
+}
+
+impl PreflightReport {
+    /// Whether no check failed. Skipped checks don't fail the report.
+    pub fn passed(&self) -> bool {
+        !self
+            .checks
+            .iter()
+            .any(|check| matches!(check.outcome, CheckOutcome::Failed(_)))
+    }
+
+    /// Outcome of the check `name`, None if it is not in the report.
+    pub fn outcome(&self, name: &str) -> Option<&CheckOutcome> {
+        self.checks
+            .iter()
+            .find(|check| check.name == name)
+            .map(|check| &check.outcome)
+    }
+
+    fn push(&mut self, name: &'static str, outcome: CheckOutcome) {
+        self.checks.push(PreflightCheck { name, outcome });
+    }
+}
+
+impl fmt::Display for PreflightReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            let (label, reason) = match &check.outcome {
+                CheckOutcome::Passed => ("PASS", ""),
+                CheckOutcome::Failed(reason) => ("FAIL", reason.as_str()),
+                CheckOutcome::Skipped(reason) => ("SKIP", reason.as_str()),
+            };
+            writeln!(f, "{label} {:<10} {reason}", check.name)?;
+        }
+
+        let count = |label: fn(&CheckOutcome) -> bool| {
+            self.checks
+                .iter()
+                .filter(|check| label(&check.outcome))
+                .count()
+        };
+        write!(
+            f,
+            "{} passed, {} failed, {} skipped",
+            count(|outcome| matches!(outcome, CheckOutcome::Passed)),
+            count(|outcome| matches!(outcome, CheckOutcome::Failed(_))),
+            count(|outcome| matches!(outcome, CheckOutcome::Skipped(_))),
+        )
+    }
+}
+
+impl<I, B> Monitor<I, B>
+where
+    I: IndexerApi,
+    B: MonitorStoreApi,
+{
+    /// Checks that the monitor can run, without ticking it:
+    /// - `node`: the node answers, skipped without a [`NodeApi`] (see [`Monitor::with_node`])
+    /// - `network`: the node follows the network recorded in the store, skipped until the first
+    ///   tick records it
+    /// - `schema`: the store is not from a newer version, skipped when it is from an older one
+    ///   and opening it migrates it
+    /// - `storage`: the store can be written
+    /// - `settings`: `confirmation_threshold` is not above `max_monitoring_confirmations`
+    ///
+    /// Every check runs whatever the outcome of the others, see [`PreflightReport::passed`].
+    pub fn preflight(&self) -> PreflightReport {
+        let mut report = PreflightReport::default();
+
+        let node_network = check_node(&mut report, self.node.as_deref().map(|node| node as _));
+
+        let network = match (node_network, self.store.get_network()) {
+            (_, Err(e)) => CheckOutcome::Failed(e.to_string()),
+            (None, Ok(_)) => CheckOutcome::Skipped("the node didn't answer".to_string()),
+            (Some(_), Ok(None)) => CheckOutcome::Skipped(
+                "no network recorded in the store, it is on the first tick".to_string(),
+            ),
+            (Some(node), Ok(Some(recorded))) if node != recorded => CheckOutcome::Failed(format!(
+                "the node follows {node}, the store was built on {recorded}"
+            )),
+            (Some(_), Ok(Some(_))) => CheckOutcome::Passed,
+        };
+        report.push("network", network);
+
+        let schema = match self.store.get_schema_version() {
+            Err(e) => CheckOutcome::Failed(e.to_string()),
+            Ok(version) if version > STORE_SCHEMA_VERSION => CheckOutcome::Failed(
+                MonitorStoreError::IncompatibleSchema(version, STORE_SCHEMA_VERSION).to_string(),
+            ),
+            Ok(version) if version < STORE_SCHEMA_VERSION => CheckOutcome::Skipped(format!(
+                "the store is at schema version {version}, the monitor migrates it to {} on start",
+                STORE_SCHEMA_VERSION
+            )),
+            Ok(_) => CheckOutcome::Passed,
+        };
+        report.push("schema", schema);
+
+        let storage = match self.store.check_writable() {
+            Ok(()) => CheckOutcome::Passed,
+            Err(e) => CheckOutcome::Failed(e.to_string()),
+        };
+        report.push("storage", storage);
+
+        report.push("settings", check_settings(&self.settings));
+
+        report
+    }
+}
+
+/// Runs [`Monitor::preflight`] on the node of `rpc_config` and the store at `storage_config`,
+/// for the `--check` mode. The store is opened without migrating it, so the preflight leaves it
+/// as it was. A node client or a storage that can't be built fails its check, the others still
+/// run.
+pub fn preflight_with_paths(
+    rpc_config: &RpcConfig,
+    storage_config: &StorageConfig,
+    settings: Option<MonitorSettingsConfig>,
+) -> PreflightReport {
+    let settings = MonitorSettings::from(settings.unwrap_or_default());
+
+    let (node, node_error) = match BitcoinClient::new_from_config(rpc_config) {
+        Ok(client) => (Some(client), None),
+        Err(e) => (None, Some(MonitorError::from(e).to_string())),
+    };
+
+    let mut report = match Storage::new(storage_config) {
+        Ok(storage) => {
+            let store = MonitorStore::new_unmigrated(Arc::new(storage));
+            let mut monitor = match Monitor::new(NoIndexer, store, settings) {
+                Ok(monitor) => monitor,
+                Err(e) => {
+                    let mut report = PreflightReport::default();
+                    report.push("monitor", CheckOutcome::Failed(e.to_string()));
+                    return report;
+                }
+            };
+            if let Some(client) = node {
+                monitor = monitor.with_node(Box::new(client));
+            }
+            monitor.preflight()
+        }
+        Err(e) => {
+            let mut report = PreflightReport::default();
+            let node_network = check_node(
+                &mut report,
+                node.as_ref().map(|client| client as &dyn NodeApi),
+            );
+            let network = match node_network {
+                Some(_) => "the store couldn't be opened",
+                None => "the node didn't answer",
+            };
+            report.push("network", CheckOutcome::Skipped(network.to_string()));
+            report.push(
+                "schema",
+                CheckOutcome::Skipped("the store couldn't be opened".to_string()),
+            );
+            report.push(
+                "storage",
+                CheckOutcome::Failed(MonitorStoreError::from(e).to_string()),
+            );
+            report.push("settings", check_settings(&settings));
+            report
+        }
+    };
+
+    // Without a client the node check was skipped, it failed
+    if let Some(error) = node_error {
+        report.checks[0].outcome = CheckOutcome::Failed(error);
+    }
+
+    report
+}
+
+/// Pushes the `node` check, returning the network of the node when it answered.
+fn check_node(report: &mut PreflightReport, node: Option<&dyn NodeApi>) -> Option<Network> {
+    match node {
+        Some(node) => match node.get_network() {
+            Ok(network) => {
+                report.push("node", CheckOutcome::Passed);
+                Some(network)
+            }
+            Err(e) => {
+                report.push("node", CheckOutcome::Failed(e.to_string()));
+                None
+            }
+        },
+        None => {
+            report.push("node", CheckOutcome::Skipped("no node set".to_string()));
+            None
+        }
+    }
+}
+
+fn check_settings(settings: &MonitorSettings) -> CheckOutcome {
+    if settings.confirmation_threshold > settings.max_monitoring_confirmations {
+        CheckOutcome::Failed(format!(
+            "confirmation_threshold {} is above max_monitoring_confirmations {}",
+            settings.confirmation_threshold, settings.max_monitoring_confirmations
+        ))
+    } else {
+        CheckOutcome::Passed
+    }
+}
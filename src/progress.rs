@@ -0,0 +1,106 @@
+use crate::store::TypesToMonitorStore;
+use crate::types::{ContextProgress, MonitorEvent, MonitorInfo};
+use bitvmx_bitcoin_rpc::types::BlockHeight;
+use std::collections::BTreeMap;
+
+/// State of a monitor given by its last events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum MonitorProgress {
+    Pending,
+    Detected,
+    Finalized,
+    Orphaned,
+    Expired,
+}
+
+/// Progress of the monitors in `monitors` by context, sorted by context. Monitors without a
+/// context (new block, pegins) are left out.
+pub fn context_progress(
+    monitors: &[MonitorInfo],
+    confirmation_threshold: u32,
+    monitor_height: BlockHeight,
+) -> BTreeMap<String, ContextProgress> {
+    let mut progress: BTreeMap<String, ContextProgress> = BTreeMap::new();
+
+    for info in monitors {
+        let context = info.monitor.context();
+        if context.is_empty() {
+            continue;
+        }
+
+        let entry = progress
+            .entry(context.to_string())
+            .or_insert_with(|| ContextProgress {
+                context: context.into(),
+                ..Default::default()
+            });
+        entry.registered += 1;
+
+        match monitor_progress(info, confirmation_threshold, monitor_height) {
+            MonitorProgress::Pending => {}
+            MonitorProgress::Detected => entry.detected += 1,
+            MonitorProgress::Finalized => {
+                entry.detected += 1;
+                entry.finalized += 1;
+            }
+            MonitorProgress::Orphaned => entry.orphaned += 1,
+            MonitorProgress::Expired => entry.expired += 1,
+        }
+    }
+
+    for entry in progress.values_mut() {
+        entry.ratio = f64::from(entry.finalized) / f64::from(entry.registered);
+    }
+
+    progress
+}
+
+/// Walks the history of the monitor, oldest first: the last transaction found decides the state.
+fn monitor_progress(
+    info: &MonitorInfo,
+    confirmation_threshold: u32,
+    monitor_height: BlockHeight,
+) -> MonitorProgress {
+    let mut state = MonitorProgress::Pending;
+    let mut current = None;
+
+    for event in &info.history {
+        match event {
+            MonitorEvent::Registered { .. } => {
+                state = MonitorProgress::Pending;
+                current = None;
+            }
+            MonitorEvent::Detected { tx_id, .. } => {
+                if current != Some(*tx_id) || state == MonitorProgress::Orphaned {
+                    state = MonitorProgress::Detected;
+                }
+                current = Some(*tx_id);
+            }
+            MonitorEvent::Confirmed {
+                tx_id,
+                confirmations,
+            } if current == Some(*tx_id) && *confirmations >= confirmation_threshold => {
+                state = MonitorProgress::Finalized;
+            }
+            MonitorEvent::Orphaned { tx_id, .. } if current == Some(*tx_id) => {
+                state = MonitorProgress::Orphaned;
+            }
+            // Deactivated past `max_monitoring_confirmations`, above the threshold
+            MonitorEvent::Deactivated { .. } if current.is_some() => {
+                state = MonitorProgress::Finalized;
+            }
+            _ => {}
+        }
+    }
+
+    let deadline_passed = matches!(
+        &info.monitor,
+        TypesToMonitorStore::SpendingUTXOTransaction(_, _, _, _, Some(deadline), _, _)
+            if deadline.height < monitor_height
+    );
+    if state == MonitorProgress::Pending && deadline_passed {
+        return MonitorProgress::Expired;
+    }
+
+    state
+}
@@ -0,0 +1,103 @@
+use crate::errors::MonitorError;
+use crate::monitor::Monitor;
+use crate::store::MonitorStoreApi;
+use crate::types::{MonitorContext, TypesToMonitor};
+use bitcoin::{psbt::Psbt, OutPoint, TxOut, Txid};
+use bitcoin_indexer::indexer::IndexerApi;
+
+/// Why an output of a PSBT got no SpendingUTXO monitor
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PsbtOutputExclusion {
+    /// OP_RETURN outputs can't be spent
+    OpReturn,
+    /// Outputs below the dust limit of the default relay policy for their script
+    Dust,
+}
+
+impl PsbtOutputExclusion {
+    fn of(output: &TxOut) -> Option<Self> {
+        if output.script_pubkey.is_op_return() {
+            Some(Self::OpReturn)
+        } else if output.value < output.script_pubkey.minimal_non_dust() {
+            Some(Self::Dust)
+        } else {
+            None
+        }
+    }
+}
+
+/// Monitors registered by [`Monitor::monitor_from_psbt`]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PsbtMonitorPlan {
+    /// Txid of the unsigned transaction, watched by a Transaction monitor
+    pub txid: Txid,
+    /// Outputs watched by a SpendingUTXO monitor, in output order
+    pub outpoints: Vec<OutPoint>,
+    /// Outputs left unwatched, in output order
+    pub excluded: Vec<(OutPoint, PsbtOutputExclusion)>,
+}
+
+impl<I, B> Monitor<I, B>
+where
+    I: IndexerApi,
+    B: MonitorStoreApi,
+{
+    /// Registers the monitors of the transaction a PSBT will be finalized into: a Transaction
+    /// monitor for the txid of its unsigned transaction and a SpendingUTXO monitor for each of
+    /// its outputs, except OP_RETURN and dust outputs. Every monitor gets `context`.
+    ///
+    /// Finalizing only adds signatures, which don't change the txid. If the inputs change
+    /// afterwards, the Transaction monitor can be moved with `replace_monitored_tx`.
+    ///
+    /// # Returns
+    /// - `Ok(PsbtMonitorPlan)`: The monitors registered and the outputs excluded
+    /// - `Err`: If the context is invalid or there was an error writing to the store, in which
+    ///   case nothing is registered
+    pub fn monitor_from_psbt(
+        &self,
+        psbt: &Psbt,
+        context: impl Into<MonitorContext>,
+    ) -> Result<PsbtMonitorPlan, MonitorError> {
+        let context = context.into();
+        let txid = psbt.unsigned_tx.compute_txid();
+        let mut plan = PsbtMonitorPlan {
+            txid,
+            outpoints: vec![],
+            excluded: vec![],
+        };
+
+        for (vout, output) in psbt.unsigned_tx.output.iter().enumerate() {
+            let outpoint = OutPoint::new(txid, vout as u32);
+            match PsbtOutputExclusion::of(output) {
+                Some(exclusion) => plan.excluded.push((outpoint, exclusion)),
+                None => plan.outpoints.push(outpoint),
+            }
+        }
+
+        let mut items = vec![TypesToMonitor::Transactions(
+            vec![txid],
+            context.clone(),
+            None,
+            None,
+        )];
+        items.extend(plan.outpoints.iter().map(|outpoint| {
+            TypesToMonitor::SpendingUTXOTransaction(
+                outpoint.txid,
+                outpoint.vout,
+                context.clone(),
+                None,
+                None,
+                None,
+                None,
+            )
+        }));
+
+        // Every item has the same context and no trigger, so either all of them are valid or
+        // none is written
+        for outcome in self.monitor_batch(items)? {
+            outcome?;
+        }
+
+        Ok(plan)
+    }
+}
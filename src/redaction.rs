@@ -0,0 +1,52 @@
+use bitcoin::hashes::{sha256, Hash};
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Bytes of a context kept in the clear when contexts are redacted.
+const REDACTED_CONTEXT_PREFIX: usize = 8;
+
+static REVEAL_CONTEXTS: AtomicBool = AtomicBool::new(false);
+
+/// Shows the full contexts in the `Debug` output of the monitor types, the news logs and the
+/// exports, for debugging environments. Contexts are redacted by default. The toggle is global
+/// to the process; a monitor sets it from its `reveal_contexts` setting when built and when its
+/// settings are updated.
+pub fn reveal_contexts(reveal: bool) {
+    REVEAL_CONTEXTS.store(reveal, Ordering::Relaxed);
+}
+
+pub fn contexts_revealed() -> bool {
+    REVEAL_CONTEXTS.load(Ordering::Relaxed)
+}
+
+/// Returns `context` as it may be written to logs and exports: unless contexts are revealed,
+/// only its first 8 bytes, followed by `…` when it is longer, and the first 4 bytes of its
+/// sha256 so equal contexts can still be correlated.
+pub fn redact_context(context: &str) -> String {
+    if contexts_revealed() || context.is_empty() {
+        return context.to_string();
+    }
+
+    let mut end = REDACTED_CONTEXT_PREFIX.min(context.len());
+    while !context.is_char_boundary(end) {
+        end -= 1;
+    }
+    let ellipsis = if end < context.len() { "…" } else { "" };
+    let hash = sha256::Hash::hash(context.as_bytes());
+
+    format!(
+        "{}{}#{}",
+        &context[..end],
+        ellipsis,
+        hex::encode(&hash.as_byte_array()[..4])
+    )
+}
+
+/// `Debug` of a context, redacted with [`redact_context`].
+pub struct RedactedContext<'a>(pub &'a str);
+
+impl fmt::Debug for RedactedContext<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&redact_context(self.0), f)
+    }
+}
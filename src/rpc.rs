@@ -0,0 +1,349 @@
+use crate::api::v1::{
+    AckRequest, ErrorResponse, MonitorSpec, MonitorsResponse, NewsResponse, OkResponse,
+    RegisterRequest, TxStatusResponse,
+};
+use crate::commands::MonitorHandle;
+use crate::errors::MonitorError;
+use crate::http::{read_request, write_json, Server, MAX_BODY_SIZE};
+use crate::types::TypesToMonitor;
+use bitcoin::Txid;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::Value;
+use std::io;
+use std::net::{SocketAddr, TcpStream, ToSocketAddrs};
+
+pub const JSONRPC_VERSION: &str = "2.0";
+
+/// The body is not valid JSON.
+pub const PARSE_ERROR: i64 = -32700;
+/// The JSON is not a JSON-RPC 2.0 request.
+pub const INVALID_REQUEST: i64 = -32600;
+pub const METHOD_NOT_FOUND: i64 = -32601;
+pub const INVALID_PARAMS: i64 = -32602;
+pub const INTERNAL_ERROR: i64 = -32603;
+/// The monitor failed the call, the error is described by the [`ErrorResponse`] of its `data`.
+pub const MONITOR_ERROR: i64 = -32000;
+
+/// A JSON-RPC 2.0 request. Build them from an [`RpcCall`] with [`RpcCall::into_request`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RpcRequest {
+    pub jsonrpc: String,
+    pub method: String,
+    /// Parameters by name, see the parameters of each [`RpcCall`]
+    #[serde(default, skip_serializing_if = "Value::is_null")]
+    pub params: Value,
+    /// None for a notification, which is not answered
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+}
+
+/// The methods of the server, with their parameters. The result of each one is a payload of
+/// [`crate::api::v1`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum RpcCall {
+    /// `monitor_add`, registers a monitor. Returns an [`OkResponse`]
+    MonitorAdd(RegisterRequest),
+    /// `monitor_cancel`, cancels a monitor. Returns an [`OkResponse`]
+    MonitorCancel(MonitorCancelParams),
+    /// `monitor_list`, lists the monitors. Returns a [`MonitorsResponse`]
+    MonitorList(MonitorListParams),
+    /// `news_get`, without parameters, returns the pending news as a [`NewsResponse`]
+    NewsGet,
+    /// `news_ack`, acknowledges a news. Returns an [`OkResponse`]
+    NewsAck(AckRequest),
+    /// `tx_status`, returns the status of a transaction as a [`TxStatusResponse`]
+    TxStatus(TxStatusParams),
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct MonitorCancelParams {
+    pub monitor: MonitorSpec,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct MonitorListParams {
+    /// Lists the inactive monitors too
+    #[serde(default)]
+    pub include_inactive: bool,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct TxStatusParams {
+    pub txid: Txid,
+}
+
+impl RpcCall {
+    pub fn method(&self) -> &'static str {
+        match self {
+            RpcCall::MonitorAdd(_) => "monitor_add",
+            RpcCall::MonitorCancel(_) => "monitor_cancel",
+            RpcCall::MonitorList(_) => "monitor_list",
+            RpcCall::NewsGet => "news_get",
+            RpcCall::NewsAck(_) => "news_ack",
+            RpcCall::TxStatus(_) => "tx_status",
+        }
+    }
+
+    pub fn into_request(self, id: impl Into<Value>) -> Result<RpcRequest, serde_json::Error> {
+        let method = self.method().to_string();
+        let params = match self {
+            RpcCall::MonitorAdd(params) => serde_json::to_value(params)?,
+            RpcCall::MonitorCancel(params) => serde_json::to_value(params)?,
+            RpcCall::MonitorList(params) => serde_json::to_value(params)?,
+            RpcCall::NewsGet => Value::Null,
+            RpcCall::NewsAck(params) => serde_json::to_value(params)?,
+            RpcCall::TxStatus(params) => serde_json::to_value(params)?,
+        };
+
+        Ok(RpcRequest {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            method,
+            params,
+            id: Some(id.into()),
+        })
+    }
+}
+
+impl TryFrom<&RpcRequest> for RpcCall {
+    type Error = RpcError;
+
+    /// Fails with [`METHOD_NOT_FOUND`] for an unknown method and [`INVALID_PARAMS`] when the
+    /// parameters don't match the method.
+    fn try_from(request: &RpcRequest) -> Result<Self, Self::Error> {
+        fn params<T: DeserializeOwned>(params: &Value) -> Result<T, RpcError> {
+            // Methods whose parameters all have defaults may be called without them
+            let params = match params {
+                Value::Null => Value::Object(Default::default()),
+                params => params.clone(),
+            };
+            serde_json::from_value(params).map_err(|e| RpcError::new(INVALID_PARAMS, e))
+        }
+
+        Ok(match request.method.as_str() {
+            "monitor_add" => RpcCall::MonitorAdd(params(&request.params)?),
+            "monitor_cancel" => RpcCall::MonitorCancel(params(&request.params)?),
+            "monitor_list" => RpcCall::MonitorList(params(&request.params)?),
+            "news_get" => {
+                params::<NoParams>(&request.params)?;
+                RpcCall::NewsGet
+            }
+            "news_ack" => RpcCall::NewsAck(params(&request.params)?),
+            "tx_status" => RpcCall::TxStatus(params(&request.params)?),
+            method => {
+                return Err(RpcError::new(
+                    METHOD_NOT_FOUND,
+                    format!("unknown method {}", method),
+                ))
+            }
+        })
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct NoParams {}
+
+/// A JSON-RPC 2.0 response, holding either a result or an error.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct RpcResponse {
+    pub jsonrpc: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+    /// Null when the id of the request couldn't be read
+    pub id: Value,
+}
+
+impl RpcResponse {
+    pub fn result(id: Value, result: Value) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            result: Some(result),
+            error: None,
+            id,
+        }
+    }
+
+    pub fn error(id: Value, error: RpcError) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            result: None,
+            error: Some(error),
+            id,
+        }
+    }
+
+    /// The result read as the payload of the method called, or the error of the response.
+    pub fn into_result<T: DeserializeOwned>(self) -> Result<T, RpcError> {
+        match (self.error, self.result) {
+            (Some(error), _) => Err(error),
+            (None, Some(result)) => {
+                serde_json::from_value(result).map_err(|e| RpcError::new(INTERNAL_ERROR, e))
+            }
+            (None, None) => Err(RpcError::new(INTERNAL_ERROR, "response without result")),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(deny_unknown_fields)]
+pub struct RpcError {
+    pub code: i64,
+    pub message: String,
+    /// Set for the [`MONITOR_ERROR`] code
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<ErrorResponse>,
+}
+
+impl RpcError {
+    pub fn new(code: i64, message: impl ToString) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+            data: None,
+        }
+    }
+}
+
+impl From<MonitorError> for RpcError {
+    fn from(error: MonitorError) -> Self {
+        Self {
+            code: MONITOR_ERROR,
+            message: error.to_string(),
+            data: Some(ErrorResponse::from(&error)),
+        }
+    }
+}
+
+/// Serves the [`RpcCall`] methods as JSON-RPC 2.0 over HTTP: a request, or a batch of them, is
+/// posted as the body and the responses are returned as the body of the answer. Notifications
+/// are applied but not answered, a body of notifications only is answered 204.
+///
+/// The calls go through a [`MonitorHandle`], see there why, and are applied by the run loop
+/// between ticks or at the start of the next one, never during a tick. Requests
+/// are served one at a time. The listener stops when the server is dropped.
+pub struct RpcServer {
+    server: Server,
+}
+
+impl RpcServer {
+    pub fn start(addr: impl ToSocketAddrs, monitor: MonitorHandle) -> io::Result<Self> {
+        let server = Server::start(addr, "rpc", move |stream| serve(stream, &monitor))?;
+
+        Ok(Self { server })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.server.local_addr()
+    }
+}
+
+fn serve(stream: TcpStream, monitor: &MonitorHandle) -> io::Result<()> {
+    let request = read_request(&stream)?;
+
+    let (status, message) = match &request.body {
+        _ if request.method != "POST" => (405, "requests are posted".to_string()),
+        None => (413, format!("the body is over {} bytes", MAX_BODY_SIZE)),
+        Some(body) => {
+            return match handle_body(body, monitor)? {
+                Some(responses) => write_json(stream, 200, &responses),
+                None => write_json(stream, 204, ""),
+            };
+        }
+    };
+
+    let response = RpcResponse::error(Value::Null, RpcError::new(INVALID_REQUEST, message));
+    let body = serde_json::to_string(&response).map_err(io::Error::other)?;
+    write_json(stream, status, &body)
+}
+
+/// The responses to a request or a batch, as JSON. None when there is nothing to answer.
+fn handle_body(body: &[u8], monitor: &MonitorHandle) -> io::Result<Option<String>> {
+    let json = match serde_json::from_slice::<Value>(body) {
+        Err(e) => serde_json::to_string(&RpcResponse::error(
+            Value::Null,
+            RpcError::new(PARSE_ERROR, e),
+        )),
+        Ok(Value::Array(calls)) if calls.is_empty() => serde_json::to_string(&RpcResponse::error(
+            Value::Null,
+            RpcError::new(INVALID_REQUEST, "empty batch"),
+        )),
+        Ok(Value::Array(calls)) => {
+            let responses: Vec<RpcResponse> = calls
+                .into_iter()
+                .filter_map(|call| handle_call(call, monitor))
+                .collect();
+            if responses.is_empty() {
+                return Ok(None);
+            }
+            serde_json::to_string(&responses)
+        }
+        Ok(call) => match handle_call(call, monitor) {
+            Some(response) => serde_json::to_string(&response),
+            None => return Ok(None),
+        },
+    };
+
+    json.map(Some).map_err(io::Error::other)
+}
+
+/// The response to a request, None for a notification.
+fn handle_call(call: Value, monitor: &MonitorHandle) -> Option<RpcResponse> {
+    let id = call.get("id").cloned().unwrap_or_default();
+    let request = match serde_json::from_value::<RpcRequest>(call) {
+        Ok(request) if request.jsonrpc == JSONRPC_VERSION => request,
+        Ok(_) => {
+            let error = RpcError::new(INVALID_REQUEST, "jsonrpc must be \"2.0\"");
+            return Some(RpcResponse::error(id, error));
+        }
+        Err(e) => return Some(RpcResponse::error(id, RpcError::new(INVALID_REQUEST, e))),
+    };
+
+    let result = RpcCall::try_from(&request).and_then(|call| execute(call, monitor));
+    let id = request.id?;
+
+    Some(match result {
+        Ok(result) => RpcResponse::result(id, result),
+        Err(error) => RpcResponse::error(id, error),
+    })
+}
+
+fn execute(call: RpcCall, monitor: &MonitorHandle) -> Result<Value, RpcError> {
+    fn json(payload: impl Serialize) -> Result<Value, RpcError> {
+        serde_json::to_value(payload).map_err(|e| RpcError::new(INTERNAL_ERROR, e))
+    }
+
+    match call {
+        RpcCall::MonitorAdd(request) => {
+            let (data, options) = request.into_registration()?;
+            monitor.monitor_with(data, options)?;
+            json(OkResponse::default())
+        }
+        RpcCall::MonitorCancel(params) => {
+            monitor.cancel(TypesToMonitor::try_from(params.monitor)?)?;
+            json(OkResponse::default())
+        }
+        RpcCall::MonitorList(params) => {
+            let monitors = monitor.get_monitors(params.include_inactive)?;
+            json(MonitorsResponse::from(monitors.as_slice()))
+        }
+        RpcCall::NewsGet => {
+            let news = monitor.get_news_envelopes()?;
+            json(NewsResponse::from(news.as_slice()))
+        }
+        RpcCall::NewsAck(request) => {
+            monitor.ack_news(request.news.into())?;
+            json(OkResponse::default())
+        }
+        RpcCall::TxStatus(params) => {
+            let status = monitor.get_tx_status(params.txid)?;
+            json(TxStatusResponse::from(&status))
+        }
+    }
+}
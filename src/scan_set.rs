@@ -0,0 +1,61 @@
+use crate::types::TransactionRef;
+use bitcoin::{ScriptBuf, TxOut, Txid};
+use std::collections::{HashMap, HashSet};
+
+/// Scripts watched by the active ScriptPubKey monitors. The monitor keeps it across ticks and
+/// builds it again only when the scan generation of the store moves, so each block is scanned
+/// with one lookup per output whatever the number of monitors.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScanSet {
+    /// Scan generation of the store the set was built at
+    pub generation: u64,
+    pub scripts: HashSet<ScriptBuf>,
+}
+
+/// An output of a block paying to a watched script.
+pub(crate) type ScannedOutput<'a> = (Txid, u32, &'a TxOut);
+
+impl ScanSet {
+    /// Set of `scripts`, added one at a time so the monitors they come from are never all loaded.
+    pub fn new(generation: u64, scripts: impl IntoIterator<Item = ScriptBuf>) -> Self {
+        Self {
+            generation,
+            scripts: scripts.into_iter().collect(),
+        }
+    }
+
+    /// Number of scripts watched.
+    pub fn len(&self) -> usize {
+        self.scripts.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scripts.is_empty()
+    }
+
+    /// Outputs of `block_txs` paying to a watched script, by script, in block order.
+    pub(crate) fn scan<'a>(
+        &self,
+        block_txs: &'a [(Txid, TransactionRef)],
+    ) -> HashMap<&'a ScriptBuf, Vec<ScannedOutput<'a>>> {
+        let mut found: HashMap<&ScriptBuf, Vec<ScannedOutput>> = HashMap::new();
+
+        if self.scripts.is_empty() {
+            return found;
+        }
+
+        for (tx_id, tx) in block_txs {
+            for (vout, output) in tx.output.iter().enumerate() {
+                if self.scripts.contains(&output.script_pubkey) {
+                    found.entry(&output.script_pubkey).or_default().push((
+                        *tx_id,
+                        vout as u32,
+                        output,
+                    ));
+                }
+            }
+        }
+
+        found
+    }
+}
@@ -0,0 +1,539 @@
+use crate::api::v1;
+use crate::migrations::STORE_SCHEMA_VERSION;
+use crate::types::MONITOR_EXPORT_VERSION;
+use serde::Serialize;
+use std::fmt;
+
+/// Description of the JSON payloads of [`crate::api::v1`]: the monitors registered, the news
+/// and the objects they carry, with the type and optionality of each field. Code generators of
+/// client types read it instead of the Rust enums, with `schema --json` or `GET /schema`.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SchemaDescription {
+    pub api_version: &'static str,
+    pub store_schema_version: u32,
+    pub monitor_export_version: u32,
+    /// Variants of [`v1::MonitorSpec`], told apart by their `kind`.
+    pub monitors: &'static [VariantSchema],
+    /// Variants of [`v1::News`], told apart by their `kind`.
+    pub news: &'static [VariantSchema],
+    /// Variants of [`v1::Target`], told apart by their `kind`.
+    pub targets: &'static [VariantSchema],
+    pub objects: &'static [ObjectSchema],
+    pub enums: &'static [EnumSchema],
+}
+
+impl SchemaDescription {
+    pub fn monitor(&self, kind: &str) -> Option<&'static VariantSchema> {
+        self.monitors.iter().find(|variant| variant.kind == kind)
+    }
+
+    pub fn news_kind(&self, kind: &str) -> Option<&'static VariantSchema> {
+        self.news.iter().find(|variant| variant.kind == kind)
+    }
+
+    pub fn target(&self, kind: &str) -> Option<&'static VariantSchema> {
+        self.targets.iter().find(|variant| variant.kind == kind)
+    }
+
+    pub fn object(&self, name: &str) -> Option<&'static ObjectSchema> {
+        self.objects.iter().find(|object| object.name == name)
+    }
+
+    pub fn enumeration(&self, name: &str) -> Option<&'static EnumSchema> {
+        self.enums
+            .iter()
+            .find(|enumeration| enumeration.name == name)
+    }
+}
+
+/// A variant of a payload tagged by `kind`, its fields are next to the tag.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct VariantSchema {
+    pub kind: &'static str,
+    pub description: &'static str,
+    pub fields: &'static [FieldSchema],
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ObjectSchema {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub fields: &'static [FieldSchema],
+}
+
+/// A string taking one of `values`.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnumSchema {
+    pub name: &'static str,
+    pub values: &'static [&'static str],
+}
+
+/// A field of an object or a variant. An optional field may be null, or left out of a request.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FieldSchema {
+    pub name: &'static str,
+    #[serde(rename = "type")]
+    pub ty: FieldType,
+    pub optional: bool,
+}
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FieldType {
+    String,
+    /// Hex string of bytes, e.g. a script or a consensus encoded transaction.
+    Hex,
+    /// Hex string of a txid, in the byte order shown by the explorers.
+    Txid,
+    /// Hex string of a block hash, in the byte order shown by the explorers.
+    BlockHash,
+    U32,
+    U64,
+    Bool,
+    /// Object with string keys and string values.
+    StringMap,
+    Array(&'static FieldType),
+    /// One of [`SchemaDescription::objects`].
+    Object(&'static str),
+    /// One of [`SchemaDescription::enums`].
+    Enum(&'static str),
+    /// One of [`SchemaDescription::news`].
+    News,
+    /// One of [`SchemaDescription::targets`].
+    Target,
+}
+
+impl fmt::Display for FieldType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FieldType::String => write!(f, "string"),
+            FieldType::Hex => write!(f, "hex"),
+            FieldType::Txid => write!(f, "txid"),
+            FieldType::BlockHash => write!(f, "block_hash"),
+            FieldType::U32 => write!(f, "u32"),
+            FieldType::U64 => write!(f, "u64"),
+            FieldType::Bool => write!(f, "bool"),
+            FieldType::StringMap => write!(f, "map<string, string>"),
+            FieldType::Array(item) => write!(f, "{}[]", item),
+            FieldType::Object(name) | FieldType::Enum(name) => write!(f, "{}", name),
+            FieldType::News => write!(f, "news"),
+            FieldType::Target => write!(f, "target"),
+        }
+    }
+}
+
+impl fmt::Display for FieldSchema {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let optional = if self.optional { "?" } else { "" };
+        write!(f, "{}{}: {}", self.name, optional, self.ty)
+    }
+}
+
+const fn field(name: &'static str, ty: FieldType) -> FieldSchema {
+    FieldSchema {
+        name,
+        ty,
+        optional: false,
+    }
+}
+
+const fn optional(name: &'static str, ty: FieldType) -> FieldSchema {
+    FieldSchema {
+        name,
+        ty,
+        optional: true,
+    }
+}
+
+const TX_STATUS: FieldType = FieldType::Object("tx_status");
+const CONTEXT: FieldSchema = field("context", FieldType::String);
+const TRIGGER: FieldSchema = optional("trigger", FieldType::U32);
+
+const MONITORS: &[VariantSchema] = &[
+    VariantSchema {
+        kind: "transactions",
+        description: "Transactions, reported when they are seen and at each confirmation",
+        fields: &[
+            field("txids", FieldType::Array(&FieldType::Txid)),
+            CONTEXT,
+            TRIGGER,
+            optional("expires_at_height", FieldType::U32),
+        ],
+    },
+    VariantSchema {
+        kind: "spending_utxo",
+        description: "An output, reported when a transaction spends it",
+        fields: &[
+            field("txid", FieldType::Txid),
+            field("vout", FieldType::U32),
+            CONTEXT,
+            TRIGGER,
+            optional("deadline", FieldType::Object("deadline")),
+            optional("value_gate", FieldType::Object("value_gate")),
+            optional("expires_at_height", FieldType::U32),
+        ],
+    },
+    VariantSchema {
+        kind: "rsk_pegin",
+        description: "RSK pegin transactions, to the committee addresses when set",
+        fields: &[
+            TRIGGER,
+            optional("committee", FieldType::Array(&FieldType::Hex)),
+            optional("packet_range", FieldType::Object("packet_range")),
+            optional("min_amount_sat", FieldType::U64),
+        ],
+    },
+    VariantSchema {
+        kind: "new_block",
+        description: "Every block processed",
+        fields: &[],
+    },
+    VariantSchema {
+        kind: "script_pubkey",
+        description: "Transactions paying to a script_pubkey",
+        fields: &[
+            field("script_pubkey", FieldType::Hex),
+            CONTEXT,
+            TRIGGER,
+            optional("filter", FieldType::Object("filter")),
+        ],
+    },
+    VariantSchema {
+        kind: "op_return_prefix",
+        description: "Transactions with an OP_RETURN output starting with a prefix",
+        fields: &[field("prefix", FieldType::Hex), CONTEXT],
+    },
+    VariantSchema {
+        kind: "alternatives",
+        description: "Alternative transactions, reported when one of them is confirmed",
+        fields: &[field("txids", FieldType::Array(&FieldType::Txid)), CONTEXT],
+    },
+];
+
+const NEWS: &[VariantSchema] = &[
+    VariantSchema {
+        kind: "transaction",
+        description: "A monitored transaction was seen or confirmed",
+        fields: &[
+            field("txid", FieldType::Txid),
+            CONTEXT,
+            field("status", TX_STATUS),
+        ],
+    },
+    VariantSchema {
+        kind: "spending_utxo",
+        description: "A monitored output was spent, `spender` is the spending transaction",
+        fields: &[
+            field("txid", FieldType::Txid),
+            field("vout", FieldType::U32),
+            CONTEXT,
+            field("spender", TX_STATUS),
+        ],
+    },
+    VariantSchema {
+        kind: "spending_utxo_unconfirmed",
+        description: "Spend of an output with a deadline seen in the mempool",
+        fields: &[
+            field("txid", FieldType::Txid),
+            field("vout", FieldType::U32),
+            CONTEXT,
+            field("spender_txid", FieldType::Txid),
+        ],
+    },
+    VariantSchema {
+        kind: "minor_spend",
+        description: "A monitored output was spent moving less than the value gate of its monitor",
+        fields: &[
+            field("txid", FieldType::Txid),
+            field("vout", FieldType::U32),
+            CONTEXT,
+            field("spender", TX_STATUS),
+        ],
+    },
+    VariantSchema {
+        kind: "rsk_pegin",
+        description: "A pegin transaction was found, `pegin` is null when it can't be decoded",
+        fields: &[
+            field("txid", FieldType::Txid),
+            field("status", TX_STATUS),
+            optional("pegin", FieldType::Object("pegin")),
+        ],
+    },
+    VariantSchema {
+        kind: "script_pubkey",
+        description: "A transaction paid to a monitored script_pubkey in output `vout`",
+        fields: &[
+            field("txid", FieldType::Txid),
+            field("vout", FieldType::U32),
+            CONTEXT,
+            field("status", TX_STATUS),
+        ],
+    },
+    VariantSchema {
+        kind: "op_return",
+        description: "A transaction has an OP_RETURN output with a monitored prefix",
+        fields: &[
+            field("txid", FieldType::Txid),
+            field("data", FieldType::Hex),
+            CONTEXT,
+        ],
+    },
+    VariantSchema {
+        kind: "new_block",
+        description: "A block was processed",
+        fields: &[
+            field("height", FieldType::U32),
+            field("hash", FieldType::BlockHash),
+        ],
+    },
+    VariantSchema {
+        kind: "orphaned_transaction",
+        description: "The block of a monitored transaction was orphaned by a reorg",
+        fields: &[
+            field("txid", FieldType::Txid),
+            CONTEXT,
+            field("status", TX_STATUS),
+        ],
+    },
+    VariantSchema {
+        kind: "monitor_replaced",
+        description: "A transaction monitor was moved to a replacement transaction (RBF)",
+        fields: &[
+            field("old_txid", FieldType::Txid),
+            field("new_txid", FieldType::Txid),
+            CONTEXT,
+        ],
+    },
+    VariantSchema {
+        kind: "replaced_transaction_confirmed",
+        description: "A replaced transaction was confirmed anyway",
+        fields: &[
+            field("old_txid", FieldType::Txid),
+            field("new_txid", FieldType::Txid),
+            CONTEXT,
+        ],
+    },
+    VariantSchema {
+        kind: "sequence_epoch_changed",
+        description: "The store was restored from a backup, the news sequence restarts from a new epoch",
+        fields: &[
+            field("old_epoch", FieldType::U64),
+            field("new_epoch", FieldType::U64),
+        ],
+    },
+    VariantSchema {
+        kind: "reorg",
+        description: "A reorg changed monitored items",
+        fields: &[
+            field("old_tip", FieldType::BlockHash),
+            field("new_tip", FieldType::BlockHash),
+            field("summary", FieldType::Object("reorg")),
+        ],
+    },
+    VariantSchema {
+        kind: "low_work_reorg",
+        description: "A reorg replaced blocks by a branch of less work, works are big endian hex",
+        fields: &[
+            field("height", FieldType::U32),
+            field("old_work", FieldType::Hex),
+            field("new_work", FieldType::Hex),
+        ],
+    },
+    VariantSchema {
+        kind: "alternative_resolved",
+        description: "An alternative reached the confirmation threshold, the others were canceled",
+        fields: &[
+            field("winner", FieldType::Txid),
+            field("losers", FieldType::Array(&FieldType::Txid)),
+            CONTEXT,
+        ],
+    },
+    VariantSchema {
+        kind: "monitor_expired",
+        description: "A monitor reached its expiry height without a detection, `vout` is null for a transaction",
+        fields: &[
+            field("txid", FieldType::Txid),
+            optional("vout", FieldType::U32),
+            CONTEXT,
+            field("height", FieldType::U32),
+        ],
+    },
+    VariantSchema {
+        kind: "health_state_changed",
+        description: "The health score crossed a threshold, the monitor entered or left its degraded mode",
+        fields: &[
+            field("state", FieldType::Enum("health")),
+            field("score", FieldType::U32),
+        ],
+    },
+    VariantSchema {
+        kind: "manually_resolved",
+        description: "A monitor was resolved by an operator and deactivated",
+        fields: &[
+            field("monitor", FieldType::Target),
+            field("note", FieldType::String),
+        ],
+    },
+    VariantSchema {
+        kind: "composite",
+        description: "Every predicate of a composite rule was satisfied",
+        fields: &[field("id", FieldType::String)],
+    },
+];
+
+const TARGETS: &[VariantSchema] = &[
+    VariantSchema {
+        kind: "transaction",
+        description: "A transaction monitor",
+        fields: &[field("txid", FieldType::Txid), CONTEXT],
+    },
+    VariantSchema {
+        kind: "spending_utxo",
+        description: "An output monitor",
+        fields: &[
+            field("txid", FieldType::Txid),
+            field("vout", FieldType::U32),
+            CONTEXT,
+        ],
+    },
+    VariantSchema {
+        kind: "rsk_pegin",
+        description: "The pegin monitor",
+        fields: &[],
+    },
+    VariantSchema {
+        kind: "new_block",
+        description: "The new block monitor",
+        fields: &[],
+    },
+    VariantSchema {
+        kind: "script_pubkey",
+        description: "A script_pubkey monitor",
+        fields: &[field("script_pubkey", FieldType::Hex), CONTEXT],
+    },
+    VariantSchema {
+        kind: "op_return_prefix",
+        description: "An OP_RETURN prefix monitor",
+        fields: &[field("prefix", FieldType::Hex), CONTEXT],
+    },
+];
+
+const OBJECTS: &[ObjectSchema] = &[
+    ObjectSchema {
+        name: "news_item",
+        description: "A pending news with its position in the news sequence",
+        fields: &[
+            field("epoch", FieldType::U64),
+            field("sequence", FieldType::U64),
+            field("resolution", FieldType::Enum("news_resolution")),
+            field("metadata", FieldType::StringMap),
+            optional("orphan_disputed", FieldType::Bool),
+            field("news", FieldType::News),
+        ],
+    },
+    ObjectSchema {
+        name: "tx_status",
+        description: "Status of a transaction, `tx` is its consensus encoding",
+        fields: &[
+            field("txid", FieldType::Txid),
+            field("status", FieldType::Enum("tx_state")),
+            field("confirmations", FieldType::U32),
+            optional("block", FieldType::Object("block")),
+            field("tx", FieldType::Hex),
+        ],
+    },
+    ObjectSchema {
+        name: "block",
+        description: "Block a transaction was found in",
+        fields: &[
+            field("height", FieldType::U32),
+            field("hash", FieldType::BlockHash),
+            field("orphan", FieldType::Bool),
+        ],
+    },
+    ObjectSchema {
+        name: "pegin",
+        description: "Payload of a pegin",
+        fields: &[
+            field("packet_number", FieldType::U64),
+            field("rsk_address", FieldType::Hex),
+            field("reimbursement_key", FieldType::Hex),
+            field("amount_sat", FieldType::U64),
+        ],
+    },
+    ObjectSchema {
+        name: "reorg",
+        description: "Number of items of each kind changed by a reorg",
+        fields: &[
+            field("moved_transactions", FieldType::U64),
+            field("disappeared_spends", FieldType::U64),
+            field("changed_spends", FieldType::U64),
+            field("invalidated_pegins", FieldType::U64),
+            field("reopened_news", FieldType::U64),
+        ],
+    },
+    ObjectSchema {
+        name: "deadline",
+        description: "Height an output has to be spent by",
+        fields: &[
+            field("height", FieldType::U32),
+            field("mempool_watch_window", FieldType::U32),
+        ],
+    },
+    ObjectSchema {
+        name: "value_gate",
+        description: "Minimum value of the spends reported",
+        fields: &[
+            field("min_spend_value_sat", FieldType::U64),
+            field("measure_monitored_output", FieldType::Bool),
+            field("report_minor", FieldType::Bool),
+        ],
+    },
+    ObjectSchema {
+        name: "packet_range",
+        description: "First and last packet numbers of the pegins reported, both included",
+        fields: &[
+            field("first", FieldType::U64),
+            field("last", FieldType::U64),
+        ],
+    },
+    ObjectSchema {
+        name: "filter",
+        description: "Outputs of a script_pubkey monitor that are not reported",
+        fields: &[
+            optional("min_value_sat", FieldType::U64),
+            field("ignore_dust_below_relay", FieldType::Bool),
+        ],
+    },
+];
+
+const ENUMS: &[EnumSchema] = &[
+    EnumSchema {
+        name: "tx_state",
+        values: &["mempool", "confirmed", "finalized", "orphan"],
+    },
+    EnumSchema {
+        name: "health",
+        values: &["healthy", "degraded"],
+    },
+    EnumSchema {
+        name: "news_resolution",
+        values: &["fresh", "snapshot", "snapshot_with_height_adjustment"],
+    },
+];
+
+const SCHEMA: SchemaDescription = SchemaDescription {
+    api_version: v1::VERSION,
+    store_schema_version: STORE_SCHEMA_VERSION,
+    monitor_export_version: MONITOR_EXPORT_VERSION,
+    monitors: MONITORS,
+    news: NEWS,
+    targets: TARGETS,
+    objects: OBJECTS,
+    enums: ENUMS,
+};
+
+/// Describes the monitors and news of this version, see [`SchemaDescription`].
+pub fn describe_schema() -> SchemaDescription {
+    SCHEMA
+}
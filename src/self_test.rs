@@ -0,0 +1,502 @@
+use crate::config::MonitorSettingsConfig;
+use crate::errors::MonitorError;
+use crate::monitor::Monitor;
+use crate::types::{AckMonitorNews, MonitorNews, MonitorType, TypesToMonitor};
+use crate::{WatchOutpoint, WatchPegin, WatchTx};
+use bitcoin::hashes::hex::FromHex;
+use bitcoin::opcodes::all::OP_RETURN;
+use bitcoin::script::{Builder, PushBytesBuf};
+use bitcoin::secp256k1::{PublicKey, Secp256k1, SecretKey};
+use bitcoin::{
+    absolute::LockTime, Address, Amount, BlockHash, Network, OutPoint, Transaction, TxIn, TxOut,
+    Txid,
+};
+use bitcoincore_rpc::RpcApi;
+use bitvmx_bitcoin_rpc::bitcoin_client::{BitcoinClient, BitcoinClientApi};
+use bitvmx_bitcoin_rpc::rpc_config::RpcConfig;
+use serde::Serialize;
+use std::fmt;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+use storage_backend::{storage::Storage, storage_config::StorageConfig};
+use uuid::Uuid;
+
+/// Ticks given to the monitor to reach the state a check waits for.
+const MAX_TICKS: u32 = 1_000;
+/// Value of each output the wallet funds for the checks.
+const FUNDING: Amount = Amount::from_sat(1_000_000);
+const FEE: Amount = Amount::from_sat(10_000);
+const CONTEXT: &str = "self-test";
+/// RSK destination of the synthetic pegin.
+const PEGIN_RSK_ADDRESS: &str = "7ac5496aee77c1ba1f0854206a26dda82a81d6d8";
+
+/// Names of the checks, in the order they run.
+const CHECKS: [&str; 8] = [
+    "node",
+    "sync",
+    "new_block",
+    "wallet",
+    "transaction",
+    "spend",
+    "pegin",
+    "reorg",
+];
+
+const NOT_ALLOWED: &str = "mutates the chain, not allowed";
+const AFTER_FAILURE: &str = "an earlier check failed";
+
+type NodeMonitor = MonitorType;
+type CheckResult<T = ()> = Result<T, String>;
+/// A check that needs the funded wallet.
+type WalletCheck = fn(&SelfTest, &Address) -> CheckResult;
+
+#[derive(Serialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "status", content = "reason", rename_all = "snake_case")]
+pub enum CheckOutcome {
+    Passed,
+    /// The check failed, with the reason
+    Failed(String),
+    /// The check didn't run, with the reason
+    Skipped(String),
+}
+
+#[derive(Debug, Clone)]
+pub struct SelfTestCheck {
+    pub name: &'static str,
+    pub outcome: CheckOutcome,
+    pub elapsed: Duration,
+}
+
+/// Checks run by [`run_self_test`], in the order they ran.
+#[derive(Debug, Clone, Default)]
+pub struct SelfTestReport {
+    pub checks: Vec<SelfTestCheck>,
+}
+
+impl SelfTestReport {
+    /// Whether no check failed. Skipped checks don't fail the report.
+    pub fn passed(&self) -> bool {
+        !self
+            .checks
+            .iter()
+            .any(|check| matches!(check.outcome, CheckOutcome::Failed(_)))
+    }
+
+    fn run<T>(&mut self, name: &'static str, check: impl FnOnce() -> CheckResult<T>) -> Option<T> {
+        if !self.passed() {
+            self.skip(name, AFTER_FAILURE);
+            return None;
+        }
+
+        let started = Instant::now();
+        let result = check();
+        let (outcome, value) = match result {
+            Ok(value) => (CheckOutcome::Passed, Some(value)),
+            Err(reason) => (CheckOutcome::Failed(reason), None),
+        };
+        self.checks.push(SelfTestCheck {
+            name,
+            outcome,
+            elapsed: started.elapsed(),
+        });
+
+        value
+    }
+
+    fn skip(&mut self, name: &'static str, reason: &str) {
+        self.checks.push(SelfTestCheck {
+            name,
+            outcome: CheckOutcome::Skipped(reason.to_string()),
+            elapsed: Duration::ZERO,
+        });
+    }
+}
+
+impl fmt::Display for SelfTestReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for check in &self.checks {
+            let (label, reason) = match &check.outcome {
+                CheckOutcome::Passed => ("PASS", ""),
+                CheckOutcome::Failed(reason) => ("FAIL", reason.as_str()),
+                CheckOutcome::Skipped(reason) => ("SKIP", reason.as_str()),
+            };
+            let elapsed = format!("{:.2?}", check.elapsed);
+            writeln!(f, "{label} {:<12} {elapsed:>10}  {reason}", check.name)?;
+        }
+
+        let count = |label: fn(&CheckOutcome) -> bool| {
+            self.checks
+                .iter()
+                .filter(|check| label(&check.outcome))
+                .count()
+        };
+        write!(
+            f,
+            "{} passed, {} failed, {} skipped",
+            count(|outcome| matches!(outcome, CheckOutcome::Passed)),
+            count(|outcome| matches!(outcome, CheckOutcome::Failed(_))),
+            count(|outcome| matches!(outcome, CheckOutcome::Skipped(_))),
+        )
+    }
+}
+
+/// Runs the whole pipeline against the regtest node of `rpc_config`, with a monitor on a
+/// temporary storage removed at the end:
+/// - `node`: the node answers and the configured network is regtest
+/// - `sync`: the monitor reaches the tip of the node
+/// - `new_block`: the news of the tip is sent and cleared by its ack
+///
+/// The following checks mine blocks and send transactions with the wallet of `rpc_config`, so
+/// they are skipped unless `allow_chain_mutation` is set:
+/// - `wallet`: 101 blocks are mined to the wallet so it can spend a coinbase
+/// - `transaction`: a watched transaction is detected once mined, its news cleared by its ack
+/// - `spend`: the spend of a watched output is detected
+/// - `pegin`: a synthetic pegin is detected
+/// - `reorg`: the block of a watched transaction is invalidated and replaced, and the monitor
+///   reports the transaction as moved by the reorg
+///
+/// Once a check fails, the ones after it are skipped.
+pub fn run_self_test(rpc_config: &RpcConfig, allow_chain_mutation: bool) -> SelfTestReport {
+    let path = std::env::temp_dir().join(format!("bitvmx-self-test-{}", Uuid::new_v4()));
+    let mut report = run_checks(rpc_config, &path.to_string_lossy(), allow_chain_mutation);
+    let _ = std::fs::remove_dir_all(&path);
+
+    // The checks not reached because the node or the monitor failed
+    for name in CHECKS.into_iter().skip(report.checks.len()) {
+        report.skip(name, AFTER_FAILURE);
+    }
+
+    report
+}
+
+fn run_checks(rpc_config: &RpcConfig, path: &str, allow_chain_mutation: bool) -> SelfTestReport {
+    let mut report = SelfTestReport::default();
+
+    let Some(client) = report.run("node", || connect(rpc_config)) else {
+        return report;
+    };
+    let Some(test) = report.run("sync", || SelfTest::start(rpc_config, path, client)) else {
+        return report;
+    };
+    report.run("new_block", || test.new_block_news());
+
+    let wallet = if allow_chain_mutation {
+        report.run("wallet", || test.fund_wallet(&rpc_config.wallet))
+    } else {
+        report.skip("wallet", NOT_ALLOWED);
+        None
+    };
+
+    let checks: [(&'static str, WalletCheck); 4] = [
+        ("transaction", SelfTest::transaction),
+        ("spend", SelfTest::spend),
+        ("pegin", SelfTest::pegin),
+        ("reorg", SelfTest::reorg),
+    ];
+    for (name, check) in checks {
+        match &wallet {
+            Some(wallet) => {
+                report.run(name, || check(&test, wallet));
+            }
+            None if allow_chain_mutation => report.skip(name, AFTER_FAILURE),
+            None => report.skip(name, NOT_ALLOWED),
+        }
+    }
+
+    report
+}
+
+fn connect(rpc_config: &RpcConfig) -> CheckResult<BitcoinClient> {
+    if rpc_config.network != Network::Regtest {
+        return Err(format!(
+            "the configured network is {}, the self-test only runs on regtest",
+            rpc_config.network
+        ));
+    }
+
+    let client = BitcoinClient::new_from_config(rpc_config).map_err(reason)?;
+    client.client.get_block_count().map_err(reason)?;
+
+    Ok(client)
+}
+
+fn reason(e: impl fmt::Display) -> String {
+    e.to_string()
+}
+
+struct SelfTest {
+    client: BitcoinClient,
+    monitor: NodeMonitor,
+}
+
+impl SelfTest {
+    fn start(rpc_config: &RpcConfig, path: &str, client: BitcoinClient) -> CheckResult<Self> {
+        let storage =
+            Rc::new(Storage::new(&StorageConfig::new(path.to_string(), None)).map_err(reason)?);
+        let monitor =
+            Monitor::new_with_paths(rpc_config, storage, Some(MonitorSettingsConfig::default()))
+                .map_err(reason)?;
+
+        let test = Self { client, monitor };
+        test.sync()?;
+
+        Ok(test)
+    }
+
+    fn new_block_news(&self) -> CheckResult {
+        self.monitor
+            .save_monitor(TypesToMonitor::NewBlock)
+            .map_err(reason)?;
+        let tip = self.tip()?;
+
+        let is_tip =
+            |news: &MonitorNews| matches!(news, MonitorNews::NewBlock(height, _) if *height == tip);
+        self.expect_news("send the news of the tip", is_tip)?;
+        self.ack(AckMonitorNews::NewBlock(None), is_tip)
+    }
+
+    fn fund_wallet(&self, wallet_name: &str) -> CheckResult<Address> {
+        let wallet = self.client.init_wallet(wallet_name).map_err(reason)?;
+        // Coinbase outputs can be spent once they have 100 confirmations
+        self.client
+            .mine_blocks_to_address(101, &wallet)
+            .map_err(reason)?;
+        self.sync()?;
+
+        Ok(wallet)
+    }
+
+    fn transaction(&self, wallet: &Address) -> CheckResult {
+        let tx_id = self.fund(wallet)?.txid;
+        self.save_monitor(WatchTx::new(tx_id).context(CONTEXT).build())?;
+        self.mine(wallet)?;
+
+        let mined = |news: &MonitorNews| matches!(news, MonitorNews::Transaction(t, status, _) if *t == tx_id && status.confirmations > 0);
+        self.expect_news("detect the transaction", mined)?;
+        self.ack(AckMonitorNews::Transaction(tx_id, CONTEXT.into()), mined)
+    }
+
+    fn spend(&self, wallet: &Address) -> CheckResult {
+        let outpoint = self.fund(wallet)?;
+        self.save_monitor(
+            WatchOutpoint::new(outpoint.txid, outpoint.vout)
+                .context(CONTEXT)
+                .build(),
+        )?;
+        let spender = self.send_from_wallet(
+            outpoint,
+            vec![TxOut {
+                value: FUNDING - FEE,
+                script_pubkey: wallet.script_pubkey(),
+            }],
+        )?;
+        self.mine(wallet)?;
+
+        let spent = |news: &MonitorNews| {
+            matches!(news, MonitorNews::SpendingUTXOTransaction(t, v, status, _)
+                if *t == outpoint.txid && *v == outpoint.vout && status.tx_id == spender && status.confirmations > 0)
+        };
+        self.expect_news("detect the spend", spent)?;
+        self.ack(
+            AckMonitorNews::SpendingUTXOTransaction(outpoint.txid, outpoint.vout, CONTEXT.into()),
+            spent,
+        )
+    }
+
+    fn pegin(&self, wallet: &Address) -> CheckResult {
+        self.save_monitor(WatchPegin::new().build())?;
+        let outpoint = self.fund(wallet)?;
+        let pegin = self.send_from_wallet(outpoint, pegin_outputs(FUNDING - FEE)?)?;
+        self.mine(wallet)?;
+
+        let detected = |news: &MonitorNews| matches!(news, MonitorNews::RskPeginTransaction(t, status, _) if *t == pegin && status.confirmations > 0);
+        self.expect_news("detect the pegin", detected)?;
+        self.ack(AckMonitorNews::RskPeginTransaction(pegin), detected)
+    }
+
+    fn reorg(&self, wallet: &Address) -> CheckResult {
+        let tx_id = self.fund(wallet)?.txid;
+        self.save_monitor(WatchTx::new(tx_id).context(CONTEXT).build())?;
+        let orphaned = self.mine(wallet)?;
+
+        // The transaction goes back to the mempool and is mined again in the replacement block
+        self.client
+            .client
+            .invalidate_block(&orphaned)
+            .map_err(reason)?;
+        let replacement = self.mine(wallet)?;
+
+        self.tick_until("report the reorg", |monitor| {
+            Ok(monitor
+                .last_reorg_impact()?
+                .is_some_and(|impact| impact.old_tip == orphaned && impact.new_tip == replacement))
+        })?;
+
+        let impact = self
+            .monitor
+            .last_reorg_impact()
+            .map_err(reason)?
+            .ok_or("the reorg impact is gone")?;
+        if !impact
+            .moved_transactions
+            .iter()
+            .any(|moved| moved.tx_id == tx_id && moved.before == orphaned)
+        {
+            return Err(format!(
+                "the reorg doesn't report {} as moved out of the orphaned block",
+                tx_id
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn tip(&self) -> CheckResult<u32> {
+        Ok(self.client.client.get_block_count().map_err(reason)? as u32)
+    }
+
+    /// Ticks until the monitor reaches the tip of the node.
+    fn sync(&self) -> CheckResult {
+        let tip = self.tip()?;
+        self.tick_until("reach the tip", |monitor| {
+            Ok(monitor.get_monitor_height()? >= tip)
+        })
+    }
+
+    fn tick_until(
+        &self,
+        goal: &str,
+        reached: impl Fn(&NodeMonitor) -> Result<bool, MonitorError>,
+    ) -> CheckResult {
+        for _ in 0..MAX_TICKS {
+            self.monitor.tick().map_err(reason)?;
+            if reached(&self.monitor).map_err(reason)? {
+                return Ok(());
+            }
+        }
+
+        Err(format!(
+            "the monitor didn't {} in {} ticks",
+            goal, MAX_TICKS
+        ))
+    }
+
+    fn expect_news(&self, goal: &str, is_expected: impl Fn(&MonitorNews) -> bool) -> CheckResult {
+        self.tick_until(goal, |monitor| {
+            Ok(monitor.get_news()?.iter().any(&is_expected))
+        })
+    }
+
+    /// Acknowledges a news and checks it is no longer pending after the next tick.
+    fn ack(&self, ack: AckMonitorNews, is_acked: impl Fn(&MonitorNews) -> bool) -> CheckResult {
+        self.monitor.ack_news(ack).map_err(reason)?;
+        self.monitor.tick().map_err(reason)?;
+
+        if self
+            .monitor
+            .get_news()
+            .map_err(reason)?
+            .iter()
+            .any(is_acked)
+        {
+            return Err("the news is still pending after its ack".to_string());
+        }
+
+        Ok(())
+    }
+
+    fn save_monitor(&self, data: Result<TypesToMonitor, MonitorError>) -> CheckResult {
+        self.monitor
+            .save_monitor(data.map_err(reason)?)
+            .map_err(reason)
+    }
+
+    /// Mines a block to the wallet and waits for the monitor to process it.
+    fn mine(&self, wallet: &Address) -> CheckResult<BlockHash> {
+        let hashes = self
+            .client
+            .mine_blocks_to_address(1, wallet)
+            .map_err(reason)?;
+        self.sync()?;
+
+        hashes
+            .last()
+            .copied()
+            .ok_or_else(|| "the node mined no block".to_string())
+    }
+
+    /// Sends `FUNDING` to the wallet and returns the output funded.
+    fn fund(&self, wallet: &Address) -> CheckResult<OutPoint> {
+        let (tx, vout) = self.client.fund_address(wallet, FUNDING).map_err(reason)?;
+        Ok(OutPoint::new(tx.compute_txid(), vout))
+    }
+
+    /// Spends an output of the wallet to `outputs`, signed by the wallet.
+    fn send_from_wallet(&self, input: OutPoint, outputs: Vec<TxOut>) -> CheckResult<Txid> {
+        let tx = Transaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![TxIn {
+                previous_output: input,
+                ..Default::default()
+            }],
+            output: outputs,
+        };
+
+        let signed = self
+            .client
+            .client
+            .sign_raw_transaction_with_wallet(&tx, None, None)
+            .map_err(reason)?;
+        if !signed.complete {
+            return Err(format!(
+                "the wallet didn't sign the transaction: {:?}",
+                signed.errors
+            ));
+        }
+
+        self.client
+            .client
+            .send_raw_transaction(&signed.hex)
+            .map_err(reason)
+    }
+}
+
+/// Outputs of a pegin: the funds locked to a committee key and the OP_RETURN with the packet
+/// number, the RSK destination and the reimbursement key. Both keys are throwaway.
+fn pegin_outputs(value: Amount) -> CheckResult<Vec<TxOut>> {
+    let secp = Secp256k1::new();
+    let key = |byte: u8| {
+        SecretKey::from_slice(&[byte; 32])
+            .map(|secret| {
+                PublicKey::from_secret_key(&secp, &secret)
+                    .x_only_public_key()
+                    .0
+            })
+            .map_err(reason)
+    };
+    let committee = Address::p2tr(&secp, key(1)?, None, Network::Regtest);
+
+    let data = [
+        b"RSK_PEGIN".as_slice(),
+        &0u64.to_be_bytes(),
+        &Vec::from_hex(PEGIN_RSK_ADDRESS).map_err(reason)?,
+        &key(2)?.serialize(),
+    ]
+    .concat();
+    let op_return = Builder::new()
+        .push_opcode(OP_RETURN)
+        .push_slice(PushBytesBuf::try_from(data).map_err(reason)?)
+        .into_script();
+
+    Ok(vec![
+        TxOut {
+            value,
+            script_pubkey: committee.script_pubkey(),
+        },
+        TxOut {
+            value: Amount::ZERO,
+            script_pubkey: op_return,
+        },
+    ])
+}
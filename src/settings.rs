@@ -2,6 +2,59 @@
 /// After this number of confirmations, the monitor will stop tracking the transaction for further updates.
 pub const DEFAULT_MAX_MONITORING_CONFIRMATIONS: u32 = 100;
 
+/// The default number of blocks past `max_monitoring_confirmations` a monitor with unacknowledged
+/// news is kept active for, about one day.
+pub const DEFAULT_DEACTIVATION_ACK_GRACE_BLOCKS: u32 = 144;
+
 /// The default number of confirmations required for a transaction to be considered final.
 /// This is the minimum number of blocks that must be mined on top of a transaction's block before it is considered Finalized.
 pub const DEFAULT_CONFIRMATION_THRESHOLD: u32 = 6;
+
+/// The default number of blocks between two sweeps of expired keys.
+pub const DEFAULT_SWEEP_INTERVAL_BLOCKS: u32 = 144;
+
+/// The default number of blocks the canceled monitors are kept in the trash for, about one week.
+pub const DEFAULT_RETENTION_BLOCKS: u32 = 1008;
+
+/// The default number of blocks kept in the chain log, about one year.
+pub const DEFAULT_CHAIN_LOG_BLOCKS: u32 = 52_560;
+
+/// The default number of blocks the news history is kept for, about one month.
+pub const DEFAULT_NEWS_HISTORY_BLOCKS: u32 = 4320;
+
+/// The number of events kept in the history of each monitor, the oldest are dropped.
+pub const MONITOR_HISTORY_LEN: usize = 20;
+
+/// The maximum number of metadata entries of a monitor.
+pub const MAX_METADATA_ENTRIES: usize = 16;
+
+/// The maximum length in bytes of a metadata key or value.
+pub const MAX_METADATA_LEN: usize = 256;
+
+/// The default number of blocks processed by a tick when the monitor is behind the indexer.
+pub const DEFAULT_MAX_BLOCKS_PER_TICK: u32 = 100;
+
+/// The default number of ticks the health score is computed over.
+pub const DEFAULT_HEALTH_WINDOW_TICKS: u32 = 20;
+
+/// The default duration above which the p95 tick duration takes its weight off the health score.
+pub const DEFAULT_SLOW_TICK_MS: u64 = 10_000;
+
+/// The default score below which the monitor switches to its degraded mode.
+pub const DEFAULT_DEGRADED_BELOW: u32 = 50;
+
+/// The default score the monitor leaves its degraded mode at.
+pub const DEFAULT_RECOVERED_AT: u32 = 80;
+
+/// The default number of blocks processed by a tick in degraded mode.
+pub const DEFAULT_DEGRADED_MAX_BLOCKS_PER_TICK: u32 = 10;
+
+/// The default wait before delivering again the news a webhook failed to take.
+pub const DEFAULT_WEBHOOK_INITIAL_RETRY_MS: u64 = 1_000;
+
+/// The default longest wait between two deliveries to a failing webhook, the wait doubles with
+/// each failure up to it.
+pub const DEFAULT_WEBHOOK_MAX_RETRY_MS: u64 = 60_000;
+
+/// The default time a webhook has to take a news before the delivery fails.
+pub const DEFAULT_WEBHOOK_TIMEOUT_MS: u64 = 5_000;
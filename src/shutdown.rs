@@ -0,0 +1,26 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// Asks a monitor to stop, checked by [`crate::monitor::Monitor::tick_with_shutdown`] between
+/// the blocks of a tick. The clones of a token share its request, so one is kept by the run
+/// loop and another moved to a signal handler or another thread.
+#[derive(Debug, Clone, Default)]
+pub struct ShutdownToken {
+    requested: Arc<AtomicBool>,
+}
+
+impl ShutdownToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Requests the shutdown. The block being processed is finished, the next ones are left for
+    /// the next run.
+    pub fn request(&self) {
+        self.requested.store(true, Ordering::SeqCst);
+    }
+
+    pub fn is_requested(&self) -> bool {
+        self.requested.load(Ordering::SeqCst)
+    }
+}
@@ -1,76 +1,840 @@
 use crate::{
+    compression::{Compressible, CompressionCounters, Decompressed},
     errors::MonitorStoreError,
+    migrations::migrate,
+    monitor::{build_minor_spend_context, build_spending_utxo_context, is_internal_context},
+    redaction::RedactedContext,
     types::{
-        AckMonitorNews, NewsAck, RskPeginMonitorState, RskPeginNewsEntry, SpendingUTXOMonitor,
-        SpendingUTXOMonitorEntry, SpendingUTXONewsEntry, TransactionMonitor,
-        TransactionMonitorEntry, TransactionNewsEntry, TypesToMonitor,
+        AckMetadata, AckMonitorNews, AddressFilterStats, AlternativeNewsEntry, AlternativesMonitor,
+        BlockWorkEntry, CancelNewsPolicy, CancelReason, ChainLogEntry, CompositeNewsEntry,
+        CompositeRule, DetectionDescriptor, DetectionRecord, EpochNewsEntry,
+        ExpiredMonitorNewsEntry, ExpiryEntry, ExternalBlockEntry, FingerprintEntry,
+        HealthNewsEntry, HealthState, LowWorkReorgNewsEntry, ManualResolutionNewsEntry,
+        MempoolSighting, MonitorContext, MonitorEvent, MonitorExport, MonitorInfo, MonitorKind,
+        MonitorMetadataEntry, NewBlockNewsEntry, NewsAck, NewsCursor, NewsHistoryAck,
+        NewsHistoryEntry, OpReturnNewsEntry, OpReturnPrefixMonitor, OutputFilter, PruneReport,
+        ReorgImpact, ReorgNewsEntry, ReorgSummary, ReplacedTxEntry, ReplacementNewsEntry,
+        RskPeginData, RskPeginMonitorState, RskPeginMonitorStatus, RskPeginNewsEntry,
+        ScriptPubKeyMonitor, ScriptPubKeyMonitorEntry, ScriptPubKeyNewsEntry, SnoozeEntry,
+        SnoozeTarget, SpendDeadline, SpendValueGate, SpendingUTXOMonitor, SpendingUTXOMonitorEntry,
+        SpendingUTXONewsEntry, StaleTx, StorageStats, TransactionMonitor, TransactionMonitorEntry,
+        TransactionNewsEntry, TransactionStatus, TrashEntry, TrashSelector, TrashedMonitors,
+        TypesToMonitor, MONITOR_EXPORT_VERSION,
     },
 };
-use bitcoin::{BlockHash, Txid};
+use bitcoin::{hashes::Hash, Amount, BlockHash, Network, OutPoint, Script, ScriptBuf, Txid, Work};
 use bitvmx_bitcoin_rpc::types::BlockHeight;
 use mockall::automock;
-use serde::{Deserialize, Serialize};
-use std::rc::Rc;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::ops::{Deref, DerefMut};
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
 use storage_backend::storage::{KeyValueStore, Storage};
+use storage_backend::storage_config::StorageConfig;
+use tracing::warn;
+use uuid::Uuid;
 
-pub struct MonitorStore {
-    store: Rc<Storage>,
+/// Store of the monitor, kept in a [`Storage`] held through `S`. The default `Arc<Storage>` makes
+/// the store `Send`; the indexer of [`crate::monitor::Monitor::new_with_paths`] shares the storage
+/// as `Rc<Storage>`, so the store built there holds it the same way and that monitor is not
+/// `Send`.
+pub struct MonitorStore<S = Arc<Storage>> {
+    store: S,
+    compression: CompressionCounters,
 }
-enum MonitorKey {
+#[derive(Clone, Copy)]
+pub(crate) enum MonitorKey {
     Transactions(bool),
     SpendingUTXOTransactions(bool),
     PendingWork,
+    ReloadRequest,
+    Network,
+    WriteProbe,
     RskPegin,
     NewBlock,
     TransactionsNews,
+    OrphanedTransactionsNews,
     RskPeginTransactionsNews,
     SpendingUTXOTransactionsNews,
+    SpendingUTXOUnconfirmedNews,
+    MinorSpendNews,
     NewBlockNews,
+    Fingerprints,
+    FingerprintsAt(BlockHeight),
+    FingerprintHeights,
+    Expiries,
+    ReplacedTransactions,
+    ReplacementNews,
+    Snoozes,
+    TransactionSnapshots,
+    TransactionSnapshotIds,
+    NewsSequence,
+    NewsEpoch,
+    EpochNews,
+    ReorgNews,
+    LastReorgImpact,
+    ScriptPubKeys(bool),
+    ScriptPubKeyNews,
+    OpReturnPrefixes,
+    OpReturnNews,
+    BlockWork,
+    LowWorkReorgNews,
+    MempoolSightings,
+    AddressFilterStats,
+    DetectionArchive,
+    Trash,
+    TrashNextId,
+    ExpectedMonitors,
+    MonitorMetadata,
+    ScanGeneration,
+    ChainLog(u64),
+    ChainLogBounds,
+    Alternatives,
+    AlternativeNews,
+    ExpiredMonitorsNews,
+    HealthNews,
+    ManualResolutionNews,
+    Spender(Txid),
+    ExternalChain,
+    NewsHistory(u64),
+    NewsHistoryStart,
+    OrphanDisputedNews,
+    CompositeRules,
+    CompositeNews,
+    SchemaVersion,
 }
 
-enum BlockchainKey {
+pub(crate) enum BlockchainKey {
     CurrentBlockHeight,
+    CurrentBlockHash,
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub enum MonitoredTypes {
-    Transaction(Txid, String),
-    RskPeginTransaction(Txid),
-    SpendingUTXOTransaction(Txid, u32, String, Txid),
-    NewBlock(BlockHash),
+    Transaction(Txid, MonitorContext),
+    RskPeginTransaction(Txid, Option<RskPeginData>),
+    SpendingUTXOTransaction(Txid, u32, MonitorContext, Txid),
+    SpendingUTXOUnconfirmed(Txid, u32, MonitorContext, Txid),
+    MinorSpend(Txid, u32, MonitorContext, Txid),
+    NewBlock(BlockHash, Option<BlockHeight>),
+    OrphanedTransaction(Txid, MonitorContext),
+    MonitorReplaced(Txid, Txid, MonitorContext),
+    ReplacedTransactionConfirmed(Txid, Txid, MonitorContext),
+    SequenceEpochChanged(u64, u64),
+    Reorg(BlockHash, BlockHash, ReorgSummary),
+    LowWorkReorg(BlockHeight, Work, Work),
+    ScriptPubKeyTransaction(ScriptBuf, MonitorContext, Txid, u32),
+    OpReturnTransaction(Vec<u8>, MonitorContext, Txid, Vec<u8>),
+    AlternativeResolved(Txid, Vec<Txid>, MonitorContext),
+    MonitorExpired(Txid, Option<u32>, MonitorContext, BlockHeight),
+    HealthStateChanged(HealthState, u32),
+    ManuallyResolved(SnoozeTarget, String),
+    Composite(String),
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+// Contexts may hold identifiers that must not end up in logs, see `crate::redaction`
+impl fmt::Debug for MonitoredTypes {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transaction(tx_id, context) => f
+                .debug_tuple("Transaction")
+                .field(tx_id)
+                .field(&RedactedContext(context))
+                .finish(),
+            Self::RskPeginTransaction(tx_id, data) => f
+                .debug_tuple("RskPeginTransaction")
+                .field(tx_id)
+                .field(data)
+                .finish(),
+            Self::SpendingUTXOTransaction(tx_id, vout, context, spender) => f
+                .debug_tuple("SpendingUTXOTransaction")
+                .field(tx_id)
+                .field(vout)
+                .field(&RedactedContext(context))
+                .field(spender)
+                .finish(),
+            Self::MinorSpend(tx_id, vout, context, spender) => f
+                .debug_tuple("MinorSpend")
+                .field(tx_id)
+                .field(vout)
+                .field(&RedactedContext(context))
+                .field(spender)
+                .finish(),
+            Self::SpendingUTXOUnconfirmed(tx_id, vout, context, spender) => f
+                .debug_tuple("SpendingUTXOUnconfirmed")
+                .field(tx_id)
+                .field(vout)
+                .field(&RedactedContext(context))
+                .field(spender)
+                .finish(),
+            Self::NewBlock(hash, height) => {
+                f.debug_tuple("NewBlock").field(hash).field(height).finish()
+            }
+            Self::OrphanedTransaction(tx_id, context) => f
+                .debug_tuple("OrphanedTransaction")
+                .field(tx_id)
+                .field(&RedactedContext(context))
+                .finish(),
+            Self::MonitorReplaced(old, new, context) => f
+                .debug_tuple("MonitorReplaced")
+                .field(old)
+                .field(new)
+                .field(&RedactedContext(context))
+                .finish(),
+            Self::ReplacedTransactionConfirmed(old, new, context) => f
+                .debug_tuple("ReplacedTransactionConfirmed")
+                .field(old)
+                .field(new)
+                .field(&RedactedContext(context))
+                .finish(),
+            Self::SequenceEpochChanged(old_epoch, new_epoch) => f
+                .debug_tuple("SequenceEpochChanged")
+                .field(old_epoch)
+                .field(new_epoch)
+                .finish(),
+            Self::Reorg(old_tip, new_tip, summary) => f
+                .debug_tuple("Reorg")
+                .field(old_tip)
+                .field(new_tip)
+                .field(summary)
+                .finish(),
+            Self::LowWorkReorg(height, old_work, new_work) => f
+                .debug_tuple("LowWorkReorg")
+                .field(height)
+                .field(old_work)
+                .field(new_work)
+                .finish(),
+            Self::ScriptPubKeyTransaction(script_pubkey, context, tx_id, vout) => f
+                .debug_tuple("ScriptPubKeyTransaction")
+                .field(script_pubkey)
+                .field(&RedactedContext(context))
+                .field(tx_id)
+                .field(vout)
+                .finish(),
+            Self::OpReturnTransaction(prefix, context, tx_id, data) => f
+                .debug_tuple("OpReturnTransaction")
+                .field(prefix)
+                .field(&RedactedContext(context))
+                .field(tx_id)
+                .field(data)
+                .finish(),
+            Self::AlternativeResolved(winner, losers, context) => f
+                .debug_tuple("AlternativeResolved")
+                .field(winner)
+                .field(losers)
+                .field(&RedactedContext(context))
+                .finish(),
+            Self::MonitorExpired(tx_id, vout, context, height) => f
+                .debug_tuple("MonitorExpired")
+                .field(tx_id)
+                .field(vout)
+                .field(&RedactedContext(context))
+                .field(height)
+                .finish(),
+            Self::HealthStateChanged(state, score) => f
+                .debug_tuple("HealthStateChanged")
+                .field(state)
+                .field(score)
+                .finish(),
+            Self::ManuallyResolved(target, note) => f
+                .debug_tuple("ManuallyResolved")
+                .field(&DetectionDescriptor {
+                    monitor: target.clone(),
+                    tx_id: None,
+                })
+                .field(note)
+                .finish(),
+            Self::Composite(id) => f.debug_tuple("Composite").field(id).finish(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub enum TypesToMonitorStore {
-    Transaction(Txid, String, Option<u32>),
-    SpendingUTXOTransaction(Txid, u32, String, Option<u32>),
+    Transaction(Txid, MonitorContext, Option<u32>, Option<BlockHeight>),
+    SpendingUTXOTransaction(
+        Txid,
+        u32,
+        MonitorContext,
+        Option<u32>,
+        Option<SpendDeadline>,
+        Option<SpendValueGate>,
+        Option<BlockHeight>,
+    ),
     NewBlock,
-    RskPegin(Option<u32>),
+    RskPegin(
+        Option<u32>,
+        Vec<ScriptBuf>,
+        Option<(u64, u64)>,
+        Option<Amount>,
+    ),
+    ScriptPubKey(ScriptBuf, MonitorContext, Option<u32>, Option<OutputFilter>),
+    OpReturnPrefix(Vec<u8>, MonitorContext),
+}
+
+impl fmt::Debug for TypesToMonitorStore {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transaction(tx_id, context, trigger, expires_at) => f
+                .debug_tuple("Transaction")
+                .field(tx_id)
+                .field(&RedactedContext(context))
+                .field(trigger)
+                .field(expires_at)
+                .finish(),
+            Self::SpendingUTXOTransaction(
+                tx_id,
+                vout,
+                context,
+                trigger,
+                deadline,
+                value_gate,
+                expires_at,
+            ) => f
+                .debug_tuple("SpendingUTXOTransaction")
+                .field(tx_id)
+                .field(vout)
+                .field(&RedactedContext(context))
+                .field(trigger)
+                .field(deadline)
+                .field(value_gate)
+                .field(expires_at)
+                .finish(),
+            Self::NewBlock => f.write_str("NewBlock"),
+            Self::RskPegin(trigger, committee, packet_range, min_amount) => f
+                .debug_tuple("RskPegin")
+                .field(trigger)
+                .field(committee)
+                .field(packet_range)
+                .field(min_amount)
+                .finish(),
+            Self::ScriptPubKey(script_pubkey, context, trigger, filter) => f
+                .debug_tuple("ScriptPubKey")
+                .field(script_pubkey)
+                .field(&RedactedContext(context))
+                .field(trigger)
+                .field(filter)
+                .finish(),
+            Self::OpReturnPrefix(prefix, context) => f
+                .debug_tuple("OpReturnPrefix")
+                .field(prefix)
+                .field(&RedactedContext(context))
+                .finish(),
+        }
+    }
+}
+
+impl TypesToMonitorStore {
+    pub fn kind(&self) -> MonitorKind {
+        match self {
+            TypesToMonitorStore::Transaction(..) => MonitorKind::Transaction,
+            TypesToMonitorStore::SpendingUTXOTransaction(..) => {
+                MonitorKind::SpendingUTXOTransaction
+            }
+            TypesToMonitorStore::NewBlock => MonitorKind::NewBlock,
+            TypesToMonitorStore::RskPegin(..) => MonitorKind::RskPegin,
+            TypesToMonitorStore::ScriptPubKey(..) => MonitorKind::ScriptPubKey,
+            TypesToMonitorStore::OpReturnPrefix(..) => MonitorKind::OpReturnPrefix,
+        }
+    }
+
+    /// Canonical evaluation order of the monitors: by kind, then by target (txid, outpoint,
+    /// script or prefix) and context. It doesn't depend on the registration order.
+    pub fn canonical_cmp(&self, other: &Self) -> Ordering {
+        self.canonical_key().cmp(&other.canonical_key())
+    }
+
+    fn canonical_key(&self) -> (MonitorKind, Vec<u8>, &str) {
+        let (target, context) = match self {
+            TypesToMonitorStore::Transaction(tx_id, context, _, _) => {
+                (tx_id.as_byte_array().to_vec(), context.as_str())
+            }
+            TypesToMonitorStore::SpendingUTXOTransaction(tx_id, vout, context, ..) => {
+                let mut target = tx_id.as_byte_array().to_vec();
+                target.extend(vout.to_be_bytes());
+                (target, context.as_str())
+            }
+            TypesToMonitorStore::NewBlock | TypesToMonitorStore::RskPegin(..) => (vec![], ""),
+            TypesToMonitorStore::ScriptPubKey(script_pubkey, context, ..) => {
+                (script_pubkey.to_bytes(), context.as_str())
+            }
+            TypesToMonitorStore::OpReturnPrefix(prefix, context) => {
+                (prefix.clone(), context.as_str())
+            }
+        };
+
+        (self.kind(), target, context)
+    }
+
+    /// Target of the monitor as written on the command line: the txid, the outpoint as
+    /// `txid:vout`, or the script or prefix in hex. Empty for the new block and pegin monitors.
+    pub fn target(&self) -> String {
+        match self {
+            TypesToMonitorStore::Transaction(tx_id, ..) => tx_id.to_string(),
+            TypesToMonitorStore::SpendingUTXOTransaction(tx_id, vout, ..) => {
+                format!("{}:{}", tx_id, vout)
+            }
+            TypesToMonitorStore::NewBlock | TypesToMonitorStore::RskPegin(..) => String::new(),
+            TypesToMonitorStore::ScriptPubKey(script_pubkey, ..) => script_pubkey.to_hex_string(),
+            TypesToMonitorStore::OpReturnPrefix(prefix, _) => hex::encode(prefix),
+        }
+    }
+
+    /// Target the snoozes, expected marks and metadata of the monitor are kept under.
+    pub fn target_of(&self) -> SnoozeTarget {
+        match self {
+            TypesToMonitorStore::Transaction(tx_id, context, _, _) => {
+                SnoozeTarget::Transaction(*tx_id, context.clone())
+            }
+            TypesToMonitorStore::SpendingUTXOTransaction(tx_id, vout, context, ..) => {
+                SnoozeTarget::SpendingUTXOTransaction(*tx_id, *vout, context.clone())
+            }
+            TypesToMonitorStore::NewBlock => SnoozeTarget::NewBlock,
+            TypesToMonitorStore::RskPegin(..) => SnoozeTarget::RskPegin,
+            TypesToMonitorStore::ScriptPubKey(script_pubkey, context, ..) => {
+                SnoozeTarget::ScriptPubKey(script_pubkey.clone(), context.clone())
+            }
+            TypesToMonitorStore::OpReturnPrefix(prefix, context) => {
+                SnoozeTarget::OpReturnPrefix(prefix.clone(), context.clone())
+            }
+        }
+    }
+
+    /// Context of the monitor, empty for the new block and pegin monitors.
+    pub fn context(&self) -> &str {
+        match self {
+            TypesToMonitorStore::Transaction(_, context, _, _)
+            | TypesToMonitorStore::SpendingUTXOTransaction(_, _, context, ..)
+            | TypesToMonitorStore::ScriptPubKey(_, context, ..)
+            | TypesToMonitorStore::OpReturnPrefix(_, context) => context,
+            TypesToMonitorStore::NewBlock | TypesToMonitorStore::RskPegin(..) => "",
+        }
+    }
+}
+
+/// Position in the active monitors of a kind, read a page at a time with
+/// [`MonitorStoreApi::next_monitors`]. Only the ids of the stored items are kept, sorted by
+/// target, so the monitors are read in their canonical order without holding all of them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MonitorCursor {
+    kind: MonitorKind,
+    /// Ids of the items left to read, in reverse order so the next one is popped
+    ids: Vec<String>,
 }
 
+impl MonitorCursor {
+    pub fn kind(&self) -> MonitorKind {
+        self.kind
+    }
+
+    /// Number of stored items left to read. An item holds the monitors of one target, one per
+    /// context; the new block, pegin and OP_RETURN prefix monitors are a single item.
+    pub fn remaining(&self) -> usize {
+        self.ids.len()
+    }
+
+    pub fn is_done(&self) -> bool {
+        self.ids.is_empty()
+    }
+}
+
+#[automock]
 pub trait MonitorStoreApi {
     fn get_monitors(&self) -> Result<Vec<TypesToMonitorStore>, MonitorStoreError>;
+    /// Cursor over the active monitors of `kind`, reading only the index of its list.
+    fn monitor_cursor(&self, kind: MonitorKind) -> Result<MonitorCursor, MonitorStoreError>;
+    /// Active monitors of the next `items` items of `cursor`, in the canonical order. Items
+    /// removed since the cursor was created are skipped, so a page can be empty before the
+    /// cursor is done.
+    fn next_monitors(
+        &self,
+        cursor: &mut MonitorCursor,
+        items: usize,
+    ) -> Result<Vec<TypesToMonitorStore>, MonitorStoreError>;
+    /// Scripts of the active ScriptPubKey monitors, read from the index of their list.
+    fn get_monitored_scripts(&self) -> Result<Vec<ScriptBuf>, MonitorStoreError>;
+    /// Lists the active monitors, followed by the inactive ones when `include_inactive` is set.
+    fn get_monitor_infos(
+        &self,
+        include_inactive: bool,
+    ) -> Result<Vec<MonitorInfo>, MonitorStoreError>;
+    /// Last events of `monitor`, active or inactive, oldest first. Monitors are matched by kind,
+    /// target and context; the new block and pegin monitors have no history.
+    fn get_monitor_history(
+        &self,
+        monitor: &TypesToMonitorStore,
+    ) -> Result<Vec<MonitorEvent>, MonitorStoreError>;
+    /// Records `event` in the history of `monitor`, see [`MonitorEvent::record`].
+    fn record_monitor_event(
+        &self,
+        monitor: &TypesToMonitorStore,
+        event: MonitorEvent,
+    ) -> Result<(), MonitorStoreError>;
     fn add_monitor(&self, data: TypesToMonitor) -> Result<(), MonitorStoreError>;
+    fn add_monitors(&self, data: Vec<TypesToMonitor>) -> Result<(), MonitorStoreError>;
+    /// Sets the spender found for the (txid, vout) of a SpendingUTXO monitor, None when it is
+    /// not spent anymore. The outpoints spent by each spender are indexed, see
+    /// `get_outpoints_spent_by`.
     fn update_spending_utxo_monitor(
         &self,
         data: (Txid, u32, Option<Txid>),
     ) -> Result<(), MonitorStoreError>;
+    /// Outpoints of SpendingUTXO monitors, active or inactive, whose recorded spender is
+    /// `spender`, in the order the spends were recorded. Spends recorded by older versions are
+    /// not indexed.
+    fn get_outpoints_spent_by(&self, spender: Txid) -> Result<Vec<(Txid, u32)>, MonitorStoreError>;
+    /// Active monitors referencing `tx_id`: the Transaction monitors of it, then the
+    /// SpendingUTXO monitors of its outputs, then the SpendingUTXO monitors it is the recorded
+    /// spender of. Only the monitors of the txid and of the outpoints it spent are read, spends
+    /// recorded by older versions are not indexed.
+    fn find_monitors_for_tx(
+        &self,
+        tx_id: Txid,
+    ) -> Result<Vec<TypesToMonitorStore>, MonitorStoreError>;
+    /// Spender recorded for the (txid, vout) of an active SpendingUTXO monitor, None when it is
+    /// not spent or not monitored.
+    fn get_spending_utxo_spender(
+        &self,
+        tx_id: Txid,
+        vout: u32,
+    ) -> Result<Option<Txid>, MonitorStoreError>;
+    /// Active SpendingUTXO monitors without a spender whose blocks before the registration were
+    /// not scanned yet, see [`SpendingUTXOMonitorEntry::backfill_done`].
+    fn get_spending_utxo_backfills(&self) -> Result<Vec<TypesToMonitorStore>, MonitorStoreError>;
+    fn set_spending_utxo_backfill_done(
+        &self,
+        tx_id: Txid,
+        vout: u32,
+        extra_data: &str,
+    ) -> Result<(), MonitorStoreError>;
     fn cancel_monitor(&self, data: TypesToMonitor) -> Result<(), MonitorStoreError>;
+    /// Removes the monitor like `cancel_monitor` and, in the same store transaction, drops or
+    /// acknowledges its unacked news following `policy`.
+    ///
+    /// The removed entries are moved to the trash with their state, see `restore_from_trash`.
+    fn cancel_monitor_with_policy(
+        &self,
+        data: TypesToMonitor,
+        policy: CancelNewsPolicy,
+    ) -> Result<(), MonitorStoreError>;
+    /// Removes the monitor like `cancel_monitor_with_policy` without keeping it in the trash.
+    fn cancel_monitor_permanent(
+        &self,
+        data: TypesToMonitor,
+        policy: CancelNewsPolicy,
+    ) -> Result<(), MonitorStoreError>;
+    /// Removes the monitor as resolved by hand, in one store transaction: it is moved to the trash
+    /// with `CancelReason::ManuallyResolved`, its unacked news are acknowledged with `metadata`,
+    /// its snoozed news dropped and a `ManuallyResolved` news added per target with the note of
+    /// `metadata`. Returns false, changing nothing, if there was no such monitor.
+    fn resolve_monitor_manually(
+        &self,
+        data: TypesToMonitor,
+        metadata: AckMetadata,
+        current_block_hash: BlockHash,
+    ) -> Result<bool, MonitorStoreError>;
+    /// Monitors removed by a cancel and kept with their state, in the order they were canceled.
+    fn get_trash(&self) -> Result<Vec<TrashEntry>, MonitorStoreError>;
+    /// Writes back the trashed monitors matched by `selector` and removes them from the trash.
+    /// Returns the restored trash entries.
+    fn restore_from_trash(
+        &self,
+        selector: &TrashSelector,
+    ) -> Result<Vec<TrashEntry>, MonitorStoreError>;
+    /// Removes the trash entries canceled before `deleted_before`, returning how many were removed.
+    fn purge_trash(&self, deleted_before: BlockHeight) -> Result<u32, MonitorStoreError>;
+    /// Removes the entries of inactive Transaction and SpendingUTXO monitors whose last event is
+    /// below `older_than_height`, with the monitors of the spenders they found, the acknowledged
+    /// news written below it and the fingerprints recorded below it, in one store transaction.
+    /// Returns what was removed.
+    fn prune(&self, older_than_height: BlockHeight) -> Result<PruneReport, MonitorStoreError>;
+    /// Every monitor of the store, active and inactive, with its state, its metadata and the
+    /// composite rules, to be loaded into another store with `import_monitors`.
+    fn export_monitors(&self) -> Result<MonitorExport, MonitorStoreError>;
+    /// Writes the monitors of `export` in one store transaction. A monitor already stored with
+    /// the same target and context is replaced with `overwrite` and kept as stored otherwise.
+    /// Exports of another version are rejected.
+    fn import_monitors(
+        &self,
+        export: MonitorExport,
+        overwrite: bool,
+    ) -> Result<(), MonitorStoreError>;
     fn deactivate_monitor(&self, data: TypesToMonitor) -> Result<(), MonitorStoreError>;
 
+    /// Moves a deactivated monitor back to the active list, keeping its context, confirmation
+    /// trigger and trigger state. Monitors that are not inactive are left untouched.
+    fn reactivate_monitor(&self, data: TypesToMonitor) -> Result<(), MonitorStoreError>;
+
+    /// Moves every entry of the active `old` transaction monitor to `new` in a single storage transaction:
+    /// the old monitor is removed, its unacked news purged, a MonitorReplaced news is added per context
+    /// and a tombstone is kept until `tombstone_expires_at`.
+    fn replace_transaction_monitor(
+        &self,
+        old: Txid,
+        new: Txid,
+        current_block_hash: BlockHash,
+        tombstone_expires_at: BlockHeight,
+    ) -> Result<(), MonitorStoreError>;
+    fn get_replaced_transactions(&self) -> Result<Vec<ReplacedTxEntry>, MonitorStoreError>;
+    fn remove_replaced_transaction(&self, old: Txid) -> Result<(), MonitorStoreError>;
+
+    /// Races of the `Alternatives` monitors, open or resolved.
+    fn get_alternatives(&self) -> Result<Vec<AlternativesMonitor>, MonitorStoreError>;
+    /// Sets the winner of `race` in a single storage transaction. A winner cancels the transaction
+    /// monitors of the other alternatives, moved to the trash with `CancelReason::AlternativeLost`.
+    /// None opens the race again: the losers of `race.winner` are registered back and the unacked
+    /// news of the resolution is dropped.
+    fn resolve_alternatives(
+        &self,
+        race: &AlternativesMonitor,
+        winner: Option<Txid>,
+    ) -> Result<(), MonitorStoreError>;
+    /// Removes a race once its winner is finalized, the transaction monitor of the winner is left
+    /// to deactivate on its own.
+    fn remove_alternatives(&self, race: &AlternativesMonitor) -> Result<(), MonitorStoreError>;
+
+    /// Composite rules, in registration order.
+    fn get_composite_rules(&self) -> Result<Vec<CompositeRule>, MonitorStoreError>;
+    /// Replaces the composite rules. A rule is marked as sent by writing its `Composite` news.
+    fn set_composite_rules(&self, rules: Vec<CompositeRule>) -> Result<(), MonitorStoreError>;
+
+    /// Snoozes `target` until `until`, snoozing it again keeps the news already held back.
+    fn snooze_monitor(
+        &self,
+        target: SnoozeTarget,
+        until: BlockHeight,
+    ) -> Result<(), MonitorStoreError>;
+    fn get_snoozes(&self) -> Result<Vec<SnoozeEntry>, MonitorStoreError>;
+    /// Marks the monitors of `targets` as registered with `expected: true`, see
+    /// [`crate::types::RegistrationOptions`]. The mark is kept if the monitor is canceled.
+    fn mark_expected(&self, targets: Vec<SnoozeTarget>) -> Result<(), MonitorStoreError>;
+    fn is_expected(&self, target: &SnoozeTarget) -> Result<bool, MonitorStoreError>;
+    /// Sets the metadata of the monitors of `targets`, see [`crate::types::RegistrationOptions`].
+    /// Empty metadata removes it. It is kept if the monitor is canceled.
+    fn set_monitor_metadata(
+        &self,
+        targets: Vec<SnoozeTarget>,
+        metadata: BTreeMap<String, String>,
+    ) -> Result<(), MonitorStoreError>;
+    fn get_monitor_metadata(&self) -> Result<Vec<MonitorMetadataEntry>, MonitorStoreError>;
+
+    /// Counter moved every time a script is added to or removed from the ScriptPubKey monitors,
+    /// so the scan set built from them can be reused until it moves, see
+    /// [`crate::scan_set::ScanSet`].
+    fn get_scan_generation(&self) -> Result<u64, MonitorStoreError>;
+    /// Values written to the compressible keys since the store was opened, with their compression
+    /// ratio.
+    fn get_storage_stats(&self) -> StorageStats;
+    /// Holds `data` back if its monitor is snoozed, replacing the previous news of the same item.
+    /// Returns `false` if the monitor is not snoozed and the news must be sent.
+    fn defer_news(&self, data: MonitoredTypes) -> Result<bool, MonitorStoreError>;
+    /// Snoozes whose `until` is below `current_height`, left in the store until `end_snooze`.
+    fn get_expired_snoozes(
+        &self,
+        current_height: BlockHeight,
+    ) -> Result<Vec<SnoozeEntry>, MonitorStoreError>;
+    /// Removes the snooze of `target` with the news it holds back. Called once those news are
+    /// written, so a crash in between sends them again instead of losing them.
+    fn end_snooze(&self, target: &SnoozeTarget) -> Result<(), MonitorStoreError>;
+
     fn get_news(&self) -> Result<Vec<MonitoredTypes>, MonitorStoreError>;
+    /// Same as `get_news`, each news comes with its position in the news sequence.
+    fn get_sequenced_news(&self) -> Result<Vec<(u64, MonitoredTypes)>, MonitorStoreError>;
+    /// Up to `limit` unacknowledged news after the `after` cursor, in cursor order.
+    fn get_news_page(
+        &self,
+        after: Option<NewsCursor>,
+        limit: usize,
+    ) -> Result<Vec<(NewsCursor, MonitoredTypes)>, MonitorStoreError>;
+    /// Number of unacknowledged news, read from the acknowledgment flags only.
+    fn get_news_count(&self) -> Result<usize, MonitorStoreError>;
+    /// Whether there is an unacknowledged news, stops reading at the first one.
+    fn has_news(&self) -> Result<bool, MonitorStoreError>;
+    /// Every stored news, acknowledged or not, with its acknowledgment info.
+    fn get_news_acks(&self) -> Result<Vec<(MonitoredTypes, NewsAck)>, MonitorStoreError>;
+    /// Writes a news for `current_block_hash`, recorded in the news history at the monitor
+    /// height. Returns whether it was written, false when the same news was already stored for
+    /// this block.
     fn update_news(
         &self,
         data: MonitoredTypes,
         current_block_hash: BlockHash,
+    ) -> Result<bool, MonitorStoreError>;
+    /// Same as `update_news` for a block not processed yet, recorded in the news history at
+    /// `height`.
+    fn update_news_at(
+        &self,
+        data: MonitoredTypes,
+        current_block_hash: BlockHash,
+        height: BlockHeight,
+    ) -> Result<bool, MonitorStoreError>;
+
+    /// Up to `limit` entries of the news history from the news sequence `from`, in sequence
+    /// order, acknowledged or not.
+    fn get_news_history(
+        &self,
+        from: u64,
+        limit: usize,
+    ) -> Result<Vec<NewsHistoryEntry>, MonitorStoreError>;
+    /// Removes the oldest entries of the news history, the ones written below `written_before`,
+    /// returning how many were removed.
+    fn prune_news_history(&self, written_before: BlockHeight) -> Result<u64, MonitorStoreError>;
+    /// Marks the last news written as disputed when it is `data`: it was decided on an orphan
+    /// flag of the indexer the chain log disagreed with. Returns whether it was marked. The marks
+    /// go away with the news history entries.
+    fn mark_news_orphan_disputed(&self, data: &MonitoredTypes) -> Result<bool, MonitorStoreError>;
+    /// News sequences marked by `mark_news_orphan_disputed`.
+    fn get_orphan_disputed_news(&self) -> Result<Vec<u64>, MonitorStoreError>;
+
+    /// Whether the detection (a pegin or a spend of a monitored output) was already recorded
+    /// for `block_hash` by `record_detection`.
+    fn is_detection_recorded(
+        &self,
+        data: &MonitoredTypes,
+        block_hash: BlockHash,
+    ) -> Result<bool, MonitorStoreError>;
+    /// Records a detection found in `block_hash` at `height`, once the monitors it registers are
+    /// written.
+    fn record_detection(
+        &self,
+        data: MonitoredTypes,
+        block_hash: BlockHash,
+        height: BlockHeight,
     ) -> Result<(), MonitorStoreError>;
 
     fn ack_news(&self, data: AckMonitorNews) -> Result<(), MonitorStoreError>;
+    /// Acknowledges the news of `items` like `ack_news`, in one store transaction where each news
+    /// key is read and written once whatever the number of items acknowledged in it. Returns the
+    /// items that matched no stored news, the others are acknowledged anyway.
+    fn ack_news_batch(
+        &self,
+        items: Vec<AckMonitorNews>,
+    ) -> Result<Vec<AckMonitorNews>, MonitorStoreError>;
+    /// Acknowledges every unacknowledged news in one store transaction, only flipping the
+    /// acknowledgment flags: the entries stay to keep the news from being written again.
+    /// Returns the number of news acknowledged.
+    fn ack_all_news(&self) -> Result<usize, MonitorStoreError>;
+
+    fn get_news_epoch(&self) -> Result<u64, MonitorStoreError>;
+    /// Checks the news sequence counter against the highest sequence of the stored news.
+    /// A counter behind the news means the store was restored from a backup: the counter is
+    /// moved past the news and the epoch is bumped.
+    /// Returns `(old_epoch, new_epoch)` if a restore was detected.
+    ///
+    /// Only a counter restored apart from the news is detected. A store restored whole from an
+    /// older backup has its counter past its news, so it keeps its epoch and the sequences given
+    /// since the backup are given again to other news: nothing outside the store records them.
+    fn recover_news_sequence(&self) -> Result<Option<(u64, u64)>, MonitorStoreError>;
+
+    /// Replaces the report of the last reorg.
+    fn save_reorg_impact(&self, impact: ReorgImpact) -> Result<(), MonitorStoreError>;
+    fn get_last_reorg_impact(&self) -> Result<Option<ReorgImpact>, MonitorStoreError>;
+
+    /// Blocks processed by the monitor within the window compared on a reorg, by height.
+    fn get_block_work(&self) -> Result<Vec<BlockWorkEntry>, MonitorStoreError>;
+    fn set_block_work(&self, blocks: Vec<BlockWorkEntry>) -> Result<(), MonitorStoreError>;
+
+    /// Last blocks applied with `Monitor::apply_block`, by height.
+    fn get_external_chain(&self) -> Result<Vec<ExternalBlockEntry>, MonitorStoreError>;
+    fn set_external_chain(&self, blocks: Vec<ExternalBlockEntry>) -> Result<(), MonitorStoreError>;
+
+    /// Appends a processed block to the chain log, each entry under its own key, dropping the
+    /// oldest entries beyond the last `cap`. A block processed again right after itself (e.g.
+    /// when a monitor is registered between two blocks) is not appended.
+    fn append_chain_log(&self, entry: ChainLogEntry, cap: u32) -> Result<(), MonitorStoreError>;
+    /// Entries of the chain log, in processing order.
+    fn get_chain_log(&self) -> Result<Vec<ChainLogEntry>, MonitorStoreError>;
+    /// Last entries of the chain log, in processing order, read back from the newest one until
+    /// an entry below `from_height`, which is left out.
+    fn get_chain_log_tail(
+        &self,
+        from_height: BlockHeight,
+    ) -> Result<Vec<ChainLogEntry>, MonitorStoreError>;
+
+    /// Stores the status of a transaction at the time its news is emitted, replacing the previous
+    /// one. The transactions of the block are dropped, only the block header fields are kept.
+    /// Each snapshot is kept until `prune` finds no transaction monitor of its transaction.
+    fn save_tx_snapshot(&self, status: TransactionStatus) -> Result<(), MonitorStoreError>;
+    fn get_tx_snapshot(&self, tx_id: &Txid)
+        -> Result<Option<TransactionStatus>, MonitorStoreError>;
+    fn get_tx_snapshots(&self) -> Result<Vec<TransactionStatus>, MonitorStoreError>;
+
+    /// Records that `tx_id` is in the mempool at `height`. The first sighting keeps its height
+    /// until the transaction is found in a block.
+    fn record_mempool_sighting(
+        &self,
+        tx_id: Txid,
+        height: BlockHeight,
+        fee_rate: u64,
+    ) -> Result<(), MonitorStoreError>;
+    /// Forgets the mempool sighting of a transaction found in a block.
+    fn remove_mempool_sighting(&self, tx_id: Txid) -> Result<(), MonitorStoreError>;
+    fn get_mempool_sightings(&self) -> Result<Vec<MempoolSighting>, MonitorStoreError>;
+    /// Monitored transactions seen in the mempool more than `older_than_blocks` blocks before the
+    /// monitor height and not found in a block since, one per context, the longest waiting first.
+    fn get_stale_unconfirmed(
+        &self,
+        older_than_blocks: u32,
+    ) -> Result<Vec<StaleTx>, MonitorStoreError>;
+
+    /// Counts an output paying to `script_pubkey` that the filter of the `extra_data` monitor
+    /// kept from being reported.
+    fn record_filtered_output(
+        &self,
+        script_pubkey: &Script,
+        extra_data: &str,
+        value: Amount,
+    ) -> Result<(), MonitorStoreError>;
+    /// Filtered outputs of every monitor of `script_pubkey`, one entry per context.
+    fn get_address_filter_stats(
+        &self,
+        script_pubkey: &Script,
+    ) -> Result<Vec<AddressFilterStats>, MonitorStoreError>;
+
+    /// Keeps `record` in the detection archive, replacing the record of the same detection found
+    /// in another block.
+    fn archive_detection(&self, record: DetectionRecord) -> Result<(), MonitorStoreError>;
+    /// Drops the archived record of the same detection as `record`, e.g. once it was orphaned.
+    fn remove_archived_detection(&self, record: &DetectionRecord) -> Result<(), MonitorStoreError>;
+    /// Archived detections found from `from_height` to `to_height` (both included), ordered by
+    /// height, kind, txid, outpoint and context.
+    fn get_archived_detections(
+        &self,
+        from_height: BlockHeight,
+        to_height: BlockHeight,
+    ) -> Result<Vec<DetectionRecord>, MonitorStoreError>;
+
+    /// Schedules `key` to be removed by `sweep_expired` once the monitor reaches `expires_at`.
+    /// Setting the expiry of a key again replaces the previous one.
+    fn set_expiry(&self, key: &str, expires_at: BlockHeight) -> Result<(), MonitorStoreError>;
+
+    /// Removes the keys whose expiry is at or below `current_height`.
+    /// Returns the number of keys removed.
+    fn sweep_expired(&self, current_height: BlockHeight) -> Result<u32, MonitorStoreError>;
 
     fn get_monitor_height(&self) -> Result<BlockHeight, MonitorStoreError>;
     fn update_monitor_height(&self, height: BlockHeight) -> Result<(), MonitorStoreError>;
+    /// Hash of the block processed at the monitor height, None before the first block.
+    fn get_monitor_block_hash(&self) -> Result<Option<BlockHash>, MonitorStoreError>;
+    fn update_monitor_block_hash(&self, hash: BlockHash) -> Result<(), MonitorStoreError>;
     fn has_pending_work(&self) -> Result<bool, MonitorStoreError>;
     fn set_pending_work(&self, is_pending_work: bool) -> Result<(), MonitorStoreError>;
+    /// Asks the process running the monitor to reload its configuration file, it is polled
+    /// from the run loop of the binary.
+    fn request_reload(&self) -> Result<(), MonitorStoreError>;
+    /// Returns whether a reload was requested since the last call, and clears the request.
+    fn take_reload_request(&self) -> Result<bool, MonitorStoreError>;
+    /// Network of the node the monitor followed, None until it is recorded with `set_network`.
+    fn get_network(&self) -> Result<Option<Network>, MonitorStoreError>;
+    fn set_network(&self, network: Network) -> Result<(), MonitorStoreError>;
+    /// Writes and removes a probe entry, failing when the storage can't be written.
+    fn check_writable(&self) -> Result<(), MonitorStoreError>;
+    /// Schema version the store was written with, 0 for a store written before the versions.
+    fn get_schema_version(&self) -> Result<u32, MonitorStoreError>;
 
     fn get_transaction_trigger_sent(
         &self,
@@ -83,722 +847,5836 @@ pub trait MonitorStoreApi {
         extra_data: &str,
         trigger_sent: bool,
     ) -> Result<(), MonitorStoreError>;
+    /// Confirmations and block (None if orphaned) of the transaction in the last news sent for
+    /// the `extra_data` monitor, None if no news was sent yet.
+    fn get_last_reported(
+        &self,
+        tx_id: Txid,
+        extra_data: &str,
+    ) -> Result<Option<(u32, Option<BlockHash>)>, MonitorStoreError>;
+    fn update_last_reported(
+        &self,
+        tx_id: Txid,
+        extra_data: &str,
+        confirmations: u32,
+        block_hash: Option<BlockHash>,
+    ) -> Result<(), MonitorStoreError>;
 }
 
-impl MonitorStore {
-    pub fn new(store: Rc<Storage>) -> Result<Self, MonitorStoreError> {
-        Ok(Self { store })
+/// Mock of the store, under the name it had when it was generated from the `MonitorStore` impl.
+pub type MockMonitorStore = MockMonitorStoreApi;
+
+/// Monitor kept in the active and inactive lists, whose entries are told apart by their context.
+/// Each monitor of a list is stored under its own key, see [`MonitorStore::read_listed`].
+pub(crate) trait ListedMonitor: Serialize + DeserializeOwned + Clone + PartialEq {
+    type Entry;
+
+    /// Identifies the monitor in its list and in the key it is stored under: the txid, the
+    /// outpoint or the script in hex.
+    fn item_id(&self) -> String;
+    fn has_entries(&self) -> bool;
+    fn same_target(&self, other: &Self) -> bool;
+    fn entries(&mut self) -> &mut Vec<Self::Entry>;
+    fn context(entry: &Self::Entry) -> &str;
+    fn history(entry: &mut Self::Entry) -> &mut Vec<MonitorEvent>;
+    /// Target of the monitor with the id `id`, as compared by the canonical order.
+    fn canonical_target(id: &str) -> Vec<u8>;
+    /// The monitors of the entries, one per context.
+    fn into_monitors(self) -> Vec<TypesToMonitorStore>;
+}
+
+impl ListedMonitor for TransactionMonitor {
+    type Entry = TransactionMonitorEntry;
+
+    fn item_id(&self) -> String {
+        self.tx_id.to_string()
     }
 
-    fn get_key(&self, key: MonitorKey) -> String {
-        let prefix = "monitor";
-        match key {
-            MonitorKey::Transactions(is_active) => format!(
-                "{prefix}/tx/list/{status}",
-                status = if is_active { "active" } else { "inactive" }
-            ),
-            MonitorKey::SpendingUTXOTransactions(is_active) => format!(
-                "{prefix}/spending/utxo/tx/list/{status}",
-                status = if is_active { "active" } else { "inactive" }
-            ),
-            MonitorKey::PendingWork => format!("{prefix}/all/pending_work"),
-            MonitorKey::RskPegin => format!("{prefix}/rsk/pegin"),
-            MonitorKey::NewBlock => format!("{prefix}/new/block"),
-            MonitorKey::TransactionsNews => format!("{prefix}/tx/news"),
-            MonitorKey::RskPeginTransactionsNews => format!("{prefix}/rsk/tx/news"),
-            MonitorKey::SpendingUTXOTransactionsNews => {
-                format!("{prefix}/spending/utxo/tx/news")
-            }
-            MonitorKey::NewBlockNews => format!("{prefix}/new/block/news"),
-        }
+    fn has_entries(&self) -> bool {
+        !self.entries.is_empty()
     }
 
-    fn get_blockchain_key(&self, key: BlockchainKey) -> String {
-        let prefix = "monitor";
-        match key {
-            BlockchainKey::CurrentBlockHeight => {
-                format!("{prefix}/blockchain/current_block_height")
-            }
-        }
+    fn same_target(&self, other: &Self) -> bool {
+        self.tx_id == other.tx_id
     }
-}
 
-#[automock]
-impl MonitorStoreApi for MonitorStore {
-    fn set_pending_work(&self, is_pending_work: bool) -> Result<(), MonitorStoreError> {
-        let key = self.get_key(MonitorKey::PendingWork);
-        self.store.set(&key, is_pending_work, None)?;
-        Ok(())
+    fn entries(&mut self) -> &mut Vec<Self::Entry> {
+        &mut self.entries
     }
 
-    fn has_pending_work(&self) -> Result<bool, MonitorStoreError> {
-        let key = self.get_key(MonitorKey::PendingWork);
-        let pending_work = self.store.get::<_, bool>(&key)?.unwrap_or(false);
-        Ok(pending_work)
+    fn context(entry: &Self::Entry) -> &str {
+        &entry.extra_data
     }
 
-    fn get_monitor_height(&self) -> Result<BlockHeight, MonitorStoreError> {
-        let last_block_height_key = self.get_blockchain_key(BlockchainKey::CurrentBlockHeight);
-        let last_block_height = self
-            .store
-            .get::<_, BlockHeight>(&last_block_height_key)?
-            .unwrap_or_default();
+    fn history(entry: &mut Self::Entry) -> &mut Vec<MonitorEvent> {
+        &mut entry.history
+    }
 
-        Ok(last_block_height)
+    fn canonical_target(id: &str) -> Vec<u8> {
+        Txid::from_str(id).map_or_else(|_| vec![], |tx_id| tx_id.as_byte_array().to_vec())
     }
 
-    fn update_monitor_height(&self, height: BlockHeight) -> Result<(), MonitorStoreError> {
-        let last_block_height_key = self.get_blockchain_key(BlockchainKey::CurrentBlockHeight);
-        self.store.set(last_block_height_key, height, None)?;
-        Ok(())
+    fn into_monitors(self) -> Vec<TypesToMonitorStore> {
+        self.entries
+            .into_iter()
+            .map(|entry| {
+                TypesToMonitorStore::Transaction(
+                    self.tx_id,
+                    entry.extra_data,
+                    entry.confirmation_trigger,
+                    entry.expires_at_height,
+                )
+            })
+            .collect()
     }
+}
 
-    fn get_news(&self) -> Result<Vec<MonitoredTypes>, MonitorStoreError> {
-        let mut news = Vec::new();
+impl ListedMonitor for SpendingUTXOMonitor {
+    type Entry = SpendingUTXOMonitorEntry;
 
-        let key = self.get_key(MonitorKey::TransactionsNews);
-        let txs_news: Vec<TransactionNewsEntry> = self.store.get(&key)?.unwrap_or_default();
+    fn item_id(&self) -> String {
+        OutPoint::new(self.tx_id, self.vout).to_string()
+    }
 
-        for entry in txs_news {
-            if !entry.ack.acknowledged {
-                news.push(MonitoredTypes::Transaction(entry.tx_id, entry.extra_data));
-            }
-        }
+    fn has_entries(&self) -> bool {
+        !self.entries.is_empty()
+    }
 
-        let rsk_news_key = self.get_key(MonitorKey::RskPeginTransactionsNews);
-        let rsk_news: Vec<RskPeginNewsEntry> = self.store.get(&rsk_news_key)?.unwrap_or_default();
+    fn same_target(&self, other: &Self) -> bool {
+        self.tx_id == other.tx_id && self.vout == other.vout
+    }
 
-        for entry in rsk_news {
-            if !entry.ack.acknowledged {
-                news.push(MonitoredTypes::RskPeginTransaction(entry.tx_id));
-            }
-        }
+    fn entries(&mut self) -> &mut Vec<Self::Entry> {
+        &mut self.entries
+    }
 
-        let spending_news_key = self.get_key(MonitorKey::SpendingUTXOTransactionsNews);
-        let spending_news: Vec<SpendingUTXONewsEntry> =
-            self.store.get(&spending_news_key)?.unwrap_or_default();
+    fn context(entry: &Self::Entry) -> &str {
+        &entry.extra_data
+    }
 
-        for entry in spending_news {
-            if !entry.ack.acknowledged {
-                news.push(MonitoredTypes::SpendingUTXOTransaction(
-                    entry.tx_id,
-                    entry.utxo_index,
+    fn history(entry: &mut Self::Entry) -> &mut Vec<MonitorEvent> {
+        &mut entry.history
+    }
+
+    fn canonical_target(id: &str) -> Vec<u8> {
+        OutPoint::from_str(id).map_or_else(
+            |_| vec![],
+            |outpoint| {
+                let mut target = outpoint.txid.as_byte_array().to_vec();
+                target.extend(outpoint.vout.to_be_bytes());
+                target
+            },
+        )
+    }
+
+    fn into_monitors(self) -> Vec<TypesToMonitorStore> {
+        self.entries
+            .into_iter()
+            .map(|entry| {
+                TypesToMonitorStore::SpendingUTXOTransaction(
+                    self.tx_id,
+                    self.vout,
                     entry.extra_data,
-                    entry.spender_tx_id,
-                ));
-            }
-        }
+                    entry.confirmation_trigger,
+                    entry.deadline,
+                    entry.value_gate,
+                    entry.expires_at_height,
+                )
+            })
+            .collect()
+    }
+}
 
-        let block_news_key = self.get_key(MonitorKey::NewBlockNews);
-        let block_news: Option<NewsAck> = self.store.get(&block_news_key)?;
+impl ListedMonitor for ScriptPubKeyMonitor {
+    type Entry = ScriptPubKeyMonitorEntry;
 
-        if let Some(ack) = block_news {
-            if !ack.acknowledged {
-                news.push(MonitoredTypes::NewBlock(ack.block_hash));
-            }
-        }
+    fn item_id(&self) -> String {
+        self.script_pubkey.to_hex_string()
+    }
 
-        Ok(news)
+    fn has_entries(&self) -> bool {
+        !self.entries.is_empty()
     }
 
-    fn update_news(
-        &self,
-        data: MonitoredTypes,
-        current_block_hash: BlockHash,
-    ) -> Result<(), MonitorStoreError> {
-        // Notification will be updated if the block_hash is different
-        // If the notification is already in the store, it will be updated with the new block_hash and ack set to false.
+    fn same_target(&self, other: &Self) -> bool {
+        self.script_pubkey == other.script_pubkey
+    }
 
-        match data {
-            MonitoredTypes::Transaction(tx_id, extra_data) => {
-                let key = self.get_key(MonitorKey::TransactionsNews);
-                let mut txs_news: Vec<TransactionNewsEntry> =
-                    self.store.get(&key)?.unwrap_or_default();
+    fn entries(&mut self) -> &mut Vec<Self::Entry> {
+        &mut self.entries
+    }
 
-                // Check if news already exists for this (tx_id, extra_data) combination
-                // Different extra_data should generate separate news entries
-                let is_new_news = txs_news
-                    .iter()
-                    .position(|e| e.tx_id == tx_id && e.extra_data == extra_data);
+    fn context(entry: &Self::Entry) -> &str {
+        &entry.extra_data
+    }
 
-                match is_new_news {
-                    None => {
-                        // Insert news with current block hash and ack in false
-                        txs_news.push(TransactionNewsEntry {
-                            tx_id,
-                            extra_data: extra_data.clone(),
-                            ack: NewsAck::new(current_block_hash, false),
-                        });
-                    }
-                    Some(pos) => {
-                        if txs_news[pos].ack.block_hash != current_block_hash {
-                            // Replace the notification with the new block hash
-                            txs_news[pos] = TransactionNewsEntry {
-                                tx_id,
-                                extra_data: extra_data.clone(),
-                                ack: NewsAck::new(current_block_hash, false),
-                            };
-                        }
-                    }
-                }
+    fn history(entry: &mut Self::Entry) -> &mut Vec<MonitorEvent> {
+        &mut entry.history
+    }
 
-                self.store.set(&key, &txs_news, None)?;
-            }
-            MonitoredTypes::RskPeginTransaction(tx_id) => {
-                let rsk_news_key = self.get_key(MonitorKey::RskPeginTransactionsNews);
-                let mut rsk_news: Vec<RskPeginNewsEntry> =
-                    self.store.get(&rsk_news_key)?.unwrap_or_default();
+    fn canonical_target(id: &str) -> Vec<u8> {
+        hex::decode(id).unwrap_or_default()
+    }
 
-                // Check if news already exists for this tx_id
-                // RskPeginTransaction doesn't have extra_data, so we only check by tx_id
-                let is_new_news = rsk_news.iter().position(|e| e.tx_id == tx_id);
+    fn into_monitors(self) -> Vec<TypesToMonitorStore> {
+        self.entries
+            .into_iter()
+            .map(|entry| {
+                TypesToMonitorStore::ScriptPubKey(
+                    self.script_pubkey.clone(),
+                    entry.extra_data,
+                    entry.confirmation_trigger,
+                    entry.filter,
+                )
+            })
+            .collect()
+    }
+}
 
-                match is_new_news {
-                    None => rsk_news.push(RskPeginNewsEntry {
-                        tx_id,
-                        ack: NewsAck::new(current_block_hash, false),
-                    }),
-                    Some(pos) => {
-                        if rsk_news[pos].ack.block_hash != current_block_hash {
-                            // Replace the notification with the new block hash
-                            rsk_news[pos] = RskPeginNewsEntry {
-                                tx_id,
-                                ack: NewsAck::new(current_block_hash, false),
-                            };
-                        }
-                    }
-                }
+/// Current unix time, in seconds, as recorded in the news history.
+fn unix_now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |elapsed| elapsed.as_secs())
+}
+
+/// A stored news entry, acknowledged through its [`NewsAck`].
+trait NewsEntry: Serialize + DeserializeOwned {
+    fn ack_mut(&mut self) -> &mut NewsAck;
+}
 
-                self.store.set(&rsk_news_key, &rsk_news, None)?;
+macro_rules! impl_news_entry {
+    ($($entry:ty),* $(,)?) => {
+        $(impl NewsEntry for $entry {
+            fn ack_mut(&mut self) -> &mut NewsAck {
+                &mut self.ack
             }
-            MonitoredTypes::SpendingUTXOTransaction(
-                tx_id,
-                utxo_index,
-                extra_data,
-                spender_tx_id,
-            ) => {
-                let utxo_news_key = self.get_key(MonitorKey::SpendingUTXOTransactionsNews);
-                let mut utxo_news: Vec<SpendingUTXONewsEntry> =
-                    self.store.get(&utxo_news_key)?.unwrap_or_default();
+        })*
+    };
+}
 
-                // Check if news already exists for this (tx_id, utxo_index, extra_data)
-                // Different extra_data should generate separate news entries
-                let is_new_news = utxo_news.iter().position(|e| {
-                    e.tx_id == tx_id && e.utxo_index == utxo_index && e.extra_data == extra_data
-                });
+impl_news_entry!(
+    TransactionNewsEntry,
+    RskPeginNewsEntry,
+    SpendingUTXONewsEntry,
+    ScriptPubKeyNewsEntry,
+    OpReturnNewsEntry,
+    ReplacementNewsEntry,
+    AlternativeNewsEntry,
+    ExpiredMonitorNewsEntry,
+    NewBlockNewsEntry,
+    EpochNewsEntry,
+    ReorgNewsEntry,
+    HealthNewsEntry,
+    LowWorkReorgNewsEntry,
+    ManualResolutionNewsEntry,
+    CompositeNewsEntry,
+);
 
-                match is_new_news {
-                    None => utxo_news.push(SpendingUTXONewsEntry {
-                        tx_id,
-                        utxo_index,
-                        extra_data: extra_data.clone(),
-                        spender_tx_id,
-                        ack: NewsAck::new(current_block_hash, false),
-                    }),
-                    Some(pos) => {
-                        // Replace the notification only if the block hash is different
-                        if utxo_news[pos].ack.block_hash != current_block_hash {
-                            utxo_news[pos] = SpendingUTXONewsEntry {
-                                tx_id,
-                                utxo_index,
-                                extra_data: extra_data.clone(),
-                                spender_tx_id,
-                                ack: NewsAck::new(current_block_hash, false),
-                            };
-                        }
-                    }
-                }
+/// The acknowledgment of a stored news entry, the rest of the entry is skipped when read.
+#[derive(Deserialize)]
+struct NewsAckView {
+    ack: NewsAck,
+}
 
-                self.store.set(&utxo_news_key, &utxo_news, None)?;
-            }
-            MonitoredTypes::NewBlock(hash) => {
-                let key = self.get_key(MonitorKey::NewBlockNews);
+/// Monitors of an active or inactive list read from the store, used as a `Vec`. Written back with
+/// [`MonitorStore::write_listed`], which only writes the monitors that changed.
+pub(crate) struct Listed<M> {
+    list: MonitorKey,
+    /// Ids of the list, in the order the monitors were added
+    index: Vec<String>,
+    /// Monitors as read, all of the list or the ones asked for
+    read: Vec<M>,
+    complete: bool,
+    monitors: Vec<M>,
+}
 
-                let data: Option<NewsAck> = self.store.get(&key)?;
+impl<M> Deref for Listed<M> {
+    type Target = Vec<M>;
 
-                if let Some(ack) = data {
-                    if ack.block_hash != hash {
-                        // Replace the notification with the new block hash
-                        self.store
-                            .set(&key, NewsAck::new(current_block_hash, false), None)?;
-                    }
-                } else {
-                    self.store
-                        .set(&key, NewsAck::new(current_block_hash, false), None)?;
-                }
+    fn deref(&self) -> &Self::Target {
+        &self.monitors
+    }
+}
+
+impl<M> DerefMut for Listed<M> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.monitors
+    }
+}
+
+impl<S: Deref<Target = Storage>> MonitorStore<S> {
+    /// Opens the store, migrating a store written by an older version to the current schema,
+    /// see [`crate::migrations::migrate`]. A store written by a newer version is rejected with
+    /// [`MonitorStoreError::IncompatibleSchema`].
+    pub fn new(store: S) -> Result<Self, MonitorStoreError> {
+        let store = Self {
+            store,
+            compression: CompressionCounters::default(),
+        };
+        migrate(&store)?;
+
+        Ok(store)
+    }
+
+    /// Opens the store as it is, without migrating it, for a check that must leave it untouched
+    /// (see [`crate::preflight::preflight_with_paths`]). Reads of a store of an older schema see
+    /// its old layout.
+    pub fn new_unmigrated(store: S) -> Self {
+        Self {
+            store,
+            compression: CompressionCounters::default(),
+        }
+    }
+
+    /// Starts a store transaction, for the migrations.
+    pub(crate) fn begin_transaction(&self) -> Uuid {
+        self.store.begin_transaction()
+    }
+
+    pub(crate) fn commit_transaction(&self, transaction_id: Uuid) -> Result<(), MonitorStoreError> {
+        Ok(self.store.commit_transaction(transaction_id)?)
+    }
+
+    pub(crate) fn rollback_transaction(
+        &self,
+        transaction_id: Uuid,
+    ) -> Result<(), MonitorStoreError> {
+        Ok(self.store.rollback_transaction(transaction_id)?)
+    }
+
+    /// Adds replacement lifecycle news, these are only sent once per (old tx, context, kind).
+    fn add_replacement_news(
+        &self,
+        entries: Vec<ReplacementNewsEntry>,
+        transaction_id: Option<Uuid>,
+    ) -> Result<(), MonitorStoreError> {
+        let key = self.get_key(MonitorKey::ReplacementNews);
+        let mut replacement_news: Vec<ReplacementNewsEntry> =
+            self.read_typed(&key)?.unwrap_or_default();
+        let len = replacement_news.len();
+
+        for entry in entries {
+            if !replacement_news.iter().any(|e| {
+                e.old_tx_id == entry.old_tx_id
+                    && e.extra_data == entry.extra_data
+                    && e.old_confirmed == entry.old_confirmed
+            }) {
+                replacement_news.push(entry);
             }
         }
 
+        if replacement_news.len() != len {
+            self.write_typed(&key, &replacement_news, transaction_id)?;
+        }
+
         Ok(())
     }
 
-    fn ack_news(&self, data: AckMonitorNews) -> Result<(), MonitorStoreError> {
-        match data {
-            AckMonitorNews::Transaction(tx_id, extra_data) => {
-                let key = self.get_key(MonitorKey::TransactionsNews);
-                let mut txs_news: Vec<TransactionNewsEntry> =
-                    self.store.get(&key)?.unwrap_or_default();
+    /// Adds a manually resolved news, replacing the one of an earlier resolution of its target.
+    fn add_resolution_news(
+        &self,
+        entry: ManualResolutionNewsEntry,
+        transaction_id: Option<Uuid>,
+    ) -> Result<(), MonitorStoreError> {
+        let key = self.get_key(MonitorKey::ManualResolutionNews);
+        let mut resolution_news: Vec<ManualResolutionNewsEntry> =
+            self.read_typed(&key)?.unwrap_or_default();
 
-                // Acknowledge only the news entry matching both tx_id and extra_data
-                if let Some(entry) = txs_news
-                    .iter_mut()
-                    .find(|e| e.tx_id == tx_id && e.extra_data == extra_data)
-                {
-                    entry.ack.acknowledged = true;
-                    self.store.set(&key, &txs_news, None)?;
+        resolution_news.retain(|e| e.target != entry.target);
+        resolution_news.push(entry);
+
+        self.write_typed(&key, &resolution_news, transaction_id)
+    }
+
+    /// Key the news acknowledged by `ack` are stored under.
+    fn news_key_of(ack: &AckMonitorNews) -> MonitorKey {
+        match ack {
+            AckMonitorNews::Transaction(..) => MonitorKey::TransactionsNews,
+            AckMonitorNews::OrphanedTransaction(..) => MonitorKey::OrphanedTransactionsNews,
+            AckMonitorNews::RskPeginTransaction(_) => MonitorKey::RskPeginTransactionsNews,
+            AckMonitorNews::SpendingUTXOTransaction(..) => MonitorKey::SpendingUTXOTransactionsNews,
+            AckMonitorNews::MinorSpend(..) => MonitorKey::MinorSpendNews,
+            AckMonitorNews::SpendingUTXOUnconfirmed(..) => MonitorKey::SpendingUTXOUnconfirmedNews,
+            AckMonitorNews::ScriptPubKeyTransaction(..) => MonitorKey::ScriptPubKeyNews,
+            AckMonitorNews::OpReturnTransaction(..) => MonitorKey::OpReturnNews,
+            AckMonitorNews::MonitorReplaced { .. }
+            | AckMonitorNews::ReplacedTransactionConfirmed { .. } => MonitorKey::ReplacementNews,
+            AckMonitorNews::NewBlock(_) => MonitorKey::NewBlockNews,
+            AckMonitorNews::Reorg { .. } => MonitorKey::ReorgNews,
+            AckMonitorNews::LowWorkReorg { .. } => MonitorKey::LowWorkReorgNews,
+            AckMonitorNews::HealthStateChanged { .. } => MonitorKey::HealthNews,
+            AckMonitorNews::AlternativeResolved { .. } => MonitorKey::AlternativeNews,
+            AckMonitorNews::MonitorExpired { .. } => MonitorKey::ExpiredMonitorsNews,
+            AckMonitorNews::SequenceEpochChanged { .. } => MonitorKey::EpochNews,
+            AckMonitorNews::ManuallyResolved(_) => MonitorKey::ManualResolutionNews,
+            AckMonitorNews::Composite(_) => MonitorKey::CompositeNews,
+        }
+    }
+
+    /// Acknowledges the news of `acks`, all stored under `key`, with one read and at most one
+    /// write of the key. Returns the acks that matched no news.
+    fn ack_news_group(
+        &self,
+        key: MonitorKey,
+        acks: Vec<AckMonitorNews>,
+        transaction_id: Uuid,
+    ) -> Result<Vec<AckMonitorNews>, MonitorStoreError> {
+        let Some(first) = acks.first() else {
+            return Ok(vec![]);
+        };
+        let tx = Some(transaction_id);
+
+        match first {
+            AckMonitorNews::Transaction(..) => self.ack_listed_news(
+                key,
+                acks,
+                tx,
+                |e: &TransactionNewsEntry, ack| {
+                    // Only the news entry matching both tx_id and extra_data
+                    match ack {
+                        AckMonitorNews::Transaction(tx_id, extra_data) => {
+                            e.tx_id == *tx_id && e.extra_data == *extra_data
+                        }
+                        _ => false,
+                    }
+                },
+                false,
+            ),
+            AckMonitorNews::OrphanedTransaction(..) => self.ack_listed_news(
+                key,
+                acks,
+                tx,
+                |e: &TransactionNewsEntry, ack| match ack {
+                    AckMonitorNews::OrphanedTransaction(tx_id, extra_data) => {
+                        e.tx_id == *tx_id && e.extra_data == *extra_data
+                    }
+                    _ => false,
+                },
+                false,
+            ),
+            //TODO: THIS SHOULD change, we need to start sending context to ack a news.
+            // RskPeginTransaction doesn't have extra_data, every news entry of the tx_id is
+            // acknowledged
+            AckMonitorNews::RskPeginTransaction(_) => self.ack_listed_news(
+                key,
+                acks,
+                tx,
+                |e: &RskPeginNewsEntry, ack| match ack {
+                    AckMonitorNews::RskPeginTransaction(tx_id) => e.tx_id == *tx_id,
+                    _ => false,
+                },
+                true,
+            ),
+            AckMonitorNews::SpendingUTXOTransaction(..) => self.ack_listed_news(
+                key,
+                acks,
+                tx,
+                |e: &SpendingUTXONewsEntry, ack| match ack {
+                    AckMonitorNews::SpendingUTXOTransaction(tx_id, utxo_index, extra_data) => {
+                        e.tx_id == *tx_id
+                            && e.utxo_index == *utxo_index
+                            && e.extra_data == *extra_data
+                    }
+                    _ => false,
+                },
+                false,
+            ),
+            AckMonitorNews::MinorSpend(..) => self.ack_listed_news(
+                key,
+                acks,
+                tx,
+                |e: &SpendingUTXONewsEntry, ack| match ack {
+                    AckMonitorNews::MinorSpend(tx_id, utxo_index, extra_data) => {
+                        e.tx_id == *tx_id
+                            && e.utxo_index == *utxo_index
+                            && e.extra_data == *extra_data
+                    }
+                    _ => false,
+                },
+                false,
+            ),
+            AckMonitorNews::SpendingUTXOUnconfirmed(..) => self.ack_listed_news(
+                key,
+                acks,
+                tx,
+                |e: &SpendingUTXONewsEntry, ack| match ack {
+                    AckMonitorNews::SpendingUTXOUnconfirmed(tx_id, utxo_index, extra_data) => {
+                        e.tx_id == *tx_id
+                            && e.utxo_index == *utxo_index
+                            && e.extra_data == *extra_data
+                    }
+                    _ => false,
+                },
+                false,
+            ),
+            AckMonitorNews::ScriptPubKeyTransaction(..) => self.ack_listed_news(
+                key,
+                acks,
+                tx,
+                |e: &ScriptPubKeyNewsEntry, ack| match ack {
+                    AckMonitorNews::ScriptPubKeyTransaction(tx_id, vout, extra_data) => {
+                        e.tx_id == *tx_id && e.vout == *vout && e.extra_data == *extra_data
+                    }
+                    _ => false,
+                },
+                false,
+            ),
+            // The news of every prefix monitored with this context
+            AckMonitorNews::OpReturnTransaction(..) => self.ack_listed_news(
+                key,
+                acks,
+                tx,
+                |e: &OpReturnNewsEntry, ack| match ack {
+                    AckMonitorNews::OpReturnTransaction(tx_id, extra_data) => {
+                        e.tx_id == *tx_id && e.extra_data == *extra_data
+                    }
+                    _ => false,
+                },
+                true,
+            ),
+            AckMonitorNews::MonitorReplaced { .. }
+            | AckMonitorNews::ReplacedTransactionConfirmed { .. } => self.ack_listed_news(
+                key,
+                acks,
+                tx,
+                |e: &ReplacementNewsEntry, ack| match ack {
+                    AckMonitorNews::MonitorReplaced { old, context } => {
+                        e.old_tx_id == *old && e.extra_data == *context && !e.old_confirmed
+                    }
+                    AckMonitorNews::ReplacedTransactionConfirmed { old, context } => {
+                        e.old_tx_id == *old && e.extra_data == *context && e.old_confirmed
+                    }
+                    _ => false,
+                },
+                false,
+            ),
+            AckMonitorNews::NewBlock(_) => {
+                let mut queue = self.read_block_news()?;
+
+                // Without a hash the oldest pending block is acknowledged
+                let (acked, unknown) = Self::mark_acked(
+                    &mut queue,
+                    acks,
+                    false,
+                    |e: &NewBlockNewsEntry, ack| match ack {
+                        AckMonitorNews::NewBlock(hash) => {
+                            !e.ack.acknowledged && hash.is_none_or(|hash| e.ack.block_hash == hash)
+                        }
+                        _ => false,
+                    },
+                );
+                if !acked.is_empty() {
+                    self.write_typed(&self.get_key(key), &queue, tx)?;
+                    self.record_news_acks(&acked, tx)?;
                 }
+                Ok(unknown)
+            }
+            AckMonitorNews::Reorg { .. } => {
+                self.ack_single_news(key, acks, tx, |e: &ReorgNewsEntry, ack| match ack {
+                    AckMonitorNews::Reorg { new_tip } => e.new_tip == *new_tip,
+                    _ => false,
+                })
+            }
+            AckMonitorNews::LowWorkReorg { .. } => {
+                self.ack_single_news(key, acks, tx, |e: &LowWorkReorgNewsEntry, ack| match ack {
+                    AckMonitorNews::LowWorkReorg { height } => e.height == *height,
+                    _ => false,
+                })
+            }
+            AckMonitorNews::HealthStateChanged { .. } => {
+                self.ack_single_news(key, acks, tx, |e: &HealthNewsEntry, ack| match ack {
+                    AckMonitorNews::HealthStateChanged { state } => e.state == *state,
+                    _ => false,
+                })
             }
-            AckMonitorNews::RskPeginTransaction(tx_id) => {
-                let key = self.get_key(MonitorKey::RskPeginTransactionsNews);
-                let mut txs_news: Vec<RskPeginNewsEntry> =
-                    self.store.get(&key)?.unwrap_or_default();
+            AckMonitorNews::AlternativeResolved { .. } => self.ack_listed_news(
+                key,
+                acks,
+                tx,
+                |e: &AlternativeNewsEntry, ack| match ack {
+                    AckMonitorNews::AlternativeResolved { winner, context } => {
+                        e.winner == *winner && e.extra_data == *context
+                    }
+                    _ => false,
+                },
+                false,
+            ),
+            AckMonitorNews::MonitorExpired { .. } => self.ack_listed_news(
+                key,
+                acks,
+                tx,
+                |e: &ExpiredMonitorNewsEntry, ack| match ack {
+                    AckMonitorNews::MonitorExpired {
+                        tx_id,
+                        vout,
+                        context,
+                    } => e.tx_id == *tx_id && e.vout == *vout && e.extra_data == *context,
+                    _ => false,
+                },
+                false,
+            ),
+            AckMonitorNews::SequenceEpochChanged { .. } => {
+                self.ack_single_news(key, acks, tx, |e: &EpochNewsEntry, ack| match ack {
+                    AckMonitorNews::SequenceEpochChanged { new_epoch } => e.new_epoch == *new_epoch,
+                    _ => false,
+                })
+            }
+            AckMonitorNews::ManuallyResolved(_) => self.ack_listed_news(
+                key,
+                acks,
+                tx,
+                |e: &ManualResolutionNewsEntry, ack| match ack {
+                    AckMonitorNews::ManuallyResolved(descriptor) => e.target == descriptor.monitor,
+                    _ => false,
+                },
+                false,
+            ),
+            AckMonitorNews::Composite(_) => self.ack_listed_news(
+                key,
+                acks,
+                tx,
+                |e: &CompositeNewsEntry, ack| match ack {
+                    AckMonitorNews::Composite(id) => e.id == *id,
+                    _ => false,
+                },
+                false,
+            ),
+        }
+    }
+
+    /// Acknowledges in the `Vec` of news under `key` the entries matched by each of `acks`, see
+    /// `mark_acked`. The key is written once if any entry matched.
+    fn ack_listed_news<E: NewsEntry>(
+        &self,
+        key: MonitorKey,
+        acks: Vec<AckMonitorNews>,
+        transaction_id: Option<Uuid>,
+        matches: impl Fn(&E, &AckMonitorNews) -> bool,
+        every: bool,
+    ) -> Result<Vec<AckMonitorNews>, MonitorStoreError> {
+        let key = self.get_key(key);
+        let mut entries: Vec<E> = self.read_typed(&key)?.unwrap_or_default();
+
+        let (acked, unknown) = Self::mark_acked(&mut entries, acks, every, matches);
+        if !acked.is_empty() {
+            self.write_typed(&key, &entries, transaction_id)?;
+            self.record_news_acks(&acked, transaction_id)?;
+        }
+
+        Ok(unknown)
+    }
 
-                //TODO: THIS SHOULD change, we need to start sending context to ack a news.
-                // Acknowledge all news entries for this tx_id
-                // RskPeginTransaction doesn't have extra_data, but we acknowledge all entries for consistency
-                let mut found_any = false;
-                for entry in txs_news.iter_mut() {
-                    if entry.tx_id == tx_id {
-                        entry.ack.acknowledged = true;
-                        found_any = true;
+    /// Same as `ack_listed_news` for the news kept as a single entry, only the last one.
+    fn ack_single_news<E: NewsEntry>(
+        &self,
+        key: MonitorKey,
+        acks: Vec<AckMonitorNews>,
+        transaction_id: Option<Uuid>,
+        matches: impl Fn(&E, &AckMonitorNews) -> bool,
+    ) -> Result<Vec<AckMonitorNews>, MonitorStoreError> {
+        let key = self.get_key(key);
+        let mut entries: Vec<E> = self.read_typed::<E>(&key)?.into_iter().collect();
+
+        let (acked, unknown) = Self::mark_acked(&mut entries, acks, false, matches);
+        if !acked.is_empty() {
+            self.write_typed(&key, entries.pop(), transaction_id)?;
+            self.record_news_acks(&acked, transaction_id)?;
+        }
+
+        Ok(unknown)
+    }
+
+    /// Acknowledges, for each of `acks` in order, the first entry it matches or, with `every`,
+    /// all of them. Returns the sequences of the entries acknowledged by this call and the acks
+    /// that matched none.
+    fn mark_acked<E: NewsEntry>(
+        entries: &mut [E],
+        acks: Vec<AckMonitorNews>,
+        every: bool,
+        matches: impl Fn(&E, &AckMonitorNews) -> bool,
+    ) -> (Vec<u64>, Vec<AckMonitorNews>) {
+        let mut acked = vec![];
+        let mut unknown = vec![];
+
+        for ack in acks {
+            let mut matched = false;
+            for entry in entries.iter_mut() {
+                if matches(entry, &ack) {
+                    let entry_ack = entry.ack_mut();
+                    if !entry_ack.acknowledged {
+                        entry_ack.acknowledged = true;
+                        acked.push(entry_ack.sequence);
+                    }
+                    matched = true;
+                    if !every {
+                        break;
                     }
                 }
+            }
 
-                if found_any {
-                    self.store.set(&key, &txs_news, None)?;
-                }
+            if !matched {
+                unknown.push(ack);
             }
-            AckMonitorNews::SpendingUTXOTransaction(tx_id, utxo_index, extra_data) => {
-                let key = self.get_key(MonitorKey::SpendingUTXOTransactionsNews);
-                let mut txs_news: Vec<SpendingUTXONewsEntry> =
-                    self.store.get(&key)?.unwrap_or_default();
+        }
 
-                // Acknowledge only the news entry matching (tx_id, utxo_index, extra_data)
-                if let Some(entry) = txs_news.iter_mut().find(|e| {
-                    e.tx_id == tx_id && e.utxo_index == utxo_index && e.extra_data == extra_data
-                }) {
-                    entry.ack.acknowledged = true;
-                    self.store.set(&key, &txs_news, None)?;
-                }
+        (acked, unknown)
+    }
+
+    /// Acknowledges every entry not acknowledged yet, returns their sequences.
+    fn mark_all_acked<E: NewsEntry>(entries: &mut [E]) -> Vec<u64> {
+        let mut acked = vec![];
+        for entry in entries.iter_mut() {
+            let ack = entry.ack_mut();
+            if !ack.acknowledged {
+                ack.acknowledged = true;
+                acked.push(ack.sequence);
             }
-            AckMonitorNews::NewBlock => {
-                let key = self.get_key(MonitorKey::NewBlockNews);
-                let mut new_block_news: Option<NewsAck> = self.store.get(&key)?;
+        }
+        acked
+    }
 
-                if let Some(ack) = new_block_news.as_mut() {
-                    ack.acknowledged = true;
-                    self.store.set(&key, new_block_news, None)?;
-                }
+    /// Records in the news history the acknowledgment of the news at `sequences`.
+    fn record_news_acks(
+        &self,
+        sequences: &[u64],
+        transaction_id: Option<Uuid>,
+    ) -> Result<(), MonitorStoreError> {
+        let acked = NewsHistoryAck {
+            height: self.get_monitor_height()?,
+            acked_at_unix: unix_now(),
+        };
+
+        for sequence in sequences {
+            let key = self.get_key(MonitorKey::NewsHistory(*sequence));
+            // News written before the history existed, or pruned from it, have no entry
+            if let Some(mut entry) = self.read_typed::<NewsHistoryEntry>(&key)? {
+                entry.acked = Some(acked.clone());
+                self.write_typed(&key, entry, transaction_id)?;
             }
         }
 
         Ok(())
     }
 
-    fn get_monitors(&self) -> Result<Vec<TypesToMonitorStore>, MonitorStoreError> {
-        let mut monitors = Vec::<TypesToMonitorStore>::new();
+    /// Acknowledges every news in the `Vec` under `key`, the key is only written if any was
+    /// pending.
+    fn ack_all_listed_news<E: NewsEntry>(
+        &self,
+        key: MonitorKey,
+        transaction_id: Uuid,
+    ) -> Result<usize, MonitorStoreError> {
+        let key = self.get_key(key);
+        let mut entries: Vec<E> = self.read_typed(&key)?.unwrap_or_default();
 
-        // Get active transactions
-        let txs_key = self.get_key(MonitorKey::Transactions(true));
-        let txs: Vec<TransactionMonitor> = self.store.get(&txs_key)?.unwrap_or_default();
+        let acked = Self::mark_all_acked(&mut entries);
+        if !acked.is_empty() {
+            self.write_typed(&key, &entries, Some(transaction_id))?;
+            self.record_news_acks(&acked, Some(transaction_id))?;
+        }
 
-        for monitor in txs {
-            for entry in monitor.entries {
-                monitors.push(TypesToMonitorStore::Transaction(
-                    monitor.tx_id,
-                    entry.extra_data,
-                    entry.confirmation_trigger,
-                ));
-            }
+        Ok(acked.len())
+    }
+
+    /// Same as `ack_all_listed_news` for the news kept as a single entry.
+    fn ack_all_single_news<E: NewsEntry>(
+        &self,
+        key: MonitorKey,
+        transaction_id: Uuid,
+    ) -> Result<usize, MonitorStoreError> {
+        let key = self.get_key(key);
+        let mut entries: Vec<E> = self.read_typed::<E>(&key)?.into_iter().collect();
+
+        let acked = Self::mark_all_acked(&mut entries);
+        if !acked.is_empty() {
+            self.write_typed(&key, entries.pop(), Some(transaction_id))?;
+            self.record_news_acks(&acked, Some(transaction_id))?;
         }
 
-        // Get RSK pegin monitor (if active)
-        let rsk_pegin_key = self.get_key(MonitorKey::RskPegin);
-        let rsk_pegin_active: Option<RskPeginMonitorState> = self.store.get(&rsk_pegin_key)?;
+        Ok(acked.len())
+    }
 
-        if let Some(state) = rsk_pegin_active {
-            if state.active {
-                monitors.push(TypesToMonitorStore::RskPegin(state.confirmation_trigger));
-            }
+    /// Acknowledges the news of every key, see `ack_all_news`.
+    fn ack_all_news_in(
+        &self,
+        block_news: &mut [NewBlockNewsEntry],
+        transaction_id: Uuid,
+    ) -> Result<usize, MonitorStoreError> {
+        let tx = transaction_id;
+        let mut acked = 0;
+
+        acked +=
+            self.ack_all_listed_news::<TransactionNewsEntry>(MonitorKey::TransactionsNews, tx)?;
+        acked += self.ack_all_listed_news::<TransactionNewsEntry>(
+            MonitorKey::OrphanedTransactionsNews,
+            tx,
+        )?;
+        acked += self
+            .ack_all_listed_news::<RskPeginNewsEntry>(MonitorKey::RskPeginTransactionsNews, tx)?;
+        for key in [
+            MonitorKey::SpendingUTXOTransactionsNews,
+            MonitorKey::MinorSpendNews,
+            MonitorKey::SpendingUTXOUnconfirmedNews,
+        ] {
+            acked += self.ack_all_listed_news::<SpendingUTXONewsEntry>(key, tx)?;
         }
+        acked +=
+            self.ack_all_listed_news::<ReplacementNewsEntry>(MonitorKey::ReplacementNews, tx)?;
+        acked +=
+            self.ack_all_listed_news::<ScriptPubKeyNewsEntry>(MonitorKey::ScriptPubKeyNews, tx)?;
+        acked += self.ack_all_listed_news::<OpReturnNewsEntry>(MonitorKey::OpReturnNews, tx)?;
+        acked +=
+            self.ack_all_listed_news::<AlternativeNewsEntry>(MonitorKey::AlternativeNews, tx)?;
+        acked += self
+            .ack_all_listed_news::<ExpiredMonitorNewsEntry>(MonitorKey::ExpiredMonitorsNews, tx)?;
+        acked += self.ack_all_listed_news::<ManualResolutionNewsEntry>(
+            MonitorKey::ManualResolutionNews,
+            tx,
+        )?;
+        acked += self.ack_all_listed_news::<CompositeNewsEntry>(MonitorKey::CompositeNews, tx)?;
 
-        // Get active spending UTXO transactions from list
-        let spending_utxo_key = self.get_key(MonitorKey::SpendingUTXOTransactions(true));
-        let spending_utxos: Vec<SpendingUTXOMonitor> =
-            self.store.get(&spending_utxo_key)?.unwrap_or_default();
+        acked += self.ack_all_single_news::<EpochNewsEntry>(MonitorKey::EpochNews, tx)?;
+        acked += self.ack_all_single_news::<ReorgNewsEntry>(MonitorKey::ReorgNews, tx)?;
+        acked +=
+            self.ack_all_single_news::<LowWorkReorgNewsEntry>(MonitorKey::LowWorkReorgNews, tx)?;
+        acked += self.ack_all_single_news::<HealthNewsEntry>(MonitorKey::HealthNews, tx)?;
 
-        for monitor in spending_utxos {
-            for entry in monitor.entries {
-                monitors.push(TypesToMonitorStore::SpendingUTXOTransaction(
+        let blocks = Self::mark_all_acked(block_news);
+        if !blocks.is_empty() {
+            self.write_typed(
+                &self.get_key(MonitorKey::NewBlockNews),
+                &*block_news,
+                Some(tx),
+            )?;
+            self.record_news_acks(&blocks, Some(tx))?;
+        }
+
+        Ok(acked + blocks.len())
+    }
+
+    /// Prunes the store in the store transaction `transaction_id`, see `prune`.
+    fn prune_in(
+        &self,
+        older_than_height: BlockHeight,
+        transaction_id: Uuid,
+    ) -> Result<PruneReport, MonitorStoreError> {
+        let tx = transaction_id;
+        let mut report = PruneReport::default();
+
+        let mut spending_utxos: Listed<SpendingUTXOMonitor> =
+            self.read_listed(MonitorKey::SpendingUTXOTransactions(false))?;
+        let mut spends: HashMap<Txid, Vec<(Txid, u32)>> = HashMap::new();
+        let mut spender_contexts = HashSet::new();
+        let mut pruned_outpoints = vec![];
+
+        for monitor in spending_utxos.iter_mut() {
+            let (pruned, kept): (Vec<_>, Vec<_>) = monitor
+                .entries
+                .drain(..)
+                .partition(|e| MonitorEvent::last_activity(&e.history) < older_than_height);
+            monitor.entries = kept;
+
+            for entry in &pruned {
+                spender_contexts.insert(build_spending_utxo_context(
                     monitor.tx_id,
                     monitor.vout,
-                    entry.extra_data,
-                    entry.confirmation_trigger,
+                    &entry.extra_data,
                 ));
+                for report_minor in [true, false] {
+                    spender_contexts.insert(build_minor_spend_context(
+                        monitor.tx_id,
+                        monitor.vout,
+                        &entry.extra_data,
+                        report_minor,
+                    ));
+                }
+                if let Some(spender) = entry.spender_tx_id {
+                    spends
+                        .entry(spender)
+                        .or_default()
+                        .push((monitor.tx_id, monitor.vout));
+                }
+            }
+
+            report.spending_utxos += pruned.len() as u32;
+            if !pruned.is_empty() && monitor.entries.is_empty() {
+                pruned_outpoints.push(OutPoint::new(monitor.tx_id, monitor.vout).to_string());
             }
         }
 
-        // Get new block monitor
-        let new_block_key = self.get_key(MonitorKey::NewBlock);
-        let monitor_new_block = self
-            .store
-            .get::<_, bool>(&new_block_key)?
-            .unwrap_or_default();
+        if report.spending_utxos > 0 {
+            self.write_listed(&spending_utxos, Some(tx))?;
+        }
 
-        if monitor_new_block {
-            monitors.push(TypesToMonitorStore::NewBlock);
+        // The spend stays indexed while an active monitor follows the output
+        let active: Vec<SpendingUTXOMonitor> = self.read_items(
+            MonitorKey::SpendingUTXOTransactions(true),
+            &pruned_outpoints,
+        )?;
+        for (spender, outpoints) in spends.iter_mut() {
+            outpoints.retain(|(tx_id, vout)| {
+                pruned_outpoints.contains(&OutPoint::new(*tx_id, *vout).to_string())
+                    && !active.iter().any(|m| m.tx_id == *tx_id && m.vout == *vout)
+            });
+            if !outpoints.is_empty() {
+                self.unindex_spends(*spender, outpoints, Some(tx))?;
+            }
         }
 
-        Ok(monitors)
+        // Internal monitors keep no history of their own, the ones following a spender go with
+        // their SpendingUTXO monitor and the others are left
+        let pruned = self.remove_transaction_entries(
+            false,
+            None,
+            |_, entry| {
+                if is_internal_context(&entry.extra_data) {
+                    spender_contexts.contains(&entry.extra_data)
+                } else {
+                    MonitorEvent::last_activity(&entry.history) < older_than_height
+                }
+            },
+            Some(tx),
+        )?;
+        report.transactions = pruned
+            .iter()
+            .flat_map(|(_, monitor)| &monitor.entries)
+            .filter(|entry| !is_internal_context(&entry.extra_data))
+            .count() as u32;
+        report.tx_snapshots = self.prune_tx_snapshots(&pruned, tx)?;
+
+        let news = &mut report.news;
+        *news += self.prune_listed_news::<TransactionNewsEntry>(
+            MonitorKey::TransactionsNews,
+            older_than_height,
+            tx,
+        )?;
+        *news += self.prune_listed_news::<TransactionNewsEntry>(
+            MonitorKey::OrphanedTransactionsNews,
+            older_than_height,
+            tx,
+        )?;
+        *news += self.prune_listed_news::<RskPeginNewsEntry>(
+            MonitorKey::RskPeginTransactionsNews,
+            older_than_height,
+            tx,
+        )?;
+        for key in [
+            MonitorKey::SpendingUTXOTransactionsNews,
+            MonitorKey::MinorSpendNews,
+            MonitorKey::SpendingUTXOUnconfirmedNews,
+        ] {
+            *news += self.prune_listed_news::<SpendingUTXONewsEntry>(key, older_than_height, tx)?;
+        }
+        *news += self.prune_listed_news::<ReplacementNewsEntry>(
+            MonitorKey::ReplacementNews,
+            older_than_height,
+            tx,
+        )?;
+        *news += self.prune_listed_news::<ScriptPubKeyNewsEntry>(
+            MonitorKey::ScriptPubKeyNews,
+            older_than_height,
+            tx,
+        )?;
+        *news += self.prune_listed_news::<OpReturnNewsEntry>(
+            MonitorKey::OpReturnNews,
+            older_than_height,
+            tx,
+        )?;
+        *news += self.prune_listed_news::<AlternativeNewsEntry>(
+            MonitorKey::AlternativeNews,
+            older_than_height,
+            tx,
+        )?;
+        *news += self.prune_listed_news::<ExpiredMonitorNewsEntry>(
+            MonitorKey::ExpiredMonitorsNews,
+            older_than_height,
+            tx,
+        )?;
+        *news += self.prune_listed_news::<ManualResolutionNewsEntry>(
+            MonitorKey::ManualResolutionNews,
+            older_than_height,
+            tx,
+        )?;
+        *news += self.prune_listed_news::<CompositeNewsEntry>(
+            MonitorKey::CompositeNews,
+            older_than_height,
+            tx,
+        )?;
+        *news += self.prune_listed_news::<NewBlockNewsEntry>(
+            MonitorKey::NewBlockNews,
+            older_than_height,
+            tx,
+        )?;
+
+        self.prune_fingerprints(older_than_height, tx)?;
+
+        Ok(report)
     }
 
-    fn add_monitor(&self, data: TypesToMonitor) -> Result<(), MonitorStoreError> {
-        match data {
-            TypesToMonitor::Transactions(tx_ids, extra_data, from) => {
-                let key = self.get_key(MonitorKey::Transactions(true));
-                let mut txs: Vec<TransactionMonitor> = self.store.get(&key)?.unwrap_or_default();
+    /// Drops the snapshots of the transactions no transaction monitor follows anymore, either
+    /// because `pruned` removed the last entries of their monitor or because it was removed
+    /// before. Reads don't see the writes of the store transaction, so the monitors emptied by
+    /// `pruned` are still listed and are told apart by their number of entries.
+    fn prune_tx_snapshots(
+        &self,
+        pruned: &[(bool, TransactionMonitor)],
+        transaction_id: Uuid,
+    ) -> Result<u32, MonitorStoreError> {
+        let ids_key = self.get_key(MonitorKey::TransactionSnapshotIds);
+        let Some(ids) = self.read_typed::<Vec<Txid>>(&ids_key)? else {
+            return Ok(0);
+        };
 
-                for txid in &tx_ids {
-                    if let Some(monitor) = txs.iter_mut().find(|m| m.tx_id == *txid) {
-                        // If tx exists and extra_data is the same, override Option<u32> and move trigger sent in false
-                        if let Some(pos) = monitor
-                            .entries
-                            .iter()
-                            .position(|e| e.extra_data == extra_data)
-                        {
-                            monitor.entries[pos] = TransactionMonitorEntry {
-                                extra_data: extra_data.clone(),
-                                confirmation_trigger: from,
-                                trigger_sent: false,
-                            };
-                        } else {
-                            // If extra_data is different, add it as a new tx_id-to-monitor entry
-                            monitor.entries.push(TransactionMonitorEntry {
-                                extra_data: extra_data.clone(),
-                                confirmation_trigger: from,
-                                trigger_sent: false,
-                            });
-                        }
-                    } else {
-                        // New txid, store it with its first (extra_data, trigger) entry
-                        txs.push(TransactionMonitor {
-                            tx_id: *txid,
-                            entries: vec![TransactionMonitorEntry {
-                                extra_data: extra_data.clone(),
-                                confirmation_trigger: from,
-                                trigger_sent: false,
-                            }],
-                        });
-                    }
-                }
+        let pruned_entries: HashMap<String, usize> = pruned
+            .iter()
+            .map(|(_, monitor)| (monitor.item_id(), monitor.entries.len()))
+            .collect();
+        let pruned_ids: Vec<String> = pruned_entries.keys().cloned().collect();
+        let emptied: HashSet<String> = self
+            .read_items::<TransactionMonitor>(MonitorKey::Transactions(false), &pruned_ids)?
+            .into_iter()
+            .filter(|monitor| {
+                pruned_entries.get(&monitor.item_id()) == Some(&monitor.entries.len())
+            })
+            .map(|monitor| monitor.item_id())
+            .collect();
+
+        let mut monitored = HashSet::new();
+        for is_active in [true, false] {
+            monitored.extend(
+                self.read_list_index::<TransactionMonitor>(MonitorKey::Transactions(is_active))?,
+            );
+        }
+
+        let (kept, dropped): (Vec<Txid>, Vec<Txid>) = ids.into_iter().partition(|tx_id| {
+            let id = tx_id.to_string();
+            monitored.contains(&id) && !emptied.contains(&id)
+        });
+
+        if dropped.is_empty() {
+            return Ok(0);
+        }
+
+        for tx_id in &dropped {
+            self.delete_key(&self.tx_snapshot_key(tx_id), Some(transaction_id))?;
+        }
+        if kept.is_empty() {
+            self.delete_key(&ids_key, Some(transaction_id))?;
+        } else {
+            self.write_typed(&ids_key, &kept, Some(transaction_id))?;
+        }
 
-                self.store.set(&key, &txs, None)?;
+        Ok(dropped.len() as u32)
+    }
+
+    /// Drops the acknowledged news of `key` written below `written_before`. The height a news was
+    /// written at is read from the news history, a news no longer in it was written before.
+    fn prune_listed_news<E: NewsEntry>(
+        &self,
+        key: MonitorKey,
+        written_before: BlockHeight,
+        transaction_id: Uuid,
+    ) -> Result<u32, MonitorStoreError> {
+        let key = self.get_key(key);
+        let entries: Vec<E> = self.read_typed(&key)?.unwrap_or_default();
+        let len = entries.len();
+
+        let mut kept = Vec::with_capacity(len);
+        for mut entry in entries {
+            let ack = entry.ack_mut();
+            let written_at = self
+                .read_typed::<NewsHistoryEntry>(
+                    &self.get_key(MonitorKey::NewsHistory(ack.sequence)),
+                )?
+                .map(|history| history.height);
+            if !ack.acknowledged || written_at.is_some_and(|height| height >= written_before) {
+                kept.push(entry);
             }
-            TypesToMonitor::RskPegin(from) => {
-                let key = self.get_key(MonitorKey::RskPegin);
-                self.store.set(
-                    &key,
-                    RskPeginMonitorState {
-                        active: true,
-                        confirmation_trigger: from,
-                    },
-                    None,
-                )?;
+        }
+
+        if kept.len() != len {
+            self.write_typed(&key, &kept, Some(transaction_id))?;
+        }
+
+        Ok((len - kept.len()) as u32)
+    }
+
+    /// Acknowledgment of a news written now, it takes the next position in the news sequence.
+    /// The news is recorded in the news history at `height`.
+    fn new_news_ack(
+        &self,
+        news: &MonitoredTypes,
+        block_hash: BlockHash,
+        height: BlockHeight,
+    ) -> Result<NewsAck, MonitorStoreError> {
+        let key = self.get_key(MonitorKey::NewsSequence);
+        let sequence = self.read_typed::<u64>(&key)?.unwrap_or_default() + 1;
+        self.write_typed(&key, sequence, None)?;
+
+        let entry = NewsHistoryEntry {
+            sequence,
+            news: news.clone(),
+            block_hash,
+            height,
+            created_at_unix: unix_now(),
+            acked: None,
+        };
+        self.write_typed(
+            &self.get_key(MonitorKey::NewsHistory(sequence)),
+            entry,
+            None,
+        )?;
+
+        Ok(NewsAck {
+            block_hash,
+            acknowledged: false,
+            sequence,
+            metadata: None,
+        })
+    }
+
+    /// Counts the unacknowledged news, up to `limit`, without building them.
+    fn count_pending_news(&self, limit: usize) -> Result<usize, MonitorStoreError> {
+        let lists = [
+            MonitorKey::TransactionsNews,
+            MonitorKey::OrphanedTransactionsNews,
+            MonitorKey::RskPeginTransactionsNews,
+            MonitorKey::SpendingUTXOTransactionsNews,
+            MonitorKey::MinorSpendNews,
+            MonitorKey::SpendingUTXOUnconfirmedNews,
+            MonitorKey::ReplacementNews,
+            MonitorKey::ScriptPubKeyNews,
+            MonitorKey::OpReturnNews,
+            MonitorKey::AlternativeNews,
+            MonitorKey::ExpiredMonitorsNews,
+            MonitorKey::ManualResolutionNews,
+            MonitorKey::CompositeNews,
+        ];
+        let singles = [
+            MonitorKey::EpochNews,
+            MonitorKey::ReorgNews,
+            MonitorKey::LowWorkReorgNews,
+            MonitorKey::HealthNews,
+        ];
+
+        let mut count = 0;
+
+        for list in lists {
+            let entries: Vec<NewsAckView> =
+                self.read_typed(&self.get_key(list))?.unwrap_or_default();
+            count += entries
+                .iter()
+                .filter(|entry| !entry.ack.acknowledged)
+                .count();
+            if count >= limit {
+                return Ok(limit);
+            }
+        }
+
+        for single in singles {
+            let entry: Option<NewsAckView> = self.read_typed(&self.get_key(single))?;
+            count += entry.is_some_and(|entry| !entry.ack.acknowledged) as usize;
+            if count >= limit {
+                return Ok(limit);
+            }
+        }
+
+        count += self
+            .read_block_news()?
+            .iter()
+            .filter(|entry| !entry.ack.acknowledged)
+            .count();
+
+        Ok(count.min(limit))
+    }
+
+    /// Highest sequence of the stored news, acknowledged or not.
+    fn max_news_sequence(&self) -> Result<u64, MonitorStoreError> {
+        let news = self.get_news_acks()?;
+        Ok(news
+            .iter()
+            .map(|(_, ack)| ack.sequence)
+            .max()
+            .unwrap_or_default())
+    }
+
+    /// Monitors of the active or the inactive lists. OP_RETURN prefix monitors are removed when
+    /// deactivated, so they are only listed as active.
+    fn list_monitors(&self, active: bool) -> Result<Vec<MonitorInfo>, MonitorStoreError> {
+        let mut monitors = Vec::<MonitorInfo>::new();
+        let info = |monitor: TypesToMonitorStore, history: Vec<MonitorEvent>| MonitorInfo {
+            monitor,
+            active,
+            spender_tx_id: None,
+            history,
+            metadata: BTreeMap::new(),
+        };
+
+        let txs: Vec<TransactionMonitor> = self.read_list(MonitorKey::Transactions(active))?;
+
+        for monitor in txs {
+            for entry in monitor.entries {
+                monitors.push(info(
+                    TypesToMonitorStore::Transaction(
+                        monitor.tx_id,
+                        entry.extra_data,
+                        entry.confirmation_trigger,
+                        entry.expires_at_height,
+                    ),
+                    entry.history,
+                ));
+            }
+        }
+
+        for (monitor, history) in self.read_unlisted(MonitorKind::RskPegin, active)? {
+            monitors.push(info(monitor, history));
+        }
+
+        let spending_utxos: Vec<SpendingUTXOMonitor> =
+            self.read_list(MonitorKey::SpendingUTXOTransactions(active))?;
+
+        for monitor in spending_utxos {
+            for entry in monitor.entries {
+                monitors.push(MonitorInfo {
+                    spender_tx_id: entry.spender_tx_id,
+                    ..info(
+                        TypesToMonitorStore::SpendingUTXOTransaction(
+                            monitor.tx_id,
+                            monitor.vout,
+                            entry.extra_data,
+                            entry.confirmation_trigger,
+                            entry.deadline,
+                            entry.value_gate,
+                            entry.expires_at_height,
+                        ),
+                        entry.history,
+                    )
+                });
+            }
+        }
+
+        let scripts: Vec<ScriptPubKeyMonitor> =
+            self.read_list(MonitorKey::ScriptPubKeys(active))?;
+
+        for monitor in scripts {
+            for entry in monitor.entries {
+                monitors.push(info(
+                    TypesToMonitorStore::ScriptPubKey(
+                        monitor.script_pubkey.clone(),
+                        entry.extra_data,
+                        entry.confirmation_trigger,
+                        entry.filter,
+                    ),
+                    entry.history,
+                ));
+            }
+        }
+
+        for kind in [MonitorKind::OpReturnPrefix, MonitorKind::NewBlock] {
+            for (monitor, history) in self.read_unlisted(kind, active)? {
+                monitors.push(info(monitor, history));
+            }
+        }
+
+        Ok(monitors)
+    }
+
+    /// Monitors of a kind kept under a single key (new block, pegin and OP_RETURN prefixes) in the
+    /// active or the inactive list, with their history.
+    fn read_unlisted(
+        &self,
+        kind: MonitorKind,
+        active: bool,
+    ) -> Result<Vec<(TypesToMonitorStore, Vec<MonitorEvent>)>, MonitorStoreError> {
+        let mut monitors = vec![];
+
+        match kind {
+            MonitorKind::RskPegin => {
+                let rsk_pegin_key = self.get_key(MonitorKey::RskPegin);
+                let rsk_pegin: Option<RskPeginMonitorState> = self.read_typed(&rsk_pegin_key)?;
+
+                let listed = if active {
+                    RskPeginMonitorStatus::Active
+                } else {
+                    RskPeginMonitorStatus::Inactive
+                };
+                if let Some(state) = rsk_pegin.filter(|state| state.status == listed) {
+                    monitors.push((
+                        TypesToMonitorStore::RskPegin(
+                            state.confirmation_trigger,
+                            state.committee,
+                            state.packet_range,
+                            state.min_amount,
+                        ),
+                        vec![],
+                    ));
+                }
+            }
+            MonitorKind::OpReturnPrefix if active => {
+                let op_return_key = self.get_key(MonitorKey::OpReturnPrefixes);
+                let op_return_prefixes: Vec<OpReturnPrefixMonitor> =
+                    self.read_typed(&op_return_key)?.unwrap_or_default();
+
+                for monitor in op_return_prefixes {
+                    monitors.push((
+                        TypesToMonitorStore::OpReturnPrefix(monitor.prefix, monitor.extra_data),
+                        monitor.history,
+                    ));
+                }
+            }
+            MonitorKind::NewBlock => {
+                // The new block monitor is inactive once deactivated, not when it was never added
+                let new_block_key = self.get_key(MonitorKey::NewBlock);
+                if self.read_typed::<bool>(&new_block_key)? == Some(active) {
+                    monitors.push((TypesToMonitorStore::NewBlock, vec![]));
+                }
+            }
+            _ => {}
+        }
+
+        Ok(monitors)
+    }
+
+    /// Cursor over the items of the active list `list`, sorted by target.
+    fn listed_cursor<M: ListedMonitor>(
+        &self,
+        kind: MonitorKind,
+        list: MonitorKey,
+    ) -> Result<MonitorCursor, MonitorStoreError> {
+        let mut ids = self.read_list_index::<M>(list)?;
+        ids.sort_by_cached_key(|id| Reverse(M::canonical_target(id)));
+
+        Ok(MonitorCursor { kind, ids })
+    }
+
+    /// Monitors of the items `ids` of the active list `list`, in the canonical order.
+    fn read_listed_page<M: ListedMonitor>(
+        &self,
+        list: MonitorKey,
+        ids: Vec<String>,
+    ) -> Result<Vec<TypesToMonitorStore>, MonitorStoreError> {
+        let mut monitors = vec![];
+
+        for monitor in self.read_items::<M>(list, &ids)? {
+            let mut item = monitor.into_monitors();
+            item.sort_by(TypesToMonitorStore::canonical_cmp);
+            // A context stored twice by an older version would be evaluated, and reported, twice
+            item.dedup_by(|a, b| a.canonical_cmp(b) == Ordering::Equal);
+            monitors.extend(item);
+        }
+
+        Ok(monitors)
+    }
+
+    /// Reads the value stored under `key` as a `V`, decompressing it if it was written
+    /// compressed. A value that can't be read as a `V` is reported as a
+    /// [`MonitorStoreError::CorruptedEntry`] naming the key and the type.
+    pub(crate) fn read_typed<V: DeserializeOwned>(
+        &self,
+        key: &str,
+    ) -> Result<Option<V>, MonitorStoreError> {
+        let value = if self.is_compressible(key) {
+            self.store
+                .get::<_, Decompressed<V>>(key)
+                .map(|value| value.map(|Decompressed(value)| value))
+        } else {
+            self.store.get(key)
+        };
+
+        value.map_err(|source| MonitorStoreError::CorruptedEntry {
+            key: key.to_string(),
+            expected_type: std::any::type_name::<V>(),
+            source,
+        })
+    }
+
+    /// Writes `value` under `key`, compressed if the key is compressible and the value large
+    /// enough, see [`crate::compression::COMPRESSION_THRESHOLD`]. Failures are reported as a
+    /// [`MonitorStoreError::WriteFailed`] naming the key.
+    pub(crate) fn write_typed<V: Serialize>(
+        &self,
+        key: &str,
+        value: V,
+        transaction_id: Option<Uuid>,
+    ) -> Result<(), MonitorStoreError> {
+        let written = if self.is_compressible(key) {
+            let value = Compressible {
+                value: &value,
+                counters: &self.compression,
+            };
+            self.store.set(key, value, transaction_id)
+        } else {
+            self.store.set(key, value, transaction_id)
+        };
+
+        written.map_err(|source| MonitorStoreError::WriteFailed {
+            key: key.to_string(),
+            source,
+        })
+    }
+
+    /// Transaction snapshots, holding the raw transactions, are written compressed, and so was
+    /// the single key they were kept under before schema version 4.
+    fn is_compressible(&self, key: &str) -> bool {
+        let snapshots = self.get_key(MonitorKey::TransactionSnapshots);
+        key.strip_prefix(snapshots.as_str())
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with("/id/"))
+    }
+
+    pub(crate) fn delete_key(
+        &self,
+        key: &str,
+        transaction_id: Option<Uuid>,
+    ) -> Result<(), MonitorStoreError> {
+        match transaction_id {
+            Some(transaction_id) => self.store.transactional_delete(key, transaction_id)?,
+            None => self.store.delete(key)?,
+        }
+        Ok(())
+    }
+
+    /// Monitors of `list`, in the order they were added.
+    pub(crate) fn read_list<M: ListedMonitor>(
+        &self,
+        list: MonitorKey,
+    ) -> Result<Vec<M>, MonitorStoreError> {
+        let index = self.read_list_index::<M>(list)?;
+        self.read_items(list, &index)
+    }
+
+    /// Reads the monitors of `list` stored under the ids `ids`, skipping the missing ones.
+    fn read_items<M: ListedMonitor>(
+        &self,
+        list: MonitorKey,
+        ids: &[String],
+    ) -> Result<Vec<M>, MonitorStoreError> {
+        let mut read = Vec::with_capacity(ids.len());
+
+        for id in ids {
+            if let Some(monitor) = self.read_typed(&self.get_listed_key(list, id))? {
+                read.push(monitor);
+            }
+        }
+
+        Ok(read)
+    }
+
+    /// Reads every monitor of `list` to be changed and written back with `write_listed`.
+    fn read_listed<M: ListedMonitor>(
+        &self,
+        list: MonitorKey,
+    ) -> Result<Listed<M>, MonitorStoreError> {
+        let index = self.read_list_index::<M>(list)?;
+        let read: Vec<M> = self.read_items(list, &index)?;
+
+        Ok(Listed {
+            list,
+            index,
+            monitors: read.clone(),
+            read,
+            complete: true,
+        })
+    }
+
+    /// Reads the monitors of `list` with the ids `ids`, skipping the ones not in the list, to be
+    /// changed and written back with `write_listed`. Monitors pushed to it are added to the list.
+    fn read_listed_items<M: ListedMonitor>(
+        &self,
+        list: MonitorKey,
+        ids: impl IntoIterator<Item = String>,
+    ) -> Result<Listed<M>, MonitorStoreError> {
+        let index = self.read_list_index::<M>(list)?;
+        let listed: HashSet<&String> = index.iter().collect();
+        let mut seen = HashSet::new();
+        let mut read = vec![];
+
+        for id in ids {
+            if !listed.contains(&id) || !seen.insert(id.clone()) {
+                continue;
+            }
+            if let Some(monitor) = self.read_typed(&self.get_listed_key(list, &id))? {
+                read.push(monitor);
+            }
+        }
+
+        Ok(Listed {
+            list,
+            index,
+            monitors: read.clone(),
+            read,
+            complete: false,
+        })
+    }
+
+    /// Writes back the monitors read with `read_listed` or `read_listed_items`. Only the monitors
+    /// that changed are written, the ones left without entries or no longer in the list are
+    /// removed, and the index is written if monitors were added or removed.
+    ///
+    /// The index is the one read, so a list must be written once per store transaction. Adding
+    /// or removing a script of a ScriptPubKey list moves the scan generation.
+    fn write_listed<M: ListedMonitor>(
+        &self,
+        listed: &Listed<M>,
+        transaction_id: Option<Uuid>,
+    ) -> Result<(), MonitorStoreError> {
+        let read: HashMap<String, &M> = listed.read.iter().map(|m| (m.item_id(), m)).collect();
+        let mut kept_ids = vec![];
+
+        for monitor in listed.monitors.iter().filter(|m| m.has_entries()) {
+            let id = monitor.item_id();
+            if read.get(&id) != Some(&monitor) {
+                let key = self.get_listed_key(listed.list, &id);
+                self.write_typed(&key, monitor, transaction_id)?;
+            }
+            kept_ids.push(id);
+        }
+
+        let kept: HashSet<&String> = kept_ids.iter().collect();
+        for id in read.keys().filter(|id| !kept.contains(id)) {
+            self.delete_key(&self.get_listed_key(listed.list, id), transaction_id)?;
+        }
+
+        // A complete list is written in its order, monitors removed and added again go last
+        let index: Vec<String> = if listed.complete {
+            kept_ids.clone()
+        } else {
+            let indexed: HashSet<&String> = listed.index.iter().collect();
+            listed
+                .index
+                .iter()
+                .filter(|id| !read.contains_key(*id) || kept.contains(id))
+                .chain(kept_ids.iter().filter(|id| !indexed.contains(id)))
+                .cloned()
+                .collect()
+        };
+
+        if index != listed.index {
+            self.write_typed(&self.get_key(listed.list), &index, transaction_id)?;
+
+            if matches!(listed.list, MonitorKey::ScriptPubKeys(_)) {
+                let key = self.get_key(MonitorKey::ScanGeneration);
+                let generation: u64 = self.read_typed(&key)?.unwrap_or_default();
+                self.write_typed(&key, generation + 1, transaction_id)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Ids of the monitors of `list`. A list stored by older versions as a single `Vec` is moved
+    /// to per-item keys, in one store transaction, the first time it is read.
+    fn read_list_index<M: ListedMonitor>(
+        &self,
+        list: MonitorKey,
+    ) -> Result<Vec<String>, MonitorStoreError> {
+        if let Some(index) = self.read_typed(&self.get_key(list))? {
+            return Ok(index);
+        }
+
+        let transaction_id = self.store.begin_transaction();
+
+        match self.move_legacy_list::<M>(list, transaction_id) {
+            Ok(index) => {
+                self.store.commit_transaction(transaction_id)?;
+                Ok(index)
+            }
+            Err(e) => {
+                self.store.rollback_transaction(transaction_id)?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Moves `list`, stored by older versions as a single `Vec`, to per-item keys in the store
+    /// transaction `transaction_id`. Returns the ids of the moved monitors, none if the list is
+    /// not stored as a `Vec`.
+    pub(crate) fn move_legacy_list<M: ListedMonitor>(
+        &self,
+        list: MonitorKey,
+        transaction_id: Uuid,
+    ) -> Result<Vec<String>, MonitorStoreError> {
+        let Some(legacy_key) = self.get_legacy_list_key(list) else {
+            return Ok(vec![]);
+        };
+        let Some(monitors) = self.read_typed::<Vec<M>>(&legacy_key)? else {
+            return Ok(vec![]);
+        };
+
+        let (monitors, duplicates) = Self::dedupe_listed(monitors);
+        if duplicates > 0 {
+            warn!(
+                "Dropped {} duplicate monitors moving {} to per-item keys",
+                duplicates, legacy_key
+            );
+        }
+
+        let index: Vec<String> = monitors.iter().map(|m| m.item_id()).collect();
+
+        for monitor in &monitors {
+            let key = self.get_listed_key(list, &monitor.item_id());
+            self.write_typed(&key, monitor, Some(transaction_id))?;
+        }
+        self.write_typed(&self.get_key(list), &index, Some(transaction_id))?;
+        self.delete_key(&legacy_key, Some(transaction_id))?;
+
+        Ok(index)
+    }
+
+    /// Merges the monitors stored more than once with the same id, and drops the entries stored
+    /// more than once with the same context, keeping the first one. Older versions could store
+    /// a txid registered twice in the same call twice, sending its news twice. Returns the
+    /// monitors and how many entries were dropped.
+    fn dedupe_listed<M: ListedMonitor>(monitors: Vec<M>) -> (Vec<M>, usize) {
+        let mut merged: Vec<M> = Vec::with_capacity(monitors.len());
+        let mut positions: HashMap<String, usize> = HashMap::new();
+
+        for mut monitor in monitors {
+            match positions.get(&monitor.item_id()) {
+                Some(&pos) => {
+                    let entries = std::mem::take(monitor.entries());
+                    merged[pos].entries().extend(entries);
+                }
+                None => {
+                    positions.insert(monitor.item_id(), merged.len());
+                    merged.push(monitor);
+                }
+            }
+        }
+
+        let mut dropped = 0;
+        for monitor in merged.iter_mut() {
+            let entries = monitor.entries();
+            let before = entries.len();
+            let mut contexts = HashSet::new();
+            entries.retain(|entry| contexts.insert(M::context(entry).to_string()));
+            dropped += before - entries.len();
+        }
+
+        (merged, dropped)
+    }
+
+    /// News of the new blocks, oldest first. The single news stored by older versions, which only
+    /// kept the latest block, is moved to the queue the first time it is read.
+    pub(crate) fn read_block_news(&self) -> Result<Vec<NewBlockNewsEntry>, MonitorStoreError> {
+        if let Some(queue) = self.read_typed(&self.get_key(MonitorKey::NewBlockNews))? {
+            return Ok(queue);
+        }
+
+        let transaction_id = self.store.begin_transaction();
+
+        match self.move_legacy_block_news(transaction_id) {
+            Ok(queue) => {
+                self.store.commit_transaction(transaction_id)?;
+                Ok(queue)
+            }
+            Err(e) => {
+                self.store.rollback_transaction(transaction_id)?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Moves the single new block news stored by older versions to the queue in the store
+    /// transaction `transaction_id`. Returns the queue, empty if there was no such news.
+    pub(crate) fn move_legacy_block_news(
+        &self,
+        transaction_id: Uuid,
+    ) -> Result<Vec<NewBlockNewsEntry>, MonitorStoreError> {
+        let legacy_key = self.get_legacy_block_news_key();
+        let Some(ack) = self.read_typed::<NewsAck>(&legacy_key)? else {
+            return Ok(vec![]);
+        };
+
+        let queue = vec![NewBlockNewsEntry { height: None, ack }];
+        self.write_typed(
+            &self.get_key(MonitorKey::NewBlockNews),
+            &queue,
+            Some(transaction_id),
+        )?;
+        self.delete_key(&legacy_key, Some(transaction_id))?;
+
+        Ok(queue)
+    }
+
+    /// Reads the value stored under `key`, e.g. one written by [`Self::set_with_expiry`].
+    pub fn get_value<V: DeserializeOwned>(
+        &self,
+        key: &str,
+    ) -> Result<Option<V>, MonitorStoreError> {
+        self.read_typed(key)
+    }
+
+    /// Writes `value` under `key` and schedules the key to be removed at `expires_at`.
+    pub fn set_with_expiry<V: Serialize>(
+        &self,
+        key: &str,
+        value: V,
+        expires_at: BlockHeight,
+    ) -> Result<(), MonitorStoreError> {
+        self.write_typed(key, value, None)?;
+        self.set_expiry(key, expires_at)
+    }
+
+    /// Family and status of the keys of `list`, None if it is not a list of monitors.
+    fn list_key_parts(list: MonitorKey) -> Option<(&'static str, &'static str)> {
+        let (family, is_active) = match list {
+            MonitorKey::Transactions(is_active) => ("tx", is_active),
+            MonitorKey::SpendingUTXOTransactions(is_active) => ("spending/utxo/tx", is_active),
+            MonitorKey::ScriptPubKeys(is_active) => ("script", is_active),
+            _ => return None,
+        };
+        Some((family, if is_active { "active" } else { "inactive" }))
+    }
+
+    /// Key of the monitor `id` of `list`, a list of active or inactive monitors.
+    pub(crate) fn get_listed_key(&self, list: MonitorKey, id: &str) -> String {
+        let (family, status) = Self::list_key_parts(list).expect("not a list of monitors");
+        format!("monitor/{family}/{status}/{id}")
+    }
+
+    /// Key `list` was stored under as a single `Vec`, before each monitor had its own key.
+    fn get_legacy_list_key(&self, list: MonitorKey) -> Option<String> {
+        let (family, status) = Self::list_key_parts(list)?;
+        Some(format!("monitor/{family}/list/{status}"))
+    }
+
+    /// Key of the single new block news, before each pending block had its own news.
+    fn get_legacy_block_news_key(&self) -> String {
+        "monitor/new/block/news".to_string()
+    }
+
+    pub(crate) fn get_key(&self, key: MonitorKey) -> String {
+        let prefix = "monitor";
+        match key {
+            MonitorKey::Transactions(is_active) => format!(
+                "{prefix}/tx/index/{status}",
+                status = if is_active { "active" } else { "inactive" }
+            ),
+            MonitorKey::SpendingUTXOTransactions(is_active) => format!(
+                "{prefix}/spending/utxo/tx/index/{status}",
+                status = if is_active { "active" } else { "inactive" }
+            ),
+            MonitorKey::PendingWork => format!("{prefix}/all/pending_work"),
+            MonitorKey::ReloadRequest => format!("{prefix}/all/reload_request"),
+            MonitorKey::Network => format!("{prefix}/all/network"),
+            MonitorKey::WriteProbe => format!("{prefix}/all/write_probe"),
+            MonitorKey::RskPegin => format!("{prefix}/rsk/pegin"),
+            MonitorKey::NewBlock => format!("{prefix}/new/block"),
+            MonitorKey::TransactionsNews => format!("{prefix}/tx/news"),
+            MonitorKey::OrphanedTransactionsNews => format!("{prefix}/tx/orphaned/news"),
+            MonitorKey::RskPeginTransactionsNews => format!("{prefix}/rsk/tx/news"),
+            MonitorKey::SpendingUTXOTransactionsNews => {
+                format!("{prefix}/spending/utxo/tx/news")
+            }
+            MonitorKey::SpendingUTXOUnconfirmedNews => {
+                format!("{prefix}/spending/utxo/unconfirmed/news")
+            }
+            MonitorKey::MinorSpendNews => format!("{prefix}/spending/utxo/minor/news"),
+            MonitorKey::NewBlockNews => format!("{prefix}/new/block/news/queue"),
+            MonitorKey::Fingerprints => format!("{prefix}/fingerprints"),
+            MonitorKey::FingerprintsAt(height) => format!("{prefix}/fingerprints/at/{height}"),
+            MonitorKey::FingerprintHeights => format!("{prefix}/fingerprints/heights"),
+            MonitorKey::Expiries => format!("{prefix}/expiries"),
+            MonitorKey::ReplacedTransactions => format!("{prefix}/tx/replaced"),
+            MonitorKey::ReplacementNews => format!("{prefix}/tx/replaced/news"),
+            MonitorKey::Snoozes => format!("{prefix}/snoozes"),
+            MonitorKey::TransactionSnapshots => format!("{prefix}/tx/snapshots"),
+            MonitorKey::TransactionSnapshotIds => format!("{prefix}/tx/snapshots/ids"),
+            MonitorKey::NewsSequence => format!("{prefix}/news/sequence"),
+            MonitorKey::NewsEpoch => format!("{prefix}/news/epoch"),
+            MonitorKey::EpochNews => format!("{prefix}/news/epoch/news"),
+            MonitorKey::ReorgNews => format!("{prefix}/reorg/news"),
+            MonitorKey::LastReorgImpact => format!("{prefix}/reorg/last_impact"),
+            MonitorKey::ScriptPubKeys(is_active) => format!(
+                "{prefix}/script/index/{status}",
+                status = if is_active { "active" } else { "inactive" }
+            ),
+            MonitorKey::ScriptPubKeyNews => format!("{prefix}/script/tx/news"),
+            MonitorKey::OpReturnPrefixes => format!("{prefix}/op_return/list"),
+            MonitorKey::OpReturnNews => format!("{prefix}/op_return/tx/news"),
+            MonitorKey::BlockWork => format!("{prefix}/block/work"),
+            MonitorKey::LowWorkReorgNews => format!("{prefix}/low_work_reorg/news"),
+            MonitorKey::MempoolSightings => format!("{prefix}/tx/mempool/sightings"),
+            MonitorKey::AddressFilterStats => format!("{prefix}/script/filter/stats"),
+            MonitorKey::DetectionArchive => format!("{prefix}/detections/archive"),
+            MonitorKey::Trash => format!("{prefix}/trash/list"),
+            MonitorKey::TrashNextId => format!("{prefix}/trash/next_id"),
+            MonitorKey::ExpectedMonitors => format!("{prefix}/strict/expected"),
+            MonitorKey::MonitorMetadata => format!("{prefix}/metadata"),
+            MonitorKey::ScanGeneration => format!("{prefix}/scan/generation"),
+            MonitorKey::ChainLog(seq) => format!("{prefix}/chain/log/{seq}"),
+            MonitorKey::ChainLogBounds => format!("{prefix}/chain/bounds"),
+            MonitorKey::NewsHistory(seq) => format!("{prefix}/news/history/{seq}"),
+            MonitorKey::NewsHistoryStart => format!("{prefix}/news/history/start"),
+            MonitorKey::OrphanDisputedNews => format!("{prefix}/news/orphan_disputed"),
+            MonitorKey::CompositeRules => format!("{prefix}/composite/rules"),
+            MonitorKey::CompositeNews => format!("{prefix}/composite/news"),
+            MonitorKey::Alternatives => format!("{prefix}/alternatives/list"),
+            MonitorKey::AlternativeNews => format!("{prefix}/alternatives/news"),
+            MonitorKey::ExpiredMonitorsNews => format!("{prefix}/expired/news"),
+            MonitorKey::HealthNews => format!("{prefix}/health/news"),
+            MonitorKey::ManualResolutionNews => format!("{prefix}/resolved/news"),
+            MonitorKey::Spender(tx_id) => format!("{prefix}/spender/{tx_id}"),
+            MonitorKey::SchemaVersion => format!("{prefix}/schema_version"),
+            MonitorKey::ExternalChain => format!("{prefix}/external/chain"),
+        }
+    }
+
+    pub(crate) fn get_blockchain_key(&self, key: BlockchainKey) -> String {
+        let prefix = "monitor";
+        match key {
+            BlockchainKey::CurrentBlockHeight => {
+                format!("{prefix}/blockchain/current_block_height")
+            }
+            BlockchainKey::CurrentBlockHash => {
+                format!("{prefix}/blockchain/current_block_hash")
+            }
+        }
+    }
+
+    fn detection_fingerprint_id(data: &MonitoredTypes) -> String {
+        match data {
+            MonitoredTypes::SpendingUTXOTransaction(tx_id, vout, extra_data, spender_tx_id) => {
+                format!("detection/spending/{tx_id}/{vout}/{extra_data}/{spender_tx_id}")
+            }
+            _ => format!("detection/{}", MonitorStore::news_fingerprint_id(data)),
+        }
+    }
+
+    /// Key of the fingerprint of `id`, each fingerprint is stored under its own key.
+    pub(crate) fn fingerprint_key(&self, id: &str) -> String {
+        format!("{}/id/{id}", self.get_key(MonitorKey::Fingerprints))
+    }
+
+    /// Key of the snapshot of `tx_id`, each snapshot is stored under its own key.
+    pub(crate) fn tx_snapshot_key(&self, tx_id: &Txid) -> String {
+        format!(
+            "{}/id/{tx_id}",
+            self.get_key(MonitorKey::TransactionSnapshots)
+        )
+    }
+
+    /// Whether `id` is recorded as seen in `block_hash`, so reprocessing the block must not write
+    /// again what the fingerprint guards.
+    fn is_fingerprint_recorded(
+        &self,
+        id: &str,
+        block_hash: BlockHash,
+    ) -> Result<bool, MonitorStoreError> {
+        let fingerprint: Option<FingerprintEntry> = self.read_typed(&self.fingerprint_key(id))?;
+        Ok(fingerprint.is_some_and(|f| f.block_hash == block_hash))
+    }
+
+    /// Records `id` as seen in `block_hash` at `height`, replacing any fingerprint of the same id
+    /// from another block. Callers record it once what it guards is written: a crash in between
+    /// writes it again when the block is reprocessed instead of losing it. The id is listed under
+    /// `height` so `prune_fingerprints` finds it.
+    fn record_fingerprint(
+        &self,
+        id: String,
+        block_hash: BlockHash,
+        height: BlockHeight,
+    ) -> Result<(), MonitorStoreError> {
+        let listed_key = self.get_key(MonitorKey::FingerprintsAt(height));
+        let mut listed: Vec<String> = self.read_typed(&listed_key)?.unwrap_or_default();
+        if !listed.contains(&id) {
+            listed.push(id.clone());
+            self.write_typed(&listed_key, &listed, None)?;
+        }
+
+        let heights_key = self.get_key(MonitorKey::FingerprintHeights);
+        let heights: Option<(BlockHeight, BlockHeight)> = self.read_typed(&heights_key)?;
+        let (lowest, highest) = heights.unwrap_or((height, height));
+        if heights.is_none() || height < lowest || height > highest {
+            self.write_typed(
+                &heights_key,
+                (lowest.min(height), highest.max(height)),
+                None,
+            )?;
+        }
+
+        let key = self.fingerprint_key(&id);
+        self.write_typed(
+            &key,
+            FingerprintEntry {
+                id,
+                block_hash,
+                height,
+            },
+            None,
+        )
+    }
+
+    /// Drops the fingerprints recorded below `older_than_height`, whose blocks are no longer
+    /// reprocessed. A fingerprint recorded again at a later height is kept.
+    fn prune_fingerprints(
+        &self,
+        older_than_height: BlockHeight,
+        transaction_id: Uuid,
+    ) -> Result<(), MonitorStoreError> {
+        let heights_key = self.get_key(MonitorKey::FingerprintHeights);
+        let Some((lowest, highest)) =
+            self.read_typed::<(BlockHeight, BlockHeight)>(&heights_key)?
+        else {
+            return Ok(());
+        };
+
+        let pruned_below = older_than_height.min(highest + 1);
+        for height in lowest..pruned_below {
+            let listed_key = self.get_key(MonitorKey::FingerprintsAt(height));
+            let Some(listed) = self.read_typed::<Vec<String>>(&listed_key)? else {
+                continue;
+            };
+
+            for id in listed {
+                let key = self.fingerprint_key(&id);
+                let fingerprint: Option<FingerprintEntry> = self.read_typed(&key)?;
+                if fingerprint.is_some_and(|f| f.height == height) {
+                    self.delete_key(&key, Some(transaction_id))?;
+                }
+            }
+            self.delete_key(&listed_key, Some(transaction_id))?;
+        }
+
+        if pruned_below > highest {
+            self.delete_key(&heights_key, Some(transaction_id))?;
+        } else if pruned_below > lowest {
+            self.write_typed(&heights_key, (pruned_below, highest), Some(transaction_id))?;
+        }
+
+        Ok(())
+    }
+
+    fn insert_transaction_monitor(
+        txs: &mut Vec<TransactionMonitor>,
+        tx_ids: &[Txid],
+        extra_data: &str,
+        from: Option<u32>,
+        expires_at_height: Option<BlockHeight>,
+        registered: &MonitorEvent,
+    ) {
+        for txid in tx_ids {
+            let mut entry = TransactionMonitorEntry {
+                extra_data: extra_data.into(),
+                confirmation_trigger: from,
+                trigger_sent: false,
+                last_reported_confirmations: None,
+                last_reported_block_hash: None,
+                expires_at_height,
+                history: vec![],
+            };
+
+            if let Some(monitor) = txs.iter_mut().find(|m| m.tx_id == *txid) {
+                // If tx exists and extra_data is the same, override Option<u32> and move trigger sent in false
+                if let Some(pos) = monitor
+                    .entries
+                    .iter()
+                    .position(|e| e.extra_data == extra_data)
+                {
+                    entry.history = std::mem::take(&mut monitor.entries[pos].history);
+                    MonitorEvent::record(&mut entry.history, registered.clone());
+                    monitor.entries[pos] = entry;
+                } else {
+                    // If extra_data is different, add it as a new tx_id-to-monitor entry
+                    MonitorEvent::record(&mut entry.history, registered.clone());
+                    monitor.entries.push(entry);
+                }
+            } else {
+                // New txid, store it with its first (extra_data, trigger) entry
+                MonitorEvent::record(&mut entry.history, registered.clone());
+                txs.push(TransactionMonitor {
+                    tx_id: *txid,
+                    entries: vec![entry],
+                });
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    fn insert_spending_utxo_monitor(
+        txs: &mut Vec<SpendingUTXOMonitor>,
+        txid: Txid,
+        vout: u32,
+        extra_data: &str,
+        from: Option<u32>,
+        deadline: Option<SpendDeadline>,
+        value_gate: Option<SpendValueGate>,
+        expires_at_height: Option<BlockHeight>,
+        registered: &MonitorEvent,
+    ) {
+        let mut entry = SpendingUTXOMonitorEntry {
+            extra_data: extra_data.into(),
+            spender_tx_id: None,
+            confirmation_trigger: from,
+            deadline,
+            value_gate,
+            expires_at_height,
+            history: vec![],
+            backfill_done: false,
+        };
+
+        if let Some(monitor) = txs.iter_mut().find(|m| m.tx_id == txid && m.vout == vout) {
+            // If extra_data is the same, override confirmation trigger and keep spender_tx_id
+            if let Some(pos) = monitor
+                .entries
+                .iter()
+                .position(|e| e.extra_data == extra_data)
+            {
+                entry.spender_tx_id = monitor.entries[pos].spender_tx_id;
+                entry.backfill_done = monitor.entries[pos].backfill_done;
+                entry.history = std::mem::take(&mut monitor.entries[pos].history);
+                MonitorEvent::record(&mut entry.history, registered.clone());
+                monitor.entries[pos] = entry;
+            } else {
+                // If extra_data is different, add it as a new entry
+                MonitorEvent::record(&mut entry.history, registered.clone());
+                monitor.entries.push(entry);
+            }
+        } else {
+            // New (txid,vout)
+            MonitorEvent::record(&mut entry.history, registered.clone());
+            txs.push(SpendingUTXOMonitor {
+                tx_id: txid,
+                vout,
+                entries: vec![entry],
+            });
+        }
+    }
+
+    /// Removes the entry of the monitor from the active and inactive lists, returning the removed
+    /// entries with their state.
+    fn remove_monitor(
+        &self,
+        data: TypesToMonitor,
+        transaction_id: Option<Uuid>,
+    ) -> Result<TrashedMonitors, MonitorStoreError> {
+        let mut removed = TrashedMonitors::default();
+
+        match data {
+            TypesToMonitor::Transactions(tx_ids, extra_data, _, _) => {
+                // Remove only the entry with matching extra_data for each txid
+                for is_active in [true, false] {
+                    removed.transactions.extend(self.remove_transaction_entries(
+                        is_active,
+                        Some(&tx_ids),
+                        |_, entry| entry.extra_data == extra_data,
+                        transaction_id,
+                    )?);
+                }
+            }
+            TypesToMonitor::RskPegin(..) => {
+                // Cancelling wipes the trigger and the filters, only the trash keeps them
+                let key = self.get_key(MonitorKey::RskPegin);
+                let state: Option<RskPeginMonitorState> = self.read_typed(&key)?;
+                removed.rsk_pegin =
+                    state.filter(|state| state.status != RskPeginMonitorStatus::Cancelled);
+
+                self.write_typed(
+                    &key,
+                    RskPeginMonitorState {
+                        status: RskPeginMonitorStatus::Cancelled,
+                        confirmation_trigger: None,
+                        committee: vec![],
+                        packet_range: None,
+                        min_amount: None,
+                    },
+                    transaction_id,
+                )?;
+            }
+            TypesToMonitor::SpendingUTXOTransaction(txid, vout, extra_data, ..) => {
+                let mut spenders: Vec<Txid> = vec![];
+                let mut monitored = false;
+
+                // Remove only the entry with matching extra_data, from both lists
+                for is_active in [true, false] {
+                    let mut txs: Listed<SpendingUTXOMonitor> = self.read_listed_items(
+                        MonitorKey::SpendingUTXOTransactions(is_active),
+                        [OutPoint::new(txid, vout).to_string()],
+                    )?;
+
+                    if let Some(monitor) =
+                        txs.iter_mut().find(|m| m.tx_id == txid && m.vout == vout)
+                    {
+                        let (entries, kept) = monitor
+                            .entries
+                            .drain(..)
+                            .partition(|e| e.extra_data == extra_data);
+                        monitor.entries = kept;
+                        monitored |= !monitor.entries.is_empty();
+
+                        for spender in entries.iter().filter_map(|e| e.spender_tx_id) {
+                            if !spenders.contains(&spender) {
+                                spenders.push(spender);
+                            }
+                        }
+
+                        if !entries.is_empty() {
+                            removed.spending_utxos.push((
+                                is_active,
+                                SpendingUTXOMonitor {
+                                    tx_id: txid,
+                                    vout,
+                                    entries,
+                                },
+                            ));
+                        }
+                    }
+                    // If no entries left for this (txid, vout), remove it entirely
+                    self.write_listed(&txs, transaction_id)?;
+                }
+
+                // The spend stays indexed while another context monitors the output
+                if !monitored {
+                    for spender in spenders {
+                        self.unindex_spends(spender, &[(txid, vout)], transaction_id)?;
+                    }
+                }
+
+                // The spender found is followed by an internal transaction monitor, which would
+                // keep sending news for the canceled monitor
+                let spender_contexts = [
+                    build_spending_utxo_context(txid, vout, &extra_data),
+                    build_minor_spend_context(txid, vout, &extra_data, true),
+                    build_minor_spend_context(txid, vout, &extra_data, false),
+                ];
+                for is_active in [true, false] {
+                    removed.transactions.extend(self.remove_transaction_entries(
+                        is_active,
+                        None,
+                        |_, entry| spender_contexts.contains(&entry.extra_data),
+                        transaction_id,
+                    )?);
+                }
+            }
+            TypesToMonitor::NewBlock => {
+                let key = self.get_key(MonitorKey::NewBlock);
+                removed.new_block = self.read_typed(&key)?.unwrap_or(false);
+                self.write_typed(&key, false, transaction_id)?;
+            }
+            TypesToMonitor::OpReturnPrefix(prefix, extra_data) => {
+                removed.op_return_prefixes =
+                    self.remove_op_return_prefix_monitor(&prefix, &extra_data, transaction_id)?;
+            }
+            TypesToMonitor::Alternatives(tx_ids, extra_data) => {
+                for is_active in [true, false] {
+                    removed.transactions.extend(self.remove_transaction_entries(
+                        is_active,
+                        Some(&tx_ids),
+                        |_, entry| entry.extra_data == extra_data,
+                        transaction_id,
+                    )?);
+                }
+                removed.alternatives =
+                    self.remove_alternatives_monitor(&tx_ids, &extra_data, transaction_id)?;
+            }
+            TypesToMonitor::ScriptPubKey(script_pubkey, extra_data, ..) => {
+                // Remove only the entry with matching extra_data, from both lists
+                for is_active in [true, false] {
+                    let mut scripts: Listed<ScriptPubKeyMonitor> = self.read_listed_items(
+                        MonitorKey::ScriptPubKeys(is_active),
+                        [script_pubkey.to_hex_string()],
+                    )?;
+
+                    if let Some(monitor) = scripts
+                        .iter_mut()
+                        .find(|m| m.script_pubkey == script_pubkey)
+                    {
+                        let (entries, kept) = monitor
+                            .entries
+                            .drain(..)
+                            .partition(|e| e.extra_data == extra_data);
+                        monitor.entries = kept;
+
+                        if !entries.is_empty() {
+                            removed.script_pubkeys.push((
+                                is_active,
+                                ScriptPubKeyMonitor {
+                                    script_pubkey: script_pubkey.clone(),
+                                    entries,
+                                },
+                            ));
+                        }
+                    }
+                    self.write_listed(&scripts, transaction_id)?;
+                }
+            }
+        }
+
+        Ok(removed)
+    }
+
+    /// Removes the entries of the transaction monitors in the active or inactive list matched by
+    /// `is_match`, dropping the monitors left without entries. Only the monitors of `tx_ids` are
+    /// read, or all of them if None. Returns the removed entries.
+    fn remove_transaction_entries(
+        &self,
+        is_active: bool,
+        tx_ids: Option<&[Txid]>,
+        is_match: impl Fn(&Txid, &TransactionMonitorEntry) -> bool,
+        transaction_id: Option<Uuid>,
+    ) -> Result<Vec<(bool, TransactionMonitor)>, MonitorStoreError> {
+        let list = MonitorKey::Transactions(is_active);
+        let mut txs: Listed<TransactionMonitor> = match tx_ids {
+            Some(tx_ids) => {
+                self.read_listed_items(list, tx_ids.iter().map(|tx_id| tx_id.to_string()))?
+            }
+            None => self.read_listed(list)?,
+        };
+        let mut removed = vec![];
+
+        for monitor in txs.iter_mut() {
+            let (entries, kept) = monitor
+                .entries
+                .drain(..)
+                .partition(|e| is_match(&monitor.tx_id, e));
+            monitor.entries = kept;
+
+            if !entries.is_empty() {
+                removed.push((
+                    is_active,
+                    TransactionMonitor {
+                        tx_id: monitor.tx_id,
+                        entries,
+                    },
+                ));
+            }
+        }
+
+        if !removed.is_empty() {
+            self.write_listed(&txs, transaction_id)?;
+        }
+
+        Ok(removed)
+    }
+
+    /// Writes back the entries of trashed monitors, replacing the entries registered again with
+    /// the same context since they were canceled. Each list is read and written once, as the
+    /// reads don't see the writes of the store transaction.
+    fn restore_monitors(
+        &self,
+        monitors: TrashedMonitors,
+        transaction_id: Option<Uuid>,
+    ) -> Result<(), MonitorStoreError> {
+        self.restore_listed(
+            MonitorKey::Transactions,
+            monitors.transactions,
+            transaction_id,
+        )?;
+        self.index_restored_spends(&monitors.spending_utxos, transaction_id)?;
+        self.restore_listed(
+            MonitorKey::SpendingUTXOTransactions,
+            monitors.spending_utxos,
+            transaction_id,
+        )?;
+        self.restore_listed(
+            MonitorKey::ScriptPubKeys,
+            monitors.script_pubkeys,
+            transaction_id,
+        )?;
+
+        if !monitors.op_return_prefixes.is_empty() {
+            let key = self.get_key(MonitorKey::OpReturnPrefixes);
+            let mut op_return_prefixes: Vec<OpReturnPrefixMonitor> =
+                self.read_typed(&key)?.unwrap_or_default();
+
+            for restored in monitors.op_return_prefixes {
+                if !op_return_prefixes
+                    .iter()
+                    .any(|m| m.prefix == restored.prefix && m.extra_data == restored.extra_data)
+                {
+                    op_return_prefixes.push(restored);
+                }
+            }
+            self.write_typed(&key, &op_return_prefixes, transaction_id)?;
+        }
+
+        if let Some(state) = monitors.rsk_pegin {
+            let key = self.get_key(MonitorKey::RskPegin);
+            self.write_typed(&key, state, transaction_id)?;
+        }
+
+        if !monitors.alternatives.is_empty() {
+            let key = self.get_key(MonitorKey::Alternatives);
+            let mut races: Vec<AlternativesMonitor> = self.read_typed(&key)?.unwrap_or_default();
+
+            for restored in monitors.alternatives {
+                races.retain(|m| !m.matches(&restored.tx_ids, &restored.extra_data));
+                races.push(restored);
+            }
+            self.write_typed(&key, &races, transaction_id)?;
+        }
+
+        if monitors.new_block {
+            let key = self.get_key(MonitorKey::NewBlock);
+            self.write_typed(&key, true, transaction_id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Records `event` in the history of the entry with `context` of the monitor of the list
+    /// matching `is_target`, active or inactive.
+    fn record_listed_event<M: ListedMonitor>(
+        &self,
+        list_key: fn(bool) -> MonitorKey,
+        id: String,
+        context: &str,
+        event: MonitorEvent,
+    ) -> Result<(), MonitorStoreError> {
+        for is_active in [true, false] {
+            let mut list: Listed<M> = self.read_listed_items(list_key(is_active), [id.clone()])?;
+
+            let entry = list
+                .iter_mut()
+                .flat_map(|monitor| monitor.entries().iter_mut())
+                .find(|entry| M::context(entry) == context);
+
+            if let Some(entry) = entry {
+                // Nothing to write when the event was already recorded
+                if MonitorEvent::record(M::history(entry), event) {
+                    self.write_listed(&list, None)?;
+                }
+                return Ok(());
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Indexes the spenders found by the SpendingUTXO monitors written back by a restore or an
+    /// import, see `get_outpoints_spent_by`.
+    fn index_restored_spends(
+        &self,
+        monitors: &[(bool, SpendingUTXOMonitor)],
+        transaction_id: Option<Uuid>,
+    ) -> Result<(), MonitorStoreError> {
+        let mut spends: Vec<(Txid, Vec<(Txid, u32)>)> = vec![];
+        for (_, monitor) in monitors {
+            for spender in monitor.entries.iter().filter_map(|e| e.spender_tx_id) {
+                let outpoint = (monitor.tx_id, monitor.vout);
+                match spends.iter_mut().find(|(s, _)| *s == spender) {
+                    Some((_, outpoints)) if outpoints.contains(&outpoint) => {}
+                    Some((_, outpoints)) => outpoints.push(outpoint),
+                    None => spends.push((spender, vec![outpoint])),
+                }
+            }
+        }
+        for (spender, outpoints) in spends {
+            self.index_spends(spender, &outpoints, transaction_id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Drops the entries of `imported` whose target and context are already in the active or
+    /// the inactive list, and the monitors left without entries.
+    fn drop_stored_entries<M: ListedMonitor>(
+        &self,
+        list_key: fn(bool) -> MonitorKey,
+        imported: &mut Vec<(bool, M)>,
+    ) -> Result<(), MonitorStoreError> {
+        let mut stored = HashSet::new();
+        for is_active in [true, false] {
+            for mut monitor in self.read_list::<M>(list_key(is_active))? {
+                let id = monitor.item_id();
+                for entry in monitor.entries().iter() {
+                    stored.insert((id.clone(), M::context(entry).to_string()));
+                }
+            }
+        }
+
+        for (_, monitor) in imported.iter_mut() {
+            let id = monitor.item_id();
+            monitor
+                .entries()
+                .retain(|e| !stored.contains(&(id.clone(), M::context(e).to_string())));
+        }
+        imported.retain(|(_, monitor)| monitor.has_entries());
+
+        Ok(())
+    }
+
+    /// Writes the monitors of an export, see `import_monitors`.
+    fn import_monitors_in(
+        &self,
+        export: MonitorExport,
+        overwrite: bool,
+        transaction_id: Option<Uuid>,
+    ) -> Result<(), MonitorStoreError> {
+        let MonitorExport {
+            mut transactions,
+            mut spending_utxos,
+            mut script_pubkeys,
+            ..
+        } = export;
+
+        if !overwrite {
+            self.drop_stored_entries(MonitorKey::Transactions, &mut transactions)?;
+            self.drop_stored_entries(MonitorKey::SpendingUTXOTransactions, &mut spending_utxos)?;
+            self.drop_stored_entries(MonitorKey::ScriptPubKeys, &mut script_pubkeys)?;
+        }
+
+        self.restore_listed(MonitorKey::Transactions, transactions, transaction_id)?;
+        self.index_restored_spends(&spending_utxos, transaction_id)?;
+        self.restore_listed(
+            MonitorKey::SpendingUTXOTransactions,
+            spending_utxos,
+            transaction_id,
+        )?;
+        self.restore_listed(MonitorKey::ScriptPubKeys, script_pubkeys, transaction_id)?;
+
+        self.import_items(
+            MonitorKey::OpReturnPrefixes,
+            export.op_return_prefixes,
+            overwrite,
+            |a: &OpReturnPrefixMonitor, b| a.prefix == b.prefix && a.extra_data == b.extra_data,
+            transaction_id,
+        )?;
+        self.import_items(
+            MonitorKey::Alternatives,
+            export.alternatives,
+            overwrite,
+            |a: &AlternativesMonitor, b| a.matches(&b.tx_ids, &b.extra_data),
+            transaction_id,
+        )?;
+        self.import_items(
+            MonitorKey::CompositeRules,
+            export.composites,
+            overwrite,
+            |a: &CompositeRule, b| a.id == b.id,
+            transaction_id,
+        )?;
+        self.import_items(
+            MonitorKey::MonitorMetadata,
+            export.metadata,
+            overwrite,
+            |a: &MonitorMetadataEntry, b| a.target == b.target,
+            transaction_id,
+        )?;
+
+        if let Some(state) = export.rsk_pegin {
+            let key = self.get_key(MonitorKey::RskPegin);
+            if overwrite || self.read_typed::<RskPeginMonitorState>(&key)?.is_none() {
+                self.write_typed(&key, state, transaction_id)?;
+            }
+        }
+
+        if let Some(is_active) = export.new_block {
+            let key = self.get_key(MonitorKey::NewBlock);
+            if overwrite || self.read_typed::<bool>(&key)?.is_none() {
+                self.write_typed(&key, is_active, transaction_id)?;
+            }
+        }
+
+        self.write_typed(&self.get_key(MonitorKey::PendingWork), true, transaction_id)
+    }
+
+    /// Adds `imported` to the items stored as a single `Vec` under `key`. An item matching a
+    /// stored one with `same` replaces it with `overwrite` and is dropped otherwise.
+    fn import_items<T: Serialize + DeserializeOwned>(
+        &self,
+        key: MonitorKey,
+        imported: Vec<T>,
+        overwrite: bool,
+        same: impl Fn(&T, &T) -> bool,
+        transaction_id: Option<Uuid>,
+    ) -> Result<(), MonitorStoreError> {
+        if imported.is_empty() {
+            return Ok(());
+        }
+
+        let key = self.get_key(key);
+        let mut items: Vec<T> = self.read_typed(&key)?.unwrap_or_default();
+
+        for item in imported {
+            match items.iter().position(|stored| same(stored, &item)) {
+                Some(pos) if overwrite => items[pos] = item,
+                Some(_) => {}
+                None => items.push(item),
+            }
+        }
+
+        self.write_typed(&key, &items, transaction_id)
+    }
+
+    fn restore_listed<M: ListedMonitor>(
+        &self,
+        list_key: fn(bool) -> MonitorKey,
+        restored: Vec<(bool, M)>,
+        transaction_id: Option<Uuid>,
+    ) -> Result<(), MonitorStoreError> {
+        if restored.is_empty() {
+            return Ok(());
+        }
+
+        let mut lists: Vec<Listed<M>> = [true, false]
+            .into_iter()
+            .map(|is_active| self.read_listed(list_key(is_active)))
+            .collect::<Result<_, MonitorStoreError>>()?;
+
+        for (is_active, mut monitor) in restored {
+            let contexts: Vec<String> = monitor
+                .entries()
+                .iter()
+                .map(|e| M::context(e).to_string())
+                .collect();
+
+            for list in lists.iter_mut() {
+                for listed in list.iter_mut().filter(|m| m.same_target(&monitor)) {
+                    listed
+                        .entries()
+                        .retain(|e| !contexts.iter().any(|c| c == M::context(e)));
+                }
+                list.retain_mut(|m| !m.entries().is_empty());
+            }
+
+            let list = &mut lists[if is_active { 0 } else { 1 }];
+            match list.iter_mut().find(|m| m.same_target(&monitor)) {
+                Some(listed) => listed.entries().append(monitor.entries()),
+                None => list.push(monitor),
+            }
+        }
+
+        for list in &lists {
+            self.write_listed(list, transaction_id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Adds `outpoints` to the outpoints spent by `spender`, see `get_outpoints_spent_by`.
+    fn index_spends(
+        &self,
+        spender: Txid,
+        outpoints: &[(Txid, u32)],
+        transaction_id: Option<Uuid>,
+    ) -> Result<(), MonitorStoreError> {
+        let mut spent = self.get_outpoints_spent_by(spender)?;
+        let len = spent.len();
+
+        for outpoint in outpoints {
+            if !spent.contains(outpoint) {
+                spent.push(*outpoint);
+            }
+        }
+
+        if spent.len() != len {
+            self.write_spends(spender, &spent, transaction_id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes `outpoints` from the outpoints spent by `spender`, the key is removed once none
+    /// is left.
+    fn unindex_spends(
+        &self,
+        spender: Txid,
+        outpoints: &[(Txid, u32)],
+        transaction_id: Option<Uuid>,
+    ) -> Result<(), MonitorStoreError> {
+        let mut spent = self.get_outpoints_spent_by(spender)?;
+        let len = spent.len();
+
+        spent.retain(|outpoint| !outpoints.contains(outpoint));
+
+        if spent.is_empty() && len > 0 {
+            let key = self.get_key(MonitorKey::Spender(spender));
+            self.delete_key(&key, transaction_id)?;
+        } else if spent.len() != len {
+            self.write_spends(spender, &spent, transaction_id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Writes the outpoints spent by `spender`. Since schema version 2 they are stored as
+    /// outpoints, `txid:vout`, instead of `(txid, vout)` tuples.
+    pub(crate) fn write_spends(
+        &self,
+        spender: Txid,
+        spent: &[(Txid, u32)],
+        transaction_id: Option<Uuid>,
+    ) -> Result<(), MonitorStoreError> {
+        let outpoints: Vec<OutPoint> = spent
+            .iter()
+            .map(|(tx_id, vout)| OutPoint::new(*tx_id, *vout))
+            .collect();
+        let key = self.get_key(MonitorKey::Spender(spender));
+        self.write_typed(&key, outpoints, transaction_id)
+    }
+
+    /// Removes the monitor and settles its news in one store transaction. Unless `permanent`,
+    /// the removed entries are moved to the trash.
+    fn cancel(
+        &self,
+        data: TypesToMonitor,
+        policy: CancelNewsPolicy,
+        permanent: bool,
+    ) -> Result<(), MonitorStoreError> {
+        let transaction_id = self.store.begin_transaction();
+
+        let result = self
+            .remove_monitor(data.clone(), Some(transaction_id))
+            .and_then(|monitors| {
+                if permanent || monitors.is_empty() {
+                    return Ok(());
+                }
+                self.trash_monitors(monitors, CancelReason::Requested, Some(transaction_id))
+            })
+            .and_then(|_| self.settle_monitor_news(&data, policy, None, Some(transaction_id)));
+
+        match result {
+            Ok(()) => self.store.commit_transaction(transaction_id)?,
+            Err(e) => {
+                self.store.rollback_transaction(transaction_id)?;
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn trash_monitors(
+        &self,
+        monitors: TrashedMonitors,
+        reason: CancelReason,
+        transaction_id: Option<Uuid>,
+    ) -> Result<(), MonitorStoreError> {
+        let next_id_key = self.get_key(MonitorKey::TrashNextId);
+        let id: u64 = self.read_typed(&next_id_key)?.unwrap_or_default();
+        self.write_typed(&next_id_key, id + 1, transaction_id)?;
+
+        let key = self.get_key(MonitorKey::Trash);
+        let mut trash: Vec<TrashEntry> = self.read_typed(&key)?.unwrap_or_default();
+        trash.push(TrashEntry {
+            id,
+            deleted_at: self.get_monitor_height()?,
+            monitors,
+            reason,
+        });
+        self.write_typed(&key, &trash, transaction_id)?;
+
+        Ok(())
+    }
+
+    /// Drops or acknowledges, following `policy`, the news of the monitor canceled with `data`.
+    /// The news acknowledged keep `metadata`.
+    fn settle_monitor_news(
+        &self,
+        data: &TypesToMonitor,
+        policy: CancelNewsPolicy,
+        metadata: Option<&AckMetadata>,
+        transaction_id: Option<Uuid>,
+    ) -> Result<(), MonitorStoreError> {
+        if policy == CancelNewsPolicy::KeepNews {
+            return Ok(());
+        }
+
+        match data {
+            TypesToMonitor::Transactions(tx_ids, extra_data, _, _) => {
+                for key in [
+                    MonitorKey::TransactionsNews,
+                    MonitorKey::OrphanedTransactionsNews,
+                ] {
+                    self.settle_news::<TransactionNewsEntry>(
+                        key,
+                        policy,
+                        |e| tx_ids.contains(&e.tx_id) && e.extra_data == *extra_data,
+                        |e| &mut e.ack,
+                        metadata,
+                        transaction_id,
+                    )?;
+                }
+                self.settle_news::<ExpiredMonitorNewsEntry>(
+                    MonitorKey::ExpiredMonitorsNews,
+                    policy,
+                    |e| {
+                        e.vout.is_none() && tx_ids.contains(&e.tx_id) && e.extra_data == *extra_data
+                    },
+                    |e| &mut e.ack,
+                    metadata,
+                    transaction_id,
+                )?;
+            }
+            TypesToMonitor::RskPegin(..) => {
+                self.settle_news::<RskPeginNewsEntry>(
+                    MonitorKey::RskPeginTransactionsNews,
+                    policy,
+                    |_| true,
+                    |e| &mut e.ack,
+                    metadata,
+                    transaction_id,
+                )?;
+            }
+            TypesToMonitor::SpendingUTXOTransaction(tx_id, vout, extra_data, ..) => {
+                for key in [
+                    MonitorKey::SpendingUTXOTransactionsNews,
+                    MonitorKey::SpendingUTXOUnconfirmedNews,
+                    MonitorKey::MinorSpendNews,
+                ] {
+                    self.settle_news::<SpendingUTXONewsEntry>(
+                        key,
+                        policy,
+                        |e| {
+                            e.tx_id == *tx_id
+                                && e.utxo_index == *vout
+                                && e.extra_data == *extra_data
+                        },
+                        |e| &mut e.ack,
+                        metadata,
+                        transaction_id,
+                    )?;
+                }
+                self.settle_news::<ExpiredMonitorNewsEntry>(
+                    MonitorKey::ExpiredMonitorsNews,
+                    policy,
+                    |e| e.tx_id == *tx_id && e.vout == Some(*vout) && e.extra_data == *extra_data,
+                    |e| &mut e.ack,
+                    metadata,
+                    transaction_id,
+                )?;
+            }
+            TypesToMonitor::NewBlock => {
+                // Block news only tell the tip moved, dropping them is the same as acking them
+                let mut queue = self.read_block_news()?;
+
+                let mut acked = vec![];
+                for entry in queue.iter_mut().filter(|entry| !entry.ack.acknowledged) {
+                    entry.ack.acknowledged = true;
+                    entry.ack.metadata = metadata.cloned();
+                    acked.push(entry.ack.sequence);
+                }
+
+                if !acked.is_empty() {
+                    self.write_typed(
+                        &self.get_key(MonitorKey::NewBlockNews),
+                        &queue,
+                        transaction_id,
+                    )?;
+                    self.record_news_acks(&acked, transaction_id)?;
+                }
+            }
+            TypesToMonitor::OpReturnPrefix(prefix, extra_data) => {
+                self.settle_news::<OpReturnNewsEntry>(
+                    MonitorKey::OpReturnNews,
+                    policy,
+                    |e| e.prefix == *prefix && e.extra_data == *extra_data,
+                    |e| &mut e.ack,
+                    metadata,
+                    transaction_id,
+                )?;
+            }
+            TypesToMonitor::ScriptPubKey(script_pubkey, extra_data, ..) => {
+                self.settle_news::<ScriptPubKeyNewsEntry>(
+                    MonitorKey::ScriptPubKeyNews,
+                    policy,
+                    |e| e.script_pubkey == *script_pubkey && e.extra_data == *extra_data,
+                    |e| &mut e.ack,
+                    metadata,
+                    transaction_id,
+                )?;
+            }
+            TypesToMonitor::Alternatives(tx_ids, extra_data) => {
+                self.settle_news::<TransactionNewsEntry>(
+                    MonitorKey::TransactionsNews,
+                    policy,
+                    |e| tx_ids.contains(&e.tx_id) && e.extra_data == *extra_data,
+                    |e| &mut e.ack,
+                    metadata,
+                    transaction_id,
+                )?;
+                self.settle_news::<AlternativeNewsEntry>(
+                    MonitorKey::AlternativeNews,
+                    policy,
+                    |e| tx_ids.contains(&e.winner) && e.extra_data == *extra_data,
+                    |e| &mut e.ack,
+                    metadata,
+                    transaction_id,
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Drops or acknowledges the unacked news of the list under `key` matched by `is_match`.
+    fn settle_news<E: Serialize + DeserializeOwned>(
+        &self,
+        key: MonitorKey,
+        policy: CancelNewsPolicy,
+        is_match: impl Fn(&E) -> bool,
+        ack: impl Fn(&mut E) -> &mut NewsAck,
+        metadata: Option<&AckMetadata>,
+        transaction_id: Option<Uuid>,
+    ) -> Result<(), MonitorStoreError> {
+        let key = self.get_key(key);
+        let mut news: Vec<E> = self.read_typed(&key)?.unwrap_or_default();
+        let mut settled = 0;
+        let mut acked = vec![];
+
+        match policy {
+            CancelNewsPolicy::KeepNews => {}
+            CancelNewsPolicy::DropNews => {
+                let len = news.len();
+                news.retain(|e| !is_match(e));
+                settled = len - news.len();
+            }
+            CancelNewsPolicy::AckNews => {
+                for entry in news.iter_mut().filter(|e| is_match(e)) {
+                    let ack = ack(entry);
+                    if !ack.acknowledged {
+                        ack.acknowledged = true;
+                        ack.metadata = metadata.cloned();
+                        acked.push(ack.sequence);
+                        settled += 1;
+                    }
+                }
+            }
+        }
+
+        if settled > 0 {
+            self.write_typed(&key, &news, transaction_id)?;
+            self.record_news_acks(&acked, transaction_id)?;
+        }
+
+        Ok(())
+    }
+
+    /// Removes the race of `tx_ids` with `extra_data`, returning it if it was found.
+    fn remove_alternatives_monitor(
+        &self,
+        tx_ids: &[Txid],
+        extra_data: &str,
+        transaction_id: Option<Uuid>,
+    ) -> Result<Vec<AlternativesMonitor>, MonitorStoreError> {
+        let key = self.get_key(MonitorKey::Alternatives);
+        let races: Vec<AlternativesMonitor> = self.read_typed(&key)?.unwrap_or_default();
+        let (removed, kept): (Vec<_>, Vec<_>) = races
+            .into_iter()
+            .partition(|m| m.matches(tx_ids, extra_data));
+
+        if !removed.is_empty() {
+            self.write_typed(&key, &kept, transaction_id)?;
+        }
+
+        Ok(removed)
+    }
+
+    /// OP_RETURN prefix monitors are never deactivated by the monitor, so deactivating one
+    /// removes it like a cancel.
+    fn remove_op_return_prefix_monitor(
+        &self,
+        prefix: &[u8],
+        extra_data: &str,
+        transaction_id: Option<Uuid>,
+    ) -> Result<Vec<OpReturnPrefixMonitor>, MonitorStoreError> {
+        let key = self.get_key(MonitorKey::OpReturnPrefixes);
+        let op_return_prefixes: Vec<OpReturnPrefixMonitor> =
+            self.read_typed(&key)?.unwrap_or_default();
+
+        let (removed, kept): (Vec<_>, Vec<_>) = op_return_prefixes
+            .into_iter()
+            .partition(|m| m.prefix == prefix && m.extra_data == extra_data);
+
+        if !removed.is_empty() {
+            self.write_typed(&key, &kept, transaction_id)?;
+        }
+
+        Ok(removed)
+    }
+
+    fn insert_script_pubkey_monitor(
+        scripts: &mut Vec<ScriptPubKeyMonitor>,
+        script_pubkey: ScriptBuf,
+        extra_data: &str,
+        from: Option<u32>,
+        filter: Option<OutputFilter>,
+        registered: &MonitorEvent,
+    ) {
+        let mut entry = ScriptPubKeyMonitorEntry {
+            extra_data: extra_data.into(),
+            confirmation_trigger: from,
+            filter,
+            history: vec![],
+        };
+
+        if let Some(monitor) = scripts
+            .iter_mut()
+            .find(|m| m.script_pubkey == script_pubkey)
+        {
+            // If extra_data is the same, override the confirmation trigger and the filter
+            match monitor
+                .entries
+                .iter()
+                .position(|e| e.extra_data == extra_data)
+            {
+                Some(pos) => {
+                    entry.history = std::mem::take(&mut monitor.entries[pos].history);
+                    MonitorEvent::record(&mut entry.history, registered.clone());
+                    monitor.entries[pos] = entry;
+                }
+                None => {
+                    MonitorEvent::record(&mut entry.history, registered.clone());
+                    monitor.entries.push(entry);
+                }
+            }
+        } else {
+            MonitorEvent::record(&mut entry.history, registered.clone());
+            scripts.push(ScriptPubKeyMonitor {
+                script_pubkey,
+                entries: vec![entry],
+            });
+        }
+    }
+}
+
+impl MonitorStore {
+    /// Opens the storage at `config` and the store in it, for a command that reads or writes the
+    /// store and exits. The storage must not be open in another process.
+    pub fn open(config: &StorageConfig) -> Result<Self, MonitorStoreError> {
+        Self::new(Arc::new(Storage::new(config)?))
+    }
+
+    /// Identifies a news entry, a new block hash for the same id replaces the previous news.
+    pub(crate) fn news_fingerprint_id(data: &MonitoredTypes) -> String {
+        match data {
+            MonitoredTypes::Transaction(tx_id, extra_data) => {
+                format!("news/tx/{tx_id}/{extra_data}")
+            }
+            MonitoredTypes::OrphanedTransaction(tx_id, extra_data) => {
+                format!("news/tx/orphaned/{tx_id}/{extra_data}")
+            }
+            MonitoredTypes::RskPeginTransaction(tx_id, _) => format!("news/pegin/{tx_id}"),
+            MonitoredTypes::SpendingUTXOTransaction(tx_id, vout, extra_data, _) => {
+                format!("news/spending/{tx_id}/{vout}/{extra_data}")
+            }
+            MonitoredTypes::SpendingUTXOUnconfirmed(tx_id, vout, extra_data, spender_tx_id) => {
+                format!("news/spending/unconfirmed/{tx_id}/{vout}/{extra_data}/{spender_tx_id}")
+            }
+            MonitoredTypes::MinorSpend(tx_id, vout, extra_data, _) => {
+                format!("news/spending/minor/{tx_id}/{vout}/{extra_data}")
+            }
+            MonitoredTypes::NewBlock(..) => "news/block".to_string(),
+            MonitoredTypes::MonitorReplaced(old, _, extra_data) => {
+                format!("news/replaced/{old}/{extra_data}")
+            }
+            MonitoredTypes::ReplacedTransactionConfirmed(old, _, extra_data) => {
+                format!("news/replaced/confirmed/{old}/{extra_data}")
+            }
+            MonitoredTypes::SequenceEpochChanged(_, new_epoch) => format!("news/epoch/{new_epoch}"),
+            MonitoredTypes::Reorg(_, new_tip, _) => format!("news/reorg/{new_tip}"),
+            MonitoredTypes::LowWorkReorg(height, ..) => format!("news/low_work_reorg/{height}"),
+            MonitoredTypes::HealthStateChanged(state, _) => format!("news/health/{state:?}"),
+            MonitoredTypes::ScriptPubKeyTransaction(_, extra_data, tx_id, vout) => {
+                format!("news/script/{tx_id}/{vout}/{extra_data}")
+            }
+            MonitoredTypes::OpReturnTransaction(op_return_prefix, extra_data, tx_id, _) => {
+                format!(
+                    "news/op_return/{tx_id}/{}/{extra_data}",
+                    hex::encode(op_return_prefix)
+                )
+            }
+            MonitoredTypes::AlternativeResolved(winner, _, extra_data) => {
+                format!("news/alternatives/{winner}/{extra_data}")
+            }
+            MonitoredTypes::MonitorExpired(tx_id, Some(vout), extra_data, _) => {
+                format!("news/expired/{tx_id}/{vout}/{extra_data}")
+            }
+            MonitoredTypes::MonitorExpired(tx_id, None, extra_data, _) => {
+                format!("news/expired/{tx_id}/{extra_data}")
+            }
+            MonitoredTypes::ManuallyResolved(target, _) => {
+                format!("news/resolved/{}", target.path())
+            }
+            MonitoredTypes::Composite(id) => format!("news/composite/{id}"),
+        }
+    }
+
+    /// Monitor a news belongs to, replacement lifecycle news are never snoozed.
+    /// Monitor `data` is a news of, None for the news that don't come from a monitor.
+    pub(crate) fn snooze_target(data: &MonitoredTypes) -> Option<SnoozeTarget> {
+        match data {
+            MonitoredTypes::Transaction(tx_id, extra_data)
+            | MonitoredTypes::OrphanedTransaction(tx_id, extra_data) => {
+                Some(SnoozeTarget::Transaction(*tx_id, extra_data.clone()))
+            }
+            MonitoredTypes::RskPeginTransaction(..) => Some(SnoozeTarget::RskPegin),
+            MonitoredTypes::SpendingUTXOTransaction(tx_id, vout, extra_data, _)
+            | MonitoredTypes::SpendingUTXOUnconfirmed(tx_id, vout, extra_data, _)
+            | MonitoredTypes::MinorSpend(tx_id, vout, extra_data, _) => Some(
+                SnoozeTarget::SpendingUTXOTransaction(*tx_id, *vout, extra_data.clone()),
+            ),
+            MonitoredTypes::NewBlock(..) => Some(SnoozeTarget::NewBlock),
+            MonitoredTypes::ScriptPubKeyTransaction(script_pubkey, extra_data, ..) => Some(
+                SnoozeTarget::ScriptPubKey(script_pubkey.clone(), extra_data.clone()),
+            ),
+            MonitoredTypes::OpReturnTransaction(op_return_prefix, extra_data, ..) => Some(
+                SnoozeTarget::OpReturnPrefix(op_return_prefix.clone(), extra_data.clone()),
+            ),
+            MonitoredTypes::MonitorExpired(tx_id, Some(vout), extra_data, _) => Some(
+                SnoozeTarget::SpendingUTXOTransaction(*tx_id, *vout, extra_data.clone()),
+            ),
+            MonitoredTypes::MonitorExpired(tx_id, None, extra_data, _) => {
+                Some(SnoozeTarget::Transaction(*tx_id, extra_data.clone()))
+            }
+            MonitoredTypes::MonitorReplaced(..)
+            | MonitoredTypes::ReplacedTransactionConfirmed(..)
+            | MonitoredTypes::SequenceEpochChanged(..)
+            | MonitoredTypes::Reorg(..)
+            | MonitoredTypes::LowWorkReorg(..)
+            | MonitoredTypes::AlternativeResolved(..)
+            | MonitoredTypes::HealthStateChanged(..)
+            | MonitoredTypes::ManuallyResolved(..)
+            | MonitoredTypes::Composite(_) => None,
+        }
+    }
+
+    /// Monitor whose metadata goes with `data`: the one of `snooze_target`, the replacement of a
+    /// replaced transaction, the winner of the alternatives or the monitor manually resolved.
+    pub(crate) fn metadata_target(data: &MonitoredTypes) -> Option<SnoozeTarget> {
+        match data {
+            MonitoredTypes::MonitorReplaced(_, new, extra_data)
+            | MonitoredTypes::ReplacedTransactionConfirmed(_, new, extra_data) => {
+                Some(SnoozeTarget::Transaction(*new, extra_data.clone()))
+            }
+            MonitoredTypes::AlternativeResolved(winner, _, extra_data) => {
+                Some(SnoozeTarget::Transaction(*winner, extra_data.clone()))
+            }
+            MonitoredTypes::ManuallyResolved(target, _) => Some(target.clone()),
+            _ => MonitorStore::snooze_target(data),
+        }
+    }
+}
+
+impl<S: Deref<Target = Storage>> MonitorStoreApi for MonitorStore<S> {
+    fn set_pending_work(&self, is_pending_work: bool) -> Result<(), MonitorStoreError> {
+        let key = self.get_key(MonitorKey::PendingWork);
+        self.write_typed(&key, is_pending_work, None)?;
+        Ok(())
+    }
+
+    fn has_pending_work(&self) -> Result<bool, MonitorStoreError> {
+        let key = self.get_key(MonitorKey::PendingWork);
+        let pending_work = self.read_typed::<bool>(&key)?.unwrap_or(false);
+        Ok(pending_work)
+    }
+
+    fn request_reload(&self) -> Result<(), MonitorStoreError> {
+        let key = self.get_key(MonitorKey::ReloadRequest);
+        self.write_typed(&key, true, None)?;
+        Ok(())
+    }
+
+    fn take_reload_request(&self) -> Result<bool, MonitorStoreError> {
+        let key = self.get_key(MonitorKey::ReloadRequest);
+        let requested = self.read_typed::<bool>(&key)?.unwrap_or(false);
+        if requested {
+            self.write_typed(&key, false, None)?;
+        }
+        Ok(requested)
+    }
+
+    fn get_network(&self) -> Result<Option<Network>, MonitorStoreError> {
+        self.read_typed(&self.get_key(MonitorKey::Network))
+    }
+
+    fn set_network(&self, network: Network) -> Result<(), MonitorStoreError> {
+        self.write_typed(&self.get_key(MonitorKey::Network), network, None)
+    }
+
+    fn check_writable(&self) -> Result<(), MonitorStoreError> {
+        let key = self.get_key(MonitorKey::WriteProbe);
+        self.write_typed(&key, true, None)?;
+        self.delete_key(&key, None)
+    }
+
+    fn get_schema_version(&self) -> Result<u32, MonitorStoreError> {
+        let key = self.get_key(MonitorKey::SchemaVersion);
+        let version: u32 = self.read_typed(&key)?.unwrap_or_default();
+        Ok(version)
+    }
+
+    fn get_news_epoch(&self) -> Result<u64, MonitorStoreError> {
+        let key = self.get_key(MonitorKey::NewsEpoch);
+        let epoch: u64 = self.read_typed(&key)?.unwrap_or_default();
+        Ok(epoch)
+    }
+
+    fn recover_news_sequence(&self) -> Result<Option<(u64, u64)>, MonitorStoreError> {
+        let key = self.get_key(MonitorKey::NewsSequence);
+        let sequence: u64 = self.read_typed(&key)?.unwrap_or_default();
+        let max_sequence = self.max_news_sequence()?;
+
+        if sequence >= max_sequence {
+            return Ok(None);
+        }
+
+        let old_epoch = self.get_news_epoch()?;
+        let new_epoch = old_epoch + 1;
+
+        let epoch_key = self.get_key(MonitorKey::NewsEpoch);
+        self.write_typed(&key, max_sequence, None)?;
+        self.write_typed(&epoch_key, new_epoch, None)?;
+
+        Ok(Some((old_epoch, new_epoch)))
+    }
+
+    fn save_reorg_impact(&self, impact: ReorgImpact) -> Result<(), MonitorStoreError> {
+        let key = self.get_key(MonitorKey::LastReorgImpact);
+        self.write_typed(&key, impact, None)?;
+        Ok(())
+    }
+
+    fn get_last_reorg_impact(&self) -> Result<Option<ReorgImpact>, MonitorStoreError> {
+        let key = self.get_key(MonitorKey::LastReorgImpact);
+        let impact: Option<ReorgImpact> = self.read_typed(&key)?;
+        Ok(impact)
+    }
+
+    fn get_block_work(&self) -> Result<Vec<BlockWorkEntry>, MonitorStoreError> {
+        let key = self.get_key(MonitorKey::BlockWork);
+        let blocks: Vec<BlockWorkEntry> = self.read_typed(&key)?.unwrap_or_default();
+        Ok(blocks)
+    }
+
+    fn set_block_work(&self, mut blocks: Vec<BlockWorkEntry>) -> Result<(), MonitorStoreError> {
+        blocks.sort_by_key(|block| block.height);
+        let key = self.get_key(MonitorKey::BlockWork);
+        self.write_typed(&key, blocks, None)?;
+        Ok(())
+    }
+
+    fn get_external_chain(&self) -> Result<Vec<ExternalBlockEntry>, MonitorStoreError> {
+        let key = self.get_key(MonitorKey::ExternalChain);
+        let blocks: Vec<ExternalBlockEntry> = self.read_typed(&key)?.unwrap_or_default();
+        Ok(blocks)
+    }
+
+    fn set_external_chain(
+        &self,
+        mut blocks: Vec<ExternalBlockEntry>,
+    ) -> Result<(), MonitorStoreError> {
+        blocks.sort_by_key(|block| block.height);
+        let key = self.get_key(MonitorKey::ExternalChain);
+        self.write_typed(&key, blocks, None)?;
+        Ok(())
+    }
+
+    fn get_news_history(
+        &self,
+        from: u64,
+        limit: usize,
+    ) -> Result<Vec<NewsHistoryEntry>, MonitorStoreError> {
+        let start: u64 = self
+            .read_typed(&self.get_key(MonitorKey::NewsHistoryStart))?
+            .unwrap_or_default();
+        let last: u64 = self
+            .read_typed(&self.get_key(MonitorKey::NewsSequence))?
+            .unwrap_or_default();
+
+        // Sequences skipped by a store restore have no entry
+        let mut entries = Vec::new();
+        for seq in from.max(start)..=last {
+            if entries.len() >= limit {
+                break;
+            }
+            if let Some(entry) = self.read_typed(&self.get_key(MonitorKey::NewsHistory(seq)))? {
+                entries.push(entry);
+            }
+        }
+
+        Ok(entries)
+    }
+
+    fn prune_news_history(&self, written_before: BlockHeight) -> Result<u64, MonitorStoreError> {
+        let start_key = self.get_key(MonitorKey::NewsHistoryStart);
+        let start: u64 = self.read_typed(&start_key)?.unwrap_or_default();
+        let last: u64 = self
+            .read_typed(&self.get_key(MonitorKey::NewsSequence))?
+            .unwrap_or_default();
+
+        // Written in sequence order, the entries are removed up to the first one to keep
+        let mut first_kept = start;
+        let mut removed = 0;
+        while first_kept <= last {
+            let key = self.get_key(MonitorKey::NewsHistory(first_kept));
+            match self.read_typed::<NewsHistoryEntry>(&key)? {
+                Some(entry) if entry.height >= written_before => break,
+                Some(_) => {
+                    self.delete_key(&key, None)?;
+                    removed += 1;
+                }
+                None => {}
+            }
+            first_kept += 1;
+        }
+
+        if first_kept != start {
+            self.write_typed(&start_key, first_kept, None)?;
+        }
+
+        let disputed_key = self.get_key(MonitorKey::OrphanDisputedNews);
+        let mut disputed: Vec<u64> = self.read_typed(&disputed_key)?.unwrap_or_default();
+        if disputed.iter().any(|sequence| *sequence < first_kept) {
+            disputed.retain(|sequence| *sequence >= first_kept);
+            self.write_typed(&disputed_key, disputed, None)?;
+        }
+
+        Ok(removed)
+    }
+
+    fn mark_news_orphan_disputed(&self, data: &MonitoredTypes) -> Result<bool, MonitorStoreError> {
+        let sequence: u64 = self
+            .read_typed(&self.get_key(MonitorKey::NewsSequence))?
+            .unwrap_or_default();
+
+        // A news not written again for the same block leaves another news as the last entry
+        let written = self
+            .read_typed::<NewsHistoryEntry>(&self.get_key(MonitorKey::NewsHistory(sequence)))?
+            .is_some_and(|entry| entry.news == *data);
+        if !written {
+            return Ok(false);
+        }
+
+        let key = self.get_key(MonitorKey::OrphanDisputedNews);
+        let mut disputed: Vec<u64> = self.read_typed(&key)?.unwrap_or_default();
+        if !disputed.contains(&sequence) {
+            disputed.push(sequence);
+            self.write_typed(&key, disputed, None)?;
+        }
+
+        Ok(true)
+    }
+
+    fn get_orphan_disputed_news(&self) -> Result<Vec<u64>, MonitorStoreError> {
+        Ok(self
+            .read_typed(&self.get_key(MonitorKey::OrphanDisputedNews))?
+            .unwrap_or_default())
+    }
+
+    fn append_chain_log(&self, entry: ChainLogEntry, cap: u32) -> Result<(), MonitorStoreError> {
+        // Sequences of the first entry kept and of the next one
+        let bounds_key = self.get_key(MonitorKey::ChainLogBounds);
+        let (mut first, next): (u64, u64) = self.read_typed(&bounds_key)?.unwrap_or_default();
+
+        if next > first {
+            let last: Option<ChainLogEntry> =
+                self.read_typed(&self.get_key(MonitorKey::ChainLog(next - 1)))?;
+            if last.is_some_and(|last| last.hash == entry.hash) {
+                return Ok(());
+            }
+        }
+
+        self.write_typed(&self.get_key(MonitorKey::ChainLog(next)), entry, None)?;
+
+        let next = next + 1;
+        while next - first > cap as u64 {
+            self.delete_key(&self.get_key(MonitorKey::ChainLog(first)), None)?;
+            first += 1;
+        }
+
+        self.write_typed(&bounds_key, (first, next), None)?;
+        Ok(())
+    }
+
+    fn get_chain_log(&self) -> Result<Vec<ChainLogEntry>, MonitorStoreError> {
+        let (first, next): (u64, u64) = self
+            .read_typed(&self.get_key(MonitorKey::ChainLogBounds))?
+            .unwrap_or_default();
+
+        let mut entries = Vec::new();
+        for seq in first..next {
+            if let Some(entry) = self.read_typed(&self.get_key(MonitorKey::ChainLog(seq)))? {
+                entries.push(entry);
+            }
+        }
+
+        Ok(entries)
+    }
+
+    fn get_chain_log_tail(
+        &self,
+        from_height: BlockHeight,
+    ) -> Result<Vec<ChainLogEntry>, MonitorStoreError> {
+        let (first, next): (u64, u64) = self
+            .read_typed(&self.get_key(MonitorKey::ChainLogBounds))?
+            .unwrap_or_default();
+
+        let mut entries = Vec::new();
+        for seq in (first..next).rev() {
+            let Some(entry) =
+                self.read_typed::<ChainLogEntry>(&self.get_key(MonitorKey::ChainLog(seq)))?
+            else {
+                continue;
+            };
+            if entry.height < from_height {
+                break;
+            }
+            entries.push(entry);
+        }
+
+        entries.reverse();
+        Ok(entries)
+    }
+
+    fn get_monitor_block_hash(&self) -> Result<Option<BlockHash>, MonitorStoreError> {
+        let key = self.get_blockchain_key(BlockchainKey::CurrentBlockHash);
+        let hash: Option<BlockHash> = self.read_typed(&key)?;
+        Ok(hash)
+    }
+
+    fn update_monitor_block_hash(&self, hash: BlockHash) -> Result<(), MonitorStoreError> {
+        let key = self.get_blockchain_key(BlockchainKey::CurrentBlockHash);
+        self.write_typed(&key, hash, None)?;
+        Ok(())
+    }
+
+    fn get_monitor_height(&self) -> Result<BlockHeight, MonitorStoreError> {
+        let last_block_height_key = self.get_blockchain_key(BlockchainKey::CurrentBlockHeight);
+        let last_block_height = self
+            .read_typed::<BlockHeight>(&last_block_height_key)?
+            .unwrap_or_default();
+
+        Ok(last_block_height)
+    }
+
+    fn update_monitor_height(&self, height: BlockHeight) -> Result<(), MonitorStoreError> {
+        let last_block_height_key = self.get_blockchain_key(BlockchainKey::CurrentBlockHeight);
+        self.write_typed(&last_block_height_key, height, None)?;
+        Ok(())
+    }
+
+    fn get_news(&self) -> Result<Vec<MonitoredTypes>, MonitorStoreError> {
+        let news = self.get_sequenced_news()?;
+        Ok(news.into_iter().map(|(_, news)| news).collect())
+    }
+
+    fn get_sequenced_news(&self) -> Result<Vec<(u64, MonitoredTypes)>, MonitorStoreError> {
+        let news = self
+            .get_news_acks()?
+            .into_iter()
+            .filter(|(_, ack)| !ack.acknowledged)
+            .map(|(news, ack)| (ack.sequence, news))
+            .collect();
+
+        Ok(news)
+    }
+
+    fn get_news_page(
+        &self,
+        after: Option<NewsCursor>,
+        limit: usize,
+    ) -> Result<Vec<(NewsCursor, MonitoredTypes)>, MonitorStoreError> {
+        let mut news: Vec<(NewsCursor, MonitoredTypes)> = self
+            .get_sequenced_news()?
+            .into_iter()
+            .map(|(sequence, news)| {
+                let id = MonitorStore::news_fingerprint_id(&news);
+                (NewsCursor { sequence, id }, news)
+            })
+            .filter(|(cursor, _)| after.as_ref().is_none_or(|after| cursor > after))
+            .collect();
+
+        news.sort_by(|(a, _), (b, _)| a.cmp(b));
+        news.truncate(limit);
+
+        Ok(news)
+    }
+
+    fn get_news_count(&self) -> Result<usize, MonitorStoreError> {
+        self.count_pending_news(usize::MAX)
+    }
+
+    fn has_news(&self) -> Result<bool, MonitorStoreError> {
+        Ok(self.count_pending_news(1)? > 0)
+    }
+
+    fn get_news_acks(&self) -> Result<Vec<(MonitoredTypes, NewsAck)>, MonitorStoreError> {
+        let mut news = Vec::new();
+
+        let key = self.get_key(MonitorKey::TransactionsNews);
+        let txs_news: Vec<TransactionNewsEntry> = self.read_typed(&key)?.unwrap_or_default();
+
+        for entry in txs_news {
+            news.push((
+                MonitoredTypes::Transaction(entry.tx_id, entry.extra_data),
+                entry.ack,
+            ));
+        }
+
+        let orphaned_key = self.get_key(MonitorKey::OrphanedTransactionsNews);
+        let orphaned_news: Vec<TransactionNewsEntry> =
+            self.read_typed(&orphaned_key)?.unwrap_or_default();
+
+        for entry in orphaned_news {
+            news.push((
+                MonitoredTypes::OrphanedTransaction(entry.tx_id, entry.extra_data),
+                entry.ack,
+            ));
+        }
+
+        let rsk_news_key = self.get_key(MonitorKey::RskPeginTransactionsNews);
+        let rsk_news: Vec<RskPeginNewsEntry> = self.read_typed(&rsk_news_key)?.unwrap_or_default();
+
+        for entry in rsk_news {
+            news.push((
+                MonitoredTypes::RskPeginTransaction(entry.tx_id, entry.data),
+                entry.ack,
+            ));
+        }
+
+        let spending_news_key = self.get_key(MonitorKey::SpendingUTXOTransactionsNews);
+        let spending_news: Vec<SpendingUTXONewsEntry> =
+            self.read_typed(&spending_news_key)?.unwrap_or_default();
+
+        for entry in spending_news {
+            news.push((
+                MonitoredTypes::SpendingUTXOTransaction(
+                    entry.tx_id,
+                    entry.utxo_index,
+                    entry.extra_data,
+                    entry.spender_tx_id,
+                ),
+                entry.ack,
+            ));
+        }
+
+        let minor_news_key = self.get_key(MonitorKey::MinorSpendNews);
+        let minor_news: Vec<SpendingUTXONewsEntry> =
+            self.read_typed(&minor_news_key)?.unwrap_or_default();
+
+        for entry in minor_news {
+            news.push((
+                MonitoredTypes::MinorSpend(
+                    entry.tx_id,
+                    entry.utxo_index,
+                    entry.extra_data,
+                    entry.spender_tx_id,
+                ),
+                entry.ack,
+            ));
+        }
+
+        let unconfirmed_news_key = self.get_key(MonitorKey::SpendingUTXOUnconfirmedNews);
+        let unconfirmed_news: Vec<SpendingUTXONewsEntry> =
+            self.read_typed(&unconfirmed_news_key)?.unwrap_or_default();
+
+        for entry in unconfirmed_news {
+            news.push((
+                MonitoredTypes::SpendingUTXOUnconfirmed(
+                    entry.tx_id,
+                    entry.utxo_index,
+                    entry.extra_data,
+                    entry.spender_tx_id,
+                ),
+                entry.ack,
+            ));
+        }
+
+        let replacement_news_key = self.get_key(MonitorKey::ReplacementNews);
+        let replacement_news: Vec<ReplacementNewsEntry> =
+            self.read_typed(&replacement_news_key)?.unwrap_or_default();
+
+        for entry in replacement_news {
+            let data = if entry.old_confirmed {
+                MonitoredTypes::ReplacedTransactionConfirmed(
+                    entry.old_tx_id,
+                    entry.new_tx_id,
+                    entry.extra_data,
+                )
+            } else {
+                MonitoredTypes::MonitorReplaced(entry.old_tx_id, entry.new_tx_id, entry.extra_data)
+            };
+            news.push((data, entry.ack));
+        }
+
+        let script_news_key = self.get_key(MonitorKey::ScriptPubKeyNews);
+        let script_news: Vec<ScriptPubKeyNewsEntry> =
+            self.read_typed(&script_news_key)?.unwrap_or_default();
+
+        for entry in script_news {
+            news.push((
+                MonitoredTypes::ScriptPubKeyTransaction(
+                    entry.script_pubkey,
+                    entry.extra_data,
+                    entry.tx_id,
+                    entry.vout,
+                ),
+                entry.ack,
+            ));
+        }
+
+        let op_return_news_key = self.get_key(MonitorKey::OpReturnNews);
+        let op_return_news: Vec<OpReturnNewsEntry> =
+            self.read_typed(&op_return_news_key)?.unwrap_or_default();
+
+        for entry in op_return_news {
+            news.push((
+                MonitoredTypes::OpReturnTransaction(
+                    entry.prefix,
+                    entry.extra_data,
+                    entry.tx_id,
+                    entry.payload,
+                ),
+                entry.ack,
+            ));
+        }
+
+        let alternative_news_key = self.get_key(MonitorKey::AlternativeNews);
+        let alternative_news: Vec<AlternativeNewsEntry> =
+            self.read_typed(&alternative_news_key)?.unwrap_or_default();
+
+        for entry in alternative_news {
+            news.push((
+                MonitoredTypes::AlternativeResolved(entry.winner, entry.losers, entry.extra_data),
+                entry.ack,
+            ));
+        }
+
+        let expired_news_key = self.get_key(MonitorKey::ExpiredMonitorsNews);
+        let expired_news: Vec<ExpiredMonitorNewsEntry> =
+            self.read_typed(&expired_news_key)?.unwrap_or_default();
+
+        for entry in expired_news {
+            news.push((
+                MonitoredTypes::MonitorExpired(
+                    entry.tx_id,
+                    entry.vout,
+                    entry.extra_data,
+                    entry.expires_at_height,
+                ),
+                entry.ack,
+            ));
+        }
+
+        for entry in self.read_block_news()? {
+            news.push((
+                MonitoredTypes::NewBlock(entry.ack.block_hash, entry.height),
+                entry.ack,
+            ));
+        }
+
+        let epoch_news_key = self.get_key(MonitorKey::EpochNews);
+        let epoch_news: Option<EpochNewsEntry> = self.read_typed(&epoch_news_key)?;
+
+        if let Some(entry) = epoch_news {
+            news.push((
+                MonitoredTypes::SequenceEpochChanged(entry.old_epoch, entry.new_epoch),
+                entry.ack,
+            ));
+        }
+
+        let reorg_news_key = self.get_key(MonitorKey::ReorgNews);
+        let reorg_news: Option<ReorgNewsEntry> = self.read_typed(&reorg_news_key)?;
+
+        if let Some(entry) = reorg_news {
+            news.push((
+                MonitoredTypes::Reorg(entry.old_tip, entry.new_tip, entry.summary),
+                entry.ack,
+            ));
+        }
+
+        let low_work_news_key = self.get_key(MonitorKey::LowWorkReorgNews);
+        let low_work_news: Option<LowWorkReorgNewsEntry> = self.read_typed(&low_work_news_key)?;
+
+        if let Some(entry) = low_work_news {
+            news.push((
+                MonitoredTypes::LowWorkReorg(entry.height, entry.old_work, entry.new_work),
+                entry.ack,
+            ));
+        }
+
+        let health_news_key = self.get_key(MonitorKey::HealthNews);
+        let health_news: Option<HealthNewsEntry> = self.read_typed(&health_news_key)?;
+
+        if let Some(entry) = health_news {
+            news.push((
+                MonitoredTypes::HealthStateChanged(entry.state, entry.score),
+                entry.ack,
+            ));
+        }
+
+        let resolution_news_key = self.get_key(MonitorKey::ManualResolutionNews);
+        let resolution_news: Vec<ManualResolutionNewsEntry> =
+            self.read_typed(&resolution_news_key)?.unwrap_or_default();
+
+        for entry in resolution_news {
+            news.push((
+                MonitoredTypes::ManuallyResolved(entry.target, entry.note),
+                entry.ack,
+            ));
+        }
+
+        let composite_news_key = self.get_key(MonitorKey::CompositeNews);
+        let composite_news: Vec<CompositeNewsEntry> =
+            self.read_typed(&composite_news_key)?.unwrap_or_default();
+
+        for entry in composite_news {
+            news.push((MonitoredTypes::Composite(entry.id), entry.ack));
+        }
+
+        Ok(news)
+    }
+
+    fn update_news(
+        &self,
+        data: MonitoredTypes,
+        current_block_hash: BlockHash,
+    ) -> Result<bool, MonitorStoreError> {
+        let height = self.get_monitor_height()?;
+        self.update_news_at(data, current_block_hash, height)
+    }
+
+    fn update_news_at(
+        &self,
+        data: MonitoredTypes,
+        current_block_hash: BlockHash,
+        height: BlockHeight,
+    ) -> Result<bool, MonitorStoreError> {
+        // Notification will be updated if the block_hash is different
+        // If the notification is already in the store, it will be updated with the new block_hash and ack set to false.
+
+        // Reprocessing a block (e.g. after a crash before the height was updated) must not touch news already recorded for it
+        let id = MonitorStore::news_fingerprint_id(&data);
+        if self.is_fingerprint_recorded(&id, current_block_hash)? {
+            return Ok(false);
+        }
+
+        // Every news written takes the next sequence, a news already stored for the block leaves it
+        let sequence_key = self.get_key(MonitorKey::NewsSequence);
+        let sequence: Option<u64> = self.read_typed(&sequence_key)?;
+        self.write_news(data, current_block_hash, height)?;
+        let written = self.read_typed::<u64>(&sequence_key)? != sequence;
+
+        self.record_fingerprint(id, current_block_hash, height)?;
+
+        Ok(written)
+    }
+
+    /// Writes the news `data` found in `current_block_hash` at `height`, see `update_news_at`.
+    fn write_news(
+        &self,
+        data: MonitoredTypes,
+        current_block_hash: BlockHash,
+        height: BlockHeight,
+    ) -> Result<(), MonitorStoreError> {
+        // As written, for the news history
+        let news = data.clone();
+
+        // Minor spends are kept apart, with the same entries as the other spends
+        let utxo_news_key = match data {
+            MonitoredTypes::MinorSpend(..) => self.get_key(MonitorKey::MinorSpendNews),
+            _ => self.get_key(MonitorKey::SpendingUTXOTransactionsNews),
+        };
+
+        match data {
+            MonitoredTypes::Transaction(tx_id, extra_data) => {
+                let key = self.get_key(MonitorKey::TransactionsNews);
+                let mut txs_news: Vec<TransactionNewsEntry> =
+                    self.read_typed(&key)?.unwrap_or_default();
+
+                // Check if news already exists for this (tx_id, extra_data) combination
+                // Different extra_data should generate separate news entries
+                let is_new_news = txs_news
+                    .iter()
+                    .position(|e| e.tx_id == tx_id && e.extra_data == extra_data);
+
+                match is_new_news {
+                    None => {
+                        // Insert news with current block hash and ack in false
+                        txs_news.push(TransactionNewsEntry {
+                            tx_id,
+                            extra_data: extra_data.clone(),
+                            ack: self.new_news_ack(&news, current_block_hash, height)?,
+                        });
+                    }
+                    Some(pos) => {
+                        if txs_news[pos].ack.block_hash != current_block_hash {
+                            // Replace the notification with the new block hash
+                            txs_news[pos] = TransactionNewsEntry {
+                                tx_id,
+                                extra_data: extra_data.clone(),
+                                ack: self.new_news_ack(&news, current_block_hash, height)?,
+                            };
+                        }
+                    }
+                }
+
+                self.write_typed(&key, &txs_news, None)?;
+            }
+            MonitoredTypes::OrphanedTransaction(tx_id, extra_data) => {
+                let key = self.get_key(MonitorKey::OrphanedTransactionsNews);
+                let mut orphaned_news: Vec<TransactionNewsEntry> =
+                    self.read_typed(&key)?.unwrap_or_default();
+
+                // An orphaned transaction that confirms and is orphaned again replaces its news
+                orphaned_news.retain(|e| e.tx_id != tx_id || e.extra_data != extra_data);
+                orphaned_news.push(TransactionNewsEntry {
+                    tx_id,
+                    extra_data,
+                    ack: self.new_news_ack(&news, current_block_hash, height)?,
+                });
+
+                self.write_typed(&key, &orphaned_news, None)?;
+            }
+            MonitoredTypes::RskPeginTransaction(tx_id, data) => {
+                let rsk_news_key = self.get_key(MonitorKey::RskPeginTransactionsNews);
+                let mut rsk_news: Vec<RskPeginNewsEntry> =
+                    self.read_typed(&rsk_news_key)?.unwrap_or_default();
+
+                // Check if news already exists for this tx_id
+                // RskPeginTransaction doesn't have extra_data, so we only check by tx_id
+                let is_new_news = rsk_news.iter().position(|e| e.tx_id == tx_id);
+
+                match is_new_news {
+                    None => rsk_news.push(RskPeginNewsEntry {
+                        tx_id,
+                        ack: self.new_news_ack(&news, current_block_hash, height)?,
+                        data,
+                    }),
+                    Some(pos) => {
+                        if rsk_news[pos].ack.block_hash != current_block_hash {
+                            // Replace the notification with the new block hash
+                            rsk_news[pos] = RskPeginNewsEntry {
+                                tx_id,
+                                ack: self.new_news_ack(&news, current_block_hash, height)?,
+                                data,
+                            };
+                        }
+                    }
+                }
+
+                self.write_typed(&rsk_news_key, &rsk_news, None)?;
+            }
+            MonitoredTypes::SpendingUTXOTransaction(
+                tx_id,
+                utxo_index,
+                extra_data,
+                spender_tx_id,
+            )
+            | MonitoredTypes::MinorSpend(tx_id, utxo_index, extra_data, spender_tx_id) => {
+                let mut utxo_news: Vec<SpendingUTXONewsEntry> =
+                    self.read_typed(&utxo_news_key)?.unwrap_or_default();
+
+                // Check if news already exists for this (tx_id, utxo_index, extra_data)
+                // Different extra_data should generate separate news entries
+                let is_new_news = utxo_news.iter().position(|e| {
+                    e.tx_id == tx_id && e.utxo_index == utxo_index && e.extra_data == extra_data
+                });
+
+                match is_new_news {
+                    None => utxo_news.push(SpendingUTXONewsEntry {
+                        tx_id,
+                        utxo_index,
+                        extra_data: extra_data.clone(),
+                        spender_tx_id,
+                        ack: self.new_news_ack(&news, current_block_hash, height)?,
+                    }),
+                    Some(pos) => {
+                        // Replace the notification only if the block hash is different
+                        if utxo_news[pos].ack.block_hash != current_block_hash {
+                            utxo_news[pos] = SpendingUTXONewsEntry {
+                                tx_id,
+                                utxo_index,
+                                extra_data: extra_data.clone(),
+                                spender_tx_id,
+                                ack: self.new_news_ack(&news, current_block_hash, height)?,
+                            };
+                        }
+                    }
+                }
+
+                self.write_typed(&utxo_news_key, &utxo_news, None)?;
+            }
+            MonitoredTypes::SpendingUTXOUnconfirmed(
+                tx_id,
+                utxo_index,
+                extra_data,
+                spender_tx_id,
+            ) => {
+                let key = self.get_key(MonitorKey::SpendingUTXOUnconfirmedNews);
+                let mut unconfirmed_news: Vec<SpendingUTXONewsEntry> =
+                    self.read_typed(&key)?.unwrap_or_default();
+
+                let is_new_news = unconfirmed_news.iter().position(|e| {
+                    e.tx_id == tx_id && e.utxo_index == utxo_index && e.extra_data == extra_data
+                });
+
+                // A spend stays in the mempool for several blocks, news is only sent again if the spender changes
+                if matches!(is_new_news, Some(pos) if unconfirmed_news[pos].spender_tx_id == spender_tx_id)
+                {
+                    return Ok(());
+                }
+
+                let entry = SpendingUTXONewsEntry {
+                    tx_id,
+                    utxo_index,
+                    extra_data,
+                    spender_tx_id,
+                    ack: self.new_news_ack(&news, current_block_hash, height)?,
+                };
+
+                match is_new_news {
+                    None => unconfirmed_news.push(entry),
+                    Some(pos) => unconfirmed_news[pos] = entry,
+                }
+
+                self.write_typed(&key, &unconfirmed_news, None)?;
+            }
+            MonitoredTypes::ScriptPubKeyTransaction(script_pubkey, extra_data, tx_id, vout) => {
+                let key = self.get_key(MonitorKey::ScriptPubKeyNews);
+                let mut script_news: Vec<ScriptPubKeyNewsEntry> =
+                    self.read_typed(&key)?.unwrap_or_default();
+
+                // Each output found for a context is a separate news entry
+                let is_new_news = script_news
+                    .iter()
+                    .position(|e| e.tx_id == tx_id && e.vout == vout && e.extra_data == extra_data);
+
+                let entry = ScriptPubKeyNewsEntry {
+                    script_pubkey,
+                    extra_data,
+                    tx_id,
+                    vout,
+                    ack: self.new_news_ack(&news, current_block_hash, height)?,
+                };
+
+                match is_new_news {
+                    None => script_news.push(entry),
+                    Some(pos) => script_news[pos] = entry,
+                }
+
+                self.write_typed(&key, &script_news, None)?;
+            }
+            MonitoredTypes::OpReturnTransaction(op_return_prefix, extra_data, tx_id, payload) => {
+                let key = self.get_key(MonitorKey::OpReturnNews);
+                let mut op_return_news: Vec<OpReturnNewsEntry> =
+                    self.read_typed(&key)?.unwrap_or_default();
+
+                let is_new_news = op_return_news.iter().position(|e| {
+                    e.tx_id == tx_id && e.prefix == op_return_prefix && e.extra_data == extra_data
+                });
+
+                let entry = OpReturnNewsEntry {
+                    prefix: op_return_prefix,
+                    extra_data,
+                    tx_id,
+                    payload,
+                    ack: self.new_news_ack(&news, current_block_hash, height)?,
+                };
+
+                match is_new_news {
+                    None => op_return_news.push(entry),
+                    Some(pos) => op_return_news[pos] = entry,
+                }
+
+                self.write_typed(&key, &op_return_news, None)?;
+            }
+            MonitoredTypes::NewBlock(hash, block_height) => {
+                // Each block gets its own news, queued until acknowledged
+                let mut queue = self.read_block_news()?;
+
+                if !queue.iter().any(|entry| entry.ack.block_hash == hash) {
+                    queue.retain(|entry| !entry.ack.acknowledged);
+                    let height = block_height.unwrap_or(height);
+                    queue.push(NewBlockNewsEntry {
+                        height: block_height,
+                        ack: self.new_news_ack(&news, current_block_hash, height)?,
+                    });
+                    self.write_typed(&self.get_key(MonitorKey::NewBlockNews), &queue, None)?;
+                }
+            }
+            MonitoredTypes::MonitorReplaced(old_tx_id, new_tx_id, extra_data) => {
+                self.add_replacement_news(
+                    vec![ReplacementNewsEntry {
+                        old_tx_id,
+                        new_tx_id,
+                        extra_data,
+                        old_confirmed: false,
+                        ack: self.new_news_ack(&news, current_block_hash, height)?,
+                    }],
+                    None,
+                )?;
+            }
+            MonitoredTypes::ReplacedTransactionConfirmed(old_tx_id, new_tx_id, extra_data) => {
+                self.add_replacement_news(
+                    vec![ReplacementNewsEntry {
+                        old_tx_id,
+                        new_tx_id,
+                        extra_data,
+                        old_confirmed: true,
+                        ack: self.new_news_ack(&news, current_block_hash, height)?,
+                    }],
+                    None,
+                )?;
+            }
+            MonitoredTypes::SequenceEpochChanged(old_epoch, new_epoch) => {
+                let key = self.get_key(MonitorKey::EpochNews);
+                let entry = EpochNewsEntry {
+                    old_epoch,
+                    new_epoch,
+                    ack: self.new_news_ack(&news, current_block_hash, height)?,
+                };
+
+                // Only the latest epoch change is kept
+                self.write_typed(&key, entry, None)?;
+            }
+            MonitoredTypes::Reorg(old_tip, new_tip, summary) => {
+                let key = self.get_key(MonitorKey::ReorgNews);
+                let entry = ReorgNewsEntry {
+                    old_tip,
+                    new_tip,
+                    summary,
+                    ack: self.new_news_ack(&news, current_block_hash, height)?,
+                };
+
+                // Only the latest reorg is kept, as its report
+                self.write_typed(&key, entry, None)?;
+            }
+            MonitoredTypes::LowWorkReorg(height, old_work, new_work) => {
+                let key = self.get_key(MonitorKey::LowWorkReorgNews);
+                let entry = LowWorkReorgNewsEntry {
+                    height,
+                    old_work,
+                    new_work,
+                    ack: self.new_news_ack(&news, current_block_hash, height)?,
+                };
+
+                // Like the reorg news, only the latest one is kept
+                self.write_typed(&key, entry, None)?;
+            }
+            MonitoredTypes::HealthStateChanged(state, score) => {
+                let key = self.get_key(MonitorKey::HealthNews);
+                let entry = HealthNewsEntry {
+                    state,
+                    score,
+                    ack: self.new_news_ack(&news, current_block_hash, height)?,
+                };
+
+                // Only the current state matters
+                self.write_typed(&key, entry, None)?;
+            }
+            MonitoredTypes::AlternativeResolved(winner, losers, extra_data) => {
+                let key = self.get_key(MonitorKey::AlternativeNews);
+                let mut alternative_news: Vec<AlternativeNewsEntry> =
+                    self.read_typed(&key)?.unwrap_or_default();
+
+                // A race resolved again after a reorg replaces its news
+                alternative_news.retain(|e| {
+                    e.extra_data != extra_data
+                        || (e.winner != winner && !losers.contains(&e.winner))
+                });
+                alternative_news.push(AlternativeNewsEntry {
+                    winner,
+                    losers,
+                    extra_data,
+                    ack: self.new_news_ack(&news, current_block_hash, height)?,
+                });
+
+                self.write_typed(&key, &alternative_news, None)?;
+            }
+            MonitoredTypes::MonitorExpired(tx_id, vout, extra_data, expires_at_height) => {
+                let key = self.get_key(MonitorKey::ExpiredMonitorsNews);
+                let mut expired_news: Vec<ExpiredMonitorNewsEntry> =
+                    self.read_typed(&key)?.unwrap_or_default();
+
+                // A monitor registered again and expired again replaces its news
+                expired_news
+                    .retain(|e| e.tx_id != tx_id || e.vout != vout || e.extra_data != extra_data);
+                expired_news.push(ExpiredMonitorNewsEntry {
+                    tx_id,
+                    vout,
+                    extra_data,
+                    expires_at_height,
+                    ack: self.new_news_ack(&news, current_block_hash, height)?,
+                });
+
+                self.write_typed(&key, &expired_news, None)?;
+            }
+            MonitoredTypes::ManuallyResolved(target, note) => {
+                let entry = ManualResolutionNewsEntry {
+                    target,
+                    note,
+                    ack: self.new_news_ack(&news, current_block_hash, height)?,
+                };
+
+                self.add_resolution_news(entry, None)?;
+            }
+            MonitoredTypes::Composite(id) => {
+                let rules_key = self.get_key(MonitorKey::CompositeRules);
+                let mut rules: Vec<CompositeRule> =
+                    self.read_typed(&rules_key)?.unwrap_or_default();
+
+                // Sent once per rule, a canceled rule sends nothing
+                let Some(rule) = rules
+                    .iter_mut()
+                    .find(|rule| rule.id == id && rule.emitted_at.is_none())
+                else {
+                    return Ok(());
+                };
+                rule.emitted_at = Some(height);
+
+                let key = self.get_key(MonitorKey::CompositeNews);
+                let mut composite_news: Vec<CompositeNewsEntry> =
+                    self.read_typed(&key)?.unwrap_or_default();
+                composite_news.retain(|e| e.id != id);
+                composite_news.push(CompositeNewsEntry {
+                    id,
+                    ack: self.new_news_ack(&news, current_block_hash, height)?,
+                });
+
+                self.write_typed(&key, &composite_news, None)?;
+                self.write_typed(&rules_key, &rules, None)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn is_detection_recorded(
+        &self,
+        data: &MonitoredTypes,
+        block_hash: BlockHash,
+    ) -> Result<bool, MonitorStoreError> {
+        self.is_fingerprint_recorded(&Self::detection_fingerprint_id(data), block_hash)
+    }
+
+    fn record_detection(
+        &self,
+        data: MonitoredTypes,
+        block_hash: BlockHash,
+        height: BlockHeight,
+    ) -> Result<(), MonitorStoreError> {
+        self.record_fingerprint(Self::detection_fingerprint_id(&data), block_hash, height)
+    }
+
+    fn set_expiry(&self, key: &str, expires_at: BlockHeight) -> Result<(), MonitorStoreError> {
+        let expiries_key = self.get_key(MonitorKey::Expiries);
+        let mut expiries: Vec<ExpiryEntry> = self.read_typed(&expiries_key)?.unwrap_or_default();
+
+        match expiries.iter_mut().find(|e| e.key == key) {
+            Some(entry) => entry.expires_at = expires_at,
+            None => expiries.push(ExpiryEntry {
+                key: key.to_string(),
+                expires_at,
+            }),
+        }
+
+        self.write_typed(&expiries_key, &expiries, None)?;
+
+        Ok(())
+    }
+
+    fn sweep_expired(&self, current_height: BlockHeight) -> Result<u32, MonitorStoreError> {
+        let expiries_key = self.get_key(MonitorKey::Expiries);
+        let expiries: Vec<ExpiryEntry> = self.read_typed(&expiries_key)?.unwrap_or_default();
+
+        let (expired, retained): (Vec<_>, Vec<_>) = expiries
+            .into_iter()
+            .partition(|e| e.expires_at <= current_height);
+
+        if expired.is_empty() {
+            return Ok(0);
+        }
+
+        for entry in &expired {
+            self.store.delete(&entry.key)?;
+        }
+
+        self.write_typed(&expiries_key, &retained, None)?;
+
+        Ok(expired.len() as u32)
+    }
+
+    fn ack_news(&self, data: AckMonitorNews) -> Result<(), MonitorStoreError> {
+        // News not found are ignored, the batch reports them
+        self.ack_news_batch(vec![data])?;
+        Ok(())
+    }
+
+    fn ack_news_batch(
+        &self,
+        items: Vec<AckMonitorNews>,
+    ) -> Result<Vec<AckMonitorNews>, MonitorStoreError> {
+        // Grouped by the key their news are stored under, in the order of their first item
+        let mut groups: Vec<(String, MonitorKey, Vec<AckMonitorNews>)> = vec![];
+        for item in items {
+            let key = Self::news_key_of(&item);
+            let name = self.get_key(key);
+            match groups.iter_mut().find(|(group, ..)| *group == name) {
+                Some((.., acks)) => acks.push(item),
+                None => groups.push((name, key, vec![item])),
+            }
+        }
+
+        let transaction_id = self.store.begin_transaction();
+        let mut unknown = vec![];
+
+        for (_, key, acks) in groups {
+            match self.ack_news_group(key, acks, transaction_id) {
+                Ok(not_found) => unknown.extend(not_found),
+                Err(e) => {
+                    self.store.rollback_transaction(transaction_id)?;
+                    return Err(e);
+                }
+            }
+        }
+
+        self.store.commit_transaction(transaction_id)?;
+
+        Ok(unknown)
+    }
+
+    fn ack_all_news(&self) -> Result<usize, MonitorStoreError> {
+        // Read first, it may move the news stored by older versions in its own transaction
+        let mut block_news = self.read_block_news()?;
+        let transaction_id = self.store.begin_transaction();
+
+        match self.ack_all_news_in(&mut block_news, transaction_id) {
+            Ok(acked) => {
+                self.store.commit_transaction(transaction_id)?;
+                Ok(acked)
+            }
+            Err(e) => {
+                self.store.rollback_transaction(transaction_id)?;
+                Err(e)
+            }
+        }
+    }
+
+    fn get_monitors(&self) -> Result<Vec<TypesToMonitorStore>, MonitorStoreError> {
+        let monitors = self.list_monitors(true)?;
+        let total = monitors.len();
+
+        // A monitor stored twice by an older version would be evaluated, and reported, twice
+        let mut seen = HashSet::new();
+        let monitors: Vec<TypesToMonitorStore> = monitors
+            .into_iter()
+            .map(|info| info.monitor)
+            .filter(|monitor| {
+                let (kind, target, context) = monitor.canonical_key();
+                seen.insert((kind, target, context.to_string()))
+            })
+            .collect();
+
+        if monitors.len() < total {
+            warn!(
+                "Skipped {} duplicate monitors stored by an older version",
+                total - monitors.len()
+            );
+        }
+
+        Ok(monitors)
+    }
+
+    fn monitor_cursor(&self, kind: MonitorKind) -> Result<MonitorCursor, MonitorStoreError> {
+        match kind {
+            MonitorKind::Transaction => {
+                self.listed_cursor::<TransactionMonitor>(kind, MonitorKey::Transactions(true))
+            }
+            MonitorKind::SpendingUTXOTransaction => self.listed_cursor::<SpendingUTXOMonitor>(
+                kind,
+                MonitorKey::SpendingUTXOTransactions(true),
+            ),
+            MonitorKind::ScriptPubKey => {
+                self.listed_cursor::<ScriptPubKeyMonitor>(kind, MonitorKey::ScriptPubKeys(true))
+            }
+            // Kept under a single key, read in one page
+            MonitorKind::NewBlock | MonitorKind::RskPegin | MonitorKind::OpReturnPrefix => {
+                Ok(MonitorCursor {
+                    kind,
+                    ids: vec![String::new()],
+                })
+            }
+        }
+    }
+
+    fn next_monitors(
+        &self,
+        cursor: &mut MonitorCursor,
+        items: usize,
+    ) -> Result<Vec<TypesToMonitorStore>, MonitorStoreError> {
+        let ids: Vec<String> = (0..items).map_while(|_| cursor.ids.pop()).collect();
+        if ids.is_empty() {
+            return Ok(vec![]);
+        }
+
+        match cursor.kind {
+            MonitorKind::Transaction => {
+                self.read_listed_page::<TransactionMonitor>(MonitorKey::Transactions(true), ids)
+            }
+            MonitorKind::SpendingUTXOTransaction => self.read_listed_page::<SpendingUTXOMonitor>(
+                MonitorKey::SpendingUTXOTransactions(true),
+                ids,
+            ),
+            MonitorKind::ScriptPubKey => {
+                self.read_listed_page::<ScriptPubKeyMonitor>(MonitorKey::ScriptPubKeys(true), ids)
+            }
+            MonitorKind::NewBlock | MonitorKind::RskPegin | MonitorKind::OpReturnPrefix => {
+                let mut monitors: Vec<TypesToMonitorStore> = self
+                    .read_unlisted(cursor.kind, true)?
+                    .into_iter()
+                    .map(|(monitor, _)| monitor)
+                    .collect();
+                monitors.sort_by(TypesToMonitorStore::canonical_cmp);
+                monitors.dedup_by(|a, b| a.canonical_cmp(b) == Ordering::Equal);
+                Ok(monitors)
+            }
+        }
+    }
+
+    fn get_monitored_scripts(&self) -> Result<Vec<ScriptBuf>, MonitorStoreError> {
+        let index = self.read_list_index::<ScriptPubKeyMonitor>(MonitorKey::ScriptPubKeys(true))?;
+
+        Ok(index
+            .iter()
+            .filter_map(|id| ScriptBuf::from_hex(id).ok())
+            .collect())
+    }
+
+    fn get_monitor_infos(
+        &self,
+        include_inactive: bool,
+    ) -> Result<Vec<MonitorInfo>, MonitorStoreError> {
+        let mut monitors = self.list_monitors(true)?;
+        if include_inactive {
+            monitors.extend(self.list_monitors(false)?);
+        }
+
+        let metadata = self.get_monitor_metadata()?;
+        if !metadata.is_empty() {
+            for info in monitors.iter_mut() {
+                let target = info.monitor.target_of();
+                if let Some(entry) = metadata.iter().find(|entry| entry.target == target) {
+                    info.metadata = entry.metadata.clone();
+                }
+            }
+        }
+
+        Ok(monitors)
+    }
+
+    fn get_monitor_history(
+        &self,
+        monitor: &TypesToMonitorStore,
+    ) -> Result<Vec<MonitorEvent>, MonitorStoreError> {
+        Ok(self
+            .get_monitor_infos(true)?
+            .into_iter()
+            .find(|info| info.monitor.canonical_cmp(monitor) == Ordering::Equal)
+            .map(|info| info.history)
+            .unwrap_or_default())
+    }
+
+    fn record_monitor_event(
+        &self,
+        monitor: &TypesToMonitorStore,
+        event: MonitorEvent,
+    ) -> Result<(), MonitorStoreError> {
+        match monitor {
+            TypesToMonitorStore::Transaction(tx_id, extra_data, _, _) => self
+                .record_listed_event::<TransactionMonitor>(
+                    MonitorKey::Transactions,
+                    tx_id.to_string(),
+                    extra_data,
+                    event,
+                ),
+            TypesToMonitorStore::SpendingUTXOTransaction(tx_id, vout, extra_data, ..) => {
+                self.record_listed_event::<SpendingUTXOMonitor>(
+                    MonitorKey::SpendingUTXOTransactions,
+                    OutPoint::new(*tx_id, *vout).to_string(),
+                    extra_data,
+                    event,
+                )
+            }
+            TypesToMonitorStore::ScriptPubKey(script_pubkey, extra_data, ..) => self
+                .record_listed_event::<ScriptPubKeyMonitor>(
+                MonitorKey::ScriptPubKeys,
+                script_pubkey.to_hex_string(),
+                extra_data,
+                event,
+            ),
+            TypesToMonitorStore::OpReturnPrefix(prefix, extra_data) => {
+                let key = self.get_key(MonitorKey::OpReturnPrefixes);
+                let mut op_return_prefixes: Vec<OpReturnPrefixMonitor> =
+                    self.read_typed(&key)?.unwrap_or_default();
+
+                if let Some(monitor) = op_return_prefixes
+                    .iter_mut()
+                    .find(|m| m.prefix == *prefix && m.extra_data == *extra_data)
+                {
+                    if MonitorEvent::record(&mut monitor.history, event) {
+                        self.write_typed(&key, &op_return_prefixes, None)?;
+                    }
+                }
+
+                Ok(())
+            }
+            TypesToMonitorStore::NewBlock | TypesToMonitorStore::RskPegin(..) => Ok(()),
+        }
+    }
+
+    fn add_monitor(&self, data: TypesToMonitor) -> Result<(), MonitorStoreError> {
+        self.add_monitors(vec![data])
+    }
+
+    fn add_monitors(&self, data: Vec<TypesToMonitor>) -> Result<(), MonitorStoreError> {
+        // Each list is read and written once, no matter how many monitors of that kind are in the
+        // batch, and only the monitors of the batch are read.
+        let (mut tx_item_ids, mut outpoint_item_ids, mut script_item_ids) =
+            (vec![], vec![], vec![]);
+        for item in &data {
+            match item {
+                TypesToMonitor::Transactions(ids, ..) | TypesToMonitor::Alternatives(ids, _) => {
+                    tx_item_ids.extend(ids.iter().map(|tx_id| tx_id.to_string()))
+                }
+                TypesToMonitor::SpendingUTXOTransaction(txid, vout, ..) => {
+                    outpoint_item_ids.push(OutPoint::new(*txid, *vout).to_string())
+                }
+                TypesToMonitor::ScriptPubKey(script_pubkey, ..) => {
+                    script_item_ids.push(script_pubkey.to_hex_string())
+                }
+                _ => {}
+            }
+        }
+
+        let mut txs: Option<Listed<TransactionMonitor>> = None;
+        let mut spending_txs: Option<Listed<SpendingUTXOMonitor>> = None;
+        let mut scripts: Option<Listed<ScriptPubKeyMonitor>> = None;
+
+        let op_return_key = self.get_key(MonitorKey::OpReturnPrefixes);
+        let mut op_return_prefixes: Option<Vec<OpReturnPrefixMonitor>> = None;
+
+        let alternatives_key = self.get_key(MonitorKey::Alternatives);
+        let mut alternatives: Option<Vec<AlternativesMonitor>> = None;
+
+        let mut rsk_pegin: Option<RskPeginMonitorState> = None;
+        let mut new_block = false;
+
+        let registered = MonitorEvent::Registered {
+            height: self.get_monitor_height()?,
+        };
+
+        for item in data {
+            match item {
+                TypesToMonitor::Transactions(tx_ids, extra_data, from, expires_at_height) => {
+                    // A txid repeated in the registration is registered once, at its first position
+                    let mut registered_ids = HashSet::new();
+                    let tx_ids: Vec<Txid> = tx_ids
+                        .into_iter()
+                        .filter(|tx_id| registered_ids.insert(*tx_id))
+                        .collect();
+
+                    if txs.is_none() {
+                        txs = Some(self.read_listed_items(
+                            MonitorKey::Transactions(true),
+                            std::mem::take(&mut tx_item_ids),
+                        )?);
+                    }
+
+                    if let Some(txs) = txs.as_mut() {
+                        Self::insert_transaction_monitor(
+                            txs,
+                            &tx_ids,
+                            &extra_data,
+                            from,
+                            expires_at_height,
+                            &registered,
+                        );
+                    }
+                }
+                TypesToMonitor::RskPegin(from, committee, packet_range, min_amount) => {
+                    rsk_pegin = Some(RskPeginMonitorState {
+                        status: RskPeginMonitorStatus::Active,
+                        confirmation_trigger: from,
+                        committee,
+                        packet_range,
+                        min_amount,
+                    });
+                }
+                TypesToMonitor::SpendingUTXOTransaction(
+                    txid,
+                    vout,
+                    extra_data,
+                    from,
+                    deadline,
+                    value_gate,
+                    expires_at_height,
+                ) => {
+                    if spending_txs.is_none() {
+                        spending_txs = Some(self.read_listed_items(
+                            MonitorKey::SpendingUTXOTransactions(true),
+                            std::mem::take(&mut outpoint_item_ids),
+                        )?);
+                    }
+
+                    if let Some(spending_txs) = spending_txs.as_mut() {
+                        Self::insert_spending_utxo_monitor(
+                            spending_txs,
+                            txid,
+                            vout,
+                            &extra_data,
+                            from,
+                            deadline,
+                            value_gate,
+                            expires_at_height,
+                            &registered,
+                        );
+                    }
+                }
+                TypesToMonitor::NewBlock => {
+                    new_block = true;
+                }
+                TypesToMonitor::ScriptPubKey(script_pubkey, extra_data, from, filter) => {
+                    if scripts.is_none() {
+                        scripts = Some(self.read_listed_items(
+                            MonitorKey::ScriptPubKeys(true),
+                            std::mem::take(&mut script_item_ids),
+                        )?);
+                    }
+
+                    if let Some(scripts) = scripts.as_mut() {
+                        Self::insert_script_pubkey_monitor(
+                            scripts,
+                            script_pubkey,
+                            &extra_data,
+                            from,
+                            filter,
+                            &registered,
+                        );
+                    }
+                }
+                TypesToMonitor::OpReturnPrefix(prefix, extra_data) => {
+                    if op_return_prefixes.is_none() {
+                        op_return_prefixes =
+                            Some(self.read_typed(&op_return_key)?.unwrap_or_default());
+                    }
+
+                    if let Some(op_return_prefixes) = op_return_prefixes.as_mut() {
+                        match op_return_prefixes
+                            .iter_mut()
+                            .find(|m| m.prefix == prefix && m.extra_data == extra_data)
+                        {
+                            Some(monitor) => {
+                                MonitorEvent::record(&mut monitor.history, registered.clone());
+                            }
+                            None => {
+                                let mut monitor = OpReturnPrefixMonitor {
+                                    prefix,
+                                    extra_data,
+                                    history: vec![],
+                                };
+                                MonitorEvent::record(&mut monitor.history, registered.clone());
+                                op_return_prefixes.push(monitor);
+                            }
+                        }
+                    }
+                }
+                TypesToMonitor::Alternatives(tx_ids, extra_data) => {
+                    if txs.is_none() {
+                        txs = Some(self.read_listed_items(
+                            MonitorKey::Transactions(true),
+                            std::mem::take(&mut tx_item_ids),
+                        )?);
+                    }
+
+                    if let Some(txs) = txs.as_mut() {
+                        Self::insert_transaction_monitor(
+                            txs,
+                            &tx_ids,
+                            &extra_data,
+                            None,
+                            None,
+                            &registered,
+                        );
+                    }
+
+                    if alternatives.is_none() {
+                        alternatives =
+                            Some(self.read_typed(&alternatives_key)?.unwrap_or_default());
+                    }
+
+                    // Registering a race again opens it, every alternative is monitored again
+                    if let Some(alternatives) = alternatives.as_mut() {
+                        alternatives.retain(|m| !m.matches(&tx_ids, &extra_data));
+                        alternatives.push(AlternativesMonitor {
+                            tx_ids,
+                            extra_data,
+                            winner: None,
+                        });
+                    }
+                }
+            }
+        }
+
+        if let Some(txs) = txs {
+            self.write_listed(&txs, None)?;
+        }
+
+        if let Some(spending_txs) = spending_txs {
+            self.write_listed(&spending_txs, None)?;
+        }
+
+        if let Some(scripts) = scripts {
+            self.write_listed(&scripts, None)?;
+        }
+
+        if let Some(op_return_prefixes) = op_return_prefixes {
+            self.write_typed(&op_return_key, &op_return_prefixes, None)?;
+        }
+
+        if let Some(alternatives) = alternatives {
+            self.write_typed(&alternatives_key, &alternatives, None)?;
+        }
+
+        if let Some(state) = rsk_pegin {
+            let key = self.get_key(MonitorKey::RskPegin);
+            self.write_typed(&key, state, None)?;
+        }
+
+        if new_block {
+            let key = self.get_key(MonitorKey::NewBlock);
+            self.write_typed(&key, true, None)?;
+        }
+
+        Ok(())
+    }
+
+    fn deactivate_monitor(&self, data: TypesToMonitor) -> Result<(), MonitorStoreError> {
+        match data {
+            TypesToMonitor::Transactions(tx_ids, extra_data, _, _) => {
+                let ids: Vec<String> = tx_ids.iter().map(|tx_id| tx_id.to_string()).collect();
+                let mut active_txs: Listed<TransactionMonitor> =
+                    self.read_listed_items(MonitorKey::Transactions(true), ids.clone())?;
+                let mut inactive_txs: Listed<TransactionMonitor> =
+                    self.read_listed_items(MonitorKey::Transactions(false), ids)?;
+
+                // Move matching transactions from active to inactive
+                // For each matching txid, move only the entry with matching extra_data
+                let mut to_move = Vec::new();
+                for txid in &tx_ids {
+                    if let Some(monitor) = active_txs.iter_mut().find(|m| m.tx_id == *txid) {
+                        // Find and remove the entry with matching extra_data
+                        let mut entry_to_move = None;
+                        monitor.entries.retain(|e| {
+                            if e.extra_data == extra_data {
+                                entry_to_move = Some(e.clone());
+                                false // Remove from active
+                            } else {
+                                true // Keep in active
+                            }
+                        });
+
+                        // If no entries left for this txid, remove the txid entirely
+                        if monitor.entries.is_empty() {
+                            active_txs.retain(|m| m.tx_id != *txid);
+                        }
+
+                        if let Some(entry) = entry_to_move {
+                            to_move.push((*txid, entry));
+                        }
+                    }
+                }
+
+                // Add moved entries to inactive
+                for (txid, entry) in to_move {
+                    if let Some(monitor) = inactive_txs.iter_mut().find(|m| m.tx_id == txid) {
+                        // Add to existing inactive txid (avoid duplicates)
+                        if !monitor
+                            .entries
+                            .iter()
+                            .any(|e| e.extra_data == entry.extra_data)
+                        {
+                            monitor.entries.push(entry);
+                        }
+                    } else {
+                        // Create new inactive txid entry
+                        inactive_txs.push(TransactionMonitor {
+                            tx_id: txid,
+                            entries: vec![entry],
+                        });
+                    }
+                }
+
+                self.write_listed(&active_txs, None)?;
+                self.write_listed(&inactive_txs, None)?;
+            }
+
+            TypesToMonitor::RskPegin(..) => {
+                // The trigger, the committee, the packet range and the minimum amount are kept for
+                // a reactivation
+                let key = self.get_key(MonitorKey::RskPegin);
+                let state: Option<RskPeginMonitorState> = self.read_typed(&key)?;
+                if let Some(state) = state.filter(|state| state.is_active()) {
+                    self.write_typed(
+                        &key,
+                        RskPeginMonitorState {
+                            status: RskPeginMonitorStatus::Inactive,
+                            ..state
+                        },
+                        None,
+                    )?;
+                }
+            }
+            TypesToMonitor::SpendingUTXOTransaction(txid, vout, extra_data, ..) => {
+                let ids: Vec<String> = vec![OutPoint::new(txid, vout).to_string()];
+                let mut active_txs: Listed<SpendingUTXOMonitor> = self
+                    .read_listed_items(MonitorKey::SpendingUTXOTransactions(true), ids.clone())?;
+                let mut inactive_txs: Listed<SpendingUTXOMonitor> =
+                    self.read_listed_items(MonitorKey::SpendingUTXOTransactions(false), ids)?;
+
+                // Move matching transaction from active to inactive
+                // Find the matching (txid, vout) and move only the entry with matching extra_data
+                let mut entry_to_move = None;
+                if let Some(monitor) = active_txs
+                    .iter_mut()
+                    .find(|m| m.tx_id == txid && m.vout == vout)
+                {
+                    // Find and remove the entry with matching extra_data
+                    monitor.entries.retain(|e| {
+                        if e.extra_data == extra_data {
+                            entry_to_move = Some(e.clone());
+                            false // Remove from active
+                        } else {
+                            true // Keep in active
+                        }
+                    });
+
+                    // If no entries left for this (txid, vout), remove it entirely
+                    if monitor.entries.is_empty() {
+                        active_txs.retain(|m| m.tx_id != txid || m.vout != vout);
+                    }
+                }
+
+                // Add moved entry to inactive
+                if let Some(entry) = entry_to_move {
+                    if let Some(monitor) = inactive_txs
+                        .iter_mut()
+                        .find(|m| m.tx_id == txid && m.vout == vout)
+                    {
+                        // Add to existing inactive (txid, vout) (avoid duplicates)
+                        if !monitor
+                            .entries
+                            .iter()
+                            .any(|e| e.extra_data == entry.extra_data)
+                        {
+                            monitor.entries.push(entry);
+                        }
+                    } else {
+                        // Create new inactive (txid, vout) entry
+                        inactive_txs.push(SpendingUTXOMonitor {
+                            tx_id: txid,
+                            vout,
+                            entries: vec![entry],
+                        });
+                    }
+                }
+
+                self.write_listed(&active_txs, None)?;
+                self.write_listed(&inactive_txs, None)?;
+            }
+            TypesToMonitor::NewBlock => {
+                let key = self.get_key(MonitorKey::NewBlock);
+                self.write_typed(&key, false, None)?;
+            }
+            TypesToMonitor::OpReturnPrefix(prefix, extra_data) => {
+                self.remove_op_return_prefix_monitor(&prefix, &extra_data, None)?;
+            }
+            TypesToMonitor::Alternatives(tx_ids, extra_data) => {
+                // The race ends with its monitors, reactivating them doesn't open it again
+                self.remove_alternatives_monitor(&tx_ids, &extra_data, None)?;
+                self.deactivate_monitor(TypesToMonitor::Transactions(
+                    tx_ids, extra_data, None, None,
+                ))?;
+            }
+            TypesToMonitor::ScriptPubKey(script_pubkey, extra_data, ..) => {
+                let ids: Vec<String> = vec![script_pubkey.to_hex_string()];
+                let mut active_scripts: Listed<ScriptPubKeyMonitor> =
+                    self.read_listed_items(MonitorKey::ScriptPubKeys(true), ids.clone())?;
+                let mut inactive_scripts: Listed<ScriptPubKeyMonitor> =
+                    self.read_listed_items(MonitorKey::ScriptPubKeys(false), ids)?;
+
+                // Move only the entry with matching extra_data from active to inactive
+                let mut entry_to_move = None;
+                if let Some(monitor) = active_scripts
+                    .iter_mut()
+                    .find(|m| m.script_pubkey == script_pubkey)
+                {
+                    if let Some(pos) = monitor
+                        .entries
+                        .iter()
+                        .position(|e| e.extra_data == extra_data)
+                    {
+                        entry_to_move = Some(monitor.entries.remove(pos));
+                    }
+                }
+                active_scripts.retain(|m| !m.entries.is_empty());
+
+                if let Some(entry) = entry_to_move {
+                    match inactive_scripts
+                        .iter_mut()
+                        .find(|m| m.script_pubkey == script_pubkey)
+                    {
+                        Some(monitor) => {
+                            monitor.entries.retain(|e| e.extra_data != entry.extra_data);
+                            monitor.entries.push(entry);
+                        }
+                        None => inactive_scripts.push(ScriptPubKeyMonitor {
+                            script_pubkey,
+                            entries: vec![entry],
+                        }),
+                    }
+                }
+
+                self.write_listed(&active_scripts, None)?;
+                self.write_listed(&inactive_scripts, None)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn reactivate_monitor(&self, data: TypesToMonitor) -> Result<(), MonitorStoreError> {
+        match data {
+            TypesToMonitor::Transactions(tx_ids, extra_data, _, _) => {
+                let ids: Vec<String> = tx_ids.iter().map(|tx_id| tx_id.to_string()).collect();
+                let mut active_txs: Listed<TransactionMonitor> =
+                    self.read_listed_items(MonitorKey::Transactions(true), ids.clone())?;
+                let mut inactive_txs: Listed<TransactionMonitor> =
+                    self.read_listed_items(MonitorKey::Transactions(false), ids)?;
+
+                // Move only the entry with matching extra_data of each txid back to active
+                for txid in &tx_ids {
+                    let Some(entry) =
+                        inactive_txs
+                            .iter_mut()
+                            .find(|m| m.tx_id == *txid)
+                            .and_then(|monitor| {
+                                let pos = monitor
+                                    .entries
+                                    .iter()
+                                    .position(|e| e.extra_data == extra_data)?;
+                                Some(monitor.entries.remove(pos))
+                            })
+                    else {
+                        continue;
+                    };
+
+                    match active_txs.iter_mut().find(|m| m.tx_id == *txid) {
+                        Some(monitor) => {
+                            monitor.entries.retain(|e| e.extra_data != entry.extra_data);
+                            monitor.entries.push(entry);
+                        }
+                        None => active_txs.push(TransactionMonitor {
+                            tx_id: *txid,
+                            entries: vec![entry],
+                        }),
+                    }
+                }
+                inactive_txs.retain(|m| !m.entries.is_empty());
+
+                self.write_listed(&active_txs, None)?;
+                self.write_listed(&inactive_txs, None)?;
+            }
+            TypesToMonitor::RskPegin(..) => {
+                // The trigger and committee kept by the deactivated state win over the ones requested
+                let key = self.get_key(MonitorKey::RskPegin);
+                let state: Option<RskPeginMonitorState> = self.read_typed(&key)?;
+                if let Some(state) =
+                    state.filter(|state| state.status == RskPeginMonitorStatus::Inactive)
+                {
+                    self.write_typed(
+                        &key,
+                        RskPeginMonitorState {
+                            status: RskPeginMonitorStatus::Active,
+                            ..state
+                        },
+                        None,
+                    )?;
+                }
+            }
+            TypesToMonitor::SpendingUTXOTransaction(txid, vout, extra_data, ..) => {
+                let ids: Vec<String> = vec![OutPoint::new(txid, vout).to_string()];
+                let mut active_txs: Listed<SpendingUTXOMonitor> = self
+                    .read_listed_items(MonitorKey::SpendingUTXOTransactions(true), ids.clone())?;
+                let mut inactive_txs: Listed<SpendingUTXOMonitor> =
+                    self.read_listed_items(MonitorKey::SpendingUTXOTransactions(false), ids)?;
+
+                let mut entry_to_move = None;
+                if let Some(monitor) = inactive_txs
+                    .iter_mut()
+                    .find(|m| m.tx_id == txid && m.vout == vout)
+                {
+                    if let Some(pos) = monitor
+                        .entries
+                        .iter()
+                        .position(|e| e.extra_data == extra_data)
+                    {
+                        entry_to_move = Some(monitor.entries.remove(pos));
+                    }
+                }
+                inactive_txs.retain(|m| !m.entries.is_empty());
+
+                if let Some(entry) = entry_to_move {
+                    match active_txs
+                        .iter_mut()
+                        .find(|m| m.tx_id == txid && m.vout == vout)
+                    {
+                        Some(monitor) => {
+                            monitor.entries.retain(|e| e.extra_data != entry.extra_data);
+                            monitor.entries.push(entry);
+                        }
+                        None => active_txs.push(SpendingUTXOMonitor {
+                            tx_id: txid,
+                            vout,
+                            entries: vec![entry],
+                        }),
+                    }
+                }
+
+                self.write_listed(&active_txs, None)?;
+                self.write_listed(&inactive_txs, None)?;
+            }
+            TypesToMonitor::NewBlock => {
+                let key = self.get_key(MonitorKey::NewBlock);
+                if self.read_typed::<bool>(&key)? == Some(false) {
+                    self.write_typed(&key, true, None)?;
+                }
+            }
+            TypesToMonitor::OpReturnPrefix(..) => {
+                // Deactivating a prefix monitor removes it, there is nothing to move back
+            }
+            TypesToMonitor::Alternatives(tx_ids, extra_data) => {
+                self.reactivate_monitor(TypesToMonitor::Transactions(
+                    tx_ids, extra_data, None, None,
+                ))?;
+            }
+            TypesToMonitor::ScriptPubKey(script_pubkey, extra_data, ..) => {
+                let ids: Vec<String> = vec![script_pubkey.to_hex_string()];
+                let mut active_scripts: Listed<ScriptPubKeyMonitor> =
+                    self.read_listed_items(MonitorKey::ScriptPubKeys(true), ids.clone())?;
+                let mut inactive_scripts: Listed<ScriptPubKeyMonitor> =
+                    self.read_listed_items(MonitorKey::ScriptPubKeys(false), ids)?;
+
+                let mut entry_to_move = None;
+                if let Some(monitor) = inactive_scripts
+                    .iter_mut()
+                    .find(|m| m.script_pubkey == script_pubkey)
+                {
+                    if let Some(pos) = monitor
+                        .entries
+                        .iter()
+                        .position(|e| e.extra_data == extra_data)
+                    {
+                        entry_to_move = Some(monitor.entries.remove(pos));
+                    }
+                }
+                inactive_scripts.retain(|m| !m.entries.is_empty());
+
+                if let Some(entry) = entry_to_move {
+                    match active_scripts
+                        .iter_mut()
+                        .find(|m| m.script_pubkey == script_pubkey)
+                    {
+                        Some(monitor) => {
+                            monitor.entries.retain(|e| e.extra_data != entry.extra_data);
+                            monitor.entries.push(entry);
+                        }
+                        None => active_scripts.push(ScriptPubKeyMonitor {
+                            script_pubkey,
+                            entries: vec![entry],
+                        }),
+                    }
+                }
+
+                self.write_listed(&active_scripts, None)?;
+                self.write_listed(&inactive_scripts, None)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn replace_transaction_monitor(
+        &self,
+        old: Txid,
+        new: Txid,
+        current_block_hash: BlockHash,
+        tombstone_expires_at: BlockHeight,
+    ) -> Result<(), MonitorStoreError> {
+        let mut active_txs: Listed<TransactionMonitor> = self.read_listed_items(
+            MonitorKey::Transactions(true),
+            [old.to_string(), new.to_string()],
+        )?;
+
+        let old_pos = active_txs
+            .iter()
+            .position(|m| m.tx_id == old)
+            .ok_or_else(|| MonitorStoreError::TransactionNotFound(old.to_string()))?;
+        let mut old_monitor = active_txs.remove(old_pos);
+        // What was reported is about the old tx
+        for entry in old_monitor.entries.iter_mut() {
+            entry.last_reported_confirmations = None;
+            entry.last_reported_block_hash = None;
+        }
+        let contexts: Vec<MonitorContext> = old_monitor
+            .entries
+            .iter()
+            .map(|e| e.extra_data.clone())
+            .collect();
+
+        // Entries already monitored for the new tx keep their own trigger
+        match active_txs.iter_mut().find(|m| m.tx_id == new) {
+            Some(new_monitor) => {
+                for entry in old_monitor.entries {
+                    if !new_monitor
+                        .entries
+                        .iter()
+                        .any(|e| e.extra_data == entry.extra_data)
+                    {
+                        new_monitor.entries.push(entry);
+                    }
+                }
+            }
+            None => active_txs.push(TransactionMonitor {
+                tx_id: new,
+                entries: old_monitor.entries,
+            }),
+        }
+
+        let txs_news_key = self.get_key(MonitorKey::TransactionsNews);
+        let mut txs_news: Vec<TransactionNewsEntry> =
+            self.read_typed(&txs_news_key)?.unwrap_or_default();
+        txs_news.retain(|e| e.tx_id != old || e.ack.acknowledged);
+
+        let height = self.get_monitor_height()?;
+        let replacement_news = contexts
+            .iter()
+            .map(|extra_data| {
+                let news = MonitoredTypes::MonitorReplaced(old, new, extra_data.clone());
+                Ok(ReplacementNewsEntry {
+                    old_tx_id: old,
+                    new_tx_id: new,
+                    extra_data: extra_data.clone(),
+                    old_confirmed: false,
+                    ack: self.new_news_ack(&news, current_block_hash, height)?,
+                })
+            })
+            .collect::<Result<_, MonitorStoreError>>()?;
+
+        let replaced_key = self.get_key(MonitorKey::ReplacedTransactions);
+        let mut replaced: Vec<ReplacedTxEntry> =
+            self.read_typed(&replaced_key)?.unwrap_or_default();
+        replaced.retain(|e| e.old_tx_id != old);
+        replaced.push(ReplacedTxEntry {
+            old_tx_id: old,
+            new_tx_id: new,
+            contexts,
+            expires_at: tombstone_expires_at,
+        });
+
+        let transaction_id = self.store.begin_transaction();
+        let pending_work_key = self.get_key(MonitorKey::PendingWork);
+
+        let result = self
+            .write_listed(&active_txs, Some(transaction_id))
+            .and_then(|_| self.write_typed(&txs_news_key, &txs_news, Some(transaction_id)))
+            .and_then(|_| self.write_typed(&replaced_key, &replaced, Some(transaction_id)))
+            .and_then(|_| self.write_typed(&pending_work_key, true, Some(transaction_id)))
+            .and_then(|_| self.add_replacement_news(replacement_news, Some(transaction_id)));
+
+        match result {
+            Ok(()) => self.store.commit_transaction(transaction_id)?,
+            Err(e) => {
+                self.store.rollback_transaction(transaction_id)?;
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn snooze_monitor(
+        &self,
+        target: SnoozeTarget,
+        until: BlockHeight,
+    ) -> Result<(), MonitorStoreError> {
+        let key = self.get_key(MonitorKey::Snoozes);
+        let mut snoozes: Vec<SnoozeEntry> = self.read_typed(&key)?.unwrap_or_default();
+
+        match snoozes.iter_mut().find(|s| s.target == target) {
+            Some(snooze) => snooze.until = until,
+            None => snoozes.push(SnoozeEntry {
+                target,
+                until,
+                pending: vec![],
+            }),
+        }
+
+        self.write_typed(&key, &snoozes, None)?;
+
+        Ok(())
+    }
+
+    fn get_snoozes(&self) -> Result<Vec<SnoozeEntry>, MonitorStoreError> {
+        let key = self.get_key(MonitorKey::Snoozes);
+        let snoozes: Vec<SnoozeEntry> = self.read_typed(&key)?.unwrap_or_default();
+        Ok(snoozes)
+    }
+
+    fn mark_expected(&self, targets: Vec<SnoozeTarget>) -> Result<(), MonitorStoreError> {
+        let key = self.get_key(MonitorKey::ExpectedMonitors);
+        let mut expected: Vec<SnoozeTarget> = self.read_typed(&key)?.unwrap_or_default();
+        let len = expected.len();
+
+        for target in targets {
+            if !expected.contains(&target) {
+                expected.push(target);
+            }
+        }
+
+        if expected.len() != len {
+            self.write_typed(&key, &expected, None)?;
+        }
+
+        Ok(())
+    }
+
+    fn is_expected(&self, target: &SnoozeTarget) -> Result<bool, MonitorStoreError> {
+        let key = self.get_key(MonitorKey::ExpectedMonitors);
+        let expected: Vec<SnoozeTarget> = self.read_typed(&key)?.unwrap_or_default();
+        Ok(expected.contains(target))
+    }
+
+    fn set_monitor_metadata(
+        &self,
+        targets: Vec<SnoozeTarget>,
+        metadata: BTreeMap<String, String>,
+    ) -> Result<(), MonitorStoreError> {
+        let key = self.get_key(MonitorKey::MonitorMetadata);
+        let mut entries: Vec<MonitorMetadataEntry> = self.read_typed(&key)?.unwrap_or_default();
+
+        entries.retain(|entry| !targets.contains(&entry.target));
+        if !metadata.is_empty() {
+            entries.extend(targets.into_iter().map(|target| MonitorMetadataEntry {
+                target,
+                metadata: metadata.clone(),
+            }));
+        }
+
+        self.write_typed(&key, &entries, None)?;
+
+        Ok(())
+    }
+
+    fn get_monitor_metadata(&self) -> Result<Vec<MonitorMetadataEntry>, MonitorStoreError> {
+        let key = self.get_key(MonitorKey::MonitorMetadata);
+        let entries: Vec<MonitorMetadataEntry> = self.read_typed(&key)?.unwrap_or_default();
+        Ok(entries)
+    }
+
+    fn get_scan_generation(&self) -> Result<u64, MonitorStoreError> {
+        let key = self.get_key(MonitorKey::ScanGeneration);
+        let generation: u64 = self.read_typed(&key)?.unwrap_or_default();
+        Ok(generation)
+    }
+
+    fn get_storage_stats(&self) -> StorageStats {
+        self.compression.stats()
+    }
+
+    fn defer_news(&self, data: MonitoredTypes) -> Result<bool, MonitorStoreError> {
+        let Some(target) = MonitorStore::snooze_target(&data) else {
+            return Ok(false);
+        };
+
+        let key = self.get_key(MonitorKey::Snoozes);
+        let mut snoozes: Vec<SnoozeEntry> = self.read_typed(&key)?.unwrap_or_default();
+
+        let Some(snooze) = snoozes.iter_mut().find(|s| s.target == target) else {
+            return Ok(false);
+        };
+
+        // Only the latest news of each item is sent when the snooze ends
+        let id = MonitorStore::news_fingerprint_id(&data);
+        snooze
+            .pending
+            .retain(|pending| MonitorStore::news_fingerprint_id(pending) != id);
+        snooze.pending.push(data);
+
+        self.write_typed(&key, &snoozes, None)?;
+
+        Ok(true)
+    }
+
+    fn get_expired_snoozes(
+        &self,
+        current_height: BlockHeight,
+    ) -> Result<Vec<SnoozeEntry>, MonitorStoreError> {
+        let key = self.get_key(MonitorKey::Snoozes);
+        let mut snoozes: Vec<SnoozeEntry> = self.read_typed(&key)?.unwrap_or_default();
+        snoozes.retain(|s| s.until < current_height);
+        Ok(snoozes)
+    }
+
+    fn end_snooze(&self, target: &SnoozeTarget) -> Result<(), MonitorStoreError> {
+        let key = self.get_key(MonitorKey::Snoozes);
+        let mut snoozes: Vec<SnoozeEntry> = self.read_typed(&key)?.unwrap_or_default();
+
+        let len = snoozes.len();
+        snoozes.retain(|s| s.target != *target);
+        if snoozes.len() != len {
+            self.write_typed(&key, &snoozes, None)?;
+        }
+
+        Ok(())
+    }
+
+    fn save_tx_snapshot(&self, mut status: TransactionStatus) -> Result<(), MonitorStoreError> {
+        if let Some(block_info) = status.block_info.as_mut() {
+            block_info.txs.clear();
+        }
+
+        // Listed before it is written, an id left without a snapshot by a crash is skipped
+        let ids_key = self.get_key(MonitorKey::TransactionSnapshotIds);
+        let mut ids: Vec<Txid> = self.read_typed(&ids_key)?.unwrap_or_default();
+        if !ids.contains(&status.tx_id) {
+            ids.push(status.tx_id);
+            self.write_typed(&ids_key, &ids, None)?;
+        }
+
+        self.write_typed(&self.tx_snapshot_key(&status.tx_id), &status, None)
+    }
+
+    fn get_tx_snapshot(
+        &self,
+        tx_id: &Txid,
+    ) -> Result<Option<TransactionStatus>, MonitorStoreError> {
+        self.read_typed(&self.tx_snapshot_key(tx_id))
+    }
+
+    fn get_tx_snapshots(&self) -> Result<Vec<TransactionStatus>, MonitorStoreError> {
+        let ids: Vec<Txid> = self
+            .read_typed(&self.get_key(MonitorKey::TransactionSnapshotIds))?
+            .unwrap_or_default();
+
+        let mut snapshots = Vec::with_capacity(ids.len());
+        for tx_id in ids {
+            if let Some(snapshot) = self.get_tx_snapshot(&tx_id)? {
+                snapshots.push(snapshot);
+            }
+        }
+
+        Ok(snapshots)
+    }
+
+    fn record_mempool_sighting(
+        &self,
+        tx_id: Txid,
+        height: BlockHeight,
+        fee_rate: u64,
+    ) -> Result<(), MonitorStoreError> {
+        let key = self.get_key(MonitorKey::MempoolSightings);
+        let mut sightings: Vec<MempoolSighting> = self.read_typed(&key)?.unwrap_or_default();
+
+        match sightings.iter_mut().find(|s| s.tx_id == tx_id) {
+            Some(sighting) => {
+                sighting.last_seen_height = height;
+                sighting.last_known_fee_rate = fee_rate;
+            }
+            None => sightings.push(MempoolSighting {
+                tx_id,
+                first_seen_height: height,
+                last_seen_height: height,
+                last_known_fee_rate: fee_rate,
+            }),
+        }
+
+        self.write_typed(&key, &sightings, None)?;
+
+        Ok(())
+    }
+
+    fn remove_mempool_sighting(&self, tx_id: Txid) -> Result<(), MonitorStoreError> {
+        let key = self.get_key(MonitorKey::MempoolSightings);
+        let mut sightings: Vec<MempoolSighting> = self.read_typed(&key)?.unwrap_or_default();
+
+        let len = sightings.len();
+        sightings.retain(|s| s.tx_id != tx_id);
+        if sightings.len() != len {
+            self.write_typed(&key, &sightings, None)?;
+        }
+
+        Ok(())
+    }
+
+    fn get_mempool_sightings(&self) -> Result<Vec<MempoolSighting>, MonitorStoreError> {
+        let key = self.get_key(MonitorKey::MempoolSightings);
+        let sightings: Vec<MempoolSighting> = self.read_typed(&key)?.unwrap_or_default();
+        Ok(sightings)
+    }
+
+    fn get_stale_unconfirmed(
+        &self,
+        older_than_blocks: u32,
+    ) -> Result<Vec<StaleTx>, MonitorStoreError> {
+        let sightings = self.get_mempool_sightings()?;
+        if sightings.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let monitor_height = self.get_monitor_height()?;
+        let active_txs: Listed<TransactionMonitor> = self.read_listed_items(
+            MonitorKey::Transactions(true),
+            sightings.iter().map(|sighting| sighting.tx_id.to_string()),
+        )?;
+
+        let mut stale = Vec::new();
+        for sighting in sightings {
+            let blocks_waiting = monitor_height.saturating_sub(sighting.first_seen_height);
+            if blocks_waiting <= older_than_blocks {
+                continue;
+            }
+
+            // Canceled monitors are not reported, their sighting is dropped once mined
+            let Some(monitor) = active_txs.iter().find(|m| m.tx_id == sighting.tx_id) else {
+                continue;
+            };
+
+            for entry in &monitor.entries {
+                stale.push(StaleTx {
+                    txid: sighting.tx_id,
+                    context: entry.extra_data.clone(),
+                    first_seen_height: sighting.first_seen_height,
+                    blocks_waiting,
+                    last_known_fee_rate: sighting.last_known_fee_rate,
+                });
+            }
+        }
+
+        stale.sort_by(|a, b| {
+            b.blocks_waiting
+                .cmp(&a.blocks_waiting)
+                .then_with(|| a.txid.cmp(&b.txid))
+                .then_with(|| a.context.cmp(&b.context))
+        });
+
+        Ok(stale)
+    }
+
+    fn record_filtered_output(
+        &self,
+        script_pubkey: &Script,
+        extra_data: &str,
+        value: Amount,
+    ) -> Result<(), MonitorStoreError> {
+        let key = self.get_key(MonitorKey::AddressFilterStats);
+        let mut stats: Vec<AddressFilterStats> = self.read_typed(&key)?.unwrap_or_default();
+
+        match stats
+            .iter_mut()
+            .find(|s| s.script_pubkey.as_script() == script_pubkey && s.context == extra_data)
+        {
+            Some(entry) => {
+                entry.filtered_outputs += 1;
+                entry.filtered_value += value;
+            }
+            None => stats.push(AddressFilterStats {
+                script_pubkey: script_pubkey.to_owned(),
+                context: extra_data.into(),
+                filtered_outputs: 1,
+                filtered_value: value,
+            }),
+        }
+
+        self.write_typed(&key, &stats, None)?;
+
+        Ok(())
+    }
+
+    fn get_address_filter_stats(
+        &self,
+        script_pubkey: &Script,
+    ) -> Result<Vec<AddressFilterStats>, MonitorStoreError> {
+        let key = self.get_key(MonitorKey::AddressFilterStats);
+        let stats: Vec<AddressFilterStats> = self.read_typed(&key)?.unwrap_or_default();
+
+        Ok(stats
+            .into_iter()
+            .filter(|s| s.script_pubkey.as_script() == script_pubkey)
+            .collect())
+    }
+
+    fn archive_detection(&self, record: DetectionRecord) -> Result<(), MonitorStoreError> {
+        let key = self.get_key(MonitorKey::DetectionArchive);
+        let mut archive: Vec<DetectionRecord> = self.read_typed(&key)?.unwrap_or_default();
+
+        match archive.iter_mut().find(|r| r.is_same_detection(&record)) {
+            Some(entry) if *entry == record => return Ok(()),
+            Some(entry) => *entry = record,
+            None => archive.push(record),
+        }
+
+        self.write_typed(&key, &archive, None)?;
+
+        Ok(())
+    }
+
+    fn remove_archived_detection(&self, record: &DetectionRecord) -> Result<(), MonitorStoreError> {
+        let key = self.get_key(MonitorKey::DetectionArchive);
+        let mut archive: Vec<DetectionRecord> = self.read_typed(&key)?.unwrap_or_default();
+        let len = archive.len();
+        archive.retain(|r| !r.is_same_detection(record));
+
+        if archive.len() != len {
+            self.write_typed(&key, &archive, None)?;
+        }
+
+        Ok(())
+    }
+
+    fn get_archived_detections(
+        &self,
+        from_height: BlockHeight,
+        to_height: BlockHeight,
+    ) -> Result<Vec<DetectionRecord>, MonitorStoreError> {
+        let key = self.get_key(MonitorKey::DetectionArchive);
+        let archive: Vec<DetectionRecord> = self.read_typed(&key)?.unwrap_or_default();
+
+        let mut records: Vec<DetectionRecord> = archive
+            .into_iter()
+            .filter(|r| r.height >= from_height && r.height <= to_height)
+            .collect();
+        records.sort_by(|a, b| {
+            a.height
+                .cmp(&b.height)
+                .then_with(|| a.kind.cmp(&b.kind))
+                .then_with(|| a.tx_id.cmp(&b.tx_id))
+                .then_with(|| a.outpoint.cmp(&b.outpoint))
+                .then_with(|| a.context.cmp(&b.context))
+        });
+
+        Ok(records)
+    }
+
+    fn get_replaced_transactions(&self) -> Result<Vec<ReplacedTxEntry>, MonitorStoreError> {
+        let key = self.get_key(MonitorKey::ReplacedTransactions);
+        let replaced: Vec<ReplacedTxEntry> = self.read_typed(&key)?.unwrap_or_default();
+        Ok(replaced)
+    }
+
+    fn get_alternatives(&self) -> Result<Vec<AlternativesMonitor>, MonitorStoreError> {
+        let key = self.get_key(MonitorKey::Alternatives);
+        Ok(self.read_typed(&key)?.unwrap_or_default())
+    }
+
+    fn resolve_alternatives(
+        &self,
+        race: &AlternativesMonitor,
+        winner: Option<Txid>,
+    ) -> Result<(), MonitorStoreError> {
+        let key = self.get_key(MonitorKey::Alternatives);
+        let mut races: Vec<AlternativesMonitor> = self.read_typed(&key)?.unwrap_or_default();
+        let Some(stored) = races
+            .iter_mut()
+            .find(|m| m.matches(&race.tx_ids, &race.extra_data))
+        else {
+            return Ok(());
+        };
+        stored.winner = winner;
+
+        let transaction_id = self.store.begin_transaction();
+
+        let result = self
+            .write_typed(&key, &races, Some(transaction_id))
+            .and_then(|_| match (winner, race.winner) {
+                (Some(winner), _) => {
+                    let losers = race.losers(winner);
+                    let mut removed = TrashedMonitors::default();
+                    for is_active in [true, false] {
+                        removed.transactions.extend(self.remove_transaction_entries(
+                            is_active,
+                            Some(&losers),
+                            |_, entry| entry.extra_data == race.extra_data,
+                            Some(transaction_id),
+                        )?);
+                    }
+                    if removed.is_empty() {
+                        return Ok(());
+                    }
+                    self.trash_monitors(
+                        removed,
+                        CancelReason::AlternativeLost,
+                        Some(transaction_id),
+                    )
+                }
+                (None, Some(previous)) => {
+                    let losers = race.losers(previous);
+                    let mut txs: Listed<TransactionMonitor> = self.read_listed_items(
+                        MonitorKey::Transactions(true),
+                        losers.iter().map(|tx_id| tx_id.to_string()),
+                    )?;
+                    let registered = MonitorEvent::Registered {
+                        height: self.get_monitor_height()?,
+                    };
+                    Self::insert_transaction_monitor(
+                        &mut txs,
+                        &losers,
+                        &race.extra_data,
+                        None,
+                        None,
+                        &registered,
+                    );
+                    self.write_listed(&txs, Some(transaction_id))?;
+
+                    let news_key = self.get_key(MonitorKey::AlternativeNews);
+                    let mut alternative_news: Vec<AlternativeNewsEntry> =
+                        self.read_typed(&news_key)?.unwrap_or_default();
+                    let len = alternative_news.len();
+                    alternative_news.retain(|e| {
+                        e.ack.acknowledged
+                            || e.winner != previous
+                            || e.extra_data != race.extra_data
+                    });
+                    if alternative_news.len() == len {
+                        return Ok(());
+                    }
+                    self.write_typed(&news_key, &alternative_news, Some(transaction_id))
+                }
+                (None, None) => Ok(()),
+            });
+
+        match result {
+            Ok(()) => self.store.commit_transaction(transaction_id)?,
+            Err(e) => {
+                self.store.rollback_transaction(transaction_id)?;
+                return Err(e);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn remove_alternatives(&self, race: &AlternativesMonitor) -> Result<(), MonitorStoreError> {
+        self.remove_alternatives_monitor(&race.tx_ids, &race.extra_data, None)?;
+        Ok(())
+    }
+
+    fn get_composite_rules(&self) -> Result<Vec<CompositeRule>, MonitorStoreError> {
+        Ok(self
+            .read_typed(&self.get_key(MonitorKey::CompositeRules))?
+            .unwrap_or_default())
+    }
+
+    fn set_composite_rules(&self, rules: Vec<CompositeRule>) -> Result<(), MonitorStoreError> {
+        self.write_typed(&self.get_key(MonitorKey::CompositeRules), rules, None)
+    }
+
+    fn remove_replaced_transaction(&self, old: Txid) -> Result<(), MonitorStoreError> {
+        let key = self.get_key(MonitorKey::ReplacedTransactions);
+        let mut replaced: Vec<ReplacedTxEntry> = self.read_typed(&key)?.unwrap_or_default();
+        let len = replaced.len();
+        replaced.retain(|e| e.old_tx_id != old);
+
+        if replaced.len() != len {
+            self.write_typed(&key, &replaced, None)?;
+        }
+
+        Ok(())
+    }
+    fn cancel_monitor(&self, data: TypesToMonitor) -> Result<(), MonitorStoreError> {
+        self.cancel_monitor_with_policy(data, CancelNewsPolicy::KeepNews)
+    }
+
+    fn cancel_monitor_with_policy(
+        &self,
+        data: TypesToMonitor,
+        policy: CancelNewsPolicy,
+    ) -> Result<(), MonitorStoreError> {
+        self.cancel(data, policy, false)
+    }
+
+    fn cancel_monitor_permanent(
+        &self,
+        data: TypesToMonitor,
+        policy: CancelNewsPolicy,
+    ) -> Result<(), MonitorStoreError> {
+        self.cancel(data, policy, true)
+    }
+
+    fn resolve_monitor_manually(
+        &self,
+        data: TypesToMonitor,
+        metadata: AckMetadata,
+        current_block_hash: BlockHash,
+    ) -> Result<bool, MonitorStoreError> {
+        let transaction_id = self.store.begin_transaction();
+        let tx = Some(transaction_id);
+
+        let result = self.remove_monitor(data.clone(), tx).and_then(|monitors| {
+            if monitors.is_empty() {
+                return Ok(false);
             }
-            TypesToMonitor::SpendingUTXOTransaction(txid, vout, extra_data, from) => {
-                let key = self.get_key(MonitorKey::SpendingUTXOTransactions(true));
-                let mut txs: Vec<SpendingUTXOMonitor> = self.store.get(&key)?.unwrap_or_default();
 
-                if let Some(monitor) = txs.iter_mut().find(|m| m.tx_id == txid && m.vout == vout) {
-                    // If extra_data is the same, override confirmation trigger and keep spender_tx_id
-                    if let Some(pos) = monitor
-                        .entries
-                        .iter()
-                        .position(|e| e.extra_data == extra_data)
-                    {
-                        let existing_spender_tx_id = monitor.entries[pos].spender_tx_id;
-                        monitor.entries[pos] = SpendingUTXOMonitorEntry {
-                            extra_data: extra_data.clone(),
-                            spender_tx_id: existing_spender_tx_id,
-                            confirmation_trigger: from,
-                        };
-                    } else {
-                        // If extra_data is different, add it as a new entry
-                        monitor.entries.push(SpendingUTXOMonitorEntry {
-                            extra_data: extra_data.clone(),
-                            spender_tx_id: None,
-                            confirmation_trigger: from,
-                        });
-                    }
-                } else {
-                    // New (txid,vout)
-                    txs.push(SpendingUTXOMonitor {
-                        tx_id: txid,
-                        vout,
-                        entries: vec![SpendingUTXOMonitorEntry {
-                            extra_data: extra_data.clone(),
-                            spender_tx_id: None,
-                            confirmation_trigger: from,
-                        }],
-                    });
-                }
+            self.trash_monitors(monitors, CancelReason::ManuallyResolved, tx)?;
+            self.settle_monitor_news(&data, CancelNewsPolicy::AckNews, Some(&metadata), tx)?;
 
-                self.store.set(&key, &txs, None)?;
+            let targets = SnoozeTarget::of_monitor(data);
+            let key = self.get_key(MonitorKey::Snoozes);
+            let mut snoozes: Vec<SnoozeEntry> = self.read_typed(&key)?.unwrap_or_default();
+            let len = snoozes.len();
+            snoozes.retain(|snooze| !targets.contains(&snooze.target));
+            if snoozes.len() != len {
+                self.write_typed(&key, &snoozes, tx)?;
             }
-            TypesToMonitor::NewBlock => {
-                let key = self.get_key(MonitorKey::NewBlock);
-                self.store.set(&key, true, None)?;
+
+            let height = self.get_monitor_height()?;
+            for target in targets {
+                let news = MonitoredTypes::ManuallyResolved(target.clone(), metadata.note.clone());
+                let entry = ManualResolutionNewsEntry {
+                    target,
+                    note: metadata.note.clone(),
+                    ack: self.new_news_ack(&news, current_block_hash, height)?,
+                };
+                self.add_resolution_news(entry, tx)?;
+            }
+
+            Ok(true)
+        });
+
+        match result {
+            Ok(true) => self.store.commit_transaction(transaction_id)?,
+            Ok(false) => self.store.rollback_transaction(transaction_id)?,
+            Err(e) => {
+                self.store.rollback_transaction(transaction_id)?;
+                return Err(e);
             }
         }
 
-        Ok(())
+        Ok(result.is_ok_and(|resolved| resolved))
     }
 
-    fn deactivate_monitor(&self, data: TypesToMonitor) -> Result<(), MonitorStoreError> {
-        match data {
-            TypesToMonitor::Transactions(tx_ids, extra_data, _) => {
-                let active_key = self.get_key(MonitorKey::Transactions(true));
-                let inactive_key = self.get_key(MonitorKey::Transactions(false));
+    fn get_trash(&self) -> Result<Vec<TrashEntry>, MonitorStoreError> {
+        let key = self.get_key(MonitorKey::Trash);
+        Ok(self.read_typed(&key)?.unwrap_or_default())
+    }
 
-                let mut active_txs: Vec<TransactionMonitor> =
-                    self.store.get(&active_key)?.unwrap_or_default();
+    fn restore_from_trash(
+        &self,
+        selector: &TrashSelector,
+    ) -> Result<Vec<TrashEntry>, MonitorStoreError> {
+        let key = self.get_key(MonitorKey::Trash);
+        let trash: Vec<TrashEntry> = self.read_typed(&key)?.unwrap_or_default();
 
-                let mut inactive_txs: Vec<TransactionMonitor> =
-                    self.store.get(&inactive_key)?.unwrap_or_default();
+        let (restored, kept): (Vec<_>, Vec<_>) =
+            trash.into_iter().partition(|entry| entry.matches(selector));
 
-                // Move matching transactions from active to inactive
-                // For each matching txid, move only the entry with matching extra_data
-                let mut to_move = Vec::new();
-                for txid in &tx_ids {
-                    if let Some(monitor) = active_txs.iter_mut().find(|m| m.tx_id == *txid) {
-                        // Find and remove the entry with matching extra_data
-                        let mut entry_to_move = None;
-                        monitor.entries.retain(|e| {
-                            if e.extra_data == extra_data {
-                                entry_to_move = Some(e.clone());
-                                false // Remove from active
-                            } else {
-                                true // Keep in active
-                            }
-                        });
+        if restored.is_empty() {
+            return Ok(restored);
+        }
 
-                        // If no entries left for this txid, remove the txid entirely
-                        if monitor.entries.is_empty() {
-                            active_txs.retain(|m| m.tx_id != *txid);
-                        }
+        // Entries are restored in the order they were trashed, so the latest state wins
+        let mut monitors = TrashedMonitors::default();
+        for entry in &restored {
+            monitors.extend(entry.monitors.clone());
+        }
 
-                        if let Some(entry) = entry_to_move {
-                            to_move.push((*txid, entry));
-                        }
-                    }
-                }
+        let transaction_id = self.store.begin_transaction();
 
-                // Add moved entries to inactive
-                for (txid, entry) in to_move {
-                    if let Some(monitor) = inactive_txs.iter_mut().find(|m| m.tx_id == txid) {
-                        // Add to existing inactive txid (avoid duplicates)
-                        if !monitor
-                            .entries
-                            .iter()
-                            .any(|e| e.extra_data == entry.extra_data)
-                        {
-                            monitor.entries.push(entry);
-                        }
-                    } else {
-                        // Create new inactive txid entry
-                        inactive_txs.push(TransactionMonitor {
-                            tx_id: txid,
-                            entries: vec![entry],
-                        });
-                    }
-                }
+        let result = self
+            .restore_monitors(monitors, Some(transaction_id))
+            .and_then(|_| self.write_typed(&key, &kept, Some(transaction_id)));
 
-                self.store.set(&active_key, &active_txs, None)?;
-                self.store.set(&inactive_key, &inactive_txs, None)?;
+        match result {
+            Ok(()) => self.store.commit_transaction(transaction_id)?,
+            Err(e) => {
+                self.store.rollback_transaction(transaction_id)?;
+                return Err(e);
             }
+        }
 
-            TypesToMonitor::RskPegin(from) => {
-                let key = self.get_key(MonitorKey::RskPegin);
-                self.store.set(&key, (false, from), None)?;
-            }
-            TypesToMonitor::SpendingUTXOTransaction(txid, vout, extra_data, _) => {
-                let active_key = self.get_key(MonitorKey::SpendingUTXOTransactions(true));
-                let inactive_key = self.get_key(MonitorKey::SpendingUTXOTransactions(false));
+        Ok(restored)
+    }
 
-                let mut active_txs: Vec<SpendingUTXOMonitor> =
-                    self.store.get(&active_key)?.unwrap_or_default();
+    fn purge_trash(&self, deleted_before: BlockHeight) -> Result<u32, MonitorStoreError> {
+        let key = self.get_key(MonitorKey::Trash);
+        let mut trash: Vec<TrashEntry> = self.read_typed(&key)?.unwrap_or_default();
+        let len = trash.len();
 
-                let mut inactive_txs: Vec<SpendingUTXOMonitor> =
-                    self.store.get(&inactive_key)?.unwrap_or_default();
+        trash.retain(|entry| entry.deleted_at >= deleted_before);
 
-                // Move matching transaction from active to inactive
-                // Find the matching (txid, vout) and move only the entry with matching extra_data
-                let mut entry_to_move = None;
-                if let Some(monitor) = active_txs
-                    .iter_mut()
-                    .find(|m| m.tx_id == txid && m.vout == vout)
-                {
-                    // Find and remove the entry with matching extra_data
-                    monitor.entries.retain(|e| {
-                        if e.extra_data == extra_data {
-                            entry_to_move = Some(e.clone());
-                            false // Remove from active
-                        } else {
-                            true // Keep in active
-                        }
-                    });
+        if trash.len() != len {
+            self.write_typed(&key, &trash, None)?;
+        }
 
-                    // If no entries left for this (txid, vout), remove it entirely
-                    if monitor.entries.is_empty() {
-                        active_txs.retain(|m| m.tx_id != txid || m.vout != vout);
-                    }
-                }
+        Ok((len - trash.len()) as u32)
+    }
 
-                // Add moved entry to inactive
-                if let Some(entry) = entry_to_move {
-                    if let Some(monitor) = inactive_txs
-                        .iter_mut()
-                        .find(|m| m.tx_id == txid && m.vout == vout)
-                    {
-                        // Add to existing inactive (txid, vout) (avoid duplicates)
-                        if !monitor
-                            .entries
-                            .iter()
-                            .any(|e| e.extra_data == entry.extra_data)
-                        {
-                            monitor.entries.push(entry);
-                        }
-                    } else {
-                        // Create new inactive (txid, vout) entry
-                        inactive_txs.push(SpendingUTXOMonitor {
-                            tx_id: txid,
-                            vout,
-                            entries: vec![entry],
-                        });
-                    }
-                }
+    fn prune(&self, older_than_height: BlockHeight) -> Result<PruneReport, MonitorStoreError> {
+        let transaction_id = self.store.begin_transaction();
 
-                self.store.set(&active_key, &active_txs, None)?;
-                self.store.set(&inactive_key, &inactive_txs, None)?;
+        match self.prune_in(older_than_height, transaction_id) {
+            Ok(report) => {
+                self.store.commit_transaction(transaction_id)?;
+                Ok(report)
             }
-            TypesToMonitor::NewBlock => {
-                let key = self.get_key(MonitorKey::NewBlock);
-                self.store.set(&key, false, None)?;
+            Err(e) => {
+                self.store.rollback_transaction(transaction_id)?;
+                Err(e)
+            }
+        }
+    }
+
+    fn export_monitors(&self) -> Result<MonitorExport, MonitorStoreError> {
+        let mut export = MonitorExport {
+            version: MONITOR_EXPORT_VERSION,
+            ..Default::default()
+        };
+
+        for is_active in [true, false] {
+            let txs: Vec<TransactionMonitor> =
+                self.read_list(MonitorKey::Transactions(is_active))?;
+            export
+                .transactions
+                .extend(txs.into_iter().map(|m| (is_active, m)));
+
+            let spending_utxos: Vec<SpendingUTXOMonitor> =
+                self.read_list(MonitorKey::SpendingUTXOTransactions(is_active))?;
+            export
+                .spending_utxos
+                .extend(spending_utxos.into_iter().map(|m| (is_active, m)));
+
+            let scripts: Vec<ScriptPubKeyMonitor> =
+                self.read_list(MonitorKey::ScriptPubKeys(is_active))?;
+            export
+                .script_pubkeys
+                .extend(scripts.into_iter().map(|m| (is_active, m)));
+        }
+
+        export.op_return_prefixes = self
+            .read_typed(&self.get_key(MonitorKey::OpReturnPrefixes))?
+            .unwrap_or_default();
+        export.rsk_pegin = self.read_typed(&self.get_key(MonitorKey::RskPegin))?;
+        export.new_block = self.read_typed(&self.get_key(MonitorKey::NewBlock))?;
+        export.alternatives = self.get_alternatives()?;
+        export.composites = self.get_composite_rules()?;
+        export.metadata = self.get_monitor_metadata()?;
+
+        Ok(export)
+    }
+
+    fn import_monitors(
+        &self,
+        export: MonitorExport,
+        overwrite: bool,
+    ) -> Result<(), MonitorStoreError> {
+        if export.version != MONITOR_EXPORT_VERSION {
+            return Err(MonitorStoreError::UnexpectedError(format!(
+                "unsupported monitor export version {}, expected {}",
+                export.version, MONITOR_EXPORT_VERSION
+            )));
+        }
+
+        let transaction_id = self.store.begin_transaction();
+
+        match self.import_monitors_in(export, overwrite, Some(transaction_id)) {
+            Ok(()) => self.store.commit_transaction(transaction_id)?,
+            Err(e) => {
+                self.store.rollback_transaction(transaction_id)?;
+                return Err(e);
             }
         }
 
         Ok(())
     }
 
-    fn cancel_monitor(&self, data: TypesToMonitor) -> Result<(), MonitorStoreError> {
-        match data {
-            TypesToMonitor::Transactions(tx_ids, extra_data, _) => {
-                let active_key = self.get_key(MonitorKey::Transactions(true));
-                let inactive_key = self.get_key(MonitorKey::Transactions(false));
+    fn update_spending_utxo_monitor(
+        &self,
+        data: (Txid, u32, Option<Txid>),
+    ) -> Result<(), MonitorStoreError> {
+        // Update spender_tx_id for the given (txid,vout) across all entries.
+        let mut txs: Listed<SpendingUTXOMonitor> = self.read_listed_items(
+            MonitorKey::SpendingUTXOTransactions(true),
+            [OutPoint::new(data.0, data.1).to_string()],
+        )?;
 
-                let mut active_txs: Vec<TransactionMonitor> =
-                    self.store.get(&active_key)?.unwrap_or_default();
+        let Some(monitor) = txs
+            .iter_mut()
+            .find(|m| m.tx_id == data.0 && m.vout == data.1)
+        else {
+            return Ok(());
+        };
 
-                let mut inactive_txs: Vec<TransactionMonitor> =
-                    self.store.get(&inactive_key)?.unwrap_or_default();
+        // Already recorded, nothing to write
+        if monitor.entries.iter().all(|e| e.spender_tx_id == data.2) {
+            return Ok(());
+        }
 
-                // Remove only the entry with matching extra_data for each txid
-                for txid in &tx_ids {
-                    // Remove from active
-                    if let Some(monitor) = active_txs.iter_mut().find(|m| m.tx_id == *txid) {
-                        monitor.entries.retain(|e| e.extra_data != extra_data);
-                        // If no entries left for this txid, remove the txid entirely
-                        if monitor.entries.is_empty() {
-                            active_txs.retain(|m| m.tx_id != *txid);
-                        }
-                    }
+        let mut previous_spenders: Vec<Txid> = vec![];
+        for spender in monitor.entries.iter().filter_map(|e| e.spender_tx_id) {
+            if Some(spender) != data.2 && !previous_spenders.contains(&spender) {
+                previous_spenders.push(spender);
+            }
+        }
 
-                    // Remove from inactive
-                    if let Some(monitor) = inactive_txs.iter_mut().find(|m| m.tx_id == *txid) {
-                        monitor.entries.retain(|e| e.extra_data != extra_data);
-                        // If no entries left for this txid, remove the txid entirely
-                        if monitor.entries.is_empty() {
-                            inactive_txs.retain(|m| m.tx_id != *txid);
-                        }
-                    }
-                }
+        // A spent output has nothing left to backfill
+        for entry in monitor.entries.iter_mut() {
+            entry.spender_tx_id = data.2;
+            entry.backfill_done |= data.2.is_some();
+        }
 
-                self.store.set(&active_key, &active_txs, None)?;
-                self.store.set(&inactive_key, &inactive_txs, None)?;
+        let outpoint = (data.0, data.1);
+        let transaction_id = self.store.begin_transaction();
+
+        let result = self.write_listed(&txs, Some(transaction_id)).and_then(|_| {
+            for spender in previous_spenders {
+                self.unindex_spends(spender, &[outpoint], Some(transaction_id))?;
             }
-            TypesToMonitor::RskPegin(from) => {
-                let key = self.get_key(MonitorKey::RskPegin);
-                self.store.set(
-                    &key,
-                    RskPeginMonitorState {
-                        active: false,
-                        confirmation_trigger: from,
-                    },
-                    None,
-                )?;
+            match data.2 {
+                Some(spender) => self.index_spends(spender, &[outpoint], Some(transaction_id)),
+                None => Ok(()),
             }
-            TypesToMonitor::SpendingUTXOTransaction(txid, vout, extra_data, _) => {
-                let active_key = self.get_key(MonitorKey::SpendingUTXOTransactions(true));
-                let inactive_key = self.get_key(MonitorKey::SpendingUTXOTransactions(false));
+        });
 
-                let mut active_txs: Vec<SpendingUTXOMonitor> =
-                    self.store.get(&active_key)?.unwrap_or_default();
+        match result {
+            Ok(()) => self.store.commit_transaction(transaction_id)?,
+            Err(e) => {
+                self.store.rollback_transaction(transaction_id)?;
+                return Err(e);
+            }
+        }
 
-                let mut inactive_txs: Vec<SpendingUTXOMonitor> =
-                    self.store.get(&inactive_key)?.unwrap_or_default();
+        Ok(())
+    }
 
-                // Remove only the entry with matching extra_data from active
-                if let Some(monitor) = active_txs
-                    .iter_mut()
-                    .find(|m| m.tx_id == txid && m.vout == vout)
-                {
-                    monitor.entries.retain(|e| e.extra_data != extra_data);
-                    // If no entries left for this (txid, vout), remove it entirely
-                    if monitor.entries.is_empty() {
-                        active_txs.retain(|m| m.tx_id != txid || m.vout != vout);
-                    }
-                }
+    fn get_outpoints_spent_by(&self, spender: Txid) -> Result<Vec<(Txid, u32)>, MonitorStoreError> {
+        let outpoints: Vec<OutPoint> = self
+            .read_typed(&self.get_key(MonitorKey::Spender(spender)))?
+            .unwrap_or_default();
+        Ok(outpoints
+            .into_iter()
+            .map(|outpoint| (outpoint.txid, outpoint.vout))
+            .collect())
+    }
 
-                // Remove only the entry with matching extra_data from inactive
-                if let Some(monitor) = inactive_txs
-                    .iter_mut()
-                    .find(|m| m.tx_id == txid && m.vout == vout)
-                {
-                    monitor.entries.retain(|e| e.extra_data != extra_data);
-                    // If no entries left for this (txid, vout), remove it entirely
-                    if monitor.entries.is_empty() {
-                        inactive_txs.retain(|m| m.tx_id != txid || m.vout != vout);
-                    }
-                }
+    fn find_monitors_for_tx(
+        &self,
+        tx_id: Txid,
+    ) -> Result<Vec<TypesToMonitorStore>, MonitorStoreError> {
+        let mut monitors = vec![];
 
-                self.store.set(&active_key, &active_txs, None)?;
-                self.store.set(&inactive_key, &inactive_txs, None)?;
-            }
-            TypesToMonitor::NewBlock => {
-                let key = self.get_key(MonitorKey::NewBlock);
-                self.store.set(&key, false, None)?;
+        let txs: Listed<TransactionMonitor> =
+            self.read_listed_items(MonitorKey::Transactions(true), [tx_id.to_string()])?;
+        for monitor in txs.iter() {
+            monitors.extend(monitor.entries.iter().map(|entry| {
+                TypesToMonitorStore::Transaction(
+                    monitor.tx_id,
+                    entry.extra_data.clone(),
+                    entry.confirmation_trigger,
+                    entry.expires_at_height,
+                )
+            }));
+        }
+
+        // The outputs of the txid are found in the index, the outpoints it spent in the spender
+        // index, without reading the other monitors of the list
+        let list = MonitorKey::SpendingUTXOTransactions(true);
+        let prefix = format!("{}:", tx_id);
+        let targets: Vec<String> = self
+            .read_list_index::<SpendingUTXOMonitor>(list)?
+            .into_iter()
+            .filter(|id| id.starts_with(&prefix))
+            .collect();
+        let spent: Vec<String> = self
+            .get_outpoints_spent_by(tx_id)?
+            .into_iter()
+            .map(|(txid, vout)| OutPoint::new(txid, vout).to_string())
+            .collect();
+
+        for (ids, as_spender) in [(targets, false), (spent, true)] {
+            let utxos: Listed<SpendingUTXOMonitor> = self.read_listed_items(list, ids)?;
+            for monitor in utxos.iter() {
+                monitors.extend(
+                    monitor
+                        .entries
+                        .iter()
+                        .filter(|entry| !as_spender || entry.spender_tx_id == Some(tx_id))
+                        .map(|entry| {
+                            TypesToMonitorStore::SpendingUTXOTransaction(
+                                monitor.tx_id,
+                                monitor.vout,
+                                entry.extra_data.clone(),
+                                entry.confirmation_trigger,
+                                entry.deadline,
+                                entry.value_gate,
+                                entry.expires_at_height,
+                            )
+                        }),
+                );
             }
         }
 
-        Ok(())
+        Ok(monitors)
     }
 
-    fn update_spending_utxo_monitor(
+    fn get_spending_utxo_spender(
         &self,
-        data: (Txid, u32, Option<Txid>),
+        tx_id: Txid,
+        vout: u32,
+    ) -> Result<Option<Txid>, MonitorStoreError> {
+        let txs: Listed<SpendingUTXOMonitor> = self.read_listed_items(
+            MonitorKey::SpendingUTXOTransactions(true),
+            [OutPoint::new(tx_id, vout).to_string()],
+        )?;
+
+        Ok(txs
+            .iter()
+            .find(|m| m.tx_id == tx_id && m.vout == vout)
+            .and_then(|m| m.entries.iter().find_map(|e| e.spender_tx_id)))
+    }
+
+    fn get_spending_utxo_backfills(&self) -> Result<Vec<TypesToMonitorStore>, MonitorStoreError> {
+        let list = MonitorKey::SpendingUTXOTransactions(true);
+        let mut backfills = vec![];
+
+        // Read one item at a time, only the few monitors still to backfill are kept
+        for id in self.read_list_index::<SpendingUTXOMonitor>(list)? {
+            let key = self.get_listed_key(list, &id);
+            let Some(mut monitor) = self.read_typed::<SpendingUTXOMonitor>(&key)? else {
+                continue;
+            };
+
+            monitor
+                .entries
+                .retain(|e| !e.backfill_done && e.spender_tx_id.is_none());
+            backfills.extend(monitor.into_monitors());
+        }
+
+        Ok(backfills)
+    }
+
+    fn set_spending_utxo_backfill_done(
+        &self,
+        tx_id: Txid,
+        vout: u32,
+        extra_data: &str,
     ) -> Result<(), MonitorStoreError> {
-        // Update spender_tx_id for the given (txid,vout) across all entries.
-        let key = self.get_key(MonitorKey::SpendingUTXOTransactions(true));
-        let mut txs: Vec<SpendingUTXOMonitor> = self.store.get(&key)?.unwrap_or_default();
+        let mut txs: Listed<SpendingUTXOMonitor> = self.read_listed_items(
+            MonitorKey::SpendingUTXOTransactions(true),
+            [OutPoint::new(tx_id, vout).to_string()],
+        )?;
 
-        if let Some(monitor) = txs
+        let entry = txs
             .iter_mut()
-            .find(|m| m.tx_id == data.0 && m.vout == data.1)
-        {
-            for entry in monitor.entries.iter_mut() {
-                entry.spender_tx_id = data.2;
+            .find(|m| m.tx_id == tx_id && m.vout == vout)
+            .and_then(|m| m.entries.iter_mut().find(|e| e.extra_data == extra_data));
+
+        if let Some(entry) = entry {
+            if !entry.backfill_done {
+                entry.backfill_done = true;
+                self.write_listed(&txs, None)?;
             }
-            self.store.set(&key, &txs, None)?;
         }
 
         Ok(())
@@ -809,8 +6687,8 @@ impl MonitorStoreApi for MonitorStore {
         tx_id: Txid,
         extra_data: &str,
     ) -> Result<bool, MonitorStoreError> {
-        let key = self.get_key(MonitorKey::Transactions(true));
-        let txs: Vec<TransactionMonitor> = self.store.get(&key)?.unwrap_or_default();
+        let txs: Listed<TransactionMonitor> =
+            self.read_listed_items(MonitorKey::Transactions(true), [tx_id.to_string()])?;
 
         if let Some(monitor) = txs.iter().find(|m| m.tx_id == tx_id) {
             if let Some(entry) = monitor.entries.iter().find(|e| e.extra_data == extra_data) {
@@ -835,8 +6713,8 @@ impl MonitorStoreApi for MonitorStore {
         extra_data: &str,
         trigger_sent: bool,
     ) -> Result<(), MonitorStoreError> {
-        let key = self.get_key(MonitorKey::Transactions(true));
-        let mut txs: Vec<TransactionMonitor> = self.store.get(&key)?.unwrap_or_default();
+        let mut txs: Listed<TransactionMonitor> =
+            self.read_listed_items(MonitorKey::Transactions(true), [tx_id.to_string()])?;
 
         if let Some(monitor) = txs.iter_mut().find(|m| m.tx_id == tx_id) {
             if let Some(entry) = monitor
@@ -845,7 +6723,51 @@ impl MonitorStoreApi for MonitorStore {
                 .find(|e| e.extra_data == extra_data)
             {
                 entry.trigger_sent = trigger_sent;
-                self.store.set(&key, &txs, None)?;
+                self.write_listed(&txs, None)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn get_last_reported(
+        &self,
+        tx_id: Txid,
+        extra_data: &str,
+    ) -> Result<Option<(u32, Option<BlockHash>)>, MonitorStoreError> {
+        let txs: Listed<TransactionMonitor> =
+            self.read_listed_items(MonitorKey::Transactions(true), [tx_id.to_string()])?;
+
+        Ok(txs
+            .iter()
+            .find(|m| m.tx_id == tx_id)
+            .and_then(|monitor| monitor.entries.iter().find(|e| e.extra_data == extra_data))
+            .and_then(|entry| {
+                entry
+                    .last_reported_confirmations
+                    .map(|confirmations| (confirmations, entry.last_reported_block_hash))
+            }))
+    }
+
+    fn update_last_reported(
+        &self,
+        tx_id: Txid,
+        extra_data: &str,
+        confirmations: u32,
+        block_hash: Option<BlockHash>,
+    ) -> Result<(), MonitorStoreError> {
+        let mut txs: Listed<TransactionMonitor> =
+            self.read_listed_items(MonitorKey::Transactions(true), [tx_id.to_string()])?;
+
+        if let Some(monitor) = txs.iter_mut().find(|m| m.tx_id == tx_id) {
+            if let Some(entry) = monitor
+                .entries
+                .iter_mut()
+                .find(|e| e.extra_data == extra_data)
+            {
+                entry.last_reported_confirmations = Some(confirmations);
+                entry.last_reported_block_hash = block_hash;
+                self.write_listed(&txs, None)?;
             }
         }
 
@@ -0,0 +1,95 @@
+use crate::news_event::NewsEvent;
+use crate::redaction::redact_context;
+use crate::store::MonitoredTypes;
+use crate::types::MonitorInfo;
+
+/// Table of `monitors` with a row per monitor: its kind, target, context, whether it is active
+/// and the spender found for it. Contexts are redacted unless they are revealed, see
+/// [`crate::redaction`].
+pub fn monitors_table(monitors: &[MonitorInfo]) -> String {
+    let rows = monitors
+        .iter()
+        .map(|info| {
+            vec![
+                format!("{:?}", info.monitor.kind()),
+                or_dash(info.monitor.target()),
+                or_dash(redact_context(info.monitor.context())),
+                info.active.to_string(),
+                info.spender_tx_id
+                    .map(|tx_id| tx_id.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect();
+
+    render(&["KIND", "TARGET", "CONTEXT", "ACTIVE", "SPENDER"], rows)
+}
+
+/// Table of `news` with a row per news and its position in the news sequence, described by
+/// the fields of its [`NewsEvent`]: the transaction, the outpoint or block, and the context.
+pub fn news_table(news: &[(u64, MonitoredTypes)]) -> String {
+    let rows = news
+        .iter()
+        .map(|(sequence, news)| {
+            let event = match news {
+                MonitoredTypes::NewBlock(block_hash, _) => {
+                    NewsEvent::new(news).at_block(*block_hash)
+                }
+                _ => NewsEvent::new(news),
+            };
+            let target = match (event.outpoint, event.block_hash) {
+                (Some(outpoint), _) => outpoint.to_string(),
+                (None, Some(block_hash)) => block_hash.to_string(),
+                (None, None) => "-".to_string(),
+            };
+
+            vec![
+                sequence.to_string(),
+                event.kind.to_string(),
+                event
+                    .tx_id
+                    .map(|tx_id| tx_id.to_string())
+                    .unwrap_or_else(|| "-".to_string()),
+                target,
+                event
+                    .context
+                    .map(or_dash)
+                    .unwrap_or_else(|| "-".to_string()),
+            ]
+        })
+        .collect();
+
+    render(&["SEQUENCE", "KIND", "TX", "TARGET", "CONTEXT"], rows)
+}
+
+fn or_dash(value: String) -> String {
+    if value.is_empty() {
+        "-".to_string()
+    } else {
+        value
+    }
+}
+
+/// Lines of the header and the rows, each column padded to its widest cell.
+fn render(header: &[&str], rows: Vec<Vec<String>>) -> String {
+    let mut widths: Vec<usize> = header.iter().map(|title| title.len()).collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let header = header.iter().map(|title| title.to_string()).collect();
+    std::iter::once(header)
+        .chain(rows)
+        .map(|row: Vec<String>| {
+            let line = row
+                .iter()
+                .zip(&widths)
+                .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+                .collect::<Vec<_>>()
+                .join("  ");
+            format!("{}\n", line.trim_end())
+        })
+        .collect()
+}
@@ -0,0 +1,6 @@
+/// Support for the tests of crates using the monitor, built with the `testing` feature.
+///
+/// [`fixture::StoreFixture`] declares the state of a store, monitors and their news, and writes
+/// it with the keys of the current schema, so a test reads as "state = fixture, then tick over
+/// block Y" instead of the sequence of calls that would lead to it.
+pub mod fixture;
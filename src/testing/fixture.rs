@@ -0,0 +1,433 @@
+use crate::errors::MonitorStoreError;
+use crate::store::{ListedMonitor, MonitorKey, MonitorStore, MonitorStoreApi};
+use crate::types::{
+    DeactivationReason, MonitorContext, MonitorEvent, NewsAck, SpendingUTXOMonitor,
+    SpendingUTXOMonitorEntry, SpendingUTXONewsEntry, TransactionMonitor, TransactionMonitorEntry,
+    TransactionNewsEntry,
+};
+use bitcoin::{BlockHash, OutPoint, Txid};
+use bitvmx_bitcoin_rpc::types::BlockHeight;
+use std::sync::Arc;
+use storage_backend::storage::Storage;
+use uuid::Uuid;
+
+/// Why a monitor of a [`StoreFixture`] is inactive.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Deactivation {
+    /// Deactivated with `deactivate_monitor`, nothing is recorded in its history
+    Requested,
+    /// Stopped after `max_monitoring_confirmations` at the monitor height `height`, for `reason`
+    MaxConfirmations {
+        height: BlockHeight,
+        reason: DeactivationReason,
+    },
+}
+
+/// News declared with [`StoreFixture::with_news`]
+#[derive(Debug, Clone, Copy)]
+struct DeclaredNews {
+    block_hash: BlockHash,
+    acknowledged: bool,
+}
+
+#[derive(Debug, Clone)]
+enum DeclaredMonitor {
+    Transaction {
+        tx_id: Txid,
+        is_active: bool,
+        entry: TransactionMonitorEntry,
+        news: Option<DeclaredNews>,
+    },
+    SpendingUTXO {
+        outpoint: OutPoint,
+        is_active: bool,
+        entry: SpendingUTXOMonitorEntry,
+        news: Option<DeclaredNews>,
+    },
+}
+
+/// State of a store declared monitor by monitor, written by [`StoreFixture::build`].
+///
+/// Each monitor is declared with its target and whether it is active, and the calls that follow
+/// it (`context`, `trigger_at`, `spent_by`, `with_news`...) set that monitor. The monitors of a
+/// target are stored in the order they were declared, and news take the positions of the news
+/// sequence in that order.
+///
+/// ```no_run
+/// use bitcoin::{BlockHash, OutPoint, Txid};
+/// use bitvmx_transaction_monitor::{
+///     store::MonitorStoreApi,
+///     testing::fixture::{Deactivation, StoreFixture},
+/// };
+/// # use std::sync::Arc;
+/// # use storage_backend::{storage::Storage, storage_config::StorageConfig};
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let path = format!("test_outputs/fixture_doc_{}", std::process::id());
+/// # let storage = Arc::new(Storage::new(&StorageConfig::new(path.clone(), None))?);
+/// let tx_id: Txid = "0000000000000000000000000000000000000000000000000000000000000001".parse()?;
+/// let block_hash: BlockHash =
+///     "0000000000000000000000000000000000000000000000000000000000000002".parse()?;
+///
+/// let store = StoreFixture::new()
+///     .at_height(200, block_hash)
+///     .active_tx(tx_id)
+///     .context("deposit")
+///     .with_news(block_hash, false)
+///     .inactive_spending(OutPoint::new(tx_id, 0), Deactivation::Requested)
+///     .build(storage)?;
+///
+/// assert_eq!(store.get_news_count()?, 1);
+/// # std::fs::remove_dir_all(path).ok();
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct StoreFixture {
+    tip: Option<(BlockHeight, BlockHash)>,
+    monitors: Vec<DeclaredMonitor>,
+}
+
+impl StoreFixture {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sets the monitor height and block hash. The monitors declared after it are registered at
+    /// `height`, the ones before at height 0.
+    pub fn at_height(mut self, height: BlockHeight, block_hash: BlockHash) -> Self {
+        self.tip = Some((height, block_hash));
+        self
+    }
+
+    /// Declares an active Transaction monitor of `tx_id`, with the default context.
+    pub fn active_tx(self, tx_id: Txid) -> Self {
+        self.push_tx(tx_id, None)
+    }
+
+    /// Declares a Transaction monitor of `tx_id` deactivated for `reason`.
+    pub fn inactive_tx(self, tx_id: Txid, reason: Deactivation) -> Self {
+        self.push_tx(tx_id, Some(reason))
+    }
+
+    /// Declares an active SpendingUTXO monitor of `outpoint`, not spent and with the blocks
+    /// before its registration already scanned.
+    pub fn active_spending(self, outpoint: OutPoint) -> Self {
+        self.push_spending(outpoint, None)
+    }
+
+    /// Declares a SpendingUTXO monitor of `outpoint` deactivated for `reason`.
+    pub fn inactive_spending(self, outpoint: OutPoint, reason: Deactivation) -> Self {
+        self.push_spending(outpoint, Some(reason))
+    }
+
+    /// Sets the context of the last monitor declared.
+    pub fn context(mut self, context: impl Into<MonitorContext>) -> Self {
+        match self.last_monitor("context") {
+            DeclaredMonitor::Transaction { entry, .. } => entry.extra_data = context.into(),
+            DeclaredMonitor::SpendingUTXO { entry, .. } => entry.extra_data = context.into(),
+        }
+        self
+    }
+
+    /// Sets the confirmation trigger of the last monitor declared.
+    pub fn trigger_at(mut self, confirmations: u32) -> Self {
+        match self.last_monitor("trigger_at") {
+            DeclaredMonitor::Transaction { entry, .. } => {
+                entry.confirmation_trigger = Some(confirmations)
+            }
+            DeclaredMonitor::SpendingUTXO { entry, .. } => {
+                entry.confirmation_trigger = Some(confirmations)
+            }
+        }
+        self
+    }
+
+    /// Records `spender` as the spender of the last monitor declared, a SpendingUTXO monitor,
+    /// and indexes the spend.
+    ///
+    /// # Panics
+    ///
+    /// If the last monitor declared is not a SpendingUTXO monitor.
+    pub fn spent_by(mut self, spender: Txid) -> Self {
+        match self.last_monitor("spent_by") {
+            DeclaredMonitor::SpendingUTXO { entry, .. } => entry.spender_tx_id = Some(spender),
+            DeclaredMonitor::Transaction { .. } => {
+                panic!("spent_by needs a SpendingUTXO monitor")
+            }
+        }
+        self
+    }
+
+    /// Leaves the blocks before the registration of the last monitor declared, a SpendingUTXO
+    /// monitor, to be scanned, as they are right after it is registered.
+    ///
+    /// # Panics
+    ///
+    /// If the last monitor declared is not a SpendingUTXO monitor.
+    pub fn backfill_pending(mut self) -> Self {
+        match self.last_monitor("backfill_pending") {
+            DeclaredMonitor::SpendingUTXO { entry, .. } => entry.backfill_done = false,
+            DeclaredMonitor::Transaction { .. } => {
+                panic!("backfill_pending needs a SpendingUTXO monitor")
+            }
+        }
+        self
+    }
+
+    /// Appends `event` to the history of the last monitor declared.
+    pub fn with_event(mut self, event: MonitorEvent) -> Self {
+        match self.last_monitor("with_event") {
+            DeclaredMonitor::Transaction { entry, .. } => entry.history.push(event),
+            DeclaredMonitor::SpendingUTXO { entry, .. } => entry.history.push(event),
+        }
+        self
+    }
+
+    /// Adds a news of the last monitor declared, sent in `block_hash`. A SpendingUTXO monitor
+    /// needs its spender, see [`StoreFixture::spent_by`].
+    pub fn with_news(mut self, block_hash: BlockHash, acknowledged: bool) -> Self {
+        let declared = Some(DeclaredNews {
+            block_hash,
+            acknowledged,
+        });
+        match self.last_monitor("with_news") {
+            DeclaredMonitor::Transaction { news, .. } => *news = declared,
+            DeclaredMonitor::SpendingUTXO { news, .. } => *news = declared,
+        }
+        self
+    }
+
+    /// Opens a store on `storage`, new or written with an older schema and migrated to the
+    /// current one, and writes the declared state in one store transaction.
+    ///
+    /// The keys are the ones of the current schema. The lists of monitors and the news lists the
+    /// fixture declares replace the ones of the store, and the news history is left empty.
+    pub fn build(self, storage: Arc<Storage>) -> Result<MonitorStore, MonitorStoreError> {
+        let store = MonitorStore::new(storage)?;
+
+        if let Some((height, block_hash)) = self.tip {
+            store.update_monitor_height(height)?;
+            store.update_monitor_block_hash(block_hash)?;
+        }
+
+        let transaction_id = store.begin_transaction();
+
+        match self.write(&store, transaction_id) {
+            Ok(()) => store.commit_transaction(transaction_id)?,
+            Err(e) => {
+                store.rollback_transaction(transaction_id)?;
+                return Err(e);
+            }
+        }
+
+        Ok(store)
+    }
+
+    fn write(&self, store: &MonitorStore, transaction_id: Uuid) -> Result<(), MonitorStoreError> {
+        let mut txs: [Vec<TransactionMonitor>; 2] = Default::default();
+        let mut spending: [Vec<SpendingUTXOMonitor>; 2] = Default::default();
+        let mut spends: Vec<(Txid, Vec<(Txid, u32)>)> = vec![];
+        let mut txs_news = vec![];
+        let mut spending_news = vec![];
+        let mut sequence = 0;
+
+        let mut next_ack = |news: DeclaredNews| {
+            sequence += 1;
+            NewsAck {
+                sequence,
+                ..NewsAck::new(news.block_hash, news.acknowledged)
+            }
+        };
+
+        for monitor in self.monitors.iter().cloned() {
+            match monitor {
+                DeclaredMonitor::Transaction {
+                    tx_id,
+                    is_active,
+                    entry,
+                    news,
+                } => {
+                    if let Some(news) = news {
+                        txs_news.push(TransactionNewsEntry {
+                            tx_id,
+                            extra_data: entry.extra_data.clone(),
+                            ack: next_ack(news),
+                        });
+                    }
+
+                    let list = &mut txs[list_index(is_active)];
+                    match list.iter_mut().find(|m| m.tx_id == tx_id) {
+                        Some(monitor) => monitor.entries.push(entry),
+                        None => list.push(TransactionMonitor {
+                            tx_id,
+                            entries: vec![entry],
+                        }),
+                    }
+                }
+                DeclaredMonitor::SpendingUTXO {
+                    outpoint,
+                    is_active,
+                    entry,
+                    news,
+                } => {
+                    if let Some(spender) = entry.spender_tx_id {
+                        let spent = (outpoint.txid, outpoint.vout);
+                        match spends.iter_mut().find(|(s, _)| *s == spender) {
+                            Some((_, outpoints)) if !outpoints.contains(&spent) => {
+                                outpoints.push(spent)
+                            }
+                            Some(_) => {}
+                            None => spends.push((spender, vec![spent])),
+                        }
+                    }
+
+                    if let Some(news) = news {
+                        let spender_tx_id = entry.spender_tx_id.unwrap_or_else(|| {
+                            panic!("the news of {outpoint} needs its spender, see spent_by")
+                        });
+                        spending_news.push(SpendingUTXONewsEntry {
+                            tx_id: outpoint.txid,
+                            utxo_index: outpoint.vout,
+                            extra_data: entry.extra_data.clone(),
+                            spender_tx_id,
+                            ack: next_ack(news),
+                        });
+                    }
+
+                    let list = &mut spending[list_index(is_active)];
+                    match list
+                        .iter_mut()
+                        .find(|m| m.tx_id == outpoint.txid && m.vout == outpoint.vout)
+                    {
+                        Some(monitor) => monitor.entries.push(entry),
+                        None => list.push(SpendingUTXOMonitor {
+                            tx_id: outpoint.txid,
+                            vout: outpoint.vout,
+                            entries: vec![entry],
+                        }),
+                    }
+                }
+            }
+        }
+
+        for is_active in [true, false] {
+            let list = MonitorKey::Transactions(is_active);
+            write_list(store, list, &txs[list_index(is_active)], transaction_id)?;
+
+            let list = MonitorKey::SpendingUTXOTransactions(is_active);
+            write_list(
+                store,
+                list,
+                &spending[list_index(is_active)],
+                transaction_id,
+            )?;
+        }
+
+        for (spender, outpoints) in spends {
+            store.write_spends(spender, &outpoints, Some(transaction_id))?;
+        }
+
+        if !txs_news.is_empty() {
+            let key = store.get_key(MonitorKey::TransactionsNews);
+            store.write_typed(&key, &txs_news, Some(transaction_id))?;
+        }
+
+        if !spending_news.is_empty() {
+            let key = store.get_key(MonitorKey::SpendingUTXOTransactionsNews);
+            store.write_typed(&key, &spending_news, Some(transaction_id))?;
+        }
+
+        if sequence > 0 {
+            let key = store.get_key(MonitorKey::NewsSequence);
+            store.write_typed(&key, sequence, Some(transaction_id))?;
+        }
+
+        Ok(())
+    }
+
+    fn push_tx(mut self, tx_id: Txid, deactivation: Option<Deactivation>) -> Self {
+        let history = self.history(deactivation);
+        self.monitors.push(DeclaredMonitor::Transaction {
+            tx_id,
+            is_active: deactivation.is_none(),
+            entry: TransactionMonitorEntry {
+                extra_data: MonitorContext::default(),
+                confirmation_trigger: None,
+                trigger_sent: false,
+                last_reported_confirmations: None,
+                last_reported_block_hash: None,
+                expires_at_height: None,
+                history,
+            },
+            news: None,
+        });
+        self
+    }
+
+    fn push_spending(mut self, outpoint: OutPoint, deactivation: Option<Deactivation>) -> Self {
+        let history = self.history(deactivation);
+        self.monitors.push(DeclaredMonitor::SpendingUTXO {
+            outpoint,
+            is_active: deactivation.is_none(),
+            entry: SpendingUTXOMonitorEntry {
+                extra_data: MonitorContext::default(),
+                spender_tx_id: None,
+                confirmation_trigger: None,
+                deadline: None,
+                value_gate: None,
+                expires_at_height: None,
+                history,
+                backfill_done: true,
+            },
+            news: None,
+        });
+        self
+    }
+
+    /// History of a monitor declared now: registered at the fixture height, then deactivated
+    /// after its confirmations if it was.
+    fn history(&self, deactivation: Option<Deactivation>) -> Vec<MonitorEvent> {
+        let height = self.tip.map(|(height, _)| height).unwrap_or_default();
+        let mut history = vec![MonitorEvent::Registered { height }];
+
+        if let Some(Deactivation::MaxConfirmations { height, reason }) = deactivation {
+            history.push(MonitorEvent::Deactivated { height, reason });
+        }
+
+        history
+    }
+
+    fn last_monitor(&mut self, call: &str) -> &mut DeclaredMonitor {
+        self.monitors
+            .last_mut()
+            .unwrap_or_else(|| panic!("{call} needs a monitor declared before it"))
+    }
+}
+
+fn list_index(is_active: bool) -> usize {
+    if is_active {
+        0
+    } else {
+        1
+    }
+}
+
+/// Writes each monitor of `list` under its own key and the index of their ids.
+fn write_list<M: ListedMonitor>(
+    store: &MonitorStore,
+    list: MonitorKey,
+    monitors: &[M],
+    transaction_id: Uuid,
+) -> Result<(), MonitorStoreError> {
+    if monitors.is_empty() {
+        return Ok(());
+    }
+
+    let index: Vec<String> = monitors.iter().map(|m| m.item_id()).collect();
+    for (id, monitor) in index.iter().zip(monitors) {
+        let key = store.get_listed_key(list, id);
+        store.write_typed(&key, monitor, Some(transaction_id))?;
+    }
+    store.write_typed(&store.get_key(list), &index, Some(transaction_id))
+}
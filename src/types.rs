@@ -1,10 +1,23 @@
-use bitcoin::{BlockHash, Transaction, Txid};
+use bitcoin::{
+    Amount, BlockHash, CompactTarget, OutPoint, ScriptBuf, Target, Transaction, TxOut, Txid, Work,
+    XOnlyPublicKey,
+};
 use bitcoin_indexer::IndexerType;
 use bitvmx_bitcoin_rpc::types::BlockHeight;
-use serde::{Deserialize, Serialize};
+use serde::{Deserialize, Deserializer, Serialize};
+use std::collections::BTreeMap;
+use std::fmt;
+use std::rc::Rc;
+use std::sync::Arc;
+use storage_backend::storage::Storage;
 use uuid::Uuid;
 
-use crate::{monitor::Monitor, store::MonitorStore};
+use crate::{
+    monitor::Monitor,
+    redaction::RedactedContext,
+    settings::MONITOR_HISTORY_LEN,
+    store::{MonitorStore, MonitoredTypes, TypesToMonitorStore},
+};
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct TransactionStore {
@@ -29,6 +42,33 @@ pub enum TransactionBlockchainStatus {
     Confirmed,
     // Represents when the transaction was confirmed by a certain number of blocks
     Finalized,
+    // Represents a transaction seen in the mempool and not confirmed yet
+    Mempool,
+}
+
+/// State of a transaction in one byte, see [`Monitor::status_bitmap`]
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u8)]
+pub enum TxState {
+    /// Neither the monitor nor the indexer know the transaction
+    Unknown = 0,
+    Mempool = 1,
+    /// In a block, with less than `confirmation_threshold` confirmations
+    Confirmed = 2,
+    Finalized = 3,
+    /// Its block was replaced by a reorg
+    Orphaned = 4,
+}
+
+impl From<&TransactionBlockchainStatus> for TxState {
+    fn from(status: &TransactionBlockchainStatus) -> Self {
+        match status {
+            TransactionBlockchainStatus::Orphan => Self::Orphaned,
+            TransactionBlockchainStatus::Confirmed => Self::Confirmed,
+            TransactionBlockchainStatus::Finalized => Self::Finalized,
+            TransactionBlockchainStatus::Mempool => Self::Mempool,
+        }
+    }
 }
 
 impl TransactionStatus {
@@ -99,62 +139,551 @@ impl BlockInfo {
     }
 }
 
-#[derive(Debug, Clone, PartialEq)]
+/// Context a monitor is registered with, sent back in its news.
+///
+/// It is stored as a string, so contexts written by older versions are read as they are. A
+/// context built with [`MonitorContext::typed`] holds a uuid, a label and a payload of arbitrary
+/// bytes, read back with [`MonitorContext::uuid`], [`MonitorContext::label`] and
+/// [`MonitorContext::payload`]; any other string is a label without uuid or payload.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[serde(transparent)]
+pub struct MonitorContext(String);
+
+impl MonitorContext {
+    /// Prefix of the contexts built with [`MonitorContext::typed`], followed by
+    /// `<uuid>:<payload hex>:<label>`.
+    const TYPED_PREFIX: &'static str = "ctx1:";
+
+    pub fn new(context: impl Into<String>) -> Self {
+        Self(context.into())
+    }
+
+    /// Context holding `uuid`, `label` and `payload`. The label may contain any character.
+    pub fn typed(uuid: Uuid, label: &str, payload: &[u8]) -> Self {
+        Self(format!(
+            "{}{}:{}:{}",
+            Self::TYPED_PREFIX,
+            uuid.hyphenated(),
+            hex::encode(payload),
+            label
+        ))
+    }
+
+    /// Uuid, payload and label of a typed context, None for any other string.
+    fn typed_parts(&self) -> Option<(Uuid, Vec<u8>, &str)> {
+        let rest = self.0.strip_prefix(Self::TYPED_PREFIX)?;
+        let mut parts = rest.splitn(3, ':');
+        let uuid = Uuid::parse_str(parts.next()?).ok()?;
+        let payload = hex::decode(parts.next()?).ok()?;
+        Some((uuid, payload, parts.next()?))
+    }
+
+    /// Uuid of a typed context, None for any other string.
+    pub fn uuid(&self) -> Option<Uuid> {
+        self.typed_parts().map(|(uuid, ..)| uuid)
+    }
+
+    /// Label of a typed context, the whole string for any other.
+    pub fn label(&self) -> &str {
+        match self.typed_parts() {
+            Some((.., label)) => label,
+            None => &self.0,
+        }
+    }
+
+    /// Payload of a typed context, empty for any other string.
+    pub fn payload(&self) -> Vec<u8> {
+        self.typed_parts()
+            .map(|(_, payload, _)| payload)
+            .unwrap_or_default()
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::ops::Deref for MonitorContext {
+    type Target = str;
+
+    fn deref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for MonitorContext {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl From<String> for MonitorContext {
+    fn from(context: String) -> Self {
+        Self(context)
+    }
+}
+
+impl From<&str> for MonitorContext {
+    fn from(context: &str) -> Self {
+        Self(context.to_string())
+    }
+}
+
+impl From<&String> for MonitorContext {
+    fn from(context: &String) -> Self {
+        Self(context.clone())
+    }
+}
+
+impl From<MonitorContext> for String {
+    fn from(context: MonitorContext) -> Self {
+        context.0
+    }
+}
+
+impl PartialEq<str> for MonitorContext {
+    fn eq(&self, other: &str) -> bool {
+        self.0 == other
+    }
+}
+
+impl PartialEq<&str> for MonitorContext {
+    fn eq(&self, other: &&str) -> bool {
+        self.0 == *other
+    }
+}
+
+impl PartialEq<String> for MonitorContext {
+    fn eq(&self, other: &String) -> bool {
+        &self.0 == other
+    }
+}
+
+impl std::borrow::Borrow<str> for MonitorContext {
+    fn borrow(&self) -> &str {
+        &self.0
+    }
+}
+
+#[derive(Clone, PartialEq)]
 pub enum TypesToMonitor {
     // Transactions to monitor
     // - Vec<Txid>: The transaction IDs to monitor
-    // - String: The context of the transaction
+    // - MonitorContext: The context of the transaction
     // - Option<u32>: The number of confirmations to wait for receive news about the transaction
-    Transactions(Vec<Txid>, String, Option<u32>),
+    // - Option<BlockHeight>: Height the monitor expires at if the transaction wasn't mined by then
+    Transactions(Vec<Txid>, MonitorContext, Option<u32>, Option<BlockHeight>),
 
     // Spending UTXO transaction to monitor
     // - Txid: The transaction ID to monitor
     // - u32: The vout index of the UTXO to monitor
-    // - String: The context of the transaction
+    // - MonitorContext: The context of the transaction
     // - Option<u32>: The number of confirmations to wait for receive news about the transaction
-    SpendingUTXOTransaction(Txid, u32, String, Option<u32>),
+    // - Option<SpendDeadline>: Deadline to look for unconfirmed spends in the mempool before it
+    // - Option<SpendValueGate>: Spends moving less than a value are only reported as minor spends
+    // - Option<BlockHeight>: Height the monitor expires at if the UTXO wasn't spent by then
+    SpendingUTXOTransaction(
+        Txid,
+        u32,
+        MonitorContext,
+        Option<u32>,
+        Option<SpendDeadline>,
+        Option<SpendValueGate>,
+        Option<BlockHeight>,
+    ),
 
     // Rsk pegin transaction to monitor
     // - Option<u32>: The number of confirmations to wait for receive news about the transaction
-    RskPegin(Option<u32>),
+    // - Vec<ScriptBuf>: The script_pubkeys of the committee addresses the first output of a pegin
+    //   must pay to, any address when empty
+    // - Option<(u64, u64)>: The first and last packet numbers of the pegins to report (both
+    //   included), any packet number when None
+    // - Option<Amount>: The minimum amount the first output of a pegin must pay, any amount when
+    //   None
+    RskPegin(
+        Option<u32>,
+        Vec<ScriptBuf>,
+        Option<(u64, u64)>,
+        Option<Amount>,
+    ),
 
     // New block to monitor
     // - BlockHeight: The block height to monitor
     NewBlock,
+
+    // Outputs paying to a script, for scripts that can't be expressed as an address
+    // - ScriptBuf: The script_pubkey to look for in the outputs of every new block
+    // - MonitorContext: The context of the transaction
+    // - Option<u32>: The number of confirmations to wait for receive news about each transaction found
+    // - Option<OutputFilter>: Outputs counted without sending news, e.g. dust sent to the address
+    ScriptPubKey(ScriptBuf, MonitorContext, Option<u32>, Option<OutputFilter>),
+
+    // Transactions tagged with an OP_RETURN output, for protocols other than the RSK pegin
+    // - Vec<u8>: The prefix the data pushed in the OP_RETURN output starts with
+    // - MonitorContext: The context of the transaction
+    OpReturnPrefix(Vec<u8>, MonitorContext),
+
+    // Mutually exclusive transactions, e.g. the happy path and the dispute of a protocol step.
+    // Each one is monitored as a transaction until the first to reach the confirmation
+    // threshold wins, the others are canceled then
+    // - Vec<Txid>: The transaction IDs of the alternatives
+    // - MonitorContext: The context of the transactions
+    Alternatives(Vec<Txid>, MonitorContext),
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+// Contexts may hold identifiers that must not end up in logs, see `crate::redaction`
+impl fmt::Debug for TypesToMonitor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transactions(tx_ids, context, trigger, expires_at) => f
+                .debug_tuple("Transactions")
+                .field(tx_ids)
+                .field(&RedactedContext(context))
+                .field(trigger)
+                .field(expires_at)
+                .finish(),
+            Self::SpendingUTXOTransaction(
+                tx_id,
+                vout,
+                context,
+                trigger,
+                deadline,
+                value_gate,
+                expires_at,
+            ) => f
+                .debug_tuple("SpendingUTXOTransaction")
+                .field(tx_id)
+                .field(vout)
+                .field(&RedactedContext(context))
+                .field(trigger)
+                .field(deadline)
+                .field(value_gate)
+                .field(expires_at)
+                .finish(),
+            Self::RskPegin(trigger, committee, packet_range, min_amount) => f
+                .debug_tuple("RskPegin")
+                .field(trigger)
+                .field(committee)
+                .field(packet_range)
+                .field(min_amount)
+                .finish(),
+            Self::NewBlock => f.write_str("NewBlock"),
+            Self::ScriptPubKey(script_pubkey, context, trigger, filter) => f
+                .debug_tuple("ScriptPubKey")
+                .field(script_pubkey)
+                .field(&RedactedContext(context))
+                .field(trigger)
+                .field(filter)
+                .finish(),
+            Self::OpReturnPrefix(prefix, context) => f
+                .debug_tuple("OpReturnPrefix")
+                .field(prefix)
+                .field(&RedactedContext(context))
+                .finish(),
+            Self::Alternatives(tx_ids, context) => f
+                .debug_tuple("Alternatives")
+                .field(tx_ids)
+                .field(&RedactedContext(context))
+                .finish(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub enum MonitorNews {
     // Transaction news
     // - Txid: The transaction ID
     // - TransactionStatus: The status of the transaction
-    // - String: The context of the transaction previously sent to the monitor
-    Transaction(Txid, TransactionStatus, String),
+    // - MonitorContext: The context of the transaction previously sent to the monitor
+    Transaction(Txid, TransactionStatus, MonitorContext),
 
     // Spending UTXO transaction news
     // - Txid: The transaction ID
     // - u32: The vout index of the UTXO
     // - TransactionStatus: The status of the transaction
-    // - String: The context of the transaction previously sent to the monitor
-    SpendingUTXOTransaction(Txid, u32, TransactionStatus, String),
+    // - MonitorContext: The context of the transaction previously sent to the monitor
+    SpendingUTXOTransaction(Txid, u32, TransactionStatus, MonitorContext),
+
+    // Spend of a UTXO with a deadline seen in the mempool
+    // - Txid: The transaction ID
+    // - u32: The vout index of the UTXO
+    // - Txid: The unconfirmed spending transaction ID
+    // - MonitorContext: The context of the transaction previously sent to the monitor
+    SpendingUTXOUnconfirmed(Txid, u32, Txid, MonitorContext),
+
+    // Spend of a UTXO moving less than the `SpendValueGate` of its monitor
+    // - Txid: The transaction ID
+    // - u32: The vout index of the UTXO
+    // - TransactionStatus: The status of the spending transaction
+    // - MonitorContext: The context of the transaction previously sent to the monitor
+    MinorSpend(Txid, u32, TransactionStatus, MonitorContext),
 
     // Rsk pegin transaction news
     // - Txid: The transaction ID
     // - TransactionStatus: The status of the transaction
-    RskPeginTransaction(Txid, TransactionStatus),
+    // - Option<RskPeginData>: The payload of the pegin OP_RETURN
+    RskPeginTransaction(Txid, TransactionStatus, Option<RskPeginData>),
+
+    // Output paying to a monitored script_pubkey
+    // - Txid: The transaction ID of the output
+    // - u32: The vout index of the output
+    // - TransactionStatus: The status of the transaction
+    // - MonitorContext: The context of the script previously sent to the monitor
+    ScriptPubKeyTransaction(Txid, u32, TransactionStatus, MonitorContext),
+
+    // Transaction with an OP_RETURN output starting with a monitored prefix
+    // - Txid: The transaction ID
+    // - Vec<u8>: The full data pushed in the OP_RETURN output
+    // - MonitorContext: The context of the prefix previously sent to the monitor
+    OpReturnTransaction(Txid, Vec<u8>, MonitorContext),
 
     // New block news
     // - BlockHeight: The block height
     // - BlockHash: The block hash
     NewBlock(BlockHeight, BlockHash),
+
+    // The block of a monitored transaction was orphaned by a reorg. A Transaction news follows
+    // once it confirms in the new chain.
+    // - Txid: The transaction ID
+    // - TransactionStatus: The status of the transaction, orphaned
+    // - MonitorContext: The context of the transaction previously sent to the monitor
+    OrphanedTransaction(Txid, TransactionStatus, MonitorContext),
+
+    // A transaction monitor was moved to a replacement transaction (RBF)
+    // - old: The replaced transaction ID
+    // - new: The replacement transaction ID
+    // - context: The context of the transaction previously sent to the monitor
+    MonitorReplaced {
+        old: Txid,
+        new: Txid,
+        context: MonitorContext,
+    },
+
+    // Warning: a replaced transaction was confirmed anyway
+    // - old: The replaced transaction ID, the one confirmed
+    // - new: The replacement transaction ID
+    // - context: The context of the transaction previously sent to the monitor
+    ReplacedTransactionConfirmed {
+        old: Txid,
+        new: Txid,
+        context: MonitorContext,
+    },
+
+    // The store was restored from a backup, the news sequence restarts from a new epoch
+    // - old_epoch: The epoch before the restore was detected
+    // - new_epoch: The epoch of the news from now on
+    SequenceEpochChanged {
+        old_epoch: u64,
+        new_epoch: u64,
+    },
+
+    // A reorg changed monitored items, the full report is returned by `last_reorg_impact`
+    // - old_tip: The tip processed before the reorg
+    // - new_tip: The tip of the new branch
+    // - summary: The number of items changed by the reorg
+    Reorg {
+        old_tip: BlockHash,
+        new_tip: BlockHash,
+        summary: ReorgSummary,
+    },
+
+    // A reorg replaced blocks with a branch of less work, which a node never does on its own
+    // (possible eclipse attack). News reopened by the reorg can't be acked until this one is.
+    // - height: The first height replaced by the reorg
+    // - old_work: The work of the replaced blocks
+    // - new_work: The work of the blocks of the new branch
+    LowWorkReorg {
+        height: BlockHeight,
+        old_work: Work,
+        new_work: Work,
+    },
+
+    // An alternative reached the confirmation threshold, the monitors of the others were canceled.
+    // A reorg taking the winner below the threshold before it is finalized opens the race again.
+    // - winner: The transaction ID of the alternative confirmed
+    // - losers: The transaction IDs of the other alternatives
+    // - context: The context of the alternatives previously sent to the monitor
+    AlternativeResolved {
+        winner: Txid,
+        losers: Vec<Txid>,
+        context: MonitorContext,
+    },
+
+    // A Transaction or SpendingUTXO monitor reached its expiry height without a detection and was
+    // deactivated
+    // - tx_id: The monitored transaction ID, or the transaction of the monitored UTXO
+    // - vout: The vout index of the monitored UTXO, None for a Transaction monitor
+    // - context: The context of the monitor previously sent to the monitor
+    // - height: The expiry height of the monitor
+    MonitorExpired {
+        tx_id: Txid,
+        vout: Option<u32>,
+        context: MonitorContext,
+        height: BlockHeight,
+    },
+
+    // The health score crossed a threshold and the monitor entered or left its degraded mode,
+    // see `crate::health::HealthScore`
+    // - state: The state of the monitor from now on
+    // - score: The health score that caused the change
+    HealthStateChanged {
+        state: HealthState,
+        score: u32,
+    },
+
+    // A monitor was resolved by an operator with `resolve_manually` and deactivated, its pending
+    // news were acknowledged
+    // - DetectionDescriptor: The monitor resolved, with no transaction detected
+    // - String: The note given with the resolution
+    ManuallyResolved(DetectionDescriptor, String),
+
+    // Every predicate of a composite rule registered with `register_composite` was satisfied,
+    // sent once per rule
+    // - String: The id of the rule
+    Composite(String),
 }
 
-#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+impl fmt::Debug for MonitorNews {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transaction(tx_id, status, context) => f
+                .debug_tuple("Transaction")
+                .field(tx_id)
+                .field(status)
+                .field(&RedactedContext(context))
+                .finish(),
+            Self::SpendingUTXOTransaction(tx_id, vout, status, context) => f
+                .debug_tuple("SpendingUTXOTransaction")
+                .field(tx_id)
+                .field(vout)
+                .field(status)
+                .field(&RedactedContext(context))
+                .finish(),
+            Self::SpendingUTXOUnconfirmed(tx_id, vout, spender, context) => f
+                .debug_tuple("SpendingUTXOUnconfirmed")
+                .field(tx_id)
+                .field(vout)
+                .field(spender)
+                .field(&RedactedContext(context))
+                .finish(),
+            Self::MinorSpend(tx_id, vout, status, context) => f
+                .debug_tuple("MinorSpend")
+                .field(tx_id)
+                .field(vout)
+                .field(status)
+                .field(&RedactedContext(context))
+                .finish(),
+            Self::RskPeginTransaction(tx_id, status, data) => f
+                .debug_tuple("RskPeginTransaction")
+                .field(tx_id)
+                .field(status)
+                .field(data)
+                .finish(),
+            Self::ScriptPubKeyTransaction(tx_id, vout, status, context) => f
+                .debug_tuple("ScriptPubKeyTransaction")
+                .field(tx_id)
+                .field(vout)
+                .field(status)
+                .field(&RedactedContext(context))
+                .finish(),
+            Self::OpReturnTransaction(tx_id, data, context) => f
+                .debug_tuple("OpReturnTransaction")
+                .field(tx_id)
+                .field(data)
+                .field(&RedactedContext(context))
+                .finish(),
+            Self::NewBlock(height, hash) => {
+                f.debug_tuple("NewBlock").field(height).field(hash).finish()
+            }
+            Self::OrphanedTransaction(tx_id, status, context) => f
+                .debug_tuple("OrphanedTransaction")
+                .field(tx_id)
+                .field(status)
+                .field(&RedactedContext(context))
+                .finish(),
+            Self::MonitorReplaced { old, new, context } => f
+                .debug_struct("MonitorReplaced")
+                .field("old", old)
+                .field("new", new)
+                .field("context", &RedactedContext(context))
+                .finish(),
+            Self::ReplacedTransactionConfirmed { old, new, context } => f
+                .debug_struct("ReplacedTransactionConfirmed")
+                .field("old", old)
+                .field("new", new)
+                .field("context", &RedactedContext(context))
+                .finish(),
+            Self::SequenceEpochChanged {
+                old_epoch,
+                new_epoch,
+            } => f
+                .debug_struct("SequenceEpochChanged")
+                .field("old_epoch", old_epoch)
+                .field("new_epoch", new_epoch)
+                .finish(),
+            Self::Reorg {
+                old_tip,
+                new_tip,
+                summary,
+            } => f
+                .debug_struct("Reorg")
+                .field("old_tip", old_tip)
+                .field("new_tip", new_tip)
+                .field("summary", summary)
+                .finish(),
+            Self::LowWorkReorg {
+                height,
+                old_work,
+                new_work,
+            } => f
+                .debug_struct("LowWorkReorg")
+                .field("height", height)
+                .field("old_work", old_work)
+                .field("new_work", new_work)
+                .finish(),
+            Self::AlternativeResolved {
+                winner,
+                losers,
+                context,
+            } => f
+                .debug_struct("AlternativeResolved")
+                .field("winner", winner)
+                .field("losers", losers)
+                .field("context", &RedactedContext(context))
+                .finish(),
+            Self::MonitorExpired {
+                tx_id,
+                vout,
+                context,
+                height,
+            } => f
+                .debug_struct("MonitorExpired")
+                .field("tx_id", tx_id)
+                .field("vout", vout)
+                .field("context", &RedactedContext(context))
+                .field("height", height)
+                .finish(),
+            Self::HealthStateChanged { state, score } => f
+                .debug_struct("HealthStateChanged")
+                .field("state", state)
+                .field("score", score)
+                .finish(),
+            Self::ManuallyResolved(descriptor, note) => f
+                .debug_tuple("ManuallyResolved")
+                .field(descriptor)
+                .field(note)
+                .finish(),
+            Self::Composite(id) => f.debug_tuple("Composite").field(id).finish(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, PartialEq)]
 pub enum AckMonitorNews {
     // Transaction news
     // - Txid: The transaction ID
-    // - String: The context of the transaction
-    Transaction(Txid, String),
+    // - MonitorContext: The context of the transaction
+    Transaction(Txid, MonitorContext),
 
     // Rsk pegin transaction news
     // - Txid: The transaction ID
@@ -163,26 +692,439 @@ pub enum AckMonitorNews {
     // Spending UTXO transaction news
     // - Txid: The transaction ID
     // - u32: The vout index of the UTXO
-    // - String: The context of the transaction
-    SpendingUTXOTransaction(Txid, u32, String),
+    // - MonitorContext: The context of the transaction
+    SpendingUTXOTransaction(Txid, u32, MonitorContext),
+
+    // Unconfirmed spending UTXO transaction news
+    // - Txid: The transaction ID
+    // - u32: The vout index of the UTXO
+    // - MonitorContext: The context of the transaction
+    SpendingUTXOUnconfirmed(Txid, u32, MonitorContext),
+
+    // Minor spend news
+    // - Txid: The transaction ID
+    // - u32: The vout index of the UTXO
+    // - MonitorContext: The context of the transaction
+    MinorSpend(Txid, u32, MonitorContext),
+
+    // Script pubkey output news
+    // - Txid: The transaction ID of the output
+    // - u32: The vout index of the output
+    // - MonitorContext: The context of the script
+    ScriptPubKeyTransaction(Txid, u32, MonitorContext),
+
+    // OP_RETURN prefix news
+    // - Txid: The transaction ID
+    // - MonitorContext: The context of the prefix
+    OpReturnTransaction(Txid, MonitorContext),
 
     // New block news
+    // - Option<BlockHash>: The block of the news, the oldest pending block when None
+    NewBlock(Option<BlockHash>),
+
+    // Orphaned transaction news
+    // - Txid: The transaction ID
+    // - MonitorContext: The context of the transaction
+    OrphanedTransaction(Txid, MonitorContext),
+
+    // Monitor replaced news
+    // - old: The replaced transaction ID
+    // - context: The context of the transaction
+    MonitorReplaced {
+        old: Txid,
+        context: MonitorContext,
+    },
+
+    // Replaced transaction confirmed news
+    // - old: The replaced transaction ID
+    // - context: The context of the transaction
+    ReplacedTransactionConfirmed {
+        old: Txid,
+        context: MonitorContext,
+    },
+
+    // Sequence epoch changed news
+    // - new_epoch: The epoch of the news
+    SequenceEpochChanged {
+        new_epoch: u64,
+    },
+
+    // Reorg news
+    // - new_tip: The tip of the new branch
+    Reorg {
+        new_tip: BlockHash,
+    },
+
+    // Low work reorg news
+    // - height: The first height replaced by the reorg
+    LowWorkReorg {
+        height: BlockHeight,
+    },
+
+    // Alternative resolved news
+    // - winner: The transaction ID of the alternative confirmed
+    // - context: The context of the alternatives
+    AlternativeResolved {
+        winner: Txid,
+        context: MonitorContext,
+    },
+
+    // Monitor expired news
+    // - tx_id: The monitored transaction ID, or the transaction of the monitored UTXO
+    // - vout: The vout index of the monitored UTXO, None for a Transaction monitor
+    // - context: The context of the monitor
+    MonitorExpired {
+        tx_id: Txid,
+        vout: Option<u32>,
+        context: MonitorContext,
+    },
+
+    // Health state changed news
+    // - state: The state the monitor changed to
+    HealthStateChanged {
+        state: HealthState,
+    },
+
+    // Manually resolved news
+    // - DetectionDescriptor: The monitor resolved, as sent in the news
+    ManuallyResolved(DetectionDescriptor),
+
+    // Composite news
+    // - String: The id of the rule
+    Composite(String),
+}
+
+impl fmt::Debug for AckMonitorNews {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Transaction(tx_id, context) => f
+                .debug_tuple("Transaction")
+                .field(tx_id)
+                .field(&RedactedContext(context))
+                .finish(),
+            Self::OrphanedTransaction(tx_id, context) => f
+                .debug_tuple("OrphanedTransaction")
+                .field(tx_id)
+                .field(&RedactedContext(context))
+                .finish(),
+            Self::RskPeginTransaction(tx_id) => {
+                f.debug_tuple("RskPeginTransaction").field(tx_id).finish()
+            }
+            Self::SpendingUTXOTransaction(tx_id, vout, context) => f
+                .debug_tuple("SpendingUTXOTransaction")
+                .field(tx_id)
+                .field(vout)
+                .field(&RedactedContext(context))
+                .finish(),
+            Self::SpendingUTXOUnconfirmed(tx_id, vout, context) => f
+                .debug_tuple("SpendingUTXOUnconfirmed")
+                .field(tx_id)
+                .field(vout)
+                .field(&RedactedContext(context))
+                .finish(),
+            Self::MinorSpend(tx_id, vout, context) => f
+                .debug_tuple("MinorSpend")
+                .field(tx_id)
+                .field(vout)
+                .field(&RedactedContext(context))
+                .finish(),
+            Self::ScriptPubKeyTransaction(tx_id, vout, context) => f
+                .debug_tuple("ScriptPubKeyTransaction")
+                .field(tx_id)
+                .field(vout)
+                .field(&RedactedContext(context))
+                .finish(),
+            Self::OpReturnTransaction(tx_id, context) => f
+                .debug_tuple("OpReturnTransaction")
+                .field(tx_id)
+                .field(&RedactedContext(context))
+                .finish(),
+            Self::NewBlock(hash) => f.debug_tuple("NewBlock").field(hash).finish(),
+            Self::MonitorReplaced { old, context } => f
+                .debug_struct("MonitorReplaced")
+                .field("old", old)
+                .field("context", &RedactedContext(context))
+                .finish(),
+            Self::ReplacedTransactionConfirmed { old, context } => f
+                .debug_struct("ReplacedTransactionConfirmed")
+                .field("old", old)
+                .field("context", &RedactedContext(context))
+                .finish(),
+            Self::SequenceEpochChanged { new_epoch } => f
+                .debug_struct("SequenceEpochChanged")
+                .field("new_epoch", new_epoch)
+                .finish(),
+            Self::Reorg { new_tip } => f.debug_struct("Reorg").field("new_tip", new_tip).finish(),
+            Self::LowWorkReorg { height } => f
+                .debug_struct("LowWorkReorg")
+                .field("height", height)
+                .finish(),
+            Self::AlternativeResolved { winner, context } => f
+                .debug_struct("AlternativeResolved")
+                .field("winner", winner)
+                .field("context", &RedactedContext(context))
+                .finish(),
+            Self::MonitorExpired {
+                tx_id,
+                vout,
+                context,
+            } => f
+                .debug_struct("MonitorExpired")
+                .field("tx_id", tx_id)
+                .field("vout", vout)
+                .field("context", &RedactedContext(context))
+                .finish(),
+            Self::HealthStateChanged { state } => f
+                .debug_struct("HealthStateChanged")
+                .field("state", state)
+                .finish(),
+            Self::ManuallyResolved(descriptor) => {
+                f.debug_tuple("ManuallyResolved").field(descriptor).finish()
+            }
+            Self::Composite(id) => f.debug_tuple("Composite").field(id).finish(),
+        }
+    }
+}
+
+impl AckMonitorNews {
+    /// Whether acknowledging this marks `news` as acknowledged.
+    pub fn acknowledges(&self, news: &MonitoredTypes) -> bool {
+        match (self, news) {
+            (Self::Transaction(tx_id, context), MonitoredTypes::Transaction(t, c))
+            | (
+                Self::OrphanedTransaction(tx_id, context),
+                MonitoredTypes::OrphanedTransaction(t, c),
+            )
+            | (
+                Self::OpReturnTransaction(tx_id, context),
+                MonitoredTypes::OpReturnTransaction(_, c, t, _),
+            ) => tx_id == t && context == c,
+            (Self::RskPeginTransaction(tx_id), MonitoredTypes::RskPeginTransaction(t, _)) => {
+                tx_id == t
+            }
+            (
+                Self::SpendingUTXOTransaction(tx_id, vout, context),
+                MonitoredTypes::SpendingUTXOTransaction(t, v, c, _),
+            )
+            | (
+                Self::SpendingUTXOUnconfirmed(tx_id, vout, context),
+                MonitoredTypes::SpendingUTXOUnconfirmed(t, v, c, _),
+            )
+            | (Self::MinorSpend(tx_id, vout, context), MonitoredTypes::MinorSpend(t, v, c, _))
+            | (
+                Self::ScriptPubKeyTransaction(tx_id, vout, context),
+                MonitoredTypes::ScriptPubKeyTransaction(_, c, t, v),
+            ) => tx_id == t && vout == v && context == c,
+            (Self::MonitorReplaced { old, context }, MonitoredTypes::MonitorReplaced(o, _, c))
+            | (
+                Self::ReplacedTransactionConfirmed { old, context },
+                MonitoredTypes::ReplacedTransactionConfirmed(o, _, c),
+            ) => old == o && context == c,
+            (Self::NewBlock(hash), MonitoredTypes::NewBlock(h, _)) => {
+                hash.is_none_or(|hash| hash == *h)
+            }
+            (
+                Self::SequenceEpochChanged { new_epoch },
+                MonitoredTypes::SequenceEpochChanged(_, epoch),
+            ) => new_epoch == epoch,
+            (Self::Reorg { new_tip }, MonitoredTypes::Reorg(_, tip, _)) => new_tip == tip,
+            (Self::LowWorkReorg { height }, MonitoredTypes::LowWorkReorg(h, ..)) => height == h,
+            (
+                Self::AlternativeResolved { winner, context },
+                MonitoredTypes::AlternativeResolved(w, _, c),
+            ) => winner == w && context == c,
+            (
+                Self::MonitorExpired {
+                    tx_id,
+                    vout,
+                    context,
+                },
+                MonitoredTypes::MonitorExpired(t, v, c, _),
+            ) => tx_id == t && vout == v && context == c,
+            (Self::HealthStateChanged { state }, MonitoredTypes::HealthStateChanged(s, _)) => {
+                state == s
+            }
+            (Self::ManuallyResolved(descriptor), MonitoredTypes::ManuallyResolved(target, _)) => {
+                descriptor.monitor == *target
+            }
+            (Self::Composite(id), MonitoredTypes::Composite(i)) => id == i,
+            _ => false,
+        }
+    }
+}
+
+/// The ack of a news returned by `get_news`, which acknowledges it and only it.
+impl From<&MonitorNews> for AckMonitorNews {
+    fn from(news: &MonitorNews) -> Self {
+        match news {
+            MonitorNews::Transaction(tx_id, _, context) => {
+                Self::Transaction(*tx_id, context.clone())
+            }
+            MonitorNews::SpendingUTXOTransaction(tx_id, vout, _, context) => {
+                Self::SpendingUTXOTransaction(*tx_id, *vout, context.clone())
+            }
+            MonitorNews::SpendingUTXOUnconfirmed(tx_id, vout, _, context) => {
+                Self::SpendingUTXOUnconfirmed(*tx_id, *vout, context.clone())
+            }
+            MonitorNews::MinorSpend(tx_id, vout, _, context) => {
+                Self::MinorSpend(*tx_id, *vout, context.clone())
+            }
+            MonitorNews::RskPeginTransaction(tx_id, ..) => Self::RskPeginTransaction(*tx_id),
+            MonitorNews::ScriptPubKeyTransaction(tx_id, vout, _, context) => {
+                Self::ScriptPubKeyTransaction(*tx_id, *vout, context.clone())
+            }
+            MonitorNews::OpReturnTransaction(tx_id, _, context) => {
+                Self::OpReturnTransaction(*tx_id, context.clone())
+            }
+            MonitorNews::NewBlock(_, hash) => Self::NewBlock(Some(*hash)),
+            MonitorNews::OrphanedTransaction(tx_id, _, context) => {
+                Self::OrphanedTransaction(*tx_id, context.clone())
+            }
+            MonitorNews::MonitorReplaced { old, context, .. } => Self::MonitorReplaced {
+                old: *old,
+                context: context.clone(),
+            },
+            MonitorNews::ReplacedTransactionConfirmed { old, context, .. } => {
+                Self::ReplacedTransactionConfirmed {
+                    old: *old,
+                    context: context.clone(),
+                }
+            }
+            MonitorNews::SequenceEpochChanged { new_epoch, .. } => Self::SequenceEpochChanged {
+                new_epoch: *new_epoch,
+            },
+            MonitorNews::Reorg { new_tip, .. } => Self::Reorg { new_tip: *new_tip },
+            MonitorNews::LowWorkReorg { height, .. } => Self::LowWorkReorg { height: *height },
+            MonitorNews::AlternativeResolved {
+                winner, context, ..
+            } => Self::AlternativeResolved {
+                winner: *winner,
+                context: context.clone(),
+            },
+            MonitorNews::MonitorExpired {
+                tx_id,
+                vout,
+                context,
+                ..
+            } => Self::MonitorExpired {
+                tx_id: *tx_id,
+                vout: *vout,
+                context: context.clone(),
+            },
+            MonitorNews::HealthStateChanged { state, .. } => {
+                Self::HealthStateChanged { state: *state }
+            }
+            MonitorNews::ManuallyResolved(descriptor, _) => {
+                Self::ManuallyResolved(descriptor.clone())
+            }
+            MonitorNews::Composite(id) => Self::Composite(id.clone()),
+        }
+    }
+}
+
+/// Kinds of monitors, in the order a tick evaluates them, see [`crate::monitor::Monitor::tick`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum MonitorKind {
     NewBlock,
+    Transaction,
+    SpendingUTXOTransaction,
+    RskPegin,
+    ScriptPubKey,
+    OpReturnPrefix,
+}
+
+impl MonitorKind {
+    /// Every kind, in the order a tick evaluates them.
+    pub const ALL: [MonitorKind; 6] = [
+        MonitorKind::NewBlock,
+        MonitorKind::Transaction,
+        MonitorKind::SpendingUTXOTransaction,
+        MonitorKind::RskPegin,
+        MonitorKind::ScriptPubKey,
+        MonitorKind::OpReturnPrefix,
+    ];
+}
+
+/// Whether the monitor runs normally or in its degraded mode, see [`crate::health::HealthScore`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum HealthState {
+    #[default]
+    Healthy,
+    /// News are resolved from their snapshots and ticks process fewer blocks, until the health
+    /// score recovers
+    Degraded,
+}
+
+/// How the transaction status of a news is resolved when the news is read
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Resolution {
+    // The status is fetched from the indexer when the news is read
+    Fresh,
+    // The status stored when the news was emitted is returned as is
+    #[default]
+    Snapshot,
+    // The stored status with confirmations recomputed from the monitor height
+    SnapshotWithHeightAdjustment,
+}
+
+/// News along with the resolution used to build it and its position in the news sequence.
+///
+/// Sequences only grow within an epoch, a new epoch starts when the store is found restored
+/// from a backup (see `MonitorNews::SequenceEpochChanged`).
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NewsEnvelope {
+    pub news: MonitorNews,
+    pub resolution: Resolution,
+    pub epoch: u64,
+    pub sequence: u64,
+    /// Metadata of the monitor the news comes from, empty if none
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+    /// Set when the news was decided on an orphan flag of the indexer the chain log of the
+    /// monitor disagreed with, see `MonitorCounters::orphan_disagreements`.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub orphan_disputed: bool,
+}
+
+/// Position of a pending news in the pages of `get_news_page`: news are ordered by sequence and
+/// then by id, so acknowledging news doesn't move the ones after a cursor.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct NewsCursor {
+    pub sequence: u64,
+    pub id: String,
+}
+
+/// A page of pending news, `next` is the cursor to ask for the following page, None after the last one.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NewsPage {
+    pub news: Vec<NewsEnvelope>,
+    pub next: Option<NewsCursor>,
 }
 
 pub type Id = Uuid;
 
-pub type MonitorType = Monitor<IndexerType, MonitorStore>;
+/// Monitor on the node built by `Monitor::new_with_paths`. Not `Send` while the indexer holds
+/// the storage as `Rc<Storage>`.
+pub type MonitorType = Monitor<IndexerType, MonitorStore<Rc<Storage>>>;
 
 pub type FullBlock = bitcoin_indexer::types::FullBlock;
 
+/// Transaction of the block being processed, shared by every monitor that detects it in the tick
+pub type TransactionRef = Arc<Transaction>;
+
 // Storage types for monitor store
 
-/// News acknowledgment info (block_hash, acknowledged)
+/// News acknowledgment info (block_hash, acknowledged, sequence)
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct NewsAck {
     pub block_hash: BlockHash,
     pub acknowledged: bool,
+    /// Position of the news in the news sequence, 0 for news stored before sequences existed
+    #[serde(default)]
+    pub sequence: u64,
+    /// Set when the news was acknowledged by something else than a call to `ack_news`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<AckMetadata>,
 }
 
 impl NewsAck {
@@ -190,15 +1132,31 @@ impl NewsAck {
         Self {
             block_hash,
             acknowledged,
+            sequence: 0,
+            metadata: None,
         }
     }
 }
 
+/// Who acknowledged a news on behalf of the caller, and why
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AckMetadata {
+    pub actor: AckActor,
+    pub note: String,
+}
+
+/// Acknowledgments made without a call to `ack_news`
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AckActor {
+    /// The monitor of the news was resolved with `resolve_manually`, the note is the one given
+    ManualResolution,
+}
+
 /// Transaction news entry stored in storage
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct TransactionNewsEntry {
     pub tx_id: Txid,
-    pub extra_data: String,
+    pub extra_data: MonitorContext,
     pub ack: NewsAck,
 }
 
@@ -207,6 +1165,9 @@ pub struct TransactionNewsEntry {
 pub struct RskPeginNewsEntry {
     pub tx_id: Txid,
     pub ack: NewsAck,
+    /// Missing in the news stored by older versions
+    #[serde(default)]
+    pub data: Option<RskPeginData>,
 }
 
 /// SpendingUTXO transaction news entry stored in storage
@@ -214,17 +1175,478 @@ pub struct RskPeginNewsEntry {
 pub struct SpendingUTXONewsEntry {
     pub tx_id: Txid,
     pub utxo_index: u32,
-    pub extra_data: String,
+    pub extra_data: MonitorContext,
     pub spender_tx_id: Txid,
     pub ack: NewsAck,
 }
 
-/// Transaction monitor entry (extra_data, confirmation_trigger, trigger_sent)
+/// ScriptPubKey output news entry stored in storage
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ScriptPubKeyNewsEntry {
+    pub script_pubkey: ScriptBuf,
+    pub extra_data: MonitorContext,
+    pub tx_id: Txid,
+    pub vout: u32,
+    pub ack: NewsAck,
+}
+
+/// OP_RETURN prefix news entry stored in storage
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct OpReturnNewsEntry {
+    pub prefix: Vec<u8>,
+    pub extra_data: MonitorContext,
+    pub tx_id: Txid,
+    pub payload: Vec<u8>,
+    pub ack: NewsAck,
+}
+
+/// Replaced transaction news entry stored in storage (MonitorReplaced or ReplacedTransactionConfirmed)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReplacementNewsEntry {
+    pub old_tx_id: Txid,
+    pub new_tx_id: Txid,
+    pub extra_data: MonitorContext,
+    pub old_confirmed: bool,
+    pub ack: NewsAck,
+}
+
+/// Alternative resolved news entry stored in storage, one per race
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AlternativeNewsEntry {
+    pub winner: Txid,
+    pub losers: Vec<Txid>,
+    pub extra_data: MonitorContext,
+    pub ack: NewsAck,
+}
+
+/// Composite news entry stored in storage, one per rule
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CompositeNewsEntry {
+    pub id: String,
+    pub ack: NewsAck,
+}
+
+/// Monitor expired news entry stored in storage, `vout` is None for a Transaction monitor
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ExpiredMonitorNewsEntry {
+    pub tx_id: Txid,
+    pub vout: Option<u32>,
+    pub extra_data: MonitorContext,
+    pub expires_at_height: BlockHeight,
+    pub ack: NewsAck,
+}
+
+/// Manually resolved news entry stored in storage, one per monitor target resolved
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ManualResolutionNewsEntry {
+    pub target: SnoozeTarget,
+    pub note: String,
+    pub ack: NewsAck,
+}
+
+/// New block news entry stored in storage, one per block until acknowledged. The block hash is
+/// the one of the ack.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NewBlockNewsEntry {
+    /// Missing in the news stored by older versions
+    pub height: Option<BlockHeight>,
+    pub ack: NewsAck,
+}
+
+/// Sequence epoch changed news entry stored in storage
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct EpochNewsEntry {
+    pub old_epoch: u64,
+    pub new_epoch: u64,
+    pub ack: NewsAck,
+}
+
+/// Reorg news entry stored in storage
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReorgNewsEntry {
+    pub old_tip: BlockHash,
+    pub new_tip: BlockHash,
+    pub summary: ReorgSummary,
+    pub ack: NewsAck,
+}
+
+/// Health state changed news entry stored in storage
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct HealthNewsEntry {
+    pub state: HealthState,
+    pub score: u32,
+    pub ack: NewsAck,
+}
+
+/// Low work reorg news entry stored in storage
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LowWorkReorgNewsEntry {
+    pub height: BlockHeight,
+    pub old_work: Work,
+    pub new_work: Work,
+    pub ack: NewsAck,
+}
+
+/// Where the monitor gets its blocks from, see [`crate::config::MonitorSettings::block_source`].
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum BlockSource {
+    // `tick` drives the indexer and processes the blocks it finds
+    #[default]
+    Indexer,
+    // The caller applies the blocks of its own pipeline with `apply_block` and `apply_reorg`
+    External,
+}
+
+/// Block applied with [`crate::monitor::Monitor::apply_block`], kept for
+/// `max_monitoring_confirmations` blocks to tell whether a transaction is still in the chain.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ExternalBlockEntry {
+    pub height: BlockHeight,
+    pub hash: BlockHash,
+    pub prev_hash: BlockHash,
+    pub estimated_fee_rate: u64,
+}
+
+impl ExternalBlockEntry {
+    /// The block with no transactions, as the indexer returns the block of a transaction.
+    pub fn to_block(&self) -> FullBlock {
+        FullBlock {
+            height: self.height,
+            hash: self.hash,
+            prev_hash: self.prev_hash,
+            txs: vec![],
+            orphan: false,
+            estimated_fee_rate: self.estimated_fee_rate,
+        }
+    }
+}
+
+/// Outcome of a block applied with [`crate::monitor::Monitor::apply_block`] or
+/// [`crate::monitor::Monitor::apply_reorg`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TickReport {
+    /// The block applied, the new tip of the monitor
+    pub height: BlockHeight,
+    pub block_hash: BlockHash,
+    /// News written while the block was applied
+    pub news_emitted: u64,
+    /// Impact of the blocks the block replaced, None when it extended the chain
+    pub reorg: Option<ReorgImpact>,
+}
+
+/// How far a tick run with [`crate::monitor::Monitor::tick_with_shutdown`] went.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct TickProgress {
+    /// Blocks the monitor moved to during the tick
+    pub blocks_processed: u32,
+    /// Height of the monitor once the tick returned
+    pub monitor_height: BlockHeight,
+    /// Whether a shutdown was requested before the tick went through all its blocks, the
+    /// mempool and the maintenance of the store. The blocks processed are complete, the others
+    /// are processed by the next tick
+    pub stopped_early: bool,
+}
+
+/// What [`crate::store::MonitorStoreApi::prune`] removed from the store.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PruneReport {
+    /// Entries of inactive transaction monitors
+    pub transactions: u32,
+    /// Entries of inactive SpendingUTXO monitors
+    pub spending_utxos: u32,
+    /// Acknowledged news
+    pub news: u32,
+    /// Snapshots of transactions no transaction monitor follows anymore
+    pub tx_snapshots: u32,
+}
+
+/// Block processed by the monitor, in the chain log kept for external audit.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ChainLogEntry {
+    pub height: BlockHeight,
+    pub hash: BlockHash,
+    pub prev_hash: BlockHash,
+    /// Unix time, in seconds, the block was processed at
+    pub processed_at_unix: u64,
+}
+
+/// A break in the hash linkage of the chain log, see [`ChainLogVerification`].
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum ChainLogIssue {
+    /// The block doesn't link to any logged block, the blocks before it were never processed
+    /// (e.g. the monitor jumped to the tip of a new branch).
+    Gap {
+        height: BlockHeight,
+        hash: BlockHash,
+        prev_hash: BlockHash,
+    },
+    /// The block links to a logged block other than the one processed before it: the blocks
+    /// after `fork_height` were replaced by a reorg, up to `replaced_tip`.
+    Fork {
+        fork_height: BlockHeight,
+        fork_hash: BlockHash,
+        replaced_tip: BlockHash,
+        hash: BlockHash,
+    },
+    /// The block links to a logged block that is not at the height below it.
+    HeightMismatch {
+        height: BlockHeight,
+        hash: BlockHash,
+        parent_height: BlockHeight,
+    },
+}
+
+/// A news as it was written, kept in the news history after it is acknowledged so missed
+/// transitions can be looked into later. Each news written takes its own entry: a news written
+/// again for another block is a new entry.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct NewsHistoryEntry {
+    /// Position of the news in the news sequence
+    pub sequence: u64,
+    pub news: MonitoredTypes,
+    /// Block the news was written for, at `height`
+    pub block_hash: BlockHash,
+    pub height: BlockHeight,
+    /// Unix time, in seconds, the news was written at
+    pub created_at_unix: u64,
+    /// None until the news is acknowledged
+    pub acked: Option<NewsHistoryAck>,
+}
+
+/// When a news of the history was acknowledged.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct NewsHistoryAck {
+    /// Monitor height at the acknowledgment
+    pub height: BlockHeight,
+    /// Unix time, in seconds, of the acknowledgment
+    pub acked_at_unix: u64,
+}
+
+/// Outcome of checking the hash linkage of the chain log, each entry against the entries
+/// processed before it. The first entry kept is not checked.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ChainLogVerification {
+    pub entries: usize,
+    /// In processing order
+    pub issues: Vec<ChainLogIssue>,
+}
+
+impl ChainLogVerification {
+    /// True if every block links to a logged block at the height below it. Forks are reorgs the
+    /// monitor followed, they don't break the continuity.
+    pub fn is_continuous(&self) -> bool {
+        self.issues
+            .iter()
+            .all(|issue| matches!(issue, ChainLogIssue::Fork { .. }))
+    }
+
+    pub fn forks(&self) -> impl Iterator<Item = &ChainLogIssue> {
+        self.issues
+            .iter()
+            .filter(|issue| matches!(issue, ChainLogIssue::Fork { .. }))
+    }
+}
+
+/// Progress of the monitors registered with a context, e.g. a protocol instance, read from the
+/// monitors and their history in the store. Each monitor is counted once in `registered`, and in
+/// at most one of `detected`, `orphaned` and `expired` by its last event. `finalized` are the
+/// detected monitors whose transaction reached the confirmation threshold.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ContextProgress {
+    pub context: MonitorContext,
+    pub registered: u32,
+    /// Found in the chain, finalized ones included
+    pub detected: u32,
+    pub finalized: u32,
+    /// The block of the transaction found was replaced by a reorg
+    pub orphaned: u32,
+    /// SpendingUTXO monitors whose deadline passed without a spend
+    pub expired: u32,
+    /// `finalized` out of `registered`, from 0 to 1, 0 when nothing is registered
+    pub ratio: f64,
+}
+
+/// Block processed by the monitor, kept for `max_monitoring_confirmations` blocks to compare the
+/// work of the branches of a reorg. `bits` is None when the header couldn't be read.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BlockWorkEntry {
+    pub height: BlockHeight,
+    pub hash: BlockHash,
+    pub bits: Option<CompactTarget>,
+}
+
+impl BlockWorkEntry {
+    pub fn work(&self) -> Option<Work> {
+        self.bits.map(|bits| Target::from_compact(bits).to_work())
+    }
+
+    /// Sum of the work of the blocks, None if the bits of any of them are missing.
+    pub fn total_work(blocks: &[BlockWorkEntry]) -> Option<Work> {
+        blocks
+            .iter()
+            .try_fold(Work::from_be_bytes([0; 32]), |total, block| {
+                Some(total + block.work()?)
+            })
+    }
+}
+
+/// What a reorg changed for the monitored items, built when the reorg is processed.
+///
+/// Block hashes are the blocks the transactions were found in, `None` when the transaction
+/// is no longer in the chain.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReorgImpact {
+    pub old_tip_height: BlockHeight,
+    pub old_tip: BlockHash,
+    pub new_tip_height: BlockHeight,
+    pub new_tip: BlockHash,
+    /// Monitored transactions found in another block, or no longer in the chain
+    pub moved_transactions: Vec<MovedTransaction>,
+    /// Spends of monitored UTXOs no longer in the chain
+    pub disappeared_spends: Vec<SpendChange>,
+    /// Monitored UTXOs now spent by another transaction
+    pub changed_spends: Vec<SpendChange>,
+    /// Pegin transactions no longer in the chain
+    pub invalidated_pegins: Vec<MovedTransaction>,
+    /// Acknowledged news sent again, with the tips they were sent at
+    pub reopened_news: Vec<ReopenedNews>,
+}
+
+impl ReorgImpact {
+    pub fn summary(&self) -> ReorgSummary {
+        ReorgSummary {
+            moved_transactions: self.moved_transactions.len(),
+            disappeared_spends: self.disappeared_spends.len(),
+            changed_spends: self.changed_spends.len(),
+            invalidated_pegins: self.invalidated_pegins.len(),
+            reopened_news: self.reopened_news.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.summary() == ReorgSummary::default()
+    }
+}
+
+/// Number of items of each kind in a [`ReorgImpact`]
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReorgSummary {
+    pub moved_transactions: usize,
+    pub disappeared_spends: usize,
+    pub changed_spends: usize,
+    pub invalidated_pegins: usize,
+    pub reopened_news: usize,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MovedTransaction {
+    pub tx_id: Txid,
+    pub before: BlockHash,
+    pub after: Option<BlockHash>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct SpendChange {
+    pub tx_id: Txid,
+    pub utxo_index: u32,
+    pub extra_data: MonitorContext,
+    pub spender_before: Txid,
+    pub before: BlockHash,
+    pub spender_after: Option<Txid>,
+    pub after: Option<BlockHash>,
+}
+
+/// Acknowledged news sent again after a reorg, `before` and `after` are the tips it was sent at
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ReopenedNews {
+    pub news: MonitoredTypes,
+    pub before: BlockHash,
+    pub after: BlockHash,
+}
+
+/// Tombstone of a transaction monitor moved to `new_tx_id`, kept until `expires_at` to warn
+/// if the replaced transaction confirms anyway
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ReplacedTxEntry {
+    pub old_tx_id: Txid,
+    pub new_tx_id: Txid,
+    pub contexts: Vec<MonitorContext>,
+    pub expires_at: BlockHeight,
+}
+
+/// Race between mutually exclusive transactions, each one monitored as a transaction with
+/// `extra_data`. `winner` is the first to reach the confirmation threshold, None while the race
+/// is open. The race is removed once the winner reaches `max_monitoring_confirmations`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AlternativesMonitor {
+    pub tx_ids: Vec<Txid>,
+    pub extra_data: MonitorContext,
+    pub winner: Option<Txid>,
+}
+
+impl AlternativesMonitor {
+    /// Whether this is the race of `tx_ids` with `extra_data`, whatever the order of `tx_ids`.
+    pub fn matches(&self, tx_ids: &[Txid], extra_data: &str) -> bool {
+        self.extra_data == extra_data
+            && self.tx_ids.len() == tx_ids.len()
+            && tx_ids.iter().all(|tx_id| self.tx_ids.contains(tx_id))
+    }
+
+    /// Alternatives other than `winner`, in registration order.
+    pub fn losers(&self, winner: Txid) -> Vec<Txid> {
+        self.tx_ids
+            .iter()
+            .copied()
+            .filter(|tx_id| *tx_id != winner)
+            .collect()
+    }
+}
+
+/// Condition of a [`CompositeRule`] on the detections of the monitor. A transaction satisfies it
+/// once mined in the chain with at least `min_confirmations`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum NewsPredicate {
+    /// The transaction is mined
+    Transaction { tx_id: Txid, min_confirmations: u32 },
+    /// The transaction is mined and is an RSK pegin
+    RskPegin { tx_id: Txid, min_confirmations: u32 },
+    /// The spender recorded by the active SpendingUTXO monitor of the output is mined
+    SpendingUTXO {
+        tx_id: Txid,
+        vout: u32,
+        min_confirmations: u32,
+    },
+}
+
+/// Rule registered with `Monitor::register_composite`, evaluated on each block processed.
+/// `satisfied` has the state of each predicate of `all_of` at the last block: a reorg undoing a
+/// predicate resets it until the news is sent, once sent the rule is no longer evaluated.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct CompositeRule {
+    pub id: String,
+    pub all_of: Vec<NewsPredicate>,
+    pub satisfied: Vec<bool>,
+    /// Height the `Composite` news was sent at, None until then
+    pub emitted_at: Option<BlockHeight>,
+}
+
+/// Transaction monitor entry (extra_data, confirmation_trigger, trigger_sent, last reported state)
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct TransactionMonitorEntry {
-    pub extra_data: String,
+    pub extra_data: MonitorContext,
     pub confirmation_trigger: Option<u32>,
     pub trigger_sent: bool,
+    /// Confirmations of the transaction in the last news sent, None before the first one
+    #[serde(default)]
+    pub last_reported_confirmations: Option<u32>,
+    /// Block of the transaction in the last news sent, None if it was orphaned
+    #[serde(default)]
+    pub last_reported_block_hash: Option<BlockHash>,
+    /// Height the monitor expires at if the transaction wasn't mined by then
+    #[serde(default)]
+    pub expires_at_height: Option<BlockHeight>,
+    #[serde(default)]
+    pub history: Vec<MonitorEvent>,
 }
 
 /// Transaction monitor stored in active/inactive lists
@@ -234,12 +1656,225 @@ pub struct TransactionMonitor {
     pub entries: Vec<TransactionMonitorEntry>,
 }
 
-/// SpendingUTXO monitor entry (extra_data, spender_tx_id, confirmation_trigger)
+/// SpendingUTXO monitor entry (extra_data, spender_tx_id, confirmation_trigger, deadline, value_gate)
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct SpendingUTXOMonitorEntry {
-    pub extra_data: String,
+    pub extra_data: MonitorContext,
     pub spender_tx_id: Option<Txid>,
     pub confirmation_trigger: Option<u32>,
+    #[serde(default)]
+    pub deadline: Option<SpendDeadline>,
+    #[serde(default)]
+    pub value_gate: Option<SpendValueGate>,
+    /// Height the monitor expires at if the UTXO wasn't spent by then
+    #[serde(default)]
+    pub expires_at_height: Option<BlockHeight>,
+    #[serde(default)]
+    pub history: Vec<MonitorEvent>,
+    /// Whether the blocks before the registration were scanned for a spend. Monitors stored
+    /// before the backfill existed count as scanned.
+    #[serde(default = "backfill_done_default")]
+    pub backfill_done: bool,
+}
+
+fn backfill_done_default() -> bool {
+    true
+}
+
+/// ScriptPubKey monitor entry (extra_data, confirmation_trigger, filter)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ScriptPubKeyMonitorEntry {
+    pub extra_data: MonitorContext,
+    pub confirmation_trigger: Option<u32>,
+    #[serde(default)]
+    pub filter: Option<OutputFilter>,
+    #[serde(default)]
+    pub history: Vec<MonitorEvent>,
+}
+
+/// ScriptPubKey monitor stored in active/inactive lists
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ScriptPubKeyMonitor {
+    pub script_pubkey: ScriptBuf,
+    pub entries: Vec<ScriptPubKeyMonitorEntry>,
+}
+
+/// OP_RETURN prefix monitor (prefix, extra_data)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct OpReturnPrefixMonitor {
+    pub prefix: Vec<u8>,
+    pub extra_data: MonitorContext,
+    #[serde(default)]
+    pub history: Vec<MonitorEvent>,
+}
+
+/// Event kept in the history of a monitor, the last `MONITOR_HISTORY_LEN` of them, see
+/// [`crate::monitor::Monitor::monitor_history`]. The events of the transactions a SpendingUTXO or
+/// ScriptPubKey monitor found are kept in the history of that monitor.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum MonitorEvent {
+    /// Registered (or registered again) at the monitor height `height`
+    Registered { height: BlockHeight },
+    /// `tx_id` was found in the block `block_hash` at `height`
+    Detected {
+        tx_id: Txid,
+        height: BlockHeight,
+        block_hash: BlockHash,
+    },
+    /// `tx_id` reached `confirmations`, the confirmation trigger of the monitor or the
+    /// confirmation threshold
+    Confirmed { tx_id: Txid, confirmations: u32 },
+    /// The block `tx_id` was found in was replaced by a reorg
+    Orphaned {
+        tx_id: Txid,
+        height: BlockHeight,
+        block_hash: BlockHash,
+    },
+    /// Stopped after `max_monitoring_confirmations`, at the monitor height `height`
+    Deactivated {
+        height: BlockHeight,
+        #[serde(default)]
+        reason: DeactivationReason,
+    },
+}
+
+/// What let a monitor past `max_monitoring_confirmations` be deactivated.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DeactivationReason {
+    /// No news of the monitor was left unacknowledged
+    NewsAcked,
+    /// `deactivation_requires_ack_grace_blocks` passed with news still unacknowledged
+    AckGraceExpired,
+    /// Deactivated by an older version, which didn't wait for the news to be acknowledged
+    #[default]
+    Unchecked,
+}
+
+impl MonitorEvent {
+    /// Highest height of the events of `history`, 0 for a history stored by older versions.
+    pub fn last_activity(history: &[MonitorEvent]) -> BlockHeight {
+        history
+            .iter()
+            .filter_map(|event| match event {
+                Self::Registered { height }
+                | Self::Detected { height, .. }
+                | Self::Orphaned { height, .. }
+                | Self::Deactivated { height, .. } => Some(*height),
+                Self::Confirmed { .. } => None,
+            })
+            .max()
+            .unwrap_or_default()
+    }
+
+    fn tx_id(&self) -> Option<Txid> {
+        match self {
+            Self::Detected { tx_id, .. }
+            | Self::Confirmed { tx_id, .. }
+            | Self::Orphaned { tx_id, .. } => Some(*tx_id),
+            Self::Registered { .. } | Self::Deactivated { .. } => None,
+        }
+    }
+
+    /// Appends `event` to `history`, dropping the oldest events past `MONITOR_HISTORY_LEN`.
+    /// Returns whether the history changed.
+    ///
+    /// Ticks report the state of a transaction again and again, so an event of a transaction
+    /// already recorded since the monitor was registered or the transaction was last orphaned
+    /// is ignored. Detecting a transaction in another block than the one it was last detected
+    /// in records that block as orphaned first.
+    pub fn record(history: &mut Vec<MonitorEvent>, event: MonitorEvent) -> bool {
+        let mut events = vec![event];
+
+        if let Some(tx_id) = events[0].tx_id() {
+            let since_orphaned: Vec<&MonitorEvent> = history
+                .iter()
+                .rev()
+                .take_while(|e| {
+                    !matches!(e, Self::Registered { .. })
+                        && !matches!(e, Self::Orphaned { tx_id: t, .. } if *t == tx_id && **e != events[0])
+                })
+                .collect();
+
+            if since_orphaned.contains(&&events[0]) {
+                return false;
+            }
+
+            if let Self::Detected { block_hash, .. } = events[0] {
+                let last_detected = since_orphaned.iter().find_map(|e| match e {
+                    Self::Detected {
+                        tx_id: t,
+                        height,
+                        block_hash: previous,
+                    } if *t == tx_id && *previous != block_hash => Some(Self::Orphaned {
+                        tx_id,
+                        height: *height,
+                        block_hash: *previous,
+                    }),
+                    _ => None,
+                });
+                if let Some(orphaned) = last_detected {
+                    events.insert(0, orphaned);
+                }
+            }
+        }
+
+        history.extend(events);
+        if history.len() > MONITOR_HISTORY_LEN {
+            history.drain(..history.len() - MONITOR_HISTORY_LEN);
+        }
+
+        true
+    }
+}
+
+/// Deadline of a SpendingUTXO monitor. During the last `mempool_watch_window` blocks before
+/// `height` the monitor also looks for spends of the UTXO in the mempool.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpendDeadline {
+    pub height: BlockHeight,
+    pub mempool_watch_window: u32,
+}
+
+impl SpendDeadline {
+    pub fn new(height: BlockHeight, mempool_watch_window: u32) -> Self {
+        Self {
+            height,
+            mempool_watch_window,
+        }
+    }
+
+    /// True from `height - mempool_watch_window` up to the deadline height, both included.
+    pub fn is_in_mempool_window(&self, current_height: BlockHeight) -> bool {
+        current_height >= self.height.saturating_sub(self.mempool_watch_window)
+            && current_height <= self.height
+    }
+}
+
+/// Threshold of a SpendingUTXO monitor below which a spend is minor, e.g. a consolidation. A
+/// minor spend is recorded in the monitor like any other, but it is reported as
+/// `MonitorNews::MinorSpend`, or not at all, instead of `MonitorNews::SpendingUTXOTransaction`.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SpendValueGate {
+    pub min_spend_value: Amount,
+    /// Measures the value of the monitored output instead of the total output value of the
+    /// spending transaction
+    pub measure_monitored_output: bool,
+    /// Sends `MonitorNews::MinorSpend` for the minor spends, otherwise they get no news
+    pub report_minor: bool,
+}
+
+impl SpendValueGate {
+    pub fn new(min_spend_value: Amount) -> Self {
+        Self {
+            min_spend_value,
+            measure_monitored_output: false,
+            report_minor: true,
+        }
+    }
+
+    pub fn is_minor(&self, value: Amount) -> bool {
+        value < self.min_spend_value
+    }
 }
 
 /// SpendingUTXO monitor stored in active/inactive lists
@@ -250,9 +1885,535 @@ pub struct SpendingUTXOMonitor {
     pub entries: Vec<SpendingUTXOMonitorEntry>,
 }
 
-/// RskPegin monitor state (active, confirmation_trigger)
+/// Status of the RskPegin monitor. A deactivated monitor keeps its trigger and filters for a
+/// reactivation, a cancelled one keeps nothing and can only be registered again.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RskPeginMonitorStatus {
+    Active,
+    Inactive,
+    Cancelled,
+}
+
+/// RskPegin monitor state (status, confirmation_trigger, committee)
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
 pub struct RskPeginMonitorState {
-    pub active: bool,
+    #[serde(alias = "active", deserialize_with = "deserialize_pegin_status")]
+    pub status: RskPeginMonitorStatus,
     pub confirmation_trigger: Option<u32>,
+    /// Script_pubkeys of the committee addresses, any address when empty
+    #[serde(default)]
+    pub committee: Vec<ScriptBuf>,
+    /// First and last packet numbers reported (both included), any packet number when None
+    #[serde(default)]
+    pub packet_range: Option<(u64, u64)>,
+    /// Minimum amount paid to the committee, any amount when None
+    #[serde(default)]
+    pub min_amount: Option<Amount>,
+}
+
+impl RskPeginMonitorState {
+    pub fn is_active(&self) -> bool {
+        self.status == RskPeginMonitorStatus::Active
+    }
+}
+
+/// Reads the status of the pegin monitor. Older versions stored an `active` flag, and an inactive
+/// one could be reactivated, so it reads as deactivated.
+fn deserialize_pegin_status<'de, D>(deserializer: D) -> Result<RskPeginMonitorStatus, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum PeginStatus {
+        Status(RskPeginMonitorStatus),
+        LegacyActive(bool),
+    }
+
+    Ok(match PeginStatus::deserialize(deserializer)? {
+        PeginStatus::Status(status) => status,
+        PeginStatus::LegacyActive(true) => RskPeginMonitorStatus::Active,
+        PeginStatus::LegacyActive(false) => RskPeginMonitorStatus::Inactive,
+    })
+}
+
+/// Payload of the OP_RETURN output of a pegin, with the amount paid to the committee.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RskPeginData {
+    pub packet_number: u64,
+    pub rsk_address: [u8; 20],
+    pub reimbursement_key: XOnlyPublicKey,
+    /// Value of the first output, paying to the committee
+    pub amount: Amount,
+}
+
+/// Monitor targeted by a snooze
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub enum SnoozeTarget {
+    // - Txid: The transaction ID
+    // - MonitorContext: The context of the transaction
+    Transaction(Txid, MonitorContext),
+
+    // - Txid: The transaction ID
+    // - u32: The vout index of the UTXO
+    // - MonitorContext: The context of the transaction
+    SpendingUTXOTransaction(Txid, u32, MonitorContext),
+
+    RskPegin,
+
+    NewBlock,
+
+    // - ScriptBuf: The monitored script_pubkey
+    // - MonitorContext: The context of the script
+    ScriptPubKey(ScriptBuf, MonitorContext),
+
+    // - Vec<u8>: The monitored OP_RETURN prefix
+    // - MonitorContext: The context of the prefix
+    OpReturnPrefix(Vec<u8>, MonitorContext),
+}
+
+impl SnoozeTarget {
+    /// Targets of the monitors registered by `data`, one per transaction for `Transactions`.
+    pub fn of_monitor(data: TypesToMonitor) -> Vec<Self> {
+        match data {
+            TypesToMonitor::Transactions(tx_ids, extra_data, _, _) => tx_ids
+                .into_iter()
+                .map(|tx_id| Self::Transaction(tx_id, extra_data.clone()))
+                .collect(),
+            TypesToMonitor::SpendingUTXOTransaction(tx_id, vout, extra_data, ..) => {
+                vec![Self::SpendingUTXOTransaction(tx_id, vout, extra_data)]
+            }
+            TypesToMonitor::RskPegin(..) => vec![Self::RskPegin],
+            TypesToMonitor::NewBlock => vec![Self::NewBlock],
+            TypesToMonitor::ScriptPubKey(script_pubkey, extra_data, ..) => {
+                vec![Self::ScriptPubKey(script_pubkey, extra_data)]
+            }
+            TypesToMonitor::OpReturnPrefix(prefix, extra_data) => {
+                vec![Self::OpReturnPrefix(prefix, extra_data)]
+            }
+            TypesToMonitor::Alternatives(tx_ids, extra_data) => tx_ids
+                .into_iter()
+                .map(|tx_id| Self::Transaction(tx_id, extra_data.clone()))
+                .collect(),
+        }
+    }
+
+    /// Path identifying the target in store ids, e.g. `tx/<txid>/<context>`.
+    pub(crate) fn path(&self) -> String {
+        match self {
+            Self::Transaction(tx_id, extra_data) => format!("tx/{tx_id}/{extra_data}"),
+            Self::SpendingUTXOTransaction(tx_id, vout, extra_data) => {
+                format!("spending/{tx_id}:{vout}/{extra_data}")
+            }
+            Self::RskPegin => "rsk_pegin".to_string(),
+            Self::NewBlock => "new_block".to_string(),
+            Self::ScriptPubKey(script_pubkey, extra_data) => {
+                format!("script/{}/{extra_data}", script_pubkey.to_hex_string())
+            }
+            Self::OpReturnPrefix(prefix, extra_data) => {
+                format!("op_return/{}/{extra_data}", hex::encode(prefix))
+            }
+        }
+    }
+}
+
+/// Options of a registration made with [`Monitor::save_monitor_with`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RegistrationOptions {
+    /// The news of the monitor are expected, they don't fail the ticks in strict mode, see
+    /// [`crate::config::MonitorSettings::strict_detections`].
+    pub expected: bool,
+    /// Metadata of the caller kept with the monitor and returned with its news, replacing the one
+    /// of an earlier registration. None keeps it. See [`crate::settings::MAX_METADATA_ENTRIES`].
+    pub metadata: Option<BTreeMap<String, String>>,
+}
+
+/// Detection that failed a tick in strict mode, see [`crate::errors::MonitorError::UnexpectedDetection`]
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct DetectionDescriptor {
+    /// Monitor the news was produced for, or the pegin and OP_RETURN rule that matched
+    pub monitor: SnoozeTarget,
+    /// Transaction detected, None for the new block news
+    pub tx_id: Option<Txid>,
+}
+
+// Contexts may hold identifiers that must not end up in logs, see `crate::redaction`
+impl fmt::Display for DetectionDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.monitor {
+            SnoozeTarget::Transaction(tx_id, context) => {
+                write!(f, "Transaction({}, {:?})", tx_id, RedactedContext(context))?
+            }
+            SnoozeTarget::SpendingUTXOTransaction(tx_id, vout, context) => write!(
+                f,
+                "SpendingUTXOTransaction({}:{}, {:?})",
+                tx_id,
+                vout,
+                RedactedContext(context)
+            )?,
+            SnoozeTarget::RskPegin => write!(f, "RskPegin")?,
+            SnoozeTarget::NewBlock => write!(f, "NewBlock")?,
+            SnoozeTarget::ScriptPubKey(script_pubkey, context) => write!(
+                f,
+                "ScriptPubKey({}, {:?})",
+                script_pubkey.to_hex_string(),
+                RedactedContext(context)
+            )?,
+            SnoozeTarget::OpReturnPrefix(prefix, context) => write!(
+                f,
+                "OpReturnPrefix({}, {:?})",
+                hex::encode(prefix),
+                RedactedContext(context)
+            )?,
+        }
+
+        match self.tx_id {
+            Some(tx_id) => write!(f, " detected {}", tx_id),
+            None => Ok(()),
+        }
+    }
+}
+
+impl fmt::Debug for DetectionDescriptor {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("DetectionDescriptor")
+            .field(&format_args!("{}", self))
+            .finish()
+    }
+}
+
+/// Metadata registered for a monitor, see [`RegistrationOptions::metadata`]
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MonitorMetadataEntry {
+    pub target: SnoozeTarget,
+    pub metadata: BTreeMap<String, String>,
+}
+
+/// Snoozed monitor, its news are held in `pending` until the monitor height passes `until`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct SnoozeEntry {
+    pub target: SnoozeTarget,
+    pub until: BlockHeight,
+    #[serde(deserialize_with = "deserialize_held_news")]
+    pub pending: Vec<MonitoredTypes>,
+}
+
+/// Reads the news held back by a snooze. The new block news held by older versions have no height.
+fn deserialize_held_news<'de, D>(deserializer: D) -> Result<Vec<MonitoredTypes>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum HeldNews {
+        News(MonitoredTypes),
+        LegacyNewBlock {
+            #[serde(rename = "NewBlock")]
+            hash: BlockHash,
+        },
+    }
+
+    let held: Vec<HeldNews> = Vec::deserialize(deserializer)?;
+
+    Ok(held
+        .into_iter()
+        .map(|news| match news {
+            HeldNews::News(news) => news,
+            HeldNews::LegacyNewBlock { hash } => MonitoredTypes::NewBlock(hash, None),
+        })
+        .collect())
+}
+
+/// Key removed by the store sweeper once the monitor reaches `expires_at`
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct ExpiryEntry {
+    pub key: String,
+    pub expires_at: BlockHeight,
+}
+
+/// Entries removed from the monitor lists by a cancel, with their state (triggers sent, spender
+/// found, last reported confirmations...). `is_active` tells the list each one was removed from.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct TrashedMonitors {
+    pub transactions: Vec<(bool, TransactionMonitor)>,
+    pub spending_utxos: Vec<(bool, SpendingUTXOMonitor)>,
+    pub script_pubkeys: Vec<(bool, ScriptPubKeyMonitor)>,
+    pub op_return_prefixes: Vec<OpReturnPrefixMonitor>,
+    pub rsk_pegin: Option<RskPeginMonitorState>,
+    pub new_block: bool,
+    #[serde(default)]
+    pub alternatives: Vec<AlternativesMonitor>,
+}
+
+impl TrashedMonitors {
+    pub fn is_empty(&self) -> bool {
+        self.transactions.is_empty()
+            && self.spending_utxos.is_empty()
+            && self.script_pubkeys.is_empty()
+            && self.op_return_prefixes.is_empty()
+            && self.rsk_pegin.is_none()
+            && !self.new_block
+            && self.alternatives.is_empty()
+    }
+
+    pub fn extend(&mut self, other: TrashedMonitors) {
+        self.transactions.extend(other.transactions);
+        self.spending_utxos.extend(other.spending_utxos);
+        self.script_pubkeys.extend(other.script_pubkeys);
+        self.op_return_prefixes.extend(other.op_return_prefixes);
+        self.rsk_pegin = other.rsk_pegin.or(self.rsk_pegin.take());
+        self.new_block |= other.new_block;
+        self.alternatives.extend(other.alternatives);
+    }
+
+    /// Contexts of the trashed entries, the pegin and new block monitors have none.
+    pub fn contexts(&self) -> impl Iterator<Item = &str> {
+        let transactions = self
+            .transactions
+            .iter()
+            .flat_map(|(_, m)| m.entries.iter().map(|e| e.extra_data.as_str()));
+        let spending_utxos = self
+            .spending_utxos
+            .iter()
+            .flat_map(|(_, m)| m.entries.iter().map(|e| e.extra_data.as_str()));
+        let script_pubkeys = self
+            .script_pubkeys
+            .iter()
+            .flat_map(|(_, m)| m.entries.iter().map(|e| e.extra_data.as_str()));
+        let op_return_prefixes = self
+            .op_return_prefixes
+            .iter()
+            .map(|m| m.extra_data.as_str());
+        let alternatives = self.alternatives.iter().map(|m| m.extra_data.as_str());
+
+        transactions
+            .chain(spending_utxos)
+            .chain(script_pubkeys)
+            .chain(op_return_prefixes)
+            .chain(alternatives)
+    }
+}
+
+/// Version of the [`MonitorExport`] documents written by this version.
+pub const MONITOR_EXPORT_VERSION: u32 = 1;
+
+/// Every monitor configured in a store, active and inactive, with its state (context, history
+/// since the registration, spender found...), as written by `MonitorStoreApi::export_monitors`
+/// and loaded by `import_monitors`. `is_active` tells the list each listed monitor is in.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct MonitorExport {
+    pub version: u32,
+    pub transactions: Vec<(bool, TransactionMonitor)>,
+    pub spending_utxos: Vec<(bool, SpendingUTXOMonitor)>,
+    pub script_pubkeys: Vec<(bool, ScriptPubKeyMonitor)>,
+    pub op_return_prefixes: Vec<OpReturnPrefixMonitor>,
+    pub rsk_pegin: Option<RskPeginMonitorState>,
+    /// Whether the new block monitor is active, None if it was never added
+    pub new_block: Option<bool>,
+    pub alternatives: Vec<AlternativesMonitor>,
+    pub composites: Vec<CompositeRule>,
+    pub metadata: Vec<MonitorMetadataEntry>,
+}
+
+/// Why the monitors of a trash entry were canceled.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CancelReason {
+    /// Canceled through the API
+    #[default]
+    Requested,
+    /// Another transaction of its `Alternatives` monitor won the race
+    AlternativeLost,
+    /// Resolved by an operator with `resolve_manually`
+    ManuallyResolved,
+}
+
+/// Monitor canceled at the monitor height `deleted_at`, kept until restored or purged by the
+/// sweeper `retention.trash_blocks` later.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct TrashEntry {
+    pub id: u64,
+    pub deleted_at: BlockHeight,
+    pub monitors: TrashedMonitors,
+    #[serde(default)]
+    pub reason: CancelReason,
+}
+
+impl TrashEntry {
+    pub fn matches(&self, selector: &TrashSelector) -> bool {
+        match selector {
+            TrashSelector::Id(id) => self.id == *id,
+            TrashSelector::Context(context) => self.monitors.contexts().any(|c| context == c),
+            TrashSelector::All => true,
+        }
+    }
+}
+
+/// Trash entries restored by `restore_from_trash`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrashSelector {
+    // - u64: The id of the trash entry
+    Id(u64),
+    // - MonitorContext: The context of the canceled monitors, e.g. to undo a cancel by context
+    Context(MonitorContext),
+    All,
+}
+
+/// Monitor listed by `get_monitors`, `spender_tx_id` is the spender found by a
+/// SpendingUTXOTransaction monitor and `history` its last events, oldest first.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct MonitorInfo {
+    pub monitor: TypesToMonitorStore,
+    pub active: bool,
+    pub spender_tx_id: Option<Txid>,
+    #[serde(default)]
+    pub history: Vec<MonitorEvent>,
+    /// Metadata given when the monitor was registered, empty if none
+    #[serde(default)]
+    pub metadata: BTreeMap<String, String>,
+}
+
+/// Heights a monitored transaction was seen unconfirmed in the mempool. `last_known_fee_rate`
+/// is the fee rate estimated by the indexer at the block of the last sighting.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct MempoolSighting {
+    pub tx_id: Txid,
+    pub first_seen_height: BlockHeight,
+    pub last_seen_height: BlockHeight,
+    pub last_known_fee_rate: u64,
+}
+
+/// What `cancel_with_policy` does with the unacked news of the canceled monitor.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CancelNewsPolicy {
+    /// The news stay until acknowledged, as with `cancel`
+    #[default]
+    KeepNews,
+    /// The news are removed
+    DropNews,
+    /// The news are marked as acknowledged
+    AckNews,
+}
+
+/// Detection kept in the archive exported by `export_detections_csv`. A detection is identified
+/// by its kind, txid, outpoint and context, and keeps the block it was last reported in.
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct DetectionRecord {
+    /// Kind of the news, as logged by [`crate::news_event::NewsEvent`]
+    pub kind: String,
+    pub tx_id: Txid,
+    /// Monitored output, spent by `tx_id` or paid by it for a ScriptPubKey monitor
+    pub outpoint: Option<OutPoint>,
+    /// Value of the output paid, only known for ScriptPubKey monitors
+    pub amount: Option<Amount>,
+    pub context: MonitorContext,
+    pub height: BlockHeight,
+    pub block_hash: BlockHash,
+}
+
+impl DetectionRecord {
+    pub fn is_same_detection(&self, other: &DetectionRecord) -> bool {
+        self.kind == other.kind
+            && self.tx_id == other.tx_id
+            && self.outpoint == other.outpoint
+            && self.context == other.context
+    }
+}
+
+impl fmt::Debug for DetectionRecord {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("DetectionRecord")
+            .field("kind", &self.kind)
+            .field("tx_id", &self.tx_id)
+            .field("outpoint", &self.outpoint)
+            .field("amount", &self.amount)
+            .field("context", &RedactedContext(&self.context))
+            .field("height", &self.height)
+            .field("block_hash", &self.block_hash)
+            .finish()
+    }
+}
+
+/// Outputs of a ScriptPubKey monitor that are counted in its `AddressFilterStats` instead of
+/// being reported, e.g. dust sent to a deposit address.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OutputFilter {
+    /// Outputs worth less than this are filtered
+    pub min_value: Option<Amount>,
+    /// Outputs below the dust limit of the default relay policy for their script are filtered
+    pub ignore_dust_below_relay: bool,
+}
+
+impl OutputFilter {
+    pub fn filters(&self, output: &TxOut) -> bool {
+        self.min_value
+            .is_some_and(|min_value| output.value < min_value)
+            || (self.ignore_dust_below_relay
+                && output.value < output.script_pubkey.minimal_non_dust())
+    }
+}
+
+/// Values written by the store to the compressible keys (the transaction snapshots) since it was
+/// opened. Values are only compressed with the `compression` feature.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct StorageStats {
+    pub compressed_values: u64,
+    /// Values under the compression threshold, or written without the `compression` feature
+    pub uncompressed_values: u64,
+    /// Size of the JSON of the compressed values
+    pub raw_bytes: u64,
+    /// Size the compressed values were written with
+    pub stored_bytes: u64,
+}
+
+impl StorageStats {
+    /// Size of the JSON of the compressed values over the size they were written with, 1 when
+    /// nothing was compressed.
+    pub fn compression_ratio(&self) -> f64 {
+        if self.stored_bytes == 0 {
+            return 1.0;
+        }
+        self.raw_bytes as f64 / self.stored_bytes as f64
+    }
+}
+
+/// Outputs filtered by the `OutputFilter` of a ScriptPubKey monitor, per context
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct AddressFilterStats {
+    pub script_pubkey: ScriptBuf,
+    pub context: MonitorContext,
+    pub filtered_outputs: u64,
+    pub filtered_value: Amount,
+}
+
+/// Monitored transaction waiting in the mempool for longer than asked, a fee bump candidate
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct StaleTx {
+    pub txid: Txid,
+    pub context: MonitorContext,
+    pub first_seen_height: BlockHeight,
+    pub blocks_waiting: u32,
+    pub last_known_fee_rate: u64,
+}
+
+impl fmt::Debug for StaleTx {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("StaleTx")
+            .field("txid", &self.txid)
+            .field("context", &RedactedContext(&self.context))
+            .field("first_seen_height", &self.first_seen_height)
+            .field("blocks_waiting", &self.blocks_waiting)
+            .field("last_known_fee_rate", &self.last_known_fee_rate)
+            .finish()
+    }
+}
+
+/// Idempotency key of a news or detection already recorded for a block (id, block_hash)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct FingerprintEntry {
+    pub id: String,
+    pub block_hash: BlockHash,
+    /// Height it was recorded at, fingerprints are pruned by it. Fingerprints of stores before
+    /// schema version 3 have none and are given the monitor height by the migration.
+    #[serde(default)]
+    pub height: BlockHeight,
 }
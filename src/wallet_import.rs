@@ -0,0 +1,219 @@
+use crate::errors::MonitorError;
+use crate::monitor::Monitor;
+use crate::store::{MonitorStoreApi, TypesToMonitorStore};
+use crate::types::{MonitorContext, TypesToMonitor};
+use bitcoin::{OutPoint, ScriptBuf, Txid};
+use bitcoin_indexer::indexer::IndexerApi;
+use bitcoincore_rpc::RpcApi;
+use bitvmx_bitcoin_rpc::bitcoin_client::BitcoinClient;
+use std::collections::HashSet;
+use tracing::info;
+
+/// Wallet transactions read per `listtransactions` call.
+const WALLET_PAGE_SIZE: usize = 1_000;
+/// Monitors registered per `monitor_batch` call, with a progress log after each.
+const IMPORT_BATCH_SIZE: usize = 1_000;
+
+/// Monitors of one kind registered by [`Monitor::import_from_wallet`]
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WalletImportCount {
+    pub registered: usize,
+    /// Targets already watched by a monitor, active or not, left as they are
+    pub skipped: usize,
+}
+
+/// Monitors registered by [`Monitor::import_from_wallet`], per kind
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WalletImportReport {
+    /// Transaction monitors, for the unconfirmed transactions of the wallet
+    pub transactions: WalletImportCount,
+    /// SpendingUTXO monitors, for the unspent outputs of the wallet
+    pub utxos: WalletImportCount,
+    /// ScriptPubKey monitors, for the receiving addresses of the wallet
+    pub scripts: WalletImportCount,
+}
+
+impl<I, B> Monitor<I, B>
+where
+    I: IndexerApi,
+    B: MonitorStoreApi,
+{
+    /// Seeds the monitor with what a Bitcoin Core wallet tracks, e.g. when migrating from a
+    /// watch-only wallet. Every monitor gets `context`:
+    /// - a Transaction monitor for each unconfirmed transaction of the wallet
+    /// - a SpendingUTXO monitor for each unspent output, confirmed or not
+    /// - a ScriptPubKey monitor for each receiving address, also the ones never paid
+    ///
+    /// `client` has to reach the wallet named `wallet`, e.g. built from an `RpcConfig` whose
+    /// `wallet` is it. Targets already watched by a monitor are skipped, so running the import
+    /// again only registers what the wallet got since. Watch-only entries are included.
+    ///
+    /// # Returns
+    /// - `Ok(WalletImportReport)`: The monitors registered and skipped per kind
+    /// - `Err`: If a wallet call failed, the context is invalid or there was an error writing
+    ///   to the store. The batches registered before the error are kept.
+    pub fn import_from_wallet(
+        &self,
+        client: &BitcoinClient,
+        wallet: &str,
+        context: impl Into<MonitorContext>,
+    ) -> Result<WalletImportReport, MonitorError> {
+        let context = context.into();
+        let rpc_error = |e: bitcoincore_rpc::Error| MonitorError::WalletRpcError(e.to_string());
+
+        let mut watched_txs: HashSet<Txid> = HashSet::new();
+        let mut watched_outpoints: HashSet<OutPoint> = HashSet::new();
+        let mut watched_scripts: HashSet<ScriptBuf> = HashSet::new();
+        for info in self.get_monitors(true)? {
+            match info.monitor {
+                TypesToMonitorStore::Transaction(tx_id, ..) => {
+                    watched_txs.insert(tx_id);
+                }
+                TypesToMonitorStore::SpendingUTXOTransaction(tx_id, vout, ..) => {
+                    watched_outpoints.insert(OutPoint::new(tx_id, vout));
+                }
+                TypesToMonitorStore::ScriptPubKey(script_pubkey, ..) => {
+                    watched_scripts.insert(script_pubkey);
+                }
+                _ => {}
+            }
+        }
+
+        // A transaction is listed once per wallet output or input it has, conflicted ones have
+        // negative confirmations and are left out
+        let mut unconfirmed: Vec<Txid> = vec![];
+        let mut listed: HashSet<Txid> = HashSet::new();
+        let mut skip = 0;
+        loop {
+            let page = client
+                .client
+                .list_transactions(None, Some(WALLET_PAGE_SIZE), Some(skip), Some(true))
+                .map_err(rpc_error)?;
+
+            for entry in &page {
+                if entry.info.confirmations == 0 && listed.insert(entry.info.txid) {
+                    unconfirmed.push(entry.info.txid);
+                }
+            }
+
+            if page.len() < WALLET_PAGE_SIZE {
+                break;
+            }
+            skip += page.len();
+        }
+
+        let unspent = client
+            .client
+            .list_unspent(Some(0), None, None, Some(true), None)
+            .map_err(rpc_error)?;
+
+        let addresses = client
+            .client
+            .list_received_by_address(None, Some(0), Some(true), Some(true))
+            .map_err(rpc_error)?;
+
+        info!(
+            "Importing wallet {} | Unconfirmed transactions({}) | Unspent outputs({}) | Addresses({})",
+            wallet,
+            unconfirmed.len(),
+            unspent.len(),
+            addresses.len()
+        );
+
+        let mut report = WalletImportReport::default();
+
+        let (transactions, skipped): (Vec<Txid>, Vec<Txid>) = unconfirmed
+            .into_iter()
+            .partition(|tx_id| !watched_txs.contains(tx_id));
+        report.transactions.skipped = skipped.len();
+        report.transactions.registered = self.import_batches(
+            wallet,
+            "transactions",
+            transactions
+                .into_iter()
+                .map(|tx_id| TypesToMonitor::Transactions(vec![tx_id], context.clone(), None, None))
+                .collect(),
+        )?;
+
+        let mut outpoints: Vec<OutPoint> = vec![];
+        for entry in unspent {
+            let outpoint = OutPoint::new(entry.txid, entry.vout);
+            if watched_outpoints.insert(outpoint) {
+                outpoints.push(outpoint);
+            } else {
+                report.utxos.skipped += 1;
+            }
+        }
+        report.utxos.registered = self.import_batches(
+            wallet,
+            "unspent outputs",
+            outpoints
+                .into_iter()
+                .map(|outpoint| {
+                    TypesToMonitor::SpendingUTXOTransaction(
+                        outpoint.txid,
+                        outpoint.vout,
+                        context.clone(),
+                        None,
+                        None,
+                        None,
+                        None,
+                    )
+                })
+                .collect(),
+        )?;
+
+        let mut scripts: Vec<ScriptBuf> = vec![];
+        for entry in addresses {
+            // Listed by the node of the wallet, so they are of its network
+            let script_pubkey = entry.address.assume_checked().script_pubkey();
+            if watched_scripts.insert(script_pubkey.clone()) {
+                scripts.push(script_pubkey);
+            } else {
+                report.scripts.skipped += 1;
+            }
+        }
+        report.scripts.registered = self.import_batches(
+            wallet,
+            "addresses",
+            scripts
+                .into_iter()
+                .map(|script_pubkey| {
+                    TypesToMonitor::ScriptPubKey(script_pubkey, context.clone(), None, None)
+                })
+                .collect(),
+        )?;
+
+        info!("Imported wallet {} | {:?}", wallet, report);
+
+        Ok(report)
+    }
+
+    /// Registers `items` in batches of `IMPORT_BATCH_SIZE`, logging the progress after each one.
+    /// Returns how many were registered.
+    fn import_batches(
+        &self,
+        wallet: &str,
+        kind: &str,
+        items: Vec<TypesToMonitor>,
+    ) -> Result<usize, MonitorError> {
+        let total = items.len();
+        let mut registered = 0;
+
+        for batch in items.chunks(IMPORT_BATCH_SIZE) {
+            // Every item has the same context and no trigger, so either all of them are valid
+            // or none is written
+            for outcome in self.monitor_batch(batch.to_vec())? {
+                outcome?;
+            }
+            registered += batch.len();
+
+            info!(
+                "Importing wallet {} | {} registered({}/{})",
+                wallet, kind, registered, total
+            );
+        }
+
+        Ok(registered)
+    }
+}
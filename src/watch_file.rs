@@ -0,0 +1,162 @@
+use crate::api::v1::MonitorSpec;
+use crate::errors::MonitorError;
+use crate::monitor::Monitor;
+use crate::store::MonitorStoreApi;
+use crate::types::TypesToMonitor;
+use bitcoin_indexer::indexer::IndexerApi;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+use tracing::warn;
+
+/// Reads the monitors listed in the watch file at `path`, each one written like the
+/// [`MonitorSpec`] of the JSON API. The file is read as JSON when its extension is `.json` and
+/// as YAML otherwise. Fails with `MonitorError::InvalidSettings` when the file can't be read or
+/// parsed, and with `MonitorError::InvalidMonitor` when a script or prefix is not valid hex.
+pub fn load_watch_file(path: &Path) -> Result<Vec<TypesToMonitor>, MonitorError> {
+    let invalid = |e: &dyn std::fmt::Display| {
+        MonitorError::InvalidSettings(format!("{}: {}", path.display(), e))
+    };
+
+    let content = fs::read_to_string(path).map_err(|e| invalid(&e))?;
+    let specs: Vec<MonitorSpec> = if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::from_str(&content).map_err(|e| invalid(&e))?
+    } else {
+        serde_yaml::from_str(&content).map_err(|e| invalid(&e))?
+    };
+
+    specs.into_iter().map(TypesToMonitor::try_from).collect()
+}
+
+/// Monitors added to and removed from a watch file since it was last read.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WatchFileChanges {
+    pub added: Vec<TypesToMonitor>,
+    pub removed: Vec<TypesToMonitor>,
+}
+
+impl WatchFileChanges {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
+/// A watch file, read again each time its modification time changes. The monitors of the last
+/// valid read are kept: a file that can't be read or parsed leaves them as they are and is
+/// reported once, until it changes again.
+///
+/// Only the changes seen while running are applied, the monitors removed from the file while
+/// the monitor was stopped stay registered.
+#[derive(Debug)]
+pub struct WatchFile {
+    path: PathBuf,
+    /// Modification time of the last read, valid or not
+    modified: Option<SystemTime>,
+    /// Whether the last read failed to get the modification time
+    unreadable: bool,
+    monitors: Vec<TypesToMonitor>,
+}
+
+impl WatchFile {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            modified: None,
+            unreadable: false,
+            monitors: vec![],
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Monitors of the last valid read.
+    pub fn monitors(&self) -> &[TypesToMonitor] {
+        &self.monitors
+    }
+
+    /// Reads the file again if its modification time changed since the last read. Returns the
+    /// changes to the monitors of the last valid read, None if the file didn't change.
+    pub fn poll(&mut self) -> Result<Option<WatchFileChanges>, MonitorError> {
+        let modified = match fs::metadata(&self.path).and_then(|metadata| metadata.modified()) {
+            Ok(modified) => modified,
+            Err(_) if self.unreadable => return Ok(None),
+            Err(e) => {
+                self.unreadable = true;
+                self.modified = None;
+                return Err(MonitorError::InvalidSettings(format!(
+                    "{}: {}",
+                    self.path.display(),
+                    e
+                )));
+            }
+        };
+
+        self.unreadable = false;
+        if self.modified == Some(modified) {
+            return Ok(None);
+        }
+        self.modified = Some(modified);
+
+        let monitors = load_watch_file(&self.path)?;
+        let changes = WatchFileChanges {
+            added: monitors
+                .iter()
+                .filter(|monitor| !self.monitors.contains(monitor))
+                .cloned()
+                .collect(),
+            removed: self
+                .monitors
+                .iter()
+                .filter(|monitor| !monitors.contains(monitor))
+                .cloned()
+                .collect(),
+        };
+        self.monitors = monitors;
+
+        Ok(Some(changes))
+    }
+
+    /// Polls the file and registers the monitors added to it on `monitor` with one
+    /// [`Monitor::monitor_batch`], cancelling the ones removed. A monitor that fails to be
+    /// registered or cancelled is logged and skipped, it is not retried until it is removed from
+    /// the file and added again.
+    pub fn sync<I: IndexerApi, B: MonitorStoreApi>(
+        &mut self,
+        monitor: &Monitor<I, B>,
+    ) -> Result<Option<WatchFileChanges>, MonitorError> {
+        let Some(changes) = self.poll()? else {
+            return Ok(None);
+        };
+
+        if !changes.added.is_empty() {
+            match monitor.monitor_batch(changes.added.clone()) {
+                Ok(outcomes) => {
+                    for (data, outcome) in changes.added.iter().zip(outcomes) {
+                        if let Err(e) = outcome {
+                            warn!(
+                                ?data,
+                                "Failed to register a monitor of the watch file: {}", e
+                            );
+                        }
+                    }
+                }
+                Err(e) => warn!(
+                    added = changes.added.len(),
+                    "Failed to register the monitors added to the watch file: {}", e
+                ),
+            }
+        }
+        for data in &changes.removed {
+            if let Err(e) = monitor.cancel(data.clone()) {
+                warn!(
+                    ?data,
+                    "Failed to cancel a monitor removed from the watch file: {}", e
+                );
+            }
+        }
+
+        Ok(Some(changes))
+    }
+}
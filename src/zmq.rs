@@ -0,0 +1,330 @@
+use crate::commands::BlockTrigger;
+use crate::errors::MonitorError;
+use bitcoin::{hashes::Hash, BlockHash};
+use std::io::{self, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::thread::JoinHandle;
+use std::time::Duration;
+use tracing::{debug, info, warn};
+
+/// Topic of the block hashes published by a node started with `-zmqpubhashblock`.
+const HASHBLOCK_TOPIC: &[u8] = b"hashblock";
+
+/// How often a blocked read checks whether the listener was stopped.
+const STOP_CHECK_INTERVAL: Duration = Duration::from_millis(200);
+
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(5);
+const INITIAL_RECONNECT_DELAY: Duration = Duration::from_secs(1);
+const MAX_RECONNECT_DELAY: Duration = Duration::from_secs(30);
+
+/// Largest frame accepted from the node, the block notifications are a few bytes long.
+const MAX_FRAME_SIZE: u64 = 1 << 16;
+
+const FLAG_MORE: u8 = 0x01;
+const FLAG_LONG: u8 = 0x02;
+const FLAG_COMMAND: u8 = 0x04;
+
+/// Subscribes to the `hashblock` notifications of a Bitcoin Core node over ZMQ and notifies a
+/// [`BlockTrigger`] for each block published, so the run loop ticks as soon as a block arrives.
+///
+/// The subscriber speaks ZMTP 3.0 with the NULL mechanism over a plain TCP connection, which is
+/// what the node publishes on. A lost connection is opened again with a backoff, the blocks
+/// published meanwhile are picked up by the regular ticks. The listener stops when dropped, or
+/// once the monitor of the trigger is dropped.
+pub struct ZmqBlockListener {
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl ZmqBlockListener {
+    /// Fails with [`MonitorError::InvalidSettings`] if `endpoint` is not a `tcp://host:port`
+    /// endpoint. The node is connected by the listener thread, it may not be up yet.
+    pub fn start(endpoint: &str, trigger: BlockTrigger) -> Result<Self, MonitorError> {
+        let (host, port) = parse_endpoint(endpoint)?;
+        let endpoint = endpoint.to_string();
+        let stop = Arc::new(AtomicBool::new(false));
+        let thread_stop = stop.clone();
+
+        let handle = std::thread::Builder::new()
+            .name("zmq-blocks".to_string())
+            .spawn(move || {
+                let mut failures = 0;
+
+                while !thread_stop.load(Ordering::SeqCst) {
+                    match subscribe(&host, port, &thread_stop, &trigger, &mut failures) {
+                        Ok(()) => break,
+                        Err(e) => {
+                            failures += 1;
+                            let delay = INITIAL_RECONNECT_DELAY
+                                .saturating_mul(1 << (failures - 1).min(5))
+                                .min(MAX_RECONNECT_DELAY);
+                            warn!(
+                                failures,
+                                "Block notifications of {} lost, reconnecting in {:?}: {}",
+                                endpoint,
+                                delay,
+                                e
+                            );
+                            sleep_unless_stopped(delay, &thread_stop);
+                        }
+                    }
+                }
+            })
+            .map_err(|e| {
+                MonitorError::UnexpectedError(format!("zmq listener not started: {}", e))
+            })?;
+
+        Ok(Self {
+            stop,
+            handle: Some(handle),
+        })
+    }
+}
+
+impl Drop for ZmqBlockListener {
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::SeqCst);
+
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn parse_endpoint(endpoint: &str) -> Result<(String, u16), MonitorError> {
+    let invalid = |reason: &str| {
+        MonitorError::InvalidSettings(format!("zmq_block_endpoint {}: {}", endpoint, reason))
+    };
+
+    let authority = endpoint
+        .strip_prefix("tcp://")
+        .ok_or_else(|| invalid("only tcp:// endpoints are supported"))?;
+    let (host, port) = authority
+        .rsplit_once(':')
+        .ok_or_else(|| invalid("missing port"))?;
+    let port = port.parse::<u16>().map_err(|_| invalid("invalid port"))?;
+    if host.is_empty() {
+        return Err(invalid("missing host"));
+    }
+
+    Ok((host.to_string(), port))
+}
+
+/// Reads the notifications of the node until the listener is stopped or the monitor is dropped,
+/// which return Ok. Connection and protocol failures are errors, `failures` is reset once
+/// subscribed.
+fn subscribe(
+    host: &str,
+    port: u16,
+    stop: &AtomicBool,
+    trigger: &BlockTrigger,
+    failures: &mut u32,
+) -> io::Result<()> {
+    let addr = (host, port)
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::other(format!("{} not resolved", host)))?;
+    let stream = TcpStream::connect_timeout(&addr, CONNECT_TIMEOUT)?;
+    stream.set_read_timeout(Some(STOP_CHECK_INTERVAL))?;
+    let mut connection = Connection { stream, stop };
+
+    if !connection.handshake()? {
+        return Ok(());
+    }
+    info!("Subscribed to the block notifications of {}:{}", host, port);
+    *failures = 0;
+
+    while let Some(message) = connection.read_message()? {
+        match message.as_slice() {
+            [topic, hash, rest @ ..] if topic.as_slice() == HASHBLOCK_TOPIC => {
+                let mut bytes: [u8; 32] = hash
+                    .as_slice()
+                    .try_into()
+                    .map_err(|_| invalid_data("invalid block hash"))?;
+                // Published in the byte order of the RPC, reversed from the internal one
+                bytes.reverse();
+                let sequence = rest
+                    .first()
+                    .and_then(|sequence| <[u8; 4]>::try_from(sequence.as_slice()).ok())
+                    .map(u32::from_le_bytes);
+                debug!(
+                    hash = %BlockHash::from_byte_array(bytes),
+                    ?sequence,
+                    "Block published"
+                );
+
+                if trigger.notify().is_err() {
+                    // The monitor is dropped, nobody is waiting for blocks
+                    return Ok(());
+                }
+            }
+            _ => debug!("Ignoring a message of another topic"),
+        }
+    }
+
+    Ok(())
+}
+
+fn sleep_unless_stopped(delay: Duration, stop: &AtomicBool) {
+    let mut slept = Duration::ZERO;
+    while slept < delay && !stop.load(Ordering::SeqCst) {
+        std::thread::sleep(STOP_CHECK_INTERVAL);
+        slept += STOP_CHECK_INTERVAL;
+    }
+}
+
+struct Frame {
+    more: bool,
+    command: bool,
+    body: Vec<u8>,
+}
+
+/// A subscriber connection. The reads return None once the listener is stopped.
+struct Connection<'a> {
+    stream: TcpStream,
+    stop: &'a AtomicBool,
+}
+
+impl Connection<'_> {
+    /// Exchanges the greetings and the READY commands, then subscribes to the block hashes.
+    /// Returns false if the listener was stopped meanwhile.
+    fn handshake(&mut self) -> io::Result<bool> {
+        let mut greeting = [0u8; 64];
+        greeting[0] = 0xff;
+        greeting[9] = 0x7f;
+        // ZMTP 3.0, so the subscription is sent as a message
+        greeting[10] = 3;
+        greeting[12..16].copy_from_slice(b"NULL");
+        self.stream.write_all(&greeting)?;
+
+        let mut peer = [0u8; 64];
+        if !self.read_full(&mut peer)? {
+            return Ok(false);
+        }
+        if peer[0] != 0xff || peer[9] != 0x7f || peer[10] < 3 {
+            return Err(invalid_data("the peer is not a ZMTP 3 publisher"));
+        }
+        if &peer[12..16] != b"NULL" || peer[16..32].iter().any(|&b| b != 0) {
+            return Err(invalid_data("the peer asks for a security mechanism"));
+        }
+
+        let mut ready = vec![5];
+        ready.extend_from_slice(b"READY");
+        ready.push(11);
+        ready.extend_from_slice(b"Socket-Type");
+        ready.extend_from_slice(&3u32.to_be_bytes());
+        ready.extend_from_slice(b"SUB");
+        self.write_frame(FLAG_COMMAND, &ready)?;
+
+        let Some(frame) = self.read_frame()? else {
+            return Ok(false);
+        };
+        if !frame.command || !frame.body.starts_with(b"\x05READY") {
+            return Err(invalid_data("the peer refused the connection"));
+        }
+
+        let mut subscription = vec![1];
+        subscription.extend_from_slice(HASHBLOCK_TOPIC);
+        self.write_frame(0, &subscription)?;
+        self.stream.flush()?;
+
+        Ok(true)
+    }
+
+    /// Next message published, as its frames. The commands of the peer are skipped.
+    fn read_message(&mut self) -> io::Result<Option<Vec<Vec<u8>>>> {
+        let mut parts = vec![];
+
+        loop {
+            let Some(frame) = self.read_frame()? else {
+                return Ok(None);
+            };
+            if frame.command {
+                continue;
+            }
+
+            parts.push(frame.body);
+            if !frame.more {
+                return Ok(Some(parts));
+            }
+        }
+    }
+
+    fn read_frame(&mut self) -> io::Result<Option<Frame>> {
+        let mut flags = [0u8; 1];
+        if !self.read_full(&mut flags)? {
+            return Ok(None);
+        }
+
+        let size = if flags[0] & FLAG_LONG != 0 {
+            let mut size = [0u8; 8];
+            if !self.read_full(&mut size)? {
+                return Ok(None);
+            }
+            u64::from_be_bytes(size)
+        } else {
+            let mut size = [0u8; 1];
+            if !self.read_full(&mut size)? {
+                return Ok(None);
+            }
+            u64::from(size[0])
+        };
+        if size > MAX_FRAME_SIZE {
+            return Err(invalid_data("frame too large"));
+        }
+
+        let mut body = vec![0; size as usize];
+        if !self.read_full(&mut body)? {
+            return Ok(None);
+        }
+
+        Ok(Some(Frame {
+            more: flags[0] & FLAG_MORE != 0,
+            command: flags[0] & FLAG_COMMAND != 0,
+            body,
+        }))
+    }
+
+    fn write_frame(&mut self, flags: u8, body: &[u8]) -> io::Result<()> {
+        if body.len() > u8::MAX as usize {
+            self.stream.write_all(&[flags | FLAG_LONG])?;
+            self.stream.write_all(&(body.len() as u64).to_be_bytes())?;
+        } else {
+            self.stream.write_all(&[flags, body.len() as u8])?;
+        }
+        self.stream.write_all(body)
+    }
+
+    /// Fills `buf`, waiting for the node as long as the listener is not stopped. Returns false
+    /// once stopped.
+    fn read_full(&mut self, buf: &mut [u8]) -> io::Result<bool> {
+        let mut read = 0;
+
+        while read < buf.len() {
+            if self.stop.load(Ordering::SeqCst) {
+                return Ok(false);
+            }
+
+            match self.stream.read(&mut buf[read..]) {
+                Ok(0) => return Err(io::ErrorKind::UnexpectedEof.into()),
+                Ok(n) => read += n,
+                Err(e)
+                    if matches!(
+                        e.kind(),
+                        io::ErrorKind::WouldBlock
+                            | io::ErrorKind::TimedOut
+                            | io::ErrorKind::Interrupted
+                    ) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(true)
+    }
+}
+
+fn invalid_data(reason: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, reason.to_string())
+}
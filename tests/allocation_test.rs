@@ -0,0 +1,202 @@
+use bitcoin::{
+    absolute::LockTime,
+    opcodes::all::{OP_CHECKMULTISIG, OP_PUSHNUM_1},
+    script::Builder,
+    Amount, BlockHash, OutPoint, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid, Witness,
+};
+use bitcoin_indexer::{
+    indexer::MockIndexerApi,
+    types::{FullBlock, TransactionInfo},
+};
+use bitvmx_transaction_monitor::{
+    config::{MonitorSettings, MonitorSettingsConfig},
+    monitor::Monitor,
+    store::MonitorStore,
+    types::MonitorNews,
+    WatchOutpoint, WatchPegin, WatchScript, WatchTx,
+};
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, AtomicUsize, Ordering},
+        Arc,
+    },
+};
+use storage_backend::{storage::Storage, storage_config::StorageConfig};
+use utils::{clear_output, generate_random_string};
+mod utils;
+
+/// Size of the witness of the block transaction, any allocation this large is a copy of it.
+const WITNESS_SIZE: usize = 200_000;
+
+static COUNTING: AtomicBool = AtomicBool::new(false);
+static LARGE_ALLOCATIONS: AtomicUsize = AtomicUsize::new(0);
+
+/// Counts the allocations of at least `WITNESS_SIZE` bytes while `COUNTING` is set.
+struct CountingAllocator;
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        if COUNTING.load(Ordering::SeqCst) && layout.size() >= WITNESS_SIZE {
+            LARGE_ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        }
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        if COUNTING.load(Ordering::SeqCst)
+            && new_size >= WITNESS_SIZE
+            && layout.size() < WITNESS_SIZE
+        {
+            LARGE_ALLOCATIONS.fetch_add(1, Ordering::SeqCst);
+        }
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Test that a block transaction detected by several monitors is not copied by the monitor:
+/// 1. A transaction with a large witness is watched by id, spends a watched output and pays to a
+///    watched script, a pegin monitor also scans the block
+/// 2. Tick the block, the three monitors send their news
+/// 3. Assert the only copies of the transaction are the ones returned by the indexer mock for
+///    each get_best_block call
+#[test]
+fn test_block_tx_shared_by_detections() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let target_tx_id =
+        Txid::from_str("0000000000000000000000000000000000000000000000000000000000000001")?;
+
+    let script_pubkey = Builder::new()
+        .push_opcode(OP_PUSHNUM_1)
+        .push_slice([2u8; 33])
+        .push_opcode(OP_PUSHNUM_1)
+        .push_opcode(OP_CHECKMULTISIG)
+        .into_script();
+
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint {
+                txid: target_tx_id,
+                vout: 0,
+            },
+            script_sig: ScriptBuf::new(),
+            sequence: Sequence::MAX,
+            witness: Witness::from_slice(&[vec![7u8; WITNESS_SIZE]]),
+        }],
+        output: vec![TxOut {
+            value: Amount::from_sat(50_000),
+            script_pubkey: script_pubkey.clone(),
+        }],
+    };
+    let tx_id = tx.compute_txid();
+
+    // The indexer status uses a stand-in, so only copies of the block transaction are counted
+    let status_tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![],
+        output: vec![],
+    };
+
+    fn block_at(height: u32, txs: Vec<Transaction>) -> FullBlock {
+        FullBlock {
+            txs,
+            ..utils::block_at(height)
+        }
+    }
+
+    let height = Arc::new(AtomicU32::new(200));
+    let best_block_calls = Arc::new(AtomicU32::new(0));
+
+    let mut mock_indexer = MockIndexerApi::new();
+    let best_height = height.clone();
+    let calls = best_block_calls.clone();
+    mock_indexer.expect_get_best_block().returning(move || {
+        calls.fetch_add(1, Ordering::SeqCst);
+        let height = best_height.load(Ordering::SeqCst);
+        let txs = if height == 201 {
+            vec![tx.clone()]
+        } else {
+            vec![]
+        };
+        Ok(Some(block_at(height, txs)))
+    });
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(|height| Ok(Some(block_at(height, vec![]))));
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let tx_height = height.clone();
+    mock_indexer.expect_get_tx().returning(move |_| {
+        let height = tx_height.load(Ordering::SeqCst);
+        if height < 201 {
+            return Ok(None);
+        }
+        Ok(Some(TransactionInfo {
+            tx: status_tx.clone(),
+            block_info: block_at(201, vec![]),
+            confirmations: height - 200,
+        }))
+    });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+
+    // 1. Three monitors matching the same transaction, and a pegin monitor
+    monitor.save_monitor(WatchTx::new(tx_id).context("tx").build()?)?;
+    monitor.save_monitor(
+        WatchOutpoint::new(target_tx_id, 0)
+            .context("spend")
+            .build()?,
+    )?;
+    monitor.save_monitor(WatchScript::new(script_pubkey).context("script").build()?)?;
+    monitor.save_monitor(WatchPegin::new().build()?)?;
+    monitor.tick()?;
+
+    // 2. Tick the block with the transaction
+    height.store(201, Ordering::SeqCst);
+    best_block_calls.store(0, Ordering::SeqCst);
+    LARGE_ALLOCATIONS.store(0, Ordering::SeqCst);
+
+    COUNTING.store(true, Ordering::SeqCst);
+    monitor.tick()?;
+    COUNTING.store(false, Ordering::SeqCst);
+
+    let news = monitor.get_news()?;
+    assert_eq!(news.len(), 3);
+    assert!(news
+        .iter()
+        .any(|n| matches!(n, MonitorNews::Transaction(t, _, _) if *t == tx_id)));
+    assert!(news.iter().any(
+        |n| matches!(n, MonitorNews::SpendingUTXOTransaction(t, 0, _, _) if *t == target_tx_id)
+    ));
+    assert!(news
+        .iter()
+        .any(|n| matches!(n, MonitorNews::ScriptPubKeyTransaction(t, 0, _, _) if *t == tx_id)));
+
+    // 3. No copies besides the blocks returned by the mock
+    assert_eq!(
+        LARGE_ALLOCATIONS.load(Ordering::SeqCst),
+        best_block_calls.load(Ordering::SeqCst) as usize
+    );
+
+    clear_output();
+
+    Ok(())
+}
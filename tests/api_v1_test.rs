@@ -0,0 +1,472 @@
+use bitcoin::{
+    absolute::LockTime,
+    secp256k1::{PublicKey, Secp256k1, SecretKey},
+    Amount, BlockHash, OutPoint, ScriptBuf, Transaction, TxIn, TxOut, Txid, Work,
+};
+use bitcoin_indexer::types::FullBlock;
+use bitvmx_transaction_monitor::{
+    api::v1::{
+        AckRequest, ErrorResponse, HealthResponse, MonitorsResponse, NewsResponse, OkResponse,
+        RegisterRequest, StatusResponse, TxStatusResponse,
+    },
+    errors::MonitorError,
+    health::HealthScore,
+    store::TypesToMonitorStore,
+    types::{
+        AckMonitorNews, DetectionDescriptor, HealthState, MonitorInfo, MonitorNews, NewsCursor,
+        NewsEnvelope, NewsPage, OutputFilter, RegistrationOptions, ReorgSummary, Resolution,
+        RskPeginData, SnoozeTarget, SpendDeadline, SpendValueGate, TransactionBlockchainStatus,
+        TransactionStatus, TypesToMonitor,
+    },
+};
+use serde::{de::DeserializeOwned, Serialize};
+use serde_json::{json, Value};
+use std::{collections::BTreeMap, fmt::Debug, str::FromStr, time::Duration};
+
+const NEWS_RESPONSE: &str = include_str!("fixtures/api_v1/news_response.json");
+const MONITORS_RESPONSE: &str = include_str!("fixtures/api_v1/monitors_response.json");
+const RESPONSES: &str = include_str!("fixtures/api_v1/responses.json");
+const ACK_REQUESTS: &str = include_str!("fixtures/api_v1/ack_requests.json");
+const REGISTER_REQUESTS: &str = include_str!("fixtures/api_v1/register_requests.json");
+
+fn txid(n: u64) -> Txid {
+    Txid::from_str(&format!("{:064x}", n)).unwrap()
+}
+
+fn block_hash(n: u64) -> BlockHash {
+    BlockHash::from_str(&format!("{:064x}", n)).unwrap()
+}
+
+fn status(n: u64, status: TransactionBlockchainStatus, confirmations: u32) -> TransactionStatus {
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::new(txid(n), 0),
+            ..Default::default()
+        }],
+        output: vec![TxOut {
+            value: Amount::from_sat(10_000),
+            script_pubkey: ScriptBuf::from_hex("51").unwrap(),
+        }],
+    };
+
+    TransactionStatus {
+        tx_id: tx.compute_txid(),
+        tx,
+        block_info: (status != TransactionBlockchainStatus::Mempool).then(|| FullBlock {
+            height: 200,
+            hash: block_hash(200),
+            prev_hash: block_hash(199),
+            txs: vec![],
+            orphan: status == TransactionBlockchainStatus::Orphan,
+            estimated_fee_rate: 1,
+        }),
+        confirmations,
+        status,
+    }
+}
+
+/// Asserts `payload` serializes to the golden JSON `fixture`, and the fixture reads back as
+/// `payload`.
+fn assert_golden<T>(payload: &T, fixture: &Value)
+where
+    T: Serialize + DeserializeOwned + PartialEq + Debug,
+{
+    assert_eq!(&serde_json::to_value(payload).unwrap(), fixture);
+    assert_eq!(
+        &serde_json::from_value::<T>(fixture.clone()).unwrap(),
+        payload
+    );
+}
+
+/// Test that the news response keeps its v1 JSON:
+/// 1. A page with a news of every kind and the cursor of the next page
+/// 2. It serializes to the golden file and reads back from it
+#[test]
+fn test_news_response_golden() -> Result<(), anyhow::Error> {
+    // 1. Every kind
+    let secp = Secp256k1::new();
+    let reimbursement_key = PublicKey::from_secret_key(&secp, &SecretKey::from_slice(&[2u8; 32])?)
+        .x_only_public_key()
+        .0;
+    let confirmed = status(1, TransactionBlockchainStatus::Confirmed, 3);
+    let news = vec![
+        MonitorNews::Transaction(txid(1), confirmed.clone(), "payout".into()),
+        MonitorNews::SpendingUTXOTransaction(
+            txid(2),
+            1,
+            status(2, TransactionBlockchainStatus::Finalized, 6),
+            "deposit".into(),
+        ),
+        MonitorNews::SpendingUTXOUnconfirmed(txid(2), 1, txid(3), "deposit".into()),
+        MonitorNews::MinorSpend(
+            txid(2),
+            0,
+            status(4, TransactionBlockchainStatus::Mempool, 0),
+            "dust".into(),
+        ),
+        MonitorNews::RskPeginTransaction(
+            txid(5),
+            confirmed.clone(),
+            Some(RskPeginData {
+                packet_number: 7,
+                rsk_address: [0x7a; 20],
+                reimbursement_key,
+                amount: Amount::from_sat(100_000_000),
+            }),
+        ),
+        MonitorNews::ScriptPubKeyTransaction(txid(6), 2, confirmed.clone(), "script".into()),
+        MonitorNews::OpReturnTransaction(txid(7), b"TAG:42".to_vec(), "tag".into()),
+        MonitorNews::NewBlock(201, block_hash(201)),
+        MonitorNews::OrphanedTransaction(
+            txid(8),
+            status(8, TransactionBlockchainStatus::Orphan, 0),
+            "payout".into(),
+        ),
+        MonitorNews::MonitorReplaced {
+            old: txid(9),
+            new: txid(10),
+            context: "rbf".into(),
+        },
+        MonitorNews::ReplacedTransactionConfirmed {
+            old: txid(9),
+            new: txid(10),
+            context: "rbf".into(),
+        },
+        MonitorNews::SequenceEpochChanged {
+            old_epoch: 0,
+            new_epoch: 1,
+        },
+        MonitorNews::Reorg {
+            old_tip: block_hash(200),
+            new_tip: block_hash(300),
+            summary: ReorgSummary {
+                moved_transactions: 1,
+                disappeared_spends: 2,
+                changed_spends: 3,
+                invalidated_pegins: 4,
+                reopened_news: 5,
+            },
+        },
+        MonitorNews::LowWorkReorg {
+            height: 199,
+            old_work: Work::from_be_bytes([0x01; 32]),
+            new_work: Work::from_be_bytes([0x00; 32]),
+        },
+        MonitorNews::AlternativeResolved {
+            winner: txid(11),
+            losers: vec![txid(12)],
+            context: "challenge".into(),
+        },
+        MonitorNews::HealthStateChanged {
+            state: HealthState::Degraded,
+            score: 40,
+        },
+        MonitorNews::ManuallyResolved(
+            DetectionDescriptor {
+                monitor: SnoozeTarget::OpReturnPrefix(b"TAG".to_vec(), "tag".into()),
+                tx_id: None,
+            },
+            "seen on a block explorer".to_string(),
+        ),
+        MonitorNews::Composite("settled".to_string()),
+    ];
+    let page = NewsPage {
+        news: news
+            .into_iter()
+            .enumerate()
+            .map(|(i, news)| NewsEnvelope {
+                news,
+                resolution: Resolution::Snapshot,
+                epoch: 1,
+                sequence: i as u64 + 1,
+                metadata: BTreeMap::from([("step".to_string(), i.to_string())]),
+                orphan_disputed: false,
+            })
+            .collect(),
+        next: Some(NewsCursor {
+            sequence: 19,
+            id: "news/tx/next".to_string(),
+        }),
+    };
+
+    // 2. Golden file
+    let fixture: Value = serde_json::from_str(NEWS_RESPONSE)?;
+    assert_golden(&NewsResponse::from(&page), &fixture);
+
+    Ok(())
+}
+
+/// Test that the monitors response keeps its v1 JSON:
+/// 1. A monitor of every kind stored, with a spender and metadata
+/// 2. It serializes to the golden file and reads back from it
+#[test]
+fn test_monitors_response_golden() -> Result<(), anyhow::Error> {
+    // 1. Every kind
+    let info = |monitor: TypesToMonitorStore| MonitorInfo {
+        monitor,
+        active: true,
+        spender_tx_id: None,
+        history: vec![],
+        metadata: BTreeMap::new(),
+    };
+    let monitors = vec![
+        MonitorInfo {
+            metadata: BTreeMap::from([("protocol".to_string(), "pegout-42".to_string())]),
+            ..info(TypesToMonitorStore::Transaction(
+                txid(1),
+                "payout".into(),
+                Some(2),
+                None,
+            ))
+        },
+        MonitorInfo {
+            active: false,
+            spender_tx_id: Some(txid(3)),
+            ..info(TypesToMonitorStore::SpendingUTXOTransaction(
+                txid(2),
+                1,
+                "deposit".into(),
+                None,
+                Some(SpendDeadline::new(300, 6)),
+                Some(SpendValueGate::new(Amount::from_sat(50_000))),
+                None,
+            ))
+        },
+        info(TypesToMonitorStore::NewBlock),
+        info(TypesToMonitorStore::RskPegin(
+            Some(6),
+            vec![ScriptBuf::from_hex("51")?],
+            Some((10, 20)),
+            Some(Amount::from_sat(1_000)),
+        )),
+        info(TypesToMonitorStore::ScriptPubKey(
+            ScriptBuf::from_hex("0014aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa")?,
+            "script".into(),
+            None,
+            Some(OutputFilter {
+                min_value: Some(Amount::from_sat(546)),
+                ignore_dust_below_relay: true,
+            }),
+        )),
+        info(TypesToMonitorStore::OpReturnPrefix(
+            b"TAG".to_vec(),
+            "tag".into(),
+        )),
+    ];
+
+    // 2. Golden file
+    let fixture: Value = serde_json::from_str(MONITORS_RESPONSE)?;
+    assert_golden(&MonitorsResponse::from(monitors.as_slice()), &fixture);
+
+    Ok(())
+}
+
+/// Test that the other responses keep their v1 JSON: the status of a transaction, the health,
+/// the status of the monitor, an error and an empty response.
+#[test]
+fn test_responses_golden() -> Result<(), anyhow::Error> {
+    let fixture: Value = serde_json::from_str(RESPONSES)?;
+
+    assert_golden(
+        &TxStatusResponse::from(&status(1, TransactionBlockchainStatus::Finalized, 6)),
+        &fixture["tx_status"],
+    );
+    assert_golden(
+        &HealthResponse::from(&HealthScore {
+            score: 40,
+            state: HealthState::Degraded,
+            indexer_failures: 2,
+            store_corruptions: 0,
+            news_resolution_failures: 4,
+            orphan_disagreements: 0,
+            tick_duration_p95: Duration::from_millis(1_500),
+        }),
+        &fixture["health"],
+    );
+    assert_golden(&StatusResponse::new(200, true), &fixture["status"]);
+    assert_golden(
+        &ErrorResponse::from(&MonitorError::InvalidMonitor("empty context".to_string())),
+        &fixture["error"],
+    );
+    assert_golden(
+        &ErrorResponse::from(&MonitorError::HeldByLowWorkReorg(199)),
+        &fixture["transient_error"],
+    );
+    assert_golden(&OkResponse::default(), &fixture["ok"]);
+
+    Ok(())
+}
+
+/// Test that the v1 requests still read as the same internal types:
+/// 1. An ack of every kind of news
+/// 2. A registration of every kind of monitor, with and without metadata
+/// 3. Both serialize back to the same JSON
+#[test]
+fn test_requests_compatibility() -> Result<(), anyhow::Error> {
+    // 1. Acks
+    let acks: Vec<AckRequest> = serde_json::from_str(ACK_REQUESTS)?;
+    let internal: Vec<AckMonitorNews> = acks.iter().cloned().map(|ack| ack.news.into()).collect();
+    assert_eq!(
+        internal,
+        vec![
+            AckMonitorNews::Transaction(txid(1), "payout".into()),
+            AckMonitorNews::SpendingUTXOTransaction(txid(2), 1, "deposit".into()),
+            AckMonitorNews::SpendingUTXOUnconfirmed(txid(2), 1, "deposit".into()),
+            AckMonitorNews::MinorSpend(txid(2), 0, "dust".into()),
+            AckMonitorNews::RskPeginTransaction(txid(5)),
+            AckMonitorNews::ScriptPubKeyTransaction(txid(6), 2, "script".into()),
+            AckMonitorNews::OpReturnTransaction(txid(7), "tag".into()),
+            AckMonitorNews::NewBlock(Some(block_hash(201))),
+            AckMonitorNews::NewBlock(None),
+            AckMonitorNews::OrphanedTransaction(txid(8), "payout".into()),
+            AckMonitorNews::MonitorReplaced {
+                old: txid(9),
+                context: "rbf".into(),
+            },
+            AckMonitorNews::ReplacedTransactionConfirmed {
+                old: txid(9),
+                context: "rbf".into(),
+            },
+            AckMonitorNews::SequenceEpochChanged { new_epoch: 1 },
+            AckMonitorNews::Reorg {
+                new_tip: block_hash(300),
+            },
+            AckMonitorNews::LowWorkReorg { height: 199 },
+            AckMonitorNews::AlternativeResolved {
+                winner: txid(11),
+                context: "challenge".into(),
+            },
+            AckMonitorNews::HealthStateChanged {
+                state: HealthState::Degraded,
+            },
+            AckMonitorNews::ManuallyResolved(DetectionDescriptor {
+                monitor: SnoozeTarget::SpendingUTXOTransaction(txid(2), 1, "deposit".into()),
+                tx_id: None,
+            }),
+            AckMonitorNews::Composite("settled".to_string()),
+        ]
+    );
+
+    // 2. Registrations
+    let requests: Vec<RegisterRequest> = serde_json::from_str(REGISTER_REQUESTS)?;
+    let registrations = requests
+        .iter()
+        .cloned()
+        .map(RegisterRequest::into_registration)
+        .collect::<Result<Vec<_>, _>>()?;
+    let metadata = BTreeMap::from([("protocol".to_string(), "pegout-42".to_string())]);
+    let options = |metadata: Option<BTreeMap<String, String>>| RegistrationOptions {
+        metadata,
+        ..Default::default()
+    };
+    assert_eq!(
+        registrations,
+        vec![
+            (
+                TypesToMonitor::Transactions(
+                    vec![txid(1), txid(2)],
+                    "payout".into(),
+                    Some(2),
+                    None
+                ),
+                options(Some(metadata)),
+            ),
+            (
+                TypesToMonitor::SpendingUTXOTransaction(
+                    txid(2),
+                    1,
+                    "deposit".into(),
+                    None,
+                    Some(SpendDeadline::new(300, 6)),
+                    Some(SpendValueGate::new(Amount::from_sat(50_000))),
+                    None,
+                ),
+                options(None),
+            ),
+            (
+                TypesToMonitor::RskPegin(
+                    Some(6),
+                    vec![ScriptBuf::from_hex("51")?],
+                    Some((10, 20)),
+                    Some(Amount::from_sat(1_000)),
+                ),
+                options(None),
+            ),
+            (TypesToMonitor::NewBlock, options(None)),
+            (
+                TypesToMonitor::ScriptPubKey(
+                    ScriptBuf::from_hex("0014aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa")?,
+                    "script".into(),
+                    None,
+                    Some(OutputFilter {
+                        min_value: None,
+                        ignore_dust_below_relay: true,
+                    }),
+                ),
+                options(None),
+            ),
+            (
+                TypesToMonitor::OpReturnPrefix(b"TAG".to_vec(), "tag".into()),
+                options(None),
+            ),
+            (
+                TypesToMonitor::Alternatives(vec![txid(11), txid(12)], "challenge".into()),
+                options(None),
+            ),
+        ]
+    );
+
+    // 3. Same JSON
+    let fixture: Value = serde_json::from_str(ACK_REQUESTS)?;
+    assert_golden(&acks, &fixture);
+    let fixture: Value = serde_json::from_str(REGISTER_REQUESTS)?;
+    assert_eq!(serde_json::to_value(&requests)?, fixture);
+
+    Ok(())
+}
+
+/// Test that the v1 payloads are strict:
+/// 1. An unknown field or an unknown kind is rejected
+/// 2. Optional fields of a monitor can be left out
+/// 3. A script that is not valid hex is an invalid monitor
+#[test]
+fn test_requests_are_strict() -> Result<(), anyhow::Error> {
+    // 1. Unknown
+    let txid = txid(1).to_string();
+    let unknown_field =
+        json!({"news": {"kind": "transaction", "txid": txid, "context": "a", "extra": 1}});
+    assert!(serde_json::from_value::<AckRequest>(unknown_field).is_err());
+    let unknown_kind = json!({"news": {"kind": "transactions", "txid": txid, "context": "a"}});
+    assert!(serde_json::from_value::<AckRequest>(unknown_kind).is_err());
+    let unknown_top_level =
+        json!({"news": {"kind": "reorg", "new_tip": block_hash(1)}, "all": true});
+    assert!(serde_json::from_value::<AckRequest>(unknown_top_level).is_err());
+    let mut response: Value = serde_json::from_str(RESPONSES)?;
+    response["health"]["uptime"] = json!(10);
+    assert!(serde_json::from_value::<HealthResponse>(response["health"].clone()).is_err());
+
+    // 2. Defaults
+    let request: RegisterRequest = serde_json::from_value(
+        json!({"monitor": {"kind": "transactions", "txids": [txid], "context": "a"}}),
+    )?;
+    assert_eq!(
+        request.into_registration()?,
+        (
+            TypesToMonitor::Transactions(vec![self::txid(1)], "a".into(), None, None),
+            RegistrationOptions::default(),
+        )
+    );
+
+    // 3. Invalid hex
+    let request: RegisterRequest = serde_json::from_value(
+        json!({"monitor": {"kind": "script_pubkey", "script_pubkey": "zz", "context": "a"}}),
+    )?;
+    assert!(matches!(
+        request.into_registration(),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+
+    Ok(())
+}
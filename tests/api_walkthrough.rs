@@ -0,0 +1,2406 @@
+use bitcoin::{
+    absolute::LockTime,
+    hex::FromHex,
+    key::Secp256k1,
+    opcodes::all::{OP_CHECKSIG, OP_RETURN},
+    script::{Builder, PushBytes},
+    secp256k1::{PublicKey, SecretKey},
+    Address, Amount, BlockHash, CompactTarget, Network, OutPoint, ScriptBuf, Transaction, TxIn,
+    TxOut, Txid,
+};
+use bitcoin_indexer::{
+    indexer::MockIndexerApi,
+    types::{FullBlock, TransactionInfo},
+};
+use bitvmx_transaction_monitor::{
+    config::{HealthSettings, MonitorSettings, MonitorSettingsConfig},
+    errors::MonitorError,
+    external::NoIndexer,
+    headers::MockBlockHeaderApi,
+    mempool::MockMempoolApi,
+    migrations::logical_snapshot,
+    monitor::Monitor,
+    store::{MonitorStore, MonitorStoreApi, MonitoredTypes, TypesToMonitorStore},
+    types::{
+        AckActor, AckMetadata, AckMonitorNews, BlockSource, CancelReason, ChainLogIssue,
+        ContextProgress, DetectionDescriptor, HealthState, MonitorNews, NewsPredicate,
+        RegistrationOptions, Resolution, SnoozeTarget, TransactionBlockchainStatus, TrashSelector,
+        TypesToMonitor,
+    },
+    WatchAlternatives, WatchOpReturn, WatchOutpoint, WatchPegin, WatchScript, WatchTx,
+};
+use std::{
+    collections::BTreeMap,
+    str::FromStr,
+    sync::{Arc, Mutex},
+    thread,
+    time::Duration,
+};
+use storage_backend::{storage::Storage, storage_config::StorageConfig};
+use utils::{clear_output, generate_random_string};
+mod utils;
+
+const FIRST_HEIGHT: u32 = 200;
+
+/// Chain and mempool served by the mocked indexer. Blocks mined after a reorg belong to a new
+/// branch, numbered in the upper bytes of their hashes, and the replaced blocks are kept as orphans.
+#[derive(Default)]
+struct ScriptedChain {
+    blocks: Vec<FullBlock>,
+    orphans: Vec<FullBlock>,
+    branch: u32,
+    mempool: Vec<Transaction>,
+    /// Transactions the indexer wrongly reports in an orphaned block
+    flagged_orphan: Vec<Txid>,
+    /// Time the indexer takes to answer the best block, to slow the ticks down
+    latency: Duration,
+}
+
+impl ScriptedChain {
+    fn hash(branch: u32, height: u32) -> BlockHash {
+        BlockHash::from_str(&format!("{:056x}{:08x}", branch, height)).unwrap()
+    }
+
+    /// Mines the next block with `txs`, which leave the mempool.
+    fn mine(&mut self, txs: Vec<Transaction>) {
+        let height = FIRST_HEIGHT + self.blocks.len() as u32;
+        let prev_hash = self
+            .blocks
+            .last()
+            .map_or(Self::hash(0, height - 1), |block| block.hash);
+        self.mempool.retain(|tx| !txs.contains(tx));
+        self.blocks.push(FullBlock {
+            height,
+            hash: Self::hash(self.branch, height),
+            prev_hash,
+            txs,
+            orphan: false,
+            estimated_fee_rate: 1,
+        });
+    }
+
+    /// Replaces the blocks above `height`, the next mined blocks start a new branch.
+    fn reorg_above(&mut self, height: u32) {
+        let replaced = self.blocks.split_off((height - FIRST_HEIGHT + 1) as usize);
+        self.orphans
+            .extend(replaced.into_iter().map(|block| FullBlock {
+                orphan: true,
+                ..block
+            }));
+        self.branch += 1;
+    }
+
+    fn tip(&self) -> Option<FullBlock> {
+        self.blocks.last().cloned()
+    }
+
+    fn block_at(&self, height: u32) -> Option<FullBlock> {
+        let index = height.checked_sub(FIRST_HEIGHT)? as usize;
+        self.blocks.get(index).cloned()
+    }
+
+    fn block_by_hash(&self, hash: &BlockHash) -> Option<FullBlock> {
+        self.blocks
+            .iter()
+            .chain(&self.orphans)
+            .find(|block| block.hash == *hash)
+            .cloned()
+    }
+
+    /// Transaction in the active chain, or else in an orphaned block with no confirmations.
+    fn tx_info(&self, tx_id: &Txid) -> Option<TransactionInfo> {
+        let tip = self.tip()?;
+        let contains = |block: &&FullBlock| block.txs.iter().any(|tx| tx.compute_txid() == *tx_id);
+        let block = self
+            .blocks
+            .iter()
+            .find(contains)
+            .or_else(|| self.orphans.iter().rev().find(contains))?;
+        let tx = block
+            .txs
+            .iter()
+            .find(|tx| tx.compute_txid() == *tx_id)?
+            .clone();
+        let orphan = block.orphan || self.flagged_orphan.contains(tx_id);
+        let confirmations = if orphan {
+            0
+        } else {
+            tip.height - block.height + 1
+        };
+
+        Some(TransactionInfo {
+            tx,
+            block_info: FullBlock {
+                orphan,
+                ..block.clone()
+            },
+            confirmations,
+        })
+    }
+}
+
+fn new_monitor(
+    chain: Arc<Mutex<ScriptedChain>>,
+) -> Result<Monitor<MockIndexerApi, MonitorStore>, anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    new_monitor_on(chain, Arc::new(Storage::new(&config)?))
+}
+
+/// Monitor of `chain` on `storage`, restarting a monitor when the storage was already used.
+fn new_monitor_on(
+    chain: Arc<Mutex<ScriptedChain>>,
+    storage: Arc<Storage>,
+) -> Result<Monitor<MockIndexerApi, MonitorStore>, anyhow::Error> {
+    let store = MonitorStore::new(storage)?;
+
+    let mut mock_indexer = MockIndexerApi::new();
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let best_chain = chain.clone();
+    mock_indexer.expect_get_best_block().returning(move || {
+        let chain = best_chain.lock().unwrap();
+        thread::sleep(chain.latency);
+        Ok(chain.tip())
+    });
+    let by_height_chain = chain.clone();
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |height| Ok(by_height_chain.lock().unwrap().block_at(height)));
+    let by_hash_chain = chain.clone();
+    mock_indexer
+        .expect_get_block_by_hash()
+        .returning(move |hash| Ok(by_hash_chain.lock().unwrap().block_by_hash(hash)));
+    let tx_chain = chain.clone();
+    mock_indexer
+        .expect_get_tx()
+        .returning(move |tx_id| Ok(tx_chain.lock().unwrap().tx_info(tx_id)));
+
+    let mut mock_mempool = MockMempoolApi::new();
+    let spenders_chain = chain.clone();
+    mock_mempool
+        .expect_get_mempool_spenders()
+        .returning(move |_| Ok(spenders_chain.lock().unwrap().mempool.clone()));
+    mock_mempool
+        .expect_get_mempool_txs()
+        .returning(move |_| Ok(chain.lock().unwrap().mempool.clone()));
+
+    // Every branch has the same difficulty, so no reorg of the walkthrough is a low work one
+    let mut mock_headers = MockBlockHeaderApi::new();
+    mock_headers
+        .expect_get_block_bits()
+        .returning(|_| Ok(Some(CompactTarget::from_consensus(0x1d00ffff))));
+
+    let mut settings = MonitorSettings::from(MonitorSettingsConfig::default());
+    settings.max_monitoring_confirmations = 4;
+    settings.track_mempool = true;
+
+    Ok(Monitor::new(mock_indexer, store, settings)?
+        .with_mempool(Box::new(mock_mempool))
+        .with_headers(Box::new(mock_headers)))
+}
+
+fn tx_with_outputs(lock_time: u32, input: Vec<TxIn>, output: Vec<TxOut>) -> Transaction {
+    Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_consensus(lock_time),
+        input,
+        output,
+    }
+}
+
+fn op_return(data: &[u8]) -> ScriptBuf {
+    Builder::new()
+        .push_opcode(OP_RETURN)
+        .push_slice(<&PushBytes>::try_from(data).unwrap())
+        .into_script()
+}
+
+/// Taproot address of the committee whose key is made of `seed` bytes.
+fn committee_address(seed: u8) -> Address {
+    let secp = Secp256k1::new();
+    let committee_pk =
+        PublicKey::from_secret_key(&secp, &SecretKey::from_slice(&[seed; 32]).unwrap());
+    Address::p2tr(
+        &secp,
+        committee_pk.x_only_public_key().0,
+        None,
+        Network::Bitcoin,
+    )
+}
+
+/// Pegin paying to a committee taproot address, with the RSK_PEGIN OP_RETURN data.
+fn pegin_tx() -> Transaction {
+    pegin_tx_to(&committee_address(1))
+}
+
+fn pegin_tx_to(committee: &Address) -> Transaction {
+    pegin_tx_with_packet(committee, 0)
+}
+
+fn pegin_tx_with_packet(committee: &Address, packet_number: u64) -> Transaction {
+    pegin_tx_paying(committee, packet_number, Amount::from_sat(100_000_000))
+}
+
+fn pegin_tx_paying(committee: &Address, packet_number: u64, amount: Amount) -> Transaction {
+    let secp = Secp256k1::new();
+    let reimbursement_pk =
+        PublicKey::from_secret_key(&secp, &SecretKey::from_slice(&[2u8; 32]).unwrap());
+    let rootstock_address = Vec::from_hex("7ac5496aee77c1ba1f0854206a26dda82a81d6d8").unwrap();
+
+    let data = [
+        b"RSK_PEGIN".as_slice(),
+        &packet_number.to_be_bytes(),
+        &rootstock_address,
+        &reimbursement_pk.x_only_public_key().0.serialize(),
+    ]
+    .concat();
+
+    tx_with_outputs(
+        0,
+        vec![],
+        vec![
+            TxOut {
+                value: amount,
+                script_pubkey: committee.script_pubkey(),
+            },
+            TxOut {
+                value: Amount::ZERO,
+                script_pubkey: op_return(&data),
+            },
+        ],
+    )
+}
+
+/// Acknowledgment of a news, as a consumer builds it from the news it read.
+fn ack_for(news: &MonitorNews) -> AckMonitorNews {
+    match news {
+        MonitorNews::Transaction(tx_id, _, context) => {
+            AckMonitorNews::Transaction(*tx_id, context.clone())
+        }
+        MonitorNews::OrphanedTransaction(tx_id, _, context) => {
+            AckMonitorNews::OrphanedTransaction(*tx_id, context.clone())
+        }
+        MonitorNews::SpendingUTXOTransaction(tx_id, vout, _, context) => {
+            AckMonitorNews::SpendingUTXOTransaction(*tx_id, *vout, context.clone())
+        }
+        MonitorNews::SpendingUTXOUnconfirmed(tx_id, vout, _, context) => {
+            AckMonitorNews::SpendingUTXOUnconfirmed(*tx_id, *vout, context.clone())
+        }
+        MonitorNews::MinorSpend(tx_id, vout, _, context) => {
+            AckMonitorNews::MinorSpend(*tx_id, *vout, context.clone())
+        }
+        MonitorNews::RskPeginTransaction(tx_id, ..) => AckMonitorNews::RskPeginTransaction(*tx_id),
+        MonitorNews::ScriptPubKeyTransaction(tx_id, vout, _, context) => {
+            AckMonitorNews::ScriptPubKeyTransaction(*tx_id, *vout, context.clone())
+        }
+        MonitorNews::OpReturnTransaction(tx_id, _, context) => {
+            AckMonitorNews::OpReturnTransaction(*tx_id, context.clone())
+        }
+        MonitorNews::NewBlock(_, hash) => AckMonitorNews::NewBlock(Some(*hash)),
+        MonitorNews::MonitorReplaced { old, context, .. } => AckMonitorNews::MonitorReplaced {
+            old: *old,
+            context: context.clone(),
+        },
+        MonitorNews::ReplacedTransactionConfirmed { old, context, .. } => {
+            AckMonitorNews::ReplacedTransactionConfirmed {
+                old: *old,
+                context: context.clone(),
+            }
+        }
+        MonitorNews::SequenceEpochChanged { new_epoch, .. } => {
+            AckMonitorNews::SequenceEpochChanged {
+                new_epoch: *new_epoch,
+            }
+        }
+        MonitorNews::Reorg { new_tip, .. } => AckMonitorNews::Reorg { new_tip: *new_tip },
+        MonitorNews::LowWorkReorg { height, .. } => {
+            AckMonitorNews::LowWorkReorg { height: *height }
+        }
+        MonitorNews::AlternativeResolved {
+            winner, context, ..
+        } => AckMonitorNews::AlternativeResolved {
+            winner: *winner,
+            context: context.clone(),
+        },
+        MonitorNews::MonitorExpired {
+            tx_id,
+            vout,
+            context,
+            ..
+        } => AckMonitorNews::MonitorExpired {
+            tx_id: *tx_id,
+            vout: *vout,
+            context: context.clone(),
+        },
+        MonitorNews::HealthStateChanged { state, .. } => {
+            AckMonitorNews::HealthStateChanged { state: *state }
+        }
+        MonitorNews::ManuallyResolved(descriptor, _) => {
+            AckMonitorNews::ManuallyResolved(descriptor.clone())
+        }
+        MonitorNews::Composite(id) => AckMonitorNews::Composite(id.clone()),
+    }
+}
+
+/// Reads the pending news with get_news and acknowledges all of them.
+fn consume_news(
+    monitor: &Monitor<MockIndexerApi, MonitorStore>,
+) -> Result<Vec<MonitorNews>, anyhow::Error> {
+    let news = monitor.get_news()?;
+    for item in &news {
+        monitor.ack_news(ack_for(item))?;
+    }
+
+    Ok(news)
+}
+
+/// Reads the pending news in pages of `limit`, acknowledging each page before asking for the next.
+fn consume_news_pages(
+    monitor: &Monitor<MockIndexerApi, MonitorStore>,
+    limit: usize,
+) -> Result<Vec<MonitorNews>, anyhow::Error> {
+    let mut news = vec![];
+    let mut cursor = None;
+
+    loop {
+        let page = monitor.get_news_page(cursor, limit)?;
+        assert!(page.news.len() <= limit);
+
+        for envelope in page.news {
+            monitor.ack_news(ack_for(&envelope.news))?;
+            news.push(envelope.news);
+        }
+
+        match page.next {
+            Some(next) => cursor = Some(next),
+            None => return Ok(news),
+        }
+    }
+}
+
+fn has_news(news: &[MonitorNews], expected: impl Fn(&MonitorNews) -> bool) -> bool {
+    news.iter().any(expected)
+}
+
+/// Walks through the public API of the monitor over a scripted chain:
+/// 1. A monitor built with a mempool and a header source registers every kind of monitor, one of
+///    them through a handle from another thread
+/// 2. A payment waits in the mempool, is reported there and listed as stale
+/// 3. The payment, a pegin, a deposit to a watched script and a tagged OP_RETURN are mined, their
+///    news are drained in pages
+/// 4. The payment output is spent, the spender is listed with its monitor
+/// 5. A reorg moves the payment to another block, the reorg is reported with its impact
+/// 6. Statuses are queried, the script and new block monitors are cancelled and stop reporting
+/// 7. Transactions past the monitoring window are deactivated, every news is acknowledged and the
+///    store only keeps the expected monitors
+#[test]
+fn test_api_walkthrough() -> Result<(), anyhow::Error> {
+    let chain = Arc::new(Mutex::new(ScriptedChain::default()));
+    let monitor = new_monitor(chain.clone())?;
+
+    let deposit_script = Builder::new()
+        .push_slice([3u8; 33])
+        .push_opcode(OP_CHECKSIG)
+        .into_script();
+    let payment = tx_with_outputs(
+        1,
+        vec![],
+        vec![TxOut {
+            value: Amount::from_sat(50_000),
+            script_pubkey: ScriptBuf::new_op_return([1u8; 4]),
+        }],
+    );
+    let spend = tx_with_outputs(
+        2,
+        vec![TxIn {
+            previous_output: OutPoint::new(payment.compute_txid(), 0),
+            ..Default::default()
+        }],
+        vec![],
+    );
+    let deposit = tx_with_outputs(
+        3,
+        vec![],
+        vec![TxOut {
+            value: Amount::from_sat(20_000),
+            script_pubkey: deposit_script.clone(),
+        }],
+    );
+    let late_deposit = tx_with_outputs(
+        4,
+        vec![],
+        vec![TxOut {
+            value: Amount::from_sat(30_000),
+            script_pubkey: deposit_script.clone(),
+        }],
+    );
+    let tagged = tx_with_outputs(
+        5,
+        vec![],
+        vec![TxOut {
+            value: Amount::ZERO,
+            script_pubkey: op_return(b"PROTO-1"),
+        }],
+    );
+    let pegin = pegin_tx();
+    let (payment_id, spend_id) = (payment.compute_txid(), spend.compute_txid());
+
+    // 1. Every kind of monitor
+    let outcomes = monitor.monitor_batch(vec![
+        WatchTx::new(payment_id).context("payment").build()?,
+        WatchOutpoint::new(payment_id, 0)
+            .context("payment-spend")
+            .build()?,
+        WatchPegin::new().build()?,
+        TypesToMonitor::NewBlock,
+        WatchScript::new(deposit_script.clone())
+            .context("deposit")
+            .build()?,
+    ])?;
+    assert!(outcomes.iter().all(Result::is_ok));
+
+    let handle = monitor.handle();
+    let registration = thread::spawn(move || {
+        let watch = WatchOpReturn::new(b"PROTO".to_vec())
+            .context("tagged")
+            .build()?;
+        handle.submit_monitor(watch)
+    })
+    .join()
+    .expect("registration thread")?;
+
+    chain.lock().unwrap().mine(vec![]);
+    chain.lock().unwrap().mempool.push(payment.clone());
+    monitor.tick()?;
+    registration.wait(Duration::from_secs(1))?;
+    assert_eq!(monitor.get_monitors(false)?.len(), 6);
+
+    // 2. Waiting in the mempool
+    let news = consume_news(&monitor)?;
+    assert!(has_news(&news, |n| matches!(
+        n,
+        MonitorNews::Transaction(t, status, _) if *t == payment_id && status.status == TransactionBlockchainStatus::Mempool
+    )));
+    assert!(has_news(&news, |n| matches!(
+        n,
+        MonitorNews::NewBlock(200, _)
+    )));
+
+    chain.lock().unwrap().mine(vec![]);
+    monitor.tick()?;
+    consume_news(&monitor)?;
+    let stale = monitor.stale_unconfirmed(0)?;
+    assert_eq!(stale.len(), 1);
+    assert_eq!((stale[0].txid, stale[0].blocks_waiting), (payment_id, 1));
+
+    // 3. Mined and drained in pages
+    chain.lock().unwrap().mine(vec![
+        payment.clone(),
+        pegin.clone(),
+        deposit.clone(),
+        tagged.clone(),
+    ]);
+    monitor.tick()?;
+    let news = consume_news_pages(&monitor, 2)?;
+    assert_eq!(news.len(), 5);
+    assert!(has_news(&news, |n| matches!(
+        n,
+        MonitorNews::Transaction(t, status, _) if *t == payment_id && status.confirmations == 1
+    )));
+    assert!(has_news(&news, |n| matches!(
+        n,
+        MonitorNews::RskPeginTransaction(t, _, Some(data)) if *t == pegin.compute_txid() && data.amount == Amount::from_sat(100_000_000)
+    )));
+    assert!(has_news(&news, |n| matches!(
+        n,
+        MonitorNews::ScriptPubKeyTransaction(t, 0, _, c) if *t == deposit.compute_txid() && c == "deposit"
+    )));
+    assert!(has_news(&news, |n| matches!(
+        n,
+        MonitorNews::OpReturnTransaction(t, data, c) if *t == tagged.compute_txid() && data == b"PROTO-1" && c == "tagged"
+    )));
+    assert!(monitor.stale_unconfirmed(0)?.is_empty());
+
+    // 4. Spent
+    chain.lock().unwrap().mine(vec![spend.clone()]);
+    monitor.tick()?;
+    let news = consume_news(&monitor)?;
+    assert!(has_news(&news, |n| matches!(
+        n,
+        MonitorNews::SpendingUTXOTransaction(t, 0, status, c) if *t == payment_id && status.tx_id == spend_id && c == "payment-spend"
+    )));
+    let outpoint = monitor
+        .get_monitors(false)?
+        .into_iter()
+        .find(|info| {
+            matches!(
+                info.monitor,
+                TypesToMonitorStore::SpendingUTXOTransaction(..)
+            )
+        })
+        .expect("outpoint monitor");
+    assert_eq!(outpoint.spender_tx_id, Some(spend_id));
+
+    // 5. Reorg, the payment is mined one block later on the new branch
+    let old_tip = {
+        let mut chain = chain.lock().unwrap();
+        let old_tip = chain.tip().unwrap().hash;
+        chain.reorg_above(201);
+        chain.mine(vec![]);
+        chain.mine(vec![
+            payment.clone(),
+            pegin.clone(),
+            deposit.clone(),
+            tagged.clone(),
+        ]);
+        chain.mine(vec![spend.clone()]);
+        old_tip
+    };
+    monitor.tick()?;
+    let new_tip = chain.lock().unwrap().tip().unwrap().hash;
+
+    let news = consume_news(&monitor)?;
+    assert!(has_news(&news, |n| matches!(
+        n,
+        MonitorNews::Reorg { old_tip: o, new_tip: t, .. } if *o == old_tip && *t == new_tip
+    )));
+    assert!(!has_news(&news, |n| matches!(
+        n,
+        MonitorNews::LowWorkReorg { .. }
+    )));
+    let impact = monitor.last_reorg_impact()?.expect("reorg impact");
+    assert!(
+        impact
+            .moved_transactions
+            .iter()
+            .any(|moved| moved.tx_id == payment_id
+                && moved.after == Some(ScriptedChain::hash(1, 203)))
+    );
+
+    // 6. Statuses and cancellations
+    let statuses = monitor.get_tx_statuses(&[payment_id, spend_id])?;
+    let payment_status = statuses[0].1.as_ref().expect("payment status");
+    assert_eq!(payment_status.confirmations, 2);
+    assert_eq!(
+        payment_status.block_info.as_ref().map(|block| block.height),
+        Some(203)
+    );
+    assert_eq!(monitor.get_tx_status(&spend_id)?.confirmations, 1);
+
+    monitor.cancel(
+        WatchScript::new(deposit_script)
+            .context("deposit")
+            .build()?,
+    )?;
+    monitor.cancel(TypesToMonitor::NewBlock)?;
+
+    chain.lock().unwrap().mine(vec![late_deposit.clone()]);
+    monitor.tick()?;
+    let news = consume_news(&monitor)?;
+    assert!(!has_news(&news, |n| matches!(
+        n,
+        MonitorNews::ScriptPubKeyTransaction(t, ..) if *t == late_deposit.compute_txid()
+    )));
+    assert!(!has_news(&news, |n| matches!(n, MonitorNews::NewBlock(..))));
+
+    // 7. Past the monitoring window
+    for _ in 0..2 {
+        chain.lock().unwrap().mine(vec![]);
+        monitor.tick()?;
+        consume_news(&monitor)?;
+    }
+    assert_eq!(monitor.get_monitor_height()?, 207);
+    assert!(monitor.get_news()?.is_empty());
+
+    let active = monitor.get_monitors(false)?;
+    assert_eq!(active.len(), 2);
+    assert!(active.iter().all(|info| matches!(
+        info.monitor,
+        TypesToMonitorStore::RskPegin(None, ..) | TypesToMonitorStore::OpReturnPrefix(..)
+    )));
+
+    let inactive: Vec<_> = monitor
+        .get_monitors(true)?
+        .into_iter()
+        .filter(|info| !info.active)
+        .collect();
+    assert!(inactive.iter().any(|info| matches!(
+        &info.monitor,
+        TypesToMonitorStore::Transaction(t, c, None, _) if *t == payment_id && c == "payment"
+    )));
+    assert!(inactive.iter().any(|info| matches!(
+        info.monitor,
+        TypesToMonitorStore::SpendingUTXOTransaction(..)
+    ) && info.spender_tx_id == Some(spend_id)));
+
+    let state = logical_snapshot(&monitor.store)?;
+    assert_eq!(state.get("height"), Some("207"));
+    for (path, value) in state.entries() {
+        if path.starts_with("news/") {
+            let value: serde_json::Value = serde_json::from_str(value)?;
+            assert_eq!(value["acknowledged"], true, "{path} is pending");
+        }
+    }
+    assert!(!state
+        .entries()
+        .keys()
+        .any(|path| path.starts_with("monitor/script/active/")));
+    assert_eq!(state.get("monitor/new_block"), Some("false"));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that a long catch-up doesn't hold back the news of the cheap monitors:
+/// 1. 500 SpendingUTXO monitors, a NewBlock monitor and a transaction monitor, then a backlog of
+///    50 blocks with the transaction in block 208 and a spend of a watched output in block 245
+/// 2. With max_blocks_per_tick of 10, the first tick stops at block 210: the NewBlock news and
+///    the news of the transaction can be read while the later blocks are still to be scanned
+/// 3. The next ticks go through the backlog and find the spend
+#[test]
+fn test_catch_up_fairness() -> Result<(), anyhow::Error> {
+    let chain = Arc::new(Mutex::new(ScriptedChain::default()));
+    let mut monitor = new_monitor(chain.clone())?;
+    monitor.settings.max_blocks_per_tick = 10;
+
+    // 1. Monitors and backlog
+    let outpoints: Vec<OutPoint> = (0..500)
+        .map(|i| OutPoint::new(Txid::from_str(&format!("{:064x}", i + 1)).unwrap(), 0))
+        .collect();
+    let outcomes = monitor.monitor_batch(
+        outpoints
+            .iter()
+            .map(|outpoint| {
+                WatchOutpoint::new(outpoint.txid, outpoint.vout)
+                    .context("vault")
+                    .build()
+            })
+            .collect::<Result<_, _>>()?,
+    )?;
+    assert!(outcomes.iter().all(Result::is_ok));
+
+    let payment = tx_with_outputs(1, vec![], vec![]);
+    let spend = tx_with_outputs(
+        2,
+        vec![TxIn {
+            previous_output: outpoints[321],
+            ..Default::default()
+        }],
+        vec![],
+    );
+    monitor.save_monitor(TypesToMonitor::NewBlock)?;
+    monitor.save_monitor(
+        WatchTx::new(payment.compute_txid())
+            .context("payment")
+            .build()?,
+    )?;
+
+    chain.lock().unwrap().mine(vec![]);
+    monitor.tick()?;
+    assert_eq!(monitor.get_monitor_height()?, FIRST_HEIGHT);
+    consume_news(&monitor)?;
+
+    for height in FIRST_HEIGHT + 1..=FIRST_HEIGHT + 50 {
+        let txs = match height - FIRST_HEIGHT {
+            8 => vec![payment.clone()],
+            45 => vec![spend.clone()],
+            _ => vec![],
+        };
+        chain.lock().unwrap().mine(txs);
+    }
+
+    // 2. First slice of the backlog
+    monitor.tick()?;
+    assert_eq!(monitor.get_monitor_height()?, FIRST_HEIGHT + 10);
+
+    let news = consume_news(&monitor)?;
+    assert!(has_news(&news, |n| matches!(
+        n,
+        MonitorNews::NewBlock(height, _) if *height == FIRST_HEIGHT + 10
+    )));
+    assert!(has_news(&news, |n| matches!(
+        n,
+        MonitorNews::Transaction(tx_id, status, context)
+            if *tx_id == payment.compute_txid()
+                && status.confirmations == 3
+                && context == "payment"
+    )));
+    assert!(!has_news(&news, |n| matches!(
+        n,
+        MonitorNews::SpendingUTXOTransaction(..)
+    )));
+
+    // 3. Rest of the backlog
+    for _ in 0..4 {
+        monitor.tick()?;
+    }
+    assert_eq!(monitor.get_monitor_height()?, FIRST_HEIGHT + 50);
+
+    let news = consume_news(&monitor)?;
+    assert!(has_news(&news, |n| matches!(
+        n,
+        MonitorNews::SpendingUTXOTransaction(tx_id, 0, status, context)
+            if *tx_id == outpoints[321].txid
+                && status.tx_id == spend.compute_txid()
+                && context == "vault"
+    )));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that the scan set of the ScriptPubKey monitors is reused across ticks:
+/// 1. 500 ScriptPubKey monitors
+/// 2. 10 blocks, one per tick, with a payment to one of the scripts in block 205: the scan set is
+///    built once, on the first block, and the payment is found
+/// 3. Cancelling a monitor moves the scan generation, the next block builds the scan set again
+///    without the script, so a payment to it is not found
+#[test]
+fn test_scan_set_reuse() -> Result<(), anyhow::Error> {
+    let chain = Arc::new(Mutex::new(ScriptedChain::default()));
+    let monitor = new_monitor(chain.clone())?;
+
+    // 1. Monitors
+    let scripts: Vec<ScriptBuf> = (0..500)
+        .map(|i| ScriptBuf::from_hex(&format!("0014{:040x}", i + 1)).unwrap())
+        .collect();
+    let outcomes = monitor.monitor_batch(
+        scripts
+            .iter()
+            .map(|script| WatchScript::new(script.clone()).context("deposits").build())
+            .collect::<Result<_, _>>()?,
+    )?;
+    assert!(outcomes.iter().all(Result::is_ok));
+    let generation = monitor.metrics_snapshot()?.scan_generation;
+
+    // 2. Ticks
+    let payment = tx_with_outputs(
+        1,
+        vec![],
+        vec![TxOut {
+            value: Amount::from_sat(50_000),
+            script_pubkey: scripts[321].clone(),
+        }],
+    );
+    for height in FIRST_HEIGHT..FIRST_HEIGHT + 10 {
+        let txs = match height - FIRST_HEIGHT {
+            5 => vec![payment.clone()],
+            _ => vec![],
+        };
+        chain.lock().unwrap().mine(txs);
+        monitor.tick()?;
+    }
+    assert_eq!(monitor.get_monitor_height()?, FIRST_HEIGHT + 9);
+
+    let snapshot = monitor.metrics_snapshot()?;
+    assert_eq!(snapshot.scan_set_builds, 1);
+    assert_eq!(snapshot.scan_set_size, 500);
+    assert_eq!(snapshot.scan_generation, generation);
+
+    let news = consume_news(&monitor)?;
+    assert!(has_news(&news, |n| matches!(
+        n,
+        MonitorNews::ScriptPubKeyTransaction(tx_id, 0, _, context)
+            if *tx_id == payment.compute_txid() && context == "deposits"
+    )));
+
+    // 3. Cancel
+    monitor.cancel(
+        WatchScript::new(scripts[7].clone())
+            .context("deposits")
+            .build()?,
+    )?;
+    let unwatched = tx_with_outputs(
+        2,
+        vec![],
+        vec![TxOut {
+            value: Amount::from_sat(50_000),
+            script_pubkey: scripts[7].clone(),
+        }],
+    );
+    chain.lock().unwrap().mine(vec![unwatched.clone()]);
+    monitor.tick()?;
+
+    let snapshot = monitor.metrics_snapshot()?;
+    assert_eq!(snapshot.scan_set_builds, 2);
+    assert_eq!(snapshot.scan_set_size, 499);
+    assert!(snapshot.scan_generation > generation);
+    assert!(!has_news(&consume_news(&monitor)?, |n| matches!(
+        n,
+        MonitorNews::ScriptPubKeyTransaction(tx_id, ..) if *tx_id == unwatched.compute_txid()
+    )));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that a pegin monitor with a committee only reports the pegins paying to it:
+/// 1. Pegins to three committees, A, B and C
+/// 2. A monitor for committee A reports the pegin to A only
+/// 3. A monitor for committees A and B reports the pegins to both, not the one to C
+/// 4. A monitor without committee reports every pegin
+#[test]
+fn test_pegin_committee_filter() -> Result<(), anyhow::Error> {
+    let chain = Arc::new(Mutex::new(ScriptedChain::default()));
+    let monitor = new_monitor(chain.clone())?;
+
+    // 1. Pegins
+    let committees: Vec<Address> = (3..6).map(committee_address).collect();
+    let pegins: Vec<Transaction> = committees.iter().map(pegin_tx_to).collect();
+    // Pegins reported among `mined`, a pegin found earlier also gets news as it confirms
+    let pegin_news = |news: &[MonitorNews], mined: &[Transaction]| -> Vec<Txid> {
+        mined
+            .iter()
+            .map(Transaction::compute_txid)
+            .filter(|txid| {
+                has_news(
+                    news,
+                    |n| matches!(n, MonitorNews::RskPeginTransaction(tx_id, ..) if tx_id == txid),
+                )
+            })
+            .collect()
+    };
+
+    // 2. Committee A
+    monitor.save_monitor(
+        WatchPegin::new()
+            .committee(committees[0].script_pubkey())
+            .build()?,
+    )?;
+    chain.lock().unwrap().mine(pegins.clone());
+    monitor.tick()?;
+    assert_eq!(
+        pegin_news(&consume_news(&monitor)?, &pegins),
+        vec![pegins[0].compute_txid()]
+    );
+
+    // 3. Committees A and B
+    monitor.save_monitor(
+        WatchPegin::new()
+            .committee(committees[0].script_pubkey())
+            .committee(committees[1].script_pubkey())
+            .build()?,
+    )?;
+    let pegins: Vec<Transaction> = pegins
+        .into_iter()
+        .map(|pegin| Transaction {
+            lock_time: LockTime::from_consensus(1),
+            ..pegin
+        })
+        .collect();
+    chain.lock().unwrap().mine(pegins.clone());
+    monitor.tick()?;
+    assert_eq!(
+        pegin_news(&consume_news(&monitor)?, &pegins),
+        vec![pegins[0].compute_txid(), pegins[1].compute_txid()]
+    );
+
+    // 4. No committee
+    monitor.save_monitor(WatchPegin::new().build()?)?;
+    let pegins: Vec<Transaction> = pegins
+        .into_iter()
+        .map(|pegin| Transaction {
+            lock_time: LockTime::from_consensus(2),
+            ..pegin
+        })
+        .collect();
+    chain.lock().unwrap().mine(pegins.clone());
+    monitor.tick()?;
+    assert_eq!(
+        pegin_news(&consume_news(&monitor)?, &pegins),
+        pegins
+            .iter()
+            .map(Transaction::compute_txid)
+            .collect::<Vec<_>>()
+    );
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that the chain log records every block processed, with both branches of a reorg:
+/// 1. Blocks 200 to 202, one per tick
+/// 2. A reorg replacing block 202, its replacement links to block 201: a fork at 201
+/// 3. A reorg replacing the blocks above 200, the monitor jumps to the new tip, whose previous
+///    blocks were never processed: a gap
+/// 4. The log lists both blocks at 202, in processing order
+#[test]
+fn test_chain_log() -> Result<(), anyhow::Error> {
+    let chain = Arc::new(Mutex::new(ScriptedChain::default()));
+    let monitor = new_monitor(chain.clone())?;
+
+    // 1. Blocks
+    for _ in 0..3 {
+        chain.lock().unwrap().mine(vec![]);
+        monitor.tick()?;
+    }
+    let verification = monitor.verify_chain_log()?;
+    assert_eq!(verification.entries, 3);
+    assert!(verification.issues.is_empty());
+
+    // 2. Fork
+    chain.lock().unwrap().reorg_above(FIRST_HEIGHT + 1);
+    for _ in 0..2 {
+        chain.lock().unwrap().mine(vec![]);
+        monitor.tick()?;
+    }
+    let verification = monitor.verify_chain_log()?;
+    assert!(verification.is_continuous());
+    assert_eq!(
+        verification.forks().collect::<Vec<_>>(),
+        vec![&ChainLogIssue::Fork {
+            fork_height: FIRST_HEIGHT + 1,
+            fork_hash: ScriptedChain::hash(0, FIRST_HEIGHT + 1),
+            replaced_tip: ScriptedChain::hash(0, FIRST_HEIGHT + 2),
+            hash: ScriptedChain::hash(1, FIRST_HEIGHT + 2),
+        }]
+    );
+
+    // 3. Gap
+    chain.lock().unwrap().reorg_above(FIRST_HEIGHT);
+    for _ in 0..4 {
+        chain.lock().unwrap().mine(vec![]);
+    }
+    monitor.tick()?;
+    let verification = monitor.verify_chain_log()?;
+    assert_eq!(verification.entries, 6);
+    assert!(!verification.is_continuous());
+    assert_eq!(
+        verification.issues.last(),
+        Some(&ChainLogIssue::Gap {
+            height: FIRST_HEIGHT + 4,
+            hash: ScriptedChain::hash(2, FIRST_HEIGHT + 4),
+            prev_hash: ScriptedChain::hash(2, FIRST_HEIGHT + 3),
+        })
+    );
+
+    // 4. Log
+    let log = monitor.chain_log(FIRST_HEIGHT + 2, FIRST_HEIGHT + 2)?;
+    assert_eq!(
+        log.iter().map(|entry| entry.hash).collect::<Vec<_>>(),
+        vec![
+            ScriptedChain::hash(0, FIRST_HEIGHT + 2),
+            ScriptedChain::hash(1, FIRST_HEIGHT + 2)
+        ]
+    );
+    assert!(log
+        .iter()
+        .all(|entry| entry.prev_hash == ScriptedChain::hash(0, FIRST_HEIGHT + 1)));
+    assert_eq!(
+        monitor
+            .chain_log(0, u32::MAX)?
+            .iter()
+            .map(|entry| entry.height - FIRST_HEIGHT)
+            .collect::<Vec<_>>(),
+        vec![0, 1, 2, 2, 3, 4]
+    );
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that the news history keeps the news once acknowledged:
+/// 1. A transaction mined in the second block: its news is recorded at the height of that block
+/// 2. Acknowledged, the news stays in the history with the monitor height it was acknowledged at
+/// 3. Its next confirmation is written as a new entry of the history
+/// 4. With a retention of one block, the sweep prunes the entries written before the tip
+#[test]
+fn test_news_history() -> Result<(), anyhow::Error> {
+    let chain = Arc::new(Mutex::new(ScriptedChain::default()));
+    let mut monitor = new_monitor(chain.clone())?;
+    let tx = tx_with_outputs(1, vec![], vec![]);
+    let tx_id = tx.compute_txid();
+    monitor.save_monitor(TypesToMonitor::Transactions(
+        vec![tx_id],
+        "history".into(),
+        None,
+        None,
+    ))?;
+    let news = MonitoredTypes::Transaction(tx_id, "history".into());
+
+    // 1. Mined
+    chain.lock().unwrap().mine(vec![]);
+    monitor.tick()?;
+    chain.lock().unwrap().mine(vec![tx]);
+    monitor.tick()?;
+    let history = monitor.get_news_history(0, 10)?;
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].news, news);
+    assert_eq!(
+        history[0].block_hash,
+        ScriptedChain::hash(0, FIRST_HEIGHT + 1)
+    );
+    assert_eq!(history[0].height, FIRST_HEIGHT + 1);
+    assert!(history[0].created_at_unix > 0);
+    assert_eq!(history[0].acked, None);
+
+    // 2. Acknowledged
+    monitor.ack_news(AckMonitorNews::Transaction(tx_id, "history".into()))?;
+    assert!(!monitor.has_news()?);
+    let acked = monitor.get_news_history(0, 10)?[0].acked.clone().unwrap();
+    assert_eq!(acked.height, FIRST_HEIGHT + 1);
+    assert!(acked.acked_at_unix >= history[0].created_at_unix);
+
+    // 3. Next confirmation
+    chain.lock().unwrap().mine(vec![]);
+    monitor.tick()?;
+    let history = monitor.get_news_history(0, 10)?;
+    assert_eq!(
+        history
+            .iter()
+            .map(|entry| (entry.sequence, entry.height, entry.acked.is_some()))
+            .collect::<Vec<_>>(),
+        vec![(1, FIRST_HEIGHT + 1, true), (2, FIRST_HEIGHT + 2, false)]
+    );
+    assert_eq!(monitor.get_news_history(2, 1)?, vec![history[1].clone()]);
+
+    // 4. Pruned
+    monitor.settings.retention.news_history_blocks = 1;
+    monitor.settings.sweep_interval_blocks = 1;
+    chain.lock().unwrap().mine(vec![]);
+    monitor.tick()?;
+    let history = monitor.get_news_history(0, 10)?;
+    assert_eq!(history.len(), 1);
+    assert_eq!(history[0].height, FIRST_HEIGHT + 3);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that an orphan flag of the indexer the chain log disagrees with is surfaced:
+/// 1. A transaction mined in a block of the chain: its news is not disputed
+/// 2. The indexer flags its block as orphaned while the chain log still has it: the
+///    disagreement is counted in the counters, the health score and the metrics, and the orphan
+///    news written from it is the only news marked as disputed
+/// 3. The indexer agrees again: nothing else is counted
+#[test]
+fn test_orphan_flag_disagreement() -> Result<(), anyhow::Error> {
+    let chain = Arc::new(Mutex::new(ScriptedChain::default()));
+    let monitor = new_monitor(chain.clone())?;
+    let tx = tx_with_outputs(1, vec![], vec![]);
+    let tx_id = tx.compute_txid();
+    monitor.save_monitor(TypesToMonitor::Transactions(
+        vec![tx_id],
+        "disputed".into(),
+        None,
+        None,
+    ))?;
+
+    // 1. Mined
+    chain.lock().unwrap().mine(vec![]);
+    monitor.tick()?;
+    chain.lock().unwrap().mine(vec![tx]);
+    monitor.tick()?;
+    let envelopes = monitor.get_news_envelopes()?;
+    assert_eq!(envelopes.len(), 1);
+    assert!(!envelopes[0].orphan_disputed);
+    assert_eq!(monitor.counters.orphan_disagreements(), 0);
+    monitor.ack_news(AckMonitorNews::Transaction(tx_id, "disputed".into()))?;
+
+    // 2. Flagged orphan
+    chain.lock().unwrap().flagged_orphan.push(tx_id);
+    chain.lock().unwrap().mine(vec![]);
+    monitor.tick()?;
+    assert_eq!(monitor.counters.orphan_disagreements(), 1);
+    let health = monitor.health();
+    assert_eq!(health.orphan_disagreements, 1);
+    assert_eq!(
+        health.score,
+        100 - HealthSettings::default().orphan_disagreement_weight
+    );
+    assert_eq!(monitor.metrics_snapshot()?.orphan_disagreements, 1);
+
+    let envelopes = monitor.get_news_envelopes()?;
+    let disputed = envelopes
+        .iter()
+        .filter(|envelope| envelope.orphan_disputed)
+        .collect::<Vec<_>>();
+    assert_eq!(disputed.len(), 1);
+    assert!(matches!(
+        disputed[0].news,
+        MonitorNews::OrphanedTransaction(id, ..) if id == tx_id
+    ));
+
+    // 3. Agreeing again
+    chain.lock().unwrap().flagged_orphan.clear();
+    chain.lock().unwrap().mine(vec![]);
+    monitor.tick()?;
+    assert_eq!(monitor.counters.orphan_disagreements(), 1);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test the latch of a composite rule across reorgs:
+/// 1. A rule on a reveal transaction and a payment with two confirmations, the reveal is mined
+///    first: its predicate is satisfied, no news
+/// 2. A reorg drops the reveal before the payment confirms: the predicate is reset, and the
+///    payment reaching two confirmations alone sends nothing
+/// 3. The reveal is mined again: the composite news is sent once, and not again on later blocks
+/// 4. A reorg dropping the reveal after the news changes nothing
+/// 5. Canceled, the rule is gone; duplicate ids, empty rules and unknown ids are rejected
+#[test]
+fn test_composite_rule_latch() -> Result<(), anyhow::Error> {
+    let chain = Arc::new(Mutex::new(ScriptedChain::default()));
+    let monitor = new_monitor(chain.clone())?;
+    let reveal = tx_with_outputs(1, vec![], vec![]);
+    let payment = tx_with_outputs(2, vec![], vec![]);
+    monitor.register_composite(
+        "settled".to_string(),
+        vec![
+            NewsPredicate::Transaction {
+                tx_id: reveal.compute_txid(),
+                min_confirmations: 1,
+            },
+            NewsPredicate::Transaction {
+                tx_id: payment.compute_txid(),
+                min_confirmations: 2,
+            },
+        ],
+    )?;
+    let composite_news = |news: Vec<MonitorNews>| {
+        news.into_iter()
+            .filter(|news| matches!(news, MonitorNews::Composite(_)))
+            .collect::<Vec<_>>()
+    };
+
+    // 1. Reveal mined
+    chain.lock().unwrap().mine(vec![]);
+    monitor.tick()?;
+    chain.lock().unwrap().mine(vec![reveal.clone()]);
+    monitor.tick()?;
+    assert_eq!(monitor.get_composites()?[0].satisfied, vec![true, false]);
+    assert!(composite_news(consume_news(&monitor)?).is_empty());
+
+    // 2. Reveal dropped by a reorg
+    {
+        let mut chain = chain.lock().unwrap();
+        chain.reorg_above(FIRST_HEIGHT);
+        chain.mine(vec![payment.clone()]);
+    }
+    monitor.tick()?;
+    assert_eq!(monitor.get_composites()?[0].satisfied, vec![false, false]);
+    chain.lock().unwrap().mine(vec![]);
+    monitor.tick()?;
+    assert_eq!(monitor.get_composites()?[0].satisfied, vec![false, true]);
+    assert!(composite_news(consume_news(&monitor)?).is_empty());
+
+    // 3. Reveal mined again
+    chain.lock().unwrap().mine(vec![reveal.clone()]);
+    monitor.tick()?;
+    assert_eq!(
+        composite_news(consume_news(&monitor)?),
+        vec![MonitorNews::Composite("settled".to_string())]
+    );
+    let rule = monitor.get_composites()?.remove(0);
+    assert_eq!(rule.satisfied, vec![true, true]);
+    assert_eq!(rule.emitted_at, Some(FIRST_HEIGHT + 3));
+    chain.lock().unwrap().mine(vec![]);
+    monitor.tick()?;
+    assert!(composite_news(consume_news(&monitor)?).is_empty());
+
+    // 4. Reveal dropped after the news
+    {
+        let mut chain = chain.lock().unwrap();
+        chain.reorg_above(FIRST_HEIGHT + 2);
+        chain.mine(vec![]);
+        chain.mine(vec![]);
+    }
+    monitor.tick()?;
+    assert!(composite_news(consume_news(&monitor)?).is_empty());
+    assert_eq!(monitor.get_composites()?, vec![rule]);
+
+    // 5. Canceled
+    assert!(matches!(
+        monitor.register_composite("settled".to_string(), vec![]),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+    assert!(matches!(
+        monitor.register_composite(
+            "settled".to_string(),
+            vec![NewsPredicate::RskPegin {
+                tx_id: reveal.compute_txid(),
+                min_confirmations: 1,
+            }],
+        ),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+    monitor.cancel_composite("settled")?;
+    assert!(monitor.get_composites()?.is_empty());
+    assert!(matches!(
+        monitor.cancel_composite("settled"),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that the spends below the minimum spend value of an outpoint are minor:
+/// 1. Two outpoints with a minimum spend value of 100_000 sats, the second ignores minor spends
+/// 2. A consolidation of both outputs into 60_000 sats: a minor spend for the first outpoint and
+///    no news for the second, both spends are recorded in their monitors
+/// 3. A reorg replaces the consolidation with a payment of 900_000 sats spending the first
+///    outpoint: a spend news, the spender of the monitor is the payment and the reorg impact
+///    lists the changed spend
+/// 4. Measuring the monitored output, a spend of an output worth 70_000 sats is minor whatever
+///    the spender pays
+#[test]
+fn test_minor_spend() -> Result<(), anyhow::Error> {
+    let chain = Arc::new(Mutex::new(ScriptedChain::default()));
+    let monitor = new_monitor(chain.clone())?;
+
+    let output = |value: u64| TxOut {
+        value: Amount::from_sat(value),
+        script_pubkey: ScriptBuf::new_op_return([1u8; 4]),
+    };
+    let spending = |outpoints: &[OutPoint]| {
+        outpoints
+            .iter()
+            .map(|outpoint| TxIn {
+                previous_output: *outpoint,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>()
+    };
+    let funding = tx_with_outputs(
+        1,
+        vec![],
+        vec![output(40_000), output(30_000), output(70_000)],
+    );
+    let funding_id = funding.compute_txid();
+    let (first, second, third) = (
+        OutPoint::new(funding_id, 0),
+        OutPoint::new(funding_id, 1),
+        OutPoint::new(funding_id, 2),
+    );
+    let consolidation = tx_with_outputs(2, spending(&[first, second]), vec![output(60_000)]);
+    let payment = tx_with_outputs(
+        3,
+        spending(&[first, third]),
+        vec![output(900_000), output(10_000)],
+    );
+    let spender_of = |vout: u32| -> Result<Option<Txid>, anyhow::Error> {
+        Ok(monitor
+            .get_monitors(false)?
+            .into_iter()
+            .find(|info| {
+                matches!(
+                    &info.monitor,
+                    TypesToMonitorStore::SpendingUTXOTransaction(t, v, ..) if *t == funding_id && *v == vout
+                )
+            })
+            .and_then(|info| info.spender_tx_id))
+    };
+
+    // 1. Outpoints
+    let min_spend_value = Amount::from_sat(100_000);
+    for watch in [
+        WatchOutpoint::new(funding_id, 0)
+            .context("first")
+            .min_spend_value(min_spend_value),
+        WatchOutpoint::new(funding_id, 1)
+            .context("second")
+            .min_spend_value(min_spend_value)
+            .ignore_minor_spends(),
+        WatchOutpoint::new(funding_id, 2)
+            .context("third")
+            .min_spend_value(min_spend_value)
+            .measure_monitored_output(),
+    ] {
+        monitor.save_monitor(watch.build()?)?;
+    }
+    chain.lock().unwrap().mine(vec![funding]);
+    monitor.tick()?;
+    consume_news(&monitor)?;
+
+    // 2. Consolidation
+    chain.lock().unwrap().mine(vec![consolidation.clone()]);
+    monitor.tick()?;
+    let news = consume_news(&monitor)?;
+    assert_eq!(news.len(), 1);
+    assert!(matches!(
+        &news[0],
+        MonitorNews::MinorSpend(t, 0, status, c) if *t == funding_id && status.tx_id == consolidation.compute_txid() && c == "first"
+    ));
+    assert_eq!(spender_of(0)?, Some(consolidation.compute_txid()));
+    assert_eq!(spender_of(1)?, Some(consolidation.compute_txid()));
+
+    // 3. Reorg
+    chain.lock().unwrap().reorg_above(FIRST_HEIGHT);
+    chain.lock().unwrap().mine(vec![payment.clone()]);
+    monitor.tick()?;
+    let news = consume_news(&monitor)?;
+    assert!(has_news(&news, |n| matches!(
+        n,
+        MonitorNews::SpendingUTXOTransaction(t, 0, status, c) if *t == funding_id && status.tx_id == payment.compute_txid() && c == "first"
+    )));
+    assert!(!has_news(&news, |n| matches!(
+        n,
+        MonitorNews::SpendingUTXOTransaction(_, 1, ..) | MonitorNews::MinorSpend(_, 1, ..)
+    )));
+    assert_eq!(spender_of(0)?, Some(payment.compute_txid()));
+    let impact = monitor.last_reorg_impact()?.expect("reorg impact");
+    assert!(impact
+        .changed_spends
+        .iter()
+        .any(|spend| spend.utxo_index == 0
+            && spend.spender_before == consolidation.compute_txid()
+            && spend.spender_after == Some(payment.compute_txid())));
+
+    // 4. Monitored output
+    assert!(has_news(&news, |n| matches!(
+        n,
+        MonitorNews::MinorSpend(t, 2, status, c) if *t == funding_id && status.tx_id == payment.compute_txid() && c == "third"
+    )));
+    assert_eq!(spender_of(2)?, Some(payment.compute_txid()));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that a pegin monitor with a packet range only reports the pegins with a packet number in
+/// it:
+/// 1. Pegins with the packet numbers 9, 10, 15, 20 and 21, and one with a truncated OP_RETURN
+/// 2. The range 10 to 20 reports 10, 15 and 20, both bounds included, the truncated pegin is
+///    skipped
+/// 3. The range of the single value 15 only reports 15
+#[test]
+fn test_pegin_packet_range() -> Result<(), anyhow::Error> {
+    let chain = Arc::new(Mutex::new(ScriptedChain::default()));
+    let monitor = new_monitor(chain.clone())?;
+
+    // 1. Pegins
+    let committee = committee_address(1);
+    let packets = [9, 10, 15, 20, 21];
+    let pegins_at = |lock_time: u32| -> Vec<Transaction> {
+        let mut pegins: Vec<Transaction> = packets
+            .iter()
+            .map(|packet| pegin_tx_with_packet(&committee, *packet))
+            .collect();
+        let mut truncated = pegin_tx_to(&committee);
+        truncated.output[1].script_pubkey = op_return(b"RSK_PEGIN\x00\x00\x00");
+        pegins.push(truncated);
+        pegins
+            .into_iter()
+            .map(|pegin| Transaction {
+                lock_time: LockTime::from_consensus(lock_time),
+                ..pegin
+            })
+            .collect()
+    };
+    let reported_packets = |news: &[MonitorNews], mined: &[Transaction]| -> Vec<u64> {
+        news.iter()
+            .filter_map(|n| match n {
+                MonitorNews::RskPeginTransaction(tx_id, _, Some(data))
+                    if mined.iter().any(|tx| tx.compute_txid() == *tx_id) =>
+                {
+                    Some(data.packet_number)
+                }
+                _ => None,
+            })
+            .collect()
+    };
+
+    // 2. Inclusive bounds
+    monitor.save_monitor(WatchPegin::new().packet_range(10, 20).build()?)?;
+    let pegins = pegins_at(0);
+    chain.lock().unwrap().mine(pegins.clone());
+    monitor.tick()?;
+    let mut reported = reported_packets(&consume_news(&monitor)?, &pegins);
+    reported.sort();
+    assert_eq!(reported, vec![10, 15, 20]);
+
+    // 3. Single value
+    monitor.save_monitor(WatchPegin::new().packet_range(15, 15).build()?)?;
+    let pegins = pegins_at(1);
+    chain.lock().unwrap().mine(pegins.clone());
+    monitor.tick()?;
+    assert_eq!(
+        reported_packets(&consume_news(&monitor)?, &pegins),
+        vec![15]
+    );
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that a pegin monitor with a minimum amount skips the pegins paying less to the committee:
+/// 1. Pegins paying just below the minimum, exactly the minimum and above it
+/// 2. Without a minimum every pegin is reported, with the amount in its news
+/// 3. With the minimum, the pegin at exactly the minimum and the one above it are reported, the
+///    one just below is skipped
+/// 4. The minimum is kept when the monitor is deactivated and reactivated without it
+#[test]
+fn test_pegin_min_amount() -> Result<(), anyhow::Error> {
+    let chain = Arc::new(Mutex::new(ScriptedChain::default()));
+    let monitor = new_monitor(chain.clone())?;
+
+    // 1. Pegins
+    let committee = committee_address(1);
+    let min_amount = Amount::from_sat(50_000);
+    let amounts = [
+        min_amount - Amount::from_sat(1),
+        min_amount,
+        min_amount + Amount::from_sat(1),
+    ];
+    let pegins_at = |lock_time: u32| -> Vec<Transaction> {
+        amounts
+            .iter()
+            .enumerate()
+            .map(|(packet, amount)| Transaction {
+                lock_time: LockTime::from_consensus(lock_time),
+                ..pegin_tx_paying(&committee, packet as u64, *amount)
+            })
+            .collect()
+    };
+    let reported_amounts = |news: &[MonitorNews], mined: &[Transaction]| -> Vec<Amount> {
+        let mut reported: Vec<Amount> = news
+            .iter()
+            .filter_map(|n| match n {
+                MonitorNews::RskPeginTransaction(tx_id, _, Some(data))
+                    if mined.iter().any(|tx| tx.compute_txid() == *tx_id) =>
+                {
+                    Some(data.amount)
+                }
+                _ => None,
+            })
+            .collect();
+        reported.sort();
+        reported
+    };
+
+    // 2. No minimum
+    monitor.save_monitor(WatchPegin::new().build()?)?;
+    let pegins = pegins_at(0);
+    chain.lock().unwrap().mine(pegins.clone());
+    monitor.tick()?;
+    assert_eq!(
+        reported_amounts(&consume_news(&monitor)?, &pegins),
+        amounts.to_vec()
+    );
+
+    // 3. Minimum
+    monitor.save_monitor(WatchPegin::new().min_amount(min_amount).build()?)?;
+    let pegins = pegins_at(1);
+    chain.lock().unwrap().mine(pegins.clone());
+    monitor.tick()?;
+    assert_eq!(
+        reported_amounts(&consume_news(&monitor)?, &pegins),
+        vec![min_amount, min_amount + Amount::from_sat(1)]
+    );
+
+    // 4. Deactivation
+    monitor
+        .store
+        .deactivate_monitor(WatchPegin::new().build()?)?;
+    monitor.reactivate(WatchPegin::new().build()?)?;
+    assert!(monitor
+        .store
+        .get_monitors()?
+        .contains(&TypesToMonitorStore::RskPegin(
+            None,
+            vec![],
+            None,
+            Some(min_amount)
+        )));
+    let pegins = pegins_at(2);
+    chain.lock().unwrap().mine(pegins.clone());
+    monitor.tick()?;
+    assert_eq!(
+        reported_amounts(&consume_news(&monitor)?, &pegins),
+        vec![min_amount, min_amount + Amount::from_sat(1)]
+    );
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that each new block gets its own news until it is acknowledged:
+/// 1. Two ticks advance two blocks with no ack in between: both blocks are delivered, oldest first
+/// 2. Acknowledging the second block by its hash keeps the first one pending
+/// 3. Acknowledging without a hash acknowledges the oldest pending block
+/// 4. A tick without a new block queues no news
+#[test]
+fn test_new_block_news_queue() -> Result<(), anyhow::Error> {
+    let chain = Arc::new(Mutex::new(ScriptedChain::default()));
+    let monitor = new_monitor(chain.clone())?;
+    monitor.save_monitor(TypesToMonitor::NewBlock)?;
+
+    // 1. Two blocks
+    let first = ScriptedChain::hash(0, FIRST_HEIGHT);
+    let second = ScriptedChain::hash(0, FIRST_HEIGHT + 1);
+    chain.lock().unwrap().mine(vec![]);
+    monitor.tick()?;
+    chain.lock().unwrap().mine(vec![]);
+    monitor.tick()?;
+    assert_eq!(
+        monitor.get_news()?,
+        vec![
+            MonitorNews::NewBlock(FIRST_HEIGHT, first),
+            MonitorNews::NewBlock(FIRST_HEIGHT + 1, second),
+        ]
+    );
+
+    // 2. Ack by hash
+    monitor.ack_news(AckMonitorNews::NewBlock(Some(second)))?;
+    assert_eq!(
+        monitor.get_news()?,
+        vec![MonitorNews::NewBlock(FIRST_HEIGHT, first)]
+    );
+
+    // 3. Ack of the oldest
+    monitor.ack_news(AckMonitorNews::NewBlock(None))?;
+    assert_eq!(monitor.get_news()?, vec![]);
+
+    // 4. No new block
+    monitor.tick()?;
+    assert_eq!(monitor.get_news()?, vec![]);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that a new block news reports the block it was recorded for, not the current tip:
+/// 1. A tick records the news of the first block
+/// 2. The tip advances without a tick: the news still reports the first block, and is read
+///    without calling the indexer
+/// 3. The next tick records the news of the new tip, next to the first one
+#[test]
+fn test_new_block_news_keeps_its_block() -> Result<(), anyhow::Error> {
+    let chain = Arc::new(Mutex::new(ScriptedChain::default()));
+    let monitor = new_monitor(chain.clone())?;
+    monitor.save_monitor(TypesToMonitor::NewBlock)?;
+
+    // 1. First block
+    let first = MonitorNews::NewBlock(FIRST_HEIGHT, ScriptedChain::hash(0, FIRST_HEIGHT));
+    chain.lock().unwrap().mine(vec![]);
+    monitor.tick()?;
+
+    // 2. Tip advanced
+    chain.lock().unwrap().mine(vec![]);
+    let indexer_calls = monitor.counters.indexer_calls();
+    assert_eq!(monitor.get_news()?, vec![first.clone()]);
+    assert_eq!(monitor.counters.indexer_calls(), indexer_calls);
+
+    // 3. Next tick
+    monitor.tick()?;
+    assert_eq!(
+        monitor.get_news()?,
+        vec![
+            first,
+            MonitorNews::NewBlock(FIRST_HEIGHT + 1, ScriptedChain::hash(0, FIRST_HEIGHT + 1)),
+        ]
+    );
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that a reorg onto a longer branch is reported when no monitored transaction moved:
+/// 1. The monitor processes two blocks, no reorg
+/// 2. The second one is replaced by a branch of two blocks: the reorg is reported from the
+///    replaced tip to the tip of the new branch
+#[test]
+fn test_reorg_onto_longer_branch() -> Result<(), anyhow::Error> {
+    let chain = Arc::new(Mutex::new(ScriptedChain::default()));
+    let monitor = new_monitor(chain.clone())?;
+
+    // 1. Two blocks
+    for _ in 0..2 {
+        chain.lock().unwrap().mine(vec![]);
+        monitor.tick()?;
+    }
+    assert!(monitor.last_reorg_impact()?.is_none());
+
+    // 2. Longer branch
+    {
+        let mut chain = chain.lock().unwrap();
+        chain.reorg_above(FIRST_HEIGHT);
+        chain.mine(vec![]);
+        chain.mine(vec![]);
+    }
+    monitor.tick()?;
+
+    let old_tip = ScriptedChain::hash(0, FIRST_HEIGHT + 1);
+    let new_tip = ScriptedChain::hash(1, FIRST_HEIGHT + 2);
+    let news = monitor.get_news()?;
+    assert!(has_news(&news, |n| matches!(
+        n,
+        MonitorNews::Reorg { old_tip: o, new_tip: t, .. } if *o == old_tip && *t == new_tip
+    )));
+    let impact = monitor.last_reorg_impact()?.expect("reorg impact");
+    assert_eq!(
+        (impact.old_tip_height, impact.new_tip_height),
+        (FIRST_HEIGHT + 1, FIRST_HEIGHT + 2)
+    );
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that the progress of a context is kept from the monitors and their history:
+/// 1. Five transactions monitored in a context and one in another
+/// 2. Two of them are mined: both are detected, none finalized
+/// 3. Blocks are mined until the first one is finalized: a ratio of one out of five
+/// 4. A monitor restarted on the same storage reads the same progress, for every context
+#[test]
+fn test_context_progress() -> Result<(), anyhow::Error> {
+    let chain = Arc::new(Mutex::new(ScriptedChain::default()));
+    let path = format!("test_outputs/{}", generate_random_string());
+    let storage = Arc::new(Storage::new(&StorageConfig::new(path, None))?);
+    let monitor = new_monitor_on(chain.clone(), storage.clone())?;
+
+    // 1. Monitors
+    let txs: Vec<Transaction> = (0..5)
+        .map(|lock_time| tx_with_outputs(lock_time, vec![], vec![]))
+        .collect();
+    for tx in &txs {
+        monitor.save_monitor(
+            WatchTx::new(tx.compute_txid())
+                .context("protocol-1")
+                .build()?,
+        )?;
+    }
+    let other = tx_with_outputs(5, vec![], vec![]);
+    monitor.save_monitor(
+        WatchTx::new(other.compute_txid())
+            .context("protocol-2")
+            .build()?,
+    )?;
+    assert_eq!(
+        monitor.context_progress("protocol-1")?,
+        ContextProgress {
+            context: "protocol-1".into(),
+            registered: 5,
+            ..Default::default()
+        }
+    );
+
+    // 2. Detected
+    for tx in &txs[..2] {
+        chain.lock().unwrap().mine(vec![tx.clone()]);
+        monitor.tick()?;
+    }
+    let progress = monitor.context_progress("protocol-1")?;
+    assert_eq!((progress.detected, progress.finalized), (2, 0));
+    assert_eq!(progress.ratio, 0.0);
+
+    // 3. Finalized
+    for _ in 0..5 {
+        if monitor.context_progress("protocol-1")?.finalized > 0 {
+            break;
+        }
+        chain.lock().unwrap().mine(vec![]);
+        monitor.tick()?;
+    }
+    let progress = monitor.context_progress("protocol-1")?;
+    assert_eq!(
+        progress,
+        ContextProgress {
+            context: "protocol-1".into(),
+            registered: 5,
+            detected: 2,
+            finalized: 1,
+            orphaned: 0,
+            expired: 0,
+            ratio: 0.2,
+        }
+    );
+
+    // 4. Restart
+    drop(monitor);
+    let monitor = new_monitor_on(chain, storage)?;
+    assert_eq!(monitor.context_progress("protocol-1")?, progress);
+    let all = monitor.all_context_progress()?;
+    assert_eq!(
+        all.iter().map(|p| p.context.as_str()).collect::<Vec<_>>(),
+        vec!["protocol-1", "protocol-2"]
+    );
+    assert_eq!(all[0], progress);
+    assert_eq!(all[1].registered, 1);
+    assert_eq!(monitor.context_progress("unknown")?.registered, 0);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that the first alternative to reach the confirmation threshold wins the race:
+/// 1. A happy path and a dispute transaction are registered as alternatives
+/// 2. The happy path reaches the threshold: an AlternativeResolved news and the monitor of the
+///    dispute is moved to the trash as lost
+/// 3. A reorg drops the happy path before it is finalized: the race opens again and both
+///    transactions are monitored
+/// 4. The dispute reaches the threshold on the new branch and wins
+/// 5. The race ends once the winner reaches `max_monitoring_confirmations`
+#[test]
+fn test_alternatives() -> Result<(), anyhow::Error> {
+    let chain = Arc::new(Mutex::new(ScriptedChain::default()));
+    let mut monitor = new_monitor(chain.clone())?;
+    monitor.settings.confirmation_threshold = 2;
+    monitor.settings.max_monitoring_confirmations = 6;
+
+    // 1. Alternatives
+    let happy_path = tx_with_outputs(0, vec![], vec![]);
+    let dispute = tx_with_outputs(1, vec![], vec![]);
+    let (happy_path_id, dispute_id) = (happy_path.compute_txid(), dispute.compute_txid());
+    monitor.save_monitor(
+        WatchAlternatives::new([happy_path_id, dispute_id])
+            .context("step-3")
+            .build()?,
+    )?;
+    let monitored = |monitor: &Monitor<MockIndexerApi, MonitorStore>| -> Vec<Txid> {
+        monitor
+            .store
+            .get_monitors()
+            .unwrap()
+            .into_iter()
+            .filter_map(|m| match m {
+                TypesToMonitorStore::Transaction(tx_id, ..) => Some(tx_id),
+                _ => None,
+            })
+            .collect()
+    };
+    assert_eq!(monitored(&monitor), vec![happy_path_id, dispute_id]);
+    chain.lock().unwrap().mine(vec![]);
+    monitor.tick()?;
+
+    // 2. Resolved
+    chain.lock().unwrap().mine(vec![happy_path]);
+    monitor.tick()?;
+    assert!(!has_news(&consume_news(&monitor)?, |n| matches!(
+        n,
+        MonitorNews::AlternativeResolved { .. }
+    )));
+
+    chain.lock().unwrap().mine(vec![]);
+    monitor.tick()?;
+    let news = consume_news(&monitor)?;
+    assert!(has_news(&news, |n| *n
+        == MonitorNews::AlternativeResolved {
+            winner: happy_path_id,
+            losers: vec![dispute_id],
+            context: "step-3".into(),
+        }));
+    assert_eq!(monitored(&monitor), vec![happy_path_id]);
+    let trash = monitor.list_trash()?;
+    assert_eq!(trash.len(), 1);
+    assert_eq!(trash[0].reason, CancelReason::AlternativeLost);
+    assert_eq!(trash[0].monitors.transactions[0].1.tx_id, dispute_id);
+
+    // 3. Reopened
+    chain.lock().unwrap().reorg_above(FIRST_HEIGHT);
+    chain.lock().unwrap().mine(vec![]);
+    chain.lock().unwrap().mine(vec![]);
+    monitor.tick()?;
+    assert!(!has_news(&consume_news(&monitor)?, |n| matches!(
+        n,
+        MonitorNews::AlternativeResolved { .. }
+    )));
+    let mut reopened = monitored(&monitor);
+    reopened.sort();
+    let mut both = vec![happy_path_id, dispute_id];
+    both.sort();
+    assert_eq!(reopened, both);
+    assert_eq!(monitor.store.get_alternatives()?[0].winner, None);
+
+    // 4. Resolved again
+    chain.lock().unwrap().mine(vec![dispute]);
+    monitor.tick()?;
+    chain.lock().unwrap().mine(vec![]);
+    monitor.tick()?;
+    let news = consume_news(&monitor)?;
+    assert!(has_news(&news, |n| *n
+        == MonitorNews::AlternativeResolved {
+            winner: dispute_id,
+            losers: vec![happy_path_id],
+            context: "step-3".into(),
+        }));
+    assert_eq!(monitored(&monitor), vec![dispute_id]);
+    assert_eq!(monitor.list_trash()?.len(), 2);
+
+    // 5. Finalized
+    for _ in 0..4 {
+        chain.lock().unwrap().mine(vec![]);
+        monitor.tick()?;
+    }
+    assert!(monitor.store.get_alternatives()?.is_empty());
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that the metadata of a monitor is kept with it and returned with its news:
+/// 1. Metadata with too many entries is rejected, the one given is listed with the monitor
+/// 2. The detection is sent with the metadata in its envelope, the news itself is unchanged
+/// 3. A monitor restarted on the same storage returns the same metadata
+/// 4. Registering again without metadata keeps it, the news is acknowledged as before
+#[test]
+fn test_monitor_metadata() -> Result<(), anyhow::Error> {
+    let chain = Arc::new(Mutex::new(ScriptedChain::default()));
+    let path = format!("test_outputs/{}", generate_random_string());
+    let storage = Arc::new(Storage::new(&StorageConfig::new(path, None))?);
+    let monitor = new_monitor_on(chain.clone(), storage.clone())?;
+
+    // 1. Registration
+    let tx = tx_with_outputs(0, vec![], vec![]);
+    let tx_id = tx.compute_txid();
+    let too_many: BTreeMap<String, String> = (0..17)
+        .map(|i| (format!("key-{i}"), "value".to_string()))
+        .collect();
+    assert!(matches!(
+        monitor.save_monitor_with(
+            WatchTx::new(tx_id).context("pegout").build()?,
+            RegistrationOptions {
+                metadata: Some(too_many),
+                ..Default::default()
+            },
+        ),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+    assert!(monitor.get_monitors(false)?.is_empty());
+
+    let metadata = BTreeMap::from([
+        ("protocol".to_string(), "pegout-42".to_string()),
+        ("step".to_string(), "3".to_string()),
+    ]);
+    monitor.save_monitor_with(
+        WatchTx::new(tx_id).context("pegout").build()?,
+        RegistrationOptions {
+            metadata: Some(metadata.clone()),
+            ..Default::default()
+        },
+    )?;
+    let other = tx_with_outputs(1, vec![], vec![]);
+    monitor.save_monitor(
+        WatchTx::new(other.compute_txid())
+            .context("other")
+            .build()?,
+    )?;
+    let listed: Vec<_> = monitor
+        .get_monitors(false)?
+        .into_iter()
+        .map(|info| info.metadata)
+        .collect();
+    assert_eq!(listed, vec![metadata.clone(), BTreeMap::new()]);
+
+    // 2. Detection
+    chain.lock().unwrap().mine(vec![tx, other]);
+    monitor.tick()?;
+    let envelopes = monitor.get_news_envelopes()?;
+    let detected: Vec<_> = envelopes
+        .iter()
+        .map(|envelope| (envelope.news.clone(), envelope.metadata.clone()))
+        .collect();
+    assert_eq!(detected.len(), 2);
+    assert!(matches!(
+        &detected[0],
+        (MonitorNews::Transaction(id, _, context), news_metadata)
+            if *id == tx_id && context == "pegout" && *news_metadata == metadata
+    ));
+    assert!(detected[1].1.is_empty());
+
+    // 3. Restart
+    let monitor = new_monitor_on(chain.clone(), storage)?;
+    assert_eq!(monitor.get_news_envelopes()?, envelopes);
+    assert_eq!(monitor.get_monitors(false)?[0].metadata, metadata);
+
+    // 4. Registered again, acknowledged
+    monitor.save_monitor(WatchTx::new(tx_id).context("pegout").build()?)?;
+    assert_eq!(monitor.get_monitors(false)?[0].metadata, metadata);
+    monitor.ack_news(AckMonitorNews::Transaction(tx_id, "pegout".into()))?;
+    let pending: Vec<_> = monitor
+        .get_news_envelopes()?
+        .into_iter()
+        .map(|envelope| envelope.metadata)
+        .collect();
+    assert_eq!(pending, vec![BTreeMap::new()]);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that slow ticks degrade the monitor and fast ticks bring it back:
+/// 1. A fast tick detects a monitored transaction, its news is resolved by the indexer
+/// 2. A slow tick takes the p95 tick duration above the slow tick duration: the monitor is
+///    degraded and sends a news, the transaction news is resolved from its snapshot
+/// 3. Degraded ticks process one block each
+/// 4. Fast ticks push the slow one out of the window: the monitor recovers, catches up and
+///    resolves news from the indexer again
+/// 5. The news of the last state change is acknowledged
+#[test]
+fn test_health_state() -> Result<(), anyhow::Error> {
+    let chain = Arc::new(Mutex::new(ScriptedChain::default()));
+    let mut monitor = new_monitor(chain.clone())?;
+    monitor.settings.max_monitoring_confirmations = 100;
+    monitor.settings.news_resolution = Resolution::Fresh;
+    monitor.settings.health = HealthSettings {
+        window_ticks: 2,
+        slow_tick_ms: 50,
+        slow_tick_weight: 60,
+        degraded_max_blocks_per_tick: 1,
+        ..Default::default()
+    };
+    let health_news = |monitor: &Monitor<MockIndexerApi, MonitorStore>| -> Vec<MonitorNews> {
+        monitor
+            .get_news()
+            .unwrap()
+            .into_iter()
+            .filter(|news| matches!(news, MonitorNews::HealthStateChanged { .. }))
+            .collect()
+    };
+    let tx_resolution = |monitor: &Monitor<MockIndexerApi, MonitorStore>| -> Resolution {
+        monitor
+            .get_news_envelopes()
+            .unwrap()
+            .into_iter()
+            .find(|envelope| matches!(envelope.news, MonitorNews::Transaction(..)))
+            .unwrap()
+            .resolution
+    };
+
+    // 1. Fast tick
+    let tx = tx_with_outputs(0, vec![], vec![]);
+    monitor.save_monitor(WatchTx::new(tx.compute_txid()).build()?)?;
+    chain.lock().unwrap().mine(vec![tx]);
+    monitor.tick()?;
+    assert_eq!(monitor.health().score, 100);
+    assert_eq!(tx_resolution(&monitor), Resolution::Fresh);
+
+    // 2. Slow tick
+    chain.lock().unwrap().latency = Duration::from_millis(100);
+    chain.lock().unwrap().mine(vec![]);
+    monitor.tick()?;
+    let health = monitor.health();
+    assert_eq!(health.state, HealthState::Degraded);
+    assert_eq!(health.score, 40);
+    assert!(health.tick_duration_p95 >= Duration::from_millis(100));
+    assert_eq!(
+        health_news(&monitor),
+        vec![MonitorNews::HealthStateChanged {
+            state: HealthState::Degraded,
+            score: 40
+        }]
+    );
+    assert_eq!(tx_resolution(&monitor), Resolution::Snapshot);
+    assert_eq!(monitor.metrics_snapshot()?.health, health);
+
+    // 3. Reduced budget
+    for _ in 0..3 {
+        chain.lock().unwrap().mine(vec![]);
+    }
+    monitor.tick()?;
+    assert_eq!(monitor.get_monitor_height()?, FIRST_HEIGHT + 2);
+
+    // 4. Recovered
+    chain.lock().unwrap().latency = Duration::ZERO;
+    monitor.tick()?;
+    assert_eq!(monitor.health().state, HealthState::Degraded);
+    monitor.tick()?;
+    assert_eq!(monitor.health().state, HealthState::Healthy);
+    assert_eq!(
+        health_news(&monitor),
+        vec![MonitorNews::HealthStateChanged {
+            state: HealthState::Healthy,
+            score: 100
+        }]
+    );
+    monitor.tick()?;
+    assert_eq!(monitor.get_monitor_height()?, FIRST_HEIGHT + 4);
+    assert_eq!(tx_resolution(&monitor), Resolution::Fresh);
+
+    // 5. Acknowledged
+    monitor.ack_news(AckMonitorNews::HealthStateChanged {
+        state: HealthState::Healthy,
+    })?;
+    assert!(health_news(&monitor).is_empty());
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that a monitored transaction whose block is orphaned gets a dedicated news:
+/// 1. The transaction is mined and reported at its trigger, the news is acknowledged
+/// 2. A reorg orphans its block: an orphaned news with the orphan status next to the reorg news,
+///    sent only once
+/// 3. Mined again in the new chain: a transaction news follows once the trigger is reached again
+#[test]
+fn test_orphaned_transaction() -> Result<(), anyhow::Error> {
+    let chain = Arc::new(Mutex::new(ScriptedChain::default()));
+    let monitor = new_monitor(chain.clone())?;
+    let context = "payout".to_string();
+
+    // 1. Reported
+    let tx = tx_with_outputs(0, vec![], vec![]);
+    let tx_id = tx.compute_txid();
+    monitor.save_monitor(
+        WatchTx::new(tx_id)
+            .context(&context)
+            .trigger_at(2)
+            .build()?,
+    )?;
+    chain.lock().unwrap().mine(vec![]);
+    chain.lock().unwrap().mine(vec![tx.clone()]);
+    chain.lock().unwrap().mine(vec![]);
+    monitor.tick()?;
+    assert!(matches!(
+        &monitor.get_news()?[..],
+        [MonitorNews::Transaction(id, status, _)] if *id == tx_id && status.confirmations == 2
+    ));
+    monitor.ack_news(AckMonitorNews::Transaction(tx_id, context.clone().into()))?;
+
+    // 2. Orphaned
+    {
+        let mut chain = chain.lock().unwrap();
+        chain.reorg_above(FIRST_HEIGHT);
+        chain.mine(vec![]);
+        chain.mine(vec![]);
+    }
+    monitor.tick()?;
+    let news = monitor.get_news()?;
+    assert!(matches!(
+        &news[..],
+        [MonitorNews::OrphanedTransaction(id, status, ctx), MonitorNews::Reorg { .. }]
+            if *id == tx_id
+                && *ctx == context
+                && status.status == TransactionBlockchainStatus::Orphan
+    ));
+    for news in &news {
+        monitor.ack_news(ack_for(news))?;
+    }
+    monitor.tick()?;
+    assert!(monitor.get_news()?.is_empty());
+
+    // 3. Confirmed again
+    chain.lock().unwrap().mine(vec![tx]);
+    monitor.tick()?;
+    assert!(monitor.get_news()?.is_empty());
+
+    chain.lock().unwrap().mine(vec![]);
+    monitor.tick()?;
+    assert!(matches!(
+        &monitor.get_news()?[..],
+        [MonitorNews::Transaction(id, status, _)]
+            if *id == tx_id
+                && status.confirmations == 2
+                && status.status != TransactionBlockchainStatus::Orphan
+    ));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test the orphan scenario with the blocks applied by the caller instead of a tick:
+/// 1. Ticking is refused, the blocks are applied one by one and the transaction is reported at its
+///    trigger, from the monitor's own snapshots
+/// 2. Applying the tip again does nothing, a block that doesn't connect is refused
+/// 3. A reorg orphans its block: an orphaned news next to the reorg news, both in the report
+/// 4. Mined again in the new chain: a transaction news once the trigger is reached again, and the
+///    indexer was never asked anything
+#[test]
+fn test_apply_blocks() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let storage = Arc::new(Storage::new(&StorageConfig::new(path, None))?);
+    let mut settings = MonitorSettings::from(MonitorSettingsConfig::default());
+    settings.max_monitoring_confirmations = 4;
+    settings.block_source = BlockSource::External;
+    let monitor = Monitor::new(NoIndexer, MonitorStore::new(storage)?, settings)?;
+    let mut chain = ScriptedChain::default();
+    let context = "payout".to_string();
+
+    // 1. Reported
+    assert!(matches!(
+        monitor.tick(),
+        Err(MonitorError::BlockSourceMismatch(BlockSource::External))
+    ));
+    let tx = tx_with_outputs(0, vec![], vec![]);
+    let tx_id = tx.compute_txid();
+    monitor.save_monitor(
+        WatchTx::new(tx_id)
+            .context(&context)
+            .trigger_at(2)
+            .build()?,
+    )?;
+    chain.mine(vec![]);
+    chain.mine(vec![tx.clone()]);
+    chain.mine(vec![]);
+    for height in FIRST_HEIGHT..FIRST_HEIGHT + 3 {
+        monitor.apply_block(&chain.block_at(height).unwrap())?;
+    }
+    assert!(matches!(
+        &monitor.get_news()?[..],
+        [MonitorNews::Transaction(id, status, _)] if *id == tx_id && status.confirmations == 2
+    ));
+    monitor.ack_news(AckMonitorNews::Transaction(tx_id, context.clone().into()))?;
+    assert_eq!(monitor.get_tx_status(&tx_id)?.confirmations, 2);
+
+    // 2. Already applied and disconnected blocks
+    let report = monitor.apply_block(&chain.tip().unwrap())?;
+    assert_eq!(report.news_emitted, 0);
+    assert!(report.reorg.is_none());
+    let disconnected = FullBlock {
+        height: FIRST_HEIGHT + 4,
+        hash: ScriptedChain::hash(9, FIRST_HEIGHT + 4),
+        prev_hash: ScriptedChain::hash(9, FIRST_HEIGHT + 3),
+        txs: vec![],
+        orphan: false,
+        estimated_fee_rate: 1,
+    };
+    assert!(matches!(
+        monitor.apply_block(&disconnected),
+        Err(MonitorError::ExternalChainMismatch(hash)) if hash == disconnected.hash
+    ));
+
+    // 3. Orphaned
+    let orphaned = [
+        ScriptedChain::hash(0, FIRST_HEIGHT + 1),
+        ScriptedChain::hash(0, FIRST_HEIGHT + 2),
+    ];
+    chain.reorg_above(FIRST_HEIGHT);
+    chain.mine(vec![]);
+    let report = monitor.apply_reorg(&orphaned, &chain.tip().unwrap())?;
+    assert!(report.reorg.is_some());
+    let news = monitor.get_news()?;
+    assert_eq!(report.news_emitted, news.len() as u64);
+    assert!(matches!(
+        &news[..],
+        [MonitorNews::OrphanedTransaction(id, status, ctx), MonitorNews::Reorg { .. }]
+            if *id == tx_id
+                && *ctx == context
+                && status.status == TransactionBlockchainStatus::Orphan
+    ));
+    for news in &news {
+        monitor.ack_news(ack_for(news))?;
+    }
+    assert_eq!(
+        monitor.get_tx_status(&tx_id)?.status,
+        TransactionBlockchainStatus::Orphan
+    );
+
+    // 4. Confirmed again
+    chain.mine(vec![tx]);
+    monitor.apply_block(&chain.tip().unwrap())?;
+    assert!(monitor.get_news()?.is_empty());
+
+    chain.mine(vec![]);
+    monitor.apply_block(&chain.tip().unwrap())?;
+    assert!(matches!(
+        &monitor.get_news()?[..],
+        [MonitorNews::Transaction(id, status, _)]
+            if *id == tx_id
+                && status.confirmations == 2
+                && status.status != TransactionBlockchainStatus::Orphan
+    ));
+    assert_eq!(monitor.counters.indexer_calls(), 0);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test resolving monitors by hand, with the blocks applied by the caller:
+/// 1. Resolved before detection: the monitor goes to the trash and a resolution news is sent
+/// 2. Both transactions are mined: only the one still monitored is reported
+/// 3. Resolved after detection: its pending news is acknowledged with the note, a resolution
+///    news is sent instead
+/// 4. Later blocks send no news for either, resolving again is refused
+#[test]
+fn test_resolve_manually() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let storage = Arc::new(Storage::new(&StorageConfig::new(path, None))?);
+    let mut settings = MonitorSettings::from(MonitorSettingsConfig::default());
+    settings.block_source = BlockSource::External;
+    let monitor = Monitor::new(NoIndexer, MonitorStore::new(storage.clone())?, settings)?;
+    let store = MonitorStore::new(storage)?;
+    let mut chain = ScriptedChain::default();
+    let context = "payout".to_string();
+    let before = tx_with_outputs(0, vec![], vec![]);
+    let after = tx_with_outputs(1, vec![], vec![]);
+    let watch = |tx: &Transaction| {
+        TypesToMonitor::Transactions(vec![tx.compute_txid()], context.clone().into(), None, None)
+    };
+    monitor.save_monitor(watch(&before))?;
+    monitor.save_monitor(watch(&after))?;
+    chain.mine(vec![]);
+    monitor.apply_block(&chain.tip().unwrap())?;
+
+    // 1. Before detection
+    monitor.resolve_manually(watch(&before), "seen on a block explorer".to_string())?;
+    let descriptor = DetectionDescriptor {
+        monitor: SnoozeTarget::Transaction(before.compute_txid(), context.clone().into()),
+        tx_id: None,
+    };
+    let news = monitor.get_news()?;
+    assert_eq!(
+        news,
+        vec![MonitorNews::ManuallyResolved(
+            descriptor,
+            "seen on a block explorer".to_string()
+        )]
+    );
+    monitor.ack_news(ack_for(&news[0]))?;
+    let trash = monitor.list_trash()?;
+    assert_eq!(trash.len(), 1);
+    assert_eq!(trash[0].reason, CancelReason::ManuallyResolved);
+
+    // 2. Mined
+    chain.mine(vec![before.clone(), after.clone()]);
+    monitor.apply_block(&chain.tip().unwrap())?;
+    assert!(matches!(
+        &monitor.get_news()?[..],
+        [MonitorNews::Transaction(id, ..)] if *id == after.compute_txid()
+    ));
+
+    // 3. After detection
+    monitor.resolve_manually(watch(&after), "paid out of band".to_string())?;
+    assert!(matches!(
+        &monitor.get_news()?[..],
+        [MonitorNews::ManuallyResolved(descriptor, note)]
+            if descriptor.monitor
+                == SnoozeTarget::Transaction(after.compute_txid(), context.clone().into())
+                && note == "paid out of band"
+    ));
+    let acked = store
+        .get_news_acks()?
+        .into_iter()
+        .find(|(news, _)| {
+            AckMonitorNews::Transaction(after.compute_txid(), context.clone().into())
+                .acknowledges(news)
+        })
+        .map(|(_, ack)| ack)
+        .unwrap();
+    assert!(acked.acknowledged);
+    assert_eq!(
+        acked.metadata,
+        Some(AckMetadata {
+            actor: AckActor::ManualResolution,
+            note: "paid out of band".to_string(),
+        })
+    );
+    for news in &monitor.get_news()? {
+        monitor.ack_news(ack_for(news))?;
+    }
+
+    // 4. Not resurrected
+    for _ in 0..3 {
+        chain.mine(vec![]);
+        monitor.apply_block(&chain.tip().unwrap())?;
+    }
+    assert!(monitor.get_news()?.is_empty());
+    assert!(monitor.get_monitors(true)?.is_empty());
+    assert!(matches!(
+        monitor.resolve_manually(watch(&after), "again".to_string()),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that the monitored outpoints spent by a transaction are found from its txid:
+/// 1. Three monitored outpoints, a consolidation spends the first two: both are listed for it,
+///    none for an unknown transaction
+/// 2. A reorg replaces the consolidation with a payment spending the first and the third: the
+///    first moves to the payment, the second is still the consolidation's
+/// 3. Canceling the monitor of the second drops it from the index, restoring it from the trash
+///    brings it back
+#[test]
+fn test_outpoints_spent_by() -> Result<(), anyhow::Error> {
+    let chain = Arc::new(Mutex::new(ScriptedChain::default()));
+    let monitor = new_monitor(chain.clone())?;
+
+    let output = TxOut {
+        value: Amount::from_sat(50_000),
+        script_pubkey: ScriptBuf::new_op_return([1u8; 4]),
+    };
+    let spending = |outpoints: &[OutPoint]| {
+        outpoints
+            .iter()
+            .map(|outpoint| TxIn {
+                previous_output: *outpoint,
+                ..Default::default()
+            })
+            .collect::<Vec<_>>()
+    };
+    let funding = tx_with_outputs(1, vec![], vec![output.clone(); 3]);
+    let funding_id = funding.compute_txid();
+    let (first, second, third) = (
+        OutPoint::new(funding_id, 0),
+        OutPoint::new(funding_id, 1),
+        OutPoint::new(funding_id, 2),
+    );
+    let consolidation = tx_with_outputs(2, spending(&[first, second]), vec![output.clone()]);
+    let payment = tx_with_outputs(3, spending(&[first, third]), vec![output]);
+
+    // 1. Consolidation
+    for (vout, context) in [(0, "first"), (1, "second"), (2, "third")] {
+        monitor.save_monitor(
+            WatchOutpoint::new(funding_id, vout)
+                .context(context)
+                .build()?,
+        )?;
+    }
+    chain.lock().unwrap().mine(vec![funding]);
+    monitor.tick()?;
+    chain.lock().unwrap().mine(vec![consolidation.clone()]);
+    monitor.tick()?;
+    assert_eq!(
+        monitor.outpoints_spent_by(&consolidation.compute_txid())?,
+        vec![(funding_id, 0), (funding_id, 1)]
+    );
+    assert!(monitor
+        .outpoints_spent_by(&payment.compute_txid())?
+        .is_empty());
+
+    // 2. Reorg
+    chain.lock().unwrap().reorg_above(FIRST_HEIGHT);
+    chain.lock().unwrap().mine(vec![payment.clone()]);
+    monitor.tick()?;
+    assert_eq!(
+        monitor.outpoints_spent_by(&consolidation.compute_txid())?,
+        vec![(funding_id, 1)]
+    );
+    assert_eq!(
+        monitor.outpoints_spent_by(&payment.compute_txid())?,
+        vec![(funding_id, 0), (funding_id, 2)]
+    );
+
+    // 3. Cancel and restore
+    monitor.cancel(
+        WatchOutpoint::new(funding_id, 1)
+            .context("second")
+            .build()?,
+    )?;
+    assert!(monitor
+        .outpoints_spent_by(&consolidation.compute_txid())?
+        .is_empty());
+    monitor.restore_from_trash(TrashSelector::Context("second".into()))?;
+    assert_eq!(
+        monitor.outpoints_spent_by(&consolidation.compute_txid())?,
+        vec![(funding_id, 1)]
+    );
+
+    clear_output();
+
+    Ok(())
+}
@@ -0,0 +1,132 @@
+#![cfg(feature = "async")]
+
+use bitcoin::{absolute::LockTime, Transaction};
+use bitcoin_indexer::{indexer::MockIndexerApi, types::TransactionInfo};
+use bitvmx_transaction_monitor::{
+    async_monitor::AsyncMonitor,
+    config::{MonitorSettings, MonitorSettingsConfig},
+    errors::{MonitorError, MonitorStoreError},
+    monitor::Monitor,
+    store::MonitorStore,
+    types::{AckMonitorNews, MonitorNews, TypesToMonitor},
+    WatchTx,
+};
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    Arc,
+};
+use storage_backend::{storage::Storage, storage_config::StorageConfig};
+use utils::{block_at, clear_output, generate_random_string, scripted_indexer};
+mod utils;
+
+/// Monitor over a chain whose tip is read from `height`, where `tx` is mined in block 200.
+/// It is built on the task of the async monitor, as the storage can't move between threads.
+fn build_monitor(
+    height: Arc<AtomicU32>,
+    tx: Transaction,
+) -> Result<Monitor<MockIndexerApi, MonitorStore>, MonitorError> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config).map_err(MonitorStoreError::from)?);
+    let store = MonitorStore::new(storage)?;
+
+    let mut mock_indexer = scripted_indexer(height.clone());
+    mock_indexer.expect_is_ready().returning(|| Ok(true));
+    mock_indexer.expect_get_tx().returning(move |_| {
+        let tip = height.load(Ordering::SeqCst);
+        Ok(Some(TransactionInfo {
+            tx: tx.clone(),
+            block_info: block_at(200),
+            confirmations: tip - 200 + 1,
+        }))
+    });
+
+    Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )
+}
+
+/// Test the async monitor under tokio:
+/// 1. The monitors are registered and a tick finds the transaction mined in block 200
+/// 2. The news are acknowledged
+/// 3. The next block only brings the news of the new confirmation
+/// 4. Errors of the monitor, including its build, are returned to the caller
+#[tokio::test]
+async fn test_async_tick_and_ack() -> Result<(), anyhow::Error> {
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_consensus(1),
+        input: vec![],
+        output: vec![],
+    };
+    let tx_id = tx.compute_txid();
+    let height = Arc::new(AtomicU32::new(200));
+
+    let build_height = height.clone();
+    let monitor = AsyncMonitor::spawn(move || build_monitor(build_height, tx)).await?;
+    assert!(monitor.is_ready().await?);
+
+    // 1. Tick
+    monitor
+        .monitor(WatchTx::new(tx_id).context("ctx").build()?)
+        .await?;
+    monitor.monitor(TypesToMonitor::NewBlock).await?;
+    assert_eq!(monitor.get_monitors(false).await?.len(), 2);
+
+    monitor.tick().await?;
+    assert_eq!(monitor.get_monitor_height().await?, 200);
+    let news = monitor.get_news().await?;
+    assert_eq!(news.len(), 2);
+    assert!(news.iter().any(|news| matches!(
+        news,
+        MonitorNews::Transaction(id, status, _) if *id == tx_id && status.confirmations == 1
+    )));
+
+    // 2. Ack
+    monitor
+        .ack_news(AckMonitorNews::Transaction(tx_id, "ctx".into()))
+        .await?;
+    monitor.ack_news(AckMonitorNews::NewBlock(None)).await?;
+    assert!(monitor.get_news().await?.is_empty());
+
+    // 3. Next block
+    height.store(201, Ordering::SeqCst);
+    monitor.cancel(TypesToMonitor::NewBlock).await?;
+    monitor.tick().await?;
+    let news = monitor.get_news().await?;
+    assert_eq!(news.len(), 1);
+    assert!(matches!(
+        &news[0],
+        MonitorNews::Transaction(id, status, _) if *id == tx_id && status.confirmations == 2
+    ));
+
+    // 4. Errors
+    let max = monitor
+        .call(|monitor| Ok(monitor.settings.max_monitoring_confirmations))
+        .await?;
+    assert!(matches!(
+        monitor
+            .monitor(
+                WatchTx::new(tx_id)
+                    .context("late")
+                    .trigger_at(max)
+                    .build()?
+            )
+            .await,
+        Err(MonitorError::InvalidConfirmationTrigger(..))
+    ));
+    assert!(matches!(
+        AsyncMonitor::<MockIndexerApi, MonitorStore>::spawn(|| Err(MonitorError::UnexpectedError(
+            "no storage".to_string()
+        )))
+        .await,
+        Err(MonitorError::UnexpectedError(_))
+    ));
+
+    drop(monitor);
+    clear_output();
+
+    Ok(())
+}
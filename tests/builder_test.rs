@@ -0,0 +1,198 @@
+use bitcoin::{Amount, ScriptBuf, Txid};
+use bitvmx_transaction_monitor::{
+    errors::MonitorError,
+    types::{MonitorContext, SpendValueGate, TypesToMonitor},
+    WatchAlternatives, WatchOpReturn, WatchOutpoint, WatchPegin, WatchScript, WatchTx,
+};
+use std::str::FromStr;
+
+/// Test that the monitor builders produce the expected TypesToMonitor variants
+/// and reject invalid monitors:
+/// 1. Defaults (empty context, no trigger) for every builder
+/// 2. A trigger of 0 confirmations is rejected
+/// 3. Contexts using the internal prefixes are rejected
+/// 4. A Transactions monitor without transaction ids, a ScriptPubKey monitor without script, or an
+///    OpReturnPrefix monitor without prefix, is rejected
+/// 5. A pegin committee is kept once and must be a taproot output
+/// 6. A pegin packet range must not be empty, a single packet is a range
+/// 7. A minimum spend value builds the value gate of an outpoint, its options need the value
+/// 8. Alternatives need at least two distinct transactions
+#[test]
+fn test_monitor_builders() -> Result<(), anyhow::Error> {
+    let tx_id = Txid::from_str("0000000000000000000000000000000000000000000000000000000000000001")?;
+    let script_pubkey = ScriptBuf::from_hex("51")?;
+
+    // 1. Defaults
+    assert_eq!(
+        WatchTx::new(tx_id).build()?,
+        TypesToMonitor::Transactions(vec![tx_id], MonitorContext::default(), None, None)
+    );
+    assert_eq!(
+        WatchOutpoint::new(tx_id, 2).trigger_at(3).build()?,
+        TypesToMonitor::SpendingUTXOTransaction(
+            tx_id,
+            2,
+            MonitorContext::default(),
+            Some(3),
+            None,
+            None,
+            None,
+        )
+    );
+    assert_eq!(
+        WatchPegin::new().build()?,
+        TypesToMonitor::RskPegin(None, vec![], None, None)
+    );
+    assert_eq!(
+        WatchScript::new(script_pubkey.clone()).build()?,
+        TypesToMonitor::ScriptPubKey(script_pubkey.clone(), MonitorContext::default(), None, None)
+    );
+    assert_eq!(
+        WatchOpReturn::new(b"PROTO").build()?,
+        TypesToMonitor::OpReturnPrefix(b"PROTO".to_vec(), MonitorContext::default())
+    );
+
+    // 2. Trigger of 0 confirmations
+    assert!(matches!(
+        WatchTx::new(tx_id).trigger_at(0).build(),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+    assert!(matches!(
+        WatchOutpoint::new(tx_id, 0).trigger_at(0).build(),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+    assert!(matches!(
+        WatchPegin::new().trigger_at(0).build(),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+    assert!(matches!(
+        WatchScript::new(script_pubkey.clone())
+            .trigger_at(0)
+            .build(),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+
+    // 3. Reserved contexts
+    assert!(matches!(
+        WatchTx::new(tx_id).context("INTERNAL_RSK_PEGIN").build(),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+    assert!(matches!(
+        WatchOutpoint::new(tx_id, 0)
+            .context("INTERNAL_SPENDING_UTXO:context")
+            .build(),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+    assert!(matches!(
+        WatchScript::new(script_pubkey)
+            .context("INTERNAL_SCRIPT_PUBKEY:context")
+            .build(),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+
+    // 4. No transaction ids
+    assert!(matches!(
+        WatchTx::many(vec![]).build(),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+    assert!(matches!(
+        WatchScript::new(ScriptBuf::new()).build(),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+    assert!(matches!(
+        WatchOpReturn::new(Vec::new()).build(),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+
+    // 5. Pegin committee
+    let committee = ScriptBuf::from_hex(&format!("5120{}", "09".repeat(32)))?;
+    assert_eq!(
+        WatchPegin::new()
+            .committee(committee.clone())
+            .committee(committee.clone())
+            .build()?,
+        TypesToMonitor::RskPegin(None, vec![committee], None, None)
+    );
+    assert!(matches!(
+        WatchPegin::new()
+            .committee(ScriptBuf::from_hex(&format!("0014{}", "09".repeat(20)))?)
+            .build(),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+
+    // 6. Pegin packet range
+    assert_eq!(
+        WatchPegin::new().packet_range(7, 7).build()?,
+        TypesToMonitor::RskPegin(None, vec![], Some((7, 7)), None)
+    );
+    assert!(matches!(
+        WatchPegin::new().packet_range(8, 7).build(),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+
+    // 7. Minimum spend value
+    let min_spend_value = Amount::from_sat(10_000);
+    assert_eq!(
+        WatchOutpoint::new(tx_id, 1)
+            .min_spend_value(min_spend_value)
+            .build()?,
+        TypesToMonitor::SpendingUTXOTransaction(
+            tx_id,
+            1,
+            MonitorContext::default(),
+            None,
+            None,
+            Some(SpendValueGate::new(min_spend_value)),
+            None,
+        )
+    );
+    assert_eq!(
+        WatchOutpoint::new(tx_id, 1)
+            .measure_monitored_output()
+            .ignore_minor_spends()
+            .min_spend_value(min_spend_value)
+            .build()?,
+        TypesToMonitor::SpendingUTXOTransaction(
+            tx_id,
+            1,
+            MonitorContext::default(),
+            None,
+            None,
+            Some(SpendValueGate {
+                min_spend_value,
+                measure_monitored_output: true,
+                report_minor: false,
+            }),
+            None,
+        )
+    );
+    assert!(matches!(
+        WatchOutpoint::new(tx_id, 1).ignore_minor_spends().build(),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+    assert!(matches!(
+        WatchOutpoint::new(tx_id, 1)
+            .context("INTERNAL_MINOR_SPEND:context")
+            .build(),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+
+    // 8. Alternatives
+    let other_tx_id = Txid::from_str(&"02".repeat(32))?;
+    assert_eq!(
+        WatchAlternatives::new([tx_id, other_tx_id])
+            .context("step-3")
+            .build()?,
+        TypesToMonitor::Alternatives(vec![tx_id, other_tx_id], "step-3".into())
+    );
+    assert!(matches!(
+        WatchAlternatives::new([tx_id]).build(),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+    assert!(matches!(
+        WatchAlternatives::new([tx_id, other_tx_id, tx_id]).build(),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+
+    Ok(())
+}
@@ -0,0 +1,233 @@
+use bitcoin::Txid;
+use bitcoin_indexer::indexer::MockIndexerApi;
+use bitvmx_transaction_monitor::{
+    config::{MonitorSettings, MonitorSettingsConfig},
+    errors::MonitorError,
+    monitor::Monitor,
+    store::MonitorStore,
+    types::{AckMonitorNews, MonitorNews, TypesToMonitor},
+    WatchTx,
+};
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+use storage_backend::{storage::Storage, storage_config::StorageConfig};
+use utils::{clear_output, generate_random_string, scripted_indexer};
+mod utils;
+
+fn txid(n: u32) -> Txid {
+    Txid::from_str(&format!("{:064x}", n)).unwrap()
+}
+
+/// Monitor over a chain whose tip is read from `height`, no monitored transaction is ever mined.
+fn new_monitor(
+    height: Arc<AtomicU32>,
+) -> Result<Monitor<MockIndexerApi, MonitorStore>, anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let mut mock_indexer = scripted_indexer(height);
+    mock_indexer.expect_get_tx().returning(|_| Ok(None));
+
+    Ok(Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?)
+}
+
+/// Test that registrations queued from another thread are not lost while the monitor ticks:
+/// 1. A thread registers 500 transaction monitors through a handle, in batches of 5
+/// 2. The monitor ticks 100 blocks, applying the queued commands between ticks
+/// 3. Every registration succeeded and every monitor is active
+#[test]
+fn test_handle_registrations_during_ticks() -> Result<(), anyhow::Error> {
+    let height = Arc::new(AtomicU32::new(200));
+    let mut monitor = new_monitor(height.clone())?;
+
+    // 1. Registrations from another thread
+    let handle = monitor.handle();
+    let hammer = thread::spawn(move || -> Result<(), MonitorError> {
+        for batch in 0..100 {
+            let receipts = (0..5)
+                .map(|n| {
+                    let watch = WatchTx::new(txid(batch * 5 + n + 1))
+                        .context("ctx")
+                        .build()?;
+                    handle.submit_monitor(watch)
+                })
+                .collect::<Result<Vec<_>, MonitorError>>()?;
+
+            for receipt in receipts {
+                receipt.wait(Duration::from_secs(10))?;
+            }
+        }
+
+        Ok(())
+    });
+
+    // 2. Ticks
+    for tip in 200..300 {
+        height.store(tip, Ordering::SeqCst);
+        monitor.tick()?;
+        monitor.apply_commands();
+    }
+    while !hammer.is_finished() {
+        monitor.wait_for_commands(Duration::from_millis(1));
+    }
+
+    // 3. No registration lost
+    hammer.join().expect("registration thread")?;
+    let monitors = monitor.get_monitors(false)?;
+    assert_eq!(monitors.len(), 500);
+    assert!(monitors.iter().all(|m| m.active));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that the commands of a handle are applied at the safe points of the monitor:
+/// 1. A registration waits until the monitor applies it, a wait that runs out reports a timeout
+/// 2. A settings update queued during a tick is left for apply_commands
+/// 3. Errors of the commands are returned to the caller and news are acknowledged
+/// 4. Commands to a dropped monitor fail
+#[test]
+fn test_handle_commands() -> Result<(), anyhow::Error> {
+    let height = Arc::new(AtomicU32::new(200));
+    let mut monitor = new_monitor(height.clone())?;
+    let handle = monitor.handle();
+
+    // 1. Applied by the monitor only
+    let watch = WatchTx::new(txid(1)).context("ctx").build()?;
+    assert!(matches!(
+        handle
+            .clone()
+            .with_timeout(Duration::from_millis(10))
+            .monitor(watch),
+        Err(MonitorError::CommandTimeout(_))
+    ));
+    assert!(monitor.get_monitors(false)?.is_empty());
+    monitor.tick()?;
+    assert_eq!(monitor.get_monitors(false)?.len(), 1);
+
+    // 2. Settings updates wait for apply_commands
+    let mut settings = monitor.settings.clone();
+    settings.confirmation_threshold += 1;
+    let receipt = handle.submit_update_settings(settings.clone())?;
+    monitor.tick()?;
+    assert_ne!(
+        monitor.settings.confirmation_threshold,
+        settings.confirmation_threshold
+    );
+
+    assert_eq!(monitor.apply_commands(), 1);
+    let update = receipt.wait(Duration::from_secs(1))?;
+    assert_eq!(update.applied, vec!["confirmation_threshold"]);
+    assert_eq!(
+        monitor.settings.confirmation_threshold,
+        settings.confirmation_threshold
+    );
+
+    // 3. Results of the commands
+    let max = monitor.settings.max_monitoring_confirmations;
+    let invalid = handle.submit_monitor(
+        WatchTx::new(txid(2))
+            .context("ctx")
+            .trigger_at(max)
+            .build()?,
+    )?;
+    height.store(201, Ordering::SeqCst);
+    monitor.save_monitor(TypesToMonitor::NewBlock)?;
+    monitor.tick()?;
+    assert!(matches!(
+        invalid.wait(Duration::from_secs(1)),
+        Err(MonitorError::InvalidConfirmationTrigger(..))
+    ));
+
+    assert!(monitor
+        .get_news()?
+        .iter()
+        .any(|news| matches!(news, MonitorNews::NewBlock(..))));
+    let ack = handle.submit_ack_news(AckMonitorNews::NewBlock(None))?;
+    assert_eq!(monitor.apply_commands(), 1);
+    ack.wait(Duration::from_secs(1))?;
+    assert!(monitor.get_news()?.is_empty());
+
+    // 4. Dropped monitor
+    drop(monitor);
+    assert!(matches!(
+        handle.cancel(WatchTx::new(txid(1)).context("ctx").build()?),
+        Err(MonitorError::MonitorStopped)
+    ));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that a block trigger ends the wait between ticks:
+/// 1. A wait without wakeups lasts its timeout
+/// 2. A wakeup from another thread ends the wait, the commands queued before it are applied
+/// 3. A wakeup sent while the monitor ticks ends the next wait, and wakeups are coalesced
+/// 4. Wakeups to a dropped monitor fail
+#[test]
+fn test_block_trigger() -> Result<(), anyhow::Error> {
+    let height = Arc::new(AtomicU32::new(200));
+    let mut monitor = new_monitor(height.clone())?;
+    let trigger = monitor.block_trigger();
+
+    // 1. No wakeup
+    let start = Instant::now();
+    assert_eq!(monitor.wait_for_commands(Duration::from_millis(50)), 0);
+    assert!(start.elapsed() >= Duration::from_millis(50));
+
+    // 2. Wakeup from another thread
+    let receipt = monitor
+        .handle()
+        .submit_monitor(WatchTx::new(txid(1)).context("ctx").build()?)?;
+    let notifier = {
+        let trigger = trigger.clone();
+        thread::spawn(move || {
+            thread::sleep(Duration::from_millis(50));
+            trigger.notify()
+        })
+    };
+    let start = Instant::now();
+    assert_eq!(monitor.wait_for_commands(Duration::from_secs(60)), 1);
+    assert!(start.elapsed() < Duration::from_secs(30));
+    notifier.join().expect("notifier thread")?;
+    receipt.wait(Duration::from_secs(1))?;
+
+    // 3. Wakeups queued before a tick
+    trigger.notify()?;
+    trigger.notify()?;
+    height.store(201, Ordering::SeqCst);
+    monitor.tick()?;
+    let start = Instant::now();
+    monitor.wait_for_commands(Duration::from_secs(60));
+    assert!(start.elapsed() < Duration::from_secs(30));
+
+    let start = Instant::now();
+    monitor.wait_for_commands(Duration::from_millis(50));
+    assert!(start.elapsed() >= Duration::from_millis(50));
+
+    // 4. Dropped monitor
+    drop(monitor);
+    assert!(matches!(
+        trigger.notify(),
+        Err(MonitorError::MonitorStopped)
+    ));
+
+    clear_output();
+
+    Ok(())
+}
@@ -0,0 +1,175 @@
+#![cfg(feature = "compression")]
+
+use bitcoin::{
+    absolute::LockTime, transaction::Version, Amount, BlockHash, OutPoint, ScriptBuf, Transaction,
+    TxIn, TxOut, Txid,
+};
+use bitcoin_indexer::types::FullBlock;
+use bitvmx_transaction_monitor::{
+    compression::COMPRESSION_THRESHOLD,
+    store::{MonitorStore, MonitorStoreApi},
+    types::{StorageStats, TransactionBlockchainStatus, TransactionStatus},
+};
+use serde_json::Value;
+use std::{str::FromStr, sync::Arc};
+use storage_backend::{
+    storage::{KeyValueStore, Storage},
+    storage_config::StorageConfig,
+};
+use utils::{clear_output, generate_random_string};
+mod utils;
+
+const SNAPSHOT_IDS_KEY: &str = "monitor/tx/snapshots/ids";
+
+fn snapshot_key(tx_id: &Txid) -> String {
+    format!("monitor/tx/snapshots/id/{tx_id}")
+}
+
+/// Transaction of a protocol, the same for every `n` but the outpoint it spends.
+fn protocol_tx(n: u8) -> Transaction {
+    Transaction {
+        version: Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::new(Txid::from_str(&format!("{:064x}", n)).unwrap(), 0),
+            ..Default::default()
+        }],
+        output: (0..20)
+            .map(|i| TxOut {
+                value: Amount::from_sat(10_000 + i),
+                script_pubkey: ScriptBuf::from_hex(&format!("0020{}", "ab".repeat(32))).unwrap(),
+            })
+            .collect(),
+    }
+}
+
+fn snapshot(n: u8) -> TransactionStatus {
+    TransactionStatus::new(
+        protocol_tx(n),
+        FullBlock {
+            height: 200,
+            hash: BlockHash::from_str(&format!("{:064x}", 200)).unwrap(),
+            prev_hash: BlockHash::from_str(&format!("{:064x}", 199)).unwrap(),
+            txs: vec![],
+            orphan: false,
+            estimated_fee_rate: 1,
+        },
+        TransactionBlockchainStatus::Confirmed,
+        1,
+    )
+}
+
+fn new_store() -> Result<(Arc<Storage>, MonitorStore), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let storage = Arc::new(Storage::new(&StorageConfig::new(path, None))?);
+    let store = MonitorStore::new(storage.clone())?;
+    Ok((storage, store))
+}
+
+fn is_compressed(storage: &Storage, key: &str) -> Result<bool, anyhow::Error> {
+    let raw: Option<Value> = storage.get(key)?;
+    Ok(matches!(raw, Some(Value::String(raw)) if raw.starts_with('\u{1}')))
+}
+
+/// Test that large values are written compressed and read back as they were:
+/// 1. Snapshots of near-identical transactions are compressed and read back
+/// 2. The storage stats count them, with a compression ratio
+#[test]
+fn test_compressed_round_trip() -> Result<(), anyhow::Error> {
+    let (storage, store) = new_store()?;
+
+    // 1. Snapshots
+    let snapshots: Vec<TransactionStatus> = (1..=5).map(snapshot).collect();
+    for snapshot in snapshots.iter().cloned() {
+        store.save_tx_snapshot(snapshot)?;
+    }
+    for snapshot in &snapshots {
+        assert!(is_compressed(&storage, &snapshot_key(&snapshot.tx_id))?);
+    }
+    assert!(!is_compressed(&storage, SNAPSHOT_IDS_KEY)?);
+    assert_eq!(store.get_tx_snapshots()?, snapshots);
+
+    // 2. Stats
+    let stats = store.get_storage_stats();
+    assert_eq!(stats.compressed_values, 5);
+    assert_eq!(stats.uncompressed_values, 0);
+    assert!(stats.raw_bytes > 5 * COMPRESSION_THRESHOLD as u64);
+    assert!(stats.compression_ratio() > 2.0);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that the values written before compression are still read:
+/// 1. Snapshots written as plain JSON are read
+/// 2. Saving another snapshot writes it compressed next to the plain ones, and they are all
+///    read again
+#[test]
+fn test_mixed_plain_and_compressed_reads() -> Result<(), anyhow::Error> {
+    let (storage, store) = new_store()?;
+
+    // 1. Plain
+    let mut snapshots: Vec<TransactionStatus> = (1..=3).map(snapshot).collect();
+    for snapshot in &snapshots {
+        storage.set(&snapshot_key(&snapshot.tx_id), snapshot, None)?;
+    }
+    let ids: Vec<Txid> = snapshots.iter().map(|s| s.tx_id).collect();
+    storage.set(SNAPSHOT_IDS_KEY, &ids, None)?;
+    assert!(!is_compressed(&storage, &snapshot_key(&ids[0]))?);
+    assert_eq!(store.get_tx_snapshots()?, snapshots);
+
+    // 2. Compressed
+    snapshots.push(snapshot(4));
+    store.save_tx_snapshot(snapshot(4))?;
+    assert!(is_compressed(&storage, &snapshot_key(&snapshots[3].tx_id))?);
+    assert!(!is_compressed(&storage, &snapshot_key(&ids[0]))?);
+    assert_eq!(store.get_tx_snapshots()?, snapshots);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that compression is skipped where it doesn't pay off:
+/// 1. A snapshot of a transaction without inputs or outputs is under the threshold and written
+///    as it is
+/// 2. A large value of a key that is not compressible is written as it is
+#[test]
+fn test_small_values_skip_compression() -> Result<(), anyhow::Error> {
+    let (storage, store) = new_store()?;
+
+    // 1. Under the threshold
+    let mut small = snapshot(1);
+    small.tx.input.clear();
+    small.tx.output.clear();
+    store.save_tx_snapshot(small.clone())?;
+    assert!(!is_compressed(&storage, &snapshot_key(&small.tx_id))?);
+    assert_eq!(store.get_tx_snapshots()?, vec![small]);
+    let stats = store.get_storage_stats();
+    assert_eq!(
+        stats,
+        StorageStats {
+            compressed_values: 0,
+            uncompressed_values: 1,
+            raw_bytes: 0,
+            stored_bytes: 0,
+        }
+    );
+    assert_eq!(stats.compression_ratio(), 1.0);
+
+    // 2. Not compressible
+    let key = "monitor/expiring/entry";
+    let snapshots: Vec<TransactionStatus> = (1..=5).map(snapshot).collect();
+    store.set_with_expiry(key, &snapshots, 300)?;
+    assert!(!is_compressed(&storage, key)?);
+    assert_eq!(
+        store.get_value::<Vec<TransactionStatus>>(key)?,
+        Some(snapshots)
+    );
+    assert_eq!(store.get_storage_stats(), stats);
+
+    clear_output();
+
+    Ok(())
+}
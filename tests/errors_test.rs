@@ -0,0 +1,156 @@
+use bitcoin::{hashes::Hash, BlockHash};
+use bitvmx_transaction_monitor::{
+    errors::{MonitorError, MonitorStoreError},
+    store::{MonitorStore, MonitorStoreApi},
+    types::{AckMonitorNews, BlockSource, DetectionDescriptor, SnoozeTarget},
+};
+use std::{io, sync::Arc, time::Duration};
+use storage_backend::{
+    storage::{KeyValueStore, Storage},
+    storage_config::StorageConfig,
+};
+use utils::{clear_output, generate_random_string};
+mod utils;
+
+#[derive(Debug, PartialEq)]
+enum Class {
+    Transient,
+    Fatal,
+    CallError,
+}
+
+/// Expected class of every variant. The match has no catch-all, so a new variant doesn't build
+/// until it is classified here too.
+fn expected_class(error: &MonitorError) -> Class {
+    match error {
+        MonitorError::IndexerError(_)
+        | MonitorError::BitcoinClientError(_)
+        | MonitorError::WalletRpcError(_)
+        | MonitorError::NodeRpcError(_)
+        | MonitorError::HeldByLowWorkReorg(_)
+        | MonitorError::IndexerInconsistency { .. }
+        | MonitorError::CommandTimeout(_) => Class::Transient,
+        MonitorError::MonitorStoreError(
+            MonitorStoreError::CorruptedEntry { .. } | MonitorStoreError::IncompatibleSchema(..),
+        ) => Class::Fatal,
+        MonitorError::MonitorStoreError(
+            MonitorStoreError::InternalStorageError(_) | MonitorStoreError::WriteFailed { .. },
+        ) => Class::Transient,
+        MonitorError::MonitorStoreError(
+            MonitorStoreError::UnexpectedError(_) | MonitorStoreError::TransactionNotFound(_),
+        ) => Class::CallError,
+        MonitorError::ExportFailed(e) if e.kind() == io::ErrorKind::TimedOut => Class::Transient,
+        MonitorError::ExportFailed(_) => Class::CallError,
+        MonitorError::InvalidSettings(_)
+        | MonitorError::MonitorStopped
+        | MonitorError::BlockSourceMismatch(_)
+        | MonitorError::UnexpectedDetection(_) => Class::Fatal,
+        MonitorError::UnexpectedError(_)
+        | MonitorError::TransactionNotFound(_)
+        | MonitorError::InvalidConfirmationTrigger(..)
+        | MonitorError::InvalidMonitor(_)
+        | MonitorError::ExternalChainMismatch(_)
+        | MonitorError::NewsNotAcked { .. } => Class::CallError,
+    }
+}
+
+/// Test that every error is classified once, as transient, fatal or an error of the call:
+/// 1. Each error of the monitor, with a corrupted entry read from a store, gets the class expected
+///    for its variant, never both transient and fatal
+/// 2. Only transient errors have a retry hint
+/// 3. The HTTP status follows the class
+/// 4. The error is found in an anyhow error wrapped with context
+#[test]
+fn test_error_classification() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage.clone())?;
+    storage.set("monitor/tx/list/active", "not a list", None)?;
+    let corrupted = store.get_monitors().unwrap_err();
+    assert!(matches!(
+        corrupted,
+        MonitorStoreError::CorruptedEntry { .. }
+    ));
+
+    let errors = vec![
+        MonitorError::MonitorStoreError(corrupted),
+        MonitorError::MonitorStoreError(MonitorStoreError::UnexpectedError("x".to_string())),
+        MonitorError::MonitorStoreError(MonitorStoreError::TransactionNotFound("x".to_string())),
+        MonitorError::MonitorStoreError(MonitorStoreError::IncompatibleSchema(3, 2)),
+        MonitorError::UnexpectedError("x".to_string()),
+        MonitorError::TransactionNotFound("x".to_string()),
+        MonitorError::WalletRpcError("x".to_string()),
+        MonitorError::NodeRpcError("x".to_string()),
+        MonitorError::InvalidConfirmationTrigger(0, 6),
+        MonitorError::InvalidMonitor("x".to_string()),
+        MonitorError::InvalidSettings("x".to_string()),
+        MonitorError::HeldByLowWorkReorg(200),
+        MonitorError::IndexerInconsistency {
+            requested: 200,
+            got: 201,
+        },
+        MonitorError::CommandTimeout(Duration::from_secs(3)),
+        MonitorError::MonitorStopped,
+        MonitorError::BlockSourceMismatch(BlockSource::External),
+        MonitorError::ExternalChainMismatch(BlockHash::all_zeros()),
+        MonitorError::NewsNotAcked {
+            unknown: vec![AckMonitorNews::NewBlock(None)],
+            held: vec![],
+        },
+        MonitorError::ExportFailed(io::Error::from(io::ErrorKind::TimedOut)),
+        MonitorError::ExportFailed(io::Error::from(io::ErrorKind::PermissionDenied)),
+        MonitorError::UnexpectedDetection(DetectionDescriptor {
+            monitor: SnoozeTarget::RskPegin,
+            tx_id: None,
+        }),
+    ];
+
+    for error in &errors {
+        // 1. Class
+        let class = expected_class(error);
+        assert_eq!(error.is_transient(), class == Class::Transient, "{error:?}");
+        assert_eq!(error.is_fatal(), class == Class::Fatal, "{error:?}");
+
+        // 2. Retry hint
+        if error.retry_after_hint().is_some() {
+            assert!(error.is_transient(), "{error:?}");
+        }
+
+        // 3. HTTP status
+        let status = error.http_status();
+        match class {
+            Class::Transient => assert_eq!(status, 503),
+            Class::Fatal => assert_eq!(status, 500),
+            Class::CallError => assert!([400, 404, 500].contains(&status)),
+        }
+    }
+    assert_eq!(
+        MonitorError::CommandTimeout(Duration::from_secs(3)).retry_after_hint(),
+        Some(Duration::from_secs(3))
+    );
+    assert_eq!(
+        MonitorError::HeldByLowWorkReorg(200).retry_after_hint(),
+        None
+    );
+    assert_eq!(
+        MonitorError::TransactionNotFound("x".to_string()).http_status(),
+        404
+    );
+    assert_eq!(
+        MonitorError::InvalidMonitor("x".to_string()).http_status(),
+        400
+    );
+
+    // 4. Wrapped in anyhow
+    let wrapped = anyhow::Error::from(MonitorError::HeldByLowWorkReorg(200)).context("acking");
+    assert!(matches!(
+        MonitorError::from_anyhow(&wrapped),
+        Some(MonitorError::HeldByLowWorkReorg(200))
+    ));
+    assert!(MonitorError::from_anyhow(&anyhow::anyhow!("other")).is_none());
+
+    clear_output();
+
+    Ok(())
+}
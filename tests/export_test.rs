@@ -0,0 +1,202 @@
+use bitcoin::{
+    absolute::LockTime, opcodes::all::OP_RETURN, script::Builder, Amount, BlockHash, OutPoint,
+    ScriptBuf, Transaction, TxIn, TxOut, Txid,
+};
+use bitcoin_indexer::{
+    indexer::MockIndexerApi,
+    types::{FullBlock, TransactionInfo},
+};
+use bitvmx_transaction_monitor::{
+    config::{MonitorSettings, MonitorSettingsConfig},
+    monitor::Monitor,
+    store::MonitorStore,
+    WatchOpReturn, WatchOutpoint, WatchScript, WatchTx,
+};
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+};
+use storage_backend::{storage::Storage, storage_config::StorageConfig};
+use utils::{clear_output, generate_random_string};
+mod utils;
+
+fn block_hash(height: u32) -> BlockHash {
+    BlockHash::from_str(&format!("{:064x}", height)).unwrap()
+}
+
+fn tx_with(lock_time: u32, input: Vec<TxIn>, output: Vec<TxOut>) -> Transaction {
+    Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_consensus(lock_time),
+        input,
+        output,
+    }
+}
+
+/// Test that the finalized detections are exported as CSV:
+/// 1. A transaction, a script output, a spend and an OP_RETURN are found in block 200, and
+///    another transaction in block 205
+/// 2. At height 209 with the default threshold of 6, only the detections of block 200 are
+///    exported, ordered and escaped
+/// 3. Heights out of the range are not exported
+#[test]
+fn test_export_detections_csv() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let script_pubkey = ScriptBuf::from_hex(&format!("0014{}", "07".repeat(20)))?;
+    let spent = OutPoint::new(Txid::from_str(&format!("{:064x}", 1))?, 0);
+    let op_return = Builder::new()
+        .push_opcode(OP_RETURN)
+        .push_slice(b"TAG:42")
+        .into_script();
+
+    let paid = tx_with(1, vec![], vec![]);
+    let deposit = tx_with(
+        2,
+        vec![],
+        vec![TxOut {
+            value: Amount::from_sat(50_000),
+            script_pubkey: script_pubkey.clone(),
+        }],
+    );
+    let spend = tx_with(
+        3,
+        vec![TxIn {
+            previous_output: spent,
+            ..Default::default()
+        }],
+        vec![],
+    );
+    let tagged = tx_with(
+        4,
+        vec![],
+        vec![TxOut {
+            value: Amount::ZERO,
+            script_pubkey: op_return,
+        }],
+    );
+    let late = tx_with(5, vec![], vec![]);
+
+    let mined: Vec<(Transaction, u32)> = vec![
+        (paid.clone(), 200),
+        (deposit.clone(), 200),
+        (spend.clone(), 200),
+        (tagged.clone(), 200),
+        (late.clone(), 205),
+    ];
+    let block_at = {
+        let mined = mined.clone();
+        move |height: u32| FullBlock {
+            height,
+            hash: block_hash(height),
+            prev_hash: block_hash(height - 1),
+            txs: mined
+                .iter()
+                .filter(|(_, h)| *h == height)
+                .map(|(tx, _)| tx.clone())
+                .collect(),
+            orphan: false,
+            estimated_fee_rate: 0,
+        }
+    };
+
+    let height = Arc::new(AtomicU32::new(200));
+
+    let mut mock_indexer = MockIndexerApi::new();
+    let (best_height, best_block) = (height.clone(), block_at.clone());
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(best_block(best_height.load(Ordering::SeqCst)))));
+    let block_by_height = block_at.clone();
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |height| Ok(Some(block_by_height(height))));
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let tx_height = height.clone();
+    mock_indexer.expect_get_tx().returning(move |tx_id| {
+        let tip = tx_height.load(Ordering::SeqCst);
+        Ok(mined
+            .iter()
+            .find(|(tx, h)| tx.compute_txid() == *tx_id && *h <= tip)
+            .map(|(tx, h)| TransactionInfo {
+                tx: tx.clone(),
+                block_info: block_at(*h),
+                confirmations: tip - h + 1,
+            }))
+    });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        // Contexts revealed, redaction is covered by redaction_test
+        MonitorSettings::from(MonitorSettingsConfig {
+            reveal_contexts: Some(true),
+            ..Default::default()
+        }),
+    )?;
+
+    // 1. Detections
+    monitor.save_monitor(
+        WatchTx::new(paid.compute_txid())
+            .context("payroll, march")
+            .build()?,
+    )?;
+    monitor.save_monitor(
+        WatchScript::new(script_pubkey)
+            .context("deposit \"vip\"")
+            .build()?,
+    )?;
+    monitor.save_monitor(
+        WatchOutpoint::new(spent.txid, spent.vout)
+            .context("cold\nwallet")
+            .build()?,
+    )?;
+    monitor.save_monitor(
+        WatchOpReturn::new(b"TAG:".to_vec())
+            .context("tags")
+            .build()?,
+    )?;
+    monitor.save_monitor(WatchTx::new(late.compute_txid()).context("late").build()?)?;
+
+    for tip in [200, 205, 209] {
+        height.store(tip, Ordering::SeqCst);
+        monitor.tick()?;
+    }
+
+    // 2. Export of the finalized detections
+    let mut csv = Vec::new();
+    assert_eq!(monitor.export_detections_csv(0, 300, &mut csv)?, 4);
+
+    let expected = format!(
+        "height,kind,txid,outpoint,amount_sat,context,block_hash\r\n\
+         200,op_return,{tagged},,,tags,{block}\r\n\
+         200,script_pubkey,{deposit},{deposit}:0,50000,\"deposit \"\"vip\"\"\",{block}\r\n\
+         200,spending_utxo,{spend},{spent},,\"cold\nwallet\",{block}\r\n\
+         200,transaction,{paid},,,\"payroll, march\",{block}\r\n",
+        tagged = tagged.compute_txid(),
+        deposit = deposit.compute_txid(),
+        spend = spend.compute_txid(),
+        spent = spent,
+        paid = paid.compute_txid(),
+        block = block_hash(200),
+    );
+    assert_eq!(String::from_utf8(csv)?, expected);
+
+    // 3. Out of range
+    let mut csv = Vec::new();
+    assert_eq!(monitor.export_detections_csv(201, 300, &mut csv)?, 0);
+    assert_eq!(
+        String::from_utf8(csv)?,
+        "height,kind,txid,outpoint,amount_sat,context,block_hash\r\n"
+    );
+
+    clear_output();
+
+    Ok(())
+}
@@ -0,0 +1,223 @@
+#![cfg(feature = "http-api")]
+
+use bitcoin::{absolute::LockTime, BlockHash, Transaction, Txid};
+use bitcoin_indexer::{
+    indexer::MockIndexerApi,
+    types::{FullBlock, TransactionInfo},
+};
+use bitvmx_transaction_monitor::{
+    config::{MonitorSettings, MonitorSettingsConfig},
+    http_api::HttpApiServer,
+    monitor::Monitor,
+    schema::describe_schema,
+    store::MonitorStore,
+};
+use serde_json::{json, Value};
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpStream},
+    str::FromStr,
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+use storage_backend::{storage::Storage, storage_config::StorageConfig};
+use utils::{clear_output, generate_random_string};
+mod utils;
+
+/// A monitor at block 200, which holds the returned transaction.
+fn create_monitor() -> Result<(Monitor<MockIndexerApi, MonitorStore>, Txid), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let storage = Arc::new(Storage::new(&StorageConfig::new(path, None))?);
+    let store = MonitorStore::new(storage)?;
+
+    let block_200 = FullBlock {
+        height: 200,
+        hash: BlockHash::from_str(
+            "0000000000000000000000000000000000000000000000000000000000000011",
+        )?,
+        prev_hash: BlockHash::from_str(
+            "0000000000000000000000000000000000000000000000000000000000000001",
+        )?,
+        txs: vec![],
+        orphan: false,
+        estimated_fee_rate: 0,
+    };
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_time(1653195600).unwrap(),
+        input: vec![],
+        output: vec![],
+    };
+    let tx_id = tx.compute_txid();
+
+    let mut mock_indexer = MockIndexerApi::new();
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let best_block = block_200.clone();
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(best_block.clone())));
+    let block_by_height = block_200.clone();
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |_| Ok(Some(block_by_height.clone())));
+    mock_indexer.expect_get_tx().returning(move |id| {
+        Ok((*id == tx_id).then(|| TransactionInfo {
+            tx: tx.clone(),
+            block_info: block_200.clone(),
+            confirmations: 1,
+        }))
+    });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+
+    Ok((monitor, tx_id))
+}
+
+/// Sends a request from another thread, applying the commands it queues until it is answered
+/// like the run loop does. Returns the status and the JSON body of the response.
+fn request(
+    monitor: &mut Monitor<MockIndexerApi, MonitorStore>,
+    addr: SocketAddr,
+    method: &'static str,
+    path: &'static str,
+    body: Option<Value>,
+) -> (u16, Value) {
+    let client = thread::spawn(move || {
+        let body = body.map(|body| body.to_string()).unwrap_or_default();
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{method} {path} HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        assert!(head.contains("Content-Type: application/json\r\n"));
+        let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+
+        (status, serde_json::from_str(body).unwrap())
+    });
+
+    while !client.is_finished() {
+        monitor.wait_for_commands(Duration::from_millis(1));
+    }
+
+    client.join().expect("client thread")
+}
+
+/// Test that the API manages the monitor through its endpoints:
+/// 1. GET /status returns the monitor height and readiness, GET /schema the schema description
+/// 2. POST /monitors registers a transaction monitor with metadata, GET /monitors lists it
+/// 3. After a tick GET /news returns its news, POST /news/ack acknowledges it
+/// 4. Invalid requests, unknown routes and methods are answered with an error response
+/// 5. The listener is closed once the server is dropped
+#[test]
+fn test_http_api() -> Result<(), anyhow::Error> {
+    let (mut monitor, tx_id) = create_monitor()?;
+    monitor.tick()?;
+
+    let server = HttpApiServer::start("127.0.0.1:0", monitor.handle())?;
+    let addr = server.local_addr();
+
+    // 1. Status
+    let (status, body) = request(&mut monitor, addr, "GET", "/status", None);
+    assert_eq!(status, 200);
+    assert_eq!(
+        body,
+        json!({"version": "v1", "monitor_height": 200, "is_ready": true})
+    );
+
+    let (status, body) = request(&mut monitor, addr, "GET", "/schema", None);
+    assert_eq!(status, 200);
+    assert_eq!(body, serde_json::to_value(describe_schema())?);
+
+    // 2. Registration
+    let registration = json!({
+        "monitor": {"kind": "transactions", "txids": [tx_id], "context": "ctx"},
+        "metadata": {"protocol": "pegout-1"}
+    });
+    let (status, body) = request(&mut monitor, addr, "POST", "/monitors", Some(registration));
+    assert_eq!(status, 200);
+    assert_eq!(body, json!({"version": "v1"}));
+
+    let (status, body) = request(&mut monitor, addr, "GET", "/monitors", None);
+    assert_eq!(status, 200);
+    let monitors = body["monitors"].as_array().unwrap();
+    assert_eq!(monitors.len(), 1);
+    assert_eq!(monitors[0]["monitor"]["kind"], "transactions");
+    assert_eq!(monitors[0]["monitor"]["txids"], json!([tx_id]));
+    assert_eq!(monitors[0]["metadata"], json!({"protocol": "pegout-1"}));
+    assert_eq!(monitors[0]["active"], true);
+
+    // 3. News
+    monitor.tick()?;
+    let (status, body) = request(&mut monitor, addr, "GET", "/news", None);
+    assert_eq!(status, 200);
+    let news = body["news"].as_array().unwrap();
+    assert_eq!(news.len(), 1);
+    assert_eq!(news[0]["news"]["kind"], "transaction");
+    assert_eq!(news[0]["news"]["context"], "ctx");
+    assert_eq!(news[0]["metadata"], json!({"protocol": "pegout-1"}));
+
+    let ack = json!({"news": {"kind": "transaction", "txid": tx_id, "context": "ctx"}});
+    let (status, body) = request(&mut monitor, addr, "POST", "/news/ack", Some(ack));
+    assert_eq!(status, 200);
+    assert_eq!(body, json!({"version": "v1"}));
+    assert_eq!(monitor.news_count()?, 0);
+
+    let (_, body) = request(&mut monitor, addr, "GET", "/news", None);
+    assert_eq!(body["news"], json!([]));
+
+    // 4. Errors
+    let (status, body) = request(
+        &mut monitor,
+        addr,
+        "POST",
+        "/monitors",
+        Some(json!({"monitor": {"kind": "unknown"}})),
+    );
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], "invalid_request");
+
+    let invalid_script = json!({
+        "monitor": {"kind": "script_pubkey", "script_pubkey": "zz", "context": "ctx"}
+    });
+    let (status, body) = request(
+        &mut monitor,
+        addr,
+        "POST",
+        "/monitors",
+        Some(invalid_script),
+    );
+    assert_eq!(status, 400);
+    assert_eq!(body["code"], "invalid_monitor");
+    assert_eq!(body["transient"], false);
+
+    let (status, body) = request(&mut monitor, addr, "GET", "/unknown", None);
+    assert_eq!(status, 404);
+    assert_eq!(body["code"], "not_found");
+
+    let (status, body) = request(&mut monitor, addr, "DELETE", "/monitors", None);
+    assert_eq!(status, 405);
+    assert_eq!(body["code"], "method_not_allowed");
+
+    let (status, body) = request(&mut monitor, addr, "POST", "/schema", None);
+    assert_eq!(status, 405);
+    assert_eq!(body["code"], "method_not_allowed");
+
+    // 5. Stopped
+    drop(server);
+    assert!(TcpStream::connect(addr).is_err());
+
+    clear_output();
+
+    Ok(())
+}
@@ -1,108 +1,203 @@
-use anyhow::{Ok, Result};
-use bitcoind::{bitcoind::Bitcoind, config::BitcoindConfig};
-use bitvmx_bitcoin_rpc::bitcoin_client::{BitcoinClient, BitcoinClientApi};
-use bitvmx_settings::settings;
+#![cfg(feature = "docker-tests")]
+
+use anyhow::Result;
+use bitcoin::Amount;
+use bitcoincore_rpc::RpcApi;
+use bitvmx_bitcoin_rpc::bitcoin_client::BitcoinClientApi;
 use bitvmx_transaction_monitor::{
-    config::{MonitorConfig, MonitorSettingsConfig},
-    monitor::{Monitor, MonitorApi},
+    monitor::MonitorApi,
     types::{AckMonitorNews, MonitorNews, TypesToMonitor},
+    wallet_import::WalletImportCount,
+    WatchOutpoint, WatchTx,
 };
-use std::rc::Rc;
-use storage_backend::{storage::Storage, storage_config::StorageConfig};
-use tracing::info;
+use utils::{create_and_send_spending_transaction, docker::Regtest};
 mod utils;
 
+const CONTEXT: &str = "integration";
+
+/// Integration test to detect a transaction monitor news and a new block news on regtest:
+/// 1. Mines 101 blocks and waits for the monitor to sync them, no news is pending
+/// 2. Monitors the coinbase of the block at 10 confirmations with a trigger of 11 confirmations,
+///    no news is sent for it
+/// 3. Mines one block: a single news of the coinbase at 11 confirmations
+/// 4. Acknowledges the news, it is not sent again on the next tick
+/// 5. Monitors new blocks and mines one block: a single news of the new tip
 #[test]
-// Integration test to detect a transaction monitor news.
-// This test verifies that the monitor can correctly detect a transaction monitor news and new block monitor news.
-// The test does the following:
-// 1. Mines 130 blocks to the created wallet to ensure there are confirmed transactions and blocks to monitor.
-// 2. Adds a Transaction to be monitored. That transaction is in block 90 (coinbase transaction).
-// 3. Ticks the monitor 99 times, but does not expect news yet (asserts news is empty). This is because the transaction is not yet 11 confirmations.
-// 4. After one more tick, expects exactly one news entry about the transaction reaching the required confirmation (11 confirmations).
-// 5. Acknowledges the news. And then tick again, but does not expect news yet (asserts news is empty).
-//   This is because the news was acknowledged and will not be returned again because monitor reach 11 confirmations. )
-// 6. Adds a New Block monitor and ticks the monitor 40 times to simulate new blocks being discovered.
-// 7. Expects exactly one news entry when the monitored block height is reached (block 130).
 fn detect_transaction_monitor() -> Result<(), anyhow::Error> {
-    tracing_subscriber::fmt()
-        .with_max_level(tracing::Level::INFO)
-        .init();
-
-    let config = settings::load_config_file::<MonitorConfig>(Some(
-        "config/monitor_config.yaml".to_string(),
-    ))?;
-
-    let storage_config = StorageConfig::new("test_outputs".to_string(), None);
-
-    let storage = Rc::new(Storage::new(&storage_config)?);
-
-    let bitcoind_config = BitcoindConfig::default();
-
-    let bitcoind = Bitcoind::new(bitcoind_config, config.bitcoin.clone(), None);
-
-    bitcoind.start()?;
-
-    let bitcoin_client = BitcoinClient::new_from_config(&config.bitcoin)?;
-    let wallet = bitcoin_client.init_wallet("test_wallet")?;
-
-    info!("Mining 100 blocks to wallet");
-    bitcoin_client.mine_blocks_to_address(130, &wallet)?;
-
-    let monitor = Monitor::new_with_paths(
-        &config.bitcoin,
-        storage,
-        Some(MonitorSettingsConfig::default()),
+    // 1. Sync
+    let regtest = Regtest::start(101)?;
+    assert_eq!(regtest.pending()?, Vec::<String>::new());
+
+    // 2. Transaction below its trigger
+    let tx_id = regtest.coinbase_at(regtest.tip()? - 9)?.compute_txid();
+    regtest.monitor.monitor(
+        WatchTx::new(tx_id)
+            .context(CONTEXT)
+            .trigger_at(11)
+            .build()?,
     )?;
+    regtest.sync()?;
+    assert_eq!(regtest.pending()?, Vec::<String>::new());
+
+    // 3. Trigger reached
+    regtest.mine(1)?;
+    assert_eq!(
+        regtest.pending()?,
+        vec![format!("transaction/{tx_id}/{CONTEXT}")]
+    );
+    let news = regtest.monitor.get_news()?;
+    assert!(matches!(
+        &news[..],
+        [MonitorNews::Transaction(_, status, _)] if status.confirmations == 11
+    ));
+
+    // 4. Acknowledged
+    regtest
+        .monitor
+        .ack_news(AckMonitorNews::Transaction(tx_id, CONTEXT.into()))?;
+    regtest.sync()?;
+    assert_eq!(regtest.pending()?, Vec::<String>::new());
+
+    // 5. New block
+    regtest.monitor.monitor(TypesToMonitor::NewBlock)?;
+    regtest.mine(1)?;
+    let tip = regtest.tip()?;
+    let tip_hash = regtest
+        .client
+        .get_block_by_height(&tip)?
+        .expect("the tip was just mined")
+        .hash;
+    assert_eq!(
+        regtest.pending()?,
+        vec![format!("new_block/{tip}/{tip_hash}")]
+    );
 
-    let block_info = bitcoin_client.get_block_by_height(&90)?.unwrap();
-
-    let tx_id = block_info.txs[0].compute_txid();
-
-    let txs_monitor = TypesToMonitor::Transactions(vec![tx_id], "Txid".to_string(), Some(11));
-    monitor.monitor(txs_monitor)?;
+    Ok(())
+}
 
-    for _ in 0..99 {
-        monitor.tick()?;
-    }
+/// Integration test to detect the spend of a monitored output on regtest:
+/// 1. Mines 101 blocks so the first coinbase can be spent, and funds an output of the wallet
+/// 2. Monitors the output and mines one block: no news while it is unspent
+/// 3. Spends the output and mines one block: a single news of the spend with one confirmation
+/// 4. Acknowledges the news, it is not sent again on the next tick
+#[test]
+fn detect_spending_utxo_monitor() -> Result<(), anyhow::Error> {
+    // 1. Funded output
+    let regtest = Regtest::start(101)?;
+    let funding = Amount::from_sat(1_000_000);
+    let (funding_tx, vout) = regtest.client.fund_address(&regtest.wallet, funding)?;
+    let tx_id = funding_tx.compute_txid();
+
+    // 2. Unspent
+    regtest
+        .monitor
+        .monitor(WatchOutpoint::new(tx_id, vout).context(CONTEXT).build()?)?;
+    regtest.mine(1)?;
+    assert_eq!(regtest.pending()?, Vec::<String>::new());
+
+    // 3. Spent
+    let (_, spender) = create_and_send_spending_transaction(
+        &regtest.client,
+        tx_id,
+        vout,
+        funding - Amount::from_sat(10_000),
+    )?;
+    regtest.mine(1)?;
+    assert_eq!(
+        regtest.pending()?,
+        vec![format!("spending_utxo/{tx_id}:{vout}/{CONTEXT}")]
+    );
+    let news = regtest.monitor.get_news()?;
+    assert!(matches!(
+        &news[..],
+        [MonitorNews::SpendingUTXOTransaction(_, _, status, _)]
+            if status.tx_id == spender && status.confirmations == 1
+    ));
+
+    // 4. Acknowledged
+    regtest
+        .monitor
+        .ack_news(AckMonitorNews::SpendingUTXOTransaction(
+            tx_id,
+            vout,
+            CONTEXT.into(),
+        ))?;
+    regtest.sync()?;
+    assert_eq!(regtest.pending()?, Vec::<String>::new());
 
-    // Assert no news yet
-    let news = monitor.get_news()?;
-    assert_eq!(news.len(), 0);
+    Ok(())
+}
 
-    // Should find the transaction and send news (11 confirmations)
-    monitor.tick()?;
-    let news = monitor.get_news()?;
-    assert_eq!(news.len(), 1);
-    match &news[0] {
-        MonitorNews::Transaction(txid, tx_status, _) => {
-            assert_eq!(txid, &tx_id);
-            assert_eq!(tx_status.confirmations, 11);
+/// Integration test to import the wallet of the node on regtest:
+/// 1. Mines 101 blocks to the wallet and funds an output of the wallet, left unconfirmed
+/// 2. The import registers a Transaction monitor for the funding transaction, a SpendingUTXO
+///    monitor per unspent output and a ScriptPubKey monitor per receiving address
+/// 3. Importing again skips all of them
+/// 4. Mines one block: a news of the funding transaction
+#[test]
+fn import_wallet() -> Result<(), anyhow::Error> {
+    // 1. Unconfirmed transaction
+    let regtest = Regtest::start(101)?;
+    let (funding_tx, _) = regtest
+        .client
+        .fund_address(&regtest.wallet, Amount::from_sat(1_000_000))?;
+    let tx_id = funding_tx.compute_txid();
+
+    // 2. Imported
+    let unspent = regtest
+        .client
+        .client
+        .list_unspent(Some(0), None, None, Some(true), None)?
+        .len();
+    let addresses = regtest
+        .client
+        .client
+        .list_received_by_address(None, Some(0), Some(true), Some(true))?
+        .len();
+    let report =
+        regtest
+            .monitor
+            .import_from_wallet(&regtest.client, &regtest.wallet_name, CONTEXT)?;
+    assert_eq!(
+        report.transactions,
+        WalletImportCount {
+            registered: 1,
+            skipped: 0
+        }
+    );
+    assert_eq!(
+        report.utxos,
+        WalletImportCount {
+            registered: unspent,
+            skipped: 0
+        }
+    );
+    assert_eq!(
+        report.scripts,
+        WalletImportCount {
+            registered: addresses,
+            skipped: 0
         }
-        _ => panic!("Expected MonitorNews::Transaction"),
-    }
-
-    // Acknowledge the news
-    monitor.ack_news(AckMonitorNews::Transaction(tx_id, "Txid".to_string()))?;
-
-    // Add a new block monitor
-    let best_block_monitor = TypesToMonitor::NewBlock;
-    monitor.monitor(best_block_monitor)?;
-
-    for _ in 0..40 {
-        monitor.tick()?;
-    }
-
-    // Should find the new block and send news (block 130)
-    let news = monitor.get_news()?;
-    assert_eq!(news.len(), 1);
-    match &news[0] {
-        MonitorNews::NewBlock(height, _) => assert_eq!(height, &130),
-        _ => panic!("Expected MonitorNews::NewBlock"),
-    }
-
-    utils::clear_output();
-    bitcoind.stop()?;
+    );
+
+    // 3. Already monitored
+    let again =
+        regtest
+            .monitor
+            .import_from_wallet(&regtest.client, &regtest.wallet_name, CONTEXT)?;
+    assert_eq!(again.transactions.skipped, 1);
+    assert_eq!(again.utxos.skipped, unspent);
+    assert_eq!(again.scripts.skipped, addresses);
+    assert_eq!(
+        again.transactions.registered + again.utxos.registered + again.scripts.registered,
+        0
+    );
+
+    // 4. Mined
+    regtest.mine(1)?;
+    assert!(regtest
+        .pending()?
+        .contains(&format!("transaction/{tx_id}/{CONTEXT}")));
 
     Ok(())
 }
@@ -9,7 +9,10 @@ use bitcoin::{
 };
 use bitcoin::{Address, Amount, Network, Transaction, TxOut};
 use bitcoincore_rpc::RawTx;
-use bitvmx_transaction_monitor::helper::is_a_pegin_tx;
+use bitvmx_transaction_monitor::{
+    helper::{is_a_pegin_tx, parse_pegin_data},
+    types::RskPeginData,
+};
 
 #[test]
 fn test_pegin_tx_detection() -> Result<(), anyhow::Error> {
@@ -58,7 +61,7 @@ fn test_pegin_tx_detection() -> Result<(), anyhow::Error> {
         value: Amount::ZERO,
         script_pubkey: Builder::new()
             .push_opcode(OP_RETURN)
-            .push_slice(&data)
+            .push_slice(data)
             .into_script(),
     };
 
@@ -120,5 +123,40 @@ fn test_pegin_tx_detection() -> Result<(), anyhow::Error> {
     // Validate that the committee address (N) is detected
     assert!(is_a_pegin_tx(&pegin_tx));
 
+    // The parsed payload builds the same OP_RETURN data back
+    let parsed = parse_pegin_data(&pegin_tx).expect("pegin data");
+    assert_eq!(
+        parsed,
+        RskPeginData {
+            packet_number,
+            rsk_address: rootstock_address,
+            reimbursement_key: reimbursement_xpk,
+            amount: Amount::from_sat(100_000_000),
+        }
+    );
+    let rebuilt = [
+        b"RSK_PEGIN".as_slice(),
+        &parsed.packet_number.to_be_bytes(),
+        &parsed.rsk_address,
+        &parsed.reimbursement_key.serialize(),
+    ]
+    .concat();
+    assert_eq!(rebuilt, data);
+
+    // A payload that fails the validation is not parsed
+    let mut invalid = data;
+    invalid[0] = b'X';
+    let mut not_pegin = pegin_tx.clone();
+    not_pegin.output[1].script_pubkey = Builder::new()
+        .push_opcode(OP_RETURN)
+        .push_slice(invalid)
+        .into_script();
+    assert!(!is_a_pegin_tx(&not_pegin));
+    assert_eq!(parse_pegin_data(&not_pegin), None);
+
+    let mut single_output = pegin_tx;
+    single_output.output.truncate(1);
+    assert_eq!(parse_pegin_data(&single_output), None);
+
     Ok(())
 }
@@ -0,0 +1,197 @@
+use bitcoin::{absolute::LockTime, BlockHash, CompactTarget, Transaction};
+use bitcoin_indexer::{
+    indexer::MockIndexerApi,
+    types::{FullBlock, TransactionInfo},
+};
+use bitvmx_transaction_monitor::{
+    config::{MonitorSettings, MonitorSettingsConfig},
+    errors::MonitorError,
+    headers::MockBlockHeaderApi,
+    monitor::Monitor,
+    store::MonitorStore,
+    types::{AckMonitorNews, BlockWorkEntry, MonitorNews},
+    WatchTx,
+};
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc,
+    },
+};
+use storage_backend::{storage::Storage, storage_config::StorageConfig};
+use utils::{clear_output, generate_random_string};
+mod utils;
+
+const BRANCH_A_BITS: u32 = 0x1d00ffff;
+
+/// Blocks of branch B are numbered from 0x1000 so their hashes differ from branch A after the fork at 200.
+fn block_at(height: u32, branch_b: bool) -> FullBlock {
+    let number = |height: u32| {
+        if branch_b && height > 200 {
+            height + 0x1000
+        } else {
+            height
+        }
+    };
+
+    FullBlock {
+        hash: utils::block_at(number(height)).hash,
+        prev_hash: utils::block_at(number(height - 1)).hash,
+        ..utils::block_at(height)
+    }
+}
+
+/// Runs a monitored transaction mined at 201 on branch A up to 202, acknowledging its news, then
+/// reorgs to branch B up to 203, where it is mined at 201 again. `branch_b_bits` is None for a monitor without
+/// a header source.
+fn reorg_to_branch_b(
+    branch_b_bits: Option<u32>,
+) -> Result<(Monitor<MockIndexerApi, MonitorStore>, Transaction), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_time(1653195600).unwrap(),
+        input: vec![],
+        output: vec![],
+    };
+
+    let height = Arc::new(AtomicU32::new(200));
+    let branch_b = Arc::new(AtomicBool::new(false));
+
+    let mut mock_indexer = MockIndexerApi::new();
+    let (best_height, best_branch) = (height.clone(), branch_b.clone());
+    mock_indexer.expect_get_best_block().returning(move || {
+        Ok(Some(block_at(
+            best_height.load(Ordering::SeqCst),
+            best_branch.load(Ordering::SeqCst),
+        )))
+    });
+    let by_height_branch = branch_b.clone();
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |height| {
+            Ok(Some(block_at(
+                height,
+                by_height_branch.load(Ordering::SeqCst),
+            )))
+        });
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let (tx_height, tx_branch, mined_tx) = (height.clone(), branch_b.clone(), tx.clone());
+    mock_indexer.expect_get_tx().returning(move |_| {
+        let height = tx_height.load(Ordering::SeqCst);
+        if height < 201 {
+            return Ok(None);
+        }
+        Ok(Some(TransactionInfo {
+            tx: mined_tx.clone(),
+            block_info: block_at(201, tx_branch.load(Ordering::SeqCst)),
+            confirmations: height - 200,
+        }))
+    });
+
+    let settings = MonitorSettings::from(MonitorSettingsConfig::default());
+    let mut monitor = Monitor::new(mock_indexer, store, settings)?;
+
+    if let Some(bits) = branch_b_bits {
+        let mut mock_headers = MockBlockHeaderApi::new();
+        mock_headers.expect_get_block_bits().returning(move |hash| {
+            let on_branch_b = (201..=203).any(|height| block_at(height, true).hash == *hash);
+            let bits = if on_branch_b { bits } else { BRANCH_A_BITS };
+            Ok(Some(CompactTarget::from_consensus(bits)))
+        });
+        monitor = monitor.with_headers(Box::new(mock_headers));
+    }
+
+    let tx_id = tx.compute_txid();
+    monitor.save_monitor(WatchTx::new(tx_id).context("ctx").build()?)?;
+
+    for tip in 200..=202 {
+        height.store(tip, Ordering::SeqCst);
+        monitor.tick()?;
+        monitor.ack_news(AckMonitorNews::Transaction(tx_id, "ctx".into()))?;
+    }
+
+    branch_b.store(true, Ordering::SeqCst);
+    height.store(203, Ordering::SeqCst);
+    monitor.tick()?;
+
+    Ok((monitor, tx))
+}
+
+/// Test that a reorg to a branch with less work is reported and holds the news it re-opened:
+/// 1. Branch B replaces blocks 201 and 202 with three blocks of the minimum regtest difficulty
+/// 2. A LowWorkReorg news reports the work of both branches
+/// 3. The re-opened transaction news can't be acknowledged until the LowWorkReorg news is
+#[test]
+fn test_low_work_reorg_alarm() -> Result<(), anyhow::Error> {
+    // 1. Reorg to the low work branch
+    let (monitor, tx) = reorg_to_branch_b(Some(0x207fffff))?;
+    let tx_id = tx.compute_txid();
+
+    // 2. Alarm with the work of the replaced blocks and of the new branch
+    let work = |bits: u32| {
+        BlockWorkEntry {
+            height: 0,
+            hash: BlockHash::from_str(&format!("{:064x}", 0)).unwrap(),
+            bits: Some(CompactTarget::from_consensus(bits)),
+        }
+        .work()
+        .unwrap()
+    };
+    let news = monitor.get_news()?;
+    assert!(news.contains(&MonitorNews::LowWorkReorg {
+        height: 201,
+        old_work: work(BRANCH_A_BITS) + work(BRANCH_A_BITS),
+        new_work: work(0x207fffff) + work(0x207fffff) + work(0x207fffff),
+    }));
+    assert!(news
+        .iter()
+        .any(|news| matches!(news, MonitorNews::Transaction(t, _, _) if *t == tx_id)));
+
+    // 3. The re-opened news is held until the alarm is acknowledged
+    let ack = AckMonitorNews::Transaction(tx_id, "ctx".into());
+    assert!(matches!(
+        monitor.ack_news(ack.clone()),
+        Err(MonitorError::HeldByLowWorkReorg(201))
+    ));
+
+    monitor.ack_news(AckMonitorNews::LowWorkReorg { height: 201 })?;
+    monitor.ack_news(ack)?;
+    assert!(!monitor.get_news()?.iter().any(|news| matches!(
+        news,
+        MonitorNews::Transaction(..) | MonitorNews::LowWorkReorg { .. }
+    )));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that reorgs are not reported as low work when the new branch isn't, or can't be compared:
+/// 1. Branch B has the same difficulty as branch A, no LowWorkReorg news is sent
+/// 2. Without a header source the reorg is processed without comparing the work
+#[test]
+fn test_low_work_reorg_benign() -> Result<(), anyhow::Error> {
+    for branch_b_bits in [Some(BRANCH_A_BITS), None] {
+        let (monitor, tx) = reorg_to_branch_b(branch_b_bits)?;
+
+        let news = monitor.get_news()?;
+        assert!(!news
+            .iter()
+            .any(|news| matches!(news, MonitorNews::LowWorkReorg { .. })));
+        assert!(news
+            .iter()
+            .any(|news| matches!(news, MonitorNews::Reorg { .. })));
+
+        monitor.ack_news(AckMonitorNews::Transaction(tx.compute_txid(), "ctx".into()))?;
+    }
+
+    clear_output();
+
+    Ok(())
+}
@@ -0,0 +1,299 @@
+use bitcoin::{absolute::LockTime, BlockHash, Transaction};
+use bitcoin_indexer::{
+    indexer::{IndexerApi, MockIndexerApi},
+    types::{FullBlock, TransactionInfo},
+};
+use bitvmx_transaction_monitor::{
+    config::{MonitorSettings, MonitorSettingsConfig},
+    errors::MonitorError,
+    monitor::Monitor,
+    store::MonitorStore,
+    types::TypesToMonitor,
+    WatchTx,
+};
+use std::{str::FromStr, sync::Arc};
+use storage_backend::{storage::Storage, storage_config::StorageConfig};
+use utils::{clear_output, generate_random_string};
+mod utils;
+
+fn create_monitor() -> Result<Monitor<MockIndexerApi, MonitorStore>, anyhow::Error> {
+    let mut mock_indexer = MockIndexerApi::new();
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_time(1653195600).unwrap(),
+        input: vec![],
+        output: vec![],
+    };
+
+    let block_200 = FullBlock {
+        height: 200,
+        hash: BlockHash::from_str(
+            "1000000000000000000000000000000000000000000000000000000000000001",
+        )?,
+        prev_hash: BlockHash::from_str(
+            "1000000000000000000000000000000000000000000000000000000000000000",
+        )?,
+        txs: vec![tx.clone()],
+        orphan: false,
+        estimated_fee_rate: 0,
+    };
+
+    let block_200_clone = block_200.clone();
+    let block_200_clone_2 = block_200.clone();
+    let block_200_clone_3 = block_200.clone();
+
+    mock_indexer.expect_tick().returning(|| Ok(()));
+
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(block_200_clone.clone())));
+
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |_| Ok(Some(block_200_clone_2.clone())));
+
+    mock_indexer
+        .expect_get_block_by_hash()
+        .returning(move |_| Ok(Some(block_200.clone())));
+
+    mock_indexer.expect_get_tx().returning(move |_| {
+        Ok(Some(TransactionInfo {
+            tx: tx.clone(),
+            block_info: block_200_clone_3.clone(),
+            confirmations: 1,
+        }))
+    });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+
+    Ok(monitor)
+}
+
+/// Test that the metrics snapshot reflects the monitor state after a tick:
+/// 1. Monitor a transaction and new blocks, tick over a block holding the transaction
+/// 2. Assert heights, active monitors, unacked news and counters in the snapshot
+/// 3. Assert the Prometheus rendering of the snapshot
+/// 4. Acknowledge the news, counted in the next snapshot
+#[test]
+fn test_metrics_snapshot() -> Result<(), anyhow::Error> {
+    let monitor = create_monitor()?;
+    let tx_id = monitor.indexer.get_best_block()?.unwrap().txs[0].compute_txid();
+
+    // 1. Monitor and tick
+    monitor.save_monitor(WatchTx::new(tx_id).context("ctx").build()?)?;
+    monitor.save_monitor(TypesToMonitor::NewBlock)?;
+    monitor.tick()?;
+
+    // 2. Snapshot
+    let snapshot = monitor.metrics_snapshot()?;
+    assert_eq!(snapshot.monitor_height, 200);
+    assert_eq!(snapshot.indexer_height, Some(200));
+    assert_eq!(snapshot.active_monitors.transaction, 1);
+    assert_eq!(snapshot.active_monitors.new_block, 1);
+    assert_eq!(snapshot.active_monitors.spending_utxo, 0);
+    assert_eq!(snapshot.unacked_news.transaction, 1);
+    assert_eq!(snapshot.unacked_news.new_block, 1);
+    assert_eq!(snapshot.news_emitted, 2);
+    assert_eq!(
+        snapshot.news_emitted_by_kind,
+        [("new_block", 1), ("transaction", 1)].into_iter().collect()
+    );
+    assert!(snapshot.indexer_calls > 0);
+
+    // 3. Rendering
+    let text = snapshot.to_prometheus();
+    assert!(text.contains("# TYPE bitvmx_monitor_height gauge\nbitvmx_monitor_height 200\n"));
+    assert!(text.contains("bitvmx_monitor_indexer_height 200\n"));
+    assert!(text.contains("bitvmx_monitor_active_monitors{kind=\"transaction\"} 1\n"));
+    assert!(text.contains("bitvmx_monitor_unacked_news{kind=\"new_block\"} 1\n"));
+    assert!(text.contains("# TYPE bitvmx_monitor_news_emitted_total counter\n"));
+    assert!(text.contains("bitvmx_monitor_news_emitted_total 2\n"));
+    assert!(text.contains("bitvmx_monitor_news_emitted_by_kind_total{kind=\"transaction\"} 1\n"));
+    assert!(text.contains("bitvmx_monitor_status_fallbacks_total 0\n"));
+    assert!(text.contains("bitvmx_monitor_news_acked_total 0\n"));
+    assert!(text.contains("bitvmx_monitor_indexer_errors_total 0\n"));
+
+    // 4. Acks
+    assert_eq!(monitor.ack_all_news()?, 2);
+    let snapshot = monitor.metrics_snapshot()?;
+    assert_eq!(snapshot.news_acked, 2);
+    assert_eq!(snapshot.unacked_news, Default::default());
+    assert!(snapshot
+        .to_prometheus()
+        .contains("bitvmx_monitor_news_acked_total 2\n"));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that the ticks failed by the indexer are counted:
+/// 1. The indexer returns the monitored transaction in a block above its best block
+/// 2. The tick fails with an indexer inconsistency, counted in `indexer_errors`
+#[test]
+fn test_metrics_indexer_errors() -> Result<(), anyhow::Error> {
+    let mut mock_indexer = MockIndexerApi::new();
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_time(1653195600).unwrap(),
+        input: vec![],
+        output: vec![],
+    };
+    let tx_id = tx.compute_txid();
+
+    let block_at = |height: u32| FullBlock {
+        height,
+        hash: BlockHash::from_str(&format!("{:064x}", height)).unwrap(),
+        prev_hash: BlockHash::from_str(&format!("{:064x}", height - 1)).unwrap(),
+        txs: vec![],
+        orphan: false,
+        estimated_fee_rate: 0,
+    };
+
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(block_at(200))));
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |height| Ok(Some(block_at(height))));
+    mock_indexer
+        .expect_get_block_by_hash()
+        .returning(move |_| Ok(Some(block_at(200))));
+
+    // 1. Found above the best block
+    mock_indexer.expect_get_tx().returning(move |_| {
+        Ok(Some(TransactionInfo {
+            tx: tx.clone(),
+            block_info: block_at(300),
+            confirmations: 1,
+        }))
+    });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+    monitor.save_monitor(WatchTx::new(tx_id).context("ctx").build()?)?;
+
+    // 2. Failed tick
+    let error = monitor.tick().unwrap_err();
+    assert!(matches!(error, MonitorError::IndexerInconsistency { .. }));
+
+    let snapshot = monitor.metrics_snapshot()?;
+    assert_eq!(snapshot.indexer_errors, 1);
+    assert!(snapshot
+        .to_prometheus()
+        .contains("bitvmx_monitor_indexer_errors_total 1\n"));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that the metrics exporter serves the snapshots published while the monitor ticks:
+/// 1. Start the exporter on a random local port
+/// 2. Tick the monitor, publish the snapshot and scrape /metrics after every tick
+/// 3. Assert /schema serves the schema description as JSON, unknown paths return 404 and a
+///    request with an oversized head is dropped without holding the next scrape
+/// 4. A failed collection is served with the status of its error until the next snapshot
+/// 5. Drop the exporter and assert the listener is closed
+#[cfg(feature = "metrics")]
+#[test]
+fn test_metrics_exporter_scrape() -> Result<(), anyhow::Error> {
+    use bitvmx_transaction_monitor::{metrics::MetricsExporter, schema::describe_schema};
+    use std::io::{Read, Write};
+    use std::net::TcpStream;
+
+    fn scrape(addr: std::net::SocketAddr, path: &str) -> Result<String, anyhow::Error> {
+        let mut stream = TcpStream::connect(addr)?;
+        write!(stream, "GET {path} HTTP/1.1\r\nHost: localhost\r\n\r\n")?;
+        let mut response = String::new();
+        stream.read_to_string(&mut response)?;
+        Ok(response)
+    }
+
+    let monitor = create_monitor()?;
+    let tx_id = monitor.indexer.get_best_block()?.unwrap().txs[0].compute_txid();
+    monitor.save_monitor(WatchTx::new(tx_id).context("ctx").build()?)?;
+
+    // 1. Start the exporter
+    let exporter = MetricsExporter::start("127.0.0.1:0")?;
+    let addr = exporter.local_addr();
+
+    // 2. Tick and scrape
+    for _ in 0..3 {
+        monitor.save_monitor(TypesToMonitor::NewBlock)?;
+        monitor.tick()?;
+        exporter.update(&monitor.metrics_snapshot()?);
+
+        let response = scrape(addr, "/metrics")?;
+        assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+        assert!(response.contains("Content-Type: text/plain; version=0.0.4\r\n"));
+        assert!(response.contains("bitvmx_monitor_height 200\n"));
+        assert!(response.contains("bitvmx_monitor_active_monitors{kind=\"transaction\"} 1\n"));
+        assert!(response.contains("bitvmx_monitor_unacked_news{kind=\"transaction\"} 1\n"));
+    }
+
+    // 3. Schema and unknown path
+    let response = scrape(addr, "/schema")?;
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(response.contains("Content-Type: application/json\r\n"));
+    let (_, body) = response.split_once("\r\n\r\n").unwrap();
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(body)?,
+        serde_json::to_value(describe_schema())?
+    );
+
+    let response = scrape(addr, "/other")?;
+    assert!(response.starts_with("HTTP/1.1 404 Not Found\r\n"));
+
+    let mut oversized = TcpStream::connect(addr)?;
+    let _ = oversized
+        .write_all(format!("GET /metrics HTTP/1.1\r\nX: {}\r\n", "a".repeat(64 << 10)).as_bytes());
+    let mut response = String::new();
+    let _ = oversized.read_to_string(&mut response);
+    assert!(response.is_empty());
+    assert!(scrape(addr, "/metrics")?.starts_with("HTTP/1.1 200 OK\r\n"));
+
+    // 4. Failed collection
+    exporter.fail(&MonitorError::IndexerInconsistency {
+        requested: 200,
+        got: 201,
+    });
+    let response = scrape(addr, "/metrics")?;
+    assert!(response.starts_with("HTTP/1.1 503 Service Unavailable\r\n"));
+    assert!(response.contains("Retry-After: 1\r\n"));
+    assert!(!response.contains("bitvmx_monitor_height"));
+
+    exporter.fail(&MonitorError::InvalidSettings("bad".to_string()));
+    let response = scrape(addr, "/metrics")?;
+    assert!(response.starts_with("HTTP/1.1 500 Internal Server Error\r\n"));
+    assert!(!response.contains("Retry-After"));
+
+    exporter.update(&monitor.metrics_snapshot()?);
+    assert!(scrape(addr, "/metrics")?.starts_with("HTTP/1.1 200 OK\r\n"));
+
+    // 5. Shutdown
+    drop(exporter);
+    assert!(TcpStream::connect(addr).is_err());
+
+    clear_output();
+
+    Ok(())
+}
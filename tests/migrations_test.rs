@@ -0,0 +1,661 @@
+use bitcoin::{
+    absolute::LockTime, transaction::Version, Amount, BlockHash, CompactTarget, OutPoint,
+    ScriptBuf, Transaction, Txid,
+};
+use bitvmx_transaction_monitor::{
+    errors::MonitorStoreError,
+    migrations::{logical_snapshot, Difference, LogicalState, STORE_SCHEMA_VERSION},
+    store::{MonitorStore, MonitorStoreApi, MonitoredTypes, TypesToMonitorStore},
+    testing::fixture::{Deactivation, StoreFixture},
+    types::{
+        AckMonitorNews, BlockWorkEntry, DetectionRecord, FullBlock, MonitorEvent, MovedTransaction,
+        ReorgImpact, SnoozeTarget, TransactionBlockchainStatus, TransactionStatus, TypesToMonitor,
+    },
+    WatchOpReturn, WatchOutpoint, WatchPegin, WatchScript, WatchTx,
+};
+use std::{collections::BTreeMap, str::FromStr, sync::Arc};
+use storage_backend::{
+    storage::{KeyValueStore, Storage},
+    storage_config::StorageConfig,
+};
+use utils::{clear_output, generate_random_string};
+mod utils;
+
+/// Raw key/values of a store written with the current layout by `write_current_layout`.
+const CURRENT_LAYOUT_FIXTURE: &str = include_str!("fixtures/store_layout_v0_8.json");
+
+/// Same items in the 0.7 layout, where each list of monitors is a single `Vec`.
+const LIST_LAYOUT_FIXTURE: &str = include_str!("fixtures/store_layout_v0_7.json");
+
+fn txid(n: u8) -> Txid {
+    Txid::from_str(&format!("{:064x}", n)).unwrap()
+}
+
+fn block_hash(n: u8) -> BlockHash {
+    BlockHash::from_str(&format!("{:064x}", n)).unwrap()
+}
+
+fn new_storage() -> Result<Arc<Storage>, anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    Ok(Arc::new(Storage::new(&config)?))
+}
+
+/// Writes every kind of item the store holds through the store API.
+fn write_current_layout(store: &MonitorStore) -> Result<(), anyhow::Error> {
+    store.update_monitor_height(200)?;
+    store.update_monitor_block_hash(block_hash(200))?;
+    store.set_pending_work(true)?;
+
+    store.add_monitor(WatchTx::new(txid(1)).context("a").trigger_at(3).build()?)?;
+    store.add_monitor(WatchTx::many(vec![txid(1), txid(2)]).context("b").build()?)?;
+    store.add_monitor(
+        WatchOutpoint::new(txid(3), 1)
+            .context("c")
+            .trigger_at(2)
+            .deadline(250, 10)
+            .build()?,
+    )?;
+    store.add_monitor(WatchPegin::new().trigger_at(6).build()?)?;
+    store.add_monitor(TypesToMonitor::NewBlock)?;
+    store.add_monitor(
+        WatchScript::new(ScriptBuf::from_hex("51")?)
+            .context("e")
+            .trigger_at(2)
+            .min_value(Amount::from_sat(1_000))
+            .build()?,
+    )?;
+    store.add_monitor(WatchOpReturn::new(b"PROTO").context("f").build()?)?;
+    store.add_monitor(WatchTx::new(txid(10)).context("g").build()?)?;
+    store.cancel_monitor(WatchTx::new(txid(10)).context("g").build()?)?;
+    store.deactivate_monitor(WatchTx::new(txid(2)).context("b").build()?)?;
+    store.update_spending_utxo_monitor((txid(3), 1, Some(txid(4))))?;
+
+    store.update_news(
+        MonitoredTypes::Transaction(txid(1), "a".into()),
+        block_hash(200),
+    )?;
+    store.update_news(
+        MonitoredTypes::SpendingUTXOTransaction(txid(3), 1, "c".into(), txid(4)),
+        block_hash(200),
+    )?;
+    store.update_news(
+        MonitoredTypes::SpendingUTXOUnconfirmed(txid(3), 1, "c".into(), txid(4)),
+        block_hash(199),
+    )?;
+    store.update_news(
+        MonitoredTypes::RskPeginTransaction(txid(5), None),
+        block_hash(200),
+    )?;
+    store.update_news(
+        MonitoredTypes::NewBlock(block_hash(200), None),
+        block_hash(200),
+    )?;
+    store.update_news(
+        MonitoredTypes::ScriptPubKeyTransaction(ScriptBuf::from_hex("51")?, "e".into(), txid(8), 1),
+        block_hash(200),
+    )?;
+    store.update_news(
+        MonitoredTypes::OpReturnTransaction(
+            b"PROTO".to_vec(),
+            "f".into(),
+            txid(9),
+            b"PROTO1".to_vec(),
+        ),
+        block_hash(200),
+    )?;
+    store.update_news(MonitoredTypes::SequenceEpochChanged(0, 1), block_hash(200))?;
+
+    let impact = ReorgImpact {
+        old_tip_height: 200,
+        old_tip: block_hash(199),
+        new_tip_height: 200,
+        new_tip: block_hash(200),
+        moved_transactions: vec![MovedTransaction {
+            tx_id: txid(1),
+            before: block_hash(198),
+            after: Some(block_hash(199)),
+        }],
+        disappeared_spends: vec![],
+        changed_spends: vec![],
+        invalidated_pegins: vec![],
+        reopened_news: vec![],
+    };
+    store.update_news(
+        MonitoredTypes::Reorg(block_hash(199), block_hash(200), impact.summary()),
+        block_hash(200),
+    )?;
+    store.save_reorg_impact(impact)?;
+
+    let blocks: Vec<BlockWorkEntry> = (199..=200)
+        .map(|n| BlockWorkEntry {
+            height: n.into(),
+            hash: block_hash(n),
+            bits: Some(CompactTarget::from_consensus(0x1d00ffff)),
+        })
+        .collect();
+    let old_work = BlockWorkEntry::total_work(&blocks).unwrap();
+    store.update_news(
+        MonitoredTypes::LowWorkReorg(200, old_work, blocks[0].work().unwrap()),
+        block_hash(200),
+    )?;
+    store.set_block_work(blocks)?;
+    store.ack_news(AckMonitorNews::Transaction(txid(1), "a".into()))?;
+    store.save_tx_snapshot(TransactionStatus {
+        tx_id: txid(5),
+        tx: Transaction {
+            version: Version::TWO,
+            lock_time: LockTime::ZERO,
+            input: vec![],
+            output: vec![],
+        },
+        block_info: Some(FullBlock {
+            height: 198,
+            hash: block_hash(198),
+            prev_hash: block_hash(197),
+            txs: vec![],
+            orphan: false,
+            estimated_fee_rate: 0,
+        }),
+        confirmations: 3,
+        status: TransactionBlockchainStatus::Confirmed,
+    })?;
+    store.record_detection(
+        MonitoredTypes::SpendingUTXOTransaction(txid(3), 1, "c".into(), txid(4)),
+        block_hash(200),
+        200,
+    )?;
+
+    store.replace_transaction_monitor(txid(1), txid(6), block_hash(200), 300)?;
+    store.update_transaction_trigger_sent(txid(6), "a", true)?;
+    store.update_last_reported(txid(6), "a", 3, Some(block_hash(200)))?;
+    store.record_monitor_event(
+        &TypesToMonitorStore::Transaction(txid(6), "a".into(), None, None),
+        MonitorEvent::Detected {
+            tx_id: txid(6),
+            height: 200,
+            block_hash: block_hash(200),
+        },
+    )?;
+    store.record_mempool_sighting(txid(6), 198, 12)?;
+    store.record_mempool_sighting(txid(6), 200, 15)?;
+    store.record_filtered_output(&ScriptBuf::from_hex("51")?, "e", Amount::from_sat(300))?;
+    store.archive_detection(DetectionRecord {
+        kind: "script_pubkey".to_string(),
+        tx_id: txid(8),
+        outpoint: Some(OutPoint::new(txid(8), 1)),
+        amount: Some(Amount::from_sat(5_000)),
+        context: "e".into(),
+        height: 200,
+        block_hash: block_hash(200),
+    })?;
+
+    store.set_with_expiry("monitor/journal/entry", "entry", 300)?;
+
+    store.snooze_monitor(SnoozeTarget::NewBlock, 205)?;
+    store.defer_news(MonitoredTypes::NewBlock(block_hash(201), None))?;
+
+    Ok(())
+}
+
+fn load_fixture(storage: &Storage, fixture: &str) -> Result<(), anyhow::Error> {
+    let raw: BTreeMap<String, serde_json::Value> = serde_json::from_str(fixture)?;
+
+    for (key, value) in raw {
+        storage.set(&key, value, None)?;
+    }
+
+    Ok(())
+}
+
+/// Test that the fixture of the current layout and the same items written through the
+/// store API produce the same logical state.
+#[test]
+fn test_current_layout_fixture_matches_store_api() -> Result<(), anyhow::Error> {
+    let fixture_storage = new_storage()?;
+    load_fixture(&fixture_storage, CURRENT_LAYOUT_FIXTURE)?;
+    let from_fixture = logical_snapshot(&MonitorStore::new(fixture_storage)?)?;
+
+    let api_store = MonitorStore::new(new_storage()?)?;
+    write_current_layout(&api_store)?;
+    let from_api = logical_snapshot(&api_store)?;
+
+    assert_eq!(from_fixture.diff(&from_api), vec![]);
+    assert_eq!(from_fixture, from_api);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that the lists of monitors stored as a single `Vec` by 0.7 are moved to per-monitor keys
+/// when the store is opened:
+/// 1. The store loaded from the 0.7 fixture has the same logical state as the current layout
+/// 2. Reading it moved the lists, every monitor is stored under the key of the current layout
+/// 3. Canceling and deactivating monitors of the migrated store gives the same monitors, in the
+///    same order, as in a store written with the current layout
+#[test]
+fn test_list_layout_migration() -> Result<(), anyhow::Error> {
+    let current_storage = new_storage()?;
+    load_fixture(&current_storage, CURRENT_LAYOUT_FIXTURE)?;
+    let current = MonitorStore::new(current_storage.clone())?;
+
+    let migrated_storage = new_storage()?;
+    load_fixture(&migrated_storage, LIST_LAYOUT_FIXTURE)?;
+    assert!(migrated_storage.has_key("monitor/tx/list/active")?);
+    let migrated = MonitorStore::new(migrated_storage.clone())?;
+
+    // 1. Logical state
+    assert_eq!(
+        logical_snapshot(&migrated)?.diff(&logical_snapshot(&current)?),
+        vec![]
+    );
+
+    // 2. Moved
+    let monitor_keys = |storage: &Storage| -> Result<Vec<String>, anyhow::Error> {
+        let mut keys: Vec<String> = storage
+            .keys()?
+            .into_iter()
+            .filter(|key| key.contains("/active/") || key.contains("/inactive/"))
+            .collect();
+        keys.sort();
+        Ok(keys)
+    };
+    assert!(!migrated_storage
+        .keys()?
+        .iter()
+        .any(|key| key.contains("/list/active") || key.contains("/list/inactive")));
+    assert_eq!(
+        monitor_keys(&migrated_storage)?,
+        monitor_keys(&current_storage)?
+    );
+    let key = format!("monitor/tx/active/{}", txid(6));
+    assert_eq!(
+        migrated_storage.get::<_, serde_json::Value>(&key)?,
+        current_storage.get::<_, serde_json::Value>(&key)?
+    );
+
+    // 3. Same behavior
+    for store in [&migrated, &current] {
+        store.cancel_monitor(WatchTx::new(txid(6)).context("b").build()?)?;
+        store.deactivate_monitor(WatchOutpoint::new(txid(3), 1).context("c").build()?)?;
+        store.add_monitor(WatchTx::new(txid(7)).context("d").build()?)?;
+    }
+    assert_eq!(
+        migrated.get_monitor_infos(true)?,
+        current.get_monitor_infos(true)?
+    );
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test the logical state read from the serialized fixture of the current layout:
+/// 1. Heights, monitors, news and bookkeeping entries are all present, and nothing else
+/// 2. Values are normalized and self-describing
+#[test]
+fn test_logical_snapshot_of_current_layout() -> Result<(), anyhow::Error> {
+    let storage = new_storage()?;
+    load_fixture(&storage, CURRENT_LAYOUT_FIXTURE)?;
+    let state = logical_snapshot(&MonitorStore::new(storage)?)?;
+
+    // 1. Every item
+    let paths: Vec<&str> = state
+        .entries()
+        .keys()
+        .map(String::as_str)
+        .filter(|p| !p.starts_with("fingerprint/"))
+        .collect();
+    let mut expected = vec![
+        "expiry/monitor/journal/entry".to_string(),
+        "height".to_string(),
+        format!("monitor/spending/active/{}:1/c", txid(3)),
+        format!("monitor/tx/active/{}/a", txid(6)),
+        format!("monitor/tx/active/{}/b", txid(6)),
+        format!("monitor/tx/inactive/{}/b", txid(2)),
+        "monitor/new_block".to_string(),
+        "monitor/rsk_pegin".to_string(),
+        "monitor/script/active/51/e".to_string(),
+        "monitor/op_return/50524f544f/f".to_string(),
+        format!("news/replaced/{}/a", txid(1)),
+        format!("news/replaced/{}/b", txid(1)),
+        format!("news/rsk_pegin/{}", txid(5)),
+        format!("news/spending/{}:1/c", txid(3)),
+        format!("news/spending_unconfirmed/{}:1/c", txid(3)),
+        format!("news/tx/{}/a", txid(1)),
+        format!("news/script/{}:1/e", txid(8)),
+        format!("news/op_return/{}/50524f544f/f", txid(9)),
+        format!("news/new_block/{}", block_hash(200)),
+        "news/epoch/1".to_string(),
+        "news_epoch".to_string(),
+        "news_sequence".to_string(),
+        format!("news/reorg/{}", block_hash(200)),
+        "reorg/last_impact".to_string(),
+        "news/low_work_reorg/200".to_string(),
+        "block_work/199".to_string(),
+        "block_work/200".to_string(),
+        "tip".to_string(),
+        "pending_work".to_string(),
+        format!("replaced/{}", txid(1)),
+        "snooze/new_block".to_string(),
+        format!("snapshot/tx/{}", txid(5)),
+        format!("mempool_sighting/{}", txid(6)),
+        "address_filter_stats/51/e".to_string(),
+        format!("detection/script_pubkey/{}/{}:1/e", txid(8), txid(8)),
+        "trash/0".to_string(),
+        "trash_next_id".to_string(),
+    ];
+    expected.sort();
+    assert_eq!(paths, expected);
+    assert_eq!(
+        state
+            .entries()
+            .keys()
+            .filter(|p| p.starts_with("fingerprint/"))
+            .count(),
+        11
+    );
+
+    // 2. Values
+    assert_eq!(state.get("height"), Some("200"));
+    assert_eq!(
+        state.get(&format!("monitor/tx/active/{}/a", txid(6))),
+        Some(
+            format!(
+                r#"{{"confirmation_trigger":3,"history":[{{"Registered":{{"height":200}}}},{{"Detected":{{"block_hash":"{}","height":200,"tx_id":"{}"}}}}],"last_reported_block_hash":"{}","last_reported_confirmations":3,"trigger_sent":true}}"#,
+                block_hash(200),
+                txid(6),
+                block_hash(200)
+            )
+            .as_str()
+        )
+    );
+    assert_eq!(
+        state.get(&format!("news/tx/{}/a", txid(1))),
+        Some(
+            format!(
+                r#"{{"acknowledged":true,"block_hash":"{}","sequence":1}}"#,
+                block_hash(200)
+            )
+            .as_str()
+        )
+    );
+    assert_eq!(
+        state.get(&format!("replaced/{}", txid(1))),
+        Some(
+            format!(
+                r#"{{"contexts":["a","b"],"expires_at":300,"new_tx_id":"{}"}}"#,
+                txid(6)
+            )
+            .as_str()
+        )
+    );
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Removes the `history` of every monitor entry, as the store wrote them before monitors kept one.
+fn strip_histories(value: &mut serde_json::Value) {
+    match value {
+        serde_json::Value::Object(map) => {
+            map.remove("history");
+            map.values_mut().for_each(strip_histories);
+        }
+        serde_json::Value::Array(values) => values.iter_mut().for_each(strip_histories),
+        _ => {}
+    }
+}
+
+/// Test that monitors stored without a history load with an empty one:
+/// 1. The fixture without histories loads, every monitor has an empty history
+/// 2. Other than the histories, its logical state is the one of the fixture
+/// 3. A new event is recorded in the empty history
+#[test]
+fn test_monitors_without_history() -> Result<(), anyhow::Error> {
+    let mut raw: BTreeMap<String, serde_json::Value> =
+        serde_json::from_str(CURRENT_LAYOUT_FIXTURE)?;
+    raw.values_mut().for_each(strip_histories);
+
+    // 1. Empty histories
+    let storage = new_storage()?;
+    load_fixture(&storage, &serde_json::to_string(&raw)?)?;
+    let store = MonitorStore::new(storage)?;
+
+    let infos = store.get_monitor_infos(true)?;
+    assert_eq!(infos.len(), 8);
+    assert!(infos.iter().all(|info| info.history.is_empty()));
+
+    // 2. Same state otherwise
+    let fixture_storage = new_storage()?;
+    load_fixture(&fixture_storage, CURRENT_LAYOUT_FIXTURE)?;
+    let without_histories = |state: LogicalState| {
+        state
+            .entries()
+            .iter()
+            .map(|(path, value)| {
+                let mut value: serde_json::Value = serde_json::from_str(value).unwrap();
+                strip_histories(&mut value);
+                (path.clone(), value)
+            })
+            .collect::<BTreeMap<_, _>>()
+    };
+    assert_eq!(
+        without_histories(logical_snapshot(&store)?),
+        without_histories(logical_snapshot(&MonitorStore::new(fixture_storage)?)?)
+    );
+
+    // 3. Recorded from there
+    let monitor = TypesToMonitorStore::Transaction(txid(6), "a".into(), None, None);
+    let event = MonitorEvent::Confirmed {
+        tx_id: txid(6),
+        confirmations: 3,
+    };
+    store.record_monitor_event(&monitor, event.clone())?;
+    assert_eq!(store.get_monitor_history(&monitor)?, vec![event]);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test the schema version written by `MonitorStore::new`:
+/// 1. A new store and a store of the 0.8 layout, without a version, are at the current version.
+///    The 0.8 store keeps its keys, but for the ones its migrations moved
+/// 2. A store of a newer version is rejected with IncompatibleSchema and left as it was
+#[test]
+fn test_schema_version() -> Result<(), anyhow::Error> {
+    // 1. Written on first use
+    let storage = new_storage()?;
+    MonitorStore::new(storage.clone())?;
+    assert_eq!(
+        storage.get::<_, u32>("monitor/schema_version")?,
+        Some(STORE_SCHEMA_VERSION)
+    );
+
+    let fixture_storage = new_storage()?;
+    load_fixture(&fixture_storage, CURRENT_LAYOUT_FIXTURE)?;
+    let before = fixture_storage.keys()?;
+    MonitorStore::new(fixture_storage.clone())?;
+    assert_eq!(
+        fixture_storage.get::<_, u32>("monitor/schema_version")?,
+        Some(STORE_SCHEMA_VERSION)
+    );
+    let after = fixture_storage.keys()?;
+    let moved = ["monitor/fingerprints", "monitor/tx/snapshots"];
+    assert!(before
+        .iter()
+        .filter(|key| !moved.contains(&key.as_str()))
+        .all(|key| after.contains(key)));
+    assert!(moved.iter().all(|key| !after.contains(&key.to_string())));
+
+    // 2. Newer version
+    let newer = STORE_SCHEMA_VERSION + 1;
+    storage.set("monitor/schema_version", newer, None)?;
+    storage.set("monitor/tx/list/active", serde_json::json!([]), None)?;
+    let error = MonitorStore::new(storage.clone()).err();
+    assert!(matches!(
+        error,
+        Some(MonitorStoreError::IncompatibleSchema(found, expected))
+            if found == newer && expected == STORE_SCHEMA_VERSION
+    ));
+    assert!(storage.has_key("monitor/tx/list/active")?);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test the migration of the spender index from version 1 to 2:
+/// 1. A version 1 store with the outpoints spent by a spender stored as `(txid, vout)` tuples
+/// 2. Opened, they are stored as outpoints and read the same
+/// 3. The logical state is kept and spends are indexed as outpoints from there
+#[test]
+fn test_spender_index_migration() -> Result<(), anyhow::Error> {
+    // 1. Version 1
+    let storage = new_storage()?;
+    let store = MonitorStore::new(storage.clone())?;
+    store.add_monitors(vec![
+        WatchOutpoint::new(txid(1), 0).context("a").build()?,
+        WatchOutpoint::new(txid(1), 2).context("b").build()?,
+    ])?;
+    store.update_spending_utxo_monitor((txid(1), 0, Some(txid(9))))?;
+    store.update_spending_utxo_monitor((txid(1), 2, Some(txid(9))))?;
+
+    let key = format!("monitor/spender/{}", txid(9));
+    storage.set("monitor/schema_version", 1, None)?;
+    storage.set(&key, serde_json::json!([[txid(1), 0], [txid(1), 2]]), None)?;
+    let before = logical_snapshot(&store)?;
+
+    // 2. Migrated
+    let migrated = MonitorStore::new(storage.clone())?;
+    assert_eq!(
+        storage.get::<_, serde_json::Value>(&key)?,
+        Some(serde_json::json!([
+            format!("{}:0", txid(1)),
+            format!("{}:2", txid(1))
+        ]))
+    );
+    assert_eq!(
+        migrated.get_outpoints_spent_by(txid(9))?,
+        vec![(txid(1), 0), (txid(1), 2)]
+    );
+    assert_eq!(
+        storage.get::<_, u32>("monitor/schema_version")?,
+        Some(STORE_SCHEMA_VERSION)
+    );
+
+    // 3. Same state
+    assert_eq!(before.diff(&logical_snapshot(&migrated)?), vec![]);
+    migrated.add_monitor(WatchOutpoint::new(txid(2), 1).context("c").build()?)?;
+    migrated.update_spending_utxo_monitor((txid(2), 1, Some(txid(9))))?;
+    assert_eq!(migrated.find_monitors_for_tx(txid(9))?.len(), 3);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that a store fixture writes the same state as the store API:
+/// 1. Active and inactive monitors, a spent output, and an acknowledged and a pending news
+/// 2. The same items written through the store API give the same logical state, apart from the
+///    fingerprints the API records for each news
+#[test]
+fn test_store_fixture_matches_store_api() -> Result<(), anyhow::Error> {
+    // 1. Declared
+    let fixture = StoreFixture::new()
+        .at_height(200, block_hash(200))
+        .active_tx(txid(1))
+        .context("a")
+        .trigger_at(3)
+        .with_news(block_hash(200), true)
+        .active_spending(OutPoint::new(txid(3), 1))
+        .context("c")
+        .spent_by(txid(4))
+        .with_news(block_hash(200), false)
+        .inactive_tx(txid(2), Deactivation::Requested)
+        .context("b")
+        .build(new_storage()?)?;
+
+    // 2. Through the API
+    let store = MonitorStore::new(new_storage()?)?;
+    store.update_monitor_height(200)?;
+    store.update_monitor_block_hash(block_hash(200))?;
+    store.add_monitor(WatchTx::new(txid(1)).context("a").trigger_at(3).build()?)?;
+    store.add_monitor(WatchOutpoint::new(txid(3), 1).context("c").build()?)?;
+    store.add_monitor(WatchTx::new(txid(2)).context("b").build()?)?;
+    store.deactivate_monitor(WatchTx::new(txid(2)).context("b").build()?)?;
+    store.update_spending_utxo_monitor((txid(3), 1, Some(txid(4))))?;
+    store.update_news(
+        MonitoredTypes::Transaction(txid(1), "a".into()),
+        block_hash(200),
+    )?;
+    store.update_news(
+        MonitoredTypes::SpendingUTXOTransaction(txid(3), 1, "c".into(), txid(4)),
+        block_hash(200),
+    )?;
+    store.ack_news(AckMonitorNews::Transaction(txid(1), "a".into()))?;
+
+    let differences = logical_snapshot(&store)?.diff(&logical_snapshot(&fixture)?);
+    assert!(differences.iter().all(|difference| matches!(
+        difference,
+        Difference::Removed { path, .. } if path.starts_with("fingerprint/")
+    )));
+    assert_eq!(fixture.get_outpoints_spent_by(txid(4))?, vec![(txid(3), 1)]);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that diff reports the paths added, removed and changed between two states.
+#[test]
+fn test_logical_state_diff() -> Result<(), anyhow::Error> {
+    let store = MonitorStore::new(new_storage()?)?;
+    write_current_layout(&store)?;
+    let before = logical_snapshot(&store)?;
+
+    assert!(before.diff(&before).is_empty());
+
+    store.update_monitor_height(201)?;
+    store.cancel_monitor(WatchTx::new(txid(6)).context("b").build()?)?;
+    store.add_monitor(WatchTx::new(txid(7)).context("d").build()?)?;
+    let after = logical_snapshot(&store)?;
+
+    assert_eq!(
+        before.diff(&after),
+        vec![
+            Difference::Changed {
+                path: "height".to_string(),
+                before: "200".to_string(),
+                after: "201".to_string(),
+            },
+            Difference::Removed {
+                path: format!("monitor/tx/active/{}/b", txid(6)),
+                value: r#"{"confirmation_trigger":null,"history":[{"Registered":{"height":200}}],"last_reported_block_hash":null,"last_reported_confirmations":null,"trigger_sent":false}"#.to_string(),
+            },
+            Difference::Added {
+                path: format!("monitor/tx/active/{}/d", txid(7)),
+                value: r#"{"confirmation_trigger":null,"history":[{"Registered":{"height":201}}],"last_reported_block_hash":null,"last_reported_confirmations":null,"trigger_sent":false}"#.to_string(),
+            },
+            Difference::Added {
+                path: "trash/1".to_string(),
+                value: format!(
+                    r#"{{"deleted_at":201,"monitors":{{"alternatives":[],"new_block":false,"op_return_prefixes":[],"rsk_pegin":null,"script_pubkeys":[],"spending_utxos":[],"transactions":[[true,{{"entries":[{{"confirmation_trigger":null,"expires_at_height":null,"extra_data":"b","history":[{{"Registered":{{"height":200}}}}],"last_reported_block_hash":null,"last_reported_confirmations":null,"trigger_sent":false}}],"tx_id":"{}"}}]]}},"reason":"Requested"}}"#,
+                    txid(6)
+                ),
+            },
+            Difference::Changed {
+                path: "trash_next_id".to_string(),
+                before: "1".to_string(),
+                after: "2".to_string(),
+            },
+        ]
+    );
+
+    clear_output();
+
+    Ok(())
+}
@@ -1,11 +1,29 @@
-use bitcoin::{absolute::LockTime, Transaction, Txid};
+use bitcoin::{absolute::LockTime, BlockHash, OutPoint, ScriptBuf, Transaction, Txid};
+use bitcoin_indexer::{
+    indexer::MockIndexerApi,
+    types::{FullBlock, TransactionInfo},
+};
 use bitvmx_transaction_monitor::{
-    store::{MonitorStore, MonitorStoreApi, TypesToMonitorStore},
-    types::TypesToMonitor,
+    config::{MonitorSettings, MonitorSettingsConfig},
+    errors::MonitorStoreError,
+    monitor::Monitor,
+    store::{MonitorStore, MonitorStoreApi, MonitoredTypes, TypesToMonitorStore},
+    testing::fixture::{Deactivation, StoreFixture},
+    types::{
+        AckMonitorNews, CancelNewsPolicy, ChainLogEntry, DeactivationReason, MonitorContext,
+        MonitorExport, MonitorKind, MonitorNews, PruneReport, SnoozeTarget,
+        TransactionBlockchainStatus, TransactionStatus, TrashSelector, TypesToMonitor,
+        MONITOR_EXPORT_VERSION,
+    },
+    WatchOpReturn, WatchOutpoint, WatchPegin, WatchScript, WatchTx,
+};
+use std::{collections::BTreeMap, str::FromStr, sync::Arc};
+use storage_backend::{
+    storage::{KeyValueStore, Storage},
+    storage_config::StorageConfig,
 };
-use std::{rc::Rc, str::FromStr};
-use storage_backend::{storage::Storage, storage_config::StorageConfig};
 use utils::{clear_output, generate_random_string};
+use uuid::Uuid;
 mod utils;
 
 /// This test verifies the functionality of the MonitorStore implementation.
@@ -17,7 +35,7 @@ mod utils;
 fn test_monitor_store_save_get_remove() -> Result<(), anyhow::Error> {
     let path = format!("test_outputs/{}", generate_random_string());
     let config = StorageConfig::new(path, None);
-    let storage = Rc::new(Storage::new(&config)?);
+    let storage = Arc::new(Storage::new(&config)?);
     let store = MonitorStore::new(storage)?;
 
     // Verify initial state - no monitors
@@ -39,17 +57,15 @@ fn test_monitor_store_save_get_remove() -> Result<(), anyhow::Error> {
     };
 
     // Test get_monitors and save_monitor with all transaction types
-    use bitvmx_transaction_monitor::types::TypesToMonitor;
 
     // 1. Test One Transaction
-    let one_tx_monitor =
-        TypesToMonitor::Transactions(vec![tx1.compute_txid()], String::new(), None);
+    let one_tx_monitor = WatchTx::new(tx1.compute_txid()).build()?;
 
     store.add_monitor(one_tx_monitor.clone())?;
     let monitors = store.get_monitors()?;
     assert!(matches!(
         monitors[0],
-        TypesToMonitorStore::Transaction(tx_id, _, _) if tx_id == tx1.compute_txid()
+        TypesToMonitorStore::Transaction(tx_id, _, _, _) if tx_id == tx1.compute_txid()
     ));
 
     store.deactivate_monitor(one_tx_monitor.clone())?;
@@ -58,25 +74,24 @@ fn test_monitor_store_save_get_remove() -> Result<(), anyhow::Error> {
     assert_eq!(monitors.len(), 0);
 
     // 3. Test RskPeginTransaction
-    let rsk_monitor = TypesToMonitor::RskPegin(None);
+    let rsk_monitor = WatchPegin::new().build()?;
     store.add_monitor(rsk_monitor.clone())?;
     let monitors = store.get_monitors()?;
     assert!(matches!(
         monitors[0].clone(),
-        TypesToMonitorStore::RskPegin(_)
+        TypesToMonitorStore::RskPegin(..)
     ));
     store.deactivate_monitor(rsk_monitor.clone())?;
     let monitors = store.get_monitors()?;
     assert_eq!(monitors.len(), 0);
 
     // 4. Test SpendingUTXOTransaction
-    let utxo_monitor =
-        TypesToMonitor::SpendingUTXOTransaction(tx3.compute_txid(), 1, String::new(), None);
+    let utxo_monitor = WatchOutpoint::new(tx3.compute_txid(), 1).build()?;
     store.add_monitor(utxo_monitor.clone())?;
     let monitors = store.get_monitors()?;
     assert!(matches!(
         monitors[0].clone(),
-        TypesToMonitorStore::SpendingUTXOTransaction(tx_id, utxo_index, _, _)
+        TypesToMonitorStore::SpendingUTXOTransaction(tx_id, utxo_index, _, _, _, _, _)
             if tx_id == tx3.compute_txid() && utxo_index == 1
     ));
     store.deactivate_monitor(utxo_monitor.clone())?;
@@ -92,17 +107,17 @@ fn test_monitor_store_save_get_remove() -> Result<(), anyhow::Error> {
 fn test_monitor_store_cancel_monitor() -> Result<(), anyhow::Error> {
     let path = format!("test_outputs/{}", generate_random_string());
     let config = StorageConfig::new(path, None);
-    let storage = Rc::new(Storage::new(&config)?);
+    let storage = Arc::new(Storage::new(&config)?);
     let store = MonitorStore::new(storage)?;
 
     let tx_id = Txid::from_str("0000000000000000000000000000000000000000000000000000000000000000")?;
     let tx_id_1 =
         Txid::from_str("0000000000000000000000000000000000000000000000000000000000000001")?;
 
-    let utxo_monitor = TypesToMonitor::SpendingUTXOTransaction(tx_id, 1, String::new(), None);
+    let utxo_monitor = WatchOutpoint::new(tx_id, 1).build()?;
     store.add_monitor(utxo_monitor.clone())?;
 
-    let tx_monitor = TypesToMonitor::Transactions(vec![tx_id_1], String::new(), None);
+    let tx_monitor = WatchTx::new(tx_id_1).build()?;
     store.add_monitor(tx_monitor.clone())?;
 
     // Cancel utxo monitor
@@ -112,7 +127,7 @@ fn test_monitor_store_cancel_monitor() -> Result<(), anyhow::Error> {
     assert_eq!(monitors.len(), 1);
     assert!(matches!(
         monitors[0].clone(),
-        TypesToMonitorStore::Transaction(tx, _, _) if tx == tx_id_1
+        TypesToMonitorStore::Transaction(tx, _, _, _) if tx == tx_id_1
     ));
 
     // Cancel utxo monitor again
@@ -120,7 +135,7 @@ fn test_monitor_store_cancel_monitor() -> Result<(), anyhow::Error> {
     let monitors = store.get_monitors()?;
     assert!(matches!(
         monitors[0].clone(),
-        TypesToMonitorStore::Transaction(tx, _, _) if tx == tx_id_1
+        TypesToMonitorStore::Transaction(tx, _, _, _) if tx == tx_id_1
     ));
 
     store.cancel_monitor(tx_monitor.clone())?;
@@ -134,27 +149,29 @@ fn test_monitor_store_cancel_monitor() -> Result<(), anyhow::Error> {
 fn test_monitor_store_cancel_deactivated_transaction_monitor() -> Result<(), anyhow::Error> {
     let path = format!("test_outputs/{}", generate_random_string());
     let config = StorageConfig::new(path, None);
-    let storage = Rc::new(Storage::new(&config)?);
-    let store = MonitorStore::new(storage)?;
+    let storage = Arc::new(Storage::new(&config)?);
 
     let tx_id_active =
         Txid::from_str("1000000000000000000000000000000000000000000000000000000000000000")?;
     let tx_id_inactive =
         Txid::from_str("2000000000000000000000000000000000000000000000000000000000000000")?;
 
-    let active_monitor = TypesToMonitor::Transactions(vec![tx_id_active], String::new(), None);
-    store.add_monitor(active_monitor.clone())?;
+    let store = StoreFixture::new()
+        .active_tx(tx_id_active)
+        .inactive_tx(tx_id_inactive, Deactivation::Requested)
+        .build(storage)?;
 
-    let inactive_monitor = TypesToMonitor::Transactions(vec![tx_id_inactive], String::new(), None);
-    store.add_monitor(inactive_monitor.clone())?;
+    let active_monitor = WatchTx::new(tx_id_active).build()?;
+    let inactive_monitor = WatchTx::new(tx_id_inactive).build()?;
 
-    store.cancel_monitor(inactive_monitor.clone())?;
+    store.cancel_monitor(inactive_monitor)?;
+    assert_eq!(store.export_monitors()?.transactions.len(), 1);
 
     let monitors = store.get_monitors()?;
     assert_eq!(monitors.len(), 1);
     assert!(matches!(
         monitors[0],
-        TypesToMonitorStore::Transaction(tx, _, _) if tx == tx_id_active
+        TypesToMonitorStore::Transaction(tx, _, _, _) if tx == tx_id_active
     ));
 
     store.cancel_monitor(active_monitor.clone())?;
@@ -175,7 +192,7 @@ fn test_monitor_store_cancel_deactivated_transaction_monitor() -> Result<(), any
 fn test_active_inactive_monitor_separation() -> Result<(), anyhow::Error> {
     let path = format!("test_outputs/{}", generate_random_string());
     let config = StorageConfig::new(path, None);
-    let storage = Rc::new(Storage::new(&config)?);
+    let storage = Arc::new(Storage::new(&config)?);
     let store = MonitorStore::new(storage)?;
 
     let tx1 = Transaction {
@@ -204,126 +221,94 @@ fn test_active_inactive_monitor_separation() -> Result<(), anyhow::Error> {
     let tx_id3 = tx3.compute_txid();
 
     // Add three transaction monitors
-    store.add_monitor(TypesToMonitor::Transactions(
-        vec![tx_id1],
-        "extra1".to_string(),
-        None,
-    ))?;
-    store.add_monitor(TypesToMonitor::Transactions(
-        vec![tx_id2],
-        "extra2".to_string(),
-        None,
-    ))?;
-    store.add_monitor(TypesToMonitor::Transactions(
-        vec![tx_id3],
-        "extra3".to_string(),
-        None,
-    ))?;
+    store.add_monitor(WatchTx::new(tx_id1).context("extra1").build()?)?;
+    store.add_monitor(WatchTx::new(tx_id2).context("extra2").build()?)?;
+    store.add_monitor(WatchTx::new(tx_id3).context("extra3").build()?)?;
 
     // All three should be active
     let monitors = store.get_monitors()?;
     assert_eq!(monitors.len(), 3);
     assert!(monitors
         .iter()
-        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _) if *id == tx_id1)));
+        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _, _) if *id == tx_id1)));
     assert!(monitors
         .iter()
-        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _) if *id == tx_id2)));
+        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _, _) if *id == tx_id2)));
     assert!(monitors
         .iter()
-        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _) if *id == tx_id3)));
+        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _, _) if *id == tx_id3)));
 
     // Deactivate tx_id2 (using the same extra_data that was used when adding)
-    store.deactivate_monitor(TypesToMonitor::Transactions(
-        vec![tx_id2],
-        "extra2".to_string(),
-        None,
-    ))?;
+    store.deactivate_monitor(WatchTx::new(tx_id2).context("extra2").build()?)?;
 
     // Only tx_id1 and tx_id3 should be active
     let monitors = store.get_monitors()?;
     assert_eq!(monitors.len(), 2);
     assert!(monitors
         .iter()
-        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _) if *id == tx_id1)));
+        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _, _) if *id == tx_id1)));
     assert!(!monitors
         .iter()
-        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _) if *id == tx_id2)));
+        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _, _) if *id == tx_id2)));
     assert!(monitors
         .iter()
-        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _) if *id == tx_id3)));
+        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _, _) if *id == tx_id3)));
 
     // Deactivate tx_id1 as well (using the same extra_data that was used when adding)
-    store.deactivate_monitor(TypesToMonitor::Transactions(
-        vec![tx_id1],
-        "extra1".to_string(),
-        None,
-    ))?;
+    store.deactivate_monitor(WatchTx::new(tx_id1).context("extra1").build()?)?;
 
     // Only tx_id3 should be active
     let monitors = store.get_monitors()?;
     assert_eq!(monitors.len(), 1);
     assert!(!monitors
         .iter()
-        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _) if *id == tx_id1)));
+        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _, _) if *id == tx_id1)));
     assert!(!monitors
         .iter()
-        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _) if *id == tx_id2)));
+        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _, _) if *id == tx_id2)));
     assert!(monitors
         .iter()
-        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _) if *id == tx_id3)));
+        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _, _) if *id == tx_id3)));
 
     // Reactivate tx_id2 (add it again)
-    store.add_monitor(TypesToMonitor::Transactions(
-        vec![tx_id2],
-        "extra2_reactivated".to_string(),
-        None,
-    ))?;
+    store.add_monitor(WatchTx::new(tx_id2).context("extra2_reactivated").build()?)?;
 
     // tx_id2 and tx_id3 should be active
     let monitors = store.get_monitors()?;
     assert_eq!(monitors.len(), 2);
     assert!(!monitors
         .iter()
-        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _) if *id == tx_id1)));
+        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _, _) if *id == tx_id1)));
     assert!(monitors
         .iter()
-        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _) if *id == tx_id2)));
+        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _, _) if *id == tx_id2)));
     assert!(monitors
         .iter()
-        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _) if *id == tx_id3)));
+        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _, _) if *id == tx_id3)));
 
     // Cancel tx_id2 (should remove from both active and inactive)
     // Cancel the reactivated entry with "extra2_reactivated"
-    store.cancel_monitor(TypesToMonitor::Transactions(
-        vec![tx_id2],
-        "extra2_reactivated".to_string(),
-        None,
-    ))?;
+    store.cancel_monitor(WatchTx::new(tx_id2).context("extra2_reactivated").build()?)?;
 
     // Only tx_id3 should be active
     let monitors = store.get_monitors()?;
     assert_eq!(monitors.len(), 1);
     assert!(monitors
         .iter()
-        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _) if *id == tx_id3)));
+        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _, _) if *id == tx_id3)));
 
     // Reactivate tx_id1
-    store.add_monitor(TypesToMonitor::Transactions(
-        vec![tx_id1],
-        "extra1_reactivated".to_string(),
-        None,
-    ))?;
+    store.add_monitor(WatchTx::new(tx_id1).context("extra1_reactivated").build()?)?;
 
     // tx_id1 and tx_id3 should be active
     let monitors = store.get_monitors()?;
     assert_eq!(monitors.len(), 2);
     assert!(monitors
         .iter()
-        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _) if *id == tx_id1)));
+        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _, _) if *id == tx_id1)));
     assert!(monitors
         .iter()
-        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _) if *id == tx_id3)));
+        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _, _) if *id == tx_id3)));
 
     clear_output();
 
@@ -335,35 +320,35 @@ fn test_active_inactive_monitor_separation() -> Result<(), anyhow::Error> {
 fn test_active_inactive_boolean_monitors() -> Result<(), anyhow::Error> {
     let path = format!("test_outputs/{}", generate_random_string());
     let config = StorageConfig::new(path, None);
-    let storage = Rc::new(Storage::new(&config)?);
+    let storage = Arc::new(Storage::new(&config)?);
     let store = MonitorStore::new(storage)?;
 
     // Test RskPeginTransaction
-    store.add_monitor(TypesToMonitor::RskPegin(None))?;
+    store.add_monitor(WatchPegin::new().build()?)?;
     let monitors = store.get_monitors()?;
     assert!(monitors
         .iter()
-        .any(|m| matches!(m, TypesToMonitorStore::RskPegin(_))));
+        .any(|m| matches!(m, TypesToMonitorStore::RskPegin(..))));
 
-    store.deactivate_monitor(TypesToMonitor::RskPegin(None))?;
+    store.deactivate_monitor(WatchPegin::new().build()?)?;
     let monitors = store.get_monitors()?;
     assert!(!monitors
         .iter()
-        .any(|m| matches!(m, TypesToMonitorStore::RskPegin(_))));
+        .any(|m| matches!(m, TypesToMonitorStore::RskPegin(..))));
 
     // Reactivate
-    store.add_monitor(TypesToMonitor::RskPegin(None))?;
+    store.add_monitor(WatchPegin::new().build()?)?;
     let monitors = store.get_monitors()?;
     assert!(monitors
         .iter()
-        .any(|m| matches!(m, TypesToMonitorStore::RskPegin(_))));
+        .any(|m| matches!(m, TypesToMonitorStore::RskPegin(..))));
 
     // Cancel
-    store.cancel_monitor(TypesToMonitor::RskPegin(None))?;
+    store.cancel_monitor(WatchPegin::new().build()?)?;
     let monitors = store.get_monitors()?;
     assert!(!monitors
         .iter()
-        .any(|m| matches!(m, TypesToMonitorStore::RskPegin(_))));
+        .any(|m| matches!(m, TypesToMonitorStore::RskPegin(..))));
 
     // Test NewBlock
     store.add_monitor(TypesToMonitor::NewBlock)?;
@@ -397,12 +382,88 @@ fn test_active_inactive_boolean_monitors() -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+/// This test verifies that deactivating and cancelling the RskPegin monitor are told apart:
+/// 1. A deactivated monitor is listed as inactive and a reactivation restores its trigger
+/// 2. A cancelled monitor is not listed and can't be reactivated, it is registered again
+/// 3. A monitor stored with the `active` flag of older versions reads as deactivated
+#[test]
+fn test_rsk_pegin_deactivate_and_cancel() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage.clone())?;
+    let pegin = |monitors: Vec<TypesToMonitorStore>| {
+        monitors
+            .into_iter()
+            .find(|m| matches!(m, TypesToMonitorStore::RskPegin(..)))
+    };
+    let listed =
+        |include_inactive: bool| -> Result<Vec<(bool, TypesToMonitorStore)>, MonitorStoreError> {
+            Ok(store
+                .get_monitor_infos(include_inactive)?
+                .into_iter()
+                .filter(|info| matches!(info.monitor, TypesToMonitorStore::RskPegin(..)))
+                .map(|info| (info.active, info.monitor))
+                .collect())
+        };
+    let registered = TypesToMonitorStore::RskPegin(Some(6), vec![], Some((10, 20)), None);
+
+    // 1. Deactivate and reactivate
+    store.add_monitor(
+        WatchPegin::new()
+            .trigger_at(6)
+            .packet_range(10, 20)
+            .build()?,
+    )?;
+    store.deactivate_monitor(WatchPegin::new().build()?)?;
+    assert_eq!(pegin(store.get_monitors()?), None);
+    assert_eq!(listed(true)?, vec![(false, registered.clone())]);
+
+    store.reactivate_monitor(WatchPegin::new().build()?)?;
+    assert_eq!(pegin(store.get_monitors()?), Some(registered.clone()));
+    assert_eq!(listed(true)?, vec![(true, registered.clone())]);
+
+    // 2. Cancel
+    store.cancel_monitor(WatchPegin::new().build()?)?;
+    assert_eq!(pegin(store.get_monitors()?), None);
+    assert_eq!(listed(true)?, vec![]);
+
+    store.reactivate_monitor(WatchPegin::new().build()?)?;
+    store.deactivate_monitor(WatchPegin::new().build()?)?;
+    store.reactivate_monitor(WatchPegin::new().build()?)?;
+    assert_eq!(listed(true)?, vec![]);
+
+    store.add_monitor(WatchPegin::new().build()?)?;
+    assert_eq!(
+        pegin(store.get_monitors()?),
+        Some(TypesToMonitorStore::RskPegin(None, vec![], None, None))
+    );
+
+    // 3. Older versions
+    storage.set(
+        "monitor/rsk/pegin",
+        serde_json::json!({"active": false, "confirmation_trigger": 6}),
+        None,
+    )?;
+    let store = MonitorStore::new(storage)?;
+    assert_eq!(pegin(store.get_monitors()?), None);
+    store.reactivate_monitor(WatchPegin::new().build()?)?;
+    assert_eq!(
+        pegin(store.get_monitors()?),
+        Some(TypesToMonitorStore::RskPegin(Some(6), vec![], None, None))
+    );
+
+    clear_output();
+
+    Ok(())
+}
+
 /// This test verifies active/inactive separation for SpendingUTXOTransaction monitors
 #[test]
 fn test_active_inactive_spending_utxo_monitors() -> Result<(), anyhow::Error> {
     let path = format!("test_outputs/{}", generate_random_string());
     let config = StorageConfig::new(path, None);
-    let storage = Rc::new(Storage::new(&config)?);
+    let storage = Arc::new(Storage::new(&config)?);
     let store = MonitorStore::new(storage)?;
 
     let tx1 = Transaction {
@@ -423,85 +484,53 @@ fn test_active_inactive_spending_utxo_monitors() -> Result<(), anyhow::Error> {
     let tx_id2 = tx2.compute_txid();
 
     // Add two UTXO monitors
-    store.add_monitor(TypesToMonitor::SpendingUTXOTransaction(
-        tx_id1,
-        0,
-        "extra1".to_string(),
-        None,
-    ))?;
-    store.add_monitor(TypesToMonitor::SpendingUTXOTransaction(
-        tx_id1,
-        1,
-        "extra2".to_string(),
-        None,
-    ))?;
-    store.add_monitor(TypesToMonitor::SpendingUTXOTransaction(
-        tx_id2,
-        0,
-        "extra3".to_string(),
-        None,
-    ))?;
+    store.add_monitor(WatchOutpoint::new(tx_id1, 0).context("extra1").build()?)?;
+    store.add_monitor(WatchOutpoint::new(tx_id1, 1).context("extra2").build()?)?;
+    store.add_monitor(WatchOutpoint::new(tx_id2, 0).context("extra3").build()?)?;
 
     // All three should be active
     let monitors = store.get_monitors()?;
     assert_eq!(monitors.len(), 3);
-    assert!(monitors.iter().any(|m| matches!(m, TypesToMonitorStore::SpendingUTXOTransaction(id, idx, _, _) if *id == tx_id1 && *idx == 0)));
-    assert!(monitors.iter().any(|m| matches!(m, TypesToMonitorStore::SpendingUTXOTransaction(id, idx, _, _) if *id == tx_id1 && *idx == 1)));
-    assert!(monitors.iter().any(|m| matches!(m, TypesToMonitorStore::SpendingUTXOTransaction(id, idx, _, _) if *id == tx_id2 && *idx == 0)));
+    assert!(monitors.iter().any(|m| matches!(m, TypesToMonitorStore::SpendingUTXOTransaction(id, idx, _, _, _, _, _) if *id == tx_id1 && *idx == 0)));
+    assert!(monitors.iter().any(|m| matches!(m, TypesToMonitorStore::SpendingUTXOTransaction(id, idx, _, _, _, _, _) if *id == tx_id1 && *idx == 1)));
+    assert!(monitors.iter().any(|m| matches!(m, TypesToMonitorStore::SpendingUTXOTransaction(id, idx, _, _, _, _, _) if *id == tx_id2 && *idx == 0)));
 
     // Deactivate one
-    store.deactivate_monitor(TypesToMonitor::SpendingUTXOTransaction(
-        tx_id1,
-        0,
-        "extra1".to_string(),
-        None,
-    ))?;
+    store.deactivate_monitor(WatchOutpoint::new(tx_id1, 0).context("extra1").build()?)?;
 
     // Two should remain active
     let monitors = store.get_monitors()?;
 
     assert_eq!(monitors.len(), 2);
-    assert!(!monitors.iter().any(|m| matches!(m, TypesToMonitorStore::SpendingUTXOTransaction(id, idx, _, _) if *id == tx_id1 && *idx == 0)));
-    assert!(monitors.iter().any(|m| matches!(m, TypesToMonitorStore::SpendingUTXOTransaction(id, idx, _, _) if *id == tx_id1 && *idx == 1)));
-    assert!(monitors.iter().any(|m| matches!(m, TypesToMonitorStore::SpendingUTXOTransaction(id, idx, _, _) if *id == tx_id2 && *idx == 0)));
+    assert!(!monitors.iter().any(|m| matches!(m, TypesToMonitorStore::SpendingUTXOTransaction(id, idx, _, _, _, _, _) if *id == tx_id1 && *idx == 0)));
+    assert!(monitors.iter().any(|m| matches!(m, TypesToMonitorStore::SpendingUTXOTransaction(id, idx, _, _, _, _, _) if *id == tx_id1 && *idx == 1)));
+    assert!(monitors.iter().any(|m| matches!(m, TypesToMonitorStore::SpendingUTXOTransaction(id, idx, _, _, _, _, _) if *id == tx_id2 && *idx == 0)));
 
     // Reactivate
-    store.add_monitor(TypesToMonitor::SpendingUTXOTransaction(
-        tx_id1,
-        0,
-        "extra1_reactivated".to_string(),
-        None,
-    ))?;
+    store.add_monitor(
+        WatchOutpoint::new(tx_id1, 0)
+            .context("extra1_reactivated")
+            .build()?,
+    )?;
 
     // All three should be active again
     let monitors = store.get_monitors()?;
     assert_eq!(monitors.len(), 3);
 
     // Cancel one monitor
-    store.cancel_monitor(TypesToMonitor::SpendingUTXOTransaction(
-        tx_id1,
-        1,
-        "extra2".to_string(),
-        None,
-    ))?;
+    store.cancel_monitor(WatchOutpoint::new(tx_id1, 1).context("extra2").build()?)?;
 
     // Two should remain
     let monitors = store.get_monitors()?;
     assert_eq!(monitors.len(), 2);
 
-    store.cancel_monitor(TypesToMonitor::SpendingUTXOTransaction(
-        tx_id1,
-        0,
-        "extra1_reactivated".to_string(),
-        None,
-    ))?;
+    store.cancel_monitor(
+        WatchOutpoint::new(tx_id1, 0)
+            .context("extra1_reactivated")
+            .build()?,
+    )?;
 
-    store.cancel_monitor(TypesToMonitor::SpendingUTXOTransaction(
-        tx_id2,
-        0,
-        "extra3".to_string(),
-        None,
-    ))?;
+    store.cancel_monitor(WatchOutpoint::new(tx_id2, 0).context("extra3").build()?)?;
 
     let monitors = store.get_monitors()?;
     assert_eq!(monitors.len(), 0);
@@ -517,7 +546,7 @@ fn test_active_inactive_spending_utxo_monitors() -> Result<(), anyhow::Error> {
 fn test_reactivate_monitor() -> Result<(), anyhow::Error> {
     let path = format!("test_outputs/{}", generate_random_string());
     let config = StorageConfig::new(path, None);
-    let storage = Rc::new(Storage::new(&config)?);
+    let storage = Arc::new(Storage::new(&config)?);
     let store = MonitorStore::new(storage)?;
 
     let tx1 = Transaction {
@@ -538,14 +567,14 @@ fn test_reactivate_monitor() -> Result<(), anyhow::Error> {
     let tx_id2 = tx2.compute_txid();
 
     // Test reactivating Transactions monitor
-    let tx_monitor = TypesToMonitor::Transactions(vec![tx_id1], "extra1".to_string(), None);
+    let tx_monitor = WatchTx::new(tx_id1).context("extra1").build()?;
     store.add_monitor(tx_monitor.clone())?;
 
     let monitors = store.get_monitors()?;
     assert_eq!(monitors.len(), 1);
     assert!(monitors
         .iter()
-        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _) if *id == tx_id1)));
+        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _, _) if *id == tx_id1)));
 
     // Deactivate
     store.deactivate_monitor(tx_monitor.clone())?;
@@ -553,40 +582,35 @@ fn test_reactivate_monitor() -> Result<(), anyhow::Error> {
     assert_eq!(monitors.len(), 0);
 
     // Reactivate by calling add_monitor again
-    store.add_monitor(TypesToMonitor::Transactions(
-        vec![tx_id1],
-        "extra1_reactivated".to_string(),
-        None,
-    ))?;
+    store.add_monitor(WatchTx::new(tx_id1).context("extra1_reactivated").build()?)?;
     let monitors = store.get_monitors()?;
     assert_eq!(monitors.len(), 1);
     assert!(monitors
         .iter()
-        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _) if *id == tx_id1)));
+        .any(|m| matches!(m, TypesToMonitorStore::Transaction(id, _, _, _) if *id == tx_id1)));
 
     // Test reactivating RskPeginTransaction monitor
-    store.add_monitor(TypesToMonitor::RskPegin(None))?;
+    store.add_monitor(WatchPegin::new().build()?)?;
     let monitors = store.get_monitors()?;
     assert_eq!(monitors.len(), 2); // tx_id1 + RskPeginTransaction
     assert!(monitors
         .iter()
-        .any(|m| matches!(m, TypesToMonitorStore::RskPegin(_))));
+        .any(|m| matches!(m, TypesToMonitorStore::RskPegin(..))));
 
-    store.deactivate_monitor(TypesToMonitor::RskPegin(None))?;
+    store.deactivate_monitor(WatchPegin::new().build()?)?;
     let monitors = store.get_monitors()?;
     assert_eq!(monitors.len(), 1); // Only tx_id1
 
     // Reactivate
-    store.add_monitor(TypesToMonitor::RskPegin(None))?;
+    store.add_monitor(WatchPegin::new().build()?)?;
     let monitors = store.get_monitors()?;
     assert_eq!(monitors.len(), 2);
     assert!(monitors
         .iter()
-        .any(|m| matches!(m, TypesToMonitorStore::RskPegin(_))));
+        .any(|m| matches!(m, TypesToMonitorStore::RskPegin(..))));
 
     // Test reactivating SpendingUTXOTransaction monitor
-    let utxo_monitor =
-        TypesToMonitor::SpendingUTXOTransaction(tx_id2, 0, "extra2".to_string(), None);
+    let utxo_monitor = WatchOutpoint::new(tx_id2, 0).context("extra2").build()?;
     store.add_monitor(utxo_monitor.clone())?;
     let monitors = store.get_monitors()?;
     assert_eq!(monitors.len(), 3); // tx_id1 + RskPeginTransaction + utxo
@@ -596,15 +620,14 @@ fn test_reactivate_monitor() -> Result<(), anyhow::Error> {
     assert_eq!(monitors.len(), 2); // tx_id1 + RskPeginTransaction
 
     // Reactivate
-    store.add_monitor(TypesToMonitor::SpendingUTXOTransaction(
-        tx_id2,
-        0,
-        "extra2_reactivated".to_string(),
-        None,
-    ))?;
+    store.add_monitor(
+        WatchOutpoint::new(tx_id2, 0)
+            .context("extra2_reactivated")
+            .build()?,
+    )?;
     let monitors = store.get_monitors()?;
     assert_eq!(monitors.len(), 3);
-    assert!(monitors.iter().any(|m| matches!(m, TypesToMonitorStore::SpendingUTXOTransaction(id, idx, _, _) if *id == tx_id2 && *idx == 0)));
+    assert!(monitors.iter().any(|m| matches!(m, TypesToMonitorStore::SpendingUTXOTransaction(id, idx, _, _, _, _, _) if *id == tx_id2 && *idx == 0)));
 
     // Test reactivating NewBlock monitor
     store.add_monitor(TypesToMonitor::NewBlock)?;
@@ -633,7 +656,7 @@ fn test_reactivate_monitor() -> Result<(), anyhow::Error> {
 fn test_multiple_entries_same_txid() -> Result<(), anyhow::Error> {
     let path = format!("test_outputs/{}", generate_random_string());
     let config = StorageConfig::new(path, None);
-    let storage = Rc::new(Storage::new(&config)?);
+    let storage = Arc::new(Storage::new(&config)?);
     let store = MonitorStore::new(storage)?;
 
     let tx1 = Transaction {
@@ -646,21 +669,24 @@ fn test_multiple_entries_same_txid() -> Result<(), anyhow::Error> {
     let tx_id1 = tx1.compute_txid();
 
     // Add same txid with different extra_data values
-    store.add_monitor(TypesToMonitor::Transactions(
-        vec![tx_id1],
-        "extra1".to_string(),
-        Some(1),
-    ))?;
-    store.add_monitor(TypesToMonitor::Transactions(
-        vec![tx_id1],
-        "extra2".to_string(),
-        Some(2),
-    ))?;
-    store.add_monitor(TypesToMonitor::Transactions(
-        vec![tx_id1],
-        "extra3".to_string(),
-        Some(3),
-    ))?;
+    store.add_monitor(
+        WatchTx::new(tx_id1)
+            .context("extra1")
+            .trigger_at(1)
+            .build()?,
+    )?;
+    store.add_monitor(
+        WatchTx::new(tx_id1)
+            .context("extra2")
+            .trigger_at(2)
+            .build()?,
+    )?;
+    store.add_monitor(
+        WatchTx::new(tx_id1)
+            .context("extra3")
+            .trigger_at(3)
+            .build()?,
+    )?;
 
     // All three entries should be present
     let monitors = store.get_monitors()?;
@@ -669,7 +695,7 @@ fn test_multiple_entries_same_txid() -> Result<(), anyhow::Error> {
     let tx_monitors: Vec<_> = monitors
         .iter()
         .filter_map(|m| match m {
-            TypesToMonitorStore::Transaction(id, extra, conf) if *id == tx_id1 => {
+            TypesToMonitorStore::Transaction(id, extra, conf, _) if *id == tx_id1 => {
                 Some((extra.clone(), *conf))
             }
             _ => None,
@@ -688,17 +714,18 @@ fn test_multiple_entries_same_txid() -> Result<(), anyhow::Error> {
         .any(|(e, c)| e == "extra3" && *c == Some(3)));
 
     // Update existing entry with same extra_data should update confirmation_trigger
-    store.add_monitor(TypesToMonitor::Transactions(
-        vec![tx_id1],
-        "extra1".to_string(),
-        Some(10),
-    ))?;
+    store.add_monitor(
+        WatchTx::new(tx_id1)
+            .context("extra1")
+            .trigger_at(10)
+            .build()?,
+    )?;
 
     let monitors = store.get_monitors()?;
     let tx_monitors: Vec<_> = monitors
         .iter()
         .filter_map(|m| match m {
-            TypesToMonitorStore::Transaction(id, extra, conf) if *id == tx_id1 => {
+            TypesToMonitorStore::Transaction(id, extra, conf, _) if *id == tx_id1 => {
                 Some((extra.clone(), *conf))
             }
             _ => None,
@@ -726,7 +753,7 @@ fn test_multiple_entries_same_txid() -> Result<(), anyhow::Error> {
 fn test_transaction_trigger_sent() -> Result<(), anyhow::Error> {
     let path = format!("test_outputs/{}", generate_random_string());
     let config = StorageConfig::new(path, None);
-    let storage = Rc::new(Storage::new(&config)?);
+    let storage = Arc::new(Storage::new(&config)?);
     let store = MonitorStore::new(storage)?;
 
     let tx1 = Transaction {
@@ -739,40 +766,42 @@ fn test_transaction_trigger_sent() -> Result<(), anyhow::Error> {
     let tx_id1 = tx1.compute_txid();
 
     // Add monitor with extra_data
-    store.add_monitor(TypesToMonitor::Transactions(
-        vec![tx_id1],
-        "extra1".to_string(),
-        Some(1),
-    ))?;
+    store.add_monitor(
+        WatchTx::new(tx_id1)
+            .context("extra1")
+            .trigger_at(1)
+            .build()?,
+    )?;
 
     // Initially trigger_sent should be false
     let trigger_sent = store.get_transaction_trigger_sent(tx_id1, "extra1")?;
-    assert_eq!(trigger_sent, false);
+    assert!(!trigger_sent);
 
     // Update trigger_sent to true
     store.update_transaction_trigger_sent(tx_id1, "extra1", true)?;
     let trigger_sent = store.get_transaction_trigger_sent(tx_id1, "extra1")?;
-    assert_eq!(trigger_sent, true);
+    assert!(trigger_sent);
 
     // Add another entry with different extra_data
-    store.add_monitor(TypesToMonitor::Transactions(
-        vec![tx_id1],
-        "extra2".to_string(),
-        Some(2),
-    ))?;
+    store.add_monitor(
+        WatchTx::new(tx_id1)
+            .context("extra2")
+            .trigger_at(2)
+            .build()?,
+    )?;
 
     // extra2 should have trigger_sent = false
     let trigger_sent = store.get_transaction_trigger_sent(tx_id1, "extra2")?;
-    assert_eq!(trigger_sent, false);
+    assert!(!trigger_sent);
 
     // extra1 should still be true
     let trigger_sent = store.get_transaction_trigger_sent(tx_id1, "extra1")?;
-    assert_eq!(trigger_sent, true);
+    assert!(trigger_sent);
 
     // Update extra2 trigger_sent
     store.update_transaction_trigger_sent(tx_id1, "extra2", true)?;
     let trigger_sent = store.get_transaction_trigger_sent(tx_id1, "extra2")?;
-    assert_eq!(trigger_sent, true);
+    assert!(trigger_sent);
 
     // Test error case - non-existent txid
     let non_existent_txid =
@@ -788,12 +817,254 @@ fn test_transaction_trigger_sent() -> Result<(), anyhow::Error> {
     Ok(())
 }
 
+/// This test verifies get_last_reported and update_last_reported, and that the reported state is
+/// dropped when the monitor is moved to a replacing transaction
+#[test]
+fn test_transaction_last_reported() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let tx_id1 =
+        Txid::from_str("1111111111111111111111111111111111111111111111111111111111111111")?;
+    let tx_id2 =
+        Txid::from_str("2222222222222222222222222222222222222222222222222222222222222222")?;
+    let block_hash =
+        BlockHash::from_str("0000000000000000000000000000000000000000000000000000000000000001")?;
+
+    store.add_monitor(WatchTx::new(tx_id1).context("extra1").build()?)?;
+    store.add_monitor(WatchTx::new(tx_id1).context("extra2").build()?)?;
+
+    // Nothing reported yet
+    assert_eq!(store.get_last_reported(tx_id1, "extra1")?, None);
+
+    // Only the entry updated keeps the state
+    store.update_last_reported(tx_id1, "extra1", 2, Some(block_hash))?;
+    assert_eq!(
+        store.get_last_reported(tx_id1, "extra1")?,
+        Some((2, Some(block_hash)))
+    );
+    assert_eq!(store.get_last_reported(tx_id1, "extra2")?, None);
+
+    // Orphaned
+    store.update_last_reported(tx_id1, "extra1", 2, None)?;
+    assert_eq!(store.get_last_reported(tx_id1, "extra1")?, Some((2, None)));
+
+    // Replaced by another transaction
+    store.replace_transaction_monitor(tx_id1, tx_id2, block_hash, 100)?;
+    assert_eq!(store.get_last_reported(tx_id2, "extra1")?, None);
+
+    clear_output();
+    Ok(())
+}
+
+/// This test verifies the trash of canceled monitors: a restored monitor replaces the entry
+/// registered again with the same context, restoring by id leaves the other entries, the pegin
+/// and new block monitors come back active and a permanent cancel keeps nothing
+#[test]
+fn test_trash_restore_and_purge() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let tx_id1 =
+        Txid::from_str("1111111111111111111111111111111111111111111111111111111111111111")?;
+    let block_hash =
+        BlockHash::from_str("0000000000000000000000000000000000000000000000000000000000000001")?;
+
+    store.update_monitor_height(100)?;
+    store.add_monitor(
+        WatchTx::new(tx_id1)
+            .context("extra1")
+            .trigger_at(2)
+            .build()?,
+    )?;
+    store.update_last_reported(tx_id1, "extra1", 2, Some(block_hash))?;
+    store.add_monitor(WatchPegin::new().trigger_at(6).build()?)?;
+    store.add_monitor(TypesToMonitor::NewBlock)?;
+
+    // Canceling a monitor that is not registered keeps nothing
+    store.cancel_monitor(WatchTx::new(tx_id1).context("missing").build()?)?;
+    assert!(store.get_trash()?.is_empty());
+
+    store.cancel_monitor(WatchTx::new(tx_id1).context("extra1").build()?)?;
+    store.update_monitor_height(101)?;
+    store.cancel_monitor(WatchPegin::new().build()?)?;
+    store.cancel_monitor(TypesToMonitor::NewBlock)?;
+    assert!(store.get_monitors()?.is_empty());
+
+    let trash = store.get_trash()?;
+    assert_eq!(
+        trash
+            .iter()
+            .map(|entry| (entry.id, entry.deleted_at))
+            .collect::<Vec<_>>(),
+        vec![(0, 100), (1, 101), (2, 101)]
+    );
+
+    // Registered again with another trigger, the restored entry replaces it
+    store.add_monitor(
+        WatchTx::new(tx_id1)
+            .context("extra1")
+            .trigger_at(4)
+            .build()?,
+    )?;
+    let restored = store.restore_from_trash(&TrashSelector::Id(0))?;
+    assert_eq!(restored, trash[..1]);
+    assert_eq!(
+        store.get_monitors()?,
+        vec![TypesToMonitorStore::Transaction(
+            tx_id1,
+            "extra1".into(),
+            Some(2),
+            None,
+        )]
+    );
+    assert_eq!(
+        store.get_last_reported(tx_id1, "extra1")?,
+        Some((2, Some(block_hash)))
+    );
+    assert_eq!(store.get_trash()?, trash[1..]);
+
+    store.restore_from_trash(&TrashSelector::All)?;
+    assert_eq!(store.get_monitors()?.len(), 3);
+    assert!(store.get_trash()?.is_empty());
+
+    // Purged by deletion height
+    store.cancel_monitor(TypesToMonitor::NewBlock)?;
+    store.update_monitor_height(102)?;
+    store.cancel_monitor(WatchPegin::new().build()?)?;
+    assert_eq!(store.purge_trash(102)?, 1);
+    assert_eq!(store.get_trash()?.len(), 1);
+    assert_eq!(store.get_trash()?[0].id, 4);
+
+    // Permanent cancel
+    store.cancel_monitor_permanent(
+        WatchTx::new(tx_id1).context("extra1").build()?,
+        CancelNewsPolicy::KeepNews,
+    )?;
+    assert!(store.get_monitors()?.is_empty());
+    assert_eq!(store.get_trash()?.len(), 1);
+
+    clear_output();
+    Ok(())
+}
+
+/// Test that an export loads into another store:
+/// 1. Register monitors of each kind, deactivate one, record a spender and set metadata
+/// 2. Export as JSON and import into a fresh store, which lists the same monitors
+/// 3. Import into a store with a monitor of the same context, kept without overwrite and replaced
+///    with it
+#[test]
+fn test_export_import_monitors() -> Result<(), anyhow::Error> {
+    let new_store = || -> Result<MonitorStore, anyhow::Error> {
+        let path = format!("test_outputs/{}", generate_random_string());
+        let storage = Arc::new(Storage::new(&StorageConfig::new(path, None))?);
+        Ok(MonitorStore::new(storage)?)
+    };
+    let store = new_store()?;
+
+    let tx_id1 =
+        Txid::from_str("1111111111111111111111111111111111111111111111111111111111111111")?;
+    let tx_id2 =
+        Txid::from_str("2222222222222222222222222222222222222222222222222222222222222222")?;
+    let spender =
+        Txid::from_str("3333333333333333333333333333333333333333333333333333333333333333")?;
+
+    // 1. Monitors with state
+    store.update_monitor_height(100)?;
+    store.add_monitors(vec![
+        WatchTx::new(tx_id1)
+            .context("extra1")
+            .trigger_at(2)
+            .build()?,
+        WatchTx::new(tx_id2).context("done").build()?,
+        WatchOutpoint::new(tx_id1, 1).context("spend").build()?,
+        WatchPegin::new().trigger_at(6).build()?,
+        WatchScript::new(ScriptBuf::from_bytes(vec![0x51]))
+            .context("script")
+            .build()?,
+        WatchOpReturn::new(b"TAG".to_vec()).context("tag").build()?,
+        TypesToMonitor::NewBlock,
+    ])?;
+    store.deactivate_monitor(WatchTx::new(tx_id2).context("done").build()?)?;
+    store.update_spending_utxo_monitor((tx_id1, 1, Some(spender)))?;
+    store.set_monitor_metadata(
+        vec![SnoozeTarget::Transaction(tx_id1, "extra1".into())],
+        BTreeMap::from([("instance".to_string(), "7".to_string())]),
+    )?;
+
+    // 2. Round trip
+    let json = serde_json::to_string(&store.export_monitors()?)?;
+    let export: MonitorExport = serde_json::from_str(&json)?;
+    assert_eq!(export.version, MONITOR_EXPORT_VERSION);
+
+    let imported = new_store()?;
+    imported.import_monitors(export.clone(), false)?;
+    assert_eq!(
+        imported.get_monitor_infos(true)?,
+        store.get_monitor_infos(true)?
+    );
+    assert_eq!(imported.get_outpoints_spent_by(spender)?, vec![(tx_id1, 1)]);
+    assert!(imported.has_pending_work()?);
+
+    let unsupported = MonitorExport {
+        version: MONITOR_EXPORT_VERSION + 1,
+        ..export.clone()
+    };
+    assert!(new_store()?.import_monitors(unsupported, false).is_err());
+
+    // 3. Non-empty store
+    let existing = new_store()?;
+    existing.add_monitors(vec![
+        WatchTx::new(tx_id1)
+            .context("extra1")
+            .trigger_at(4)
+            .build()?,
+        WatchTx::new(tx_id1).context("local").build()?,
+    ])?;
+
+    let triggers = |store: &MonitorStore| -> Result<Vec<(String, Option<u32>)>, anyhow::Error> {
+        Ok(store
+            .get_monitors()?
+            .into_iter()
+            .filter_map(|monitor| match monitor {
+                TypesToMonitorStore::Transaction(tx_id, context, trigger, _) if tx_id == tx_id1 => {
+                    Some((context.to_string(), trigger))
+                }
+                _ => None,
+            })
+            .collect())
+    };
+
+    existing.import_monitors(export.clone(), false)?;
+    assert_eq!(
+        triggers(&existing)?,
+        vec![("extra1".to_string(), Some(4)), ("local".to_string(), None)]
+    );
+    assert_eq!(
+        existing.get_monitors()?.len(),
+        store.get_monitors()?.len() + 1
+    );
+
+    existing.import_monitors(export, true)?;
+    assert_eq!(
+        triggers(&existing)?,
+        vec![("local".to_string(), None), ("extra1".to_string(), Some(2))]
+    );
+
+    clear_output();
+    Ok(())
+}
+
 /// This test verifies update_spending_utxo_monitor and multiple entries for same (txid, vout)
 #[test]
 fn test_spending_utxo_multiple_entries_and_update() -> Result<(), anyhow::Error> {
     let path = format!("test_outputs/{}", generate_random_string());
     let config = StorageConfig::new(path, None);
-    let storage = Rc::new(Storage::new(&config)?);
+    let storage = Arc::new(Storage::new(&config)?);
     let store = MonitorStore::new(storage)?;
 
     let tx1 = Transaction {
@@ -814,18 +1085,18 @@ fn test_spending_utxo_multiple_entries_and_update() -> Result<(), anyhow::Error>
     let tx_id2 = tx2.compute_txid();
 
     // Add same (txid, vout) with different extra_data values
-    store.add_monitor(TypesToMonitor::SpendingUTXOTransaction(
-        tx_id1,
-        0,
-        "extra1".to_string(),
-        Some(1),
-    ))?;
-    store.add_monitor(TypesToMonitor::SpendingUTXOTransaction(
-        tx_id1,
-        0,
-        "extra2".to_string(),
-        Some(2),
-    ))?;
+    store.add_monitor(
+        WatchOutpoint::new(tx_id1, 0)
+            .context("extra1")
+            .trigger_at(1)
+            .build()?,
+    )?;
+    store.add_monitor(
+        WatchOutpoint::new(tx_id1, 0)
+            .context("extra2")
+            .trigger_at(2)
+            .build()?,
+    )?;
 
     // Both entries should be present
     let monitors = store.get_monitors()?;
@@ -839,30 +1110,30 @@ fn test_spending_utxo_multiple_entries_and_update() -> Result<(), anyhow::Error>
     assert_eq!(monitors.len(), 2);
 
     // Update existing entry with same extra_data should preserve spender_tx_id
-    store.add_monitor(TypesToMonitor::SpendingUTXOTransaction(
-        tx_id1,
-        0,
-        "extra1".to_string(),
-        Some(10),
-    ))?;
+    store.add_monitor(
+        WatchOutpoint::new(tx_id1, 0)
+            .context("extra1")
+            .trigger_at(10)
+            .build()?,
+    )?;
 
     // Verify both entries still exist and confirmation trigger is updated
     let monitors = store.get_monitors()?;
     assert_eq!(monitors.len(), 2);
-    assert!(monitors.iter().any(|m| matches!(m, TypesToMonitorStore::SpendingUTXOTransaction(id, vout, extra, conf) if *id == tx_id1 && *vout == 0 && *extra == "extra1" && *conf == Some(10))));
-    assert!(monitors.iter().any(|m| matches!(m, TypesToMonitorStore::SpendingUTXOTransaction(id, vout, extra, conf) if *id == tx_id1 && *vout == 0 && *extra == "extra2" && *conf == Some(2))));
+    assert!(monitors.iter().any(|m| matches!(m, TypesToMonitorStore::SpendingUTXOTransaction(id, vout, extra, conf, _, _, _) if *id == tx_id1 && *vout == 0 && *extra == "extra1" && *conf == Some(10))));
+    assert!(monitors.iter().any(|m| matches!(m, TypesToMonitorStore::SpendingUTXOTransaction(id, vout, extra, conf, _, _, _) if *id == tx_id1 && *vout == 0 && *extra == "extra2" && *conf == Some(2))));
 
     // Should still have 2 entries (extra1 updated, extra2 unchanged)
     let monitors = store.get_monitors()?;
     assert_eq!(monitors.len(), 2);
 
     // Add new entry with different extra_data should have spender_tx_id = None initially
-    store.add_monitor(TypesToMonitor::SpendingUTXOTransaction(
-        tx_id1,
-        0,
-        "extra3".to_string(),
-        Some(3),
-    ))?;
+    store.add_monitor(
+        WatchOutpoint::new(tx_id1, 0)
+            .context("extra3")
+            .trigger_at(3)
+            .build()?,
+    )?;
 
     // Now should have 3 entries
     let monitors = store.get_monitors()?;
@@ -877,7 +1148,7 @@ fn test_spending_utxo_multiple_entries_and_update() -> Result<(), anyhow::Error>
 fn test_edge_cases_non_existent_entries() -> Result<(), anyhow::Error> {
     let path = format!("test_outputs/{}", generate_random_string());
     let config = StorageConfig::new(path, None);
-    let storage = Rc::new(Storage::new(&config)?);
+    let storage = Arc::new(Storage::new(&config)?);
     let store = MonitorStore::new(storage)?;
 
     let tx1 = Transaction {
@@ -890,29 +1161,17 @@ fn test_edge_cases_non_existent_entries() -> Result<(), anyhow::Error> {
     let tx_id1 = tx1.compute_txid();
 
     // Add a monitor
-    store.add_monitor(TypesToMonitor::Transactions(
-        vec![tx_id1],
-        "extra1".to_string(),
-        None,
-    ))?;
+    store.add_monitor(WatchTx::new(tx_id1).context("extra1").build()?)?;
 
     // Try to deactivate with wrong extra_data - should not fail, just do nothing
-    store.deactivate_monitor(TypesToMonitor::Transactions(
-        vec![tx_id1],
-        "wrong_extra".to_string(),
-        None,
-    ))?;
+    store.deactivate_monitor(WatchTx::new(tx_id1).context("wrong_extra").build()?)?;
 
     // Monitor should still be active
     let monitors = store.get_monitors()?;
     assert_eq!(monitors.len(), 1);
 
     // Try to cancel with wrong extra_data - should not fail, just do nothing
-    store.cancel_monitor(TypesToMonitor::Transactions(
-        vec![tx_id1],
-        "wrong_extra".to_string(),
-        None,
-    ))?;
+    store.cancel_monitor(WatchTx::new(tx_id1).context("wrong_extra").build()?)?;
 
     // Monitor should still be active
     let monitors = store.get_monitors()?;
@@ -921,16 +1180,8 @@ fn test_edge_cases_non_existent_entries() -> Result<(), anyhow::Error> {
     // Try to deactivate/cancel non-existent txid - should not fail
     let non_existent_txid =
         Txid::from_str("ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff")?;
-    store.deactivate_monitor(TypesToMonitor::Transactions(
-        vec![non_existent_txid],
-        "extra1".to_string(),
-        None,
-    ))?;
-    store.cancel_monitor(TypesToMonitor::Transactions(
-        vec![non_existent_txid],
-        "extra1".to_string(),
-        None,
-    ))?;
+    store.deactivate_monitor(WatchTx::new(non_existent_txid).context("extra1").build()?)?;
+    store.cancel_monitor(WatchTx::new(non_existent_txid).context("extra1").build()?)?;
 
     // Original monitor should still be active
     let monitors = store.get_monitors()?;
@@ -945,7 +1196,7 @@ fn test_edge_cases_non_existent_entries() -> Result<(), anyhow::Error> {
 fn test_update_entry_resets_trigger_sent() -> Result<(), anyhow::Error> {
     let path = format!("test_outputs/{}", generate_random_string());
     let config = StorageConfig::new(path, None);
-    let storage = Rc::new(Storage::new(&config)?);
+    let storage = Arc::new(Storage::new(&config)?);
     let store = MonitorStore::new(storage)?;
 
     let tx1 = Transaction {
@@ -958,25 +1209,845 @@ fn test_update_entry_resets_trigger_sent() -> Result<(), anyhow::Error> {
     let tx_id1 = tx1.compute_txid();
 
     // Add monitor
-    store.add_monitor(TypesToMonitor::Transactions(
-        vec![tx_id1],
-        "extra1".to_string(),
-        Some(1),
-    ))?;
+    store.add_monitor(
+        WatchTx::new(tx_id1)
+            .context("extra1")
+            .trigger_at(1)
+            .build()?,
+    )?;
 
     // Set trigger_sent to true
     store.update_transaction_trigger_sent(tx_id1, "extra1", true)?;
-    assert_eq!(store.get_transaction_trigger_sent(tx_id1, "extra1")?, true);
+    assert!(store.get_transaction_trigger_sent(tx_id1, "extra1")?);
 
     // Update the entry with same extra_data - should reset trigger_sent to false
+    store.add_monitor(
+        WatchTx::new(tx_id1)
+            .context("extra1")
+            .trigger_at(10)
+            .build()?,
+    )?;
+
+    // trigger_sent should be reset to false
+    assert!(!store.get_transaction_trigger_sent(tx_id1, "extra1")?);
+
+    clear_output();
+    Ok(())
+}
+
+/// Test that add_monitors stores every monitor of the batch and keeps the same
+/// update semantics as add_monitor for repeated (txid, extra_data) entries.
+#[test]
+fn test_add_monitors_batch() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let tx_id1 =
+        Txid::from_str("0000000000000000000000000000000000000000000000000000000000000001")?;
+    let tx_id2 =
+        Txid::from_str("0000000000000000000000000000000000000000000000000000000000000002")?;
+
+    store.add_monitor(WatchTx::new(tx_id1).context("existing").build()?)?;
+
+    store.add_monitors(vec![
+        WatchTx::many(vec![tx_id1, tx_id2])
+            .context("extra1")
+            .build()?,
+        WatchTx::new(tx_id1)
+            .context("extra1")
+            .trigger_at(5)
+            .build()?,
+        WatchOutpoint::new(tx_id2, 0).context("extra2").build()?,
+        WatchOutpoint::new(tx_id2, 1).context("extra2").build()?,
+        WatchPegin::new().build()?,
+        TypesToMonitor::NewBlock,
+    ])?;
+
+    let monitors = store.get_monitors()?;
+    assert_eq!(monitors.len(), 7);
+    assert!(monitors.contains(&TypesToMonitorStore::Transaction(
+        tx_id1,
+        "existing".into(),
+        None,
+        None,
+    )));
+    // The later item of the batch overrides the trigger of the same entry
+    assert!(monitors.contains(&TypesToMonitorStore::Transaction(
+        tx_id1,
+        "extra1".into(),
+        Some(5),
+        None,
+    )));
+    assert!(monitors.contains(&TypesToMonitorStore::Transaction(
+        tx_id2,
+        "extra1".into(),
+        None,
+        None,
+    )));
+    assert!(
+        monitors.contains(&TypesToMonitorStore::SpendingUTXOTransaction(
+            tx_id2,
+            0,
+            "extra2".into(),
+            None,
+            None,
+            None,
+            None,
+        ))
+    );
+    assert!(
+        monitors.contains(&TypesToMonitorStore::SpendingUTXOTransaction(
+            tx_id2,
+            1,
+            "extra2".into(),
+            None,
+            None,
+            None,
+            None,
+        ))
+    );
+    assert!(monitors.contains(&TypesToMonitorStore::RskPegin(None, vec![], None, None)));
+    assert!(monitors.contains(&TypesToMonitorStore::NewBlock));
+
+    // An empty batch is a no-op
+    store.add_monitors(vec![])?;
+    assert_eq!(store.get_monitors()?.len(), 7);
+
+    clear_output();
+    Ok(())
+}
+
+/// Test that sweep_expired removes exactly the keys whose expiry was reached:
+/// 1. Write keys of several families with short expiries and one key without expiry
+/// 2. Sweep below, at and above the expiries and check the removed keys
+/// 3. Re-setting an expiry replaces the previous one
+#[test]
+fn test_sweep_expired_keys() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage.clone())?;
+
+    // 1. Write keys
+    store.set_with_expiry("monitor/quarantine/a", "a", 102)?;
+    store.set_with_expiry("monitor/journal/b", "b", 105)?;
+    store.set_with_expiry("monitor/nonce/c", "c", 105)?;
+    store.set_with_expiry("monitor/block_snapshot/d", "d", 110)?;
+    storage.set("monitor/other", "kept", None)?;
+
+    let exists =
+        |key: &str| -> Result<bool, anyhow::Error> { Ok(storage.get::<_, String>(key)?.is_some()) };
+
+    // 2. Sweep
+    assert_eq!(store.sweep_expired(101)?, 0);
+    assert!(exists("monitor/quarantine/a")?);
+
+    assert_eq!(store.sweep_expired(102)?, 1);
+    assert!(!exists("monitor/quarantine/a")?);
+    assert!(exists("monitor/journal/b")?);
+
+    assert_eq!(store.sweep_expired(107)?, 2);
+    assert!(!exists("monitor/journal/b")?);
+    assert!(!exists("monitor/nonce/c")?);
+    assert!(exists("monitor/block_snapshot/d")?);
+
+    // Already swept keys are not counted again
+    assert_eq!(store.sweep_expired(107)?, 0);
+
+    // 3. Extend the expiry of the remaining key
+    store.set_expiry("monitor/block_snapshot/d", 120)?;
+    assert_eq!(store.sweep_expired(115)?, 0);
+    assert!(exists("monitor/block_snapshot/d")?);
+
+    assert_eq!(store.sweep_expired(120)?, 1);
+    assert!(!exists("monitor/block_snapshot/d")?);
+    assert!(exists("monitor/other")?);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that a value that doesn't match the type stored under its key is reported with the key
+/// and the expected type:
+/// 1. Write a string where the list of active transaction monitors is stored
+/// 2. Reading the monitors fails with a CorruptedEntry naming the key and the type
+#[test]
+fn test_monitor_store_corrupted_entry() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage.clone())?;
+
+    // 1. Incompatible value
+    storage.set("monitor/tx/list/active", "not a list", None)?;
+
+    // 2. Error names the key and the type
+    let error = store.get_monitors().unwrap_err();
+    match &error {
+        MonitorStoreError::CorruptedEntry {
+            key, expected_type, ..
+        } => {
+            assert_eq!(key, "monitor/tx/list/active");
+            assert!(expected_type.contains("TransactionMonitor"));
+        }
+        other => panic!("unexpected error: {other:?}"),
+    }
+    let message = error.to_string();
+    assert!(message.contains("monitor/tx/list/active"));
+    assert!(message.contains("TransactionMonitor"));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that each monitor is stored under its own key, so changing a monitor doesn't read or
+/// write the others:
+/// 1. Register three transactions and corrupt the stored monitor of the second one
+/// 2. Update, deactivate, reactivate and cancel the others, and register a new one
+/// 3. Only listing every monitor reads the corrupted one
+/// 4. Once repaired, the monitors are listed in the order they were added to the active list
+#[test]
+fn test_monitor_store_per_monitor_keys() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage.clone())?;
+
+    let txid = |n: u8| Txid::from_str(&format!("{:064x}", n)).unwrap();
+
+    // 1. Corrupted monitor
+    for n in 1..=3 {
+        store.add_monitor(WatchTx::new(txid(n)).context("a").build()?)?;
+    }
+    let corrupted_key = format!("monitor/tx/active/{}", txid(2));
+    let stored = storage
+        .read(&corrupted_key)?
+        .expect("monitor stored under its own key");
+    storage.set(&corrupted_key, "not a monitor", None)?;
+
+    // 2. Changes to the other monitors
+    store.update_transaction_trigger_sent(txid(1), "a", true)?;
+    store.update_last_reported(txid(1), "a", 1, None)?;
+    assert!(store.get_transaction_trigger_sent(txid(1), "a")?);
+    store.deactivate_monitor(WatchTx::new(txid(1)).context("a").build()?)?;
+    store.reactivate_monitor(WatchTx::new(txid(1)).context("a").build()?)?;
+    store.cancel_monitor(WatchTx::new(txid(3)).context("a").build()?)?;
+    store.add_monitor(WatchTx::new(txid(4)).context("a").build()?)?;
+
+    // 3. Listing
+    match store.get_monitors().unwrap_err() {
+        MonitorStoreError::CorruptedEntry { key, .. } => assert_eq!(key, corrupted_key),
+        other => panic!("unexpected error: {other:?}"),
+    }
+
+    // 4. Order
+    storage.write(&corrupted_key, &stored)?;
+    let tx_ids: Vec<Txid> = store
+        .get_monitors()?
+        .into_iter()
+        .map(|monitor| match monitor {
+            TypesToMonitorStore::Transaction(tx_id, ..) => tx_id,
+            other => panic!("unexpected monitor: {other:?}"),
+        })
+        .collect();
+    assert_eq!(tx_ids, vec![txid(2), txid(1), txid(4)]);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that the chain log keeps the last blocks appended, up to its cap:
+/// 1. Five blocks appended with a cap of 3, the two oldest are dropped
+/// 2. A block appended again right after itself is logged once
+#[test]
+fn test_chain_log_cap() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let hash = |height: u32| BlockHash::from_str(&format!("{:064x}", height)).unwrap();
+    let entry = |height: u32| ChainLogEntry {
+        height,
+        hash: hash(height),
+        prev_hash: hash(height - 1),
+        processed_at_unix: 1_700_000_000 + height as u64,
+    };
+
+    // 1. Cap
+    for height in 1..=5 {
+        store.append_chain_log(entry(height), 3)?;
+    }
+    assert_eq!(store.get_chain_log()?, vec![entry(3), entry(4), entry(5)]);
+
+    // 2. Same block
+    store.append_chain_log(entry(5), 3)?;
+    assert_eq!(store.get_chain_log()?, vec![entry(3), entry(4), entry(5)]);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test the typed context of a monitor and that it is stored as the strings of older versions:
+/// 1. A typed context gives back its uuid, label and payload
+/// 2. Any other string is a label, without uuid nor payload
+/// 3. A monitor registered with a typed context is stored with the context as a plain string
+/// 4. A monitor stored by an older version is read with its context as it was
+#[test]
+fn test_monitor_context() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage.clone())?;
+
+    let txid = |n: u8| Txid::from_str(&format!("{:064x}", n)).unwrap();
+    let stored_context = |tx_id: Txid| -> Result<serde_json::Value, anyhow::Error> {
+        let stored: serde_json::Value = storage
+            .get(format!("monitor/tx/active/{}", tx_id))?
+            .expect("monitor stored under its own key");
+        Ok(stored["entries"][0]["extra_data"].clone())
+    };
+
+    // 1. Typed
+    let uuid = Uuid::new_v4();
+    let context = MonitorContext::typed(uuid, "pegout:round 2", &[0, 1, 0xff]);
+    assert_eq!(context.uuid(), Some(uuid));
+    assert_eq!(context.label(), "pegout:round 2");
+    assert_eq!(context.payload(), vec![0, 1, 0xff]);
+
+    // 2. Plain string
+    let plain = MonitorContext::from("pegout");
+    assert_eq!(plain.uuid(), None);
+    assert_eq!(plain.label(), "pegout");
+    assert!(plain.payload().is_empty());
+    assert_eq!(plain, "pegout");
+
+    // 3. Stored as a string
+    store.add_monitor(WatchTx::new(txid(1)).context(context.clone()).build()?)?;
+    assert_eq!(
+        stored_context(txid(1))?,
+        serde_json::Value::String(context.to_string())
+    );
+    assert_eq!(
+        store.get_monitors()?,
+        vec![TypesToMonitorStore::Transaction(
+            txid(1),
+            context.clone(),
+            None,
+            None,
+        )]
+    );
+
+    // 4. Older versions
+    store.add_monitor(WatchTx::new(txid(2)).context("legacy").build()?)?;
+    let key = format!("monitor/tx/active/{}", txid(2));
+    let mut stored: serde_json::Value = storage.get(&key)?.expect("monitor stored");
+    stored["entries"][0]["extra_data"] = serde_json::json!("legacy context");
+    storage.set(&key, stored, None)?;
+
+    let store = MonitorStore::new(storage)?;
+    let monitors = store.get_monitors()?;
+    assert!(monitors.contains(&TypesToMonitorStore::Transaction(
+        txid(2),
+        "legacy context".into(),
+        None,
+        None,
+    )));
+    assert!(monitors.contains(&TypesToMonitorStore::Transaction(
+        txid(1),
+        context,
+        None,
+        None
+    )));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that a txid registered twice is stored and reported once:
+/// 1. Registering `a, a, b` stores a single entry of `a`, with a single registration in its
+///    history
+/// 2. A tick finding `a` sends a single news for it
+/// 3. A list stored by an older version with `a` twice is moved to per-item keys with `a` once
+#[test]
+fn test_duplicate_txids_in_registration() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage.clone())?;
+
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_consensus(1),
+        input: vec![],
+        output: vec![],
+    };
+    let a = tx.compute_txid();
+    let b = Txid::from_str(&format!("{:064x}", 2))?;
+
+    // 1. Stored once
     store.add_monitor(TypesToMonitor::Transactions(
-        vec![tx_id1],
-        "extra1".to_string(),
-        Some(10),
+        vec![a, a, b],
+        "ctx".into(),
+        None,
+        None,
+    ))?;
+    assert_eq!(
+        store.get_monitors()?,
+        vec![
+            TypesToMonitorStore::Transaction(a, "ctx".into(), None, None),
+            TypesToMonitorStore::Transaction(b, "ctx".into(), None, None),
+        ]
+    );
+    let history = store.get_monitor_history(&TypesToMonitorStore::Transaction(
+        a,
+        "ctx".into(),
+        None,
+        None,
     ))?;
+    assert_eq!(history.len(), 1);
+
+    // 2. Reported once
+    let block = |height: u32| FullBlock {
+        height,
+        hash: BlockHash::from_str(&format!("{:064x}", height)).unwrap(),
+        prev_hash: BlockHash::from_str(&format!("{:064x}", height.saturating_sub(1))).unwrap(),
+        txs: vec![],
+        orphan: false,
+        estimated_fee_rate: 0,
+    };
+    let mut mock_indexer = MockIndexerApi::new();
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(block(200))));
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |height| Ok(Some(block(height))));
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    mock_indexer.expect_get_tx().returning(move |tx_id| {
+        Ok((*tx_id == a).then(|| TransactionInfo {
+            tx: tx.clone(),
+            block_info: block(200),
+            confirmations: 1,
+        }))
+    });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+    monitor.tick()?;
+    assert!(matches!(
+        &monitor.get_news()?[..],
+        [MonitorNews::Transaction(tx_id, _, _)] if *tx_id == a
+    ));
 
-    // trigger_sent should be reset to false
-    assert_eq!(store.get_transaction_trigger_sent(tx_id1, "extra1")?, false);
+    // 3. Older versions
+    let legacy_storage = Arc::new(Storage::new(&StorageConfig::new(
+        format!("test_outputs/{}", generate_random_string()),
+        None,
+    ))?);
+    let entry = |context: &str| {
+        serde_json::json!({
+            "extra_data": context,
+            "confirmation_trigger": null,
+            "trigger_sent": false,
+        })
+    };
+    legacy_storage.set(
+        "monitor/tx/list/active",
+        serde_json::json!([
+            { "tx_id": a, "entries": [entry("ctx"), entry("ctx")] },
+            { "tx_id": b, "entries": [entry("ctx")] },
+            { "tx_id": a, "entries": [entry("ctx"), entry("other")] },
+        ]),
+        None,
+    )?;
+
+    let legacy = MonitorStore::new(legacy_storage.clone())?;
+    assert_eq!(
+        legacy.get_monitors()?,
+        vec![
+            TypesToMonitorStore::Transaction(a, "ctx".into(), None, None),
+            TypesToMonitorStore::Transaction(a, "other".into(), None, None),
+            TypesToMonitorStore::Transaction(b, "ctx".into(), None, None),
+        ]
+    );
+    let index: Vec<String> = legacy_storage
+        .get("monitor/tx/index/active")?
+        .expect("index written");
+    assert_eq!(index, vec![a.to_string(), b.to_string()]);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// This test verifies find_monitors_for_tx returns the active monitors referencing a txid:
+/// 1. A Transaction monitor of the txid
+/// 2. A SpendingUTXO monitor of one of its outputs
+/// 3. A SpendingUTXO monitor whose recorded spender is the txid, not the other contexts of it
+/// 4. Deactivated monitors and monitors of other txids are left out
+#[test]
+fn test_find_monitors_for_tx() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+
+    let tx = |time: u32| Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_time(time).unwrap(),
+        input: vec![],
+        output: vec![],
+    };
+    let tx_id = tx(1653195600).compute_txid();
+    let funding_id = tx(1653195601).compute_txid();
+    let other_id = tx(1653195602).compute_txid();
+
+    // Only the spent entry has tx_id as its spender, the one added after it has none yet
+    let store = StoreFixture::new()
+        .active_tx(tx_id)
+        .context("direct")
+        .active_spending(OutPoint::new(tx_id, 1))
+        .context("output")
+        .active_spending(OutPoint::new(funding_id, 0))
+        .context("spent")
+        .spent_by(tx_id)
+        .active_tx(other_id)
+        .context("other")
+        .active_spending(OutPoint::new(other_id, 0))
+        .context("other")
+        .spent_by(funding_id)
+        .active_spending(OutPoint::new(funding_id, 0))
+        .context("unrelated")
+        .build(storage)?;
+
+    let monitors = store.find_monitors_for_tx(tx_id)?;
+    assert_eq!(monitors.len(), 3);
+    assert!(matches!(
+        &monitors[0],
+        TypesToMonitorStore::Transaction(id, extra, ..) if *id == tx_id && *extra == "direct"
+    ));
+    assert!(matches!(
+        &monitors[1],
+        TypesToMonitorStore::SpendingUTXOTransaction(id, 1, extra, ..) if *id == tx_id && *extra == "output"
+    ));
+    assert!(matches!(
+        &monitors[2],
+        TypesToMonitorStore::SpendingUTXOTransaction(id, 0, extra, ..) if *id == funding_id && *extra == "spent"
+    ));
+
+    // Deactivated monitors are not returned
+    store.deactivate_monitor(WatchTx::new(tx_id).context("direct").build()?)?;
+    store.deactivate_monitor(WatchOutpoint::new(tx_id, 1).context("output").build()?)?;
+
+    let monitors = store.find_monitors_for_tx(tx_id)?;
+    assert_eq!(monitors.len(), 1);
+    assert!(matches!(
+        &monitors[0],
+        TypesToMonitorStore::SpendingUTXOTransaction(id, 0, extra, ..) if *id == funding_id && *extra == "spent"
+    ));
+
+    // Unknown txids have no monitors
+    assert!(store
+        .find_monitors_for_tx(tx(1653195603).compute_txid())?
+        .is_empty());
+
+    clear_output();
+    Ok(())
+}
+
+/// This test verifies prune removes the inactive monitors idle since before the height and the
+/// acknowledged news:
+/// 1. Store at 100 with an active monitor, monitors deactivated at 150 and 250, a canceled one
+///    with an unacked news and a spent SpendingUTXO monitor deactivated at 150, and snapshots of
+///    the active, old and recent transactions
+/// 2. Prune below 200, the monitors deactivated at 150 and the canceled one go with the acked news,
+///    the spend index and the snapshot of the old transaction, the unacked news stays
+/// 3. Pruning again removes nothing
+#[test]
+fn test_prune_inactive_monitors_and_acked_news() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+
+    let tx = |time: u32| Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_time(time).unwrap(),
+        input: vec![],
+        output: vec![],
+    };
+    let tx_id = |time: u32| tx(time).compute_txid();
+    let (active, old, recent, canceled, funding, spender) = (
+        tx_id(1653195600),
+        tx_id(1653195601),
+        tx_id(1653195602),
+        tx_id(1653195603),
+        tx_id(1653195604),
+        tx_id(1653195605),
+    );
+    let block_hash = BlockHash::from_str(&format!("{:064x}", 100))?;
+    let deactivated_at = |height| Deactivation::MaxConfirmations {
+        height,
+        reason: DeactivationReason::NewsAcked,
+    };
+
+    // 1. Store at 100
+    let store = StoreFixture::new()
+        .at_height(100, block_hash)
+        .active_tx(active)
+        .context("active")
+        .inactive_tx(old, deactivated_at(150))
+        .context("old")
+        .with_news(block_hash, true)
+        .inactive_tx(recent, deactivated_at(250))
+        .context("recent")
+        .inactive_tx(canceled, Deactivation::Requested)
+        .context("canceled")
+        .with_news(block_hash, false)
+        .inactive_spending(OutPoint::new(funding, 0), deactivated_at(150))
+        .context("spent")
+        .spent_by(spender)
+        .build(storage)?;
+    for time in [1653195600, 1653195601, 1653195602] {
+        store.save_tx_snapshot(TransactionStatus {
+            tx_id: tx_id(time),
+            tx: tx(time),
+            block_info: None,
+            confirmations: 0,
+            status: TransactionBlockchainStatus::Mempool,
+        })?;
+    }
+
+    // 2. Prune below 200
+    let report = store.prune(200)?;
+    assert_eq!(
+        report,
+        PruneReport {
+            transactions: 2,
+            spending_utxos: 1,
+            news: 1,
+            tx_snapshots: 1,
+        }
+    );
+
+    let export = store.export_monitors()?;
+    let transactions: Vec<(bool, Txid)> = export
+        .transactions
+        .iter()
+        .map(|(is_active, monitor)| (*is_active, monitor.tx_id))
+        .collect();
+    assert_eq!(transactions, vec![(true, active), (false, recent)]);
+    assert!(export.spending_utxos.is_empty());
+    assert!(store.get_outpoints_spent_by(spender)?.is_empty());
+    let snapshots: Vec<Txid> = store
+        .get_tx_snapshots()?
+        .iter()
+        .map(|snapshot| snapshot.tx_id)
+        .collect();
+    assert_eq!(snapshots, vec![active, recent]);
+    assert!(store.get_tx_snapshot(&old)?.is_none());
+
+    let news = store.get_news_acks()?;
+    assert_eq!(news.len(), 1);
+    assert!(!news[0].1.acknowledged);
+
+    // 3. Nothing left to prune
+    assert_eq!(store.prune(200)?, PruneReport::default());
+
+    clear_output();
+    Ok(())
+}
+
+/// This test verifies an expired snooze keeps its held back news until it is ended:
+/// 1. A news of a snoozed transaction is held back
+/// 2. Reading the expired snoozes leaves them in the store, as after a crash before the news
+///    are written
+/// 3. Ending the snooze removes it with its news, the others are kept
+#[test]
+fn test_expired_snooze_kept_until_ended() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let tx_id = Txid::from_str("0000000000000000000000000000000000000000000000000000000000000001")?;
+    let expired = SnoozeTarget::Transaction(tx_id, "a".into());
+    let active = SnoozeTarget::Transaction(tx_id, "b".into());
+    store.snooze_monitor(expired.clone(), 203)?;
+    store.snooze_monitor(active.clone(), 210)?;
+
+    // 1. Held back
+    assert!(store.defer_news(MonitoredTypes::Transaction(tx_id, "a".into()))?);
+
+    // 2. Read only
+    for _ in 0..2 {
+        let snoozes = store.get_expired_snoozes(204)?;
+        assert_eq!(snoozes.len(), 1);
+        assert_eq!(snoozes[0].target, expired);
+        assert_eq!(snoozes[0].pending.len(), 1);
+    }
+    assert_eq!(store.get_snoozes()?.len(), 2);
+
+    // 3. Ended
+    store.end_snooze(&expired)?;
+    assert!(store.get_expired_snoozes(204)?.is_empty());
+    let snoozes = store.get_snoozes()?;
+    assert_eq!(snoozes.len(), 1);
+    assert_eq!(snoozes[0].target, active);
+
+    clear_output();
+    Ok(())
+}
+
+/// This test verifies the fingerprints guarding news and detections against a reprocessed block:
+/// 1. A detection checked but not recorded yet, as after a crash before `record_detection`, is
+///    found again; once recorded it is only new for another block
+/// 2. Each fingerprint is stored under its own key, a news written again for the same block is
+///    a no-op reported as not written
+/// 3. Pruning below a height drops the fingerprints recorded below it, with the acked news they
+///    guarded, and leaves the recent ones. A detection is listed at the height of its block, even
+///    when recorded with the monitor height above it
+#[test]
+fn test_fingerprints_recorded_per_id_and_pruned() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage.clone())?;
+
+    let tx_id = |time: u32| {
+        Transaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: LockTime::from_time(time).unwrap(),
+            input: vec![],
+            output: vec![],
+        }
+        .compute_txid()
+    };
+    let (funding, spender, old, recent) = (
+        tx_id(1653195600),
+        tx_id(1653195601),
+        tx_id(1653195602),
+        tx_id(1653195603),
+    );
+    let block_100 = BlockHash::from_str(&format!("{:064x}", 100))?;
+    let block_250 = BlockHash::from_str(&format!("{:064x}", 250))?;
+
+    // 1. Recorded last
+    store.update_monitor_height(100)?;
+    let detection = MonitoredTypes::SpendingUTXOTransaction(funding, 0, "a".into(), spender);
+    assert!(!store.is_detection_recorded(&detection, block_100)?);
+    assert!(!store.is_detection_recorded(&detection, block_100)?);
+    store.record_detection(detection.clone(), block_100, 100)?;
+    assert!(store.is_detection_recorded(&detection, block_100)?);
+    assert!(!store.is_detection_recorded(&detection, block_250)?);
+
+    // 2. Per id
+    assert!(store.update_news(MonitoredTypes::Transaction(old, "a".into()), block_100)?);
+    assert!(!store.update_news(MonitoredTypes::Transaction(old, "a".into()), block_100)?);
+    assert_eq!(store.get_news()?.len(), 1);
+    assert!(storage.has_key(&format!("monitor/fingerprints/id/news/tx/{old}/a"))?);
+    assert!(!storage.has_key("monitor/fingerprints")?);
+
+    // 3. Pruned
+    store.ack_news(AckMonitorNews::Transaction(old, "a".into()))?;
+    store.update_monitor_height(250)?;
+    store.update_news(MonitoredTypes::Transaction(recent, "a".into()), block_250)?;
+    let late = MonitoredTypes::SpendingUTXOTransaction(funding, 1, "a".into(), spender);
+    store.record_detection(late.clone(), block_100, 100)?;
+    store.prune(200)?;
+
+    assert!(!storage.has_key(&format!("monitor/fingerprints/id/news/tx/{old}/a"))?);
+    assert!(!store.is_detection_recorded(&detection, block_100)?);
+    assert!(!store.is_detection_recorded(&late, block_100)?);
+    assert!(storage.has_key(&format!("monitor/fingerprints/id/news/tx/{recent}/a"))?);
+    assert_eq!(
+        store.get_news()?,
+        vec![MonitoredTypes::Transaction(recent, "a".into())]
+    );
+
+    clear_output();
+    Ok(())
+}
+
+/// This test verifies the monitor cursors read the active monitors of a kind a page at a time:
+/// 1. The cursor only counts the stored items, one per txid whatever its contexts
+/// 2. Read one item per page, the monitors come in the canonical order of the whole set
+/// 3. An item canceled after the cursor was created is skipped with an empty page
+/// 4. Kinds kept under a single key are one item, empty when not registered
+#[test]
+fn test_monitor_cursor() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let tx_id = |time: u32| {
+        Transaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: LockTime::from_time(time).unwrap(),
+            input: vec![],
+            output: vec![],
+        }
+        .compute_txid()
+    };
+    let tx_ids = [tx_id(1653195600), tx_id(1653195601), tx_id(1653195602)];
+
+    store.add_monitor(WatchTx::new(tx_ids[0]).context("b").build()?)?;
+    store.add_monitor(WatchTx::new(tx_ids[1]).context("a").build()?)?;
+    store.add_monitor(WatchTx::new(tx_ids[2]).context("a").build()?)?;
+    store.add_monitor(WatchTx::new(tx_ids[0]).context("a").build()?)?;
+    store.add_monitor(WatchOutpoint::new(tx_ids[0], 0).context("a").build()?)?;
+
+    // 1. Counted by item
+    let mut cursor = store.monitor_cursor(MonitorKind::Transaction)?;
+    assert_eq!(cursor.kind(), MonitorKind::Transaction);
+    assert_eq!(cursor.remaining(), 3);
+
+    // 2. Canonical order
+    let mut expected: Vec<TypesToMonitorStore> = store
+        .get_monitors()?
+        .into_iter()
+        .filter(|monitor| monitor.kind() == MonitorKind::Transaction)
+        .collect();
+    expected.sort_by(TypesToMonitorStore::canonical_cmp);
+
+    let mut read = vec![];
+    while !cursor.is_done() {
+        read.extend(store.next_monitors(&mut cursor, 1)?);
+    }
+    assert_eq!(read, expected);
+    assert!(store.next_monitors(&mut cursor, 1)?.is_empty());
+
+    // 3. Canceled after the cursor was created
+    let mut cursor = store.monitor_cursor(MonitorKind::Transaction)?;
+    let first = store.next_monitors(&mut cursor, 1)?;
+    let last_target = expected.last().unwrap().target();
+    for monitor in expected.iter().filter(|m| m.target() == last_target) {
+        if let TypesToMonitorStore::Transaction(id, context, ..) = monitor {
+            store.cancel_monitor(WatchTx::new(*id).context(context.as_str()).build()?)?;
+        }
+    }
+    let mut rest = vec![];
+    while !cursor.is_done() {
+        rest.push(store.next_monitors(&mut cursor, 1)?);
+    }
+    assert_eq!(first, expected[..first.len()]);
+    assert!(rest.last().unwrap().is_empty());
+
+    // 4. Single key kinds
+    let mut cursor = store.monitor_cursor(MonitorKind::NewBlock)?;
+    assert_eq!(cursor.remaining(), 1);
+    assert!(store.next_monitors(&mut cursor, 10)?.is_empty());
+    assert!(cursor.is_done());
 
     clear_output();
     Ok(())
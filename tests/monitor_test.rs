@@ -2,10 +2,10 @@ use bitcoin::{
     absolute::LockTime,
     hex::FromHex,
     key::{rand::thread_rng, Secp256k1},
-    opcodes::all::OP_RETURN,
+    opcodes::all::{OP_CHECKMULTISIG, OP_PUSHNUM_1, OP_RETURN},
     script::Builder,
     secp256k1::PublicKey,
-    Address, Amount, BlockHash, Network, Transaction, TxOut,
+    Address, Amount, BlockHash, Network, ScriptBuf, Transaction, TxOut, Txid,
 };
 use bitcoin_indexer::{
     indexer::MockIndexerApi,
@@ -13,14 +13,33 @@ use bitcoin_indexer::{
 };
 use bitvmx_transaction_monitor::{
     config::{MonitorSettings, MonitorSettingsConfig},
+    errors::MonitorError,
+    mempool::MockMempoolApi,
+    migrations::logical_snapshot,
     monitor::Monitor,
-    store::{MonitorStore, MonitorStoreApi, TypesToMonitorStore},
-    types::{AckMonitorNews, MonitorNews, TypesToMonitor},
+    store::{MockMonitorStore, MonitorStore, MonitorStoreApi, MonitoredTypes, TypesToMonitorStore},
+    testing::fixture::{Deactivation, StoreFixture},
+    types::{
+        AckMonitorNews, AddressFilterStats, CancelNewsPolicy, DeactivationReason,
+        DetectionDescriptor, MonitorContext, MonitorEvent, MonitorInfo, MonitorNews,
+        MovedTransaction, OutputFilter, RegistrationOptions, Resolution, SnoozeTarget,
+        TransactionBlockchainStatus, TransactionStatus, TrashSelector, TxState, TypesToMonitor,
+    },
+    WatchOpReturn, WatchOutpoint, WatchPegin, WatchScript, WatchTx,
 };
 use mockall::predicate::*;
-use std::{rc::Rc, str::FromStr};
-use storage_backend::{storage::Storage, storage_config::StorageConfig};
-use utils::{clear_output, generate_random_string};
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicBool, AtomicU32, Ordering},
+        Arc,
+    },
+};
+use storage_backend::{
+    storage::{KeyValueStore, Storage},
+    storage_config::StorageConfig,
+};
+use utils::{block_at, clear_output, generate_random_string, scripted_indexer};
 mod utils;
 
 fn create_pegin_tx() -> Transaction {
@@ -62,7 +81,7 @@ fn create_pegin_tx() -> Transaction {
         value: Amount::ZERO,
         script_pubkey: Builder::new()
             .push_opcode(OP_RETURN)
-            .push_slice(&data)
+            .push_slice(data)
             .into_script(),
     };
 
@@ -79,7 +98,7 @@ fn no_monitors() -> Result<(), anyhow::Error> {
     let mut mock_indexer = MockIndexerApi::new();
     let path = format!("test_outputs/{}", generate_random_string());
     let config = StorageConfig::new(path, None);
-    let storage = Rc::new(Storage::new(&config)?);
+    let storage = Arc::new(Storage::new(&config)?);
     let store = MonitorStore::new(storage)?;
 
     let best_block_100 = FullBlock {
@@ -101,8 +120,13 @@ fn no_monitors() -> Result<(), anyhow::Error> {
 
     mock_indexer
         .expect_get_block_by_height()
+        .with(eq(100))
         .returning(move |_| Ok(Some(best_block_100_clone.clone())));
 
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |_| Ok(None));
+
     mock_indexer.expect_tick().returning(move || Ok(()));
 
     mock_indexer
@@ -126,7 +150,7 @@ fn monitor_txs_detected() -> Result<(), anyhow::Error> {
     let mut mock_indexer = MockIndexerApi::new();
     let path = format!("test_outputs/{}", generate_random_string());
     let config = StorageConfig::new(path, None);
-    let storage = Rc::new(Storage::new(&config)?);
+    let storage = Arc::new(Storage::new(&config)?);
     let store = MonitorStore::new(storage)?;
 
     let block_height_200 = 200;
@@ -223,16 +247,8 @@ fn monitor_txs_detected() -> Result<(), anyhow::Error> {
         MonitorSettings::from(MonitorSettingsConfig::default()),
     )?;
 
-    monitor.save_monitor(TypesToMonitor::Transactions(
-        vec![tx_id],
-        "test".to_string(),
-        None,
-    ))?;
-    monitor.save_monitor(TypesToMonitor::Transactions(
-        vec![tx_id_2],
-        "test 2".to_string(),
-        None,
-    ))?;
+    monitor.save_monitor(WatchTx::new(tx_id).context("test").build()?)?;
+    monitor.save_monitor(WatchTx::new(tx_id_2).context("test 2").build()?)?;
 
     monitor.tick()?;
 
@@ -250,8 +266,8 @@ fn monitor_txs_detected() -> Result<(), anyhow::Error> {
     }
 
     // Acknowledge the news
-    monitor.ack_news(AckMonitorNews::Transaction(tx_id, "test".to_string()))?;
-    monitor.ack_news(AckMonitorNews::Transaction(tx_id_2, "test 2".to_string()))?;
+    monitor.ack_news(AckMonitorNews::Transaction(tx_id, "test".into()))?;
+    monitor.ack_news(AckMonitorNews::Transaction(tx_id_2, "test 2".into()))?;
 
     // Verify news are gone after acknowledgment
     let news_after_ack = monitor.get_news()?;
@@ -271,7 +287,7 @@ fn test_monitor_deactivation_after_100_confirmations() -> Result<(), anyhow::Err
     let mut mock_indexer = MockIndexerApi::new();
     let path = format!("test_outputs/{}", generate_random_string());
     let config = StorageConfig::new(path, None);
-    let storage = Rc::new(Storage::new(&config)?);
+    let storage = Arc::new(Storage::new(&config)?);
     let store = MonitorStore::new(storage)?;
 
     let tx = Transaction {
@@ -350,8 +366,13 @@ fn test_monitor_deactivation_after_100_confirmations() -> Result<(), anyhow::Err
 
     mock_indexer
         .expect_get_block_by_height()
+        .with(eq(200))
         .returning(move |_| Ok(Some(block_200_clone_1.clone())));
 
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |_| Ok(None));
+
     mock_indexer.expect_tick().returning(move || Ok(()));
 
     let monitor = Monitor::new(
@@ -360,11 +381,7 @@ fn test_monitor_deactivation_after_100_confirmations() -> Result<(), anyhow::Err
         MonitorSettings::from(MonitorSettingsConfig::default()),
     )?;
 
-    monitor.save_monitor(TypesToMonitor::Transactions(
-        vec![tx_id],
-        "test".to_string(),
-        None,
-    ))?;
+    monitor.save_monitor(WatchTx::new(tx_id).context("test").build()?)?;
 
     monitor.tick()?;
 
@@ -377,13 +394,154 @@ fn test_monitor_deactivation_after_100_confirmations() -> Result<(), anyhow::Err
     Ok(())
 }
 
+/// Test the deactivation after max_monitoring_confirmations 8 of two monitors of a transaction
+/// found at 200, with the ack grace of 4 blocks:
+/// 1. Both news are sent at 200 and none is acknowledged
+/// 2. At 207 the transaction reaches 8 confirmations, both monitors stay active and their news
+///    are still returned
+/// 3. The news of "acked" is acknowledged, its monitor is deactivated at 208 for `NewsAcked`
+/// 4. "unacked" stays active until the grace passes at 211, deactivated for `AckGraceExpired`,
+///    and its news is still returned
+#[test]
+fn test_deactivation_waits_for_news_ack() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![],
+        output: vec![],
+    };
+    let tx_id = tx.compute_txid();
+
+    let tip = Arc::new(AtomicU32::new(199));
+    let hash_at = |height: u32| BlockHash::from_str(&format!("{:064x}", height)).unwrap();
+    let block_at = move |height: u32| FullBlock {
+        height,
+        hash: hash_at(height),
+        prev_hash: hash_at(height.saturating_sub(1)),
+        txs: vec![],
+        orphan: false,
+        estimated_fee_rate: 0,
+    };
+
+    let mut mock_indexer = MockIndexerApi::new();
+    let best_tip = tip.clone();
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(block_at(best_tip.load(Ordering::SeqCst)))));
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |height| Ok(Some(block_at(height))));
+    mock_indexer
+        .expect_get_block_by_hash()
+        .returning(move |hash| Ok((190..=220).map(block_at).find(|block| block.hash == *hash)));
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let tx_tip = tip.clone();
+    mock_indexer.expect_get_tx().returning(move |_| {
+        let tip = tx_tip.load(Ordering::SeqCst);
+        if tip < 200 {
+            return Ok(None);
+        }
+        Ok(Some(TransactionInfo {
+            tx: tx.clone(),
+            block_info: block_at(200),
+            confirmations: tip - 200 + 1,
+        }))
+    });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig {
+            max_monitoring_confirmations: Some(8),
+            deactivation_requires_ack_grace_blocks: Some(4),
+            ..Default::default()
+        }),
+    )?;
+
+    let is_active = |context: &str| -> Result<bool, anyhow::Error> {
+        let info = monitor
+            .get_monitors(true)?
+            .into_iter()
+            .find(|info| {
+                matches!(&info.monitor, TypesToMonitorStore::Transaction(id, ctx, ..) if *id == tx_id && ctx == context)
+            })
+            .expect("monitor listed");
+        Ok(info.active)
+    };
+    let has_news = |context: &str| -> Result<bool, anyhow::Error> {
+        Ok(monitor.get_news()?.iter().any(|news| {
+            matches!(news, MonitorNews::Transaction(id, _, ctx) if *id == tx_id && ctx == context)
+        }))
+    };
+
+    // 1. Both news are sent
+    monitor.tick()?;
+    monitor.save_monitor(WatchTx::new(tx_id).context("acked").build()?)?;
+    monitor.save_monitor(WatchTx::new(tx_id).context("unacked").build()?)?;
+    tip.store(200, Ordering::SeqCst);
+    monitor.tick()?;
+    assert!(has_news("acked")?);
+    assert!(has_news("unacked")?);
+
+    // 2. Past max_monitoring_confirmations with the news unacknowledged
+    tip.store(207, Ordering::SeqCst);
+    monitor.tick()?;
+    assert!(is_active("acked")?);
+    assert!(is_active("unacked")?);
+    assert!(has_news("acked")?);
+    assert!(has_news("unacked")?);
+
+    // 3. Deactivated once acknowledged
+    monitor.ack_news(AckMonitorNews::Transaction(tx_id, "acked".into()))?;
+    tip.store(208, Ordering::SeqCst);
+    monitor.tick()?;
+    assert!(!is_active("acked")?);
+    assert!(is_active("unacked")?);
+
+    let target = TypesToMonitorStore::Transaction(tx_id, "acked".into(), None, None);
+    assert_eq!(
+        monitor.monitor_history(&target)?.last(),
+        Some(&MonitorEvent::Deactivated {
+            height: 208,
+            reason: DeactivationReason::NewsAcked,
+        })
+    );
+
+    // 4. Deactivated once the grace passed, the news is still there
+    tip.store(210, Ordering::SeqCst);
+    monitor.tick()?;
+    assert!(is_active("unacked")?);
+
+    tip.store(211, Ordering::SeqCst);
+    monitor.tick()?;
+    assert!(!is_active("unacked")?);
+    assert!(has_news("unacked")?);
+
+    let target = TypesToMonitorStore::Transaction(tx_id, "unacked".into(), None, None);
+    assert_eq!(
+        monitor.monitor_history(&target)?.last(),
+        Some(&MonitorEvent::Deactivated {
+            height: 211,
+            reason: DeactivationReason::AckGraceExpired,
+        })
+    );
+
+    clear_output();
+
+    Ok(())
+}
+
 #[test]
 fn test_inactive_monitors_are_skipped() -> Result<(), anyhow::Error> {
     let mut mock_indexer = MockIndexerApi::new();
     let path = format!("test_outputs/{}", generate_random_string());
     let config = StorageConfig::new(path, None);
-    let storage = Rc::new(Storage::new(&config)?);
-    let store = MonitorStore::new(storage)?;
+    let storage = Arc::new(Storage::new(&config)?);
 
     let tx = Transaction {
         version: bitcoin::transaction::Version::TWO,
@@ -393,16 +551,9 @@ fn test_inactive_monitors_are_skipped() -> Result<(), anyhow::Error> {
     };
 
     let tx_id = tx.compute_txid();
-    store.add_monitor(TypesToMonitor::Transactions(
-        vec![tx_id],
-        String::new(),
-        None,
-    ))?;
-    store.deactivate_monitor(TypesToMonitor::Transactions(
-        vec![tx_id],
-        String::new(),
-        None,
-    ))?;
+    let store = StoreFixture::new()
+        .inactive_tx(tx_id, Deactivation::Requested)
+        .build(storage)?;
 
     let full_block = FullBlock {
         height: 200,
@@ -427,8 +578,13 @@ fn test_inactive_monitors_are_skipped() -> Result<(), anyhow::Error> {
 
     mock_indexer
         .expect_get_block_by_height()
+        .with(eq(200))
         .returning(move |_| Ok(Some(full_block_clone.clone())));
 
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |_| Ok(None));
+
     mock_indexer.expect_tick().returning(move || Ok(()));
 
     let monitor = Monitor::new(
@@ -452,7 +608,7 @@ fn test_rsk_pegin_monitor_not_deactivated() -> Result<(), anyhow::Error> {
     let mut mock_indexer = MockIndexerApi::new();
     let path = format!("test_outputs/{}", generate_random_string());
     let config = StorageConfig::new(path, None);
-    let storage = Rc::new(Storage::new(&config)?);
+    let storage = Arc::new(Storage::new(&config)?);
     let store = MonitorStore::new(storage)?;
 
     let full_block = FullBlock {
@@ -480,8 +636,13 @@ fn test_rsk_pegin_monitor_not_deactivated() -> Result<(), anyhow::Error> {
 
     mock_indexer
         .expect_get_block_by_height()
+        .with(eq(200))
         .returning(move |_| Ok(Some(full_block_clone.clone())));
 
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |_| Ok(None));
+
     mock_indexer.expect_tick().returning(move || Ok(()));
 
     let monitor = Monitor::new(
@@ -489,13 +650,13 @@ fn test_rsk_pegin_monitor_not_deactivated() -> Result<(), anyhow::Error> {
         store,
         MonitorSettings::from(MonitorSettingsConfig::default()),
     )?;
-    monitor.save_monitor(TypesToMonitor::RskPegin(None))?;
+    monitor.save_monitor(WatchPegin::new().build()?)?;
     monitor.tick()?;
 
     // Verify monitor is still active
     let monitors = monitor.store.get_monitors()?;
     assert_eq!(monitors.len(), 1);
-    assert!(matches!(monitors[0], TypesToMonitorStore::RskPegin(_)));
+    assert!(matches!(monitors[0], TypesToMonitorStore::RskPegin(..)));
 
     clear_output();
 
@@ -507,7 +668,7 @@ fn test_best_block_news() -> Result<(), anyhow::Error> {
     let mut mock_indexer = MockIndexerApi::new();
     let path = format!("test_outputs/{}", generate_random_string());
     let config = StorageConfig::new(path, None);
-    let storage = Rc::new(Storage::new(&config)?);
+    let storage = Arc::new(Storage::new(&config)?);
     let store = MonitorStore::new(storage)?;
 
     // Simulate the monitor's current height is 199, but the best block is 200
@@ -581,11 +742,11 @@ fn test_best_block_news() -> Result<(), anyhow::Error> {
     assert_eq!(news.len(), 1);
     assert!(matches!(
         news[0],
-        bitvmx_transaction_monitor::store::MonitoredTypes::NewBlock(hash) if hash == block_200_clone_2.hash
+        bitvmx_transaction_monitor::store::MonitoredTypes::NewBlock(hash, _) if hash == block_200_clone_2.hash
     ));
 
     // Acknowledge the news and verify it's gone
-    monitor.ack_news(AckMonitorNews::NewBlock)?;
+    monitor.ack_news(AckMonitorNews::NewBlock(None))?;
     let news = monitor.store.get_news()?;
     assert_eq!(news.len(), 0);
 
@@ -615,11 +776,7 @@ fn test_best_block_news() -> Result<(), anyhow::Error> {
     .compute_txid();
 
     // Save a monitor for the transaction and set a description "test"
-    monitor.save_monitor(TypesToMonitor::Transactions(
-        vec![tx_id],
-        "test".to_string(),
-        None,
-    ))?;
+    monitor.save_monitor(WatchTx::new(tx_id).context("test").build()?)?;
 
     // Check if there's pending work after saving the transaction monitor; it should be true
     let is_pending_work = monitor.store.has_pending_work()?;
@@ -635,7 +792,7 @@ fn test_spending_utxo_monitor_orphan_handling() -> Result<(), anyhow::Error> {
     let mut mock_indexer = MockIndexerApi::new();
     let path = format!("test_outputs/{}", generate_random_string());
     let config = StorageConfig::new(path, None);
-    let storage = Rc::new(Storage::new(&config)?);
+    let storage = Arc::new(Storage::new(&config)?);
     let store = MonitorStore::new(storage)?;
 
     let target_tx = Transaction {
@@ -746,6 +903,8 @@ fn test_spending_utxo_monitor_orphan_handling() -> Result<(), anyhow::Error> {
 
     // Set up expectations
     let block_100_clone = block_100.clone();
+    let block_100_clone_2 = block_100.clone();
+    let block_100_reorg_clone = block_100_reorg.clone();
 
     // Each tick in the monitor uses 2 get_best_block call but if there is pending work, it will use 1 get_best_block call
     mock_indexer
@@ -759,6 +918,7 @@ fn test_spending_utxo_monitor_orphan_handling() -> Result<(), anyhow::Error> {
         .returning(move |_| Ok(Some(block_100_clone.clone())));
 
     let block_101_clone = block_101.clone();
+    let block_101_clone_2 = block_101.clone();
 
     // Each tick in the monitor uses 2 get_best_block call but if there is pending work, it will use 1 get_best_block call
     mock_indexer
@@ -780,55 +940,43 @@ fn test_spending_utxo_monitor_orphan_handling() -> Result<(), anyhow::Error> {
     // Expect get_tx to be called for the spending transaction
     // First tick: detect spending_tx1, create monitor, and process it
     // - get_tx is called from process_spending_utxo_transaction -> process_transaction_monitor
-    // - get_tx is called from get_news() -> get_tx_status()
+    // - get_news() -> get_tx_status() reads it from the lookups cached by the tick
     mock_indexer
         .expect_get_tx()
         .with(eq(spending_tx1_id))
-        .times(2)
+        .times(1)
         .returning(move |_| Ok(Some(spending_tx1_clone.clone())));
 
     // Second tick: process the spending_tx1 monitor (it now has 2 confirmations)
     // - get_tx is called from process_transaction_monitor
-    // - get_tx is called from get_news() -> get_tx_status()
+    // - get_news() -> get_tx_status() reads it from the lookups cached by the tick
     spending_tx1_clone_2.confirmations = 2;
     mock_indexer
         .expect_get_tx()
         .with(eq(spending_tx1_id))
-        .times(2)
+        .times(1)
         .returning(move |_| Ok(Some(spending_tx1_clone_2.clone())));
 
     // Third tick: reorg detected, spending_tx1 becomes orphan, detect spending_tx2
-    // - get_tx is called for spending_tx1 (to check orphan status)
-    // - get_tx is called from process_spending_utxo_transaction -> process_transaction_monitor for spending_tx2
-    // - get_tx is called from get_news() -> get_tx_status() for spending_tx2
-    mock_indexer
-        .expect_get_tx()
-        .with(eq(spending_tx1_id))
-        .times(1)
-        .returning(move |_| Ok(Some(spending_tx2.clone())));
-
+    // - tx1 and tx2 are the same transaction, so get_tx is called once for the orphan check,
+    //   then process_spending_utxo_transaction and get_news() read the lookup cached by the tick
     mock_indexer
         .expect_get_tx()
         .with(eq(spending_tx2_id))
-        .times(2)
+        .times(1)
         .returning(move |_| Ok(Some(spending_tx2_clone.clone())));
 
     // Handle any other get_tx calls that might happen
     mock_indexer.expect_get_tx().returning(move |_| Ok(None));
 
-    let monitor = Monitor::new(
-        mock_indexer,
-        store,
-        MonitorSettings::from(MonitorSettingsConfig::default()),
-    )?;
+    // get_news resolves the news from the get_tx calls above
+    let mut settings = MonitorSettings::from(MonitorSettingsConfig::default());
+    settings.news_resolution = Resolution::Fresh;
+
+    let monitor = Monitor::new(mock_indexer, store, settings)?;
 
     // Add the SpendingUTXOTransaction monitor
-    monitor.save_monitor(TypesToMonitor::SpendingUTXOTransaction(
-        target_tx_id,
-        target_utxo_index,
-        String::new(),
-        None,
-    ))?;
+    monitor.save_monitor(WatchOutpoint::new(target_tx_id, target_utxo_index).build()?)?;
 
     // First tick - should detect the spending transaction
     monitor.tick()?;
@@ -845,7 +993,7 @@ fn test_spending_utxo_monitor_orphan_handling() -> Result<(), anyhow::Error> {
     monitor.ack_news(AckMonitorNews::SpendingUTXOTransaction(
         target_tx_id,
         target_utxo_index,
-        String::new(),
+        MonitorContext::default(),
     ))?;
 
     // Second tick - should confirm the spending transaction (2 confirmations)
@@ -863,7 +1011,7 @@ fn test_spending_utxo_monitor_orphan_handling() -> Result<(), anyhow::Error> {
     monitor.ack_news(AckMonitorNews::SpendingUTXOTransaction(
         target_tx_id,
         target_utxo_index,
-        String::new(),
+        MonitorContext::default(),
     ))?;
 
     // Third tick - Reorg with block 100, and should detect the new spending transaction tx2
@@ -871,13 +1019,44 @@ fn test_spending_utxo_monitor_orphan_handling() -> Result<(), anyhow::Error> {
 
     let news = monitor.get_news()?;
 
-    assert_eq!(news.len(), 1);
+    assert_eq!(news.len(), 2);
     assert!(matches!(
         news[0].clone(),
         MonitorNews::SpendingUTXOTransaction(t, u, tx_status, _)
             if t == target_tx_id && u == target_utxo_index && tx_status.tx_id == spending_tx2_clone_2.tx.compute_txid() && tx_status.confirmations == 1
     ));
 
+    // The reorg moved the spend (tx1 and tx2 are the same transaction) from block 100 to the new block 100
+    let impact = monitor.last_reorg_impact()?.expect("reorg impact");
+    assert_eq!(
+        news[1],
+        MonitorNews::Reorg {
+            old_tip: block_101_clone_2.hash,
+            new_tip: block_100_reorg_clone.hash,
+            summary: impact.summary(),
+        }
+    );
+    assert_eq!((impact.old_tip_height, impact.new_tip_height), (101, 100));
+    assert_eq!(
+        impact.moved_transactions,
+        vec![MovedTransaction {
+            tx_id: spending_tx1_id,
+            before: block_100_clone_2.hash,
+            after: Some(block_100_reorg_clone.hash),
+        }]
+    );
+    assert!(impact.disappeared_spends.is_empty());
+    assert!(impact.changed_spends.is_empty());
+    assert!(impact.invalidated_pegins.is_empty());
+    assert_eq!(impact.reopened_news.len(), 1);
+    assert_eq!(impact.reopened_news[0].before, block_101_clone_2.hash);
+    assert_eq!(impact.reopened_news[0].after, block_100_reorg_clone.hash);
+
+    monitor.ack_news(AckMonitorNews::Reorg {
+        new_tip: block_100_reorg_clone.hash,
+    })?;
+    assert_eq!(monitor.store.get_news()?.len(), 1);
+
     clear_output();
 
     Ok(())
@@ -891,7 +1070,7 @@ fn test_spending_utxo_monitor_deactivation_after_max_confirmations() -> Result<(
     let mut mock_indexer = MockIndexerApi::new();
     let path = format!("test_outputs/{}", generate_random_string());
     let config = StorageConfig::new(path, None);
-    let storage = Rc::new(Storage::new(&config)?);
+    let storage = Arc::new(Storage::new(&config)?);
     let store = MonitorStore::new(storage)?;
 
     let target_tx = Transaction {
@@ -1022,10 +1201,11 @@ fn test_spending_utxo_monitor_deactivation_after_max_confirmations() -> Result<(
 
     mock_indexer.expect_tick().returning(move || Ok(()));
 
+    // First tick and its news: 1 confirmation
     mock_indexer
         .expect_get_tx()
         .with(eq(spending_tx_id))
-        .times(4)
+        .times(2)
         .returning(move |_| Ok(Some(spending_tx_info_at_100.clone())));
 
     // Second tick: confirmations reach 2, should send news and deactivate
@@ -1041,16 +1221,12 @@ fn test_spending_utxo_monitor_deactivation_after_max_confirmations() -> Result<(
 
     let mut settings = MonitorSettings::from(MonitorSettingsConfig::default());
     settings.max_monitoring_confirmations = 2;
+    settings.news_resolution = Resolution::Fresh;
 
     let monitor = Monitor::new(mock_indexer, store, settings)?;
 
     // Add the SpendingUTXOTransaction monitor
-    monitor.save_monitor(TypesToMonitor::SpendingUTXOTransaction(
-        target_tx_id,
-        target_utxo_index,
-        String::new(),
-        None,
-    ))?;
+    monitor.save_monitor(WatchOutpoint::new(target_tx_id, target_utxo_index).build()?)?;
 
     // Ensure the monitor is initially active
     let monitors = monitor.store.get_monitors()?;
@@ -1069,7 +1245,7 @@ fn test_spending_utxo_monitor_deactivation_after_max_confirmations() -> Result<(
     let has_spending_utxo_monitor = monitors.iter().any(|m| {
         matches!(
             m,
-            TypesToMonitorStore::SpendingUTXOTransaction(t, u, _, _)
+            TypesToMonitorStore::SpendingUTXOTransaction(t, u, _, _, _, _, _)
                 if *t == target_tx_id && *u == target_utxo_index
         )
     });
@@ -1081,7 +1257,7 @@ fn test_spending_utxo_monitor_deactivation_after_max_confirmations() -> Result<(
     let has_transaction_monitor = monitors.iter().any(|m| {
         matches!(
             m,
-            TypesToMonitorStore::Transaction(tx_id, extra_data, _)
+            TypesToMonitorStore::Transaction(tx_id, extra_data, _, _)
                 if *tx_id == spending_tx_id && extra_data.starts_with("INTERNAL_SPENDING_UTXO")
         )
     });
@@ -1101,7 +1277,7 @@ fn test_spending_utxo_monitor_deactivation_after_max_confirmations() -> Result<(
     monitor.ack_news(AckMonitorNews::SpendingUTXOTransaction(
         target_tx_id,
         target_utxo_index,
-        String::new(),
+        MonitorContext::default(),
     ))?;
 
     // Second tick: confirmations reach the threshold; the monitor should send news and then be deactivated
@@ -1119,19 +1295,9 @@ fn test_spending_utxo_monitor_deactivation_after_max_confirmations() -> Result<(
     // The actual deactivation verification will be done in the third tick
     assert!(monitors.len() <= 2, "Should have at most 2 monitors");
 
+    // Reaching max_monitoring_confirmations deactivates the monitors without a new news
     let news = monitor.get_news()?;
-    assert_eq!(news.len(), 1);
-    assert!(matches!(
-        news[0].clone(),
-        MonitorNews::SpendingUTXOTransaction(t, u, _, _)
-            if t == target_tx_id && u == target_utxo_index
-    ));
-
-    monitor.ack_news(AckMonitorNews::SpendingUTXOTransaction(
-        target_tx_id,
-        target_utxo_index,
-        String::new(),
-    ))?;
+    assert_eq!(news.len(), 0);
 
     // Third tick: monitor is already deactivated, so no processing should happen
     monitor.tick()?;
@@ -1153,7 +1319,7 @@ fn test_all_monitors_with_confirmation_trigger() -> Result<(), anyhow::Error> {
         let mut mock_indexer = MockIndexerApi::new();
         let path = format!("test_outputs/{}", generate_random_string());
         let config = StorageConfig::new(path, None);
-        let storage = Rc::new(Storage::new(&config)?);
+        let storage = Arc::new(Storage::new(&config)?);
         let store = MonitorStore::new(storage)?;
 
         let tx = Transaction {
@@ -1269,16 +1435,15 @@ fn test_all_monitors_with_confirmation_trigger() -> Result<(), anyhow::Error> {
         settings.max_monitoring_confirmations = 2;
         let monitor = Monitor::new(mock_indexer, store, settings)?;
 
-        monitor.save_monitor(TypesToMonitor::Transactions(
-            vec![tx_id],
-            String::new(),
-            Some(1),
-        ))?;
+        monitor.save_monitor(WatchTx::new(tx_id).trigger_at(1).build()?)?;
         monitor.tick()?;
         let news = monitor.get_news()?;
         assert_eq!(news.len(), 1);
         assert!(matches!(news[0].clone(), MonitorNews::Transaction(t, _, _) if t == tx_id));
-        monitor.ack_news(AckMonitorNews::Transaction(tx_id, String::new()))?;
+        monitor.ack_news(AckMonitorNews::Transaction(
+            tx_id,
+            MonitorContext::default(),
+        ))?;
         monitor.tick()?;
         let news = monitor.get_news()?;
         assert_eq!(news.len(), 0);
@@ -1292,7 +1457,7 @@ fn test_all_monitors_with_confirmation_trigger() -> Result<(), anyhow::Error> {
         let mut mock_indexer = MockIndexerApi::new();
         let path = format!("test_outputs/{}", generate_random_string());
         let config = StorageConfig::new(path, None);
-        let storage = Rc::new(Storage::new(&config)?);
+        let storage = Arc::new(Storage::new(&config)?);
         let store = MonitorStore::new(storage)?;
 
         let pegin_tx = create_pegin_tx();
@@ -1388,12 +1553,12 @@ fn test_all_monitors_with_confirmation_trigger() -> Result<(), anyhow::Error> {
         settings.max_monitoring_confirmations = 2;
         let monitor = Monitor::new(mock_indexer, store, settings)?;
 
-        monitor.save_monitor(TypesToMonitor::RskPegin(Some(1)))?;
+        monitor.save_monitor(WatchPegin::new().trigger_at(1).build()?)?;
         monitor.tick()?;
         let news = monitor.get_news()?;
         assert_eq!(news.len(), 1);
         assert!(
-            matches!(news[0].clone(), MonitorNews::RskPeginTransaction(t, _) if t == pegin_tx_id_from_block)
+            matches!(news[0].clone(), MonitorNews::RskPeginTransaction(t, ..) if t == pegin_tx_id_from_block)
         );
         monitor.ack_news(AckMonitorNews::RskPeginTransaction(pegin_tx_id_from_block))?;
         monitor.tick()?;
@@ -1402,10 +1567,10 @@ fn test_all_monitors_with_confirmation_trigger() -> Result<(), anyhow::Error> {
         monitor.tick()?;
         let monitors = monitor.store.get_monitors()?;
         assert_eq!(monitors.len(), 2);
-        assert!(matches!(monitors[1], TypesToMonitorStore::RskPegin(_)));
+        assert!(matches!(monitors[1], TypesToMonitorStore::RskPegin(..)));
         assert!(matches!(
             monitors[0],
-            TypesToMonitorStore::Transaction(_, _, _)
+            TypesToMonitorStore::Transaction(_, _, _, _)
         ));
     }
 
@@ -1414,7 +1579,7 @@ fn test_all_monitors_with_confirmation_trigger() -> Result<(), anyhow::Error> {
         let mut mock_indexer = MockIndexerApi::new();
         let path = format!("test_outputs/{}", generate_random_string());
         let config = StorageConfig::new(path, None);
-        let storage = Rc::new(Storage::new(&config)?);
+        let storage = Arc::new(Storage::new(&config)?);
         let store = MonitorStore::new(storage)?;
 
         let target_tx = Transaction {
@@ -1487,7 +1652,7 @@ fn test_all_monitors_with_confirmation_trigger() -> Result<(), anyhow::Error> {
 
         let spending_tx_info_at_101 = TransactionInfo {
             tx: spending_tx.clone(),
-            block_info: block_102.clone(),
+            block_info: block_with_spending_tx.clone(),
             confirmations: 2,
         };
 
@@ -1547,12 +1712,11 @@ fn test_all_monitors_with_confirmation_trigger() -> Result<(), anyhow::Error> {
         let monitor = Monitor::new(mock_indexer, store, settings)?;
 
         // Add the SpendingUTXOTransaction monitor with confirmation trigger 1
-        monitor.save_monitor(TypesToMonitor::SpendingUTXOTransaction(
-            target_tx_id,
-            target_utxo_index,
-            String::new(),
-            Some(1),
-        ))?;
+        monitor.save_monitor(
+            WatchOutpoint::new(target_tx_id, target_utxo_index)
+                .trigger_at(1)
+                .build()?,
+        )?;
 
         monitor.tick()?;
         let monitors = monitor.store.get_monitors()?;
@@ -1565,7 +1729,7 @@ fn test_all_monitors_with_confirmation_trigger() -> Result<(), anyhow::Error> {
         let has_spending_utxo_monitor = monitors.iter().any(|m| {
             matches!(
                 m,
-                TypesToMonitorStore::SpendingUTXOTransaction(t, u, _, _)
+                TypesToMonitorStore::SpendingUTXOTransaction(t, u, _, _, _, _, _)
                     if *t == target_tx_id && *u == target_utxo_index
             )
         });
@@ -1577,7 +1741,7 @@ fn test_all_monitors_with_confirmation_trigger() -> Result<(), anyhow::Error> {
         let has_transaction_monitor = monitors.iter().any(|m| {
             matches!(
                 m,
-                TypesToMonitorStore::Transaction(tx_id, extra_data, _)
+                TypesToMonitorStore::Transaction(tx_id, extra_data, _, _)
                     if *tx_id == spending_tx_id && extra_data.starts_with("INTERNAL_SPENDING_UTXO")
             )
         });
@@ -1597,7 +1761,7 @@ fn test_all_monitors_with_confirmation_trigger() -> Result<(), anyhow::Error> {
         monitor.ack_news(AckMonitorNews::SpendingUTXOTransaction(
             target_tx_id,
             target_utxo_index,
-            String::new(),
+            MonitorContext::default(),
         ))?;
 
         monitor.tick()?;
@@ -1617,7 +1781,7 @@ fn test_all_monitors_without_confirmation_trigger() -> Result<(), anyhow::Error>
         let mut mock_indexer = MockIndexerApi::new();
         let path = format!("test_outputs/{}", generate_random_string());
         let config = StorageConfig::new(path, None);
-        let storage = Rc::new(Storage::new(&config)?);
+        let storage = Arc::new(Storage::new(&config)?);
         let store = MonitorStore::new(storage)?;
 
         let tx = Transaction {
@@ -1719,7 +1883,7 @@ fn test_all_monitors_without_confirmation_trigger() -> Result<(), anyhow::Error>
         mock_indexer
             .expect_get_tx()
             .with(eq(tx_id))
-            .times(2)
+            .times(1)
             .returning(move |_| Ok(Some(tx_info_1_conf_clone.clone())));
         let tx_info_2_conf_clone = tx_info_2_conf.clone();
         mock_indexer
@@ -1731,18 +1895,18 @@ fn test_all_monitors_without_confirmation_trigger() -> Result<(), anyhow::Error>
 
         let mut settings = MonitorSettings::from(MonitorSettingsConfig::default());
         settings.max_monitoring_confirmations = 2;
+        settings.news_resolution = Resolution::Fresh;
         let monitor = Monitor::new(mock_indexer, store, settings)?;
 
-        monitor.save_monitor(TypesToMonitor::Transactions(
-            vec![tx_id],
-            String::new(),
-            None,
-        ))?;
+        monitor.save_monitor(WatchTx::new(tx_id).build()?)?;
         monitor.tick()?;
         let news = monitor.get_news()?;
         assert_eq!(news.len(), 1);
         assert!(matches!(news[0].clone(), MonitorNews::Transaction(t, _, _) if t == tx_id));
-        monitor.ack_news(AckMonitorNews::Transaction(tx_id, String::new()))?;
+        monitor.ack_news(AckMonitorNews::Transaction(
+            tx_id,
+            MonitorContext::default(),
+        ))?;
         monitor.tick()?;
         let news = monitor.get_news()?;
         assert_eq!(news.len(), 0);
@@ -1757,7 +1921,7 @@ fn test_all_monitors_without_confirmation_trigger() -> Result<(), anyhow::Error>
         let mut mock_indexer = MockIndexerApi::new();
         let path = format!("test_outputs/{}", generate_random_string());
         let config = StorageConfig::new(path, None);
-        let storage = Rc::new(Storage::new(&config)?);
+        let storage = Arc::new(Storage::new(&config)?);
         let store = MonitorStore::new(storage)?;
 
         let pegin_tx = create_pegin_tx();
@@ -1845,20 +2009,21 @@ fn test_all_monitors_without_confirmation_trigger() -> Result<(), anyhow::Error>
         mock_indexer
             .expect_get_tx()
             .with(eq(pegin_tx_id_from_block))
-            .times(2)
+            .times(1)
             .returning(move |_| Ok(Some(tx_info_1_conf_clone.clone())));
         mock_indexer.expect_get_tx().returning(move |_| Ok(None));
 
         let mut settings = MonitorSettings::from(MonitorSettingsConfig::default());
         settings.max_monitoring_confirmations = 2;
+        settings.news_resolution = Resolution::Fresh;
         let monitor = Monitor::new(mock_indexer, store, settings)?;
 
-        monitor.save_monitor(TypesToMonitor::RskPegin(None))?;
+        monitor.save_monitor(WatchPegin::new().build()?)?;
         monitor.tick()?;
         let news = monitor.get_news()?;
         assert_eq!(news.len(), 1);
         assert!(
-            matches!(news[0].clone(), MonitorNews::RskPeginTransaction(t, _) if t == pegin_tx_id_from_block)
+            matches!(news[0].clone(), MonitorNews::RskPeginTransaction(t, ..) if t == pegin_tx_id_from_block)
         );
         monitor.ack_news(AckMonitorNews::RskPeginTransaction(pegin_tx_id_from_block))?;
         monitor.tick()?;
@@ -1867,10 +2032,10 @@ fn test_all_monitors_without_confirmation_trigger() -> Result<(), anyhow::Error>
         monitor.tick()?;
         let monitors = monitor.store.get_monitors()?;
         assert_eq!(monitors.len(), 2);
-        assert!(matches!(monitors[1], TypesToMonitorStore::RskPegin(_)));
+        assert!(matches!(monitors[1], TypesToMonitorStore::RskPegin(..)));
         assert!(matches!(
             monitors[0],
-            TypesToMonitorStore::Transaction(_, _, _)
+            TypesToMonitorStore::Transaction(_, _, _, _)
         ));
     }
 
@@ -1879,7 +2044,7 @@ fn test_all_monitors_without_confirmation_trigger() -> Result<(), anyhow::Error>
         let mut mock_indexer = MockIndexerApi::new();
         let path = format!("test_outputs/{}", generate_random_string());
         let config = StorageConfig::new(path, None);
-        let storage = Rc::new(Storage::new(&config)?);
+        let storage = Arc::new(Storage::new(&config)?);
         let store = MonitorStore::new(storage)?;
 
         let target_tx = Transaction {
@@ -2002,20 +2167,16 @@ fn test_all_monitors_without_confirmation_trigger() -> Result<(), anyhow::Error>
         mock_indexer
             .expect_get_tx()
             .with(eq(spending_tx_id))
-            .times(2)
+            .times(1)
             .returning(move |_| Ok(Some(spending_tx_info_at_101_clone.clone())));
         mock_indexer.expect_get_tx().returning(move |_| Ok(None));
 
         let mut settings = MonitorSettings::from(MonitorSettingsConfig::default());
         settings.max_monitoring_confirmations = 2;
+        settings.news_resolution = Resolution::Fresh;
         let monitor = Monitor::new(mock_indexer, store, settings)?;
 
-        monitor.save_monitor(TypesToMonitor::SpendingUTXOTransaction(
-            target_tx_id,
-            target_utxo_index,
-            String::new(),
-            None,
-        ))?;
+        monitor.save_monitor(WatchOutpoint::new(target_tx_id, target_utxo_index).build()?)?;
         monitor.tick()?;
         let monitors = monitor.store.get_monitors()?;
         // After detecting the spending transaction, we should have:
@@ -2027,7 +2188,7 @@ fn test_all_monitors_without_confirmation_trigger() -> Result<(), anyhow::Error>
         let has_spending_utxo_monitor = monitors.iter().any(|m| {
             matches!(
                 m,
-                TypesToMonitorStore::SpendingUTXOTransaction(t, u, _, _)
+                TypesToMonitorStore::SpendingUTXOTransaction(t, u, _, _, _, _, _)
                     if *t == target_tx_id && *u == target_utxo_index
             )
         });
@@ -2039,7 +2200,7 @@ fn test_all_monitors_without_confirmation_trigger() -> Result<(), anyhow::Error>
         let has_transaction_monitor = monitors.iter().any(|m| {
             matches!(
                 m,
-                TypesToMonitorStore::Transaction(tx_id, extra_data, _)
+                TypesToMonitorStore::Transaction(tx_id, extra_data, _, _)
                     if *tx_id == spending_tx_id && extra_data.starts_with("INTERNAL_SPENDING_UTXO")
             )
         });
@@ -2056,7 +2217,7 @@ fn test_all_monitors_without_confirmation_trigger() -> Result<(), anyhow::Error>
         monitor.ack_news(AckMonitorNews::SpendingUTXOTransaction(
             target_tx_id,
             target_utxo_index,
-            String::new(),
+            MonitorContext::default(),
         ))?;
         monitor.tick()?;
         let monitors = monitor.store.get_monitors()?;
@@ -2080,7 +2241,7 @@ fn test_transaction_monitor_deactivation_after_max_confirmations() -> Result<(),
     let mut mock_indexer = MockIndexerApi::new();
     let path = format!("test_outputs/{}", generate_random_string());
     let config = StorageConfig::new(path, None);
-    let storage = Rc::new(Storage::new(&config)?);
+    let storage = Arc::new(Storage::new(&config)?);
     let store = MonitorStore::new(storage)?;
 
     let tx = Transaction {
@@ -2199,20 +2360,20 @@ fn test_transaction_monitor_deactivation_after_max_confirmations() -> Result<(),
 
     mock_indexer.expect_tick().returning(move || Ok(()));
 
-    // First tick: from tick() and get_news()
+    // First tick: from tick(), get_news() -> get_tx_status() reads the lookup cached by the tick
     let tx_info_1_conf_clone = tx_info_1_conf.clone();
     mock_indexer
         .expect_get_tx()
         .with(eq(tx_id))
-        .times(2) // Once from tick(), once from get_news() -> get_tx_status()
+        .times(1)
         .returning(move |_| Ok(Some(tx_info_1_conf_clone.clone())));
 
-    // Second tick: from tick() and get_news()
+    // Second tick: from tick(), get_news() reads the cached lookup
     let tx_info_2_conf_clone = tx_info_2_conf.clone();
     mock_indexer
         .expect_get_tx()
         .with(eq(tx_id))
-        .times(2)
+        .times(1)
         .returning(move |_| Ok(Some(tx_info_2_conf_clone.clone())));
 
     mock_indexer
@@ -2223,15 +2384,12 @@ fn test_transaction_monitor_deactivation_after_max_confirmations() -> Result<(),
 
     let mut settings = MonitorSettings::from(MonitorSettingsConfig::default());
     settings.max_monitoring_confirmations = 3;
+    settings.news_resolution = Resolution::Fresh;
 
     let monitor = Monitor::new(mock_indexer, store, settings)?;
 
     // Add monitor without confirmation trigger
-    monitor.save_monitor(TypesToMonitor::Transactions(
-        vec![tx_id],
-        String::new(),
-        None,
-    ))?;
+    monitor.save_monitor(WatchTx::new(tx_id).build()?)?;
 
     // First tick: should send news
     monitor.tick()?;
@@ -2243,7 +2401,10 @@ fn test_transaction_monitor_deactivation_after_max_confirmations() -> Result<(),
         MonitorNews::Transaction(t, _, _) if t == tx_id
     ));
 
-    monitor.ack_news(AckMonitorNews::Transaction(tx_id, String::new()))?;
+    monitor.ack_news(AckMonitorNews::Transaction(
+        tx_id,
+        MonitorContext::default(),
+    ))?;
 
     // Second tick: should send news and then deactivate
     monitor.tick()?;
@@ -2255,7 +2416,10 @@ fn test_transaction_monitor_deactivation_after_max_confirmations() -> Result<(),
         MonitorNews::Transaction(t, _, _) if t == tx_id
     ));
 
-    monitor.ack_news(AckMonitorNews::Transaction(tx_id, String::new()))?;
+    monitor.ack_news(AckMonitorNews::Transaction(
+        tx_id,
+        MonitorContext::default(),
+    ))?;
 
     // Third tick: should deactivate
     monitor.tick()?;
@@ -2268,3 +2432,3941 @@ fn test_transaction_monitor_deactivation_after_max_confirmations() -> Result<(),
 
     Ok(())
 }
+
+/// Test that monitor_batch registers the valid items of a batch and reports the invalid ones:
+/// 1. Build a batch mixing valid monitors with invalid ones (no txids, trigger of 0,
+///    trigger over the max monitoring confirmations, reserved context)
+/// 2. Assert the per-item outcomes are returned in input order
+/// 3. Assert only the valid monitors are stored
+#[test]
+fn test_monitor_batch_partial_success() -> Result<(), anyhow::Error> {
+    let mock_indexer = MockIndexerApi::new();
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+
+    let tx_id = Txid::from_str("0000000000000000000000000000000000000000000000000000000000000001")?;
+    let tx_id_2 =
+        Txid::from_str("0000000000000000000000000000000000000000000000000000000000000002")?;
+
+    // 1. Mixed batch
+    let outcomes = monitor.monitor_batch(vec![
+        WatchTx::new(tx_id).context("ctx_1").build()?,
+        TypesToMonitor::Transactions(vec![], "ctx_2".into(), None, None),
+        WatchOutpoint::new(tx_id_2, 1).context("ctx_3").build()?,
+        TypesToMonitor::Transactions(vec![tx_id_2], "ctx_4".into(), Some(0), None),
+        TypesToMonitor::SpendingUTXOTransaction(
+            tx_id,
+            0,
+            "ctx_5".into(),
+            Some(100),
+            None,
+            None,
+            None,
+        ),
+        TypesToMonitor::Transactions(vec![tx_id_2], "INTERNAL_RSK_PEGIN".into(), None, None),
+        WatchTx::new(tx_id_2)
+            .context("ctx_6")
+            .trigger_at(3)
+            .build()?,
+        TypesToMonitor::NewBlock,
+    ])?;
+
+    // 2. Outcomes in input order
+    assert_eq!(outcomes.len(), 8);
+    assert!(outcomes[0].is_ok());
+    assert!(matches!(outcomes[1], Err(MonitorError::InvalidMonitor(_))));
+    assert!(outcomes[2].is_ok());
+    assert!(matches!(outcomes[3], Err(MonitorError::InvalidMonitor(_))));
+    assert!(matches!(
+        outcomes[4],
+        Err(MonitorError::InvalidConfirmationTrigger(100, 100))
+    ));
+    assert!(matches!(outcomes[5], Err(MonitorError::InvalidMonitor(_))));
+    assert!(outcomes[6].is_ok());
+    assert!(outcomes[7].is_ok());
+
+    // 3. Only the valid monitors are stored
+    let monitors = monitor.store.get_monitors()?;
+    assert_eq!(monitors.len(), 4);
+    assert!(monitors.contains(&TypesToMonitorStore::Transaction(
+        tx_id,
+        "ctx_1".into(),
+        None,
+        None,
+    )));
+    assert!(monitors.contains(&TypesToMonitorStore::Transaction(
+        tx_id_2,
+        "ctx_6".into(),
+        Some(3),
+        None,
+    )));
+    assert!(
+        monitors.contains(&TypesToMonitorStore::SpendingUTXOTransaction(
+            tx_id_2,
+            1,
+            "ctx_3".into(),
+            None,
+            None,
+            None,
+            None,
+        ))
+    );
+    assert!(monitors.contains(&TypesToMonitorStore::NewBlock));
+    assert!(monitor.store.has_pending_work()?);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that monitor_batch writes the whole batch with a single store update
+/// and marks pending work only once.
+#[test]
+fn test_monitor_batch_single_write() -> Result<(), anyhow::Error> {
+    let mock_indexer = MockIndexerApi::new();
+    let mut mock_store = MockMonitorStore::new();
+
+    let tx_id = Txid::from_str("0000000000000000000000000000000000000000000000000000000000000001")?;
+
+    mock_store
+        .expect_set_pending_work()
+        .with(eq(true))
+        .times(1)
+        .returning(|_| Ok(()));
+
+    mock_store
+        .expect_add_monitors()
+        .withf(|items| items.len() == 3)
+        .times(1)
+        .returning(|_| Ok(()));
+
+    mock_store.expect_add_monitor().never();
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        mock_store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+
+    let outcomes = monitor.monitor_batch(vec![
+        WatchTx::new(tx_id).context("ctx_1").build()?,
+        WatchTx::new(tx_id).context("ctx_2").build()?,
+        TypesToMonitor::Transactions(vec![], "ctx_3".into(), None, None),
+        WatchOutpoint::new(tx_id, 0).build()?,
+    ])?;
+
+    assert_eq!(outcomes.len(), 4);
+    assert!(outcomes[2].is_err());
+
+    Ok(())
+}
+
+/// Test that processing the same block twice (e.g. after a crash between the news write and
+/// the height update) does not emit the pegin and spending detections again:
+/// 1. Monitor pegins and a UTXO with a trigger of 1 confirmation, tick over a block holding
+///    a pegin and a spend of the UTXO
+/// 2. Reset the height cursor and tick again over the same block, news are identical
+/// 3. Ack the news, reprocess the block once more, no news are emitted and the triggers stay sent
+#[test]
+fn test_reprocessing_same_block_is_idempotent() -> Result<(), anyhow::Error> {
+    let mut mock_indexer = MockIndexerApi::new();
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let target_tx_id =
+        Txid::from_str("0000000000000000000000000000000000000000000000000000000000000001")?;
+
+    let pegin_tx = create_pegin_tx();
+    let pegin_tx_id = pegin_tx.compute_txid();
+
+    let spending_tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![bitcoin::TxIn {
+            previous_output: bitcoin::OutPoint {
+                txid: target_tx_id,
+                vout: 0,
+            },
+            script_sig: bitcoin::ScriptBuf::new(),
+            sequence: bitcoin::Sequence::MAX,
+            witness: bitcoin::Witness::new(),
+        }],
+        output: vec![],
+    };
+    let spending_tx_id = spending_tx.compute_txid();
+
+    let block_200 = FullBlock {
+        height: 200,
+        hash: BlockHash::from_str(
+            "1000000000000000000000000000000000000000000000000000000000000001",
+        )?,
+        prev_hash: BlockHash::from_str(
+            "1000000000000000000000000000000000000000000000000000000000000000",
+        )?,
+        txs: vec![pegin_tx.clone(), spending_tx.clone()],
+        orphan: false,
+        estimated_fee_rate: 0,
+    };
+
+    let block_200_clone = block_200.clone();
+    let block_200_clone_2 = block_200.clone();
+
+    mock_indexer.expect_tick().returning(|| Ok(()));
+
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(block_200_clone.clone())));
+
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |_| Ok(Some(block_200_clone_2.clone())));
+
+    mock_indexer.expect_get_tx().returning(move |tx_id| {
+        let tx = if *tx_id == pegin_tx_id {
+            pegin_tx.clone()
+        } else {
+            spending_tx.clone()
+        };
+
+        Ok(Some(TransactionInfo {
+            tx,
+            block_info: block_200.clone(),
+            confirmations: 1,
+        }))
+    });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+
+    // 1. First pass over block 200
+    monitor.save_monitor(WatchPegin::new().trigger_at(1).build()?)?;
+    monitor.save_monitor(
+        WatchOutpoint::new(target_tx_id, 0)
+            .context("ctx")
+            .trigger_at(1)
+            .build()?,
+    )?;
+    monitor.tick()?;
+
+    let news = monitor.get_news()?;
+    assert_eq!(news.len(), 2);
+
+    // 2. Reprocess block 200
+    monitor.store.update_monitor_height(199)?;
+    monitor.store.set_pending_work(true)?;
+    monitor.tick()?;
+
+    assert_eq!(monitor.get_news()?, news);
+
+    // 3. Ack and reprocess block 200 again
+    monitor.ack_news(AckMonitorNews::RskPeginTransaction(pegin_tx_id))?;
+    monitor.ack_news(AckMonitorNews::SpendingUTXOTransaction(
+        target_tx_id,
+        0,
+        "ctx".into(),
+    ))?;
+
+    monitor.store.update_monitor_height(199)?;
+    monitor.store.set_pending_work(true)?;
+    monitor.tick()?;
+
+    assert_eq!(monitor.get_news()?.len(), 0);
+    assert!(monitor
+        .store
+        .get_transaction_trigger_sent(pegin_tx_id, "INTERNAL_RSK_PEGIN")?);
+    assert!(monitor.store.get_transaction_trigger_sent(
+        spending_tx_id,
+        &format!("INTERNAL_SPENDING_UTXO:{target_tx_id}:0:ctx")
+    )?);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that spends of an output nearing its deadline are looked up in the mempool:
+/// 1. Watch an output with a deadline at height 210 and a mempool window of 5 blocks
+/// 2. Tick from height 200 to 212, the spender enters the mempool at height 207
+/// 3. Assert the mempool is only queried inside the window (heights 205 to 210)
+/// 4. Assert the unconfirmed spend is reported once and can be acked
+#[test]
+fn test_mempool_spend_within_deadline_window() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let target_tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_time(1653195600).unwrap(),
+        input: vec![],
+        output: vec![],
+    };
+    let target_tx_id = target_tx.compute_txid();
+    let target_utxo_index = 0u32;
+
+    let spending_tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_time(1653195601).unwrap(),
+        input: vec![bitcoin::TxIn {
+            previous_output: bitcoin::OutPoint {
+                txid: target_tx_id,
+                vout: target_utxo_index,
+            },
+            script_sig: bitcoin::ScriptBuf::new(),
+            sequence: bitcoin::Sequence::MAX,
+            witness: bitcoin::Witness::new(),
+        }],
+        output: vec![],
+    };
+    let spending_tx_id = spending_tx.compute_txid();
+
+    let height = Arc::new(AtomicU32::new(200));
+
+    let mut mock_indexer = scripted_indexer(height.clone());
+    mock_indexer.expect_get_tx().returning(|_| Ok(None));
+
+    // 3. Only queried at heights 205 to 210
+    let mut mock_mempool = MockMempoolApi::new();
+    let mempool_height = height.clone();
+    mock_mempool
+        .expect_get_mempool_spenders()
+        .times(6)
+        .returning(move |outpoints| {
+            let height = mempool_height.load(Ordering::SeqCst);
+            assert!((205..=210).contains(&height));
+            assert_eq!(
+                outpoints,
+                [bitcoin::OutPoint::new(target_tx_id, target_utxo_index)]
+            );
+
+            if height >= 207 {
+                Ok(vec![spending_tx.clone()])
+            } else {
+                Ok(vec![])
+            }
+        });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?
+    .with_mempool(Box::new(mock_mempool));
+
+    // 1. Watch the output
+    monitor.save_monitor(
+        WatchOutpoint::new(target_tx_id, target_utxo_index)
+            .context("ctx")
+            .deadline(210, 5)
+            .build()?,
+    )?;
+
+    // 2. Tick through the window
+    for current_height in 200..=212 {
+        height.store(current_height, Ordering::SeqCst);
+        monitor.tick()?;
+
+        let news = monitor.get_news()?;
+
+        if current_height == 207 {
+            // 4. Reported once and acked
+            assert_eq!(news.len(), 1);
+            assert_eq!(
+                news[0],
+                MonitorNews::SpendingUTXOUnconfirmed(
+                    target_tx_id,
+                    target_utxo_index,
+                    spending_tx_id,
+                    "ctx".into()
+                )
+            );
+
+            monitor.ack_news(AckMonitorNews::SpendingUTXOUnconfirmed(
+                target_tx_id,
+                target_utxo_index,
+                "ctx".into(),
+            ))?;
+        } else {
+            assert!(news.is_empty());
+        }
+    }
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that tick sweeps expired keys once every `sweep_interval_blocks`:
+/// 1. Write a key at height 200 expiring at 203, with a sweep interval of 2
+/// 2. Tick from height 200 to 204
+/// 3. Assert the key outlives its expiry at 203 and is removed by the sweep at 204
+#[test]
+fn test_tick_sweeps_expired_keys() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage.clone())?;
+
+    let height = Arc::new(AtomicU32::new(200));
+
+    let mock_indexer = scripted_indexer(height.clone());
+
+    let mut settings = MonitorSettings::from(MonitorSettingsConfig::default());
+    settings.sweep_interval_blocks = 2;
+    let monitor = Monitor::new(mock_indexer, store, settings)?;
+
+    // 1. Write the key
+    monitor
+        .store
+        .set_with_expiry("monitor/journal/test", "entry", 203)?;
+
+    // 2. Tick, the new block monitor keeps the monitor processing every block
+    monitor.save_monitor(TypesToMonitor::NewBlock)?;
+
+    for current_height in 200..=204 {
+        height.store(current_height, Ordering::SeqCst);
+        monitor.tick()?;
+
+        // 3. Swept at 200, 202 and 204 only
+        let exists = storage.get::<_, String>("monitor/journal/test")?.is_some();
+        assert_eq!(exists, current_height < 204, "height {current_height}");
+    }
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that replace_monitored_tx moves a transaction monitor to its replacement:
+/// 1. Monitor the old transaction with two contexts and record unacked news for it
+/// 2. Replace it and check the monitors, purged news, MonitorReplaced news and tombstone
+/// 3. Ack the MonitorReplaced news
+/// 4. Replacing an unmonitored transaction or a transaction with itself fails
+#[test]
+fn test_replace_monitored_tx_transfers_monitor() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let old = Txid::from_str("0000000000000000000000000000000000000000000000000000000000000001")?;
+    let new = Txid::from_str("0000000000000000000000000000000000000000000000000000000000000002")?;
+    let block_hash =
+        BlockHash::from_str("0000000000000000000000000000000000000000000000000000000000000064")?;
+
+    let mut mock_indexer = MockIndexerApi::new();
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(|_| Ok(None));
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+
+    // 1. Monitor the old tx
+    monitor.save_monitor(WatchTx::new(old).context("ctx_a").trigger_at(3).build()?)?;
+    monitor.save_monitor(WatchTx::new(old).context("ctx_b").build()?)?;
+    monitor
+        .store
+        .update_news(MonitoredTypes::Transaction(old, "ctx_a".into()), block_hash)?;
+
+    // 2. Replace
+    monitor.replace_monitored_tx(old, new)?;
+
+    let monitors = monitor.store.get_monitors()?;
+    assert_eq!(monitors.len(), 2);
+    assert!(monitors.contains(&TypesToMonitorStore::Transaction(
+        new,
+        "ctx_a".into(),
+        Some(3),
+        None,
+    )));
+    assert!(monitors.contains(&TypesToMonitorStore::Transaction(
+        new,
+        "ctx_b".into(),
+        None,
+        None
+    )));
+
+    let news = monitor.get_news()?;
+    assert_eq!(news.len(), 2);
+    for context in ["ctx_a", "ctx_b"] {
+        assert!(news.contains(&MonitorNews::MonitorReplaced {
+            old,
+            new,
+            context: context.into(),
+        }));
+    }
+
+    let replaced = monitor.store.get_replaced_transactions()?;
+    assert_eq!(replaced.len(), 1);
+    assert_eq!(replaced[0].old_tx_id, old);
+    assert_eq!(replaced[0].new_tx_id, new);
+    assert_eq!(
+        replaced[0].expires_at,
+        monitor.settings.max_monitoring_confirmations
+    );
+
+    // 3. Ack
+    for context in ["ctx_a", "ctx_b"] {
+        monitor.ack_news(AckMonitorNews::MonitorReplaced {
+            old,
+            context: context.into(),
+        })?;
+    }
+    assert!(monitor.get_news()?.is_empty());
+
+    // 4. Errors
+    assert!(monitor.replace_monitored_tx(old, new).is_err());
+    assert!(matches!(
+        monitor.replace_monitored_tx(new, new),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that a replaced transaction confirming anyway is reported:
+/// 1. Monitor a transaction at height 200 and replace it
+/// 2. Tick at 201 while the old transaction is unconfirmed, no warning
+/// 3. Tick at 202 with the old transaction confirmed, a ReplacedTransactionConfirmed news is sent
+/// 4. Tick at 203, the tombstone is gone and the warning is not sent again
+#[test]
+fn test_replaced_tx_confirmed_tombstone() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let old_tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_time(1653195600).unwrap(),
+        input: vec![],
+        output: vec![],
+    };
+    let old = old_tx.compute_txid();
+    let new = Txid::from_str("0000000000000000000000000000000000000000000000000000000000000002")?;
+
+    let height = Arc::new(AtomicU32::new(200));
+
+    let mut mock_indexer = scripted_indexer(height.clone());
+    let tx_height = height.clone();
+    mock_indexer.expect_get_tx().returning(move |tx_id| {
+        let height = tx_height.load(Ordering::SeqCst);
+        if *tx_id != old || height < 202 {
+            return Ok(None);
+        }
+        Ok(Some(TransactionInfo {
+            tx: old_tx.clone(),
+            block_info: block_at(202),
+            confirmations: height - 201,
+        }))
+    });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+
+    // 1. Monitor and replace
+    monitor.save_monitor(WatchTx::new(old).context("ctx").build()?)?;
+    monitor.tick()?;
+    monitor.replace_monitored_tx(old, new)?;
+    monitor.ack_news(AckMonitorNews::MonitorReplaced {
+        old,
+        context: "ctx".into(),
+    })?;
+
+    // 2. Old tx not confirmed
+    height.store(201, Ordering::SeqCst);
+    monitor.tick()?;
+    assert!(monitor.get_news()?.is_empty());
+
+    // 3. Old tx confirmed
+    height.store(202, Ordering::SeqCst);
+    monitor.tick()?;
+    let news = monitor.get_news()?;
+    assert_eq!(
+        news,
+        vec![MonitorNews::ReplacedTransactionConfirmed {
+            old,
+            new,
+            context: "ctx".into(),
+        }]
+    );
+    assert!(monitor.store.get_replaced_transactions()?.is_empty());
+
+    monitor.ack_news(AckMonitorNews::ReplacedTransactionConfirmed {
+        old,
+        context: "ctx".into(),
+    })?;
+
+    // 4. Not sent again
+    height.store(203, Ordering::SeqCst);
+    monitor.tick()?;
+    assert!(monitor.get_news()?.is_empty());
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that a snoozed monitor keeps tracking but holds its news back until the snooze ends:
+/// 1. Monitor a transaction at height 200 and snooze it for 3 blocks
+/// 2. Tick from 201 to 203 with the transaction confirmed at 201, no news is sent
+/// 3. Tick at 204, exactly one news is sent with the latest confirmations
+/// 4. Snoozing an unmonitored transaction or for 0 blocks fails
+#[test]
+fn test_snooze_holds_news_until_expiry() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_time(1653195600).unwrap(),
+        input: vec![],
+        output: vec![],
+    };
+    let tx_id = tx.compute_txid();
+
+    let height = Arc::new(AtomicU32::new(200));
+
+    let mut mock_indexer = scripted_indexer(height.clone());
+    let tx_height = height.clone();
+    mock_indexer.expect_get_tx().returning(move |_| {
+        let height = tx_height.load(Ordering::SeqCst);
+        if height < 201 {
+            return Ok(None);
+        }
+        Ok(Some(TransactionInfo {
+            tx: tx.clone(),
+            block_info: block_at(201),
+            confirmations: height - 200,
+        }))
+    });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+
+    // 1. Monitor and snooze
+    let watch = WatchTx::new(tx_id).context("ctx").build()?;
+    monitor.save_monitor(watch.clone())?;
+    monitor.tick()?;
+    monitor.snooze(watch, 3)?;
+
+    let snoozes = monitor.get_snoozes()?;
+    assert_eq!(snoozes.len(), 1);
+    assert_eq!(
+        snoozes[0].target,
+        SnoozeTarget::Transaction(tx_id, "ctx".into())
+    );
+    assert_eq!(snoozes[0].until, 203);
+
+    // 2. Detection while snoozed
+    for current_height in 201..=203 {
+        height.store(current_height, Ordering::SeqCst);
+        monitor.tick()?;
+        assert!(monitor.get_news()?.is_empty(), "height {current_height}");
+    }
+    assert_eq!(monitor.get_snoozes()?[0].pending.len(), 1);
+
+    // 3. Catch-up news
+    height.store(204, Ordering::SeqCst);
+    monitor.tick()?;
+    let news = monitor.get_news()?;
+    assert_eq!(news.len(), 1);
+    assert!(
+        matches!(&news[0], MonitorNews::Transaction(t, status, ctx) if *t == tx_id && status.confirmations == 4 && ctx == "ctx")
+    );
+    assert!(monitor.get_snoozes()?.is_empty());
+
+    // 4. Errors
+    let other = Txid::from_str("0000000000000000000000000000000000000000000000000000000000000009")?;
+    assert!(matches!(
+        monitor.snooze(WatchTx::new(other).context("ctx").build()?, 3),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+    assert!(matches!(
+        monitor.snooze(WatchTx::new(tx_id).context("ctx").build()?, 0),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that news statuses are resolved following `news_resolution`:
+/// 1. A transaction confirmed at 201 with a trigger at 1 confirmation sends its news, then the monitor ticks up to 205
+/// 2. Snapshot returns the status the news was emitted with and never calls get_tx
+/// 3. SnapshotWithHeightAdjustment recomputes confirmations from the monitor height without calling get_tx
+/// 4. Fresh fetches the status from the indexer
+#[test]
+fn test_news_resolution_modes() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage.clone())?;
+
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_time(1653195600).unwrap(),
+        input: vec![],
+        output: vec![],
+    };
+    let tx_id = tx.compute_txid();
+
+    let height = Arc::new(AtomicU32::new(200));
+
+    let mut mock_indexer = scripted_indexer(height.clone());
+    let tx_height = height.clone();
+    mock_indexer.expect_get_tx().returning(move |_| {
+        let height = tx_height.load(Ordering::SeqCst);
+        if height < 201 {
+            return Ok(None);
+        }
+        Ok(Some(TransactionInfo {
+            tx: tx.clone(),
+            block_info: block_at(201),
+            confirmations: height - 200,
+        }))
+    });
+
+    let mut settings = MonitorSettings::from(MonitorSettingsConfig::default());
+    settings.confirmation_threshold = 3;
+    settings.news_resolution = Resolution::Fresh;
+
+    let monitor = Monitor::new(mock_indexer, store, settings.clone())?;
+
+    // 1. News emitted at 1 confirmation
+    monitor.save_monitor(WatchTx::new(tx_id).context("ctx").trigger_at(1).build()?)?;
+    for current_height in 200..=205 {
+        height.store(current_height, Ordering::SeqCst);
+        monitor.tick()?;
+    }
+
+    // 2. Snapshot, read through a monitor whose indexer must not be asked for transactions
+    let mut reader_indexer = MockIndexerApi::new();
+    reader_indexer.expect_get_tx().never();
+    settings.news_resolution = Resolution::Snapshot;
+    let mut reader = Monitor::new(reader_indexer, MonitorStore::new(storage)?, settings)?;
+
+    let envelopes = reader.get_news_envelopes()?;
+    assert_eq!(envelopes.len(), 1);
+    assert_eq!(envelopes[0].resolution, Resolution::Snapshot);
+    assert!(matches!(
+        &envelopes[0].news,
+        MonitorNews::Transaction(t, status, ctx)
+            if *t == tx_id
+                && ctx == "ctx"
+                && status.confirmations == 1
+                && status.status == TransactionBlockchainStatus::Confirmed
+                && status.block_info.as_ref().map(|b| b.hash) == Some(block_at(201).hash)
+    ));
+
+    // 3. Snapshot adjusted to the monitor height
+    reader.settings.news_resolution = Resolution::SnapshotWithHeightAdjustment;
+    let envelopes = reader.get_news_envelopes()?;
+    assert_eq!(envelopes.len(), 1);
+    assert_eq!(
+        envelopes[0].resolution,
+        Resolution::SnapshotWithHeightAdjustment
+    );
+    assert!(matches!(
+        &envelopes[0].news,
+        MonitorNews::Transaction(_, status, _)
+            if status.confirmations == 5 && status.status == TransactionBlockchainStatus::Finalized
+    ));
+
+    // 4. Fresh
+    let envelopes = monitor.get_news_envelopes()?;
+    assert_eq!(envelopes.len(), 1);
+    assert_eq!(envelopes[0].resolution, Resolution::Fresh);
+    assert!(matches!(
+        &envelopes[0].news,
+        MonitorNews::Transaction(_, status, _)
+            if status.confirmations == 5 && status.status == TransactionBlockchainStatus::Finalized
+    ));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that a news sequence counter restored from a backup starts a new epoch:
+/// 1. A transaction news is sent with sequence 1 in epoch 0
+/// 2. Restarting the monitor with an untouched store keeps the epoch
+/// 3. Rolling back the counter key and restarting bumps the epoch and sends a SequenceEpochChanged news
+///    with a sequence past the stored news
+/// 4. Acknowledging the SequenceEpochChanged news removes it
+#[test]
+fn test_news_sequence_epoch_after_restore() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_time(1653195600).unwrap(),
+        input: vec![],
+        output: vec![],
+    };
+    let tx_id = tx.compute_txid();
+
+    fn mock_indexer(height: Arc<AtomicU32>, tx: Transaction) -> MockIndexerApi {
+        let mut mock_indexer = scripted_indexer(height.clone());
+        mock_indexer.expect_get_tx().returning(move |_| {
+            let height = height.load(Ordering::SeqCst);
+            if height < 201 {
+                return Ok(None);
+            }
+            Ok(Some(TransactionInfo {
+                tx: tx.clone(),
+                block_info: block_at(201),
+                confirmations: height - 200,
+            }))
+        });
+        mock_indexer
+    }
+
+    let height = Arc::new(AtomicU32::new(200));
+    let settings = MonitorSettings::from(MonitorSettingsConfig::default());
+
+    // 1. First news
+    let monitor = Monitor::new(
+        mock_indexer(height.clone(), tx.clone()),
+        MonitorStore::new(storage.clone())?,
+        settings.clone(),
+    )?;
+    monitor.save_monitor(WatchTx::new(tx_id).context("ctx").trigger_at(1).build()?)?;
+    monitor.tick()?;
+    height.store(201, Ordering::SeqCst);
+    monitor.tick()?;
+
+    let envelopes = monitor.get_news_envelopes()?;
+    assert_eq!(envelopes.len(), 1);
+    assert!(matches!(&envelopes[0].news, MonitorNews::Transaction(t, _, _) if *t == tx_id));
+    assert_eq!((envelopes[0].epoch, envelopes[0].sequence), (0, 1));
+
+    // 2. Restart with an untouched store
+    let monitor = Monitor::new(
+        mock_indexer(height.clone(), tx.clone()),
+        MonitorStore::new(storage.clone())?,
+        settings.clone(),
+    )?;
+    monitor.tick()?;
+
+    let envelopes = monitor.get_news_envelopes()?;
+    assert_eq!(envelopes.len(), 1);
+    assert_eq!((envelopes[0].epoch, envelopes[0].sequence), (0, 1));
+
+    // 3. Restart after rolling back the counter
+    storage.set("monitor/news/sequence", 0u64, None)?;
+    let monitor = Monitor::new(
+        mock_indexer(height.clone(), tx.clone()),
+        MonitorStore::new(storage.clone())?,
+        settings,
+    )?;
+    monitor.tick()?;
+
+    let envelopes = monitor.get_news_envelopes()?;
+    assert_eq!(envelopes.len(), 2);
+    assert!(envelopes.iter().all(|envelope| envelope.epoch == 1));
+    let epoch_news = envelopes
+        .iter()
+        .find(|envelope| {
+            envelope.news
+                == MonitorNews::SequenceEpochChanged {
+                    old_epoch: 0,
+                    new_epoch: 1,
+                }
+        })
+        .expect("SequenceEpochChanged news");
+    assert_eq!(epoch_news.sequence, 2);
+
+    // 4. Ack
+    monitor.ack_news(AckMonitorNews::SequenceEpochChanged { new_epoch: 1 })?;
+    let news = monitor.get_news()?;
+    assert_eq!(news.len(), 1);
+    assert!(matches!(&news[0], MonitorNews::Transaction(t, _, _) if *t == tx_id));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that a ScriptPubKey monitor reports outputs paying to a non-standard script:
+/// 1. Watch a bare multisig script, nothing is found at height 200
+/// 2. Tick at 201 where a transaction pays to the script at vout 1, one news with the txid and vout
+/// 3. Reprocess block 201 before and after the ack, the output is not reported again
+/// 4. Tick up to 203, the last news is sent below max_monitoring_confirmations and the
+///    transaction monitor is deactivated while the script keeps being watched
+#[test]
+fn test_script_pubkey_monitor() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let script_pubkey = Builder::new()
+        .push_opcode(OP_PUSHNUM_1)
+        .push_slice([2u8; 33])
+        .push_opcode(OP_PUSHNUM_1)
+        .push_opcode(OP_CHECKMULTISIG)
+        .into_script();
+
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![],
+        output: vec![
+            TxOut {
+                value: Amount::from_sat(1_000),
+                script_pubkey: ScriptBuf::new_op_return([1u8; 4]),
+            },
+            TxOut {
+                value: Amount::from_sat(50_000),
+                script_pubkey: script_pubkey.clone(),
+            },
+        ],
+    };
+    let tx_id = tx.compute_txid();
+
+    fn block_at(height: u32, txs: Vec<Transaction>) -> FullBlock {
+        FullBlock {
+            txs,
+            ..utils::block_at(height)
+        }
+    }
+
+    let height = Arc::new(AtomicU32::new(200));
+
+    let mut mock_indexer = MockIndexerApi::new();
+    let best_height = height.clone();
+    let block_tx = tx.clone();
+    mock_indexer.expect_get_best_block().returning(move || {
+        let height = best_height.load(Ordering::SeqCst);
+        let txs = if height == 201 {
+            vec![block_tx.clone()]
+        } else {
+            vec![]
+        };
+        Ok(Some(block_at(height, txs)))
+    });
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(|height| Ok(Some(block_at(height, vec![]))));
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let tx_height = height.clone();
+    mock_indexer.expect_get_tx().returning(move |_| {
+        let height = tx_height.load(Ordering::SeqCst);
+        if height < 201 {
+            return Ok(None);
+        }
+        Ok(Some(TransactionInfo {
+            tx: tx.clone(),
+            block_info: block_at(201, vec![]),
+            confirmations: height - 200,
+        }))
+    });
+
+    let mut settings = MonitorSettings::from(MonitorSettingsConfig::default());
+    settings.max_monitoring_confirmations = 3;
+    let monitor = Monitor::new(mock_indexer, store, settings)?;
+
+    // 1. Nothing paying to the script yet
+    let watch = WatchScript::new(script_pubkey.clone())
+        .context("ctx")
+        .build()?;
+    monitor.save_monitor(watch)?;
+    monitor.tick()?;
+    assert!(monitor.get_news()?.is_empty());
+
+    // 2. Output found at vout 1
+    height.store(201, Ordering::SeqCst);
+    monitor.tick()?;
+    let news = monitor.get_news()?;
+    assert_eq!(news.len(), 1);
+    assert!(
+        matches!(&news[0], MonitorNews::ScriptPubKeyTransaction(t, 1, status, ctx) if *t == tx_id && status.confirmations == 1 && ctx == "ctx")
+    );
+
+    // 3. Reprocess block 201
+    monitor.store.update_monitor_height(200)?;
+    monitor.store.set_pending_work(true)?;
+    monitor.tick()?;
+    assert_eq!(monitor.get_news()?, news);
+
+    monitor.ack_news(AckMonitorNews::ScriptPubKeyTransaction(
+        tx_id,
+        1,
+        "ctx".into(),
+    ))?;
+    monitor.store.update_monitor_height(200)?;
+    monitor.store.set_pending_work(true)?;
+    monitor.tick()?;
+    assert!(monitor.get_news()?.is_empty());
+
+    // 4. Deactivation at max_monitoring_confirmations
+    for current_height in 202..=203 {
+        height.store(current_height, Ordering::SeqCst);
+        monitor.tick()?;
+    }
+    let news = monitor.get_news()?;
+    assert_eq!(news.len(), 1);
+    assert!(
+        matches!(&news[0], MonitorNews::ScriptPubKeyTransaction(t, 1, status, _) if *t == tx_id && status.confirmations == 2)
+    );
+    assert_eq!(
+        monitor.store.get_monitors()?,
+        vec![TypesToMonitorStore::ScriptPubKey(
+            script_pubkey,
+            "ctx".into(),
+            None,
+            None
+        )]
+    );
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that the filter of a ScriptPubKey monitor keeps dust sent to the address from being reported:
+/// 1. Watch a P2WPKH script ignoring outputs below the relay dust limit or worth less than 1000 sats
+/// 2. Tick at 201 with one deposit and three dust outputs, a single news and the dust is counted
+/// 3. Reprocess block 201, the dust is not counted again
+/// 4. Each filter option on its own
+#[test]
+fn test_script_pubkey_dust_filter() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let script_pubkey = ScriptBuf::from_hex(&format!("0014{}", "07".repeat(20)))?;
+    let pay = |sats: u64, lock_time: u32| Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_consensus(lock_time),
+        input: vec![],
+        output: vec![TxOut {
+            value: Amount::from_sat(sats),
+            script_pubkey: script_pubkey.clone(),
+        }],
+    };
+    // The deposit, two outputs below the relay dust limit (294 sats) and one below min_value
+    let txs = vec![pay(50_000, 0), pay(1, 1), pay(200, 2), pay(600, 3)];
+    let deposit_id = txs[0].compute_txid();
+
+    fn block_at(height: u32, txs: Vec<Transaction>) -> FullBlock {
+        FullBlock {
+            txs,
+            ..utils::block_at(height)
+        }
+    }
+
+    let height = Arc::new(AtomicU32::new(200));
+
+    let mut mock_indexer = MockIndexerApi::new();
+    let best_height = height.clone();
+    let block_txs = txs.clone();
+    mock_indexer.expect_get_best_block().returning(move || {
+        let height = best_height.load(Ordering::SeqCst);
+        let txs = if height == 201 {
+            block_txs.clone()
+        } else {
+            vec![]
+        };
+        Ok(Some(block_at(height, txs)))
+    });
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(|height| Ok(Some(block_at(height, vec![]))));
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let tx_height = height.clone();
+    mock_indexer.expect_get_tx().returning(move |tx_id| {
+        let height = tx_height.load(Ordering::SeqCst);
+        Ok(txs
+            .iter()
+            .find(|tx| tx.compute_txid() == *tx_id && height >= 201)
+            .map(|tx| TransactionInfo {
+                tx: tx.clone(),
+                block_info: block_at(201, vec![]),
+                confirmations: height - 200,
+            }))
+    });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+
+    // 1. Watch with both filter options
+    let watch = WatchScript::new(script_pubkey.clone())
+        .context("deposits")
+        .min_value(Amount::from_sat(1_000))
+        .ignore_dust_below_relay()
+        .build()?;
+    monitor.save_monitor(watch)?;
+    monitor.tick()?;
+
+    // 2. Only the deposit is reported
+    height.store(201, Ordering::SeqCst);
+    monitor.tick()?;
+    let news = monitor.get_news()?;
+    assert_eq!(news.len(), 1);
+    assert!(
+        matches!(&news[0], MonitorNews::ScriptPubKeyTransaction(t, 0, _, ctx) if *t == deposit_id && ctx == "deposits")
+    );
+    let stats = vec![AddressFilterStats {
+        script_pubkey: script_pubkey.clone(),
+        context: "deposits".into(),
+        filtered_outputs: 3,
+        filtered_value: Amount::from_sat(801),
+    }];
+    assert_eq!(monitor.address_filter_stats(&script_pubkey)?, stats);
+
+    // 3. Reprocess block 201
+    monitor.store.update_monitor_height(200)?;
+    monitor.store.set_pending_work(true)?;
+    monitor.tick()?;
+    assert_eq!(monitor.get_news()?, news);
+    assert_eq!(monitor.address_filter_stats(&script_pubkey)?, stats);
+
+    // 4. Filter options
+    let dust = |sats: u64| TxOut {
+        value: Amount::from_sat(sats),
+        script_pubkey: script_pubkey.clone(),
+    };
+    let relay_dust = OutputFilter {
+        min_value: None,
+        ignore_dust_below_relay: true,
+    };
+    assert!(relay_dust.filters(&dust(293)));
+    assert!(!relay_dust.filters(&dust(294)));
+    let min_value = OutputFilter {
+        min_value: Some(Amount::from_sat(1_000)),
+        ignore_dust_below_relay: false,
+    };
+    assert!(min_value.filters(&dust(600)));
+    assert!(!min_value.filters(&dust(1_000)));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that an OpReturnPrefix monitor reports the transactions tagged with its prefix:
+/// 1. Watch the `PROTO` prefix next to the pegin monitor, nothing is found at height 200
+/// 2. Tick at 201 with a tagged transaction and a pegin, one news with the txid and full payload
+/// 3. Reprocess block 201 after the ack, the transaction is not reported again
+/// 4. Cancel the prefix monitor, the pegin monitor keeps running
+#[test]
+fn test_op_return_prefix_monitor() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let payload = [b"PROTO".as_slice(), &[9u8; 8]].concat();
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![],
+        output: vec![TxOut {
+            value: Amount::ZERO,
+            script_pubkey: Builder::new()
+                .push_opcode(OP_RETURN)
+                .push_slice(<&bitcoin::script::PushBytes>::try_from(payload.as_slice())?)
+                .into_script(),
+        }],
+    };
+    let tx_id = tx.compute_txid();
+    let pegin_tx = create_pegin_tx();
+
+    fn block_at(height: u32, txs: Vec<Transaction>) -> FullBlock {
+        FullBlock {
+            txs,
+            ..utils::block_at(height)
+        }
+    }
+
+    let height = Arc::new(AtomicU32::new(200));
+
+    let mut mock_indexer = MockIndexerApi::new();
+    let best_height = height.clone();
+    let block_txs = vec![tx.clone(), pegin_tx.clone()];
+    mock_indexer.expect_get_best_block().returning(move || {
+        let height = best_height.load(Ordering::SeqCst);
+        let txs = if height == 201 {
+            block_txs.clone()
+        } else {
+            vec![]
+        };
+        Ok(Some(block_at(height, txs)))
+    });
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(|height| Ok(Some(block_at(height, vec![]))));
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let tx_height = height.clone();
+    mock_indexer.expect_get_tx().returning(move |_| {
+        let height = tx_height.load(Ordering::SeqCst);
+        if height < 201 {
+            return Ok(None);
+        }
+        Ok(Some(TransactionInfo {
+            tx: pegin_tx.clone(),
+            block_info: block_at(201, vec![]),
+            confirmations: height - 200,
+        }))
+    });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+
+    let op_return_news = |news: Vec<MonitorNews>| -> Vec<MonitorNews> {
+        news.into_iter()
+            .filter(|n| matches!(n, MonitorNews::OpReturnTransaction(..)))
+            .collect()
+    };
+
+    // 1. Nothing tagged yet
+    let watch = WatchOpReturn::new(b"PROTO").context("ctx").build()?;
+    monitor.save_monitor(watch.clone())?;
+    monitor.save_monitor(WatchPegin::new().build()?)?;
+    monitor.tick()?;
+    assert!(monitor.get_news()?.is_empty());
+
+    // 2. Only the tagged transaction is reported, with the whole payload
+    height.store(201, Ordering::SeqCst);
+    monitor.tick()?;
+    assert_eq!(
+        op_return_news(monitor.get_news()?),
+        vec![MonitorNews::OpReturnTransaction(
+            tx_id,
+            payload,
+            "ctx".into()
+        )]
+    );
+
+    // 3. Reprocess block 201 after the ack
+    monitor.ack_news(AckMonitorNews::OpReturnTransaction(tx_id, "ctx".into()))?;
+    monitor.store.update_monitor_height(200)?;
+    monitor.store.set_pending_work(true)?;
+    monitor.tick()?;
+    assert!(op_return_news(monitor.get_news()?).is_empty());
+
+    // 4. Cancel the prefix monitor only
+    monitor.store.cancel_monitor(watch)?;
+    let monitors = monitor.store.get_monitors()?;
+    assert!(!monitors
+        .iter()
+        .any(|m| matches!(m, TypesToMonitorStore::OpReturnPrefix(..))));
+    assert!(monitors
+        .iter()
+        .any(|m| matches!(m, TypesToMonitorStore::RskPegin(..))));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that with `track_mempool` a monitored transaction is reported as soon as it is in the mempool:
+/// 1. Watch a transaction, nothing is reported while it is not in the mempool
+/// 2. The transaction enters the mempool, one news with the Mempool status and 0 confirmations
+/// 3. Tick again before and after the ack, the mempool news is not sent again
+/// 4. The transaction is mined at 201, the confirmed news replaces the mempool one
+#[test]
+fn test_mempool_transaction_news() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_time(1653195600).unwrap(),
+        input: vec![],
+        output: vec![],
+    };
+    let tx_id = tx.compute_txid();
+
+    let height = Arc::new(AtomicU32::new(200));
+    let in_mempool = Arc::new(AtomicBool::new(false));
+
+    let mut mock_indexer = scripted_indexer(height.clone());
+    let tx_height = height.clone();
+    let mined_tx = tx.clone();
+    mock_indexer.expect_get_tx().returning(move |_| {
+        let height = tx_height.load(Ordering::SeqCst);
+        if height < 201 {
+            return Ok(None);
+        }
+        Ok(Some(TransactionInfo {
+            tx: mined_tx.clone(),
+            block_info: block_at(201),
+            confirmations: height - 200,
+        }))
+    });
+
+    let mut mock_mempool = MockMempoolApi::new();
+    let mempool_has_tx = in_mempool.clone();
+    mock_mempool
+        .expect_get_mempool_txs()
+        .returning(move |tx_ids| {
+            assert!(tx_ids.contains(&tx_id));
+            if mempool_has_tx.load(Ordering::SeqCst) {
+                Ok(vec![tx.clone()])
+            } else {
+                Ok(vec![])
+            }
+        });
+
+    let mut settings = MonitorSettings::from(MonitorSettingsConfig::default());
+    settings.track_mempool = true;
+    let monitor = Monitor::new(mock_indexer, store, settings)?.with_mempool(Box::new(mock_mempool));
+
+    // 1. Not in the mempool yet
+    monitor.save_monitor(WatchTx::new(tx_id).context("ctx").build()?)?;
+    monitor.tick()?;
+    assert!(monitor.get_news()?.is_empty());
+
+    // 2. Reported from the mempool
+    in_mempool.store(true, Ordering::SeqCst);
+    monitor.tick()?;
+    let news = monitor.get_news()?;
+    assert_eq!(news.len(), 1);
+    assert!(
+        matches!(&news[0], MonitorNews::Transaction(t, status, ctx) if *t == tx_id
+            && status.status == TransactionBlockchainStatus::Mempool
+            && status.confirmations == 0
+            && status.block_info.is_none()
+            && ctx == "ctx")
+    );
+
+    // 3. Not sent again
+    monitor.tick()?;
+    assert_eq!(monitor.get_news()?, news);
+
+    monitor.ack_news(AckMonitorNews::Transaction(tx_id, "ctx".into()))?;
+    monitor.tick()?;
+    assert!(monitor.get_news()?.is_empty());
+
+    // 4. Mined at 201
+    in_mempool.store(false, Ordering::SeqCst);
+    height.store(201, Ordering::SeqCst);
+    monitor.tick()?;
+    let news = monitor.get_news()?;
+    assert_eq!(news.len(), 1);
+    assert!(
+        matches!(&news[0], MonitorNews::Transaction(t, status, _) if *t == tx_id
+            && status.status == TransactionBlockchainStatus::Confirmed
+            && status.confirmations == 1)
+    );
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that the statuses of several transactions are fetched in one call:
+/// 1. One transaction is known by the indexer and the other is not
+/// 2. The known one has its status and the unknown one is None, in the order they were asked
+/// 3. An empty slice returns no statuses without calling the indexer
+#[test]
+fn test_get_tx_statuses() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![],
+        output: vec![],
+    };
+    let known_tx_id = tx.compute_txid();
+    let unknown_tx_id =
+        Txid::from_str("0000000000000000000000000000000000000000000000000000000000000001")?;
+
+    let block = FullBlock {
+        height: 200,
+        hash: BlockHash::from_str(&format!("{:064x}", 200))?,
+        prev_hash: BlockHash::from_str(&format!("{:064x}", 199))?,
+        txs: vec![],
+        orphan: false,
+        estimated_fee_rate: 0,
+    };
+
+    // 1. Only the first transaction is known
+    let mut mock_indexer = MockIndexerApi::new();
+    mock_indexer
+        .expect_get_tx()
+        .times(2)
+        .returning(move |tx_id| {
+            if *tx_id != known_tx_id {
+                return Ok(None);
+            }
+            Ok(Some(TransactionInfo {
+                tx: tx.clone(),
+                block_info: block.clone(),
+                confirmations: 3,
+            }))
+        });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+
+    // 2. Mixed found and not found
+    let statuses = monitor.get_tx_statuses(&[known_tx_id, unknown_tx_id])?;
+    assert_eq!(statuses.len(), 2);
+    assert!(
+        matches!(&statuses[0], (t, Some(status)) if *t == known_tx_id && status.confirmations == 3)
+    );
+    assert_eq!(statuses[1], (unknown_tx_id, None));
+
+    // 3. Empty slice
+    assert!(monitor.get_tx_statuses(&[])?.is_empty());
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that a block returned by the indexer at another height than the requested one stops the tick:
+/// 1. The first tick at 200 is processed
+/// 2. The indexer returns block 200 when asked for block 201, the tick to 202 processing the
+///    blocks in between fails with IndexerInconsistency
+/// 3. The monitor height, block hash and news are the ones of the first tick
+#[test]
+fn test_indexer_inconsistent_block_height() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let height = Arc::new(AtomicU32::new(200));
+    let broken = Arc::new(AtomicBool::new(false));
+
+    let mut mock_indexer = MockIndexerApi::new();
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let best_height = height.clone();
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(block_at(best_height.load(Ordering::SeqCst)))));
+    let by_height_broken = broken.clone();
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |height| {
+            if by_height_broken.load(Ordering::SeqCst) {
+                return Ok(Some(block_at(height - 1)));
+            }
+            Ok(Some(block_at(height)))
+        });
+    mock_indexer
+        .expect_get_block_by_hash()
+        .returning(|_| Ok(Some(block_at(200))));
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+    monitor.save_monitor(TypesToMonitor::NewBlock)?;
+
+    // 1. First tick
+    monitor.tick()?;
+    let news = monitor.get_news()?;
+    assert_eq!(news, vec![MonitorNews::NewBlock(200, block_at(200).hash)]);
+
+    // 2. Block 200 returned for height 201
+    broken.store(true, Ordering::SeqCst);
+    height.store(202, Ordering::SeqCst);
+    assert!(matches!(
+        monitor.tick(),
+        Err(MonitorError::IndexerInconsistency {
+            requested: 201,
+            got: 200
+        })
+    ));
+
+    // 3. Nothing changed
+    assert_eq!(monitor.store.get_monitor_height()?, 200);
+    assert_eq!(
+        monitor.store.get_monitor_block_hash()?,
+        Some(block_at(200).hash)
+    );
+    assert_eq!(monitor.get_news()?, news);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that a transaction returned by the indexer in a block above the best block stops the tick:
+/// 1. The indexer places the monitored transaction at height 205 while its best block is 200
+/// 2. The tick fails with IndexerInconsistency
+/// 3. No news, snapshot or monitor height is written
+#[test]
+fn test_indexer_tx_above_best_block() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![],
+        output: vec![],
+    };
+    let tx_id = tx.compute_txid();
+
+    // 1. Transaction at 205 with the best block at 200
+    let mut mock_indexer = MockIndexerApi::new();
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    mock_indexer
+        .expect_get_best_block()
+        .returning(|| Ok(Some(block_at(200))));
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(|height| Ok(Some(block_at(height))));
+    mock_indexer.expect_get_tx().returning(move |_| {
+        Ok(Some(TransactionInfo {
+            tx: tx.clone(),
+            block_info: block_at(205),
+            confirmations: 1,
+        }))
+    });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+    monitor.save_monitor(WatchTx::new(tx_id).context("ctx").build()?)?;
+
+    // 2. Tick fails
+    assert!(matches!(
+        monitor.tick(),
+        Err(MonitorError::IndexerInconsistency {
+            requested: 200,
+            got: 205
+        })
+    ));
+
+    // 3. Nothing written
+    assert_eq!(monitor.store.get_monitor_height()?, 0);
+    assert!(monitor.store.get_news()?.is_empty());
+    assert!(monitor.store.get_tx_snapshots()?.is_empty());
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that the pending news can be read in pages:
+/// 1. Five news are emitted, the first page has two of them and a cursor to the next one
+/// 2. The same page is returned again while nothing changes
+/// 3. Acknowledging a news read and one of the next page doesn't move the rest between pages
+/// 4. The last page has no next cursor
+#[test]
+fn test_get_news_page() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let monitor = Monitor::new(
+        MockIndexerApi::new(),
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+
+    let old = Txid::from_str("0000000000000000000000000000000000000000000000000000000000000001")?;
+    let new = Txid::from_str("0000000000000000000000000000000000000000000000000000000000000002")?;
+    let block_hash = BlockHash::from_str(&format!("{:064x}", 200))?;
+    let context = |news: &MonitorNews| match news {
+        MonitorNews::MonitorReplaced { context, .. } => context.clone(),
+        _ => panic!("Expected MonitorReplaced news"),
+    };
+
+    // 1. Five news, pages of two
+    for i in 0..5 {
+        monitor.store.update_news(
+            MonitoredTypes::MonitorReplaced(old, new, format!("c{i}").into()),
+            block_hash,
+        )?;
+    }
+
+    let first = monitor.get_news_page(None, 2)?;
+    let contexts: Vec<MonitorContext> = first.news.iter().map(|e| context(&e.news)).collect();
+    assert_eq!(contexts, ["c0", "c1"]);
+    assert!(first.next.is_some());
+
+    // 2. Stable
+    assert_eq!(monitor.get_news_page(None, 2)?, first);
+
+    // 3. Acks don't move the pending news
+    monitor.ack_news(AckMonitorNews::MonitorReplaced {
+        old,
+        context: "c0".into(),
+    })?;
+    monitor.ack_news(AckMonitorNews::MonitorReplaced {
+        old,
+        context: "c3".into(),
+    })?;
+
+    let second = monitor.get_news_page(first.next, 2)?;
+    let contexts: Vec<MonitorContext> = second.news.iter().map(|e| context(&e.news)).collect();
+    assert_eq!(contexts, ["c2", "c4"]);
+
+    // 4. Last page
+    assert!(second.next.is_none());
+    assert!(monitor.get_news_page(None, 0)?.news.is_empty());
+    assert_eq!(monitor.get_news_page(None, 10)?.news.len(), 3);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that deactivated monitors come back with reactivate:
+/// 1. A transaction monitor with a trigger and a SpendingUTXO monitor are deactivated at height 200
+/// 2. The transaction is mined at 201, no news is sent
+/// 3. Both monitors are reactivated, keeping their context and trigger
+/// 4. The spender is mined at 202, both monitors send news
+#[test]
+fn test_reactivate_monitor() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_time(1653195600).unwrap(),
+        input: vec![],
+        output: vec![],
+    };
+    let tx_id = tx.compute_txid();
+
+    let target_tx_id =
+        Txid::from_str("0000000000000000000000000000000000000000000000000000000000000009")?;
+    let spender = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_time(1653195601).unwrap(),
+        input: vec![bitcoin::TxIn {
+            previous_output: bitcoin::OutPoint {
+                txid: target_tx_id,
+                vout: 0,
+            },
+            script_sig: bitcoin::ScriptBuf::new(),
+            sequence: bitcoin::Sequence::MAX,
+            witness: bitcoin::Witness::new(),
+        }],
+        output: vec![],
+    };
+    let spender_id = spender.compute_txid();
+
+    let mined_at = [(tx.clone(), 201), (spender.clone(), 202)];
+    let block_at = move |height: u32| FullBlock {
+        height,
+        hash: BlockHash::from_str(&format!("{:064x}", height)).unwrap(),
+        prev_hash: BlockHash::from_str(&format!("{:064x}", height - 1)).unwrap(),
+        txs: mined_at
+            .iter()
+            .filter(|(_, at)| *at == height)
+            .map(|(tx, _)| tx.clone())
+            .collect(),
+        orphan: false,
+        estimated_fee_rate: 0,
+    };
+
+    let height = Arc::new(AtomicU32::new(200));
+
+    let mut mock_indexer = MockIndexerApi::new();
+    let (best_height, best_block_at) = (height.clone(), block_at.clone());
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(best_block_at(best_height.load(Ordering::SeqCst)))));
+    let by_height_block_at = block_at.clone();
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |height| Ok(Some(by_height_block_at(height))));
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let tx_height = height.clone();
+    mock_indexer.expect_get_tx().returning(move |tx_id| {
+        let height = tx_height.load(Ordering::SeqCst);
+        Ok((201..=height).map(&block_at).find_map(|block| {
+            let tx = block
+                .txs
+                .iter()
+                .find(|tx| tx.compute_txid() == *tx_id)?
+                .clone();
+            Some(TransactionInfo {
+                tx,
+                confirmations: height - block.height + 1,
+                block_info: block,
+            })
+        }))
+    });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+
+    // 1. Deactivate both monitors
+    let watch_tx = WatchTx::new(tx_id).context("tx").trigger_at(2).build()?;
+    let watch_outpoint = WatchOutpoint::new(target_tx_id, 0)
+        .context("spend")
+        .build()?;
+    monitor.save_monitor(watch_tx.clone())?;
+    monitor.save_monitor(watch_outpoint.clone())?;
+    monitor.tick()?;
+
+    monitor.store.deactivate_monitor(watch_tx.clone())?;
+    monitor.store.deactivate_monitor(watch_outpoint.clone())?;
+    assert!(monitor.get_monitors(false)?.is_empty());
+    assert_eq!(monitor.get_monitors(true)?.len(), 2);
+
+    // 2. Mined while deactivated
+    height.store(201, Ordering::SeqCst);
+    monitor.tick()?;
+    assert!(monitor.get_news()?.is_empty());
+
+    // 3. Reactivate
+    monitor.reactivate(watch_tx)?;
+    monitor.reactivate(watch_outpoint)?;
+    let monitors = monitor.store.get_monitors()?;
+    assert_eq!(monitors.len(), 2);
+    assert!(monitors.contains(&TypesToMonitorStore::Transaction(
+        tx_id,
+        "tx".into(),
+        Some(2),
+        None,
+    )));
+    assert!(monitors.iter().any(|m| matches!(
+        m,
+        TypesToMonitorStore::SpendingUTXOTransaction(t, 0, ctx, None, _, _, _) if *t == target_tx_id && ctx == "spend"
+    )));
+
+    // 4. News on the next tick
+    height.store(202, Ordering::SeqCst);
+    monitor.tick()?;
+    let news = monitor.get_news()?;
+    assert_eq!(news.len(), 2);
+    assert!(news.iter().any(|n| matches!(
+        n,
+        MonitorNews::Transaction(t, status, ctx) if *t == tx_id && status.confirmations == 2 && ctx == "tx"
+    )));
+    assert!(news.iter().any(|n| matches!(
+        n,
+        MonitorNews::SpendingUTXOTransaction(t, 0, status, ctx) if *t == target_tx_id && status.tx_id == spender_id && ctx == "spend"
+    )));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that the monitored transactions waiting in the mempool are listed as fee bump candidates:
+/// 1. Three transactions enter the mempool at 200, 203 and 202, one of them with a trigger
+/// 2. At 206 they are listed by how long they have been waiting, with the last fee rate seen
+/// 3. Only the transactions waiting for more than the threshold are listed
+/// 4. The transaction mined at 207 is no longer listed
+#[test]
+fn test_stale_unconfirmed() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    // (transaction, height it enters the mempool at)
+    let entries: Vec<(Transaction, u32)> =
+        [(1653195600, 200), (1653195601, 203), (1653195602, 202)]
+            .into_iter()
+            .map(|(time, entered_at)| {
+                let tx = Transaction {
+                    version: bitcoin::transaction::Version::TWO,
+                    lock_time: LockTime::from_time(time).unwrap(),
+                    input: vec![],
+                    output: vec![],
+                };
+                (tx, entered_at)
+            })
+            .collect();
+    let tx_ids: Vec<Txid> = entries.iter().map(|(tx, _)| tx.compute_txid()).collect();
+    let mined = entries[2].0.clone();
+
+    fn block_at(height: u32) -> FullBlock {
+        FullBlock {
+            estimated_fee_rate: height as u64,
+            ..utils::block_at(height)
+        }
+    }
+
+    let height = Arc::new(AtomicU32::new(200));
+
+    let mut mock_indexer = MockIndexerApi::new();
+    let best_height = height.clone();
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(block_at(best_height.load(Ordering::SeqCst)))));
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(|height| Ok(Some(block_at(height))));
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let (tx_height, mined_tx) = (height.clone(), mined.clone());
+    mock_indexer.expect_get_tx().returning(move |tx_id| {
+        let height = tx_height.load(Ordering::SeqCst);
+        if height < 207 || *tx_id != mined_tx.compute_txid() {
+            return Ok(None);
+        }
+        Ok(Some(TransactionInfo {
+            tx: mined_tx.clone(),
+            block_info: block_at(207),
+            confirmations: height - 206,
+        }))
+    });
+
+    let mut mock_mempool = MockMempoolApi::new();
+    let mempool_height = height.clone();
+    mock_mempool.expect_get_mempool_txs().returning(move |_| {
+        let height = mempool_height.load(Ordering::SeqCst);
+        Ok(entries
+            .iter()
+            .filter(|(tx, entered_at)| *entered_at <= height && (height < 207 || *tx != mined))
+            .map(|(tx, _)| tx.clone())
+            .collect())
+    });
+
+    let mut settings = MonitorSettings::from(MonitorSettingsConfig::default());
+    settings.track_mempool = true;
+    let monitor = Monitor::new(mock_indexer, store, settings)?.with_mempool(Box::new(mock_mempool));
+
+    // 1. Enter the mempool
+    monitor.save_monitor(WatchTx::new(tx_ids[0]).context("a").build()?)?;
+    monitor.save_monitor(WatchTx::new(tx_ids[1]).context("b").trigger_at(2).build()?)?;
+    monitor.save_monitor(WatchTx::new(tx_ids[2]).context("c").build()?)?;
+    for current_height in 200..=206 {
+        height.store(current_height, Ordering::SeqCst);
+        monitor.tick()?;
+    }
+
+    // 2. Longest waiting first
+    let stale = monitor.stale_unconfirmed(0)?;
+    let waiting: Vec<(Txid, &str, u32, u32)> = stale
+        .iter()
+        .map(|s| {
+            (
+                s.txid,
+                s.context.as_str(),
+                s.first_seen_height,
+                s.blocks_waiting,
+            )
+        })
+        .collect();
+    assert_eq!(
+        waiting,
+        vec![
+            (tx_ids[0], "a", 200, 6),
+            (tx_ids[2], "c", 202, 4),
+            (tx_ids[1], "b", 203, 3),
+        ]
+    );
+    assert!(stale.iter().all(|s| s.last_known_fee_rate == 206));
+
+    // 3. Threshold
+    let stale = monitor.stale_unconfirmed(3)?;
+    assert_eq!(
+        stale.iter().map(|s| s.txid).collect::<Vec<_>>(),
+        vec![tx_ids[0], tx_ids[2]]
+    );
+    assert!(monitor.stale_unconfirmed(6)?.is_empty());
+
+    // 4. Mined
+    height.store(207, Ordering::SeqCst);
+    monitor.tick()?;
+    let stale = monitor.stale_unconfirmed(0)?;
+    assert_eq!(
+        stale.iter().map(|s| s.txid).collect::<Vec<_>>(),
+        vec![tx_ids[0], tx_ids[1]]
+    );
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that the monitors are listed with their state and the spender found:
+/// 1. A transaction, a SpendingUTXO and a NewBlock monitor are listed as active
+/// 2. The transaction reaches max_monitoring_confirmations at 202, it is only listed with the inactive ones
+/// 3. The spender found at 202 is listed with the SpendingUTXO monitor, also once deactivated
+#[test]
+fn test_get_monitors() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_time(1653195600).unwrap(),
+        input: vec![],
+        output: vec![],
+    };
+    let tx_id = tx.compute_txid();
+
+    let target_tx_id =
+        Txid::from_str("0000000000000000000000000000000000000000000000000000000000000009")?;
+    let spender = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_time(1653195601).unwrap(),
+        input: vec![bitcoin::TxIn {
+            previous_output: bitcoin::OutPoint {
+                txid: target_tx_id,
+                vout: 0,
+            },
+            script_sig: bitcoin::ScriptBuf::new(),
+            sequence: bitcoin::Sequence::MAX,
+            witness: bitcoin::Witness::new(),
+        }],
+        output: vec![],
+    };
+    let spender_id = spender.compute_txid();
+
+    let mined_at = [(tx, 201), (spender, 202)];
+    let block_at = move |height: u32| FullBlock {
+        height,
+        hash: BlockHash::from_str(&format!("{:064x}", height)).unwrap(),
+        prev_hash: BlockHash::from_str(&format!("{:064x}", height - 1)).unwrap(),
+        txs: mined_at
+            .iter()
+            .filter(|(_, at)| *at == height)
+            .map(|(tx, _)| tx.clone())
+            .collect(),
+        orphan: false,
+        estimated_fee_rate: 0,
+    };
+
+    let height = Arc::new(AtomicU32::new(200));
+
+    let mut mock_indexer = MockIndexerApi::new();
+    let (best_height, best_block_at) = (height.clone(), block_at.clone());
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(best_block_at(best_height.load(Ordering::SeqCst)))));
+    let by_height_block_at = block_at.clone();
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |height| Ok(Some(by_height_block_at(height))));
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let tx_height = height.clone();
+    mock_indexer.expect_get_tx().returning(move |tx_id| {
+        let height = tx_height.load(Ordering::SeqCst);
+        Ok((201..=height).map(&block_at).find_map(|block| {
+            let tx = block
+                .txs
+                .iter()
+                .find(|tx| tx.compute_txid() == *tx_id)?
+                .clone();
+            Some(TransactionInfo {
+                tx,
+                confirmations: height - block.height + 1,
+                block_info: block,
+            })
+        }))
+    });
+
+    let mut settings = MonitorSettings::from(MonitorSettingsConfig::default());
+    settings.max_monitoring_confirmations = 2;
+    let monitor = Monitor::new(mock_indexer, store, settings)?;
+
+    // 1. All active
+    monitor.save_monitor(WatchTx::new(tx_id).context("tx").build()?)?;
+    monitor.save_monitor(
+        WatchOutpoint::new(target_tx_id, 0)
+            .context("spend")
+            .build()?,
+    )?;
+    monitor.save_monitor(TypesToMonitor::NewBlock)?;
+    monitor.tick()?;
+
+    let monitors = monitor.get_monitors(false)?;
+    assert_eq!(monitors.len(), 3);
+    assert!(monitors
+        .iter()
+        .all(|m| m.active && m.spender_tx_id.is_none()));
+    assert_eq!(monitor.get_monitors(true)?, monitors);
+
+    // 2. The transaction is deactivated
+    for current_height in 201..=202 {
+        height.store(current_height, Ordering::SeqCst);
+        monitor.tick()?;
+    }
+
+    let is_tx = |m: &MonitorInfo| matches!(&m.monitor, TypesToMonitorStore::Transaction(t, ctx, None, _) if *t == tx_id && ctx == "tx");
+    let is_spend = |m: &MonitorInfo| matches!(&m.monitor, TypesToMonitorStore::SpendingUTXOTransaction(t, 0, ctx, None, None, None, _) if *t == target_tx_id && ctx == "spend");
+
+    let active = monitor.get_monitors(false)?;
+    assert!(!active.iter().any(is_tx));
+    assert!(active
+        .iter()
+        .any(|m| matches!(m.monitor, TypesToMonitorStore::NewBlock)));
+
+    let all = monitor.get_monitors(true)?;
+    assert_eq!(all.iter().filter(|m| !m.active).count(), 1);
+    assert!(all.iter().any(|m| is_tx(m) && !m.active));
+
+    // 3. The spender is listed with its monitor
+    let spend = active
+        .iter()
+        .find(|m| is_spend(m))
+        .expect("spending monitor");
+    assert!(spend.active);
+    assert_eq!(spend.spender_tx_id, Some(spender_id));
+
+    height.store(203, Ordering::SeqCst);
+    monitor.tick()?;
+    let all = monitor.get_monitors(true)?;
+    let spend = all.iter().find(|m| is_spend(m)).expect("spending monitor");
+    assert!(!spend.active);
+    assert_eq!(spend.spender_tx_id, Some(spender_id));
+    assert!(!monitor.get_monitors(false)?.iter().any(is_spend));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that a transaction news is only sent again when its confirmations or block changed:
+/// 1. The transaction is found in block 200 with 2 confirmations
+/// 2. After the ack, reprocessing the same tip or a tip replaced at the same height sends nothing
+/// 3. After the ack, a new block sends the news with 3 confirmations
+/// 4. A reorg moving the transaction to block 201 sends the news with the same confirmations
+#[test]
+fn test_transaction_news_only_on_changes() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_time(1653195600).unwrap(),
+        input: vec![],
+        output: vec![],
+    };
+    let tx_id = tx.compute_txid();
+
+    /// Tip height, the branch of the blocks from the fork height on and the transaction height
+    #[derive(Clone, Copy)]
+    struct Chain {
+        height: u32,
+        branch: u32,
+        fork: u32,
+        tx_height: u32,
+    }
+
+    impl Chain {
+        fn block_at(&self, height: u32) -> FullBlock {
+            let hash = |height: u32| {
+                let branch = if height >= self.fork { self.branch } else { 0 };
+                BlockHash::from_str(&format!("{:056x}{:08x}", branch, height)).unwrap()
+            };
+
+            FullBlock {
+                height,
+                hash: hash(height),
+                prev_hash: hash(height.saturating_sub(1)),
+                txs: vec![],
+                orphan: false,
+                estimated_fee_rate: 0,
+            }
+        }
+    }
+
+    let chain = Arc::new(std::sync::Mutex::new(Chain {
+        height: 201,
+        branch: 0,
+        fork: 0,
+        tx_height: 200,
+    }));
+
+    let mut mock_indexer = MockIndexerApi::new();
+    let best_chain = chain.clone();
+    mock_indexer.expect_get_best_block().returning(move || {
+        let chain = *best_chain.lock().unwrap();
+        Ok(Some(chain.block_at(chain.height)))
+    });
+    let height_chain = chain.clone();
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |height| Ok(Some(height_chain.lock().unwrap().block_at(height))));
+    let hash_chain = chain.clone();
+    mock_indexer
+        .expect_get_block_by_hash()
+        .returning(move |hash| {
+            let chain = *hash_chain.lock().unwrap();
+            let height = u32::from_str_radix(&hash.to_string()[56..], 16).unwrap();
+            Ok(Some(chain.block_at(height)).filter(|block| block.hash == *hash))
+        });
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let tx_chain = chain.clone();
+    mock_indexer.expect_get_tx().returning(move |_| {
+        let chain = *tx_chain.lock().unwrap();
+        Ok(Some(TransactionInfo {
+            tx: tx.clone(),
+            block_info: chain.block_at(chain.tx_height),
+            confirmations: chain.height - chain.tx_height + 1,
+        }))
+    });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+
+    let transaction_news = |monitor: &Monitor<MockIndexerApi, MonitorStore>| {
+        Ok::<_, MonitorError>(
+            monitor
+                .get_news()?
+                .into_iter()
+                .filter_map(|news| match news {
+                    MonitorNews::Transaction(_, status, _) => {
+                        Some((status.confirmations, status.block_info.map(|b| b.hash)))
+                    }
+                    _ => None,
+                })
+                .collect::<Vec<_>>(),
+        )
+    };
+    let ack = |monitor: &Monitor<MockIndexerApi, MonitorStore>| {
+        monitor.ack_news(AckMonitorNews::Transaction(tx_id, "ctx".into()))
+    };
+
+    // 1. Found with 2 confirmations
+    monitor.save_monitor(WatchTx::new(tx_id).context("ctx").build()?)?;
+    monitor.tick()?;
+    let block_200 = chain.lock().unwrap().block_at(200).hash;
+    assert_eq!(transaction_news(&monitor)?, vec![(2, Some(block_200))]);
+    ack(&monitor)?;
+
+    // 2. Same tip reprocessed, then the tip replaced at the same height
+    monitor.store.update_monitor_height(200)?;
+    monitor.store.set_pending_work(true)?;
+    monitor.tick()?;
+    assert!(transaction_news(&monitor)?.is_empty());
+
+    {
+        let mut chain = chain.lock().unwrap();
+        chain.branch = 1;
+        chain.fork = 201;
+    }
+    monitor.store.set_pending_work(true)?;
+    monitor.tick()?;
+    assert!(transaction_news(&monitor)?.is_empty());
+
+    // 3. New block
+    chain.lock().unwrap().height = 202;
+    monitor.tick()?;
+    assert_eq!(transaction_news(&monitor)?, vec![(3, Some(block_200))]);
+    ack(&monitor)?;
+
+    // 4. Reorg from block 200 to a longer chain, the transaction is in the new block 201
+    *chain.lock().unwrap() = Chain {
+        height: 203,
+        branch: 2,
+        fork: 200,
+        tx_height: 201,
+    };
+    monitor.tick()?;
+    let block_201 = chain.lock().unwrap().block_at(201).hash;
+    assert_eq!(transaction_news(&monitor)?, vec![(3, Some(block_201))]);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test what canceling monitors does with their unacked news under each `CancelNewsPolicy`:
+/// 1. A Transaction and a SpendingUTXOTransaction monitor find their transactions in block 200
+/// 2. The indexer forgets the transactions, so their news fail to resolve in `Fresh` mode after
+///    the next tick
+/// 3. Both monitors are canceled with the policy: KeepNews keeps the failing news, DropNews
+///    removes them and AckNews acknowledges them, so get_news succeeds again
+/// 4. The next block sends no news for the canceled monitors
+#[test]
+fn test_cancel_news_policies() -> Result<(), anyhow::Error> {
+    for policy in [
+        CancelNewsPolicy::KeepNews,
+        CancelNewsPolicy::DropNews,
+        CancelNewsPolicy::AckNews,
+    ] {
+        let path = format!("test_outputs/{}", generate_random_string());
+        let config = StorageConfig::new(path, None);
+        let storage = Arc::new(Storage::new(&config)?);
+        let store = MonitorStore::new(storage)?;
+
+        let target = Txid::from_str(&format!("{:064x}", 1))?;
+        let paid = Transaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: LockTime::from_consensus(1),
+            input: vec![],
+            output: vec![],
+        };
+        let spender = Transaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: LockTime::from_consensus(2),
+            input: vec![bitcoin::TxIn {
+                previous_output: bitcoin::OutPoint::new(target, 0),
+                ..Default::default()
+            }],
+            output: vec![],
+        };
+        let paid_id = paid.compute_txid();
+
+        let block_at = {
+            let txs = vec![paid.clone(), spender.clone()];
+            move |height: u32| FullBlock {
+                height,
+                hash: BlockHash::from_str(&format!("{:064x}", height)).unwrap(),
+                prev_hash: BlockHash::from_str(&format!("{:064x}", height - 1)).unwrap(),
+                txs: if height == 200 { txs.clone() } else { vec![] },
+                orphan: false,
+                estimated_fee_rate: 0,
+            }
+        };
+
+        let height = Arc::new(AtomicU32::new(200));
+        let forgotten = Arc::new(AtomicBool::new(false));
+
+        let mut mock_indexer = MockIndexerApi::new();
+        let (best_height, best_block) = (height.clone(), block_at.clone());
+        mock_indexer
+            .expect_get_best_block()
+            .returning(move || Ok(Some(best_block(best_height.load(Ordering::SeqCst)))));
+        let block_by_height = block_at.clone();
+        mock_indexer
+            .expect_get_block_by_height()
+            .returning(move |height| Ok(Some(block_by_height(height))));
+        mock_indexer.expect_tick().returning(|| Ok(()));
+        let (tx_height, tx_forgotten) = (height.clone(), forgotten.clone());
+        mock_indexer.expect_get_tx().returning(move |tx_id| {
+            if tx_forgotten.load(Ordering::SeqCst) {
+                return Ok(None);
+            }
+            let tip = tx_height.load(Ordering::SeqCst);
+            Ok([paid.clone(), spender.clone()]
+                .into_iter()
+                .find(|tx| tx.compute_txid() == *tx_id)
+                .map(|tx| TransactionInfo {
+                    tx,
+                    block_info: block_at(200),
+                    confirmations: tip - 199,
+                }))
+        });
+
+        let mut settings = MonitorSettings::from(MonitorSettingsConfig::default());
+        settings.news_resolution = Resolution::Fresh;
+        let monitor = Monitor::new(mock_indexer, store, settings)?;
+
+        // 1. News of both monitors
+        let watch_tx = WatchTx::new(paid_id).context("paid").build()?;
+        let watch_spend = WatchOutpoint::new(target, 0).context("spent").build()?;
+        monitor.save_monitor(watch_tx.clone())?;
+        monitor.save_monitor(watch_spend.clone())?;
+        monitor.tick()?;
+        assert_eq!(monitor.get_news()?.len(), 2);
+
+        // 2. Resolution fails, once the indexer lookups cached by the tick are gone
+        forgotten.store(true, Ordering::SeqCst);
+        monitor.tick()?;
+        assert!(matches!(
+            monitor.get_news(),
+            Err(MonitorError::TransactionNotFound(_))
+        ));
+
+        // 3. Canceled with the policy
+        monitor.cancel_with_policy(watch_tx, policy)?;
+        monitor.cancel_with_policy(watch_spend, policy)?;
+        assert!(monitor.get_monitors(true)?.is_empty());
+
+        let state = logical_snapshot(&monitor.store)?;
+        let tx_news = state.get(&format!("news/tx/{}/paid", paid_id));
+        let spend_news = state.get(&format!("news/spending/{}:0/spent", target));
+        match policy {
+            CancelNewsPolicy::KeepNews => {
+                assert!(monitor.get_news().is_err());
+                for news in [tx_news, spend_news] {
+                    assert!(news.is_some_and(|value| value.contains(r#""acknowledged":false"#)));
+                }
+            }
+            CancelNewsPolicy::DropNews => {
+                assert!(monitor.get_news()?.is_empty());
+                assert_eq!((tx_news, spend_news), (None, None));
+            }
+            CancelNewsPolicy::AckNews => {
+                assert!(monitor.get_news()?.is_empty());
+                for news in [tx_news, spend_news] {
+                    assert!(news.is_some_and(|value| value.contains(r#""acknowledged":true"#)));
+                }
+            }
+        }
+
+        // 4. Nothing more for the canceled monitors
+        forgotten.store(false, Ordering::SeqCst);
+        if policy == CancelNewsPolicy::KeepNews {
+            monitor.ack_news(AckMonitorNews::Transaction(paid_id, "paid".into()))?;
+            monitor.ack_news(AckMonitorNews::SpendingUTXOTransaction(
+                target,
+                0,
+                "spent".into(),
+            ))?;
+        }
+        height.store(201, Ordering::SeqCst);
+        monitor.tick()?;
+        assert!(monitor.get_news()?.is_empty(), "{policy:?}");
+
+        clear_output();
+    }
+
+    Ok(())
+}
+
+/// Test that canceled monitors are kept in the trash and restored with their state:
+/// 1. A transaction monitor with a trigger and a spending monitor of the "batch" context detect
+///    their transactions in block 200, next to another monitor
+/// 2. Canceling the "batch" monitors moves them to the trash
+/// 3. Restoring the "batch" context brings both back with the trigger sent and the spender found,
+///    so the trigger news is not sent again
+/// 4. A permanent cancel is not kept in the trash
+/// 5. The sweeper purges the trash entries older than `retention.trash_blocks`
+#[test]
+fn test_trash_restore() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let target = Txid::from_str(&format!("{:064x}", 1))?;
+    let other = Txid::from_str(&format!("{:064x}", 2))?;
+    let paid = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_consensus(1),
+        input: vec![],
+        output: vec![],
+    };
+    let spender = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_consensus(2),
+        input: vec![bitcoin::TxIn {
+            previous_output: bitcoin::OutPoint::new(target, 0),
+            ..Default::default()
+        }],
+        output: vec![],
+    };
+    let (paid_id, spender_id) = (paid.compute_txid(), spender.compute_txid());
+
+    let block_at = {
+        let txs = vec![paid.clone(), spender.clone()];
+        move |height: u32| FullBlock {
+            height,
+            hash: BlockHash::from_str(&format!("{:064x}", height)).unwrap(),
+            prev_hash: BlockHash::from_str(&format!("{:064x}", height - 1)).unwrap(),
+            txs: if height == 200 { txs.clone() } else { vec![] },
+            orphan: false,
+            estimated_fee_rate: 0,
+        }
+    };
+
+    let height = Arc::new(AtomicU32::new(200));
+
+    let mut mock_indexer = MockIndexerApi::new();
+    let (best_height, best_block) = (height.clone(), block_at.clone());
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(best_block(best_height.load(Ordering::SeqCst)))));
+    let block_by_height = block_at.clone();
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |height| Ok(Some(block_by_height(height))));
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let tx_height = height.clone();
+    mock_indexer.expect_get_tx().returning(move |tx_id| {
+        let tip = tx_height.load(Ordering::SeqCst);
+        Ok([paid.clone(), spender.clone()]
+            .into_iter()
+            .find(|tx| tx.compute_txid() == *tx_id)
+            .map(|tx| TransactionInfo {
+                tx,
+                block_info: block_at(200),
+                confirmations: tip - 199,
+            }))
+    });
+
+    let mut settings = MonitorSettings::from(MonitorSettingsConfig::default());
+    settings.sweep_interval_blocks = 1;
+    settings.retention.trash_blocks = 5;
+    let monitor = Monitor::new(mock_indexer, store, settings)?;
+
+    // 1. Detections
+    let watch_tx = WatchTx::new(paid_id)
+        .context("batch")
+        .trigger_at(1)
+        .build()?;
+    let watch_spend = WatchOutpoint::new(target, 0).context("batch").build()?;
+    let watch_other = WatchTx::new(other).context("other").build()?;
+    monitor.save_monitor(watch_tx.clone())?;
+    monitor.save_monitor(watch_spend.clone())?;
+    monitor.save_monitor(watch_other.clone())?;
+    monitor.tick()?;
+    assert_eq!(monitor.get_news()?.len(), 2);
+    monitor.ack_news(AckMonitorNews::Transaction(paid_id, "batch".into()))?;
+    monitor.ack_news(AckMonitorNews::SpendingUTXOTransaction(
+        target,
+        0,
+        "batch".into(),
+    ))?;
+    let monitors = monitor.get_monitors(true)?;
+    assert_eq!(monitors.len(), 4);
+
+    // 2. Canceled to the trash
+    monitor.cancel(watch_tx)?;
+    monitor.cancel(watch_spend)?;
+    assert_eq!(monitor.get_monitors(true)?.len(), 1);
+
+    let trash = monitor.list_trash()?;
+    assert_eq!(trash.len(), 2);
+    assert!(trash.iter().all(|entry| entry.deleted_at == 200));
+    assert!(trash
+        .iter()
+        .all(|entry| entry.matches(&TrashSelector::Context("batch".into()))));
+
+    // 3. Restored with their state
+    let restored = monitor.restore_from_trash(TrashSelector::Context("batch".into()))?;
+    assert_eq!(restored, trash);
+    assert!(monitor.list_trash()?.is_empty());
+
+    let mut restored_monitors = monitor.get_monitors(true)?;
+    let mut expected = monitors;
+    let key = |info: &MonitorInfo| format!("{:?}", info.monitor);
+    restored_monitors.sort_by_key(key);
+    expected.sort_by_key(key);
+    assert_eq!(restored_monitors, expected);
+    assert!(restored_monitors
+        .iter()
+        .any(|info| info.spender_tx_id == Some(spender_id)));
+
+    height.store(201, Ordering::SeqCst);
+    monitor.tick()?;
+    let news = monitor.get_news()?;
+    assert!(!news
+        .iter()
+        .any(|news| matches!(news, MonitorNews::Transaction(..))));
+    assert!(news.iter().any(|news| matches!(
+        news,
+        MonitorNews::SpendingUTXOTransaction(tx_id, 0, status, context)
+            if *tx_id == target && status.tx_id == spender_id && context == "batch"
+    )));
+
+    // 4. Permanent cancel
+    monitor.cancel_permanent(watch_other)?;
+    assert!(monitor.list_trash()?.is_empty());
+    assert_eq!(monitor.get_monitors(true)?.len(), 3);
+
+    // 5. Purged by the sweeper
+    monitor.cancel(WatchTx::new(paid_id).context("batch").build()?)?;
+    for tip in [202, 205] {
+        height.store(tip, Ordering::SeqCst);
+        monitor.tick()?;
+        assert_eq!(monitor.list_trash()?.len(), 1);
+    }
+    height.store(206, Ordering::SeqCst);
+    monitor.tick()?;
+    assert!(monitor.list_trash()?.is_empty());
+    assert!(monitor.restore_from_trash(TrashSelector::All)?.is_empty());
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that the news of a block get the same sequences whatever the order the monitors were
+/// registered in:
+/// 1. Monitors of every kind are registered in a shuffled order, and in the reverse order on a
+///    fresh store, then block 200 is processed
+/// 2. Both runs assign the same sequences to the same news
+/// 3. The news follow the canonical order: by kind, then by target and context
+#[test]
+fn test_news_order_is_canonical() -> Result<(), anyhow::Error> {
+    let target = Txid::from_str(&format!("{:064x}", 1))?;
+    let script_pubkey = ScriptBuf::from_hex(&format!("0014{}", "07".repeat(20)))?;
+    let op_return = Builder::new()
+        .push_opcode(OP_RETURN)
+        .push_slice(b"TAG:1")
+        .into_script();
+
+    let tx_with = |lock_time: u32, input: Vec<bitcoin::TxIn>, output: Vec<TxOut>| Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_consensus(lock_time),
+        input,
+        output,
+    };
+    let paid = tx_with(1, vec![], vec![]);
+    let other = tx_with(2, vec![], vec![]);
+    let spender = tx_with(
+        3,
+        vec![bitcoin::TxIn {
+            previous_output: bitcoin::OutPoint::new(target, 0),
+            ..Default::default()
+        }],
+        vec![],
+    );
+    let deposit = tx_with(
+        4,
+        vec![],
+        vec![TxOut {
+            value: Amount::from_sat(10_000),
+            script_pubkey: script_pubkey.clone(),
+        }],
+    );
+    let tagged = tx_with(
+        5,
+        vec![],
+        vec![TxOut {
+            value: Amount::ZERO,
+            script_pubkey: op_return,
+        }],
+    );
+    let mined = vec![paid, other, spender, deposit, tagged];
+    let ids: Vec<Txid> = mined.iter().map(|tx| tx.compute_txid()).collect();
+
+    let monitors = vec![
+        WatchTx::new(ids[0]).context("b").build()?,
+        WatchScript::new(script_pubkey).context("e").build()?,
+        TypesToMonitor::NewBlock,
+        WatchOutpoint::new(target, 0).context("d").build()?,
+        WatchTx::new(ids[0]).context("a").build()?,
+        WatchOpReturn::new(b"TAG:".to_vec()).context("f").build()?,
+        WatchTx::new(ids[1]).context("c").build()?,
+    ];
+
+    let run = |monitors: Vec<TypesToMonitor>| -> Result<Vec<(u64, MonitorNews)>, anyhow::Error> {
+        let path = format!("test_outputs/{}", generate_random_string());
+        let config = StorageConfig::new(path, None);
+        let storage = Arc::new(Storage::new(&config)?);
+        let store = MonitorStore::new(storage)?;
+
+        let block = FullBlock {
+            height: 200,
+            hash: BlockHash::from_str(&format!("{:064x}", 200))?,
+            prev_hash: BlockHash::from_str(&format!("{:064x}", 199))?,
+            txs: mined.clone(),
+            orphan: false,
+            estimated_fee_rate: 0,
+        };
+
+        let mut mock_indexer = MockIndexerApi::new();
+        let best_block = block.clone();
+        mock_indexer
+            .expect_get_best_block()
+            .returning(move || Ok(Some(best_block.clone())));
+        let block_by_height = block.clone();
+        mock_indexer
+            .expect_get_block_by_height()
+            .returning(move |_| Ok(Some(block_by_height.clone())));
+        let block_by_hash = block.clone();
+        mock_indexer
+            .expect_get_block_by_hash()
+            .returning(move |_| Ok(Some(block_by_hash.clone())));
+        mock_indexer.expect_tick().returning(|| Ok(()));
+        let txs = block.clone();
+        mock_indexer.expect_get_tx().returning(move |tx_id| {
+            Ok(txs
+                .txs
+                .iter()
+                .find(|tx| tx.compute_txid() == *tx_id)
+                .map(|tx| TransactionInfo {
+                    tx: tx.clone(),
+                    block_info: txs.clone(),
+                    confirmations: 1,
+                }))
+        });
+
+        let monitor = Monitor::new(
+            mock_indexer,
+            store,
+            MonitorSettings::from(MonitorSettingsConfig::default()),
+        )?;
+        for data in monitors {
+            monitor.save_monitor(data)?;
+        }
+        monitor.tick()?;
+
+        let mut news: Vec<(u64, MonitorNews)> = monitor
+            .get_news_envelopes()?
+            .into_iter()
+            .map(|envelope| (envelope.sequence, envelope.news))
+            .collect();
+        news.sort_by_key(|(sequence, _)| *sequence);
+
+        clear_output();
+
+        Ok(news)
+    };
+
+    // 1. Shuffled and reversed registrations
+    let shuffled = run(monitors.clone())?;
+    let reversed = run(monitors.into_iter().rev().collect())?;
+
+    // 2. Same sequences
+    assert_eq!(shuffled, reversed);
+
+    // 3. Canonical order
+    let order: Vec<String> = shuffled
+        .iter()
+        .map(|(_, news)| match news {
+            MonitorNews::NewBlock(..) => "new_block".to_string(),
+            MonitorNews::Transaction(tx_id, _, context) => format!("tx/{tx_id}/{context}"),
+            MonitorNews::SpendingUTXOTransaction(_, _, _, context) => {
+                format!("spending/{context}")
+            }
+            MonitorNews::ScriptPubKeyTransaction(_, _, _, context) => format!("script/{context}"),
+            MonitorNews::OpReturnTransaction(_, _, context) => format!("op_return/{context}"),
+            news => panic!("unexpected news {news:?}"),
+        })
+        .collect();
+    let mut tx_monitors = [(ids[0], "a"), (ids[0], "b"), (ids[1], "c")];
+    tx_monitors.sort();
+    let mut expected = vec!["new_block".to_string()];
+    expected.extend(
+        tx_monitors
+            .iter()
+            .map(|(tx_id, context)| format!("tx/{tx_id}/{context}")),
+    );
+    expected.extend([
+        "spending/d".to_string(),
+        "script/e".to_string(),
+        "op_return/f".to_string(),
+    ]);
+    assert_eq!(order, expected);
+
+    Ok(())
+}
+
+/// Test that a monitor with a `Send` indexer can be moved to another thread. The monitor on the
+/// node, `MonitorType`, is not covered: its indexer holds the storage as `Rc` until
+/// `bitcoin-indexer` holds it as `Arc`.
+/// 1. A monitor with a `Send` indexer on a `MonitorStore` holding the default `Arc<Storage>` is
+///    `Send`
+/// 2. A monitor built on this thread ticks on a spawned one and reports block 100
+#[test]
+fn test_monitor_ticks_on_another_thread() -> Result<(), anyhow::Error> {
+    fn assert_send<T: Send>() {}
+
+    // 1. Send
+    assert_send::<Monitor<MockIndexerApi, MonitorStore>>();
+
+    // 2. Tick on a spawned thread
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let block_100 = FullBlock {
+        height: 100,
+        hash: BlockHash::from_str(&format!("{:064x}", 100))?,
+        prev_hash: BlockHash::from_str(&format!("{:064x}", 99))?,
+        txs: vec![],
+        orphan: false,
+        estimated_fee_rate: 0,
+    };
+
+    let mut mock_indexer = MockIndexerApi::new();
+    let best_block = block_100.clone();
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(best_block.clone())));
+    let block_by_hash = block_100.clone();
+    mock_indexer
+        .expect_get_block_by_hash()
+        .returning(move |_| Ok(Some(block_by_hash.clone())));
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |height| Ok((height == 100).then(|| block_100.clone())));
+    mock_indexer.expect_tick().returning(|| Ok(()));
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+    monitor.save_monitor(TypesToMonitor::NewBlock)?;
+
+    let news = std::thread::spawn(move || -> Result<Vec<MonitorNews>, MonitorError> {
+        monitor.tick()?;
+        monitor.get_news()
+    })
+    .join()
+    .expect("monitor thread panicked")?;
+
+    assert!(matches!(news.as_slice(), [MonitorNews::NewBlock(100, _)]));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test the history kept for a transaction monitor with confirmation trigger 3, the
+/// confirmation threshold 6 and max_monitoring_confirmations 8:
+/// 1. Registered at 199 and found in block A at 200
+/// 2. A reorg replaces block A, the transaction is orphaned and then found in block B at 201
+/// 3. It reaches the trigger at 203, the threshold at 206, and the monitor is deactivated at 208
+///    with its news unacknowledged, as there is no ack grace
+/// 4. The history is the same through `monitor_history` and `get_monitors`, and ticks past the
+///    deactivation record nothing new
+#[test]
+fn test_monitor_history() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![],
+        output: vec![],
+    };
+    let tx_id = tx.compute_txid();
+
+    // Blocks from 200 are replaced by the reorg
+    let tip = Arc::new(AtomicU32::new(199));
+    let reorged = Arc::new(AtomicBool::new(false));
+    let hash_at = |height: u32, reorged: bool| {
+        let fork = if reorged && height >= 200 { 0x1000 } else { 0 };
+        BlockHash::from_str(&format!("{:064x}", fork + height)).unwrap()
+    };
+    let block_at = {
+        let reorged = reorged.clone();
+        move |height: u32| {
+            let reorged = reorged.load(Ordering::SeqCst);
+            FullBlock {
+                height,
+                hash: hash_at(height, reorged),
+                prev_hash: hash_at(height.saturating_sub(1), reorged),
+                txs: vec![],
+                orphan: false,
+                estimated_fee_rate: 0,
+            }
+        }
+    };
+    let (block_a, block_b) = (hash_at(200, false), hash_at(201, true));
+
+    let mut mock_indexer = MockIndexerApi::new();
+    let (best_tip, best_block) = (tip.clone(), block_at.clone());
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(best_block(best_tip.load(Ordering::SeqCst)))));
+    let block_by_height = block_at.clone();
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |height| Ok(Some(block_by_height(height))));
+    let block_by_hash = block_at.clone();
+    mock_indexer
+        .expect_get_block_by_hash()
+        .returning(move |hash| {
+            Ok((190..=210)
+                .map(&block_by_hash)
+                .find(|block| block.hash == *hash))
+        });
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let (tx_tip, tx_reorged) = (tip.clone(), reorged.clone());
+    mock_indexer.expect_get_tx().returning(move |_| {
+        let tip = tx_tip.load(Ordering::SeqCst);
+        let (height, hash, orphan) = match (tx_reorged.load(Ordering::SeqCst), tip) {
+            (false, 199) => return Ok(None),
+            (false, _) => (200, block_a, false),
+            // Block A is orphaned and B doesn't include the transaction yet
+            (true, 201) => (200, block_a, true),
+            (true, _) => (201, block_b, false),
+        };
+        Ok(Some(TransactionInfo {
+            tx: tx.clone(),
+            block_info: FullBlock {
+                height,
+                hash,
+                prev_hash: hash_at(height - 1, false),
+                txs: vec![],
+                orphan,
+                estimated_fee_rate: 0,
+            },
+            confirmations: if orphan { 0 } else { tip - height + 1 },
+        }))
+    });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig {
+            confirmation_threshold: Some(6),
+            max_monitoring_confirmations: Some(8),
+            deactivation_requires_ack_grace_blocks: Some(0),
+            ..Default::default()
+        }),
+    )?;
+
+    // 1. Registered and found in block A
+    monitor.tick()?;
+    monitor.save_monitor(WatchTx::new(tx_id).context("a").trigger_at(3).build()?)?;
+    tip.store(200, Ordering::SeqCst);
+    monitor.tick()?;
+
+    // 2. Reorg
+    reorged.store(true, Ordering::SeqCst);
+    for height in [201, 202] {
+        tip.store(height, Ordering::SeqCst);
+        monitor.tick()?;
+    }
+
+    // 3. Trigger, threshold and deactivation
+    for height in [203, 206, 208] {
+        tip.store(height, Ordering::SeqCst);
+        monitor.tick()?;
+    }
+
+    let expected = vec![
+        MonitorEvent::Registered { height: 199 },
+        MonitorEvent::Detected {
+            tx_id,
+            height: 200,
+            block_hash: block_a,
+        },
+        MonitorEvent::Orphaned {
+            tx_id,
+            height: 200,
+            block_hash: block_a,
+        },
+        MonitorEvent::Detected {
+            tx_id,
+            height: 201,
+            block_hash: block_b,
+        },
+        MonitorEvent::Confirmed {
+            tx_id,
+            confirmations: 3,
+        },
+        MonitorEvent::Confirmed {
+            tx_id,
+            confirmations: 6,
+        },
+        MonitorEvent::Deactivated {
+            height: 208,
+            reason: DeactivationReason::AckGraceExpired,
+        },
+    ];
+    let target = TypesToMonitorStore::Transaction(tx_id, "a".into(), Some(3), None);
+    assert_eq!(monitor.monitor_history(&target)?, expected);
+
+    // 4. Listed, and nothing more once deactivated
+    tip.store(209, Ordering::SeqCst);
+    monitor.tick()?;
+
+    let monitors = monitor.get_monitors(true)?;
+    assert_eq!(monitors.len(), 1);
+    assert!(!monitors[0].active);
+    assert_eq!(monitors[0].history, expected);
+    assert_eq!(monitor.monitor_history(&target)?, expected);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that the indexer lookups of a tick are cached until the next tick:
+/// 1. A monitored transaction is mined in block 200
+/// 2. The tick, get_news in `Fresh` mode and get_tx_status look it up from the indexer once
+/// 3. The next tick looks it up again and sees its new confirmations
+#[test]
+fn test_indexer_lookups_cached_per_tick() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    // 1. Transaction mined in block 200
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_consensus(1),
+        input: vec![],
+        output: vec![],
+    };
+    let tx_id = tx.compute_txid();
+
+    let block_at = {
+        let tx = tx.clone();
+        move |height: u32| FullBlock {
+            height,
+            hash: BlockHash::from_str(&format!("{:064x}", height)).unwrap(),
+            prev_hash: BlockHash::from_str(&format!("{:064x}", height - 1)).unwrap(),
+            txs: if height == 200 {
+                vec![tx.clone()]
+            } else {
+                vec![]
+            },
+            orphan: false,
+            estimated_fee_rate: 0,
+        }
+    };
+    let tx_info = {
+        let block_at = block_at.clone();
+        move |confirmations: u32| TransactionInfo {
+            tx: tx.clone(),
+            block_info: block_at(200),
+            confirmations,
+        }
+    };
+
+    let height = Arc::new(AtomicU32::new(200));
+
+    let mut mock_indexer = MockIndexerApi::new();
+    let (best_height, best_block) = (height.clone(), block_at.clone());
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(best_block(best_height.load(Ordering::SeqCst)))));
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |height| Ok(Some(block_at(height))));
+    mock_indexer.expect_tick().returning(|| Ok(()));
+
+    let mut sequence = mockall::Sequence::new();
+    let first = tx_info.clone();
+    mock_indexer
+        .expect_get_tx()
+        .with(eq(tx_id))
+        .times(1)
+        .in_sequence(&mut sequence)
+        .returning(move |_| Ok(Some(first(1))));
+    mock_indexer
+        .expect_get_tx()
+        .with(eq(tx_id))
+        .times(1)
+        .in_sequence(&mut sequence)
+        .returning(move |_| Ok(Some(tx_info(2))));
+
+    let mut settings = MonitorSettings::from(MonitorSettingsConfig::default());
+    settings.news_resolution = Resolution::Fresh;
+    let monitor = Monitor::new(mock_indexer, store, settings)?;
+
+    // 2. One lookup for the tick, get_news and get_tx_status
+    monitor.save_monitor(WatchTx::new(tx_id).context("paid").build()?)?;
+    monitor.tick()?;
+
+    let news = monitor.get_news()?;
+    assert_eq!(news.len(), 1);
+    assert!(matches!(
+        &news[0],
+        MonitorNews::Transaction(t, status, _) if *t == tx_id && status.confirmations == 1
+    ));
+    assert_eq!(monitor.get_tx_status(&tx_id)?.confirmations, 1);
+
+    // 3. Looked up again by the next tick
+    height.store(201, Ordering::SeqCst);
+    monitor.tick()?;
+    assert_eq!(monitor.get_tx_status(&tx_id)?.confirmations, 2);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that status_bitmap resolves the state of each transaction:
+/// 1. The store holds the statuses of a mempool, an orphaned and two confirmed transactions,
+///    the monitor is at height 205 with a confirmation threshold of 6
+/// 2. Recorded transactions are resolved without the indexer, confirmations follow the
+///    monitor height
+/// 3. The others are looked up in the indexer and counted as fallbacks
+/// 4. Lookups are cached until the next tick, fallbacks are counted on every call
+#[test]
+fn test_status_bitmap() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    // 1. Recorded statuses
+    let tx = |lock_time: u32| Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_consensus(lock_time),
+        input: vec![],
+        output: vec![],
+    };
+    let block_at = |height: u32, orphan: bool| FullBlock {
+        height,
+        hash: BlockHash::from_str(&format!("{:064x}", height)).unwrap(),
+        prev_hash: BlockHash::from_str(&format!("{:064x}", height - 1)).unwrap(),
+        txs: vec![],
+        orphan,
+        estimated_fee_rate: 0,
+    };
+
+    let recorded = [
+        (tx(1), None, TransactionBlockchainStatus::Mempool),
+        (
+            tx(2),
+            Some(block_at(200, true)),
+            TransactionBlockchainStatus::Orphan,
+        ),
+        (
+            tx(3),
+            Some(block_at(204, false)),
+            TransactionBlockchainStatus::Confirmed,
+        ),
+        (
+            tx(4),
+            Some(block_at(200, false)),
+            TransactionBlockchainStatus::Confirmed,
+        ),
+    ];
+    for (tx, block_info, status) in recorded.iter().cloned() {
+        store.save_tx_snapshot(TransactionStatus {
+            tx_id: tx.compute_txid(),
+            tx,
+            confirmations: u32::from(block_info.is_some()),
+            block_info,
+            status,
+        })?;
+    }
+    store.update_monitor_height(205)?;
+
+    let (indexed, unknown) = (tx(5), tx(6));
+    let (indexed_id, unknown_id) = (indexed.compute_txid(), unknown.compute_txid());
+
+    let mut mock_indexer = MockIndexerApi::new();
+    mock_indexer
+        .expect_get_tx()
+        .with(eq(indexed_id))
+        .times(1)
+        .returning(move |_| {
+            Ok(Some(TransactionInfo {
+                tx: indexed.clone(),
+                block_info: block_at(199, false),
+                confirmations: 7,
+            }))
+        });
+    mock_indexer
+        .expect_get_tx()
+        .with(eq(unknown_id))
+        .times(1)
+        .returning(|_| Ok(None));
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+
+    // 2. and 3. States
+    let mut tx_ids: Vec<Txid> = recorded
+        .iter()
+        .map(|(tx, _, _)| tx.compute_txid())
+        .collect();
+    tx_ids.extend([indexed_id, unknown_id]);
+
+    let states = monitor.status_bitmap(&tx_ids)?;
+    assert_eq!(
+        states,
+        vec![
+            TxState::Mempool,
+            TxState::Orphaned,
+            TxState::Confirmed,
+            TxState::Finalized,
+            TxState::Finalized,
+            TxState::Unknown,
+        ]
+    );
+    assert_eq!(
+        states.iter().map(|state| *state as u8).collect::<Vec<_>>(),
+        vec![1, 4, 2, 3, 3, 0]
+    );
+    assert_eq!(monitor.counters.status_fallbacks(), 2);
+
+    // 4. Cached lookups
+    assert_eq!(monitor.status_bitmap(&tx_ids[4..])?, states[4..]);
+    assert_eq!(monitor.counters.status_fallbacks(), 4);
+    assert_eq!(monitor.status_bitmap(&[])?, vec![]);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that a tick at the same tip skips the monitors:
+/// 1. The first tick at 200 looks up the monitored transaction
+/// 2. Ticks at the same tip only ask the indexer for its best block
+/// 3. A new monitor makes the next tick at the same tip process the monitors again
+#[test]
+fn test_tick_at_same_tip_skips_monitors() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let block_200 = FullBlock {
+        height: 200,
+        hash: BlockHash::from_str(&format!("{:064x}", 200))?,
+        prev_hash: BlockHash::from_str(&format!("{:064x}", 199))?,
+        txs: vec![],
+        orphan: false,
+        estimated_fee_rate: 0,
+    };
+    let tx_id = Txid::from_str(&format!("{:064x}", 1))?;
+    let other_tx_id = Txid::from_str(&format!("{:064x}", 2))?;
+
+    let mut mock_indexer = MockIndexerApi::new();
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(block_200.clone())));
+    mock_indexer.expect_get_block_by_height().never();
+    // Once by the first tick and once by the tick after the new monitor
+    mock_indexer
+        .expect_get_tx()
+        .with(eq(tx_id))
+        .times(2)
+        .returning(|_| Ok(None));
+    mock_indexer
+        .expect_get_tx()
+        .with(eq(other_tx_id))
+        .times(1)
+        .returning(|_| Ok(None));
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+
+    // 1. First tick
+    monitor.save_monitor(WatchTx::new(tx_id).context("paid").build()?)?;
+    monitor.tick()?;
+    assert_eq!(monitor.get_monitor_height()?, 200);
+
+    // 2. Same tip
+    for _ in 0..3 {
+        let indexer_calls = monitor.counters.indexer_calls();
+        monitor.tick()?;
+        // The tick of the indexer and its best block
+        assert_eq!(monitor.counters.indexer_calls(), indexer_calls + 2);
+    }
+
+    // 3. New monitor
+    monitor.save_monitor(WatchTx::new(other_tx_id).context("other").build()?)?;
+    monitor.tick()?;
+    monitor.tick()?;
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that a monitor behind the indexer processes the blocks in between:
+/// 1. The first tick at 200, then the indexer moves to 205 with a spend of the watched output
+///    in block 202
+/// 2. With max_blocks_per_tick of 3, the tick processes blocks 201 to 203 in order, finds the
+///    spend in block 202 and counts its confirmations up to block 203
+/// 3. The next tick processes blocks 204 and 205
+#[test]
+fn test_tick_catches_up_block_by_block() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    // 1. Spend in block 202
+    let target = bitcoin::OutPoint::new(Txid::from_str(&format!("{:064x}", 1))?, 0);
+    let spender = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_consensus(1),
+        input: vec![bitcoin::TxIn {
+            previous_output: target,
+            ..Default::default()
+        }],
+        output: vec![],
+    };
+    let spender_id = spender.compute_txid();
+
+    let block_at = {
+        let spender = spender.clone();
+        move |height: u32| FullBlock {
+            height,
+            hash: BlockHash::from_str(&format!("{:064x}", height)).unwrap(),
+            prev_hash: BlockHash::from_str(&format!("{:064x}", height - 1)).unwrap(),
+            txs: if height == 202 {
+                vec![spender.clone()]
+            } else {
+                vec![]
+            },
+            orphan: false,
+            estimated_fee_rate: 0,
+        }
+    };
+
+    let height = Arc::new(AtomicU32::new(200));
+    let scanned = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    let mut mock_indexer = MockIndexerApi::new();
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let (best_height, best_block) = (height.clone(), block_at.clone());
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(best_block(best_height.load(Ordering::SeqCst)))));
+    let (by_height, by_height_scanned) = (block_at.clone(), scanned.clone());
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |height| {
+            by_height_scanned.lock().unwrap().push(height);
+            Ok(Some(by_height(height)))
+        });
+    // The watched output comes from a transaction the indexer doesn't know, nothing to backfill
+    mock_indexer
+        .expect_get_tx()
+        .with(eq(target.txid))
+        .returning(|_| Ok(None));
+    let tx_height = height.clone();
+    mock_indexer
+        .expect_get_tx()
+        .with(eq(spender_id))
+        .returning(move |_| {
+            let tip = tx_height.load(Ordering::SeqCst);
+            Ok(Some(TransactionInfo {
+                tx: spender.clone(),
+                block_info: block_at(202),
+                confirmations: tip - 201,
+            }))
+        });
+
+    let mut settings = MonitorSettings::from(MonitorSettingsConfig::default());
+    settings.max_blocks_per_tick = 3;
+    let monitor = Monitor::new(mock_indexer, store, settings)?;
+
+    let watch = WatchOutpoint::new(target.txid, target.vout)
+        .context("cold")
+        .build()?;
+    monitor.save_monitor(watch)?;
+    monitor.tick()?;
+    assert_eq!(monitor.get_monitor_height()?, 200);
+    assert!(monitor.get_news()?.is_empty());
+
+    // 2. Blocks 201 to 203
+    height.store(205, Ordering::SeqCst);
+    monitor.tick()?;
+    assert_eq!(monitor.get_monitor_height()?, 203);
+    assert_eq!(*scanned.lock().unwrap(), vec![201, 202, 203]);
+
+    let news = monitor.get_news()?;
+    assert_eq!(news.len(), 1);
+    assert!(matches!(
+        &news[0],
+        MonitorNews::SpendingUTXOTransaction(t, 0, status, context)
+            if *t == target.txid
+                && status.tx_id == spender_id
+                && status.confirmations == 2
+                && context == "cold"
+    ));
+    assert!(monitor.get_monitors(false)?.iter().any(|info| matches!(
+        info.monitor,
+        TypesToMonitorStore::SpendingUTXOTransaction(..)
+    ) && info.spender_tx_id
+        == Some(spender_id)));
+
+    // 3. Blocks 204 and 205
+    scanned.lock().unwrap().clear();
+    monitor.tick()?;
+    assert_eq!(monitor.get_monitor_height()?, 205);
+    assert_eq!(*scanned.lock().unwrap(), vec![204]);
+
+    let news = monitor.get_news()?;
+    assert_eq!(news.len(), 1);
+    assert!(matches!(
+        &news[0],
+        MonitorNews::SpendingUTXOTransaction(_, _, status, _) if status.confirmations == 4
+    ));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that strict mode fails the ticks with unexpected detections, once their news is written:
+/// 1. A transaction registered as expected and an allowed OP_RETURN in block 200 don't fail
+///    the tick
+/// 2. An OP_RETURN out of the allow-list in block 201 fails the tick, its news is kept
+/// 3. A transaction registered without the flag in block 202 fails the tick, its news is kept
+/// 4. Without strict mode the same news don't fail the tick
+#[test]
+fn test_strict_detections() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let tagged = |lock_time: u32| Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_consensus(lock_time),
+        input: vec![],
+        output: vec![TxOut {
+            value: Amount::ZERO,
+            script_pubkey: Builder::new()
+                .push_opcode(OP_RETURN)
+                .push_slice(b"TAG:42")
+                .into_script(),
+        }],
+    };
+    let plain = |lock_time: u32| Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_consensus(lock_time),
+        input: vec![],
+        output: vec![],
+    };
+
+    let (expected_tx, allowed_tx, other_tx, unflagged_tx) =
+        (plain(1), tagged(2), tagged(3), plain(4));
+    let mined: Vec<(Transaction, u32)> = vec![
+        (expected_tx.clone(), 200),
+        (allowed_tx.clone(), 200),
+        (other_tx.clone(), 201),
+        (unflagged_tx.clone(), 202),
+    ];
+    let block_at = {
+        let mined = mined.clone();
+        move |height: u32| FullBlock {
+            height,
+            hash: BlockHash::from_str(&format!("{:064x}", height)).unwrap(),
+            prev_hash: BlockHash::from_str(&format!("{:064x}", height - 1)).unwrap(),
+            txs: mined
+                .iter()
+                .filter(|(_, h)| *h == height)
+                .map(|(tx, _)| tx.clone())
+                .collect(),
+            orphan: false,
+            estimated_fee_rate: 0,
+        }
+    };
+
+    let height = Arc::new(AtomicU32::new(200));
+
+    let mut mock_indexer = MockIndexerApi::new();
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let (best_height, best_block) = (height.clone(), block_at.clone());
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(best_block(best_height.load(Ordering::SeqCst)))));
+    let by_height = block_at.clone();
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |height| Ok(Some(by_height(height))));
+    let tx_height = height.clone();
+    mock_indexer.expect_get_tx().returning(move |tx_id| {
+        let tip = tx_height.load(Ordering::SeqCst);
+        Ok(mined
+            .iter()
+            .find(|(tx, h)| tx.compute_txid() == *tx_id && *h <= tip)
+            .map(|(tx, h)| TransactionInfo {
+                tx: tx.clone(),
+                block_info: block_at(*h),
+                confirmations: tip - h + 1,
+            }))
+    });
+
+    let mut settings = MonitorSettings::from(MonitorSettingsConfig::default());
+    assert!(!settings.strict_detections);
+    settings.strict_detections = true;
+    settings.strict_allowed_txids = vec![allowed_tx.compute_txid()];
+    let mut monitor = Monitor::new(mock_indexer, store, settings)?;
+
+    // 1. Expected detections
+    monitor.save_monitor_with(
+        WatchTx::new(expected_tx.compute_txid())
+            .context("expected")
+            .build()?,
+        RegistrationOptions {
+            expected: true,
+            ..Default::default()
+        },
+    )?;
+    monitor.save_monitor(
+        WatchTx::new(unflagged_tx.compute_txid())
+            .context("unflagged")
+            .build()?,
+    )?;
+    monitor.save_monitor(WatchOpReturn::new(b"TAG:").context("tags").build()?)?;
+
+    monitor.tick()?;
+    assert_eq!(monitor.get_news()?.len(), 2);
+
+    // 2. OP_RETURN out of the allow-list
+    height.store(201, Ordering::SeqCst);
+    let other_id = other_tx.compute_txid();
+    match monitor.tick() {
+        Err(MonitorError::UnexpectedDetection(detection)) => assert_eq!(
+            detection,
+            DetectionDescriptor {
+                monitor: SnoozeTarget::OpReturnPrefix(b"TAG:".to_vec(), "tags".into()),
+                tx_id: Some(other_id),
+            }
+        ),
+        other => panic!("expected an unexpected detection, got {:?}", other),
+    }
+    assert_eq!(monitor.get_monitor_height()?, 201);
+    assert!(monitor.get_news()?.iter().any(|news| matches!(
+        news,
+        MonitorNews::OpReturnTransaction(tx_id, _, context)
+            if *tx_id == other_id && context == "tags"
+    )));
+
+    // 3. Transaction registered without the flag
+    height.store(202, Ordering::SeqCst);
+    let unflagged_id = unflagged_tx.compute_txid();
+    match monitor.tick() {
+        Err(MonitorError::UnexpectedDetection(detection)) => assert_eq!(
+            detection,
+            DetectionDescriptor {
+                monitor: SnoozeTarget::Transaction(unflagged_id, "unflagged".into()),
+                tx_id: Some(unflagged_id),
+            }
+        ),
+        other => panic!("expected an unexpected detection, got {:?}", other),
+    }
+    assert!(monitor.get_news()?.iter().any(|news| matches!(
+        news,
+        MonitorNews::Transaction(tx_id, _, context)
+            if *tx_id == unflagged_id && context == "unflagged"
+    )));
+
+    // 4. Strict mode off
+    let mut settings = monitor.settings.clone();
+    settings.strict_detections = false;
+    let update = monitor.update_settings(settings)?;
+    assert_eq!(update.applied, vec!["strict_detections"]);
+
+    height.store(203, Ordering::SeqCst);
+    monitor.tick()?;
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that a spend in a block below the tip is found when the monitor catches up:
+/// 1. The first tick at 201, then the indexer moves to 204 with the spend in block 202
+/// 2. The tick walks blocks 202 to 204, records the spender found two blocks below the tip and
+///    reports it with 3 confirmations
+#[test]
+fn test_spend_below_tip_found_on_catch_up() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    // 1. Spend in block 202
+    let target = bitcoin::OutPoint::new(Txid::from_str(&format!("{:064x}", 1))?, 1);
+    let spender = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_consensus(1),
+        input: vec![bitcoin::TxIn {
+            previous_output: target,
+            ..Default::default()
+        }],
+        output: vec![],
+    };
+    let spender_id = spender.compute_txid();
+
+    let block_at = {
+        let spender = spender.clone();
+        move |height: u32| FullBlock {
+            height,
+            hash: BlockHash::from_str(&format!("{:064x}", height)).unwrap(),
+            prev_hash: BlockHash::from_str(&format!("{:064x}", height - 1)).unwrap(),
+            txs: if height == 202 {
+                vec![spender.clone()]
+            } else {
+                vec![]
+            },
+            orphan: false,
+            estimated_fee_rate: 0,
+        }
+    };
+
+    let height = Arc::new(AtomicU32::new(201));
+    let scanned = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    let mut mock_indexer = MockIndexerApi::new();
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let (best_height, best_block) = (height.clone(), block_at.clone());
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(best_block(best_height.load(Ordering::SeqCst)))));
+    let (by_height, by_height_scanned) = (block_at.clone(), scanned.clone());
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |height| {
+            by_height_scanned.lock().unwrap().push(height);
+            Ok(Some(by_height(height)))
+        });
+    // The watched output comes from a transaction the indexer doesn't know, nothing to backfill
+    mock_indexer
+        .expect_get_tx()
+        .with(eq(target.txid))
+        .returning(|_| Ok(None));
+    let tx_height = height.clone();
+    mock_indexer
+        .expect_get_tx()
+        .with(eq(spender_id))
+        .returning(move |_| {
+            let tip = tx_height.load(Ordering::SeqCst);
+            Ok(Some(TransactionInfo {
+                tx: spender.clone(),
+                block_info: block_at(202),
+                confirmations: tip - 201,
+            }))
+        });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+
+    monitor.save_monitor(
+        WatchOutpoint::new(target.txid, target.vout)
+            .context("vault")
+            .build()?,
+    )?;
+    monitor.tick()?;
+    assert_eq!(monitor.get_monitor_height()?, 201);
+    assert!(monitor.get_news()?.is_empty());
+
+    // 2. Catch up to 204
+    height.store(204, Ordering::SeqCst);
+    monitor.tick()?;
+    assert_eq!(monitor.get_monitor_height()?, 204);
+    assert_eq!(*scanned.lock().unwrap(), vec![202, 203]);
+
+    assert!(monitor.get_monitors(false)?.iter().any(|info| matches!(
+        info.monitor,
+        TypesToMonitorStore::SpendingUTXOTransaction(t, 1, ..) if t == target.txid
+    ) && info.spender_tx_id
+        == Some(spender_id)));
+
+    let news = monitor.get_news()?;
+    assert_eq!(news.len(), 1);
+    assert!(matches!(
+        &news[0],
+        MonitorNews::SpendingUTXOTransaction(t, 1, status, context)
+            if *t == target.txid
+                && status.tx_id == spender_id
+                && status.confirmations == 3
+                && context == "vault"
+    ));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that the SpendingUTXO monitors registered late look for spends in the past blocks:
+/// 1. The watched transaction is mined in block 195 and its output 0 spent in block 198, the
+///    monitor is at 200 when both outputs get a monitor
+/// 2. The next tick scans blocks 195 to 200 once for both monitors, records the spender of
+///    output 0 and reports it; output 1 was not spent and gets no news
+/// 3. The following ticks only look at the new blocks
+#[test]
+fn test_spending_utxo_backfill() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    // 1. Spend before the registration
+    let funding = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_consensus(1),
+        input: vec![],
+        output: vec![
+            TxOut {
+                value: Amount::from_sat(10_000),
+                script_pubkey: ScriptBuf::new(),
+            };
+            2
+        ],
+    };
+    let funding_id = funding.compute_txid();
+    let spender = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_consensus(2),
+        input: vec![bitcoin::TxIn {
+            previous_output: bitcoin::OutPoint::new(funding_id, 0),
+            ..Default::default()
+        }],
+        output: vec![],
+    };
+    let spender_id = spender.compute_txid();
+
+    let mined: Vec<(Transaction, u32)> = vec![(funding, 195), (spender, 198)];
+    let block_at = {
+        let mined = mined.clone();
+        move |height: u32| FullBlock {
+            height,
+            hash: BlockHash::from_str(&format!("{:064x}", height)).unwrap(),
+            prev_hash: BlockHash::from_str(&format!("{:064x}", height - 1)).unwrap(),
+            txs: mined
+                .iter()
+                .filter(|(_, h)| *h == height)
+                .map(|(tx, _)| tx.clone())
+                .collect(),
+            orphan: false,
+            estimated_fee_rate: 0,
+        }
+    };
+
+    let height = Arc::new(AtomicU32::new(200));
+    let scanned = Arc::new(std::sync::Mutex::new(Vec::new()));
+
+    let mut mock_indexer = MockIndexerApi::new();
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let (best_height, best_block) = (height.clone(), block_at.clone());
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(best_block(best_height.load(Ordering::SeqCst)))));
+    let (by_height, by_height_scanned) = (block_at.clone(), scanned.clone());
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |height| {
+            by_height_scanned.lock().unwrap().push(height);
+            Ok(Some(by_height(height)))
+        });
+    let tx_height = height.clone();
+    mock_indexer.expect_get_tx().returning(move |tx_id| {
+        let tip = tx_height.load(Ordering::SeqCst);
+        Ok(mined
+            .iter()
+            .find(|(tx, h)| tx.compute_txid() == *tx_id && *h <= tip)
+            .map(|(tx, h)| TransactionInfo {
+                tx: tx.clone(),
+                block_info: block_at(*h),
+                confirmations: tip - h + 1,
+            }))
+    });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+
+    let unrelated = Txid::from_str(&format!("{:064x}", 9))?;
+    monitor.save_monitor(WatchTx::new(unrelated).context("other").build()?)?;
+    monitor.tick()?;
+    assert_eq!(monitor.get_monitor_height()?, 200);
+
+    monitor.save_monitor(WatchOutpoint::new(funding_id, 0).context("late").build()?)?;
+    monitor.save_monitor(
+        WatchOutpoint::new(funding_id, 1)
+            .context("unspent")
+            .build()?,
+    )?;
+
+    // 2. Backfill
+    height.store(201, Ordering::SeqCst);
+    monitor.tick()?;
+    assert_eq!(monitor.get_monitor_height()?, 201);
+    assert_eq!(*scanned.lock().unwrap(), vec![195, 196, 197, 198, 199, 200]);
+
+    let spent = |info: &MonitorInfo, vout: u32| {
+        matches!(
+            info.monitor,
+            TypesToMonitorStore::SpendingUTXOTransaction(t, v, ..) if t == funding_id && v == vout
+        )
+    };
+    let monitors = monitor.get_monitors(false)?;
+    assert!(monitors
+        .iter()
+        .any(|info| spent(info, 0) && info.spender_tx_id == Some(spender_id)));
+    assert!(monitors
+        .iter()
+        .any(|info| spent(info, 1) && info.spender_tx_id.is_none()));
+
+    let news = monitor.get_news()?;
+    assert!(news.iter().any(|n| matches!(
+        n,
+        MonitorNews::SpendingUTXOTransaction(t, 0, status, context)
+            if *t == funding_id
+                && status.tx_id == spender_id
+                && status.confirmations == 4
+                && context == "late"
+    )));
+    assert!(!news
+        .iter()
+        .any(|n| matches!(n, MonitorNews::SpendingUTXOTransaction(_, 1, ..))));
+
+    // 3. Only new blocks
+    scanned.lock().unwrap().clear();
+    height.store(202, Ordering::SeqCst);
+    monitor.tick()?;
+    assert_eq!(monitor.get_monitor_height()?, 202);
+    assert!(scanned.lock().unwrap().is_empty());
+    assert!(!monitor
+        .get_news()?
+        .iter()
+        .any(|n| matches!(n, MonitorNews::SpendingUTXOTransaction(_, 1, ..))));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that Transaction and SpendingUTXO monitors with an expiry height end without a detection:
+/// 1. Registered at 200, nothing expires at 201, before any of the expiry heights
+/// 2. At 202 the monitors of a missing transaction, of a transaction mined later and of an
+///    unspent output expire, are deactivated and send a `MonitorExpired` news each
+/// 3. At 203 the monitors expiring exactly at that height find their transaction and spend, so
+///    they don't expire, and the expired monitor of the mined transaction sends nothing
+/// 4. The monitors that detected their transaction stay active after their expiry height
+#[test]
+fn test_monitor_expiry() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let missing_id = Txid::from_str(&format!("{:064x}", 1))?;
+    let target = Txid::from_str(&format!("{:064x}", 2))?;
+    let mined = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_consensus(1),
+        input: vec![],
+        output: vec![],
+    };
+    let spender = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_consensus(2),
+        input: vec![bitcoin::TxIn {
+            previous_output: bitcoin::OutPoint::new(target, 0),
+            ..Default::default()
+        }],
+        output: vec![],
+    };
+    let mined_id = mined.compute_txid();
+
+    let block_at = {
+        let txs = vec![mined.clone(), spender.clone()];
+        move |height: u32| FullBlock {
+            height,
+            hash: BlockHash::from_str(&format!("{:064x}", height)).unwrap(),
+            prev_hash: BlockHash::from_str(&format!("{:064x}", height - 1)).unwrap(),
+            txs: if height == 203 { txs.clone() } else { vec![] },
+            orphan: false,
+            estimated_fee_rate: 0,
+        }
+    };
+
+    let height = Arc::new(AtomicU32::new(200));
+
+    let mut mock_indexer = MockIndexerApi::new();
+    let (best_height, best_block) = (height.clone(), block_at.clone());
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(best_block(best_height.load(Ordering::SeqCst)))));
+    let block_by_height = block_at.clone();
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |height| Ok(Some(block_by_height(height))));
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let tx_height = height.clone();
+    mock_indexer.expect_get_tx().returning(move |tx_id| {
+        let tip = tx_height.load(Ordering::SeqCst);
+        Ok([mined.clone(), spender.clone()]
+            .into_iter()
+            .filter(|_| tip >= 203)
+            .find(|tx| tx.compute_txid() == *tx_id)
+            .map(|tx| TransactionInfo {
+                tx,
+                block_info: block_at(203),
+                confirmations: tip - 202,
+            }))
+    });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+
+    let expired_news = |monitor: &Monitor<MockIndexerApi, MonitorStore>| {
+        Ok::<_, MonitorError>(
+            monitor
+                .get_news()?
+                .into_iter()
+                .filter(|news| matches!(news, MonitorNews::MonitorExpired { .. }))
+                .collect::<Vec<_>>(),
+        )
+    };
+    let expired = |tx_id: Txid, vout: Option<u32>, context: &str| MonitorNews::MonitorExpired {
+        tx_id,
+        vout,
+        context: context.into(),
+        height: 202,
+    };
+    let active_contexts = |monitor: &Monitor<MockIndexerApi, MonitorStore>| {
+        let mut contexts: Vec<MonitorContext> = monitor
+            .get_monitors(false)?
+            .into_iter()
+            .filter_map(|info| match info.monitor {
+                TypesToMonitorStore::Transaction(_, context, ..)
+                | TypesToMonitorStore::SpendingUTXOTransaction(_, _, context, ..) => Some(context),
+                _ => None,
+            })
+            .filter(|context| !context.starts_with("INTERNAL"))
+            .collect();
+        contexts.sort();
+        Ok::<_, MonitorError>(contexts)
+    };
+
+    monitor.save_monitor(
+        WatchTx::new(missing_id)
+            .context("missing")
+            .expires_at(202)
+            .build()?,
+    )?;
+    monitor.save_monitor(
+        WatchTx::new(mined_id)
+            .context("late")
+            .expires_at(202)
+            .build()?,
+    )?;
+    monitor.save_monitor(
+        WatchTx::new(mined_id)
+            .context("mined")
+            .expires_at(203)
+            .build()?,
+    )?;
+    monitor.save_monitor(
+        WatchOutpoint::new(target, 1)
+            .context("unspent")
+            .expires_at(202)
+            .build()?,
+    )?;
+    monitor.save_monitor(
+        WatchOutpoint::new(target, 0)
+            .context("spent")
+            .expires_at(203)
+            .build()?,
+    )?;
+
+    // 1. Before the expiry heights
+    monitor.tick()?;
+    height.store(201, Ordering::SeqCst);
+    monitor.tick()?;
+    assert!(expired_news(&monitor)?.is_empty());
+
+    // 2. Expired at 202
+    height.store(202, Ordering::SeqCst);
+    monitor.tick()?;
+    let news = expired_news(&monitor)?;
+    assert_eq!(news.len(), 3);
+    for expected in [
+        expired(missing_id, None, "missing"),
+        expired(mined_id, None, "late"),
+        expired(target, Some(1), "unspent"),
+    ] {
+        assert!(news.contains(&expected), "{expected:?}");
+    }
+
+    assert_eq!(active_contexts(&monitor)?, ["mined", "spent"]);
+
+    monitor.ack_news(AckMonitorNews::MonitorExpired {
+        tx_id: missing_id,
+        vout: None,
+        context: "missing".into(),
+    })?;
+    monitor.ack_news(AckMonitorNews::MonitorExpired {
+        tx_id: mined_id,
+        vout: None,
+        context: "late".into(),
+    })?;
+    monitor.ack_news(AckMonitorNews::MonitorExpired {
+        tx_id: target,
+        vout: Some(1),
+        context: "unspent".into(),
+    })?;
+
+    // 3. Detected at the expiry height
+    height.store(203, Ordering::SeqCst);
+    monitor.tick()?;
+    let news = monitor.get_news()?;
+    assert!(expired_news(&monitor)?.is_empty());
+    assert!(news.iter().any(|news| matches!(
+        news,
+        MonitorNews::Transaction(tx_id, _, context) if *tx_id == mined_id && context == "mined"
+    )));
+    assert!(!news.iter().any(|news| matches!(
+        news,
+        MonitorNews::Transaction(_, _, context) if context == "late"
+    )));
+    assert!(news.iter().any(|news| matches!(
+        news,
+        MonitorNews::SpendingUTXOTransaction(tx_id, 0, _, context)
+            if *tx_id == target && context == "spent"
+    )));
+
+    // 4. Still active after the expiry height
+    height.store(204, Ordering::SeqCst);
+    monitor.tick()?;
+    height.store(205, Ordering::SeqCst);
+    monitor.tick()?;
+    assert!(expired_news(&monitor)?.is_empty());
+    assert_eq!(active_contexts(&monitor)?, ["mined", "spent"]);
+
+    clear_output();
+
+    Ok(())
+}
@@ -0,0 +1,253 @@
+use bitcoin::{absolute::LockTime, BlockHash, Transaction};
+use bitcoin_indexer::{
+    indexer::MockIndexerApi,
+    types::{FullBlock, TransactionInfo},
+};
+use bitvmx_transaction_monitor::{
+    config::{MonitorSettings, MonitorSettingsConfig, WebhookConfig, WebhookRetrySettings},
+    errors::MonitorError,
+    monitor::Monitor,
+    news_dispatcher::NewsDispatcher,
+    store::MonitorStore,
+    WatchTx,
+};
+use serde_json::Value;
+use std::{
+    io::{BufRead, BufReader, Read, Write},
+    net::{SocketAddr, TcpListener},
+    str::FromStr,
+    sync::{mpsc, Arc},
+    thread::JoinHandle,
+    time::Duration,
+};
+use storage_backend::{storage::Storage, storage_config::StorageConfig};
+use utils::{clear_output, generate_random_string};
+mod utils;
+
+/// Request received by the webhook: its headers, lowercased, and its body.
+struct Received {
+    headers: Vec<String>,
+    body: Value,
+}
+
+/// Answers one request per status of `statuses`, in order, sending what it received.
+fn start_webhook(statuses: Vec<u16>) -> (SocketAddr, mpsc::Receiver<Received>, JoinHandle<()>) {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (sender, receiver) = mpsc::channel();
+
+    let handle = std::thread::spawn(move || {
+        for status in statuses {
+            let (stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+            let mut headers = vec![];
+            let mut line = String::new();
+            while reader.read_line(&mut line).unwrap() > 0 && line.trim() != "" {
+                headers.push(line.trim().to_lowercase());
+                line.clear();
+            }
+            let length = headers
+                .iter()
+                .find_map(|header| header.strip_prefix("content-length: "))
+                .map_or(0, |length| length.parse().unwrap());
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+
+            sender
+                .send(Received {
+                    headers,
+                    body: serde_json::from_slice(&body).unwrap(),
+                })
+                .unwrap();
+
+            let mut stream = stream;
+            write!(
+                stream,
+                "HTTP/1.1 {} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n",
+                status
+            )
+            .unwrap();
+        }
+    });
+
+    (addr, receiver, handle)
+}
+
+/// A monitor with a transaction news pending, found in block 200.
+fn monitor_with_news() -> Result<Monitor<MockIndexerApi, MonitorStore>, anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let storage = Arc::new(Storage::new(&StorageConfig::new(path, None))?);
+    let store = MonitorStore::new(storage)?;
+
+    let block_200 = FullBlock {
+        height: 200,
+        hash: BlockHash::from_str(
+            "0000000000000000000000000000000000000000000000000000000000000011",
+        )?,
+        prev_hash: BlockHash::from_str(
+            "0000000000000000000000000000000000000000000000000000000000000001",
+        )?,
+        txs: vec![],
+        orphan: false,
+        estimated_fee_rate: 0,
+    };
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_time(1653195600).unwrap(),
+        input: vec![],
+        output: vec![],
+    };
+    let tx_id = tx.compute_txid();
+
+    let mut mock_indexer = MockIndexerApi::new();
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let best_block = block_200.clone();
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(best_block.clone())));
+    let block_by_height = block_200.clone();
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |_| Ok(Some(block_by_height.clone())));
+    mock_indexer.expect_get_tx().returning(move |id| {
+        Ok((*id == tx_id).then(|| TransactionInfo {
+            tx: tx.clone(),
+            block_info: block_200.clone(),
+            confirmations: 1,
+        }))
+    });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+    monitor.save_monitor(WatchTx::new(tx_id).context("ctx").build()?)?;
+    monitor.tick()?;
+    assert_eq!(monitor.news_count()?, 1);
+
+    Ok(monitor)
+}
+
+fn webhook(addr: SocketAddr, initial_delay_ms: u64) -> WebhookConfig {
+    WebhookConfig {
+        url: format!("http://{}/news", addr),
+        auth_header: Some("Bearer secret".to_string()),
+        retry: WebhookRetrySettings {
+            initial_delay_ms,
+            max_delay_ms: 60_000,
+            timeout_ms: 5_000,
+        },
+    }
+}
+
+/// Test that a news is acknowledged only once the webhook took it:
+/// 1. The webhook answers 503, the news is reported pending with the failure and stays unacked
+/// 2. The next dispatch posts it again, the webhook answers 200 and the news is acknowledged
+/// 3. Assert both requests carried the news item as JSON and the authorization header
+#[test]
+fn test_dispatch_acks_after_2xx() -> Result<(), anyhow::Error> {
+    let monitor = monitor_with_news()?;
+    let (addr, received, server) = start_webhook(vec![503, 200]);
+    let mut dispatcher = NewsDispatcher::new(webhook(addr, 0))?;
+
+    // 1. Failed delivery
+    let report = dispatcher.dispatch(&monitor)?;
+    assert_eq!(report.delivered, 0);
+    assert_eq!(report.pending, 1);
+    assert_eq!(report.failure.as_deref(), Some("webhook answered 503"));
+    assert_eq!(monitor.news_count()?, 1);
+
+    // 2. Delivered on the next dispatch
+    let report = dispatcher.dispatch(&monitor)?;
+    assert_eq!(report.delivered, 1);
+    assert_eq!(report.pending, 0);
+    assert_eq!(report.failure, None);
+    assert_eq!(report.retry_in, None);
+    assert_eq!(monitor.news_count()?, 0);
+
+    // 3. Requests
+    server.join().unwrap();
+    let requests: Vec<Received> = received.try_iter().collect();
+    assert_eq!(requests.len(), 2);
+    for request in &requests {
+        assert_eq!(request.headers[0], "post /news http/1.1");
+        assert!(request
+            .headers
+            .contains(&"authorization: bearer secret".to_string()));
+        assert!(request
+            .headers
+            .contains(&"content-type: application/json".to_string()));
+        assert_eq!(request.body["news"]["kind"], "transaction");
+        assert_eq!(request.body["news"]["context"], "ctx");
+    }
+    assert_eq!(requests[0].body, requests[1].body);
+
+    // Nothing left to deliver, the webhook is not called
+    let report = dispatcher.dispatch(&monitor)?;
+    assert_eq!(report, Default::default());
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that a failing webhook is not called again before its backoff passed:
+/// 1. The webhook answers 500, the dispatch reports the wait before the next attempt
+/// 2. A dispatch during the backoff doesn't post and keeps the news pending
+#[test]
+fn test_dispatch_backs_off() -> Result<(), anyhow::Error> {
+    let monitor = monitor_with_news()?;
+    let (addr, received, server) = start_webhook(vec![500]);
+    let mut dispatcher = NewsDispatcher::new(webhook(addr, 60_000))?;
+
+    // 1. Failure
+    let report = dispatcher.dispatch(&monitor)?;
+    assert_eq!(report.pending, 1);
+    assert_eq!(report.retry_in, Some(Duration::from_secs(60)));
+    server.join().unwrap();
+    assert_eq!(received.try_iter().count(), 1);
+
+    // 2. Backing off, the listener is closed so a post would fail
+    let report = dispatcher.dispatch(&monitor)?;
+    assert_eq!(report.delivered, 0);
+    assert_eq!(report.pending, 1);
+    assert_eq!(report.failure, None);
+    assert!(report
+        .retry_in
+        .is_some_and(|wait| wait <= Duration::from_secs(60)));
+    assert_eq!(monitor.news_count()?, 1);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that only plain http urls are accepted.
+#[test]
+fn test_dispatcher_url() {
+    for url in [
+        "https://example.com/news",
+        "example.com",
+        "http://:80/",
+        "http://host:x/",
+    ] {
+        let config = WebhookConfig {
+            url: url.to_string(),
+            auth_header: None,
+            retry: WebhookRetrySettings::default(),
+        };
+        assert!(matches!(
+            NewsDispatcher::new(config),
+            Err(MonitorError::InvalidSettings(_))
+        ));
+    }
+
+    let config = WebhookConfig {
+        url: "http://localhost".to_string(),
+        auth_header: None,
+        retry: WebhookRetrySettings::default(),
+    };
+    assert!(NewsDispatcher::new(config).is_ok());
+}
@@ -0,0 +1,364 @@
+use bitcoin::{absolute::LockTime, BlockHash, Transaction};
+use bitcoin_indexer::{
+    indexer::MockIndexerApi,
+    types::{FullBlock, TransactionInfo},
+};
+use bitvmx_transaction_monitor::{
+    config::{MonitorSettings, MonitorSettingsConfig},
+    monitor::Monitor,
+    news_event::NewsEvent,
+    redaction,
+    store::{MonitorStore, MonitoredTypes},
+    types::AckMonitorNews,
+    WatchTx,
+};
+use std::{
+    collections::BTreeMap,
+    fmt,
+    str::FromStr,
+    sync::{Arc, Mutex},
+};
+use storage_backend::{storage::Storage, storage_config::StorageConfig};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Subscriber,
+};
+use tracing_subscriber::{layer::Context, prelude::*, registry::LookupSpan, Layer};
+use utils::{clear_output, generate_random_string};
+mod utils;
+
+type Fields = BTreeMap<String, String>;
+
+/// Keeps the fields of every event, the message included, and the name and fields of every
+/// span with the names of its parents, outermost first.
+#[derive(Clone, Default)]
+struct CaptureLayer {
+    events: Arc<Mutex<Vec<Fields>>>,
+    spans: Arc<Mutex<Vec<(Vec<String>, Fields)>>>,
+}
+
+struct FieldsVisitor(Fields);
+
+impl Visit for FieldsVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{:?}", value));
+    }
+}
+
+impl<S: Subscriber + for<'a> LookupSpan<'a>> Layer<S> for CaptureLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = FieldsVisitor(Fields::new());
+        event.record(&mut visitor);
+        self.events.lock().unwrap().push(visitor.0);
+    }
+
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        let mut visitor = FieldsVisitor(Fields::new());
+        attrs.record(&mut visitor);
+
+        let path = ctx
+            .span_scope(id)
+            .map(|scope| {
+                scope
+                    .from_root()
+                    .map(|span| span.name().to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+        self.spans.lock().unwrap().push((path, visitor.0));
+    }
+}
+
+impl CaptureLayer {
+    fn news_events(&self, message: &str) -> Vec<Fields> {
+        self.events
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|fields| fields.get("message").is_some_and(|m| m == message))
+            .cloned()
+            .collect()
+    }
+
+    /// Fields of the spans opened under the names of `path`, outermost first.
+    fn spans(&self, path: &[&str]) -> Vec<Fields> {
+        self.spans
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(names, _)| names.iter().map(String::as_str).eq(path.iter().copied()))
+            .map(|(_, fields)| fields.clone())
+            .collect()
+    }
+}
+
+/// Test that the news of the monitor_txs_detected scenario are logged with their correlation fields:
+/// 1. Two transaction monitors with their contexts find their transactions in block 200
+/// 2. Each emitted news is logged with its kind, context, txid, block hash and confirmations
+/// 3. Reading and acknowledging the news log the same context and txid
+#[test]
+fn test_news_events_carry_context() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let block_200 = FullBlock {
+        height: 200,
+        hash: BlockHash::from_str(
+            "0000000000000000000000000000000000000000000000000000000000000011",
+        )?,
+        prev_hash: BlockHash::from_str(
+            "0000000000000000000000000000000000000000000000000000000000000001",
+        )?,
+        txs: vec![],
+        orphan: false,
+        estimated_fee_rate: 0,
+    };
+
+    let txs: Vec<Transaction> = [1653195600, 1653195601]
+        .into_iter()
+        .map(|time| Transaction {
+            version: bitcoin::transaction::Version::TWO,
+            lock_time: LockTime::from_time(time).unwrap(),
+            input: vec![],
+            output: vec![],
+        })
+        .collect();
+
+    let mut mock_indexer = MockIndexerApi::new();
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let best_block = block_200.clone();
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(best_block.clone())));
+    let block_by_height = block_200.clone();
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |_| Ok(Some(block_by_height.clone())));
+    let (indexed_txs, tx_block) = (txs.clone(), block_200.clone());
+    mock_indexer.expect_get_tx().returning(move |tx_id| {
+        Ok(indexed_txs
+            .iter()
+            .find(|tx| tx.compute_txid() == *tx_id)
+            .map(|tx| TransactionInfo {
+                tx: tx.clone(),
+                block_info: tx_block.clone(),
+                confirmations: 1,
+            }))
+    });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        // Contexts revealed, redaction is covered by redaction_test
+        MonitorSettings::from(MonitorSettingsConfig {
+            reveal_contexts: Some(true),
+            ..Default::default()
+        }),
+    )?;
+
+    let capture = CaptureLayer::default();
+    let subscriber = tracing_subscriber::registry().with(capture.clone());
+    let contexts = ["test", "test 2"];
+
+    tracing::subscriber::with_default(subscriber, || -> Result<(), anyhow::Error> {
+        // 1. Both transactions found
+        for (tx, context) in txs.iter().zip(contexts) {
+            monitor.save_monitor(WatchTx::new(tx.compute_txid()).context(context).build()?)?;
+        }
+        monitor.tick()?;
+
+        // 2. Emitted with the correlation fields
+        let emitted = capture.news_events("News emitted");
+        assert_eq!(emitted.len(), 2);
+        for (tx, context) in txs.iter().zip(contexts) {
+            let event = emitted
+                .iter()
+                .find(|fields| fields.get("context").is_some_and(|c| c == context))
+                .expect("news event with the context");
+            assert_eq!(event["kind"], "transaction");
+            assert_eq!(event["tx_id"], tx.compute_txid().to_string());
+            assert_eq!(event["block_hash"], block_200.hash.to_string());
+            assert_eq!(event["confirmations"], "1");
+        }
+
+        // 3. Read and acknowledged with the same fields
+        monitor.get_news()?;
+        for (tx, context) in txs.iter().zip(contexts) {
+            monitor.ack_news(AckMonitorNews::Transaction(
+                tx.compute_txid(),
+                context.into(),
+            ))?;
+        }
+
+        for message in ["News read", "News acknowledged"] {
+            let events = capture.news_events(message);
+            for (tx, context) in txs.iter().zip(contexts) {
+                assert!(events.iter().any(|fields| {
+                    fields.get("context").is_some_and(|c| c == context)
+                        && fields.get("tx_id") == Some(&tx.compute_txid().to_string())
+                }));
+            }
+        }
+
+        Ok(())
+    })?;
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that the context of a news event can't break a log line and is cut when too long.
+#[test]
+fn test_news_event_context_sanitized() -> Result<(), anyhow::Error> {
+    redaction::reveal_contexts(true);
+
+    let tx_id = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![],
+        output: vec![],
+    }
+    .compute_txid();
+
+    let event = NewsEvent::new(&MonitoredTypes::Transaction(tx_id, "a\nb".into()));
+    assert_eq!(event.context.as_deref(), Some("a?b"));
+
+    let event = NewsEvent::new(&MonitoredTypes::Transaction(tx_id, "x".repeat(100).into()));
+    assert_eq!(event.context, Some(format!("{}...", "x".repeat(64))));
+
+    Ok(())
+}
+
+/// Test that a tick runs in the spans of its phases and ends with a summary event:
+/// 1. Monitor a transaction with 1 confirmation and one with 10, past the 6 confirmations
+///    monitored, in a tick over block 200
+/// 2. Assert the summary counts the news of the first and the deactivation of the second
+/// 3. Assert the indexer tick, the block, the monitors evaluated with their txids, the news
+///    write and the height update run in the spans of the tick
+#[test]
+fn test_tick_spans_and_summary() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let block_200 = FullBlock {
+        height: 200,
+        hash: BlockHash::from_str(
+            "0000000000000000000000000000000000000000000000000000000000000011",
+        )?,
+        prev_hash: BlockHash::from_str(
+            "0000000000000000000000000000000000000000000000000000000000000001",
+        )?,
+        txs: vec![],
+        orphan: false,
+        estimated_fee_rate: 0,
+    };
+
+    let txs: Vec<(Transaction, u32)> = [(1653195600, 1), (1653195601, 10)]
+        .into_iter()
+        .map(|(time, confirmations)| {
+            let tx = Transaction {
+                version: bitcoin::transaction::Version::TWO,
+                lock_time: LockTime::from_time(time).unwrap(),
+                input: vec![],
+                output: vec![],
+            };
+            (tx, confirmations)
+        })
+        .collect();
+
+    let mut mock_indexer = MockIndexerApi::new();
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let best_block = block_200.clone();
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(best_block.clone())));
+    let block_by_height = block_200.clone();
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |_| Ok(Some(block_by_height.clone())));
+    let (indexed_txs, tx_block) = (txs.clone(), block_200.clone());
+    mock_indexer.expect_get_tx().returning(move |tx_id| {
+        Ok(indexed_txs
+            .iter()
+            .find(|(tx, _)| tx.compute_txid() == *tx_id)
+            .map(|(tx, confirmations)| TransactionInfo {
+                tx: tx.clone(),
+                block_info: tx_block.clone(),
+                confirmations: *confirmations,
+            }))
+    });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig {
+            max_monitoring_confirmations: Some(6),
+            ..Default::default()
+        }),
+    )?;
+
+    let capture = CaptureLayer::default();
+    let subscriber = tracing_subscriber::registry().with(capture.clone());
+
+    tracing::subscriber::with_default(subscriber, || -> Result<(), anyhow::Error> {
+        // 1. One news and one deactivation
+        for (tx, _) in &txs {
+            monitor.save_monitor(WatchTx::new(tx.compute_txid()).context("ctx").build()?)?;
+        }
+        monitor.tick()?;
+
+        // 2. Summary
+        let summary = capture.news_events("Tick summary");
+        assert_eq!(summary.len(), 1);
+        assert_eq!(summary[0]["height"], "200");
+        assert_eq!(summary[0]["news"], "1");
+        assert_eq!(summary[0]["deactivated"], "1");
+        assert_eq!(summary[0]["failed"], "false");
+
+        // 3. Spans of the phases
+        assert_eq!(capture.spans(&["tick"]).len(), 1);
+        assert_eq!(capture.spans(&["tick", "indexer_tick"]).len(), 1);
+
+        let blocks = capture.spans(&["tick", "block"]);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0]["height"], "200");
+        assert_eq!(blocks[0]["hash"], block_200.hash.to_string());
+
+        let kinds: Vec<String> = capture
+            .spans(&["tick", "block", "monitors"])
+            .iter()
+            .map(|fields| fields["monitor_kind"].clone())
+            .collect();
+        assert!(kinds.contains(&"Transaction".to_string()));
+
+        let evaluated = capture.spans(&["tick", "block", "monitors", "monitor"]);
+        for (tx, _) in &txs {
+            let txid = tx.compute_txid().to_string();
+            assert!(evaluated
+                .iter()
+                .any(|fields| fields.get("txid") == Some(&txid)));
+        }
+
+        let writes = capture.spans(&["tick", "block", "monitors", "monitor", "write_news"]);
+        assert_eq!(writes.len(), 1);
+        assert_eq!(writes[0]["kind"], "transaction");
+
+        assert_eq!(capture.spans(&["tick", "block", "update_height"]).len(), 1);
+
+        Ok(())
+    })?;
+
+    clear_output();
+
+    Ok(())
+}
@@ -0,0 +1,96 @@
+use bitcoin::{absolute::LockTime, Transaction};
+use bitcoin_indexer::types::TransactionInfo;
+use bitvmx_transaction_monitor::{
+    config::{MonitorSettings, MonitorSettingsConfig},
+    monitor::Monitor,
+    store::MonitorStore,
+    types::{AckMonitorNews, MonitorNews, TypesToMonitor},
+    WatchTx,
+};
+use std::sync::{
+    atomic::{AtomicU32, Ordering},
+    mpsc::TryRecvError,
+    Arc,
+};
+use storage_backend::{storage::Storage, storage_config::StorageConfig};
+use utils::{block_at, clear_output, generate_random_string, scripted_indexer};
+mod utils;
+
+/// Test that the news of a tick are sent to the news receivers:
+/// 1. A transaction mined in block 200 and the new block are received once the tick ends
+/// 2. Received news are not acknowledged, get_news still returns them
+/// 3. A tick without news sends nothing
+/// 4. Ticks keep working once a receiver is dropped, the other receivers still get the news
+#[test]
+fn test_news_receiver() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_consensus(1),
+        input: vec![],
+        output: vec![],
+    };
+    let tx_id = tx.compute_txid();
+
+    let height = Arc::new(AtomicU32::new(200));
+
+    let mut mock_indexer = scripted_indexer(height.clone());
+    let tx_height = height.clone();
+    mock_indexer.expect_get_tx().returning(move |_| {
+        let tip = tx_height.load(Ordering::SeqCst);
+        Ok(Some(TransactionInfo {
+            tx: tx.clone(),
+            block_info: block_at(200),
+            confirmations: tip - 200 + 1,
+        }))
+    });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+
+    let receiver = monitor.news_receiver();
+    let dropped = monitor.news_receiver();
+
+    monitor.save_monitor(WatchTx::new(tx_id).context("ctx").build()?)?;
+    monitor.save_monitor(TypesToMonitor::NewBlock)?;
+
+    // 1. News of the tick
+    monitor.tick()?;
+    let received: Vec<MonitorNews> = receiver.try_iter().collect();
+    assert_eq!(received.len(), 2);
+    assert!(received.iter().any(|news| matches!(
+        news,
+        MonitorNews::Transaction(id, status, ctx) if *id == tx_id && status.confirmations == 1 && ctx == "ctx"
+    )));
+    assert!(received
+        .iter()
+        .any(|news| matches!(news, MonitorNews::NewBlock(200, _))));
+
+    // 2. Not acknowledged
+    assert_eq!(monitor.get_news()?.len(), 2);
+    monitor.ack_news(AckMonitorNews::NewBlock(None))?;
+    monitor.ack_news(AckMonitorNews::Transaction(tx_id, "ctx".into()))?;
+
+    // 3. Nothing new
+    monitor.tick()?;
+    assert!(matches!(receiver.try_recv(), Err(TryRecvError::Empty)));
+
+    // 4. Dropped receiver
+    drop(dropped);
+    height.store(201, Ordering::SeqCst);
+    monitor.tick()?;
+    let received: Vec<MonitorNews> = receiver.try_iter().collect();
+    assert_eq!(received.len(), 2);
+    assert_eq!(monitor.get_news()?.len(), 2);
+
+    clear_output();
+
+    Ok(())
+}
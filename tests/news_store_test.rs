@@ -1,9 +1,14 @@
-use bitcoin::{absolute::LockTime, BlockHash, Transaction};
+use bitcoin::{absolute::LockTime, BlockHash, OutPoint, Transaction};
 use bitvmx_transaction_monitor::{
+    config::{MonitorSettings, MonitorSettingsConfig},
+    errors::MonitorError,
+    external::NoIndexer,
+    monitor::Monitor,
     store::{MonitorStore, MonitorStoreApi, MonitoredTypes},
-    types::AckMonitorNews,
+    testing::fixture::StoreFixture,
+    types::{AckMonitorNews, MonitorContext},
 };
-use std::{rc::Rc, str::FromStr};
+use std::{str::FromStr, sync::Arc};
 use storage_backend::{storage::Storage, storage_config::StorageConfig};
 use utils::{clear_output, generate_random_string};
 use uuid::Uuid;
@@ -29,7 +34,7 @@ mod utils;
 fn news_test() -> Result<(), anyhow::Error> {
     let path = format!("test_outputs/{}", generate_random_string());
     let config = StorageConfig::new(path, None);
-    let storage = Rc::new(Storage::new(&config)?);
+    let storage = Arc::new(Storage::new(&config)?);
     let store = MonitorStore::new(storage)?;
     let tx = Transaction {
         version: bitcoin::transaction::Version::TWO,
@@ -49,20 +54,20 @@ fn news_test() -> Result<(), anyhow::Error> {
         BlockHash::from_str("0000000000000000000000000000000000000000000000000000000000000001")?;
 
     // Test one transaction news
-    let tx_news = MonitoredTypes::Transaction(tx.compute_txid(), "Context_1".to_string());
+    let tx_news = MonitoredTypes::Transaction(tx.compute_txid(), "Context_1".into());
     store.update_news(tx_news.clone(), block_hash)?;
     let news = store.get_news()?;
     assert_eq!(news.len(), 1);
     // Make ack to that news
     store.ack_news(AckMonitorNews::Transaction(
         tx.compute_txid(),
-        "Context_1".to_string(),
+        "Context_1".into(),
     ))?;
     let news = store.get_news()?;
     assert_eq!(news.len(), 0);
 
     // Update the existing news with same block hash
-    let txs_news = MonitoredTypes::Transaction(tx.compute_txid(), "Context_1".to_string());
+    let txs_news = MonitoredTypes::Transaction(tx.compute_txid(), "Context_1".into());
     store.update_news(txs_news.clone(), block_hash)?;
 
     // Verify we have a No news because for this block hash we already have an ack
@@ -79,7 +84,7 @@ fn news_test() -> Result<(), anyhow::Error> {
     // Make ack to that news and verify we have no news
     store.ack_news(AckMonitorNews::Transaction(
         tx.compute_txid(),
-        "Context_1".to_string(),
+        "Context_1".into(),
     ))?;
     let news = store.get_news()?;
     assert_eq!(news.len(), 0);
@@ -121,7 +126,7 @@ fn news_test() -> Result<(), anyhow::Error> {
     // assert_eq!(news.len(), 1);
     // assert_eq!(news[0], block_news);
 
-    // store.ack_news(AckMonitorNews::NewBlock)?;
+    // store.ack_news(AckMonitorNews::NewBlock(None))?;
     // let news = store.get_news()?;
     // assert_eq!(news.len(), 0);
 
@@ -134,7 +139,7 @@ fn news_test() -> Result<(), anyhow::Error> {
 fn test_duplicate_news() -> Result<(), anyhow::Error> {
     let path = format!("test_outputs/{}", generate_random_string());
     let config = StorageConfig::new(path, None);
-    let storage = Rc::new(Storage::new(&config)?);
+    let storage = Arc::new(Storage::new(&config)?);
     let store = MonitorStore::new(storage)?;
     let tx = Transaction {
         version: bitcoin::transaction::Version::TWO,
@@ -150,7 +155,7 @@ fn test_duplicate_news() -> Result<(), anyhow::Error> {
         BlockHash::from_str("0000000000000000000000000000000000000000000000000000000000000001")?;
 
     // Test duplicate transaction news
-    let tx_news = MonitoredTypes::Transaction(tx.compute_txid(), String::new());
+    let tx_news = MonitoredTypes::Transaction(tx.compute_txid(), MonitorContext::default());
     store.update_news(tx_news.clone(), block_hash)?;
     store.update_news(tx_news.clone(), block_hash)?; // Try adding same tx again
     let news = store.get_news()?;
@@ -158,12 +163,13 @@ fn test_duplicate_news() -> Result<(), anyhow::Error> {
     assert_eq!(news[0], tx_news);
     store.ack_news(AckMonitorNews::Transaction(
         tx.compute_txid(),
-        String::new(),
+        MonitorContext::default(),
     ))?;
 
     // Test duplicate group transaction news
     let context_data = Uuid::new_v4();
-    let monitored_tx = MonitoredTypes::Transaction(tx.compute_txid(), context_data.to_string());
+    let monitored_tx =
+        MonitoredTypes::Transaction(tx.compute_txid(), context_data.to_string().into());
     store.update_news(monitored_tx.clone(), block_hash_1)?;
     store.update_news(monitored_tx.clone(), block_hash_1)?; // Try adding same group tx again
     let news = store.get_news()?;
@@ -171,11 +177,11 @@ fn test_duplicate_news() -> Result<(), anyhow::Error> {
     assert!(news.contains(&monitored_tx));
     store.ack_news(AckMonitorNews::Transaction(
         tx.compute_txid(),
-        context_data.to_string(),
+        context_data.to_string().into(),
     ))?;
 
     // Test duplicate RSK pegin transaction news
-    let rsk_tx_news = MonitoredTypes::RskPeginTransaction(tx.compute_txid());
+    let rsk_tx_news = MonitoredTypes::RskPeginTransaction(tx.compute_txid(), None);
     store.update_news(rsk_tx_news.clone(), block_hash)?;
     store.update_news(rsk_tx_news.clone(), block_hash)?; // Try adding same RSK tx again
     let news = store.get_news()?;
@@ -187,7 +193,7 @@ fn test_duplicate_news() -> Result<(), anyhow::Error> {
     let spending_tx_news = MonitoredTypes::SpendingUTXOTransaction(
         tx.compute_txid(),
         0,
-        String::new(),
+        MonitorContext::default(),
         tx.compute_txid(),
     );
     store.update_news(spending_tx_news.clone(), block_hash)?;
@@ -198,17 +204,17 @@ fn test_duplicate_news() -> Result<(), anyhow::Error> {
     store.ack_news(AckMonitorNews::SpendingUTXOTransaction(
         tx.compute_txid(),
         0,
-        String::new(),
+        MonitorContext::default(),
     ))?;
 
     // Test duplicate new block news
-    let block_news = MonitoredTypes::NewBlock(block_hash);
+    let block_news = MonitoredTypes::NewBlock(block_hash, Some(200));
     store.update_news(block_news.clone(), block_hash)?;
     store.update_news(block_news.clone(), block_hash)?; // Try adding same block news again
     let news = store.get_news()?;
     assert_eq!(news.len(), 1); // Should have only block news
     assert!(news.contains(&block_news));
-    store.ack_news(AckMonitorNews::NewBlock)?;
+    store.ack_news(AckMonitorNews::NewBlock(None))?;
 
     let news = store.get_news()?;
     assert_eq!(news.len(), 0); // Should have no news after all acknowledgements
@@ -222,7 +228,7 @@ fn test_duplicate_news() -> Result<(), anyhow::Error> {
 fn test_multiple_transactions_per_type() -> Result<(), anyhow::Error> {
     let path = format!("test_outputs/{}", generate_random_string());
     let config = StorageConfig::new(path, None);
-    let storage = Rc::new(Storage::new(&config)?);
+    let storage = Arc::new(Storage::new(&config)?);
     let store = MonitorStore::new(storage)?;
 
     // Create 3 different transactions
@@ -246,9 +252,9 @@ fn test_multiple_transactions_per_type() -> Result<(), anyhow::Error> {
     };
 
     // Test multiple transactions
-    let monitor_tx1 = MonitoredTypes::Transaction(tx1.compute_txid(), String::new());
-    let monitor_tx2 = MonitoredTypes::Transaction(tx2.compute_txid(), String::new());
-    let monitor_tx3 = MonitoredTypes::Transaction(tx3.compute_txid(), String::new());
+    let monitor_tx1 = MonitoredTypes::Transaction(tx1.compute_txid(), MonitorContext::default());
+    let monitor_tx2 = MonitoredTypes::Transaction(tx2.compute_txid(), MonitorContext::default());
+    let monitor_tx3 = MonitoredTypes::Transaction(tx3.compute_txid(), MonitorContext::default());
 
     let block_hash =
         BlockHash::from_str("0000000000000000000000000000000000000000000000000000000000000000")?;
@@ -268,15 +274,15 @@ fn test_multiple_transactions_per_type() -> Result<(), anyhow::Error> {
 
     store.ack_news(AckMonitorNews::Transaction(
         tx1.compute_txid(),
-        String::new(),
+        MonitorContext::default(),
     ))?;
     store.ack_news(AckMonitorNews::Transaction(
         tx2.compute_txid(),
-        String::new(),
+        MonitorContext::default(),
     ))?;
     store.ack_news(AckMonitorNews::Transaction(
         tx3.compute_txid(),
-        String::new(),
+        MonitorContext::default(),
     ))?;
 
     let news = store.get_news()?;
@@ -287,9 +293,12 @@ fn test_multiple_transactions_per_type() -> Result<(), anyhow::Error> {
     let context_data2 = Uuid::new_v4();
     let context_data3 = Uuid::new_v4();
 
-    let monitored_tx1 = MonitoredTypes::Transaction(tx1.compute_txid(), context_data1.to_string());
-    let monitored_tx2 = MonitoredTypes::Transaction(tx2.compute_txid(), context_data2.to_string());
-    let monitored_tx3 = MonitoredTypes::Transaction(tx3.compute_txid(), context_data3.to_string());
+    let monitored_tx1 =
+        MonitoredTypes::Transaction(tx1.compute_txid(), context_data1.to_string().into());
+    let monitored_tx2 =
+        MonitoredTypes::Transaction(tx2.compute_txid(), context_data2.to_string().into());
+    let monitored_tx3 =
+        MonitoredTypes::Transaction(tx3.compute_txid(), context_data3.to_string().into());
 
     store.update_news(monitored_tx1.clone(), block_hash_1)?;
     store.update_news(monitored_tx2.clone(), block_hash_1)?;
@@ -303,24 +312,24 @@ fn test_multiple_transactions_per_type() -> Result<(), anyhow::Error> {
 
     store.ack_news(AckMonitorNews::Transaction(
         tx1.compute_txid(),
-        context_data1.to_string(),
+        context_data1.to_string().into(),
     ))?;
     store.ack_news(AckMonitorNews::Transaction(
         tx2.compute_txid(),
-        context_data2.to_string(),
+        context_data2.to_string().into(),
     ))?;
     store.ack_news(AckMonitorNews::Transaction(
         tx3.compute_txid(),
-        context_data3.to_string(),
+        context_data3.to_string().into(),
     ))?;
 
     let news = store.get_news()?;
     assert_eq!(news.len(), 0);
 
     // Test multiple RSK pegin transactions
-    let rsk_tx1 = MonitoredTypes::RskPeginTransaction(tx1.compute_txid());
-    let rsk_tx2 = MonitoredTypes::RskPeginTransaction(tx2.compute_txid());
-    let rsk_tx3 = MonitoredTypes::RskPeginTransaction(tx3.compute_txid());
+    let rsk_tx1 = MonitoredTypes::RskPeginTransaction(tx1.compute_txid(), None);
+    let rsk_tx2 = MonitoredTypes::RskPeginTransaction(tx2.compute_txid(), None);
+    let rsk_tx3 = MonitoredTypes::RskPeginTransaction(tx3.compute_txid(), None);
 
     store.update_news(rsk_tx1.clone(), block_hash)?;
     store.update_news(rsk_tx2.clone(), block_hash)?;
@@ -343,19 +352,19 @@ fn test_multiple_transactions_per_type() -> Result<(), anyhow::Error> {
     let spending_tx1 = MonitoredTypes::SpendingUTXOTransaction(
         tx1.compute_txid(),
         0,
-        String::new(),
+        MonitorContext::default(),
         tx1.compute_txid(),
     );
     let spending_tx2 = MonitoredTypes::SpendingUTXOTransaction(
         tx2.compute_txid(),
         1,
-        String::new(),
+        MonitorContext::default(),
         tx1.compute_txid(),
     );
     let spending_tx3 = MonitoredTypes::SpendingUTXOTransaction(
         tx3.compute_txid(),
         2,
-        String::new(),
+        MonitorContext::default(),
         tx1.compute_txid(),
     );
 
@@ -372,31 +381,31 @@ fn test_multiple_transactions_per_type() -> Result<(), anyhow::Error> {
     store.ack_news(AckMonitorNews::SpendingUTXOTransaction(
         tx1.compute_txid(),
         0,
-        String::new(),
+        MonitorContext::default(),
     ))?;
     store.ack_news(AckMonitorNews::SpendingUTXOTransaction(
         tx2.compute_txid(),
         1,
-        String::new(),
+        MonitorContext::default(),
     ))?;
     store.ack_news(AckMonitorNews::SpendingUTXOTransaction(
         tx3.compute_txid(),
         2,
-        String::new(),
+        MonitorContext::default(),
     ))?;
 
     let news = store.get_news()?;
     assert_eq!(news.len(), 0);
 
     // Test multiple new block notifications
-    let block_news1 = MonitoredTypes::NewBlock(block_hash);
+    let block_news1 = MonitoredTypes::NewBlock(block_hash, Some(200));
     store.update_news(block_news1.clone(), block_hash)?;
 
     let news = store.get_news()?;
     assert_eq!(news.len(), 1);
     assert!(news.contains(&block_news1));
 
-    store.ack_news(AckMonitorNews::NewBlock)?;
+    store.ack_news(AckMonitorNews::NewBlock(None))?;
 
     let news = store.get_news()?;
     assert_eq!(news.len(), 0);
@@ -405,3 +414,214 @@ fn test_multiple_transactions_per_type() -> Result<(), anyhow::Error> {
 
     Ok(())
 }
+
+/// Test has_news and get_news_count of the MonitorStore
+/// This test verifies:
+/// 1. Initial state - no news and a count of 0
+/// 2. The count follows the pending news of the lists, the single news and the block queue
+/// 3. Acknowledged news are not counted, the count matches get_news
+/// 4. has_news is false once every news is acknowledged
+#[test]
+fn test_news_count() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_time(1653195600).unwrap(),
+        input: vec![],
+        output: vec![],
+    };
+    let tx_id = tx.compute_txid();
+
+    let block_hash =
+        BlockHash::from_str("0000000000000000000000000000000000000000000000000000000000000000")?;
+
+    assert!(!store.has_news()?);
+    assert_eq!(store.get_news_count()?, 0);
+
+    store.update_news(
+        MonitoredTypes::Transaction(tx_id, "first".into()),
+        block_hash,
+    )?;
+    store.update_news(
+        MonitoredTypes::Transaction(tx_id, "second".into()),
+        block_hash,
+    )?;
+    store.update_news(
+        MonitoredTypes::SpendingUTXOTransaction(tx_id, 0, MonitorContext::default(), tx_id),
+        block_hash,
+    )?;
+    store.update_news(MonitoredTypes::SequenceEpochChanged(0, 1), block_hash)?;
+    store.update_news(MonitoredTypes::NewBlock(block_hash, Some(200)), block_hash)?;
+
+    assert!(store.has_news()?);
+    assert_eq!(store.get_news_count()?, 5);
+    assert_eq!(store.get_news_count()?, store.get_news()?.len());
+
+    store.ack_news(AckMonitorNews::Transaction(tx_id, "first".into()))?;
+    store.ack_news(AckMonitorNews::SequenceEpochChanged { new_epoch: 1 })?;
+    store.ack_news(AckMonitorNews::NewBlock(None))?;
+
+    assert!(store.has_news()?);
+    assert_eq!(store.get_news_count()?, 2);
+    assert_eq!(store.get_news_count()?, store.get_news()?.len());
+
+    store.ack_news(AckMonitorNews::Transaction(tx_id, "second".into()))?;
+    store.ack_news(AckMonitorNews::SpendingUTXOTransaction(
+        tx_id,
+        0,
+        MonitorContext::default(),
+    ))?;
+
+    assert!(!store.has_news()?);
+    assert_eq!(store.get_news_count()?, 0);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test acknowledging news in batches
+/// 1. Two news of the same key, one of another key and a block are acknowledged in one batch
+/// 2. The store returns the items matching no news, the others are still acknowledged
+/// 3. The monitor reports those items in a NewsNotAcked error after acknowledging the rest
+#[test]
+fn test_ack_news_batch() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_time(1653195600).unwrap(),
+        input: vec![],
+        output: vec![],
+    };
+    let tx_id = tx.compute_txid();
+
+    let block_hash =
+        BlockHash::from_str("0000000000000000000000000000000000000000000000000000000000000000")?;
+
+    let store = StoreFixture::new()
+        .active_tx(tx_id)
+        .context("first")
+        .with_news(block_hash, false)
+        .active_tx(tx_id)
+        .context("second")
+        .with_news(block_hash, false)
+        .active_spending(OutPoint::new(tx_id, 0))
+        .spent_by(tx_id)
+        .with_news(block_hash, false)
+        .build(storage.clone())?;
+    store.update_news(MonitoredTypes::NewBlock(block_hash, Some(200)), block_hash)?;
+    assert_eq!(store.get_news_count()?, 4);
+
+    // 1. and 2. Partial batch
+    let unknown = AckMonitorNews::Transaction(tx_id, "missing".into());
+    let not_acked = store.ack_news_batch(vec![
+        AckMonitorNews::Transaction(tx_id, "first".into()),
+        unknown.clone(),
+        AckMonitorNews::Transaction(tx_id, "second".into()),
+        AckMonitorNews::NewBlock(None),
+    ])?;
+    assert!(not_acked == vec![unknown.clone()]);
+    assert_eq!(
+        store.get_news()?,
+        vec![MonitoredTypes::SpendingUTXOTransaction(
+            tx_id,
+            0,
+            MonitorContext::default(),
+            tx_id
+        )]
+    );
+
+    // 3. Through the monitor
+    let monitor = Monitor::new(
+        NoIndexer,
+        MonitorStore::new(storage)?,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+    let result = monitor.ack_news_batch(vec![
+        unknown.clone(),
+        AckMonitorNews::SpendingUTXOTransaction(tx_id, 0, MonitorContext::default()),
+    ]);
+    match result {
+        Err(MonitorError::NewsNotAcked {
+            unknown: not_acked,
+            held,
+        }) => {
+            assert!(not_acked == vec![unknown]);
+            assert!(held.is_empty());
+        }
+        _ => panic!("expected a NewsNotAcked error"),
+    }
+    assert!(!monitor.has_news()?);
+
+    monitor.ack_news_batch(vec![])?;
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test acknowledging every pending news at once
+/// 1. Transaction, RSK pegin, spending UTXO and block news are flushed, one already acknowledged
+///    is not counted
+/// 2. The entries are kept: the same news reported again in the same block is not sent again
+/// 3. Nothing left to flush
+#[test]
+fn test_ack_all_news() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage.clone())?;
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_time(1653195600).unwrap(),
+        input: vec![],
+        output: vec![],
+    };
+    let tx_id = tx.compute_txid();
+
+    let block_hash =
+        BlockHash::from_str("0000000000000000000000000000000000000000000000000000000000000000")?;
+
+    let news = vec![
+        MonitoredTypes::Transaction(tx_id, "first".into()),
+        MonitoredTypes::Transaction(tx_id, "second".into()),
+        MonitoredTypes::RskPeginTransaction(tx_id, None),
+        MonitoredTypes::SpendingUTXOTransaction(tx_id, 0, MonitorContext::default(), tx_id),
+        MonitoredTypes::NewBlock(block_hash, Some(200)),
+    ];
+    for data in news.clone() {
+        store.update_news(data, block_hash)?;
+    }
+    store.ack_news(AckMonitorNews::Transaction(tx_id, "second".into()))?;
+    assert_eq!(store.get_news_count()?, 4);
+
+    // 1. Flush through the monitor
+    let monitor = Monitor::new(
+        NoIndexer,
+        MonitorStore::new(storage)?,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+    assert_eq!(monitor.ack_all_news()?, 4);
+    assert!(!monitor.has_news()?);
+
+    // 2. Entries kept for deduplication
+    let acks = store.get_news_acks()?;
+    assert_eq!(acks.len(), news.len());
+    assert!(acks.iter().all(|(_, ack)| ack.acknowledged));
+    for data in news {
+        store.update_news(data, block_hash)?;
+    }
+    assert_eq!(store.get_news_count()?, 0);
+
+    // 3. Nothing left
+    assert_eq!(store.ack_all_news()?, 0);
+
+    clear_output();
+
+    Ok(())
+}
@@ -0,0 +1,231 @@
+use bitcoin::{BlockHash, Network};
+use bitcoin_indexer::{indexer::MockIndexerApi, types::FullBlock};
+use bitvmx_bitcoin_rpc::rpc_config::RpcConfig;
+use bitvmx_transaction_monitor::{
+    config::{MonitorSettings, MonitorSettingsConfig},
+    errors::MonitorError,
+    external::NoIndexer,
+    migrations::STORE_SCHEMA_VERSION,
+    monitor::Monitor,
+    preflight::{preflight_with_paths, MockNodeApi},
+    self_test::CheckOutcome,
+    store::{MonitorStore, MonitorStoreApi},
+};
+use std::{str::FromStr, sync::Arc};
+use storage_backend::{
+    storage::{KeyValueStore, Storage},
+    storage_config::StorageConfig,
+};
+use utils::{clear_output, generate_random_string};
+mod utils;
+
+fn node_on(network: Network) -> Box<MockNodeApi> {
+    let mut node = MockNodeApi::new();
+    node.expect_get_network().returning(move || Ok(network));
+    Box::new(node)
+}
+
+/// Test that the network of the node is recorded on the first tick and checked by the preflight:
+/// 1. Before the first tick the network check is skipped and the others pass
+/// 2. The first tick records the network of the node
+/// 3. The preflight passes with a node on the recorded network
+/// 4. A node on another network fails the network check
+#[test]
+fn test_preflight_network() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let storage = Arc::new(Storage::new(&StorageConfig::new(path, None))?);
+    let settings = MonitorSettings::from(MonitorSettingsConfig::default());
+
+    let block = FullBlock {
+        height: 100,
+        hash: BlockHash::from_str(&format!("{:064x}", 100))?,
+        prev_hash: BlockHash::from_str(&format!("{:064x}", 99))?,
+        txs: vec![],
+        orphan: false,
+        estimated_fee_rate: 0,
+    };
+    let mut mock_indexer = MockIndexerApi::new();
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let best_block = block.clone();
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(best_block.clone())));
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |_| Ok(Some(block.clone())));
+    mock_indexer.expect_get_tx().returning(|_| Ok(None));
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        MonitorStore::new(storage.clone())?,
+        settings.clone(),
+    )?
+    .with_node(node_on(Network::Regtest));
+
+    // 1. Nothing recorded
+    let report = monitor.preflight();
+    assert!(report.passed(), "{}", report);
+    assert_eq!(report.outcome("node"), Some(&CheckOutcome::Passed));
+    assert!(matches!(
+        report.outcome("network"),
+        Some(CheckOutcome::Skipped(_))
+    ));
+    assert_eq!(report.outcome("schema"), Some(&CheckOutcome::Passed));
+    assert_eq!(report.outcome("storage"), Some(&CheckOutcome::Passed));
+    assert_eq!(report.outcome("settings"), Some(&CheckOutcome::Passed));
+
+    // 2. Recorded
+    monitor.tick()?;
+    assert_eq!(monitor.store.get_network()?, Some(Network::Regtest));
+
+    // 3. Same network
+    let report = monitor.preflight();
+    assert!(report.passed(), "{}", report);
+    assert_eq!(report.outcome("network"), Some(&CheckOutcome::Passed));
+
+    // 4. Another network
+    let monitor = Monitor::new(NoIndexer, MonitorStore::new(storage)?, settings)?
+        .with_node(node_on(Network::Testnet));
+    let report = monitor.preflight();
+    assert!(!report.passed());
+    assert!(matches!(
+        report.outcome("network"),
+        Some(CheckOutcome::Failed(_))
+    ));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that the preflight reports every problem instead of stopping at the first one:
+/// 1. A node that doesn't answer fails the node check and skips the network check
+/// 2. A confirmation threshold above the max monitoring confirmations fails the settings check
+/// 3. The schema and storage checks still run and pass
+#[test]
+fn test_preflight_reports_all_problems() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let storage = Arc::new(Storage::new(&StorageConfig::new(path, None))?);
+    let settings = MonitorSettings::from(MonitorSettingsConfig {
+        confirmation_threshold: Some(10),
+        max_monitoring_confirmations: Some(6),
+        ..MonitorSettingsConfig::default()
+    });
+
+    let mut node = MockNodeApi::new();
+    node.expect_get_network()
+        .returning(|| Err(MonitorError::NodeRpcError("connection refused".to_string())));
+    let monitor =
+        Monitor::new(NoIndexer, MonitorStore::new(storage)?, settings)?.with_node(Box::new(node));
+
+    let report = monitor.preflight();
+    assert!(!report.passed());
+
+    // 1. Node
+    assert!(matches!(
+        report.outcome("node"),
+        Some(CheckOutcome::Failed(_))
+    ));
+    assert!(matches!(
+        report.outcome("network"),
+        Some(CheckOutcome::Skipped(_))
+    ));
+
+    // 2. Settings
+    assert!(matches!(
+        report.outcome("settings"),
+        Some(CheckOutcome::Failed(_))
+    ));
+
+    // 3. Storage
+    assert_eq!(report.outcome("schema"), Some(&CheckOutcome::Passed));
+    assert_eq!(report.outcome("storage"), Some(&CheckOutcome::Passed));
+    assert!(report
+        .to_string()
+        .ends_with("2 passed, 2 failed, 1 skipped"));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that the `--check` preflight leaves the store as it was and reports what it can't open:
+/// 1. A store of an older schema is reported as migrated on start and is not migrated
+/// 2. A store of a newer schema fails the schema check
+/// 3. A storage that can't be opened fails the storage check, the node and settings checks
+///    still run
+#[test]
+fn test_preflight_with_paths_leaves_store_untouched() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path.clone(), None);
+    let rpc_config = RpcConfig {
+        network: Network::Regtest,
+        url: "http://127.0.0.1:1".to_string(),
+        username: "foo".to_string(),
+        password: "rpcpassword".to_string(),
+        wallet: "test_wallet".to_string(),
+    };
+
+    // 1. Older schema
+    Storage::new(&config)?.set("monitor/schema_version", 1, None)?;
+
+    let report = preflight_with_paths(&rpc_config, &config, None);
+    assert!(matches!(
+        report.outcome("node"),
+        Some(CheckOutcome::Failed(_))
+    ));
+    assert!(matches!(
+        report.outcome("schema"),
+        Some(CheckOutcome::Skipped(reason)) if reason.contains("schema version 1")
+    ));
+    assert_eq!(report.outcome("storage"), Some(&CheckOutcome::Passed));
+    assert_eq!(
+        Storage::new(&config)?.get::<_, u32>("monitor/schema_version")?,
+        Some(1)
+    );
+
+    // 2. Newer schema
+    Storage::new(&config)?.set("monitor/schema_version", STORE_SCHEMA_VERSION + 1, None)?;
+
+    let report = preflight_with_paths(&rpc_config, &config, None);
+    assert!(matches!(
+        report.outcome("schema"),
+        Some(CheckOutcome::Failed(_))
+    ));
+
+    // 3. Storage that can't be opened
+    let file = format!("{path}-file");
+    std::fs::write(&file, "not a directory")?;
+    let settings = MonitorSettingsConfig {
+        confirmation_threshold: Some(10),
+        max_monitoring_confirmations: Some(6),
+        ..MonitorSettingsConfig::default()
+    };
+
+    let report = preflight_with_paths(
+        &rpc_config,
+        &StorageConfig::new(format!("{file}/store"), None),
+        Some(settings),
+    );
+    let names: Vec<&str> = report.checks.iter().map(|check| check.name).collect();
+    assert_eq!(
+        names,
+        vec!["node", "network", "schema", "storage", "settings"]
+    );
+    assert!(matches!(
+        report.outcome("node"),
+        Some(CheckOutcome::Failed(_))
+    ));
+    assert!(matches!(
+        report.outcome("storage"),
+        Some(CheckOutcome::Failed(_))
+    ));
+    assert!(matches!(
+        report.outcome("settings"),
+        Some(CheckOutcome::Failed(_))
+    ));
+
+    clear_output();
+
+    Ok(())
+}
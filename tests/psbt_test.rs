@@ -0,0 +1,126 @@
+#![cfg(feature = "psbt")]
+
+use bitcoin::{
+    absolute::LockTime, opcodes::all::OP_RETURN, psbt::Psbt, script::Builder, Amount, OutPoint,
+    ScriptBuf, Transaction, TxIn, TxOut, Txid, Witness,
+};
+use bitcoin_indexer::indexer::MockIndexerApi;
+use bitvmx_transaction_monitor::{
+    config::{MonitorSettings, MonitorSettingsConfig},
+    errors::MonitorError,
+    monitor::Monitor,
+    psbt::{PsbtMonitorPlan, PsbtOutputExclusion},
+    store::{MonitorStore, MonitorStoreApi, TypesToMonitorStore},
+};
+use std::{str::FromStr, sync::Arc};
+use storage_backend::{storage::Storage, storage_config::StorageConfig};
+use utils::{clear_output, generate_random_string};
+mod utils;
+
+fn output(value: u64, script_pubkey: ScriptBuf) -> TxOut {
+    TxOut {
+        value: Amount::from_sat(value),
+        script_pubkey,
+    }
+}
+
+/// Test that the monitors of a transaction are registered from its PSBT:
+/// 1. A PSBT paying to two addresses, with an OP_RETURN output and a dust output
+/// 2. An invalid context registers nothing
+/// 3. The transaction and its two spendable outputs are monitored, the plan lists the excluded
+///    outputs
+/// 4. Finalizing the PSBT doesn't change the txid that is monitored
+#[test]
+fn test_monitor_from_psbt() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let monitor = Monitor::new(
+        MockIndexerApi::new(),
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+
+    // 1. PSBT
+    let payment_script = ScriptBuf::from_hex(&format!("0014{}", "07".repeat(20)))?;
+    let change_script = ScriptBuf::from_hex(&format!("5120{}", "08".repeat(32)))?;
+    let op_return = Builder::new()
+        .push_opcode(OP_RETURN)
+        .push_slice(b"TAG:42")
+        .into_script();
+
+    let unsigned_tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::ZERO,
+        input: vec![TxIn {
+            previous_output: OutPoint::new(Txid::from_str(&format!("{:064x}", 1))?, 0),
+            ..Default::default()
+        }],
+        output: vec![
+            output(50_000, payment_script.clone()),
+            output(0, op_return),
+            output(100, payment_script),
+            output(10_000, change_script),
+        ],
+    };
+    let txid = unsigned_tx.compute_txid();
+    let mut psbt = Psbt::from_unsigned_tx(unsigned_tx)?;
+
+    // 2. Invalid context
+    assert!(matches!(
+        monitor.monitor_from_psbt(&psbt, "INTERNAL_RSK_PEGIN".to_string()),
+        Err(MonitorError::InvalidMonitor(_))
+    ));
+    assert!(monitor.store.get_monitors()?.is_empty());
+
+    // 3. Registered monitors
+    let plan = monitor.monitor_from_psbt(&psbt, "withdrawal-7".to_string())?;
+    assert_eq!(
+        plan,
+        PsbtMonitorPlan {
+            txid,
+            outpoints: vec![OutPoint::new(txid, 0), OutPoint::new(txid, 3)],
+            excluded: vec![
+                (OutPoint::new(txid, 1), PsbtOutputExclusion::OpReturn),
+                (OutPoint::new(txid, 2), PsbtOutputExclusion::Dust),
+            ],
+        }
+    );
+
+    let context = "withdrawal-7".to_string();
+    assert_eq!(
+        monitor.store.get_monitors()?,
+        vec![
+            TypesToMonitorStore::Transaction(txid, context.clone().into(), None, None),
+            TypesToMonitorStore::SpendingUTXOTransaction(
+                txid,
+                0,
+                context.clone().into(),
+                None,
+                None,
+                None,
+                None,
+            ),
+            TypesToMonitorStore::SpendingUTXOTransaction(
+                txid,
+                3,
+                context.into(),
+                None,
+                None,
+                None,
+                None
+            ),
+        ]
+    );
+    assert!(monitor.store.has_pending_work()?);
+
+    // 4. Finalized
+    psbt.inputs[0].final_script_witness = Some(Witness::from_slice(&[vec![1u8; 64]]));
+    assert_eq!(psbt.extract_tx_unchecked_fee_rate().compute_txid(), txid);
+
+    clear_output();
+
+    Ok(())
+}
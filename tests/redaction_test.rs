@@ -0,0 +1,105 @@
+use bitcoin::{BlockHash, Txid};
+use bitvmx_transaction_monitor::{
+    export::export_detections_csv,
+    redaction,
+    store::{MonitorStore, MonitorStoreApi, TypesToMonitorStore},
+    types::{AckMonitorNews, DetectionRecord, MonitorNews},
+};
+use std::{str::FromStr, sync::Arc};
+use storage_backend::{storage::Storage, storage_config::StorageConfig};
+use utils::{clear_output, generate_random_string};
+mod utils;
+
+// The toggle is global to the process, so every step runs in a single test
+
+/// Test that contexts are redacted in the Debug output and the exports unless revealed:
+/// 1. By default, Debug keeps the first 8 bytes of a context and a hash of it
+/// 2. Once revealed, Debug shows the full contexts
+/// 3. An archived detection keeps its full context, its Debug and the CSV export show the same
+///    redacted context, or the full one once revealed
+#[test]
+fn test_context_redaction() -> Result<(), anyhow::Error> {
+    let tx_id = Txid::from_str(&format!("{:064x}", 1))?;
+    let new_tx_id = Txid::from_str(&format!("{:064x}", 2))?;
+    let context = "payroll-2024/acct-991";
+
+    let monitor = TypesToMonitorStore::Transaction(tx_id, context.into(), Some(6), None);
+    let news = MonitorNews::MonitorReplaced {
+        old: tx_id,
+        new: new_tx_id,
+        context: context.into(),
+    };
+    let short = AckMonitorNews::OpReturnTransaction(tx_id, "abc".into());
+    let multibyte = AckMonitorNews::OpReturnTransaction(tx_id, "ñññññ".into());
+
+    // 1. Redacted
+    redaction::reveal_contexts(false);
+    assert_eq!(
+        format!("{monitor:?}"),
+        format!("Transaction({tx_id:?}, \"payroll-…#e7d80f23\", Some(6), None)")
+    );
+    assert_eq!(
+        format!("{news:?}"),
+        format!(
+            "MonitorReplaced {{ old: {tx_id:?}, new: {new_tx_id:?}, context: \"payroll-…#e7d80f23\" }}"
+        )
+    );
+    assert_eq!(
+        format!("{short:?}"),
+        format!("OpReturnTransaction({tx_id:?}, \"abc#ba7816bf\")")
+    );
+    assert_eq!(
+        format!("{multibyte:?}"),
+        format!("OpReturnTransaction({tx_id:?}, \"ññññ…#e3807fe5\")")
+    );
+
+    // 2. Revealed
+    redaction::reveal_contexts(true);
+    assert_eq!(
+        format!("{monitor:?}"),
+        format!("Transaction({tx_id:?}, \"{context}\", Some(6), None)")
+    );
+    assert_eq!(
+        format!("{news:?}"),
+        format!("MonitorReplaced {{ old: {tx_id:?}, new: {new_tx_id:?}, context: \"{context}\" }}")
+    );
+    assert_eq!(
+        format!("{short:?}"),
+        format!("OpReturnTransaction({tx_id:?}, \"abc\")")
+    );
+
+    // 3. Archive and export
+    let path = format!("test_outputs/{}", generate_random_string());
+    let config = StorageConfig::new(path, None);
+    let storage = Arc::new(Storage::new(&config)?);
+    let store = MonitorStore::new(storage)?;
+
+    let block_hash = BlockHash::from_str(&format!("{:064x}", 0))?;
+    store.archive_detection(DetectionRecord {
+        kind: "transaction".to_string(),
+        tx_id,
+        outpoint: None,
+        amount: None,
+        context: context.into(),
+        height: 0,
+        block_hash,
+    })?;
+
+    for (reveal, shown) in [(false, "payroll-…#e7d80f23"), (true, context)] {
+        redaction::reveal_contexts(reveal);
+
+        let records = store.get_archived_detections(0, 0)?;
+        assert_eq!(records.len(), 1);
+        assert_eq!(records[0].context, context);
+        assert!(format!("{:?}", records[0]).contains(&format!("context: \"{shown}\"")));
+
+        let mut csv = Vec::new();
+        assert_eq!(export_detections_csv(&store, 0, 0, 0, &mut csv)?, 1);
+        assert!(String::from_utf8(csv)?
+            .contains(&format!("0,transaction,{tx_id},,,{shown},{block_hash}\r\n")));
+    }
+
+    clear_output();
+
+    Ok(())
+}
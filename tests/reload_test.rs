@@ -0,0 +1,90 @@
+use bitcoin_indexer::indexer::MockIndexerApi;
+use bitvmx_transaction_monitor::{
+    config::{MonitorConfig, MonitorSettings, MonitorSettingsConfig},
+    errors::MonitorError,
+    monitor::Monitor,
+    store::{MonitorStore, MonitorStoreApi},
+};
+use serde_json::json;
+use std::sync::Arc;
+use storage_backend::{storage::Storage, storage_config::StorageConfig};
+use utils::{clear_output, generate_random_string};
+mod utils;
+
+fn config(storage_path: &str, settings: serde_json::Value) -> Result<MonitorConfig, anyhow::Error> {
+    Ok(serde_json::from_value(json!({
+        "storage": { "path": storage_path },
+        "bitcoin": {
+            "network": "regtest",
+            "url": "http://127.0.0.1:18443",
+            "username": "foo",
+            "password": "rpcpassword",
+            "wallet": "test_wallet"
+        },
+        "settings": settings,
+        "log_level": "info",
+    }))?)
+}
+
+/// Test that a running monitor reloads its settings when a reload is requested through the store:
+/// 1. Another process requests a reload, it is taken once
+/// 2. Reload a configuration changing a runtime setting, the indexer settings and the storage
+///    path, only the runtime setting is applied and the others are reported as requiring a restart
+/// 3. A reloaded configuration with invalid settings is rejected and the current ones are kept
+#[test]
+fn test_reload_requested_through_store() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let storage = Arc::new(Storage::new(&StorageConfig::new(path.clone(), None))?);
+    let store = MonitorStore::new(storage.clone())?;
+
+    let current = config(
+        &path,
+        json!({ "confirmation_threshold": 6, "indexer_settings": { "checkpoint_height": 10 } }),
+    )?;
+    let mut monitor = Monitor::new(
+        MockIndexerApi::new(),
+        store,
+        MonitorSettings::from(current.settings.clone().unwrap_or_default()),
+    )?;
+
+    // 1. The reload key is written by the `reload` subcommand
+    assert!(!monitor.take_reload_request()?);
+    MonitorStore::new(storage)?.request_reload()?;
+    assert!(monitor.take_reload_request()?);
+    assert!(!monitor.take_reload_request()?);
+
+    // 2. Only the runtime settings are applied
+    let reloaded = config(
+        "another_path",
+        json!({ "confirmation_threshold": 3, "indexer_settings": { "checkpoint_height": 20 } }),
+    )?;
+    let update = monitor.reload_config(&current, &reloaded)?;
+    assert_eq!(update.applied, vec!["confirmation_threshold"]);
+    assert_eq!(
+        update.requires_restart,
+        vec!["indexer_settings", "storage.path"]
+    );
+    assert_eq!(monitor.settings.confirmation_threshold, 3);
+    assert_eq!(
+        format!("{:?}", monitor.settings.indexer_settings),
+        format!(
+            "{:?}",
+            MonitorSettings::from(current.settings.clone().unwrap_or_default()).indexer_settings
+        )
+    );
+
+    // 3. Invalid settings
+    let invalid = MonitorSettings::from(MonitorSettingsConfig {
+        confirmation_threshold: Some(0),
+        ..MonitorSettingsConfig::default()
+    });
+    assert!(matches!(
+        monitor.update_settings(invalid),
+        Err(MonitorError::InvalidSettings(_))
+    ));
+    assert_eq!(monitor.settings.confirmation_threshold, 3);
+
+    clear_output();
+
+    Ok(())
+}
@@ -0,0 +1,349 @@
+use bitcoin::{absolute::LockTime, BlockHash, Transaction, Txid};
+use bitcoin_indexer::{
+    indexer::MockIndexerApi,
+    types::{FullBlock, TransactionInfo},
+};
+use bitvmx_transaction_monitor::{
+    api::v1::{
+        Ack, AckRequest, MonitorSpec, MonitorsResponse, NewsResponse, OkResponse, RegisterRequest,
+        TxStatusResponse,
+    },
+    config::{MonitorSettings, MonitorSettingsConfig},
+    errors::MonitorError,
+    monitor::Monitor,
+    rpc::{
+        MonitorCancelParams, MonitorListParams, RpcCall, RpcError, RpcRequest, RpcResponse,
+        RpcServer, TxStatusParams, INVALID_PARAMS, INVALID_REQUEST, METHOD_NOT_FOUND,
+        MONITOR_ERROR, PARSE_ERROR,
+    },
+    store::MonitorStore,
+};
+use serde_json::{json, Value};
+use std::{
+    io::{Read, Write},
+    net::{SocketAddr, TcpStream},
+    str::FromStr,
+    sync::Arc,
+    thread,
+    time::Duration,
+};
+use storage_backend::{storage::Storage, storage_config::StorageConfig};
+use utils::{clear_output, generate_random_string};
+mod utils;
+
+/// A monitor at block 200, which holds the returned transaction.
+fn create_monitor() -> Result<(Monitor<MockIndexerApi, MonitorStore>, Txid), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let storage = Arc::new(Storage::new(&StorageConfig::new(path, None))?);
+    let store = MonitorStore::new(storage)?;
+
+    let block_200 = FullBlock {
+        height: 200,
+        hash: BlockHash::from_str(
+            "0000000000000000000000000000000000000000000000000000000000000011",
+        )?,
+        prev_hash: BlockHash::from_str(
+            "0000000000000000000000000000000000000000000000000000000000000001",
+        )?,
+        txs: vec![],
+        orphan: false,
+        estimated_fee_rate: 0,
+    };
+    let tx = Transaction {
+        version: bitcoin::transaction::Version::TWO,
+        lock_time: LockTime::from_time(1653195600).unwrap(),
+        input: vec![],
+        output: vec![],
+    };
+    let tx_id = tx.compute_txid();
+
+    let mut mock_indexer = MockIndexerApi::new();
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let best_block = block_200.clone();
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(best_block.clone())));
+    let block_by_height = block_200.clone();
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |_| Ok(Some(block_by_height.clone())));
+    mock_indexer.expect_get_tx().returning(move |id| {
+        Ok((*id == tx_id).then(|| TransactionInfo {
+            tx: tx.clone(),
+            block_info: block_200.clone(),
+            confirmations: 1,
+        }))
+    });
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+
+    Ok((monitor, tx_id))
+}
+
+/// Posts `body` from another thread, applying the commands it queues until it is answered like
+/// the run loop does. Returns the HTTP status and the body of the answer.
+fn post(
+    monitor: &mut Monitor<MockIndexerApi, MonitorStore>,
+    addr: SocketAddr,
+    method: &'static str,
+    body: String,
+) -> (u16, String) {
+    let client = thread::spawn(move || {
+        let mut stream = TcpStream::connect(addr).unwrap();
+        write!(
+            stream,
+            "{method} / HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+
+        let mut response = String::new();
+        stream.read_to_string(&mut response).unwrap();
+        let (head, body) = response.split_once("\r\n\r\n").unwrap();
+        let status = head.split_whitespace().nth(1).unwrap().parse().unwrap();
+
+        (status, body.to_string())
+    });
+
+    while !client.is_finished() {
+        monitor.wait_for_commands(Duration::from_millis(1));
+    }
+
+    client.join().expect("client thread")
+}
+
+/// Calls a method and returns its response.
+fn call(
+    monitor: &mut Monitor<MockIndexerApi, MonitorStore>,
+    addr: SocketAddr,
+    id: u64,
+    call: RpcCall,
+) -> Result<RpcResponse, anyhow::Error> {
+    let request = serde_json::to_string(&call.into_request(id)?)?;
+    let (status, body) = post(monitor, addr, "POST", request);
+    assert_eq!(status, 200);
+
+    let response: RpcResponse = serde_json::from_str(&body)?;
+    assert_eq!(response.jsonrpc, "2.0");
+    assert_eq!(response.id, json!(id));
+
+    Ok(response)
+}
+
+/// Test that the calls convert to requests and back, and that the errors carry their codes:
+/// 1. Every call converts to a request and back to the same call
+/// 2. Unknown methods and parameters not matching the method are rejected
+/// 3. Monitor errors carry their error response as data
+#[test]
+fn test_rpc_types() -> Result<(), anyhow::Error> {
+    let tx_id = Txid::from_str(&format!("{:064x}", 1))?;
+
+    // 1. Round trip
+    let calls = vec![
+        RpcCall::MonitorAdd(RegisterRequest {
+            monitor: MonitorSpec::NewBlock {},
+            metadata: None,
+        }),
+        RpcCall::MonitorCancel(MonitorCancelParams {
+            monitor: MonitorSpec::NewBlock {},
+        }),
+        RpcCall::MonitorList(MonitorListParams {
+            include_inactive: true,
+        }),
+        RpcCall::NewsGet,
+        RpcCall::NewsAck(AckRequest {
+            news: Ack::NewBlock { hash: None },
+        }),
+        RpcCall::TxStatus(TxStatusParams { txid: tx_id }),
+    ];
+    for (id, call) in calls.into_iter().enumerate() {
+        let request = call.clone().into_request(id)?;
+        let json = serde_json::to_value(&request)?;
+        assert_eq!(json["jsonrpc"], "2.0");
+        assert_eq!(json["method"], call.method());
+        assert_eq!(json["id"], json!(id));
+
+        let request: RpcRequest = serde_json::from_value(json)?;
+        assert_eq!(RpcCall::try_from(&request)?, call);
+    }
+
+    // Parameters with defaults may be left out
+    let request: RpcRequest =
+        serde_json::from_value(json!({"jsonrpc": "2.0", "method": "monitor_list", "id": 1}))?;
+    assert_eq!(
+        RpcCall::try_from(&request)?,
+        RpcCall::MonitorList(MonitorListParams::default())
+    );
+
+    // 2. Rejected requests
+    let request: RpcRequest =
+        serde_json::from_value(json!({"jsonrpc": "2.0", "method": "monitor_drop", "id": 1}))?;
+    assert_eq!(
+        RpcCall::try_from(&request).unwrap_err().code,
+        METHOD_NOT_FOUND
+    );
+
+    for (method, params) in [
+        ("tx_status", json!({})),
+        ("tx_status", json!([tx_id])),
+        ("news_get", json!({"limit": 1})),
+        ("monitor_add", json!({"monitor": {"kind": "unknown"}})),
+    ] {
+        let request = RpcRequest {
+            jsonrpc: "2.0".to_string(),
+            method: method.to_string(),
+            params,
+            id: Some(json!(1)),
+        };
+        assert_eq!(
+            RpcCall::try_from(&request).unwrap_err().code,
+            INVALID_PARAMS
+        );
+    }
+
+    // 3. Monitor errors
+    let error = RpcError::from(MonitorError::HeldByLowWorkReorg(199));
+    assert_eq!(error.code, MONITOR_ERROR);
+    let data = error.data.expect("error response");
+    assert_eq!(data.code, "held_by_low_work_reorg");
+    assert!(data.transient);
+
+    let response = RpcResponse::error(json!(1), RpcError::new(INVALID_REQUEST, "invalid"));
+    assert_eq!(
+        response.into_result::<OkResponse>().unwrap_err().code,
+        INVALID_REQUEST
+    );
+
+    Ok(())
+}
+
+/// Test that the server maps the methods onto the monitor:
+/// 1. monitor_add registers a transaction monitor and monitor_list lists it
+/// 2. After a tick news_get returns its news and tx_status the status of the transaction
+/// 3. news_ack acknowledges the news and monitor_cancel cancels the monitor
+/// 4. A batch is answered with the responses of its requests, notifications are not answered
+/// 5. Monitor errors, invalid JSON and other HTTP methods are answered with errors
+#[test]
+fn test_rpc_server() -> Result<(), anyhow::Error> {
+    let (mut monitor, tx_id) = create_monitor()?;
+    monitor.tick()?;
+
+    let server = RpcServer::start("127.0.0.1:0", monitor.handle())?;
+    let addr = server.local_addr();
+    let spec = MonitorSpec::Transactions {
+        txids: vec![tx_id],
+        context: "ctx".to_string(),
+        trigger: None,
+        expires_at_height: None,
+    };
+
+    // 1. Registration
+    let response = call(
+        &mut monitor,
+        addr,
+        1,
+        RpcCall::MonitorAdd(RegisterRequest {
+            monitor: spec.clone(),
+            metadata: None,
+        }),
+    )?;
+    assert_eq!(response.into_result::<OkResponse>()?, OkResponse::default());
+
+    let response = call(
+        &mut monitor,
+        addr,
+        2,
+        RpcCall::MonitorList(MonitorListParams::default()),
+    )?;
+    let list: MonitorsResponse = response.into_result()?;
+    assert_eq!(list.monitors.len(), 1);
+    assert_eq!(list.monitors[0].monitor, spec);
+
+    // 2. News and status
+    monitor.tick()?;
+    let news: NewsResponse = call(&mut monitor, addr, 3, RpcCall::NewsGet)?.into_result()?;
+    assert_eq!(news.news.len(), 1);
+
+    let response = call(
+        &mut monitor,
+        addr,
+        4,
+        RpcCall::TxStatus(TxStatusParams { txid: tx_id }),
+    )?;
+    let status: TxStatusResponse = response.into_result()?;
+    assert_eq!(status.status.txid, tx_id);
+
+    // 3. Ack and cancel
+    let ack = AckRequest {
+        news: Ack::Transaction {
+            txid: tx_id,
+            context: "ctx".to_string(),
+        },
+    };
+    call(&mut monitor, addr, 5, RpcCall::NewsAck(ack))?.into_result::<OkResponse>()?;
+    assert_eq!(monitor.news_count()?, 0);
+
+    let cancel = MonitorCancelParams {
+        monitor: spec.clone(),
+    };
+    call(&mut monitor, addr, 6, RpcCall::MonitorCancel(cancel))?.into_result::<OkResponse>()?;
+    assert!(monitor.get_monitors(false)?.is_empty());
+
+    // 4. Batch and notifications
+    let batch = json!([
+        {"jsonrpc": "2.0", "method": "news_get", "id": "a"},
+        {"jsonrpc": "2.0", "method": "monitor_list"},
+        {"jsonrpc": "2.0", "method": "monitor_drop", "id": "b"},
+    ]);
+    let (status, body) = post(&mut monitor, addr, "POST", batch.to_string());
+    assert_eq!(status, 200);
+    let responses: Vec<RpcResponse> = serde_json::from_str(&body)?;
+    assert_eq!(responses.len(), 2);
+    assert_eq!(responses[0].id, json!("a"));
+    assert!(responses[0].result.is_some());
+    assert_eq!(responses[1].id, json!("b"));
+    assert_eq!(responses[1].error.as_ref().unwrap().code, METHOD_NOT_FOUND);
+
+    let notification = json!({"jsonrpc": "2.0", "method": "news_get"});
+    let (status, body) = post(&mut monitor, addr, "POST", notification.to_string());
+    assert_eq!(status, 204);
+    assert!(body.is_empty());
+
+    // 5. Errors
+    let unknown = Txid::from_str(&format!("{:064x}", 1))?;
+    let response = call(
+        &mut monitor,
+        addr,
+        7,
+        RpcCall::TxStatus(TxStatusParams { txid: unknown }),
+    )?;
+    let error = response.into_result::<TxStatusResponse>().unwrap_err();
+    assert_eq!(error.code, MONITOR_ERROR);
+    assert_eq!(error.data.unwrap().code, "transaction_not_found");
+
+    let (status, body) = post(&mut monitor, addr, "POST", "{".to_string());
+    assert_eq!(status, 200);
+    let response: RpcResponse = serde_json::from_str(&body)?;
+    assert_eq!(response.id, Value::Null);
+    assert_eq!(response.error.unwrap().code, PARSE_ERROR);
+
+    let request = json!({"jsonrpc": "1.0", "method": "news_get", "id": 8});
+    let (_, body) = post(&mut monitor, addr, "POST", request.to_string());
+    let response: RpcResponse = serde_json::from_str(&body)?;
+    assert_eq!(response.id, json!(8));
+    assert_eq!(response.error.unwrap().code, INVALID_REQUEST);
+
+    let (status, _) = post(&mut monitor, addr, "GET", String::new());
+    assert_eq!(status, 405);
+
+    drop(server);
+    assert!(TcpStream::connect(addr).is_err());
+
+    clear_output();
+
+    Ok(())
+}
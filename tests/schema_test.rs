@@ -0,0 +1,476 @@
+use bitcoin::{BlockHash, ScriptBuf, Txid};
+use bitvmx_transaction_monitor::{
+    api::v1::{
+        self, Block, Deadline, Filter, Health, MonitorSpec, News, NewsItem, NewsResolution,
+        PacketRange, Pegin, Reorg, Target, TxState, TxStatus, ValueGate,
+    },
+    migrations::STORE_SCHEMA_VERSION,
+    schema::{describe_schema, FieldSchema, FieldType, SchemaDescription, VariantSchema},
+    types::MONITOR_EXPORT_VERSION,
+};
+use serde::Serialize;
+use serde_json::Value;
+use std::{collections::BTreeMap, str::FromStr};
+
+fn txid(n: u64) -> Txid {
+    Txid::from_str(&format!("{:064x}", n)).unwrap()
+}
+
+fn block_hash(n: u64) -> BlockHash {
+    BlockHash::from_str(&format!("{:064x}", n)).unwrap()
+}
+
+fn tx_status(block: Option<Block>) -> TxStatus {
+    TxStatus {
+        txid: txid(1),
+        status: TxState::Confirmed,
+        confirmations: 1,
+        block,
+        tx: "02000000".to_string(),
+    }
+}
+
+fn block() -> Option<Block> {
+    Some(Block {
+        height: 200,
+        hash: block_hash(200),
+        orphan: false,
+    })
+}
+
+/// The kind of every news variant. The match has no wildcard so a new variant doesn't build
+/// until it is added here, to `news_samples` and to the schema.
+fn news_kind(news: &News) -> &'static str {
+    match news {
+        News::Transaction { .. } => "transaction",
+        News::SpendingUtxo { .. } => "spending_utxo",
+        News::SpendingUtxoUnconfirmed { .. } => "spending_utxo_unconfirmed",
+        News::MinorSpend { .. } => "minor_spend",
+        News::RskPegin { .. } => "rsk_pegin",
+        News::ScriptPubKey { .. } => "script_pubkey",
+        News::OpReturn { .. } => "op_return",
+        News::NewBlock { .. } => "new_block",
+        News::OrphanedTransaction { .. } => "orphaned_transaction",
+        News::MonitorReplaced { .. } => "monitor_replaced",
+        News::ReplacedTransactionConfirmed { .. } => "replaced_transaction_confirmed",
+        News::SequenceEpochChanged { .. } => "sequence_epoch_changed",
+        News::Reorg { .. } => "reorg",
+        News::LowWorkReorg { .. } => "low_work_reorg",
+        News::AlternativeResolved { .. } => "alternative_resolved",
+        News::MonitorExpired { .. } => "monitor_expired",
+        News::HealthStateChanged { .. } => "health_state_changed",
+        News::ManuallyResolved { .. } => "manually_resolved",
+        News::Composite { .. } => "composite",
+    }
+}
+
+fn monitor_kind(monitor: &MonitorSpec) -> &'static str {
+    match monitor {
+        MonitorSpec::Transactions { .. } => "transactions",
+        MonitorSpec::SpendingUtxo { .. } => "spending_utxo",
+        MonitorSpec::RskPegin { .. } => "rsk_pegin",
+        MonitorSpec::NewBlock {} => "new_block",
+        MonitorSpec::ScriptPubKey { .. } => "script_pubkey",
+        MonitorSpec::OpReturnPrefix { .. } => "op_return_prefix",
+        MonitorSpec::Alternatives { .. } => "alternatives",
+    }
+}
+
+fn target_kind(target: &Target) -> &'static str {
+    match target {
+        Target::Transaction { .. } => "transaction",
+        Target::SpendingUtxo { .. } => "spending_utxo",
+        Target::RskPegin {} => "rsk_pegin",
+        Target::NewBlock {} => "new_block",
+        Target::ScriptPubKey { .. } => "script_pubkey",
+        Target::OpReturnPrefix { .. } => "op_return_prefix",
+    }
+}
+
+fn news_samples() -> Vec<News> {
+    vec![
+        News::Transaction {
+            txid: txid(1),
+            context: "ctx".to_string(),
+            status: tx_status(block()),
+        },
+        News::SpendingUtxo {
+            txid: txid(1),
+            vout: 0,
+            context: "ctx".to_string(),
+            spender: tx_status(block()),
+        },
+        News::SpendingUtxoUnconfirmed {
+            txid: txid(1),
+            vout: 0,
+            context: "ctx".to_string(),
+            spender_txid: txid(2),
+        },
+        News::MinorSpend {
+            txid: txid(1),
+            vout: 0,
+            context: "ctx".to_string(),
+            spender: tx_status(None),
+        },
+        News::RskPegin {
+            txid: txid(1),
+            status: tx_status(block()),
+            pegin: Some(Pegin {
+                packet_number: 7,
+                rsk_address: "00".repeat(20),
+                reimbursement_key: "11".repeat(32),
+                amount_sat: 100_000,
+            }),
+        },
+        News::ScriptPubKey {
+            txid: txid(1),
+            vout: 1,
+            context: "ctx".to_string(),
+            status: tx_status(block()),
+        },
+        News::OpReturn {
+            txid: txid(1),
+            data: "6a".to_string(),
+            context: "ctx".to_string(),
+        },
+        News::NewBlock {
+            height: 200,
+            hash: block_hash(200),
+        },
+        News::OrphanedTransaction {
+            txid: txid(1),
+            context: "ctx".to_string(),
+            status: tx_status(block()),
+        },
+        News::MonitorReplaced {
+            old_txid: txid(1),
+            new_txid: txid(2),
+            context: "ctx".to_string(),
+        },
+        News::ReplacedTransactionConfirmed {
+            old_txid: txid(1),
+            new_txid: txid(2),
+            context: "ctx".to_string(),
+        },
+        News::SequenceEpochChanged {
+            old_epoch: 1,
+            new_epoch: 2,
+        },
+        News::Reorg {
+            old_tip: block_hash(200),
+            new_tip: block_hash(201),
+            summary: Reorg {
+                moved_transactions: 1,
+                disappeared_spends: 0,
+                changed_spends: 0,
+                invalidated_pegins: 0,
+                reopened_news: 1,
+            },
+        },
+        News::LowWorkReorg {
+            height: 199,
+            old_work: "02".to_string(),
+            new_work: "01".to_string(),
+        },
+        News::AlternativeResolved {
+            winner: txid(1),
+            losers: vec![txid(2)],
+            context: "ctx".to_string(),
+        },
+        News::MonitorExpired {
+            txid: txid(1),
+            vout: None,
+            context: "ctx".to_string(),
+            height: 300,
+        },
+        News::HealthStateChanged {
+            state: Health::Degraded,
+            score: 40,
+        },
+        News::ManuallyResolved {
+            monitor: Target::Transaction {
+                txid: txid(1),
+                context: "ctx".to_string(),
+            },
+            note: "paid out of band".to_string(),
+        },
+        News::Composite {
+            id: "rule".to_string(),
+        },
+    ]
+}
+
+fn monitor_samples() -> Vec<MonitorSpec> {
+    vec![
+        MonitorSpec::Transactions {
+            txids: vec![txid(1)],
+            context: "ctx".to_string(),
+            trigger: Some(6),
+            expires_at_height: Some(300),
+        },
+        MonitorSpec::SpendingUtxo {
+            txid: txid(1),
+            vout: 0,
+            context: "ctx".to_string(),
+            trigger: None,
+            deadline: Some(Deadline {
+                height: 300,
+                mempool_watch_window: 6,
+            }),
+            value_gate: Some(ValueGate {
+                min_spend_value_sat: 1_000,
+                measure_monitored_output: true,
+                report_minor: false,
+            }),
+            expires_at_height: None,
+        },
+        MonitorSpec::RskPegin {
+            trigger: None,
+            committee: vec!["51".to_string()],
+            packet_range: Some(PacketRange { first: 1, last: 9 }),
+            min_amount_sat: Some(10_000),
+        },
+        MonitorSpec::NewBlock {},
+        MonitorSpec::ScriptPubKey {
+            script_pubkey: "51".to_string(),
+            context: "ctx".to_string(),
+            trigger: None,
+            filter: Some(Filter {
+                min_value_sat: None,
+                ignore_dust_below_relay: true,
+            }),
+        },
+        MonitorSpec::OpReturnPrefix {
+            prefix: "6a".to_string(),
+            context: "ctx".to_string(),
+        },
+        MonitorSpec::Alternatives {
+            txids: vec![txid(1), txid(2)],
+            context: "ctx".to_string(),
+        },
+    ]
+}
+
+fn target_samples() -> Vec<Target> {
+    vec![
+        Target::Transaction {
+            txid: txid(1),
+            context: "ctx".to_string(),
+        },
+        Target::SpendingUtxo {
+            txid: txid(1),
+            vout: 0,
+            context: "ctx".to_string(),
+        },
+        Target::RskPegin {},
+        Target::NewBlock {},
+        Target::ScriptPubKey {
+            script_pubkey: ScriptBuf::from_hex("51").unwrap(),
+            context: "ctx".to_string(),
+        },
+        Target::OpReturnPrefix {
+            prefix: vec![0x6a],
+            context: "ctx".to_string(),
+        },
+    ]
+}
+
+/// Asserts the JSON `value` of a field has the type `ty` of the schema.
+fn assert_type(schema: &SchemaDescription, ty: &FieldType, value: &Value, path: &str) {
+    match ty {
+        FieldType::String | FieldType::Hex | FieldType::Txid | FieldType::BlockHash => {
+            assert!(value.is_string(), "{path} is not a string: {value}")
+        }
+        FieldType::U32 | FieldType::U64 => assert!(value.is_u64(), "{path} is not a number"),
+        FieldType::Bool => assert!(value.is_boolean(), "{path} is not a bool"),
+        FieldType::StringMap => assert!(
+            value
+                .as_object()
+                .is_some_and(|map| map.values().all(Value::is_string)),
+            "{path} is not a map of strings"
+        ),
+        FieldType::Array(item) => {
+            let items = value
+                .as_array()
+                .unwrap_or_else(|| panic!("{path} is not an array"));
+            for (i, value) in items.iter().enumerate() {
+                assert_type(schema, item, value, &format!("{path}[{i}]"));
+            }
+        }
+        FieldType::Object(name) => {
+            let object = schema
+                .object(name)
+                .unwrap_or_else(|| panic!("{path}: object {name} is not in the schema"));
+            assert_fields(schema, object.fields, value, path);
+        }
+        FieldType::Enum(name) => {
+            let enumeration = schema
+                .enumeration(name)
+                .unwrap_or_else(|| panic!("{path}: enum {name} is not in the schema"));
+            assert!(
+                enumeration
+                    .values
+                    .contains(&value.as_str().unwrap_or_default()),
+                "{path}: {value} is not a value of {name}"
+            );
+        }
+        FieldType::News => assert_variant(schema, schema.news, value, path),
+        FieldType::Target => assert_variant(schema, schema.targets, value, path),
+    }
+}
+
+/// Asserts the JSON object `value` has exactly the `fields` of the schema, leaving the tag out.
+fn assert_fields(schema: &SchemaDescription, fields: &[FieldSchema], value: &Value, path: &str) {
+    let object = value
+        .as_object()
+        .unwrap_or_else(|| panic!("{path} is not an object"));
+
+    for key in object.keys().filter(|key| *key != "kind") {
+        assert!(
+            fields.iter().any(|field| field.name == key),
+            "{path}.{key} is not in the schema"
+        );
+    }
+
+    for field in fields {
+        let path = format!("{path}.{}", field.name);
+        match object.get(field.name) {
+            None | Some(Value::Null) => assert!(field.optional, "{path} is not optional"),
+            Some(value) => assert_type(schema, &field.ty, value, &path),
+        }
+    }
+}
+
+fn assert_variant(
+    schema: &SchemaDescription,
+    variants: &[VariantSchema],
+    value: &Value,
+    path: &str,
+) {
+    let kind = value["kind"].as_str().unwrap();
+    let variant = variants
+        .iter()
+        .find(|variant| variant.kind == kind)
+        .unwrap_or_else(|| panic!("{path}: kind {kind} is not in the schema"));
+    assert_fields(schema, variant.fields, value, &format!("{path}({kind})"));
+}
+
+/// Asserts every sample serializes as described by `variants`, and the schema has no variant
+/// without a sample.
+fn assert_in_sync<T: Serialize>(
+    schema: &SchemaDescription,
+    variants: &[VariantSchema],
+    samples: &[T],
+    kind_of: fn(&T) -> &'static str,
+) {
+    let mut kinds: Vec<&str> = samples.iter().map(kind_of).collect();
+    kinds.sort();
+    let mut described: Vec<&str> = variants.iter().map(|variant| variant.kind).collect();
+    described.sort();
+    assert_eq!(kinds, described);
+
+    for sample in samples {
+        let value = serde_json::to_value(sample).unwrap();
+        assert_eq!(value["kind"], kind_of(sample));
+        assert_variant(schema, variants, &value, "sample");
+    }
+}
+
+/// Test that the schema describes every variant of the JSON API as it is serialized:
+/// 1. Assert the versions are the ones of this build
+/// 2. Assert the monitors, news and targets have one entry per variant, with the fields and
+///    types of a sample of each
+/// 3. Assert a news item, which nests a news, matches the news_item object
+/// 4. Assert the fields referencing objects and enums point to ones in the schema
+#[test]
+fn test_schema_in_sync_with_the_api() -> Result<(), anyhow::Error> {
+    let schema = describe_schema();
+
+    // 1. Versions
+    assert_eq!(schema.api_version, v1::VERSION);
+    assert_eq!(schema.store_schema_version, STORE_SCHEMA_VERSION);
+    assert_eq!(schema.monitor_export_version, MONITOR_EXPORT_VERSION);
+
+    // 2. One entry per variant
+    assert_in_sync(&schema, schema.monitors, &monitor_samples(), monitor_kind);
+    assert_in_sync(&schema, schema.news, &news_samples(), news_kind);
+    assert_in_sync(&schema, schema.targets, &target_samples(), target_kind);
+
+    // 3. News item
+    let item = NewsItem {
+        epoch: 1,
+        sequence: 10,
+        resolution: NewsResolution::SnapshotWithHeightAdjustment,
+        metadata: BTreeMap::from([("source".to_string(), "test".to_string())]),
+        orphan_disputed: true,
+        news: news_samples().remove(0),
+    };
+    let news_item = schema.object("news_item").unwrap();
+    assert_fields(
+        &schema,
+        news_item.fields,
+        &serde_json::to_value(&item)?,
+        "news_item",
+    );
+
+    // 4. References
+    fn referenced(ty: &FieldType) -> Option<&FieldType> {
+        match ty {
+            FieldType::Array(item) => referenced(item),
+            FieldType::Object(_) | FieldType::Enum(_) => Some(ty),
+            _ => None,
+        }
+    }
+    let fields = schema
+        .monitors
+        .iter()
+        .chain(schema.news)
+        .chain(schema.targets)
+        .flat_map(|variant| variant.fields)
+        .chain(schema.objects.iter().flat_map(|object| object.fields));
+    for field in fields {
+        match referenced(&field.ty) {
+            Some(FieldType::Object(name)) => assert!(schema.object(name).is_some(), "{name}"),
+            Some(FieldType::Enum(name)) => assert!(schema.enumeration(name).is_some(), "{name}"),
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Test that the schema is rendered as the JSON read by the code generators:
+/// 1. Assert the types are snake_case, with the referenced name for objects and arrays
+/// 2. Assert the optional fields are flagged
+#[test]
+fn test_schema_json() -> Result<(), anyhow::Error> {
+    let json = serde_json::to_value(describe_schema())?;
+
+    // 1. Types
+    let transaction = &json["news"][0];
+    assert_eq!(transaction["kind"], "transaction");
+    assert_eq!(
+        transaction["fields"][0],
+        serde_json::json!({ "name": "txid", "type": "txid", "optional": false })
+    );
+    assert_eq!(
+        transaction["fields"][2]["type"],
+        serde_json::json!({ "object": "tx_status" })
+    );
+
+    let transactions = &json["monitors"][0];
+    assert_eq!(transactions["kind"], "transactions");
+    assert_eq!(
+        transactions["fields"][0]["type"],
+        serde_json::json!({ "array": "txid" })
+    );
+
+    // 2. Optional fields
+    assert_eq!(
+        transactions["fields"][2],
+        serde_json::json!({ "name": "trigger", "type": "u32", "optional": true })
+    );
+    assert_eq!(json["api_version"], v1::VERSION);
+
+    Ok(())
+}
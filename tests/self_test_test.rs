@@ -0,0 +1,55 @@
+use bitcoin::Network;
+use bitvmx_bitcoin_rpc::rpc_config::RpcConfig;
+use bitvmx_transaction_monitor::self_test::{run_self_test, CheckOutcome};
+
+/// Test that the self-test refuses to run against a node that is not on regtest:
+/// 1. A configuration of a mainnet node
+/// 2. The node check fails before connecting, every other check is skipped
+/// 3. The report fails and prints a line per check
+#[test]
+fn test_self_test_requires_regtest() -> Result<(), anyhow::Error> {
+    // 1. Mainnet
+    let rpc_config = RpcConfig {
+        network: Network::Bitcoin,
+        url: "http://127.0.0.1:8332".to_string(),
+        username: "foo".to_string(),
+        password: "rpcpassword".to_string(),
+        wallet: "test_wallet".to_string(),
+    };
+
+    // 2. Checks
+    let report = run_self_test(&rpc_config, true);
+    let names: Vec<&str> = report.checks.iter().map(|check| check.name).collect();
+    assert_eq!(
+        names,
+        vec![
+            "node",
+            "sync",
+            "new_block",
+            "wallet",
+            "transaction",
+            "spend",
+            "pegin",
+            "reorg"
+        ]
+    );
+    assert!(matches!(
+        &report.checks[0].outcome,
+        CheckOutcome::Failed(reason) if reason.contains("only runs on regtest")
+    ));
+    assert!(
+        report.checks[1..]
+            .iter()
+            .all(|check| check.outcome
+                == CheckOutcome::Skipped("an earlier check failed".to_string()))
+    );
+
+    // 3. Report
+    assert!(!report.passed());
+    let printed = report.to_string();
+    assert_eq!(printed.lines().count(), 9);
+    assert!(printed.starts_with("FAIL node"));
+    assert!(printed.ends_with("0 passed, 1 failed, 7 skipped"));
+
+    Ok(())
+}
@@ -0,0 +1,106 @@
+use bitcoin::Txid;
+use bitcoin_indexer::indexer::MockIndexerApi;
+use bitvmx_transaction_monitor::{
+    config::{MonitorSettings, MonitorSettingsConfig},
+    monitor::Monitor,
+    shutdown::ShutdownToken,
+    store::MonitorStore,
+    types::TickProgress,
+    WatchTx,
+};
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+};
+use storage_backend::{storage::Storage, storage_config::StorageConfig};
+use utils::{block_at, clear_output, generate_random_string};
+mod utils;
+
+/// Test that a tick stops between blocks once a shutdown is requested:
+/// 1. The first tick processes the best block at 200 and completes
+/// 2. With the indexer at 205, a shutdown requested while block 202 is fetched lets the tick
+///    finish block 202 and return as stopped early at 202
+/// 3. A tick with the shutdown already requested processes no block
+/// 4. The next run processes the blocks left, 203 to 205
+#[test]
+fn test_tick_with_shutdown() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let storage = Arc::new(Storage::new(&StorageConfig::new(path, None))?);
+    let store = MonitorStore::new(storage)?;
+
+    let height = Arc::new(AtomicU32::new(200));
+    let shutdown = ShutdownToken::new();
+
+    let mut mock_indexer = MockIndexerApi::new();
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let best_height = height.clone();
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(block_at(best_height.load(Ordering::SeqCst)))));
+    let fetch_shutdown = shutdown.clone();
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |height| {
+            if height == 202 {
+                fetch_shutdown.request();
+            }
+            Ok(Some(block_at(height)))
+        });
+    mock_indexer.expect_get_tx().returning(|_| Ok(None));
+
+    let monitor = Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+    let tx_id = Txid::from_str(&format!("{:064x}", 1))?;
+    monitor.save_monitor(WatchTx::new(tx_id).context("ctx").build()?)?;
+
+    // 1. First tick
+    let progress = monitor.tick_with_shutdown(&shutdown)?;
+    assert_eq!(
+        progress,
+        TickProgress {
+            blocks_processed: 1,
+            monitor_height: 200,
+            stopped_early: false,
+        }
+    );
+
+    // 2. Stopped after block 202
+    height.store(205, Ordering::SeqCst);
+    let progress = monitor.tick_with_shutdown(&shutdown)?;
+    assert_eq!(
+        progress,
+        TickProgress {
+            blocks_processed: 2,
+            monitor_height: 202,
+            stopped_early: true,
+        }
+    );
+    assert_eq!(monitor.get_monitor_height()?, 202);
+
+    // 3. Already requested
+    let progress = monitor.tick_with_shutdown(&shutdown)?;
+    assert_eq!(progress.blocks_processed, 0);
+    assert!(progress.stopped_early);
+    assert_eq!(monitor.get_monitor_height()?, 202);
+
+    // 4. Next run
+    let progress = monitor.tick_with_shutdown(&ShutdownToken::new())?;
+    assert_eq!(
+        progress,
+        TickProgress {
+            blocks_processed: 3,
+            monitor_height: 205,
+            stopped_early: false,
+        }
+    );
+
+    clear_output();
+
+    Ok(())
+}
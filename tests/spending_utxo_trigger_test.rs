@@ -1,3 +1,5 @@
+#![cfg(feature = "docker-tests")]
+
 use anyhow::Result;
 use bitcoin::Amount;
 use bitcoind::{bitcoind::Bitcoind, config::BitcoindConfig};
@@ -6,7 +8,8 @@ use bitvmx_settings::settings;
 use bitvmx_transaction_monitor::{
     config::{MonitorConfig, MonitorSettingsConfig},
     monitor::{Monitor, MonitorApi},
-    types::{AckMonitorNews, MonitorNews, TypesToMonitor},
+    types::{AckMonitorNews, MonitorNews},
+    WatchOutpoint,
 };
 use std::rc::Rc;
 use storage_backend::{storage::Storage, storage_config::StorageConfig};
@@ -82,20 +85,20 @@ fn test_spending_utxo_confirmation_trigger() -> Result<(), anyhow::Error> {
     );
 
     // Monitor transaction1's UTXO with SpendingUTXOTransaction and confirmation_trigger Some(1)
-    monitor.monitor(TypesToMonitor::SpendingUTXOTransaction(
-        transaction1_txid,
-        transaction1_vout,
-        "context_1".to_string(),
-        Some(1),
-    ))?;
+    monitor.monitor(
+        WatchOutpoint::new(transaction1_txid, transaction1_vout)
+            .context("context_1")
+            .trigger_at(1)
+            .build()?,
+    )?;
 
     // Monitor the same transaction1's UTXO again with a different context
-    monitor.monitor(TypesToMonitor::SpendingUTXOTransaction(
-        transaction1_txid,
-        transaction1_vout,
-        "context_2".to_string(),
-        Some(1),
-    ))?;
+    monitor.monitor(
+        WatchOutpoint::new(transaction1_txid, transaction1_vout)
+            .context("context_2")
+            .trigger_at(1)
+            .build()?,
+    )?;
 
     // Send transaction1 to the network (fund_address already sent it, but we need to ensure it's in mempool)
     // fund_address already sent it, so we just need to mine a block to confirm it
@@ -225,12 +228,12 @@ fn test_spending_utxo_confirmation_trigger() -> Result<(), anyhow::Error> {
     monitor.ack_news(AckMonitorNews::SpendingUTXOTransaction(
         transaction1_txid,
         transaction1_vout,
-        "context_1".to_string(),
+        "context_1".into(),
     ))?;
     monitor.ack_news(AckMonitorNews::SpendingUTXOTransaction(
         transaction1_txid,
         transaction1_vout,
-        "context_2".to_string(),
+        "context_2".into(),
     ))?;
 
     // 11) Mine 10 more blocks, do 10 ticks
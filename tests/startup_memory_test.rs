@@ -0,0 +1,198 @@
+#![cfg(feature = "memory-tests")]
+use bitcoin::{hashes::Hash, BlockHash, Txid};
+use bitcoin_indexer::{indexer::MockIndexerApi, types::FullBlock};
+use bitvmx_transaction_monitor::{
+    config::{MonitorSettings, MonitorSettingsConfig},
+    monitor::Monitor,
+    store::MonitorStore,
+    types::MonitorKind,
+};
+use serde_json::json;
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    sync::{
+        atomic::{AtomicBool, AtomicIsize, Ordering},
+        Arc,
+    },
+};
+use storage_backend::{
+    storage::{KeyValueStore, Storage},
+    storage_config::StorageConfig,
+};
+use utils::{clear_output, generate_random_string};
+mod utils;
+
+const TRANSACTION_MONITORS: u32 = 60_000;
+const SPENDING_UTXO_MONITORS: u32 = 40_000;
+
+/// Most bytes the monitor may hold at once while starting and running its first tick, about twice
+/// what it takes. Loading the whole set at once took over 50 MB.
+const PEAK_BUDGET: isize = 24 * 1024 * 1024;
+
+static COUNTING: AtomicBool = AtomicBool::new(false);
+/// Bytes allocated minus the bytes freed while `COUNTING` is set.
+static LIVE: AtomicIsize = AtomicIsize::new(0);
+static PEAK: AtomicIsize = AtomicIsize::new(0);
+
+/// Tracks the bytes held by the test while `COUNTING` is set, and the most it ever held.
+struct CountingAllocator;
+
+impl CountingAllocator {
+    fn record(delta: isize) {
+        if COUNTING.load(Ordering::SeqCst) {
+            let live = LIVE.fetch_add(delta, Ordering::SeqCst) + delta;
+            PEAK.fetch_max(live, Ordering::SeqCst);
+        }
+    }
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        Self::record(layout.size() as isize);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        Self::record(-(layout.size() as isize));
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        Self::record(new_size as isize - layout.size() as isize);
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+fn tx_id(n: u32) -> Txid {
+    let mut bytes = [0u8; 32];
+    bytes[..4].copy_from_slice(&n.to_be_bytes());
+    Txid::from_byte_array(bytes)
+}
+
+/// Writes the per-item keys and indexes of the active Transaction and SpendingUTXO lists directly,
+/// registering this many monitors one by one would take too long.
+fn write_synthetic_store(storage: &Storage) -> Result<(), anyhow::Error> {
+    let registered = json!([{ "Registered": { "height": 199 } }]);
+    let mut tx_index = vec![];
+    let mut spending_index = vec![];
+
+    for n in 0..TRANSACTION_MONITORS {
+        let id = tx_id(n).to_string();
+        let monitor = json!({
+            "entries": [{
+                "confirmation_trigger": null,
+                "expires_at_height": null,
+                "extra_data": format!("context {n}"),
+                "history": registered,
+                "last_reported_block_hash": null,
+                "last_reported_confirmations": null,
+                "trigger_sent": false,
+            }],
+            "tx_id": id,
+        });
+        storage.set(format!("monitor/tx/active/{id}"), monitor, None)?;
+        tx_index.push(id);
+    }
+
+    for n in 0..SPENDING_UTXO_MONITORS {
+        let funding = tx_id(TRANSACTION_MONITORS + n);
+        let id = format!("{funding}:0");
+        let monitor = json!({
+            "entries": [{
+                "confirmation_trigger": null,
+                "deadline": null,
+                "extra_data": format!("context {n}"),
+                "history": registered,
+                "spender_tx_id": null,
+            }],
+            "tx_id": funding,
+            "vout": 0,
+        });
+        storage.set(
+            format!("monitor/spending/utxo/tx/active/{id}"),
+            monitor,
+            None,
+        )?;
+        spending_index.push(id);
+    }
+
+    storage.set("monitor/tx/index/active", tx_index, None)?;
+    storage.set(
+        "monitor/spending/utxo/tx/index/active",
+        spending_index,
+        None,
+    )?;
+    storage.set("monitor/all/pending_work", true, None)?;
+
+    Ok(())
+}
+
+/// Test that a monitor started on a store with 100k monitors stays under a memory budget:
+/// 1. Write 60k Transaction and 40k SpendingUTXO monitors straight to the store
+/// 2. Build the monitor and run its first tick while counting the bytes held
+/// 3. Assert the peak stays under `PEAK_BUDGET` and every monitor was read
+#[test]
+fn test_startup_memory_is_bounded() -> Result<(), anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let storage = Arc::new(Storage::new(&StorageConfig::new(path, None))?);
+    write_synthetic_store(&storage)?;
+
+    let block = FullBlock {
+        height: 200,
+        hash: BlockHash::from_byte_array([200; 32]),
+        prev_hash: BlockHash::from_byte_array([199; 32]),
+        txs: vec![],
+        orphan: false,
+        estimated_fee_rate: 0,
+    };
+    let mut mock_indexer = MockIndexerApi::new();
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    let best_block = block.clone();
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(best_block.clone())));
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(move |_| Ok(Some(block.clone())));
+    mock_indexer.expect_get_tx().returning(|_| Ok(None));
+
+    // 2. Start and tick
+    COUNTING.store(true, Ordering::SeqCst);
+    let monitor = Monitor::new(
+        mock_indexer,
+        MonitorStore::new(storage)?,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+    let ticked = monitor.tick();
+    COUNTING.store(false, Ordering::SeqCst);
+    ticked?;
+
+    // 3. Bounded
+    let peak = PEAK.load(Ordering::SeqCst);
+    assert!(
+        peak < PEAK_BUDGET,
+        "peak of {} bytes over the budget of {} bytes",
+        peak,
+        PEAK_BUDGET
+    );
+
+    assert_eq!(monitor.get_monitor_height()?, 200);
+    let mut monitors = 0;
+    for kind in [
+        MonitorKind::Transaction,
+        MonitorKind::SpendingUTXOTransaction,
+    ] {
+        monitor.for_each_monitor(kind, |_| {
+            monitors += 1;
+            Ok(())
+        })?;
+    }
+    assert_eq!(monitors, TRANSACTION_MONITORS + SPENDING_UTXO_MONITORS);
+
+    clear_output();
+
+    Ok(())
+}
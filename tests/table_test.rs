@@ -0,0 +1,79 @@
+use bitcoin::{BlockHash, Txid};
+use bitvmx_transaction_monitor::{
+    redaction::reveal_contexts,
+    store::{MonitorStore, MonitorStoreApi, MonitoredTypes},
+    table::{monitors_table, news_table},
+    WatchOutpoint, WatchTx,
+};
+use std::str::FromStr;
+use storage_backend::storage_config::StorageConfig;
+use utils::{clear_output, generate_random_string};
+mod utils;
+
+/// Test the tables printed by the store subcommands of the binary:
+/// 1. A store opened with `MonitorStore::open` keeps the monitors added once it is opened again
+/// 2. The monitors table has a row per monitor with its kind, target, context and state
+/// 3. The news table has a row per pending news with its sequence, kind, transaction, target
+///    and context, the contexts being redacted unless they are revealed
+/// 4. Empty lists print the header only
+#[test]
+fn test_tables() -> Result<(), anyhow::Error> {
+    reveal_contexts(true);
+    let config = StorageConfig::new(format!("test_outputs/{}", generate_random_string()), None);
+    let tx_id = Txid::from_str("0000000000000000000000000000000000000000000000000000000000000001")?;
+    let block_hash =
+        BlockHash::from_str("0000000000000000000000000000000000000000000000000000000000000002")?;
+
+    // 1. Reopened store
+    {
+        let store = MonitorStore::open(&config)?;
+        store.add_monitor(WatchTx::new(tx_id).context("payout-0001").build()?)?;
+        store.add_monitor(WatchOutpoint::new(tx_id, 1).build()?)?;
+    }
+    let store = MonitorStore::open(&config)?;
+
+    // 2. Monitors
+    let table = monitors_table(&store.get_monitor_infos(false)?);
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("KIND"));
+    assert!(lines.iter().any(|line| line.starts_with("Transaction ")
+        && line.contains(&tx_id.to_string())
+        && line.contains("payout-0001")
+        && line.contains("true")));
+    assert!(lines
+        .iter()
+        .any(|line| line.starts_with("SpendingUTXOTransaction")
+            && line.contains(&format!("{}:1", tx_id))));
+
+    // 3. News
+    store.update_news(
+        MonitoredTypes::Transaction(tx_id, "payout-0001".into()),
+        block_hash,
+    )?;
+    store.update_news(MonitoredTypes::NewBlock(block_hash, Some(2)), block_hash)?;
+
+    let table = news_table(&store.get_sequenced_news()?);
+    let lines: Vec<&str> = table.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("SEQUENCE"));
+    assert!(lines.iter().any(|line| line.contains(" transaction ")
+        && line.contains(&tx_id.to_string())
+        && line.ends_with("payout-0001")));
+    assert!(lines
+        .iter()
+        .any(|line| line.contains(" new_block ") && line.contains(&block_hash.to_string())));
+
+    reveal_contexts(false);
+    let table = news_table(&store.get_sequenced_news()?);
+    assert!(!table.contains("payout-0001"));
+    reveal_contexts(true);
+
+    // 4. Empty lists
+    assert_eq!(news_table(&[]).lines().count(), 1);
+    assert_eq!(monitors_table(&[]).lines().count(), 1);
+
+    clear_output();
+
+    Ok(())
+}
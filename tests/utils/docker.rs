@@ -0,0 +1,157 @@
+use anyhow::{anyhow, Result};
+use bitcoin::{Address, Transaction};
+use bitcoind::{bitcoind::Bitcoind, config::BitcoindConfig};
+use bitvmx_bitcoin_rpc::bitcoin_client::{BitcoinClient, BitcoinClientApi};
+use bitvmx_settings::settings;
+use bitvmx_transaction_monitor::{
+    config::{MonitorConfig, MonitorSettingsConfig},
+    monitor::Monitor,
+    types::{MonitorNews, MonitorType},
+};
+use std::{
+    rc::Rc,
+    sync::{Mutex, MutexGuard},
+};
+use storage_backend::{storage::Storage, storage_config::StorageConfig};
+use tracing::{info, warn};
+
+use super::generate_random_string;
+
+/// Ticks allowed for the monitor to reach the tip of the node before a test fails.
+const MAX_TICKS: u32 = 1_000;
+
+/// Every test starts its own bitcoind container on the port of the config, so they run one at a
+/// time even when the test harness runs them in parallel.
+static NODE: Mutex<()> = Mutex::new(());
+
+/// A regtest bitcoind running in docker with a monitor on a storage of its own. The container is
+/// stopped and the storage removed when it is dropped, also when the test panics.
+pub struct Regtest {
+    pub client: BitcoinClient,
+    pub wallet: Address,
+    /// Name of the wallet `wallet` belongs to, the one of the config
+    pub wallet_name: String,
+    pub monitor: MonitorType,
+    /// Dropped after the monitor, so the storage is closed before it is removed
+    _node: Node,
+}
+
+/// Container and storage directory of a [`Regtest`], built before the node is started so a
+/// failure while starting it or setting up the monitor stops the container and removes the
+/// directory too.
+struct Node {
+    bitcoind: Bitcoind,
+    path: String,
+    _lock: MutexGuard<'static, ()>,
+}
+
+impl Drop for Node {
+    fn drop(&mut self) {
+        if let Err(e) = self.bitcoind.stop() {
+            warn!("Failed to stop bitcoind: {:?}", e);
+        }
+        let _ = std::fs::remove_dir_all(&self.path);
+    }
+}
+
+impl Regtest {
+    /// Starts the node, mines `blocks` blocks to the wallet and waits for the monitor to sync them.
+    pub fn start(blocks: u64) -> Result<Self> {
+        // A test that panicked left the lock poisoned, its container is already stopped
+        let lock = NODE.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let _ = tracing_subscriber::fmt()
+            .with_max_level(tracing::Level::INFO)
+            .try_init();
+
+        let config = settings::load_config_file::<MonitorConfig>(Some(
+            "config/monitor_config.yaml".to_string(),
+        ))?;
+
+        let node = Node {
+            bitcoind: Bitcoind::new(BitcoindConfig::default(), config.bitcoin.clone(), None),
+            path: format!("test_outputs/{}", generate_random_string()),
+            _lock: lock,
+        };
+        node.bitcoind.start()?;
+
+        let storage = Rc::new(Storage::new(&StorageConfig::new(node.path.clone(), None))?);
+
+        let client = BitcoinClient::new_from_config(&config.bitcoin)?;
+        let wallet = client.init_wallet(&config.bitcoin.wallet)?;
+        let monitor = Monitor::new_with_paths(
+            &config.bitcoin,
+            storage,
+            Some(MonitorSettingsConfig::default()),
+        )?;
+
+        let regtest = Self {
+            client,
+            wallet,
+            wallet_name: config.bitcoin.wallet.clone(),
+            monitor,
+            _node: node,
+        };
+        regtest.mine(blocks)?;
+
+        Ok(regtest)
+    }
+
+    /// Mines exactly `blocks` blocks to the wallet and waits for the monitor to process them.
+    pub fn mine(&self, blocks: u64) -> Result<()> {
+        info!("Mining {} blocks to wallet", blocks);
+        self.client.mine_blocks_to_address(blocks, &self.wallet)?;
+        self.sync()
+    }
+
+    /// Ticks until the indexer is ready and the monitor has processed the tip of the node.
+    pub fn sync(&self) -> Result<()> {
+        let tip = self.tip()?;
+
+        for _ in 0..MAX_TICKS {
+            self.monitor.tick()?;
+            if self.monitor.is_ready()? && self.monitor.get_monitor_height()? >= tip {
+                return Ok(());
+            }
+        }
+
+        Err(anyhow!(
+            "the monitor didn't reach the tip {} in {} ticks",
+            tip,
+            MAX_TICKS
+        ))
+    }
+
+    pub fn tip(&self) -> Result<u32> {
+        Ok(self.client.get_best_block()?)
+    }
+
+    pub fn coinbase_at(&self, height: u32) -> Result<Transaction> {
+        let block = self
+            .client
+            .get_block_by_height(&height)?
+            .ok_or_else(|| anyhow!("no block at height {}", height))?;
+
+        Ok(block.txs[0].clone())
+    }
+
+    /// Fingerprints of the pending news, sorted.
+    pub fn pending(&self) -> Result<Vec<String>> {
+        let mut pending: Vec<String> = self.monitor.get_news()?.iter().map(fingerprint).collect();
+        pending.sort();
+
+        Ok(pending)
+    }
+}
+
+/// Identifies a news by its kind, its subject and its context, the same news being sent again
+/// keeps its fingerprint.
+pub fn fingerprint(news: &MonitorNews) -> String {
+    match news {
+        MonitorNews::Transaction(tx_id, _, context) => format!("transaction/{tx_id}/{context}"),
+        MonitorNews::SpendingUTXOTransaction(tx_id, vout, _, context) => {
+            format!("spending_utxo/{tx_id}:{vout}/{context}")
+        }
+        MonitorNews::NewBlock(height, hash) => format!("new_block/{height}/{hash}"),
+        other => format!("{other:?}"),
+    }
+}
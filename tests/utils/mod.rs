@@ -1,11 +1,24 @@
+#![allow(dead_code)]
+
 use anyhow::Result;
-use bitcoin::{Amount, Transaction, Txid};
+use bitcoin::{Amount, BlockHash, Transaction, Txid};
+use bitcoin_indexer::{indexer::MockIndexerApi, types::FullBlock};
 use bitcoincore_rpc::RpcApi;
 use bitvmx_bitcoin_rpc::bitcoin_client::BitcoinClient;
+use std::{
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU32, Ordering},
+        Arc,
+    },
+};
 
 use bitcoin::key::rand;
 use rand::Rng;
 
+#[cfg(feature = "docker-tests")]
+pub mod docker;
+
 pub fn generate_random_string() -> String {
     let mut rng = rand::thread_rng();
     (0..10).map(|_| rng.gen_range('a'..='z')).collect()
@@ -15,6 +28,37 @@ pub fn clear_output() {
     let _ = std::fs::remove_dir_all("test_outputs");
 }
 
+/// Empty block at `height` on a single chain, its hash is the height in hex and its parent is the
+/// block at `height - 1`.
+pub fn block_at(height: u32) -> FullBlock {
+    FullBlock {
+        height,
+        hash: BlockHash::from_str(&format!("{:064x}", height)).unwrap(),
+        prev_hash: BlockHash::from_str(&format!("{:064x}", height.saturating_sub(1))).unwrap(),
+        txs: vec![],
+        orphan: false,
+        estimated_fee_rate: 0,
+    }
+}
+
+/// Indexer whose best block follows `height` on the `block_at` chain. Only the block lookups and
+/// `tick` are set, the tests add the expectations they need on top.
+pub fn scripted_indexer(height: Arc<AtomicU32>) -> MockIndexerApi {
+    let mut mock_indexer = MockIndexerApi::new();
+    mock_indexer
+        .expect_get_best_block()
+        .returning(move || Ok(Some(block_at(height.load(Ordering::SeqCst)))));
+    mock_indexer
+        .expect_get_block_by_height()
+        .returning(|height| Ok(Some(block_at(height))));
+    mock_indexer.expect_get_block_by_hash().returning(|hash| {
+        let height = u32::from_str_radix(&hash.to_string()[56..], 16).unwrap();
+        Ok(Some(block_at(height)))
+    });
+    mock_indexer.expect_tick().returning(|| Ok(()));
+    mock_indexer
+}
+
 /// Creates and sends a transaction that spends a specific UTXO.
 /// Returns the decoded transaction and its txid.
 pub fn create_and_send_spending_transaction(
@@ -0,0 +1,196 @@
+use bitcoin::Txid;
+use bitvmx_transaction_monitor::{
+    config::{MonitorSettings, MonitorSettingsConfig},
+    errors::MonitorError,
+    external::NoIndexer,
+    monitor::Monitor,
+    store::MonitorStore,
+    types::TypesToMonitor,
+    watch_file::{load_watch_file, WatchFile},
+};
+use std::{
+    fs::{self, File},
+    path::Path,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+use storage_backend::{storage::Storage, storage_config::StorageConfig};
+use utils::{clear_output, generate_random_string};
+mod utils;
+
+/// Writes `content` to `path` with a modification time `seconds` after the epoch, so each write
+/// is seen as a change whatever the resolution of the file system clock.
+fn write(path: &Path, content: &str, seconds: u64) -> Result<(), anyhow::Error> {
+    fs::write(path, content)?;
+    File::options()
+        .write(true)
+        .open(path)?
+        .set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))?;
+    Ok(())
+}
+
+/// Test that a watch file is followed while it changes:
+/// 1. The monitors of a YAML file are loaded, and the first read adds all of them
+/// 2. An unchanged file is not read again
+/// 3. A monitor removed and another added are reported as such
+/// 4. A malformed file is reported once and keeps the previous monitors
+/// 5. A missing file is reported once and keeps the previous monitors too
+/// 6. JSON files are read by their extension
+#[test]
+fn test_watch_file() -> Result<(), anyhow::Error> {
+    let dir = format!("test_outputs/{}", generate_random_string());
+    fs::create_dir_all(&dir)?;
+    let path = Path::new(&dir).join("watch.yaml");
+    let tx_1 = Txid::from_str("0000000000000000000000000000000000000000000000000000000000000001")?;
+    let tx_2 = Txid::from_str("0000000000000000000000000000000000000000000000000000000000000002")?;
+    let monitor_1 = TypesToMonitor::Transactions(vec![tx_1], "ctx".into(), None, None);
+    let monitor_2 =
+        TypesToMonitor::SpendingUTXOTransaction(tx_2, 1, "ctx".into(), Some(3), None, None, None);
+
+    // 1. First read
+    write(
+        &path,
+        &format!(
+            "- kind: transactions\n  txids: [\"{tx_1}\"]\n  context: ctx\n\
+             - kind: new_block\n"
+        ),
+        1,
+    )?;
+    assert_eq!(
+        load_watch_file(&path)?,
+        vec![monitor_1.clone(), TypesToMonitor::NewBlock]
+    );
+
+    let mut watch_file = WatchFile::new(&path);
+    let changes = watch_file.poll()?.expect("first read");
+    assert_eq!(
+        changes.added,
+        vec![monitor_1.clone(), TypesToMonitor::NewBlock]
+    );
+    assert!(changes.removed.is_empty());
+
+    // 2. Unchanged
+    assert_eq!(watch_file.poll()?, None);
+
+    // 3. Changes
+    write(
+        &path,
+        &format!(
+            "- kind: spending_utxo\n  txid: \"{tx_2}\"\n  vout: 1\n  context: ctx\n  trigger: 3\n\
+             - kind: new_block\n"
+        ),
+        2,
+    )?;
+    let changes = watch_file.poll()?.expect("changed file");
+    assert_eq!(changes.added, vec![monitor_2.clone()]);
+    assert_eq!(changes.removed, vec![monitor_1.clone()]);
+    assert_eq!(
+        watch_file.monitors(),
+        &[monitor_2.clone(), TypesToMonitor::NewBlock]
+    );
+
+    // 4. Malformed
+    write(&path, "- kind: unknown\n", 3)?;
+    assert!(matches!(
+        watch_file.poll(),
+        Err(MonitorError::InvalidSettings(_))
+    ));
+    assert_eq!(watch_file.poll()?, None);
+    assert_eq!(
+        watch_file.monitors(),
+        &[monitor_2.clone(), TypesToMonitor::NewBlock]
+    );
+
+    // 5. Missing
+    fs::remove_file(&path)?;
+    assert!(watch_file.poll().is_err());
+    assert_eq!(watch_file.poll()?, None);
+    assert_eq!(watch_file.monitors().len(), 2);
+
+    // 6. JSON
+    let json_path = Path::new(&dir).join("watch.json");
+    write(
+        &json_path,
+        &format!(r#"[{{"kind": "transactions", "txids": ["{tx_1}"], "context": "ctx"}}]"#),
+        1,
+    )?;
+    assert_eq!(load_watch_file(&json_path)?, vec![monitor_1]);
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that syncing a watch file registers and cancels the monitors on the monitor:
+/// 1. The monitors of the file are registered
+/// 2. A monitor removed from the file is cancelled, the others stay active
+/// 3. Monitors added together are registered in one batch, an invalid one is skipped and the
+///    others are registered
+#[test]
+fn test_watch_file_sync() -> Result<(), anyhow::Error> {
+    let dir = format!("test_outputs/{}", generate_random_string());
+    fs::create_dir_all(&dir)?;
+    let path = Path::new(&dir).join("watch.json");
+    let storage = Arc::new(Storage::new(&StorageConfig::new(
+        format!("{dir}/storage"),
+        None,
+    ))?);
+    let monitor = Monitor::new(
+        NoIndexer,
+        MonitorStore::new(storage)?,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?;
+    let tx_1 = Txid::from_str("0000000000000000000000000000000000000000000000000000000000000001")?;
+    let tx_2 = Txid::from_str("0000000000000000000000000000000000000000000000000000000000000002")?;
+    let tx_3 = Txid::from_str("0000000000000000000000000000000000000000000000000000000000000003")?;
+
+    // 1. Registered
+    write(
+        &path,
+        &format!(
+            r#"[{{"kind": "transactions", "txids": ["{tx_1}"], "context": "a"}},
+                {{"kind": "transactions", "txids": ["{tx_2}"], "context": "b"}}]"#
+        ),
+        1,
+    )?;
+    let mut watch_file = WatchFile::new(&path);
+    watch_file.sync(&monitor)?;
+    assert_eq!(monitor.get_monitors(false)?.len(), 2);
+
+    // 2. Cancelled
+    write(
+        &path,
+        &format!(r#"[{{"kind": "transactions", "txids": ["{tx_2}"], "context": "b"}}]"#),
+        2,
+    )?;
+    let changes = watch_file.sync(&monitor)?.expect("changed file");
+    assert_eq!(changes.removed.len(), 1);
+    let active = monitor.get_monitors(false)?;
+    assert_eq!(active.len(), 1);
+    assert_eq!(active[0].monitor.target(), tx_2.to_string());
+
+    // 3. Batch with an invalid monitor
+    write(
+        &path,
+        &format!(
+            r#"[{{"kind": "transactions", "txids": ["{tx_2}"], "context": "b"}},
+                {{"kind": "transactions", "txids": ["{tx_1}"], "context": "c", "trigger": 1000}},
+                {{"kind": "transactions", "txids": ["{tx_3}"], "context": "d"}}]"#
+        ),
+        3,
+    )?;
+    let changes = watch_file.sync(&monitor)?.expect("changed file");
+    assert_eq!(changes.added.len(), 2);
+    let mut targets: Vec<String> = monitor
+        .get_monitors(false)?
+        .iter()
+        .map(|info| info.monitor.target())
+        .collect();
+    targets.sort();
+    assert_eq!(targets, vec![tx_2.to_string(), tx_3.to_string()]);
+
+    clear_output();
+
+    Ok(())
+}
@@ -0,0 +1,153 @@
+use bitcoin::{hashes::Hash, BlockHash};
+use bitcoin_indexer::indexer::MockIndexerApi;
+use bitvmx_transaction_monitor::{
+    config::{MonitorSettings, MonitorSettingsConfig},
+    errors::MonitorError,
+    monitor::Monitor,
+    store::MonitorStore,
+    zmq::ZmqBlockListener,
+};
+use std::{
+    io::{Read, Write},
+    net::{TcpListener, TcpStream},
+    sync::{atomic::AtomicU32, Arc},
+    thread,
+    time::{Duration, Instant},
+};
+use storage_backend::{storage::Storage, storage_config::StorageConfig};
+use utils::{block_at, clear_output, generate_random_string, scripted_indexer};
+mod utils;
+
+fn new_monitor() -> Result<Monitor<MockIndexerApi, MonitorStore>, anyhow::Error> {
+    let path = format!("test_outputs/{}", generate_random_string());
+    let storage = Arc::new(Storage::new(&StorageConfig::new(path, None))?);
+    let store = MonitorStore::new(storage)?;
+
+    let mut mock_indexer = scripted_indexer(Arc::new(AtomicU32::new(200)));
+    mock_indexer.expect_get_tx().returning(|_| Ok(None));
+
+    Ok(Monitor::new(
+        mock_indexer,
+        store,
+        MonitorSettings::from(MonitorSettingsConfig::default()),
+    )?)
+}
+
+fn read_frame(stream: &mut TcpStream) -> (u8, Vec<u8>) {
+    let mut header = [0u8; 2];
+    stream.read_exact(&mut header).unwrap();
+    let mut body = vec![0; header[1] as usize];
+    stream.read_exact(&mut body).unwrap();
+    (header[0], body)
+}
+
+fn write_frame(stream: &mut TcpStream, flags: u8, body: &[u8]) {
+    stream.write_all(&[flags, body.len() as u8]).unwrap();
+    stream.write_all(body).unwrap();
+}
+
+/// Plays the publisher side of a node: accepts a subscriber, checks its handshake, then publishes
+/// `hash`. Returns the subscription received and the connection, left open.
+fn publish(listener: &TcpListener, hash: BlockHash, sequence: u32) -> (Vec<u8>, TcpStream) {
+    let (mut stream, _) = listener.accept().unwrap();
+
+    let mut greeting = [0u8; 64];
+    stream.read_exact(&mut greeting).unwrap();
+    assert_eq!((greeting[0], greeting[9], greeting[10]), (0xff, 0x7f, 3));
+    assert_eq!(&greeting[12..16], b"NULL");
+
+    let mut reply = [0u8; 64];
+    reply[0] = 0xff;
+    reply[9] = 0x7f;
+    reply[10] = 3;
+    reply[11] = 1;
+    reply[12..16].copy_from_slice(b"NULL");
+    reply[32] = 1;
+    stream.write_all(&reply).unwrap();
+
+    let (flags, ready) = read_frame(&mut stream);
+    assert_eq!(flags, 0x04);
+    assert!(ready.starts_with(b"\x05READY\x0bSocket-Type"));
+    assert!(ready.ends_with(b"SUB"));
+    write_frame(
+        &mut stream,
+        0x04,
+        b"\x05READY\x0bSocket-Type\x00\x00\x00\x03PUB",
+    );
+
+    let (flags, subscription) = read_frame(&mut stream);
+    assert_eq!(flags, 0x00);
+
+    let mut bytes = hash.to_byte_array();
+    bytes.reverse();
+    write_frame(&mut stream, 0x01, b"hashblock");
+    write_frame(&mut stream, 0x01, &bytes);
+    write_frame(&mut stream, 0x00, &sequence.to_le_bytes());
+
+    (subscription, stream)
+}
+
+/// Test that the blocks published over ZMQ wake the monitor:
+/// 1. The listener subscribes to the hashblock topic and a block published ends the wait
+/// 2. The publisher closes the connection, the listener connects again and the next block
+///    published ends the wait too
+/// 3. The listener stops when dropped
+#[test]
+fn test_zmq_block_listener() -> Result<(), anyhow::Error> {
+    let mut monitor = new_monitor()?;
+    let publisher = TcpListener::bind("127.0.0.1:0")?;
+    let endpoint = format!("tcp://{}", publisher.local_addr()?);
+    let block_listener = ZmqBlockListener::start(&endpoint, monitor.block_trigger())?;
+
+    // 1. Block published
+    let node = thread::spawn(move || {
+        let first = publish(&publisher, block_at(201).hash, 0);
+        (publisher, first)
+    });
+    let start = Instant::now();
+    monitor.wait_for_commands(Duration::from_secs(60));
+    assert!(start.elapsed() < Duration::from_secs(30));
+    let (publisher, (subscription, connection)) = node.join().expect("publisher thread");
+    assert_eq!(subscription, b"\x01hashblock");
+
+    // 2. Reconnection
+    drop(connection);
+    let node = thread::spawn(move || publish(&publisher, block_at(202).hash, 1));
+    let start = Instant::now();
+    monitor.wait_for_commands(Duration::from_secs(60));
+    assert!(start.elapsed() < Duration::from_secs(30));
+    let (subscription, _connection) = node.join().expect("publisher thread");
+    assert_eq!(subscription, b"\x01hashblock");
+
+    // 3. Stopped
+    let start = Instant::now();
+    drop(block_listener);
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    clear_output();
+
+    Ok(())
+}
+
+/// Test that only tcp://host:port endpoints are accepted.
+#[test]
+fn test_zmq_endpoint() -> Result<(), anyhow::Error> {
+    let monitor = new_monitor()?;
+
+    for endpoint in [
+        "ipc:///tmp/bitcoind",
+        "127.0.0.1:28332",
+        "tcp://127.0.0.1",
+        "tcp://:28332",
+        "tcp://127.0.0.1:x",
+    ] {
+        assert!(matches!(
+            ZmqBlockListener::start(endpoint, monitor.block_trigger()),
+            Err(MonitorError::InvalidSettings(_))
+        ));
+    }
+
+    clear_output();
+
+    Ok(())
+}